@@ -4,7 +4,7 @@ use anyhow::*;
 use aries::core::Lit;
 use aries::model::lang::expr::or;
 use aries::solver::parallel::{ParSolver, SolverResult};
-use aries::solver::search::activity::{ActivityBrancher, BranchingParams};
+use aries::solver::search::activity::{ActivityBrancher, BranchingParams, ValueSelection};
 use aries::solver::Solver;
 use std::collections::HashMap;
 use std::fs::File;
@@ -165,10 +165,7 @@ pub fn load(cnf: varisat_formula::CnfFormula) -> Result<Model> {
 fn search_params() -> [BranchingParams; 4] {
     [
         Default::default(),
-        BranchingParams {
-            prefer_min_value: !BranchingParams::default().prefer_min_value,
-            ..Default::default()
-        },
+        BranchingParams::default().with_value_selection(ValueSelection::Max),
         BranchingParams {
             allowed_conflicts: 10,
             increase_ratio_for_allowed_conflicts: 1.02,
@@ -31,7 +31,7 @@ pub struct Opt {
     lower_bound: u32,
     #[structopt(long = "upper-bound", default_value = "100000")]
     upper_bound: u32,
-    /// Search strategy to use in {activity, est, parallel}
+    /// Search strategy to use in {activity, est, parallel, learning-rate, dom-wdeg, impact}
     #[structopt(long = "search", default_value = "learning-rate")]
     search: SearchStrategy,
     /// maximum runtime, in seconds.
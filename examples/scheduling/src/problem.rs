@@ -1,6 +1,8 @@
 use crate::search::{Model, Var};
-use aries::model::lang::expr::leq;
-use aries::model::lang::IVar;
+use aries::core::Lit;
+use aries::model::lang::expr::f_leq;
+use aries::model::lang::interval::{end_before_start, IntervalVar};
+use aries::model::lang::FAtom;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ProblemKind {
@@ -123,20 +125,32 @@ impl Problem {
 }
 
 pub(crate) fn encode(pb: &Problem, lower_bound: u32, upper_bound: u32) -> Model {
-    let start = |model: &Model, j: u32, t: u32| IVar::new(model.shape.get_variable(&Var::Start(j, t)).unwrap());
-    let end = |model: &Model, j: u32, t: u32| start(model, j, t) + pb.duration(j, t);
-
     let lower_bound = lower_bound as i32;
     let upper_bound = upper_bound as i32;
     let mut m = Model::new();
 
     let makespan_variable = m.new_ivar(lower_bound, upper_bound, Var::Makespan);
+    // interval of each task, indexed by job then by task in the job
+    let mut tasks: Vec<Vec<IntervalVar>> = Vec::with_capacity(pb.num_jobs as usize);
     for j in 0..pb.num_jobs {
+        let mut job = Vec::with_capacity(pb.num_machines as usize);
         for m1 in 0..pb.num_machines {
             let task_start = m.new_ivar(0, upper_bound, Var::Start(j, m1));
-            m.enforce(leq(task_start + pb.duration(j, m1), makespan_variable), []);
+            let duration = pb.duration(j, m1);
+            // the end is defined relatively to the start, which makes the interval consistent by construction
+            let task = IntervalVar::new(
+                FAtom::new(task_start.into(), 1),
+                FAtom::new(duration.into(), 1),
+                FAtom::new(task_start + duration, 1),
+                Lit::TRUE,
+            );
+            m.enforce(f_leq(task.end, FAtom::new(makespan_variable.into(), 1)), []);
+            job.push(task);
         }
+        tasks.push(job);
     }
+    let task = |j: u32, t: u32| tasks[j as usize][t as usize];
+
     for machine in 0..(pb.num_machines) {
         for j1 in 0..pb.num_jobs {
             for j2 in (j1 + 1)..pb.num_jobs {
@@ -145,8 +159,8 @@ pub(crate) fn encode(pb: &Problem, lower_bound: u32, upper_bound: u32) -> Model
                 // variable that is true if (j1, i1) comes first and false otherwise.
                 // in any case, setting a value to it enforces that the two tasks do not overlap
                 let prec = m.new_bvar(Var::Prec(j1, i1, j2, i2));
-                m.bind(leq(end(&m, j1, i1), start(&m, j2, i2)), prec.true_lit());
-                m.bind(leq(end(&m, j2, i2), start(&m, j1, i1)), prec.false_lit());
+                m.bind(end_before_start(task(j1, i1), task(j2, i2)), prec.true_lit());
+                m.bind(end_before_start(task(j2, i2), task(j1, i1)), prec.false_lit());
             }
         }
     }
@@ -155,7 +169,7 @@ pub(crate) fn encode(pb: &Problem, lower_bound: u32, upper_bound: u32) -> Model
             // enforce total order between tasks of the same job
             for j in pb.jobs() {
                 for i in 1..pb.num_machines {
-                    m.enforce(leq(end(&m, j, i - 1), start(&m, j, i)), []);
+                    m.enforce(end_before_start(task(j, i - 1), task(j, i)), []);
                 }
             }
         }
@@ -165,8 +179,8 @@ pub(crate) fn encode(pb: &Problem, lower_bound: u32, upper_bound: u32) -> Model
                 for m1 in 0..pb.num_machines {
                     for m2 in (m1 + 1)..pb.num_machines {
                         let prec = m.new_bvar(Var::Prec(j, m1, j, m2));
-                        m.bind(leq(end(&m, j, m1), start(&m, j, m2)), prec.true_lit());
-                        m.bind(leq(end(&m, j, m2), start(&m, j, m1)), prec.false_lit());
+                        m.bind(end_before_start(task(j, m1), task(j, m2)), prec.true_lit());
+                        m.bind(end_before_start(task(j, m2), task(j, m1)), prec.false_lit());
                     }
                 }
             }
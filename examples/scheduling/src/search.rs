@@ -7,7 +7,9 @@ use crate::search::greedy::EstBrancher;
 use aries::core::*;
 use aries::solver::search::activity::Heuristic;
 use aries::solver::search::combinators::{CombinatorExt, UntilFirstConflict};
+use aries::solver::search::impact::MeasuredImpactBrancher;
 use aries::solver::search::lexical::LexicalMinValue;
+use aries::solver::search::wdeg::DomWDegBrancher;
 use aries::solver::search::Brancher;
 use std::str::FromStr;
 
@@ -37,6 +39,10 @@ pub enum SearchStrategy {
     Activity,
     /// greedy earliest-starting-time then LRB with solution guidance
     LearningRate,
+    /// greedy earliest-starting-time then dom/wdeg
+    DomWDeg,
+    /// greedy earliest-starting-time then impact-based search
+    Impact,
 }
 impl FromStr for SearchStrategy {
     type Err = String;
@@ -45,6 +51,8 @@ impl FromStr for SearchStrategy {
         match s {
             "lrb" | "learning-rate" => Ok(SearchStrategy::LearningRate),
             "vsids" | "activity" => Ok(SearchStrategy::Activity),
+            "wdeg" | "dom-wdeg" => Ok(SearchStrategy::DomWDeg),
+            "impact" => Ok(SearchStrategy::Impact),
             e => Err(format!("Unrecognized option: '{e}'")),
         }
     }
@@ -67,15 +75,15 @@ pub fn get_solver(base: Solver, strategy: SearchStrategy, pb: &Problem) -> ParSo
 
     let base_solver = Box::new(base);
 
-    let make_solver = |s: &mut Solver, params: conflicts::Params| {
-        let ema: Brancher<Var> = Box::new(ConflictBasedBrancher::with(params));
-        let ema = ema.with_restarts(100, 1.2);
+    let make_solver_with = |s: &mut Solver, brancher: Brancher<Var>| {
         let strat = first_est
             .clone_to_box()
-            .and_then(ema)
+            .and_then(brancher.with_restarts(100, 1.2))
             .and_then(Box::new(LexicalMinValue::new()));
         s.set_brancher_boxed(strat);
     };
+    let make_solver =
+        |s: &mut Solver, params: conflicts::Params| make_solver_with(s, Box::new(ConflictBasedBrancher::with(params)));
 
     match strategy {
         SearchStrategy::Activity => ParSolver::new(base_solver, 1, |_, s| {
@@ -96,5 +104,11 @@ pub fn get_solver(base: Solver, strategy: SearchStrategy, pb: &Problem) -> ParSo
                 },
             )
         }),
+        SearchStrategy::DomWDeg => ParSolver::new(base_solver, 1, |_, s| {
+            make_solver_with(s, Box::new(DomWDegBrancher::new()))
+        }),
+        SearchStrategy::Impact => ParSolver::new(base_solver, 1, |_, s| {
+            make_solver_with(s, Box::new(MeasuredImpactBrancher::new()))
+        }),
     }
 }
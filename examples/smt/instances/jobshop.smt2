@@ -0,0 +1,19 @@
+; Two jobs of two tasks each on two machines, with a makespan of at most 8.
+(set-logic QF_IDL)
+(declare-fun s11 () Int)
+(declare-fun s12 () Int)
+(declare-fun s21 () Int)
+(declare-fun s22 () Int)
+; tasks start after the origin
+(assert (and (>= s11 0) (>= s21 0)))
+; precedences within jobs
+(assert (>= (- s12 s11) 3))
+(assert (>= (- s22 s21) 2))
+; tasks 11 and 22 on the first machine, 12 and 21 on the second
+(assert (or (>= (- s22 s11) 3) (>= (- s11 s22) 4)))
+(assert (or (>= (- s21 s12) 2) (>= (- s12 s21) 2)))
+; makespan
+(assert (and (<= s12 6) (<= s22 4)))
+(check-sat)
+(get-model)
+(exit)
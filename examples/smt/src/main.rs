@@ -0,0 +1,43 @@
+//! Solver for SMT-LIB 2 scripts in difference logic (QF_IDL and QF_RDL).
+
+mod sexpr;
+mod smtlib;
+
+use crate::smtlib::{Interpreter, Next};
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "aries-smt")]
+struct Opt {
+    /// SMT-LIB 2 script to execute. The script is read from the standard input if not provided.
+    file: Option<PathBuf>,
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::from_args();
+    let input = match &opt.file {
+        Some(file) => std::fs::read_to_string(file).with_context(|| format!("Could not read {}", file.display()))?,
+        None => {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        }
+    };
+    let commands = sexpr::parse(&input)?;
+
+    let mut interpreter = Interpreter::new();
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    for command in &commands {
+        match interpreter.execute(command, &mut out) {
+            Ok(Next::Continue) => {}
+            Ok(Next::Exit) => break,
+            Err(e) => writeln!(out, "(error \"{}\")", format!("{e:#}").replace('"', "\"\""))?,
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use anyhow::{bail, Result};
+use std::fmt::{Display, Formatter};
+
+/// An S-expression, as used by the SMT-LIB format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SExpr {
+    /// A symbol, keyword, numeral, decimal or string literal (with its surrounding quotes).
+    /// Quoted symbols (`|a b|`) are stored without their vertical bars.
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+impl SExpr {
+    pub fn as_atom(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom(a) => Some(a.as_str()),
+            SExpr::List(_) => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[SExpr]> {
+        match self {
+            SExpr::Atom(_) => None,
+            SExpr::List(l) => Some(l.as_slice()),
+        }
+    }
+}
+
+impl Display for SExpr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SExpr::Atom(a) => write!(f, "{a}"),
+            SExpr::List(l) => {
+                write!(f, "(")?;
+                for (i, e) in l.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{e}")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Parses all S-expressions of the input, ignoring comments (from a `;` to the end of the line).
+pub fn parse(input: &str) -> Result<Vec<SExpr>> {
+    let mut chars = input.chars().peekable();
+    // stack of the lists currently being built
+    let mut stack: Vec<Vec<SExpr>> = Vec::new();
+    let mut exprs = Vec::new();
+
+    while let Some(c) = chars.next() {
+        let atom = match c {
+            ';' => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                continue;
+            }
+            '(' => {
+                stack.push(Vec::new());
+                continue;
+            }
+            ')' => {
+                let Some(list) = stack.pop() else {
+                    bail!("Unexpected closing parenthesis")
+                };
+                SExpr::List(list)
+            }
+            c if c.is_whitespace() => continue,
+            '|' => {
+                let mut sym = String::new();
+                loop {
+                    match chars.next() {
+                        Some('|') => break,
+                        Some(c) => sym.push(c),
+                        None => bail!("Unterminated quoted symbol"),
+                    }
+                }
+                SExpr::Atom(sym)
+            }
+            '"' => {
+                let mut s = String::from('"');
+                loop {
+                    match chars.next() {
+                        // a double quote is escaped by doubling it
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            s.push_str("\"\"");
+                        }
+                        Some('"') => break,
+                        Some(c) => s.push(c),
+                        None => bail!("Unterminated string literal"),
+                    }
+                }
+                s.push('"');
+                SExpr::Atom(s)
+            }
+            c => {
+                let mut sym = String::from(c);
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !matches!(c, '(' | ')' | ';' | '|' | '"'))
+                {
+                    sym.push(c);
+                }
+                SExpr::Atom(sym)
+            }
+        };
+        match stack.last_mut() {
+            Some(list) => list.push(atom),
+            None => exprs.push(atom),
+        }
+    }
+    if !stack.is_empty() {
+        bail!("Unexpected end of input: missing closing parenthesis")
+    }
+    Ok(exprs)
+}
@@ -0,0 +1,863 @@
+//! Interpreter for SMT-LIB 2 scripts in the difference logic fragments (QF_IDL and QF_RDL).
+//!
+//! Assertions are encoded on each `check-sat` into a fresh model, where difference constraints are handled
+//! by the STN theory and the boolean structure by the SAT solver.
+//!
+//! The solver only supports integer variables: in QF_RDL, all real values are scaled by a common factor `K = D.(n + 1)`
+//! where `D` is the least common multiple of the denominators of all constants and `n` the number of real variables.
+//! A strict inequality `x - y < c` is encoded as `K.x - K.y <= K.c - 1`.
+//! This is sound and complete because a system of difference constraints is infeasible iff it has a cycle with
+//! a negative weight (or a null weight and a strict inequality), and a simple cycle has at most `n + 1` edges
+//! (including the edges to the origin, used for bounds): the `-1` of strict inequalities can thus only make
+//! negative the weight of the cycles whose weight was null, and had at least one strict inequality.
+
+use crate::sexpr::SExpr;
+use anyhow::{bail, ensure, Context, Result};
+use aries::core::{IntCst, Lit, INT_CST_MAX, INT_CST_MIN};
+use aries::model::extensions::AssignmentExt;
+use aries::model::lang::expr::{and, eq, leq, or};
+use aries::model::lang::{IAtom, IVar};
+use num_rational::Rational64;
+use std::collections::HashMap;
+use std::io::Write;
+
+type Model = aries::model::Model<String>;
+type Solver = aries::solver::Solver<String>;
+
+/// Domain of numeric variables, chosen to leave room for the shifts of difference constraints.
+const NUM_LB: IntCst = INT_CST_MIN / 2;
+const NUM_UB: IntCst = INT_CST_MAX / 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Sort {
+    Bool,
+    Int,
+    Real,
+}
+
+impl Sort {
+    fn parse(e: &SExpr) -> Result<Sort> {
+        match e.as_atom() {
+            Some("Bool") => Ok(Sort::Bool),
+            Some("Int") => Ok(Sort::Int),
+            Some("Real") => Ok(Sort::Real),
+            _ => bail!("Unsupported sort: {e}"),
+        }
+    }
+}
+
+/// Value of a term in a solution.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Value {
+    Bool(bool),
+    Num(Rational64),
+}
+
+/// Outcome of the last `check-sat` command, with the values of all declared constants if satisfiable.
+enum CheckSat {
+    Sat(HashMap<String, Value>),
+    Unsat,
+    Unknown,
+}
+
+/// Whether the interpreter should proceed with the next command.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Next {
+    Continue,
+    Exit,
+}
+
+#[derive(Default)]
+pub struct Interpreter {
+    /// Declared constants, in declaration order.
+    declarations: Vec<(String, Sort)>,
+    /// Constants defined with `define-fun` (without parameters), with their sort and body.
+    definitions: Vec<(String, Sort, SExpr)>,
+    assertions: Vec<SExpr>,
+    /// Number of declarations, definitions and assertions when each of the currently open scopes was pushed.
+    scopes: Vec<(usize, usize, usize)>,
+    last_check: Option<CheckSat>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Executes a command, writing its response (if any) to `out`.
+    pub fn execute(&mut self, command: &SExpr, out: &mut impl Write) -> Result<Next> {
+        let Some([SExpr::Atom(name), args @ ..]) = command.as_list() else {
+            bail!("Invalid command: {command}")
+        };
+        match (name.as_str(), args) {
+            ("set-logic", [logic]) => match logic.as_atom() {
+                Some("QF_IDL" | "QF_RDL") => {}
+                _ => bail!("Unsupported logic: {logic}"),
+            },
+            ("set-info" | "set-option", _) => {}
+            ("declare-const", [SExpr::Atom(name), sort]) => self.declare(name, sort)?,
+            ("declare-fun", [SExpr::Atom(name), SExpr::List(params), sort]) => {
+                ensure!(params.is_empty(), "Unsupported: function with parameters");
+                self.declare(name, sort)?
+            }
+            ("define-fun", [SExpr::Atom(name), SExpr::List(params), sort, body]) => {
+                ensure!(params.is_empty(), "Unsupported: function with parameters");
+                self.check_fresh(name)?;
+                self.definitions.push((name.clone(), Sort::parse(sort)?, body.clone()));
+            }
+            ("assert", [assertion]) => {
+                self.assertions.push(assertion.clone());
+                self.last_check = None;
+            }
+            ("check-sat", []) => {
+                let result = self.check_sat()?;
+                let answer = match result {
+                    CheckSat::Sat(_) => "sat",
+                    CheckSat::Unsat => "unsat",
+                    CheckSat::Unknown => "unknown",
+                };
+                writeln!(out, "{answer}")?;
+                self.last_check = Some(result);
+            }
+            ("get-model", []) => {
+                let values = self.solution()?;
+                writeln!(out, "(")?;
+                for (name, sort) in &self.declarations {
+                    let value = format_value(values[name], *sort);
+                    writeln!(out, "  (define-fun {} () {sort:?} {value})", format_symbol(name))?;
+                }
+                writeln!(out, ")")?;
+            }
+            ("get-value", [SExpr::List(terms)]) => {
+                let values = self.solution()?;
+                let mut evaluated = Vec::with_capacity(terms.len());
+                for term in terms {
+                    let value = self.eval(term, values, &mut Vec::new())?;
+                    let sort = match value {
+                        Value::Bool(_) => Sort::Bool,
+                        Value::Num(_) if self.is_real() => Sort::Real,
+                        Value::Num(_) => Sort::Int,
+                    };
+                    evaluated.push(format!("({term} {})", format_value(value, sort)));
+                }
+                writeln!(out, "({})", evaluated.join(" "))?;
+            }
+            ("push", _) | ("pop", _) => {
+                let n = match args {
+                    [] => 1,
+                    [n] => n
+                        .as_atom()
+                        .and_then(|n| n.parse().ok())
+                        .context("Invalid number of scopes")?,
+                    _ => bail!("Invalid command: {command}"),
+                };
+                if name == "push" {
+                    for _ in 0..n {
+                        let sizes = (self.declarations.len(), self.definitions.len(), self.assertions.len());
+                        self.scopes.push(sizes);
+                    }
+                } else {
+                    ensure!(n <= self.scopes.len(), "Cannot pop more scopes than were pushed");
+                    for _ in 0..n {
+                        let (num_decls, num_defs, num_assertions) = self.scopes.pop().unwrap();
+                        self.declarations.truncate(num_decls);
+                        self.definitions.truncate(num_defs);
+                        self.assertions.truncate(num_assertions);
+                    }
+                    self.last_check = None;
+                }
+            }
+            ("reset-assertions", []) => {
+                self.assertions.clear();
+                self.scopes.clear();
+                self.last_check = None;
+            }
+            ("reset", []) => *self = Interpreter::new(),
+            ("echo", [message]) => writeln!(out, "{message}")?,
+            ("exit", []) => return Ok(Next::Exit),
+            _ => writeln!(out, "unsupported")?,
+        }
+        Ok(Next::Continue)
+    }
+
+    fn check_fresh(&self, name: &str) -> Result<()> {
+        let declared = self.declarations.iter().any(|(n, _)| n == name);
+        let defined = self.definitions.iter().any(|(n, _, _)| n == name);
+        ensure!(!declared && !defined, "Symbol {name} is already declared");
+        Ok(())
+    }
+
+    fn declare(&mut self, name: &str, sort: &SExpr) -> Result<()> {
+        self.check_fresh(name)?;
+        self.declarations.push((name.to_string(), Sort::parse(sort)?));
+        self.last_check = None;
+        Ok(())
+    }
+
+    /// True if the numeric constants are reals (QF_RDL).
+    fn is_real(&self) -> bool {
+        self.declarations.iter().any(|(_, sort)| *sort == Sort::Real)
+    }
+
+    fn solution(&self) -> Result<&HashMap<String, Value>> {
+        match &self.last_check {
+            Some(CheckSat::Sat(values)) => Ok(values),
+            _ => bail!("Model is not available"),
+        }
+    }
+
+    fn check_sat(&self) -> Result<CheckSat> {
+        let reals = self.is_real();
+        ensure!(
+            !reals || self.declarations.iter().all(|(_, sort)| *sort != Sort::Int),
+            "Unsupported: mixing integer and real constants"
+        );
+        let scale = if reals {
+            let num_vars = self.declarations.iter().filter(|(_, s)| *s == Sort::Real).count() as i64;
+            let mut denominators = 1;
+            for e in self
+                .assertions
+                .iter()
+                .chain(self.definitions.iter().map(|(_, _, body)| body))
+            {
+                collect_denominators(e, &mut denominators)?;
+            }
+            denominators * (num_vars + 1)
+        } else {
+            1
+        };
+        ensure!(
+            scale <= NUM_UB as i64,
+            "Unsupported: constants require a too large scaling factor"
+        );
+
+        let mut encoder = Encoder {
+            interpreter: self,
+            model: Model::new(),
+            scale,
+            constants: HashMap::new(),
+            defined: HashMap::new(),
+        };
+        for (name, sort) in &self.declarations {
+            let term = match sort {
+                Sort::Bool => Term::Bool(encoder.model.new_bvar(name.clone()).true_lit()),
+                Sort::Int | Sort::Real => Term::Num(Lin::var(encoder.model.new_ivar(NUM_LB, NUM_UB, name.clone()))),
+            };
+            encoder.constants.insert(name.clone(), term);
+        }
+        for assertion in &self.assertions {
+            encoder.assert(assertion)?;
+        }
+
+        let mut solver = Solver::new(encoder.model);
+        match solver.solve() {
+            Ok(Some(assignment)) => {
+                let mut values = HashMap::new();
+                for (name, term) in &encoder.constants {
+                    let value = match term {
+                        Term::Bool(lit) => Value::Bool(assignment.value_of_literal(*lit).unwrap_or(false)),
+                        Term::Num(lin) => {
+                            let var = lin.terms[0].0;
+                            Value::Num(Rational64::new(assignment.lower_bound(var) as i64, scale))
+                        }
+                    };
+                    values.insert(name.clone(), value);
+                }
+                Ok(CheckSat::Sat(values))
+            }
+            Ok(None) => Ok(CheckSat::Unsat),
+            Err(_) => Ok(CheckSat::Unknown),
+        }
+    }
+
+    /// Evaluates a term with the given values of the declared constants.
+    fn eval(&self, e: &SExpr, values: &HashMap<String, Value>, env: &mut Vec<(String, Value)>) -> Result<Value> {
+        match e {
+            SExpr::Atom(a) => {
+                if let Some(b) = parse_bool(a) {
+                    Ok(Value::Bool(b))
+                } else if let Some(n) = parse_number(a) {
+                    Ok(Value::Num(n))
+                } else if let Some((_, v)) = env.iter().rev().find(|(n, _)| n == a) {
+                    Ok(*v)
+                } else if let Some(v) = values.get(a) {
+                    Ok(*v)
+                } else if let Some((_, _, body)) = self.definitions.iter().find(|(n, _, _)| n == a) {
+                    self.eval(body, values, &mut Vec::new())
+                } else {
+                    bail!("Unknown symbol: {a}")
+                }
+            }
+            SExpr::List(list) => {
+                let Some((SExpr::Atom(head), args)) = list.split_first() else {
+                    bail!("Invalid term: {e}")
+                };
+                if head == "let" {
+                    let [SExpr::List(bindings), body] = args else {
+                        bail!("Invalid term: {e}")
+                    };
+                    let mut bound = Vec::with_capacity(bindings.len());
+                    for binding in bindings {
+                        let Some([SExpr::Atom(name), value]) = binding.as_list() else {
+                            bail!("Invalid binding: {binding}")
+                        };
+                        bound.push((name.clone(), self.eval(value, values, env)?));
+                    }
+                    let size = env.len();
+                    env.extend(bound);
+                    let result = self.eval(body, values, env);
+                    env.truncate(size);
+                    return result;
+                }
+                if head == "!" {
+                    let [term, ..] = args else { bail!("Invalid term: {e}") };
+                    return self.eval(term, values, env);
+                }
+                let mut vals = Vec::with_capacity(args.len());
+                for arg in args {
+                    vals.push(self.eval(arg, values, env)?);
+                }
+                let bools = || -> Result<Vec<bool>> {
+                    vals.iter()
+                        .map(|v| match v {
+                            Value::Bool(b) => Ok(*b),
+                            Value::Num(_) => bail!("Expected a boolean term in {e}"),
+                        })
+                        .collect()
+                };
+                let nums = || -> Result<Vec<Rational64>> {
+                    vals.iter()
+                        .map(|v| match v {
+                            Value::Num(n) => Ok(*n),
+                            Value::Bool(_) => bail!("Expected a numeric term in {e}"),
+                        })
+                        .collect()
+                };
+                let value = match (head.as_str(), vals.as_slice()) {
+                    ("not", [Value::Bool(b)]) => Value::Bool(!b),
+                    ("and", _) => Value::Bool(bools()?.iter().all(|b| *b)),
+                    ("or", _) => Value::Bool(bools()?.iter().any(|b| *b)),
+                    ("=>", [_, _, ..]) => {
+                        let bs = bools()?;
+                        let (last, premises) = bs.split_last().unwrap();
+                        Value::Bool(*last || premises.iter().any(|b| !b))
+                    }
+                    ("xor", _) => Value::Bool(bools()?.iter().fold(false, |acc, b| acc ^ b)),
+                    ("ite", [Value::Bool(c), a, b]) => {
+                        if *c {
+                            *a
+                        } else {
+                            *b
+                        }
+                    }
+                    ("=", [_, _, ..]) => Value::Bool(vals.windows(2).all(|w| w[0] == w[1])),
+                    ("distinct", _) => {
+                        Value::Bool((0..vals.len()).all(|i| (i + 1..vals.len()).all(|j| vals[i] != vals[j])))
+                    }
+                    ("<=", [_, _, ..]) => Value::Bool(nums()?.windows(2).all(|w| w[0] <= w[1])),
+                    ("<", [_, _, ..]) => Value::Bool(nums()?.windows(2).all(|w| w[0] < w[1])),
+                    (">=", [_, _, ..]) => Value::Bool(nums()?.windows(2).all(|w| w[0] >= w[1])),
+                    (">", [_, _, ..]) => Value::Bool(nums()?.windows(2).all(|w| w[0] > w[1])),
+                    ("+", _) => Value::Num(nums()?.into_iter().sum()),
+                    ("-", [_]) => Value::Num(-nums()?[0]),
+                    ("-", [_, _, ..]) => {
+                        let ns = nums()?;
+                        Value::Num(ns[1..].iter().fold(ns[0], |acc, n| acc - n))
+                    }
+                    ("*", _) => Value::Num(nums()?.into_iter().product()),
+                    ("/", [_, _]) => {
+                        let ns = nums()?;
+                        ensure!(ns[1] != Rational64::from_integer(0), "Division by zero");
+                        Value::Num(ns[0] / ns[1])
+                    }
+                    _ => bail!("Unsupported term: {e}"),
+                };
+                Ok(value)
+            }
+        }
+    }
+}
+
+/// Linear combination of numeric variables: `sum(factor * var) + cst`.
+#[derive(Clone, Debug)]
+struct Lin {
+    terms: Vec<(IVar, i64)>,
+    cst: Rational64,
+}
+
+impl Lin {
+    fn var(var: IVar) -> Lin {
+        Lin {
+            terms: vec![(var, 1)],
+            cst: Rational64::from_integer(0),
+        }
+    }
+
+    fn cst(cst: Rational64) -> Lin {
+        Lin { terms: vec![], cst }
+    }
+
+    fn add(mut self, other: &Lin, factor: i64) -> Lin {
+        for &(var, f) in &other.terms {
+            match self.terms.iter_mut().find(|(v, _)| *v == var) {
+                Some((_, existing)) => *existing += factor * f,
+                None => self.terms.push((var, factor * f)),
+            }
+        }
+        self.terms.retain(|(_, f)| *f != 0);
+        self.cst += other.cst * factor;
+        self
+    }
+
+    fn scale(mut self, factor: Rational64) -> Result<Lin> {
+        for (_, f) in &mut self.terms {
+            let scaled = factor * *f;
+            ensure!(scaled.is_integer(), "Unsupported: non-integer factor of a variable");
+            *f = scaled.to_integer();
+        }
+        self.terms.retain(|(_, f)| *f != 0);
+        self.cst *= factor;
+        Ok(self)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Term {
+    Bool(Lit),
+    Num(Lin),
+}
+
+/// Encodes the assertions of an interpreter into a model.
+struct Encoder<'a> {
+    interpreter: &'a Interpreter,
+    model: Model,
+    /// Factor by which all numeric values are multiplied in the model.
+    scale: i64,
+    /// Encoding of the declared constants.
+    constants: HashMap<String, Term>,
+    /// Encoding of the defined constants, built on their first usage.
+    defined: HashMap<String, Term>,
+}
+
+impl<'a> Encoder<'a> {
+    fn assert(&mut self, assertion: &SExpr) -> Result<()> {
+        // enforce the conjuncts of a top-level conjunction independently, to avoid reifying them
+        if let Some([SExpr::Atom(head), conjuncts @ ..]) = assertion.as_list() {
+            if head == "and" {
+                for conjunct in conjuncts {
+                    self.assert(conjunct)?;
+                }
+                return Ok(());
+            }
+        }
+        let lit = self.encode_bool(assertion, &mut Vec::new())?;
+        self.model.enforce(lit, []);
+        Ok(())
+    }
+
+    fn encode_bool(&mut self, e: &SExpr, env: &mut Vec<(String, Term)>) -> Result<Lit> {
+        match self.encode(e, env)? {
+            Term::Bool(lit) => Ok(lit),
+            Term::Num(_) => bail!("Expected a boolean term: {e}"),
+        }
+    }
+
+    fn encode_num(&mut self, e: &SExpr, env: &mut Vec<(String, Term)>) -> Result<Lin> {
+        match self.encode(e, env)? {
+            Term::Num(lin) => Ok(lin),
+            Term::Bool(_) => bail!("Expected a numeric term: {e}"),
+        }
+    }
+
+    fn encode(&mut self, e: &SExpr, env: &mut Vec<(String, Term)>) -> Result<Term> {
+        let list = match e {
+            SExpr::Atom(a) => return self.encode_atom(a, env),
+            SExpr::List(list) => list,
+        };
+        let Some((SExpr::Atom(head), args)) = list.split_first() else {
+            bail!("Invalid term: {e}")
+        };
+        match head.as_str() {
+            "let" => {
+                let [SExpr::List(bindings), body] = args else {
+                    bail!("Invalid term: {e}")
+                };
+                // all bindings are evaluated in the outer environment
+                let mut bound = Vec::with_capacity(bindings.len());
+                for binding in bindings {
+                    let Some([SExpr::Atom(name), value]) = binding.as_list() else {
+                        bail!("Invalid binding: {binding}")
+                    };
+                    bound.push((name.clone(), self.encode(value, env)?));
+                }
+                let size = env.len();
+                env.extend(bound);
+                let result = self.encode(body, env);
+                env.truncate(size);
+                result
+            }
+            "!" => {
+                let [term, ..] = args else { bail!("Invalid term: {e}") };
+                self.encode(term, env)
+            }
+            "not" | "and" | "or" | "=>" | "xor" => {
+                let mut lits = Vec::with_capacity(args.len());
+                for arg in args {
+                    lits.push(self.encode_bool(arg, env)?);
+                }
+                let lit = match (head.as_str(), lits.as_slice()) {
+                    ("not", [l]) => !*l,
+                    ("and", _) => self.and(lits),
+                    ("or", _) => self.or(lits),
+                    ("=>", [_, _, ..]) => {
+                        let last = lits.pop().unwrap();
+                        let mut disjuncts: Vec<Lit> = lits.into_iter().map(|l| !l).collect();
+                        disjuncts.push(last);
+                        self.or(disjuncts)
+                    }
+                    ("xor", [first, rest @ ..]) => {
+                        let mut acc = *first;
+                        for &l in rest {
+                            acc = !self.iff(acc, l);
+                        }
+                        acc
+                    }
+                    _ => bail!("Invalid term: {e}"),
+                };
+                Ok(Term::Bool(lit))
+            }
+            "ite" => {
+                let [cond, then, otherwise] = args else {
+                    bail!("Invalid term: {e}")
+                };
+                let cond = self.encode_bool(cond, env)?;
+                let then = self.encode_bool(then, env).context("Unsupported: non-boolean ite")?;
+                let otherwise = self
+                    .encode_bool(otherwise, env)
+                    .context("Unsupported: non-boolean ite")?;
+                let a = self.or(vec![!cond, then]);
+                let b = self.or(vec![cond, otherwise]);
+                Ok(Term::Bool(self.and(vec![a, b])))
+            }
+            "=" | "distinct" | "<=" | "<" | ">=" | ">" => {
+                ensure!(args.len() >= 2, "Invalid term: {e}");
+                let mut terms = Vec::with_capacity(args.len());
+                for arg in args {
+                    terms.push(self.encode(arg, env)?);
+                }
+                // pairs of terms on which the relation must hold
+                let pairs: Vec<(usize, usize)> = if head == "distinct" {
+                    (0..terms.len())
+                        .flat_map(|i| (i + 1..terms.len()).map(move |j| (i, j)))
+                        .collect()
+                } else {
+                    (1..terms.len()).map(|i| (i - 1, i)).collect()
+                };
+                let mut conjuncts = Vec::with_capacity(pairs.len());
+                for (i, j) in pairs {
+                    let lit = match (&terms[i], &terms[j]) {
+                        (Term::Bool(a), Term::Bool(b)) if head == "=" => self.iff(*a, *b),
+                        (Term::Bool(a), Term::Bool(b)) if head == "distinct" => !self.iff(*a, *b),
+                        (Term::Num(a), Term::Num(b)) => {
+                            let diff = a.clone().add(b, -1);
+                            match head.as_str() {
+                                "=" => self.eq_zero(&diff)?,
+                                "distinct" => !self.eq_zero(&diff)?,
+                                "<=" => self.leq_zero(&diff, false)?,
+                                "<" => self.leq_zero(&diff, true)?,
+                                ">=" => self.leq_zero(&Lin::cst(Rational64::from_integer(0)).add(&diff, -1), false)?,
+                                ">" => self.leq_zero(&Lin::cst(Rational64::from_integer(0)).add(&diff, -1), true)?,
+                                _ => unreachable!(),
+                            }
+                        }
+                        _ => bail!("Ill-sorted term: {e}"),
+                    };
+                    conjuncts.push(lit);
+                }
+                Ok(Term::Bool(self.and(conjuncts)))
+            }
+            "+" | "-" | "*" | "/" => {
+                let mut lins = Vec::with_capacity(args.len());
+                for arg in args {
+                    lins.push(self.encode_num(arg, env)?);
+                }
+                let zero = Lin::cst(Rational64::from_integer(0));
+                let lin = match (head.as_str(), lins.as_slice()) {
+                    ("+", _) => lins.iter().fold(zero, |acc, l| acc.add(l, 1)),
+                    ("-", [l]) => zero.add(l, -1),
+                    ("-", [first, rest @ ..]) => rest.iter().fold(first.clone(), |acc, l| acc.add(l, -1)),
+                    ("*", _) => {
+                        // product of constants with at most one non-constant term
+                        let mut product = Lin::cst(Rational64::from_integer(1));
+                        for l in lins {
+                            if product.terms.is_empty() {
+                                product = l.scale(product.cst)?;
+                            } else if l.terms.is_empty() {
+                                product = product.scale(l.cst)?;
+                            } else {
+                                bail!("Unsupported: non-linear term {e}")
+                            }
+                        }
+                        product
+                    }
+                    ("/", [num, denom]) if denom.terms.is_empty() => {
+                        ensure!(denom.cst != Rational64::from_integer(0), "Division by zero");
+                        num.clone().scale(denom.cst.recip())?
+                    }
+                    _ => bail!("Unsupported term: {e}"),
+                };
+                Ok(Term::Num(lin))
+            }
+            _ => bail!("Unsupported term: {e}"),
+        }
+    }
+
+    fn encode_atom(&mut self, a: &str, env: &[(String, Term)]) -> Result<Term> {
+        if let Some(b) = parse_bool(a) {
+            return Ok(Term::Bool(if b { Lit::TRUE } else { Lit::FALSE }));
+        }
+        if let Some(n) = parse_number(a) {
+            return Ok(Term::Num(Lin::cst(n)));
+        }
+        if let Some((_, term)) = env.iter().rev().find(|(name, _)| name == a) {
+            return Ok(term.clone());
+        }
+        if let Some(term) = self.constants.get(a).or_else(|| self.defined.get(a)) {
+            return Ok(term.clone());
+        }
+        let interpreter = self.interpreter;
+        if let Some((_, sort, body)) = interpreter.definitions.iter().find(|(name, _, _)| name == a) {
+            // a definition may only refer to the symbols declared before it, in an empty environment
+            let term = self.encode(body, &mut Vec::new())?;
+            ensure!(
+                matches!(
+                    (sort, &term),
+                    (Sort::Bool, Term::Bool(_)) | (Sort::Int | Sort::Real, Term::Num(_))
+                ),
+                "Ill-sorted definition of {a}"
+            );
+            self.defined.insert(a.to_string(), term.clone());
+            return Ok(term);
+        }
+        bail!("Unknown symbol: {a}")
+    }
+
+    fn and(&mut self, lits: Vec<Lit>) -> Lit {
+        match lits.as_slice() {
+            [] => Lit::TRUE,
+            [l] => *l,
+            _ => self.model.reify(and(lits)),
+        }
+    }
+
+    fn or(&mut self, lits: Vec<Lit>) -> Lit {
+        match lits.as_slice() {
+            [] => Lit::FALSE,
+            [l] => *l,
+            _ => self.model.reify(or(lits)),
+        }
+    }
+
+    fn iff(&mut self, a: Lit, b: Lit) -> Lit {
+        let a_implies_b = self.or(vec![!a, b]);
+        let b_implies_a = self.or(vec![a, !b]);
+        self.and(vec![a_implies_b, b_implies_a])
+    }
+
+    /// Decomposes a difference constraint `x - y + c` into the atoms `x + c` and `y`, where `c` is scaled.
+    fn difference(&self, diff: &Lin) -> Result<(IAtom, IAtom)> {
+        let cst = diff.cst * self.scale;
+        ensure!(cst.is_integer(), "Unsupported constant: {}", diff.cst);
+        let cst = cst.to_integer();
+        ensure!(
+            (NUM_LB as i64..=NUM_UB as i64).contains(&cst),
+            "Unsupported: constant {} is too large",
+            diff.cst
+        );
+        let cst = cst as IntCst;
+        let (pos, neg) = match diff.terms.as_slice() {
+            [] => (None, None),
+            [(x, 1)] => (Some(*x), None),
+            [(y, -1)] => (None, Some(*y)),
+            [(x, 1), (y, -1)] | [(y, -1), (x, 1)] => (Some(*x), Some(*y)),
+            _ => bail!("Not a difference logic constraint"),
+        };
+        let lhs = pos.map_or(IAtom::from(cst), |x| IAtom::new(x, cst));
+        let rhs = neg.map_or(IAtom::from(0), IAtom::from);
+        Ok((lhs, rhs))
+    }
+
+    /// Literal that holds iff `diff <= 0` (or `diff < 0` if strict).
+    fn leq_zero(&mut self, diff: &Lin, strict: bool) -> Result<Lit> {
+        let (lhs, rhs) = self.difference(diff)?;
+        let lhs = if strict { lhs + 1 } else { lhs };
+        Ok(self.model.reify(leq(lhs, rhs)))
+    }
+
+    /// Literal that holds iff `diff = 0`.
+    fn eq_zero(&mut self, diff: &Lin) -> Result<Lit> {
+        let (lhs, rhs) = self.difference(diff)?;
+        Ok(self.model.reify(eq(lhs, rhs)))
+    }
+}
+
+fn parse_bool(a: &str) -> Option<bool> {
+    match a {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a numeral (`12`) or a decimal (`1.25`).
+fn parse_number(a: &str) -> Option<Rational64> {
+    if !a.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    match a.split_once('.') {
+        None => a.parse().ok().map(Rational64::from_integer),
+        Some((int, frac)) => {
+            let denom = 10i64.checked_pow(frac.len() as u32)?;
+            let num = format!("{int}{frac}").parse::<i64>().ok()?;
+            Some(Rational64::new(num, denom))
+        }
+    }
+}
+
+/// Updates `acc` with the least common multiple of the denominators of all constants appearing in the expression.
+fn collect_denominators(e: &SExpr, acc: &mut i64) -> Result<()> {
+    match e {
+        SExpr::Atom(a) => {
+            if let Some(n) = parse_number(a) {
+                *acc = lcm(*acc, *n.denom());
+            }
+        }
+        SExpr::List(list) => {
+            if let [SExpr::Atom(head), SExpr::Atom(num), SExpr::Atom(denom)] = list.as_slice() {
+                if head == "/" {
+                    if let (Some(num), Some(denom)) = (parse_number(num), parse_number(denom)) {
+                        ensure!(denom != Rational64::from_integer(0), "Division by zero");
+                        *acc = lcm(*acc, *(num / denom).denom());
+                    }
+                }
+            }
+            for e in list {
+                collect_denominators(e, acc)?;
+            }
+        }
+    }
+    ensure!(
+        *acc <= NUM_UB as i64,
+        "Unsupported: constants with too large denominators"
+    );
+    Ok(())
+}
+
+fn lcm(a: i64, b: i64) -> i64 {
+    num_integer::lcm(a, b)
+}
+
+fn format_symbol(name: &str) -> String {
+    let simple = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c));
+    if simple {
+        name.to_string()
+    } else {
+        format!("|{name}|")
+    }
+}
+
+fn format_value(value: Value, sort: Sort) -> String {
+    match value {
+        Value::Bool(b) => b.to_string(),
+        Value::Num(n) => {
+            let abs = if n < Rational64::from_integer(0) { -n } else { n };
+            let formatted = match sort {
+                Sort::Real if abs.is_integer() => format!("{}.0", abs.numer()),
+                Sort::Real => format!("(/ {}.0 {}.0)", abs.numer(), abs.denom()),
+                _ => abs.to_string(),
+            };
+            if abs == n {
+                formatted
+            } else {
+                format!("(- {formatted})")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sexpr::parse;
+
+    /// Runs the script and returns the non-empty lines of its output.
+    fn run(script: &str) -> Vec<String> {
+        let mut interpreter = Interpreter::new();
+        let mut out = Vec::new();
+        for command in parse(script).unwrap() {
+            if interpreter.execute(&command, &mut out).unwrap() == Next::Exit {
+                break;
+            }
+        }
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_idl() {
+        let out = run("
+            (set-logic QF_IDL)
+            (declare-fun x () Int)
+            (declare-fun y () Int)
+            (declare-fun b () Bool)
+            (assert (<= (- x y) (- 3)))
+            (assert (or b (< y x)))
+            (assert (=> b (= x 10)))
+            (check-sat)
+            (get-value (b x (- y x)))
+            (push 1)
+            (assert (not b))
+            (check-sat)
+            (pop 1)
+            (check-sat)
+            (exit)
+            (check-sat)");
+        assert_eq!(out, ["sat", "((b true) (x 10) ((- y x) 3))", "unsat", "sat"]);
+    }
+
+    #[test]
+    fn test_rdl() {
+        // x < y < x + 0.5 only has non-integer solutions after fixing x
+        let out = run("
+            (set-logic QF_RDL)
+            (declare-const x Real)
+            (declare-const y Real)
+            (define-fun d () Real (- y x))
+            (assert (and (= x 1) (< 0 d) (< d 0.5)))
+            (check-sat)
+            (get-value ((and (> y 1.0) (< y (/ 3 2)))))
+            (assert (let ((e (- x y))) (>= e 0)))
+            (check-sat)");
+        assert_eq!(out, ["sat", "(((and (> y 1.0) (< y (/ 3 2))) true))", "unsat"]);
+
+        // a negative cycle with a null weight and a strict inequality
+        let out = run("
+            (set-logic QF_RDL)
+            (declare-const x Real)
+            (declare-const y Real)
+            (declare-const z Real)
+            (assert (<= (- x y) 0.25))
+            (assert (<= (- y z) 0.25))
+            (assert (< (- z x) (- 0.5)))
+            (check-sat)");
+        assert_eq!(out, ["unsat"]);
+    }
+}
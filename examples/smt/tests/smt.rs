@@ -479,3 +479,261 @@ fn test_opt_leq_eager_propagation() {
     let mut solver = Solver::new(model);
     run_tests(&mut solver, &tests);
 }
+
+#[test]
+fn test_max_propagation() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let m = model.new_ivar(0, 20, "m");
+    model.enforce(eq_max(m, [a, b, c]), []);
+
+    let tests = vec![
+        Test::new(&[], &[m.leq(10)]),
+        Test::new(&[b.geq(5)], &[m.geq(5)]),
+        Test::new(&[m.leq(4)], &[a.leq(4), b.leq(4), c.leq(4)]),
+        Test::new(&[m.geq(7), a.leq(5), b.leq(6)], &[c.geq(7)]),
+    ];
+
+    let mut solver = Solver::new(model);
+    run_tests(&mut solver, &tests);
+}
+
+#[test]
+fn test_opt_max_propagation() {
+    let mut model = Model::new();
+    let pa = model.new_bvar("pa").true_lit();
+    let a = model.new_optional_ivar(0, 10, pa, "a");
+    let b = model.new_ivar(0, 5, "b");
+    let m = model.new_ivar(0, 20, "m");
+    model.enforce(eq_max(m, [a, b]), []);
+
+    let tests = vec![
+        Test::new(&[!pa], &[m.leq(5)]),
+        Test::new(&[pa, a.geq(7)], &[m.geq(7)]),
+        Test::new(&[m.geq(6)], &[pa, a.geq(6)]),
+        Test::new(&[m.leq(4)], &[b.leq(4)]),
+    ];
+
+    let mut solver = Solver::new(model);
+    run_tests(&mut solver, &tests);
+}
+
+#[test]
+fn minimize_max() {
+    let mut model = Model::new();
+    let a = model.new_ivar(0, 10, "a");
+    let b = model.new_ivar(0, 10, "b");
+    let c = model.new_ivar(0, 10, "c");
+    let makespan = model.new_ivar(0, 100, "makespan");
+
+    model.enforce(lt(a, b), []);
+    model.enforce(lt(a, c), []);
+    model.enforce(neq(b, c), []);
+    model.enforce(eq_max(makespan, [a, b, c]), []);
+
+    let mut solver = Solver::new(model);
+    match solver.minimize(makespan).unwrap() {
+        None => panic!(),
+        Some((val, sol)) => {
+            assert_eq!(val, 2);
+            let values = [a, b, c].map(|v| sol.lower_bound(v));
+            assert_eq!(values.iter().max().copied(), Some(val));
+        }
+    }
+}
+
+/// Enumerates all solutions of the solver, returning the values of the given variables in each of them.
+fn all_solutions(solver: &mut Solver, vars: &[IVar]) -> Vec<Vec<i32>> {
+    let mut solutions = Vec::new();
+    while let Some(sol) = solver.solve().unwrap() {
+        let values: Vec<i32> = vars.iter().map(|&v| sol.lower_bound(v)).collect();
+        // exclude the current assignment
+        let clause: Vec<Lit> = vars
+            .iter()
+            .zip(&values)
+            .flat_map(|(&v, &value)| [v.lt(value), v.gt(value)])
+            .collect();
+        solutions.push(values);
+        solver.reset();
+        solver.enforce(or(clause), []);
+    }
+    solutions
+}
+
+#[test]
+fn min_solutions() {
+    let mut model = Model::new();
+    let vars: Vec<IVar> = (0..3).map(|i| model.new_ivar(-3, 3, format!("x{i}"))).collect();
+    let min = model.new_ivar(-10, 10, "min");
+    model.enforce(eq_min(min, vars.iter().copied()), []);
+
+    let mut solver = Solver::new(model);
+    let mut all_vars = vars.clone();
+    all_vars.push(min);
+    let solutions = all_solutions(&mut solver, &all_vars);
+    for sol in &solutions {
+        assert_eq!(sol[3], *sol[0..3].iter().min().unwrap());
+    }
+    assert_eq!(solutions.len(), 7 * 7 * 7);
+}
+
+/// Checks that the solutions of the constraint `y = f(x)` (posted by `enforce`) are exactly the ones expected, for `x` in [-20, 20] and `y` in [-10, 10].
+fn check_functional(enforce: impl Fn(&mut Model, IVar, IVar), f: impl Fn(i32) -> i32) {
+    let mut model = Model::new();
+    let x = model.new_ivar(-20, 20, "x");
+    let y = model.new_ivar(-10, 10, "y");
+    enforce(&mut model, y, x);
+    let mut solver = Solver::new(model);
+    let mut solutions = all_solutions(&mut solver, &[x, y]);
+    solutions.sort();
+    let expected: Vec<Vec<i32>> = (-20..=20)
+        .map(|x| vec![x, f(x)])
+        .filter(|sol| (-10..=10).contains(&sol[1]))
+        .collect();
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn arith_solutions() {
+    check_functional(|m, y, x| m.enforce(eq_abs(y, x), []), |x| x.abs());
+    check_functional(|m, y, x| m.enforce(eq_abs(y + 3, x - 2), []), |x| (x - 2).abs() - 3);
+    for d in [-7, -3, -2, -1, 1, 2, 3, 5] {
+        check_functional(|m, y, x| m.enforce(eq_div(y, x, d), []), |x| x / d);
+        check_functional(|m, y, x| m.enforce(eq_div(y - 1, x + 4, d), []), |x| (x + 4) / d + 1);
+        check_functional(|m, y, x| m.enforce(eq_mod(y, x, d), []), |x| x % d);
+        check_functional(|m, y, x| m.enforce(eq_mod(y + 2, x - 3, d), []), |x| (x - 3) % d - 2);
+    }
+}
+
+#[test]
+fn mul_solutions() {
+    for k in [-3, -1, 0, 1, 2, 5] {
+        check_functional(|m, y, x| m.enforce(eq_mul(y, k, x), []), |x| k * x);
+        check_functional(|m, y, x| m.enforce(eq_mul(y - 2, x + 1, k), []), |x| (x + 1) * k + 2);
+    }
+
+    // bilinear case, compared with an enumeration of all candidate assignments
+    let mut model = Model::new();
+    let x = model.new_ivar(-4, 5, "x");
+    let y = model.new_ivar(-3, 6, "y");
+    let z = model.new_ivar(-10, 12, "z");
+    model.enforce(eq_mul(z, x, y + 1), []);
+    let mut solver = Solver::new(model);
+    let mut solutions = all_solutions(&mut solver, &[x, y, z]);
+    solutions.sort();
+    let expected: Vec<Vec<i32>> = (-4..=5)
+        .cartesian_product(-3..=6)
+        .map(|(x, y)| vec![x, y, x * (y + 1)])
+        .filter(|sol| (-10..=12).contains(&sol[2]))
+        .collect();
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn test_mul_propagation() {
+    let mut model = Model::new();
+    let x = model.new_ivar(-10, 10, "x");
+    let y = model.new_ivar(-10, 10, "y");
+    let z = model.new_ivar(-200, 200, "z");
+    model.enforce(eq_mul(z, x, y), []);
+
+    let tests = vec![
+        Test::new(&[], &[z.geq(-100), z.leq(100)]),
+        Test::new(&[x.geq(2), y.geq(3)], &[z.geq(6)]),
+        Test::new(&[x.geq(2), y.leq(-3)], &[z.leq(-6)]),
+        Test::new(&[x.geq(-2), y.geq(5)], &[z.geq(-20)]),
+        // z >= 30 with |y| <= 10 requires |x| >= 3, on the side given by the sign of y
+        Test::new(&[z.geq(30), y.geq(1)], &[x.geq(3)]),
+        Test::new(&[z.geq(30), y.leq(-1)], &[x.leq(-3)]),
+    ];
+
+    let mut solver = Solver::new(model);
+    run_tests(&mut solver, &tests);
+}
+
+#[test]
+fn test_abs_propagation() {
+    let mut model = Model::new();
+    let x = model.new_ivar(-10, 10, "x");
+    let y = model.new_ivar(-5, 20, "y");
+    model.enforce(eq_abs(y, x), []);
+
+    let tests = vec![
+        Test::new(&[], &[y.geq(0), y.leq(10)]),
+        Test::new(&[y.leq(3)], &[x.leq(3), x.geq(-3)]),
+        Test::new(&[x.geq(2)], &[y.geq(2)]),
+        Test::new(&[x.leq(-4)], &[y.geq(4)]),
+        Test::new(&[y.geq(5), x.geq(-4)], &[x.geq(5)]),
+        Test::new(&[y.geq(5), x.leq(4)], &[x.leq(-5)]),
+    ];
+
+    let mut solver = Solver::new(model);
+    run_tests(&mut solver, &tests);
+}
+
+#[test]
+fn test_div_propagation() {
+    let mut model = Model::new();
+    let x = model.new_ivar(-100, 100, "x");
+    let y = model.new_ivar(-100, 100, "y");
+    model.enforce(eq_div(y, x, 3), []);
+
+    let tests = vec![
+        Test::new(&[], &[y.geq(-33), y.leq(33)]),
+        Test::new(&[x.geq(7)], &[y.geq(2)]),
+        Test::new(&[x.leq(-7)], &[y.leq(-2)]),
+        Test::new(&[y.geq(2)], &[x.geq(6)]),
+        Test::new(&[y.leq(0)], &[x.leq(2)]),
+        Test::new(&[y.geq(0)], &[x.geq(-2)]),
+    ];
+
+    let mut solver = Solver::new(model);
+    run_tests(&mut solver, &tests);
+}
+
+#[test]
+fn channel_solutions() {
+    let mut model = Model::new();
+    let x = model.new_ivar(-5, 5, "x");
+    let lits: Vec<Lit> = (0..4).map(|i| model.new_bvar(format!("b{i}")).true_lit()).collect();
+    model.enforce(channel(x - 1, 2, lits.iter().copied()), []);
+    let bools: Vec<IVar> = lits.iter().map(|l| IVar::new(l.variable())).collect();
+    let mut vars = vec![x];
+    vars.extend_from_slice(&bools);
+
+    let mut solver = Solver::new(model);
+    let mut solutions = all_solutions(&mut solver, &vars);
+    solutions.sort();
+    // x - 1 in [2, 5], with exactly the literal of its value being true
+    let expected: Vec<Vec<i32>> = (3..=5)
+        .map(|x| {
+            let mut sol = vec![x];
+            sol.extend((0..4).map(|i| (x - 1 == 2 + i) as i32));
+            sol
+        })
+        .collect();
+    assert_eq!(solutions, expected);
+}
+
+#[test]
+fn test_channel_propagation() {
+    let mut model = Model::new();
+    let x = model.new_ivar(0, 10, "x");
+    let lits: Vec<Lit> = (0..5).map(|i| model.new_bvar(format!("b{i}")).true_lit()).collect();
+    model.enforce(channel(x, 1, lits.iter().copied()), []);
+
+    let tests = vec![
+        Test::new(&[], &[x.geq(1), x.leq(5)]),
+        Test::new(&[lits[2]], &[x.geq(3), x.leq(3), !lits[0], !lits[4]]),
+        Test::new(&[!lits[0], !lits[1]], &[x.geq(3)]),
+        Test::new(&[!lits[4], !lits[3]], &[x.leq(3)]),
+        Test::new(&[x.geq(3)], &[!lits[0], !lits[1]]),
+        Test::new(&[x.geq(4), x.leq(4)], &[lits[3]]),
+        Test::new(&[!lits[0], !lits[1], !lits[3], !lits[4]], &[lits[2]]),
+    ];
+
+    let mut solver = Solver::new(model);
+    run_tests(&mut solver, &tests);
+}
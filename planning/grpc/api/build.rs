@@ -2,16 +2,17 @@
 #[cfg(feature = "generate_bindings")]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     use std::fs;
-    let proto_file = "src/unified_planning.proto";
+    // upstream definitions of the Unified Planning protocol, and extensions specific to Aries (in the `aries` package)
+    let proto_files = ["src/unified_planning.proto", "src/aries.proto"];
 
-    let x: [&str; 0] = [];
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
         .out_dir("src/")
-        .compile(&[proto_file], &x)
+        .compile(&proto_files, &["src/"])
         .unwrap_or_else(|e| panic!("Failed to compile proto: {}", e));
 
+    // the definitions without a package are written to `_.rs`, and those of the `aries` package to `aries.rs`
     fs::rename("src/_.rs", "src/unified_planning.rs")?;
 
     Ok(())
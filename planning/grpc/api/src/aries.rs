@@ -0,0 +1,617 @@
+/// A batch of independent plan requests, typically variants of the same problem.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlanBatchRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub requests: ::prost::alloc::vec::Vec<super::PlanRequest>,
+}
+/// The final result of one of the requests of a batch.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlanBatchResult {
+    /// Index of the request in the batch.
+    #[prost(uint64, tag = "1")]
+    pub request_index: u64,
+    #[prost(message, optional, tag = "2")]
+    pub result: ::core::option::Option<super::PlanGenerationResult>,
+}
+/// Handle on a plan request submitted as a job, whose solving is not tied to the connection of the client.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobHandle {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+}
+/// Request for the results of a job.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AttachJobRequest {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+    /// Number of results of the job to skip, e.g. those received on a previous stream before it was interrupted.
+    #[prost(uint64, tag = "2")]
+    pub first_result: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobStatus {
+    #[prost(string, tag = "1")]
+    pub job_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "job_status::State", tag = "2")]
+    pub state: i32,
+    /// Number of results produced by the job so far, including its final result.
+    #[prost(uint64, tag = "3")]
+    pub num_results: u64,
+    /// Final result of the job, only present once the job is over.
+    #[prost(message, optional, tag = "4")]
+    pub result: ::core::option::Option<super::PlanGenerationResult>,
+}
+/// Nested message and enum types in `JobStatus`.
+pub mod job_status {
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum State {
+        /// The job is being solved, or waiting for the engine to be available.
+        Running = 0,
+        /// The job is over, its final result is available.
+        Completed = 1,
+        /// The job was cancelled. Its final result, with the best plan found before the cancellation, is available
+        /// once the engine has stopped solving it.
+        Cancelled = 2,
+    }
+    impl State {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                State::Running => "RUNNING",
+                State::Completed => "COMPLETED",
+                State::Cancelled => "CANCELLED",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "RUNNING" => Some(Self::Running),
+                "COMPLETED" => Some(Self::Completed),
+                "CANCELLED" => Some(Self::Cancelled),
+                _ => None,
+            }
+        }
+    }
+}
+/// Generated client implementations.
+pub mod planning_extensions_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    /// Services of the Aries engine, served alongside the `UnifiedPlanning` service.
+    #[derive(Debug, Clone)]
+    pub struct PlanningExtensionsClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl PlanningExtensionsClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> PlanningExtensionsClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> PlanningExtensionsClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+            >>::Error: Into<StdError> + Send + Sync,
+        {
+            PlanningExtensionsClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// A batch of plan requests to the engine.
+        /// The engine replies with one `PlanBatchResult` per request, in the order in which they complete.
+        pub async fn plan_batch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PlanBatchRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::PlanBatchResult>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aries.PlanningExtensions/planBatch",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
+        /// A plan request to the engine, solved as a job that is not interrupted if the connection of the client drops.
+        /// The engine replies with a handle on the job, whose results are obtained with `attachJob`.
+        pub async fn submit_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::super::PlanRequest>,
+        ) -> Result<tonic::Response<super::JobHandle>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aries.PlanningExtensions/submitJob",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Streams the results of a job as `planAnytime` does, starting with those already produced.
+        /// The stream ends after the final result. Dropping the stream detaches from the job without affecting it,
+        /// and the client can attach again later, skipping the results it already received.
+        pub async fn attach_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AttachJobRequest>,
+        ) -> Result<
+            tonic::Response<tonic::codec::Streaming<super::super::PlanGenerationResult>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aries.PlanningExtensions/attachJob",
+            );
+            self.inner.server_streaming(request.into_request(), path, codec).await
+        }
+        /// The engine replies with the current status of the job.
+        pub async fn job_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::JobHandle>,
+        ) -> Result<tonic::Response<super::JobStatus>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aries.PlanningExtensions/jobStatus",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Cancels a job, whose final result is the best plan found so far.
+        /// The engine replies with the status of the job, without waiting for the solving to stop.
+        pub async fn cancel_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::JobHandle>,
+        ) -> Result<tonic::Response<super::JobStatus>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/aries.PlanningExtensions/cancelJob",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod planning_extensions_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with PlanningExtensionsServer.
+    #[async_trait]
+    pub trait PlanningExtensions: Send + Sync + 'static {
+        /// Server streaming response type for the planBatch method.
+        type planBatchStream: futures_core::Stream<
+                Item = Result<super::PlanBatchResult, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// A batch of plan requests to the engine.
+        /// The engine replies with one `PlanBatchResult` per request, in the order in which they complete.
+        async fn plan_batch(
+            &self,
+            request: tonic::Request<super::PlanBatchRequest>,
+        ) -> Result<tonic::Response<Self::planBatchStream>, tonic::Status>;
+        /// A plan request to the engine, solved as a job that is not interrupted if the connection of the client drops.
+        /// The engine replies with a handle on the job, whose results are obtained with `attachJob`.
+        async fn submit_job(
+            &self,
+            request: tonic::Request<super::super::PlanRequest>,
+        ) -> Result<tonic::Response<super::JobHandle>, tonic::Status>;
+        /// Server streaming response type for the attachJob method.
+        type attachJobStream: futures_core::Stream<
+                Item = Result<super::super::PlanGenerationResult, tonic::Status>,
+            >
+            + Send
+            + 'static;
+        /// Streams the results of a job as `planAnytime` does, starting with those already produced.
+        /// The stream ends after the final result. Dropping the stream detaches from the job without affecting it,
+        /// and the client can attach again later, skipping the results it already received.
+        async fn attach_job(
+            &self,
+            request: tonic::Request<super::AttachJobRequest>,
+        ) -> Result<tonic::Response<Self::attachJobStream>, tonic::Status>;
+        /// The engine replies with the current status of the job.
+        async fn job_status(
+            &self,
+            request: tonic::Request<super::JobHandle>,
+        ) -> Result<tonic::Response<super::JobStatus>, tonic::Status>;
+        /// Cancels a job, whose final result is the best plan found so far.
+        /// The engine replies with the status of the job, without waiting for the solving to stop.
+        async fn cancel_job(
+            &self,
+            request: tonic::Request<super::JobHandle>,
+        ) -> Result<tonic::Response<super::JobStatus>, tonic::Status>;
+    }
+    /// Services of the Aries engine, served alongside the `UnifiedPlanning` service.
+    #[derive(Debug)]
+    pub struct PlanningExtensionsServer<T: PlanningExtensions> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: PlanningExtensions> PlanningExtensionsServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for PlanningExtensionsServer<T>
+    where
+        T: PlanningExtensions,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/aries.PlanningExtensions/planBatch" => {
+                    #[allow(non_camel_case_types)]
+                    struct planBatchSvc<T: PlanningExtensions>(pub Arc<T>);
+                    impl<
+                        T: PlanningExtensions,
+                    > tonic::server::ServerStreamingService<super::PlanBatchRequest>
+                    for planBatchSvc<T> {
+                        type Response = super::PlanBatchResult;
+                        type ResponseStream = T::planBatchStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PlanBatchRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).plan_batch(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = planBatchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/aries.PlanningExtensions/submitJob" => {
+                    #[allow(non_camel_case_types)]
+                    struct submitJobSvc<T: PlanningExtensions>(pub Arc<T>);
+                    impl<
+                        T: PlanningExtensions,
+                    > tonic::server::UnaryService<super::super::PlanRequest>
+                    for submitJobSvc<T> {
+                        type Response = super::JobHandle;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::super::PlanRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).submit_job(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = submitJobSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/aries.PlanningExtensions/attachJob" => {
+                    #[allow(non_camel_case_types)]
+                    struct attachJobSvc<T: PlanningExtensions>(pub Arc<T>);
+                    impl<
+                        T: PlanningExtensions,
+                    > tonic::server::ServerStreamingService<super::AttachJobRequest>
+                    for attachJobSvc<T> {
+                        type Response = super::super::PlanGenerationResult;
+                        type ResponseStream = T::attachJobStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AttachJobRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).attach_job(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = attachJobSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/aries.PlanningExtensions/jobStatus" => {
+                    #[allow(non_camel_case_types)]
+                    struct jobStatusSvc<T: PlanningExtensions>(pub Arc<T>);
+                    impl<
+                        T: PlanningExtensions,
+                    > tonic::server::UnaryService<super::JobHandle> for jobStatusSvc<T> {
+                        type Response = super::JobStatus;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JobHandle>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).job_status(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = jobStatusSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/aries.PlanningExtensions/cancelJob" => {
+                    #[allow(non_camel_case_types)]
+                    struct cancelJobSvc<T: PlanningExtensions>(pub Arc<T>);
+                    impl<
+                        T: PlanningExtensions,
+                    > tonic::server::UnaryService<super::JobHandle> for cancelJobSvc<T> {
+                        type Response = super::JobStatus;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::JobHandle>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).cancel_job(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cancelJobSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => {
+                    Box::pin(async move {
+                        Ok(
+                            http::Response::builder()
+                                .status(200)
+                                .header("grpc-status", "12")
+                                .header("content-type", "application/grpc")
+                                .body(empty_body())
+                                .unwrap(),
+                        )
+                    })
+                }
+            }
+        }
+    }
+    impl<T: PlanningExtensions> Clone for PlanningExtensionsServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+            }
+        }
+    }
+    impl<T: PlanningExtensions> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: PlanningExtensions> tonic::server::NamedService
+    for PlanningExtensionsServer<T> {
+        const NAME: &'static str = "aries.PlanningExtensions";
+    }
+}
@@ -0,0 +1,133 @@
+//! Convenience layer over the generated client of the planning service, for Rust programs that delegate
+//! the resolution of their problems to a (possibly remote) server.
+use crate::plan_request::Mode;
+use crate::unified_planning_client::UnifiedPlanningClient;
+use crate::{PlanGenerationResult, PlanRequest, Problem};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Status};
+
+/// Stream of the results of a plan request, as returned by [UnifiedPlanningClientExt::solve_problem].
+pub type PlanStream = Pin<Box<dyn Stream<Item = Result<PlanGenerationResult, Status>> + Send>>;
+
+/// Options of a plan request sent with [UnifiedPlanningClientExt::solve_problem_with].
+#[derive(Clone, Debug)]
+pub struct SolveOptions {
+    /// Whether the planner should look for any plan or for an optimal one.
+    pub resolution_mode: Mode,
+    /// Time allowed to the planner, unlimited if `None`.
+    pub timeout: Option<Duration>,
+    /// Time granted to the server after the timeout to send its last results, before the request is cancelled.
+    pub grace_period: Duration,
+    /// Number of times the request is resent when the server is unavailable, as long as it has not produced any result.
+    pub max_retries: u32,
+    /// Delay before resending the request, doubled after each retry.
+    pub retry_delay: Duration,
+    /// Options passed to the engine (see `request_config` in the server).
+    pub engine_options: HashMap<String, String>,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions {
+            resolution_mode: Mode::Satisfiable,
+            timeout: None,
+            grace_period: Duration::from_secs(5),
+            max_retries: 3,
+            retry_delay: Duration::from_millis(500),
+            engine_options: HashMap::new(),
+        }
+    }
+}
+
+/// Creates a client of the server at the given address (e.g. `http://localhost:2222`).
+///
+/// The connection is only established on the first request, and re-established transparently by the subsequent
+/// ones if it is lost, so that the client can be created before the server is up.
+pub fn connect_lazy(address: impl Into<String>) -> Result<UnifiedPlanningClient<Channel>, tonic::transport::Error> {
+    let channel = Endpoint::from_shared(address.into())?.connect_lazy();
+    Ok(UnifiedPlanningClient::new(channel))
+}
+
+/// Extension of the generated client with higher level methods.
+pub trait UnifiedPlanningClientExt {
+    /// Sends an anytime plan request for the problem with the default options, and returns the stream of its results:
+    /// each improved plan, followed by the final result.
+    fn solve_problem(&self, problem: Problem) -> PlanStream {
+        self.solve_problem_with(problem, SolveOptions::default())
+    }
+
+    /// Sends an anytime plan request for the problem, and returns the stream of its results.
+    ///
+    /// If the server is unavailable, the request is resent (see [SolveOptions::max_retries]).
+    /// If the server does not answer before the end of the timeout and of the grace period,
+    /// the stream ends with a `DeadlineExceeded` error.
+    fn solve_problem_with(&self, problem: Problem, options: SolveOptions) -> PlanStream;
+}
+
+impl UnifiedPlanningClientExt for UnifiedPlanningClient<Channel> {
+    fn solve_problem_with(&self, problem: Problem, options: SolveOptions) -> PlanStream {
+        let client = self.clone();
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            if let Err(status) = forward_results(client, problem, &options, &tx).await {
+                // the receiver may have been dropped, in which case nobody is interested in the error
+                let _ = tx.send(Err(status)).await;
+            }
+        });
+        Box::pin(ReceiverStream::new(rx))
+    }
+}
+
+/// Sends the plan request and forwards its results to `tx`, resending it as long as the server is unavailable
+/// and no result was received.
+async fn forward_results(
+    mut client: UnifiedPlanningClient<Channel>,
+    problem: Problem,
+    options: &SolveOptions,
+    tx: &mpsc::Sender<Result<PlanGenerationResult, Status>>,
+) -> Result<(), Status> {
+    let mut retries = 0;
+    let mut delay = options.retry_delay;
+    let mut received_any = false;
+    loop {
+        let mut request = Request::new(PlanRequest {
+            problem: Some(problem.clone()),
+            resolution_mode: options.resolution_mode as i32,
+            timeout: options.timeout.map_or(0.0, |t| t.as_secs_f64()),
+            engine_options: options.engine_options.clone(),
+        });
+        if let Some(timeout) = options.timeout {
+            request.set_timeout(timeout + options.grace_period);
+        }
+        let status = match client.plan_anytime(request).await {
+            Ok(response) => {
+                let mut results = response.into_inner();
+                loop {
+                    match results.message().await {
+                        Ok(Some(result)) => {
+                            received_any = true;
+                            if tx.send(Ok(result)).await.is_err() {
+                                return Ok(()); // the stream was dropped, stop listening
+                            }
+                        }
+                        Ok(None) => return Ok(()),
+                        Err(status) => break status,
+                    }
+                }
+            }
+            Err(status) => status,
+        };
+        if status.code() != Code::Unavailable || received_any || retries >= options.max_retries {
+            return Err(status);
+        }
+        retries += 1;
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+    }
+}
@@ -3,6 +3,13 @@
 #[rustfmt::skip]
 mod unified_planning;
 
+/// Messages and services specific to the Aries engine, that extend the Unified Planning protocol.
+/// They are generated from `aries.proto`, as the upstream `unified_planning.proto` must not be modified.
+#[rustfmt::skip]
+pub mod aries;
+
+pub mod client;
+
 // Re-exports all items from the unified_planning module, that is automatically
 // generated by prost from the protobuf definition.
 pub use crate::unified_planning::*;
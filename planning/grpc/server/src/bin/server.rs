@@ -1,23 +1,35 @@
-use anyhow::{bail, ensure, Context, Error};
+use anyhow::{Context, Error};
+use aries::core::IntCst;
 use aries::model::extensions::SavedAssignment;
+use aries::solver::parallel::signals::Progress;
 use aries_grpc_server::chronicles::problem_to_chronicles;
+use aries_grpc_server::features::{check_features, UnsupportedFeatures};
+use aries_grpc_server::jobs::Jobs;
+use aries_grpc_server::metrics::problem_metrics;
+use aries_grpc_server::security::{tls_config, TokenAuth};
 use aries_grpc_server::serialize::{engine, serialize_plan};
+use aries_grpc_server::stats::{serve_metrics, ServiceStats};
 use aries_plan_validator::validate_upf;
+use aries_planners::fmt::{count_partial_actions, format_partial_assignment};
 use aries_planners::solver;
-use aries_planners::solver::{Metric, SolverResult};
+use aries_planners::solver::{Metric, PlannerConfig, SolverResult};
 use aries_planning::chronicles::analysis::hierarchical_is_non_recursive;
 use aries_planning::chronicles::FiniteProblem;
 use async_trait::async_trait;
 use clap::Parser;
 use itertools::Itertools;
 use prost::Message;
-use std::sync::Arc;
-use std::time::Instant;
-use tokio::sync::mpsc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Semaphore};
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{transport::Server, Request, Response, Status};
 use unified_planning as up;
-use unified_planning::metric::MetricKind;
+use unified_planning::aries::planning_extensions_server::{PlanningExtensions, PlanningExtensionsServer};
+use unified_planning::aries::{AttachJobRequest, JobHandle, JobStatus, PlanBatchRequest, PlanBatchResult};
 use unified_planning::unified_planning_server::{UnifiedPlanning, UnifiedPlanningServer};
 use unified_planning::validation_result::ValidationResultStatus;
 use unified_planning::{log_message, plan_generation_result, LogMessage, PlanGenerationResult, PlanRequest};
@@ -34,31 +46,129 @@ struct Args {
     #[clap(short, long)]
     /// Encoded UP problem to solve. Optional if a problem is provided in a request.
     file_path: Option<String>,
+
+    /// PEM file with the certificate of the server. If provided, connections are encrypted with TLS.
+    #[clap(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM file with the private key of the server.
+    #[clap(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// PEM file with the certificate of the authority that must have signed the certificates of the clients.
+    /// If provided, clients must authenticate with a certificate.
+    #[clap(long, requires = "tls_cert")]
+    tls_client_ca: Option<PathBuf>,
+
+    /// File containing a token that clients must provide in an `authorization: Bearer <token>` metadata entry.
+    /// The token is read from a file so that it does not appear in the arguments of the process.
+    #[clap(long)]
+    token_file: Option<PathBuf>,
+
+    /// Address on which counters on the activity of the server (requests served, solving time, conflicts, memory)
+    /// are exposed over HTTP in the Prometheus text format, at the `/metrics` path (e.g. `0.0.0.0:9090`).
+    #[clap(long)]
+    metrics_address: Option<String>,
+
+    /// Time (in seconds) during which the results of a job submitted with `submitJob` remain available once it is over.
+    #[clap(long, default_value = "3600")]
+    job_retention: u64,
+}
+
+/// Converts the progress reports of the solvers into log messages, passed to the `on_log` callback.
+///
+/// Reports announcing a solution that improves on the best one found so far are logged at the INFO level,
+/// all others at the DEBUG level. Improvements are increases of the cost if the objective is maximized.
+fn progress_logger(
+    on_log: impl Fn(LogMessage) + Send + Sync,
+    maximize: bool,
+) -> impl Fn(usize, &Progress) + Send + Sync {
+    let best_cost: Mutex<Option<IntCst>> = Mutex::new(None);
+    move |worker: usize, progress: &Progress| {
+        let mut best_cost = best_cost.lock().unwrap();
+        let improved = match (progress.best_cost, *best_cost) {
+            (Some(cost), Some(best)) if maximize => cost > best,
+            (Some(cost), Some(best)) => cost < best,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        let (level, message) = if improved {
+            *best_cost = progress.best_cost;
+            let cost = progress.best_cost.unwrap();
+            let bound = match progress.objective_bound {
+                Some(bound) => {
+                    let gap = (cost - bound).abs() as f64 / cost.abs().max(1) as f64;
+                    let kind = if maximize { "upper" } else { "lower" };
+                    format!(" ({kind} bound {bound}, gap {:.1}%)", gap * 100.0)
+                }
+                None => String::new(),
+            };
+            let message = format!(
+                "[worker {worker}] [{:.3}s] New best solution with cost {cost}{bound}",
+                progress.solve_time.as_secs_f64(),
+            );
+            (log_message::LogLevel::Info, message)
+        } else {
+            let message = format!(
+                "[worker {worker}] [{:.3}s] {} restarts, {} conflicts ({:.0} conflicts/sec), {} solutions",
+                progress.solve_time.as_secs_f64(),
+                progress.num_restarts,
+                progress.num_conflicts,
+                progress.conflicts_per_sec(),
+                progress.num_solutions
+            );
+            (log_message::LogLevel::Debug, message)
+        };
+        on_log(LogMessage {
+            level: level as i32,
+            message,
+        })
+    }
 }
 
-/// Solves the given problem, giving any intermediate solution to the callback.
+/// Solves the given problem, giving any intermediate solution to the `on_new_sol` callback
+/// and log messages on the progress of the search to the `on_log` callback.
+/// The solving time and conflicts of the search are recorded in `stats`.
 pub fn solve(
     problem: &up::Problem,
+    config: &PlannerConfig,
+    stats: &Arc<ServiceStats>,
     on_new_sol: impl Fn(up::Plan) + Clone,
+    on_log: impl Fn(LogMessage) + Send + Sync + 'static,
+    deadline: Option<Instant>,
+) -> Result<up::PlanGenerationResult, Error> {
+    if let Err(unsupported) = check_features(problem) {
+        return Ok(unsupported_problem(unsupported));
+    }
+    let (base_problem, metric) = problem_to_chronicles(problem)
+        .with_context(|| format!("In problem {}/{}", &problem.domain_name, &problem.problem_name))?;
+    solve_chronicles(
+        problem,
+        base_problem,
+        metric,
+        config,
+        stats,
+        on_new_sol,
+        on_log,
+        deadline,
+    )
+}
+
+/// Same as [solve] but for a problem that was already converted into chronicles.
+#[allow(clippy::too_many_arguments)]
+fn solve_chronicles(
+    problem: &up::Problem,
+    base_problem: aries_planning::chronicles::Problem,
+    metric: Option<Metric>,
+    config: &PlannerConfig,
+    stats: &Arc<ServiceStats>,
+    on_new_sol: impl Fn(up::Plan) + Clone,
+    on_log: impl Fn(LogMessage) + Send + Sync + 'static,
     deadline: Option<Instant>,
 ) -> Result<up::PlanGenerationResult, Error> {
     let strategies = vec![];
     let htn_mode = problem.hierarchy.is_some();
 
-    ensure!(problem.metrics.len() <= 1, "Unsupported: multiple metrics provided.");
-    let metric = if let Some(metric) = problem.metrics.get(0) {
-        match up::metric::MetricKind::from_i32(metric.kind) {
-            Some(MetricKind::MinimizeActionCosts) => Some(Metric::ActionCosts),
-            Some(MetricKind::MinimizeSequentialPlanLength) => Some(Metric::PlanLength),
-            Some(MetricKind::MinimizeMakespan) => Some(Metric::Makespan),
-            _ => bail!("Unsupported metric kind with ID: {}", metric.kind),
-        }
-    } else {
-        None
-    };
-
-    let base_problem = problem_to_chronicles(problem)
-        .with_context(|| format!("In problem {}/{}", &problem.domain_name, &problem.problem_name))?;
     let bounded = htn_mode && hierarchical_is_non_recursive(&base_problem);
 
     let max_depth = u32::MAX;
@@ -76,7 +186,27 @@ pub fn solve(
             Err(err) => eprintln!("Error when serializing intermediate plan: {err}"),
         }
     };
+    // callback that will be invoked each time a solver reports its progress
+    let maximize = metric.map_or(false, Metric::is_maximization);
+    let progress_logger = Arc::new(progress_logger(on_log, maximize));
+    let conflicts_recorder = stats.conflicts_recorder();
+    let on_progress = move |worker: usize, progress: &Progress| {
+        conflicts_recorder(worker, progress);
+        progress_logger(worker, progress)
+    };
+
+    // features of the problem, reported in the final result
+    let mut metrics = problem_metrics(&base_problem);
+
+    // callback that will be invoked with the deepest partial plan, if the search times out without a plan
+    let partial_plan = RefCell::new(None);
+    let on_partial_plan = |pb: &FiniteProblem, ass: Arc<SavedAssignment>| {
+        let num_actions = count_partial_actions(pb, &ass);
+        *partial_plan.borrow_mut() = Some((num_actions, format_partial_assignment(pb, &ass)));
+    };
+
     // run solver
+    let start = Instant::now();
     let result = solver::solve(
         base_problem,
         min_depth,
@@ -84,10 +214,14 @@ pub fn solve(
         &strategies,
         metric,
         htn_mode,
+        config,
         on_new_solution,
+        on_progress,
+        on_partial_plan,
         deadline,
-    )?;
-    match result {
+    );
+    stats.record_solve(start.elapsed());
+    match result? {
         SolverResult::Sol((finite_problem, plan)) => {
             println!(
                 "************* SOLUTION FOUND **************\n\n{}",
@@ -102,7 +236,7 @@ pub fn solve(
             Ok(up::PlanGenerationResult {
                 status: status as i32,
                 plan: Some(plan),
-                metrics: Default::default(),
+                metrics,
                 log_messages: vec![],
                 engine: Some(aries_grpc_server::serialize::engine()),
             })
@@ -112,7 +246,7 @@ pub fn solve(
             Ok(up::PlanGenerationResult {
                 status: up::plan_generation_result::Status::UnsolvableIncompletely as i32,
                 plan: None,
-                metrics: Default::default(),
+                metrics,
                 log_messages: vec![],
                 engine: Some(engine()),
             })
@@ -123,18 +257,155 @@ pub fn solve(
             } else {
                 None
             };
+            let mut log_messages = vec![];
+            if let (None, Some(((present, undecided), formatted))) = (&opt_plan, partial_plan.into_inner()) {
+                metrics.insert("aries_partial_plan_actions".to_string(), present.to_string());
+                metrics.insert(
+                    "aries_partial_plan_undecided_actions".to_string(),
+                    undecided.to_string(),
+                );
+                if let Ok(formatted) = formatted {
+                    log_messages.push(LogMessage {
+                        level: log_message::LogLevel::Info as i32,
+                        message: format!("Deepest partial plan reached before the timeout:\n{formatted}"),
+                    });
+                }
+            }
             Ok(up::PlanGenerationResult {
                 status: up::plan_generation_result::Status::Timeout as i32,
                 plan: opt_plan,
-                metrics: Default::default(),
-                log_messages: vec![],
+                metrics,
+                log_messages,
                 engine: Some(engine()),
             })
         }
     }
 }
-#[derive(Default)]
-pub struct UnifiedPlanningService {}
+
+/// Intermediate result announcing a new plan.
+fn intermediate_plan(plan: up::Plan) -> PlanGenerationResult {
+    PlanGenerationResult {
+        status: plan_generation_result::Status::Intermediate as i32,
+        plan: Some(plan),
+        metrics: Default::default(),
+        log_messages: vec![],
+        engine: Some(engine()),
+    }
+}
+
+/// Intermediate result carrying a log message.
+fn intermediate_log(log_message: LogMessage) -> PlanGenerationResult {
+    PlanGenerationResult {
+        status: plan_generation_result::Status::Intermediate as i32,
+        plan: None,
+        metrics: Default::default(),
+        log_messages: vec![log_message],
+        engine: Some(engine()),
+    }
+}
+
+/// Final result of a job that was cancelled before the engine started solving it.
+fn cancelled_before_start() -> PlanGenerationResult {
+    PlanGenerationResult {
+        status: plan_generation_result::Status::Timeout as i32,
+        plan: None,
+        metrics: Default::default(),
+        log_messages: vec![LogMessage {
+            level: log_message::LogLevel::Info as i32,
+            message: "The job was cancelled before being solved".to_string(),
+        }],
+        engine: Some(engine()),
+    }
+}
+
+/// Result reporting an error that prevented the engine from solving the problem.
+fn internal_error(e: Error) -> PlanGenerationResult {
+    let message = format!("{}", e.chain().rev().format("\n    Context: "));
+    let log_message = LogMessage {
+        level: log_message::LogLevel::Error as i32,
+        message,
+    };
+    PlanGenerationResult {
+        status: plan_generation_result::Status::InternalError as i32,
+        plan: None,
+        metrics: Default::default(),
+        log_messages: vec![log_message],
+        engine: Some(engine()),
+    }
+}
+
+/// Result reporting that the problem uses features that are not supported by the engine, with a log message
+/// locating each of them in the problem.
+fn unsupported_problem(unsupported: UnsupportedFeatures) -> PlanGenerationResult {
+    let log_messages = unsupported
+        .0
+        .iter()
+        .map(|feature| LogMessage {
+            level: log_message::LogLevel::Error as i32,
+            message: feature.to_string(),
+        })
+        .collect();
+    PlanGenerationResult {
+        status: plan_generation_result::Status::UnsupportedProblem as i32,
+        plan: None,
+        metrics: Default::default(),
+        log_messages,
+        engine: Some(engine()),
+    }
+}
+
+/// Deadline of a plan request with the given timeout (in seconds), where a null timeout means no deadline.
+fn deadline(timeout: f64) -> Option<Instant> {
+    if timeout != 0f64 {
+        Some(Instant::now() + std::time::Duration::from_secs_f64(timeout))
+    } else {
+        None
+    }
+}
+
+/// Configuration of the planner for a request: the configuration of the server, adapted with the engine options
+/// of the request. The supported options, whose value is either `true` or `false`, are:
+///
+///  - `remove_redundant_actions`: removes from the plan found the actions that are not needed for it to be valid
+///  - `reschedule`: reschedules the actions of the plan found to minimize its makespan
+fn request_config(config: &PlannerConfig, options: &HashMap<String, String>) -> Result<PlannerConfig, Error> {
+    let mut config = config.clone();
+    for (option, value) in options {
+        let flag = match option.as_str() {
+            "remove_redundant_actions" => &mut config.post_processing.remove_redundant_actions,
+            "reschedule" => &mut config.post_processing.reschedule,
+            _ => anyhow::bail!("Unknown engine option `{option}`"),
+        };
+        *flag = value
+            .parse()
+            .with_context(|| format!("Invalid value `{value}` for the engine option `{option}`"))?;
+    }
+    Ok(config)
+}
+
+#[derive(Clone)]
+pub struct UnifiedPlanningService {
+    /// Configuration of the planner, shared by all requests.
+    config: Arc<PlannerConfig>,
+    /// Counters on the requests served.
+    stats: Arc<ServiceStats>,
+    /// Plan requests submitted as jobs.
+    jobs: Arc<Jobs>,
+    /// Bounds the number of problems of batches and jobs that are solved concurrently, one per available core.
+    solver_permits: Arc<Semaphore>,
+}
+
+impl Default for UnifiedPlanningService {
+    fn default() -> Self {
+        let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+        UnifiedPlanningService {
+            config: Default::default(),
+            stats: Default::default(),
+            jobs: Default::default(),
+            solver_permits: Arc::new(Semaphore::new(parallelism)),
+        }
+    }
+}
 
 #[async_trait]
 impl UnifiedPlanning for UnifiedPlanningService {
@@ -143,26 +414,19 @@ impl UnifiedPlanning for UnifiedPlanningService {
     async fn plan_anytime(&self, request: Request<PlanRequest>) -> Result<Response<Self::planAnytimeStream>, Status> {
         let (tx, rx) = mpsc::channel(32);
         let plan_request = request.into_inner();
+        self.stats.record_plan_request();
 
         let problem = plan_request
             .problem
             .ok_or_else(|| Status::aborted("The `problem` field is empty"))?;
 
-        let deadline = if plan_request.timeout != 0f64 {
-            Some(std::time::Instant::now() + std::time::Duration::from_secs_f64(plan_request.timeout))
-        } else {
-            None
-        };
+        let deadline = deadline(plan_request.timeout);
+        let config = request_config(&self.config, &plan_request.engine_options)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
 
         let tx2 = tx.clone();
         let on_new_sol = move |plan: up::Plan| {
-            let answer = up::PlanGenerationResult {
-                status: up::plan_generation_result::Status::Intermediate as i32,
-                plan: Some(plan),
-                metrics: Default::default(),
-                log_messages: vec![],
-                engine: Some(aries_grpc_server::serialize::engine()),
-            };
+            let answer = intermediate_plan(plan);
 
             // start a new green thread in charge for sending the result
             let tx2 = tx2.clone();
@@ -173,29 +437,25 @@ impl UnifiedPlanning for UnifiedPlanningService {
             });
         };
 
+        let tx3 = tx.clone();
+        let on_log = move |log_message: LogMessage| {
+            let answer = intermediate_log(log_message);
+
+            // start a new green thread in charge for sending the log message
+            let tx3 = tx3.clone();
+            tokio::spawn(async move {
+                if tx3.send(Ok(answer)).await.is_err() {
+                    eprintln!("Could not send log message through the gRPC channel.");
+                }
+            });
+        };
+
         // run a new green thread in which the solver will run
+        let stats = self.stats.clone();
         tokio::spawn(async move {
-            let result = solve(&problem, on_new_sol, deadline);
-            match result {
-                Ok(answer) => {
-                    tx.send(Ok(answer)).await.unwrap();
-                }
-                Err(e) => {
-                    let message = format!("{}", e.chain().rev().format("\n    Context: "));
-                    let log_message = LogMessage {
-                        level: log_message::LogLevel::Error as i32,
-                        message,
-                    };
-                    let result = PlanGenerationResult {
-                        status: plan_generation_result::Status::InternalError as i32,
-                        plan: None,
-                        metrics: Default::default(),
-                        log_messages: vec![log_message],
-                        engine: Some(engine()),
-                    };
-                    tx.send(Ok(result)).await.unwrap();
-                }
-            }
+            let result = solve(&problem, &config, &stats, on_new_sol, on_log, deadline);
+            let answer = result.unwrap_or_else(internal_error);
+            tx.send(Ok(answer)).await.unwrap();
         });
         // return the output channel
         Ok(Response::new(ReceiverStream::new(rx)))
@@ -203,40 +463,24 @@ impl UnifiedPlanning for UnifiedPlanningService {
 
     async fn plan_one_shot(&self, request: Request<PlanRequest>) -> Result<Response<PlanGenerationResult>, Status> {
         let plan_request = request.into_inner();
+        self.stats.record_plan_request();
 
         let problem = plan_request
             .problem
             .ok_or_else(|| Status::aborted("The `problem` field is empty"))?;
 
-        let deadline = if plan_request.timeout != 0f64 {
-            Some(std::time::Instant::now() + std::time::Duration::from_secs_f64(plan_request.timeout))
-        } else {
-            None
-        };
+        let deadline = deadline(plan_request.timeout);
+        let config = request_config(&self.config, &plan_request.engine_options)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
 
-        let result = solve(&problem, |_| {}, deadline);
-        let answer = match result {
-            Ok(answer) => answer,
-            Err(e) => {
-                let message = format!("{}", e.chain().rev().format("\n    Context: "));
-                let log_message = LogMessage {
-                    level: log_message::LogLevel::Error as i32,
-                    message,
-                };
-                PlanGenerationResult {
-                    status: plan_generation_result::Status::InternalError as i32,
-                    plan: None,
-                    metrics: Default::default(),
-                    log_messages: vec![log_message],
-                    engine: Some(engine()),
-                }
-            }
-        };
+        let result = solve(&problem, &config, &self.stats, |_| {}, |_| {}, deadline);
+        let answer = result.unwrap_or_else(internal_error);
         Ok(Response::new(answer))
     }
 
     async fn validate_plan(&self, request: Request<ValidationRequest>) -> Result<Response<ValidationResult>, Status> {
         let validation_request = request.into_inner();
+        self.stats.record_validation_request();
 
         let problem = validation_request
             .problem
@@ -281,6 +525,186 @@ impl UnifiedPlanning for UnifiedPlanningService {
     }
 }
 
+#[async_trait]
+impl PlanningExtensions for UnifiedPlanningService {
+    type planBatchStream = ReceiverStream<Result<PlanBatchResult, Status>>;
+
+    async fn plan_batch(&self, request: Request<PlanBatchRequest>) -> Result<Response<Self::planBatchStream>, Status> {
+        let requests = request.into_inner().requests;
+        let (tx, rx) = mpsc::channel(requests.len().max(1));
+
+        // Requests of a sweep typically share the same problem, that is only converted once.
+        // The conversions are indexed by the encoding of the problem.
+        let mut conversions: HashMap<
+            Vec<u8>,
+            Arc<Result<(aries_planning::chronicles::Problem, Option<Metric>), String>>,
+        > = HashMap::new();
+
+        for (request_index, plan_request) in requests.into_iter().enumerate() {
+            self.stats.record_plan_request();
+            let timeout = plan_request.timeout;
+            let request = match (
+                plan_request.problem,
+                request_config(&self.config, &plan_request.engine_options),
+            ) {
+                (Some(problem), Ok(config)) => Ok((problem, config)),
+                (None, _) => Err(Error::msg("The `problem` field is empty")),
+                (_, Err(error)) => Err(error),
+            };
+            let (problem, config) = match request {
+                Ok(request) => request,
+                Err(error) => {
+                    let answer = PlanBatchResult {
+                        request_index: request_index as u64,
+                        result: Some(internal_error(error)),
+                    };
+                    if tx.send(Ok(answer)).await.is_err() {
+                        eprintln!("Could not send batch result through the gRPC channel.");
+                    }
+                    continue;
+                }
+            };
+            if let Err(unsupported) = check_features(&problem) {
+                let answer = PlanBatchResult {
+                    request_index: request_index as u64,
+                    result: Some(unsupported_problem(unsupported)),
+                };
+                if tx.send(Ok(answer)).await.is_err() {
+                    eprintln!("Could not send batch result through the gRPC channel.");
+                }
+                continue;
+            }
+            let key = problem.encode_to_vec();
+            let base_problem = match conversions.get(&key) {
+                Some(converted) => converted.clone(),
+                None => {
+                    // the conversion may be expensive and is kept off the async runtime
+                    let to_convert = problem.clone();
+                    let converted = tokio::task::spawn_blocking(move || {
+                        problem_to_chronicles(&to_convert)
+                            .with_context(|| {
+                                format!("In problem {}/{}", &to_convert.domain_name, &to_convert.problem_name)
+                            })
+                            .map_err(|e| format!("{}", e.chain().rev().format("\n    Context: ")))
+                    })
+                    .await
+                    .unwrap_or_else(|e| Err(format!("Conversion task failed: {e}")));
+                    let converted = Arc::new(converted);
+                    conversions.insert(key, converted.clone());
+                    converted
+                }
+            };
+
+            let tx = tx.clone();
+            let permits = self.solver_permits.clone();
+            let stats = self.stats.clone();
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await.unwrap();
+                // the timeout of a request only starts once it leaves the queue
+                let deadline = deadline(timeout);
+                let result = tokio::task::spawn_blocking(move || match base_problem.as_ref() {
+                    Ok((base_problem, metric)) => {
+                        let base_problem = base_problem.clone();
+                        solve_chronicles(
+                            &problem,
+                            base_problem,
+                            *metric,
+                            &config,
+                            &stats,
+                            |_| {},
+                            |_| {},
+                            deadline,
+                        )
+                    }
+                    Err(message) => Err(Error::msg(message.clone())),
+                })
+                .await
+                .unwrap_or_else(|e| Err(Error::msg(format!("Solver task failed: {e}"))));
+                let answer = PlanBatchResult {
+                    request_index: request_index as u64,
+                    result: Some(result.unwrap_or_else(internal_error)),
+                };
+                if tx.send(Ok(answer)).await.is_err() {
+                    eprintln!("Could not send batch result through the gRPC channel.");
+                }
+            });
+        }
+        // return the output channel, closed once all requests have completed
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn submit_job(&self, request: Request<PlanRequest>) -> Result<Response<JobHandle>, Status> {
+        let plan_request = request.into_inner();
+        self.stats.record_plan_request();
+
+        let problem = plan_request
+            .problem
+            .ok_or_else(|| Status::aborted("The `problem` field is empty"))?;
+
+        let deadline = deadline(plan_request.timeout);
+        let mut config = request_config(&self.config, &plan_request.engine_options)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let job = self.jobs.create();
+        config.interrupt = Some(job.interrupt_flag());
+        let handle = JobHandle {
+            job_id: job.id().to_string(),
+        };
+
+        // the job is solved independently of the connection of the client, its results are recorded in the job
+        let stats = self.stats.clone();
+        let permits = self.solver_permits.clone();
+        tokio::spawn(async move {
+            let _permit = permits.acquire_owned().await.unwrap();
+            let solved = tokio::task::spawn_blocking(move || {
+                if !job.start() {
+                    job.finish(cancelled_before_start());
+                    return;
+                }
+                let on_new_sol = |plan: up::Plan| job.push_result(intermediate_plan(plan));
+                let log_job = job.clone();
+                let on_log = move |log_message: LogMessage| log_job.push_result(intermediate_log(log_message));
+                let result = solve(&problem, &config, &stats, on_new_sol, on_log, deadline);
+                job.finish(result.unwrap_or_else(internal_error));
+            });
+            if let Err(e) = solved.await {
+                eprintln!("Solver task of a job failed: {e}");
+            }
+        });
+        Ok(Response::new(handle))
+    }
+
+    type attachJobStream = ReceiverStream<Result<PlanGenerationResult, Status>>;
+
+    async fn attach_job(&self, request: Request<AttachJobRequest>) -> Result<Response<Self::attachJobStream>, Status> {
+        let request = request.into_inner();
+        let job = self
+            .jobs
+            .get(&request.job_id)
+            .ok_or_else(|| Status::not_found(format!("No job with ID `{}`", request.job_id)))?;
+        Ok(Response::new(job.attach(request.first_result as usize)))
+    }
+
+    async fn job_status(&self, request: Request<JobHandle>) -> Result<Response<JobStatus>, Status> {
+        let job_id = request.into_inner().job_id;
+        let job = self
+            .jobs
+            .get(&job_id)
+            .ok_or_else(|| Status::not_found(format!("No job with ID `{job_id}`")))?;
+        Ok(Response::new(job.status()))
+    }
+
+    async fn cancel_job(&self, request: Request<JobHandle>) -> Result<Response<JobStatus>, Status> {
+        let job_id = request.into_inner().job_id;
+        let job = self
+            .jobs
+            .get(&job_id)
+            .ok_or_else(|| Status::not_found(format!("No job with ID `{job_id}`")))?;
+        job.cancel();
+        Ok(Response::new(job.status()))
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
@@ -293,7 +717,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Set address to localhost
     let addr = args.address.as_str().parse()?;
-    let upf_service = UnifiedPlanningService::default();
+    let upf_service = UnifiedPlanningService {
+        jobs: Arc::new(Jobs::new(Duration::from_secs(args.job_retention))),
+        ..Default::default()
+    };
 
     // If argument is provided, then read the file and send it to the server
     if let Some(file) = args.file_path {
@@ -309,9 +736,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let answer = response.into_inner();
         println!("{answer:?}");
     } else {
+        let mut server = Server::builder();
+        if let (Some(cert), Some(key)) = (&args.tls_cert, &args.tls_key) {
+            server = server.tls_config(tls_config(cert, key, args.tls_client_ca.as_deref())?)?;
+        }
+        let auth = match &args.token_file {
+            Some(path) => TokenAuth::from_file(path)?,
+            None => TokenAuth::default(),
+        };
+        if let Some(metrics_address) = &args.metrics_address {
+            let metrics_address = metrics_address.parse()?;
+            let stats = upf_service.stats.clone();
+            println!("Serving metrics: http://{metrics_address}/metrics");
+            tokio::spawn(async move {
+                if let Err(e) = serve_metrics(metrics_address, stats).await {
+                    eprintln!("Metrics endpoint stopped: {e}");
+                }
+            });
+        }
+        tokio::spawn(upf_service.jobs.clone().evict_expired_periodically());
         println!("Serving: {addr}");
-        Server::builder()
-            .add_service(UnifiedPlanningServer::new(upf_service))
+        server
+            .add_service(UnifiedPlanningServer::with_interceptor(
+                upf_service.clone(),
+                auth.clone(),
+            ))
+            .add_service(PlanningExtensionsServer::with_interceptor(upf_service, auth))
             .serve(addr)
             .await?;
     }
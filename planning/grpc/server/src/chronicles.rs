@@ -1,3 +1,4 @@
+use crate::initial_state::initial_state;
 use anyhow::{anyhow, bail, ensure, Context, Error, Ok};
 use aries::core::{IntCst, Lit, INT_CST_MAX, INT_CST_MIN};
 use aries::model::extensions::Shaped;
@@ -5,11 +6,13 @@ use aries::model::lang::*;
 use aries::model::symbols::SymbolTable;
 use aries::model::types::TypeHierarchy;
 use aries::utils::input::Sym;
-use aries_planning::chronicles::constraints::{Constraint, ConstraintType};
+use aries_planners::solver::Metric;
+use aries_planning::chronicles::constraints::{Constraint, ConstraintType, Table};
 use aries_planning::chronicles::VarType::Reification;
 use aries_planning::chronicles::*;
 use aries_planning::parsing::pddl::TypedSymbol;
-use std::collections::HashMap;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::sync::Arc;
 use unified_planning as up;
@@ -29,7 +32,10 @@ static METHOD_TYPE: &str = "★method★";
 static FLUENT_TYPE: &str = "★fluent★";
 static OBJECT_TYPE: &str = "★object★";
 
-pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chronicles::Problem, Error> {
+/// Converts the problem into chronicles, together with the metric to optimize if the problem has one.
+pub fn problem_to_chronicles(
+    problem: &Problem,
+) -> Result<(aries_planning::chronicles::Problem, Option<Metric>), Error> {
     // Construct the type hierarchy
     let types = {
         // Static types present in any problem
@@ -68,41 +74,26 @@ pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chroni
             let object_type = Sym::from(&obj.r#type);
 
             // declare the object as a new symbol with the given type
-            symbols.push(TypedSymbol {
-                symbol: object_symbol,
-                tpe: Some(object_type),
-            });
+            symbols.push(TypedSymbol::new(object_symbol, object_type));
         }
 
         // record all symbols representing fluents
         for fluent in &problem.fluents {
-            symbols.push(TypedSymbol {
-                symbol: Sym::from(&fluent.name),
-                tpe: Some(FLUENT_TYPE.into()),
-            });
+            symbols.push(TypedSymbol::new(Sym::from(&fluent.name), FLUENT_TYPE));
         }
 
         // actions are symbols as well, add them to the table
         for action in &problem.actions {
-            symbols.push(TypedSymbol {
-                symbol: Sym::from(&action.name),
-                tpe: Some(ACTION_TYPE.into()),
-            });
+            symbols.push(TypedSymbol::new(Sym::from(&action.name), ACTION_TYPE));
         }
 
         if let Some(hierarchy) = &problem.hierarchy {
             for task in &hierarchy.abstract_tasks {
-                symbols.push(TypedSymbol {
-                    symbol: Sym::from(&task.name),
-                    tpe: Some(ABSTRACT_TASK_TYPE.into()),
-                })
+                symbols.push(TypedSymbol::new(Sym::from(&task.name), ABSTRACT_TASK_TYPE))
             }
 
             for method in &hierarchy.methods {
-                symbols.push(TypedSymbol {
-                    symbol: Sym::from(&method.name),
-                    tpe: Some(METHOD_TYPE.into()),
-                })
+                symbols.push(TypedSymbol::new(Sym::from(&method.name), METHOD_TYPE))
             }
         }
     }
@@ -150,7 +141,7 @@ pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chroni
         }
     }
 
-    let mut context = Ctx::new(Arc::new(symbol_table), state_variables);
+    let mut context = Ctx::with_time_scale(Arc::new(symbol_table), state_variables, time_scale(problem)?);
 
     // Initial chronicle construction
     let init_ch = Chronicle {
@@ -175,19 +166,11 @@ pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chroni
         variables: vec![],
     };
 
-    // initial state is converted as a set of effects at the initial time
-    for init_state in &problem.initial_state {
-        let state_var = init_state
-            .fluent
-            .as_ref()
-            .context("Initial state assignment has no valid fluent")?;
-        let value = init_state
-            .value
-            .as_ref()
-            .context("Initial state assignment has no valid value")?;
+    // initial state (completed with the default values of the fluents) is converted as a set of effects at the initial time
+    for (state_var, value) in initial_state(problem)? {
         let init_time = Span::instant(factory.chronicle.start);
 
-        factory.add_effect(init_time, state_var, value, EffectKind::Assign)?;
+        factory.add_effect(init_time, &state_var, &value, EffectKind::Assign)?;
     }
 
     // goals translate as condition at the global end time
@@ -226,9 +209,14 @@ pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chroni
         }
     }
 
+    ensure!(problem.metrics.len() <= 1, "No support for multiple metrics.");
+    let metric = match problem.metrics.first() {
+        Some(metric) => Some(factory.read_metric(metric)?),
+        None => None,
+    };
+
     let init_ch = factory.build_instance(ChronicleOrigin::Original)?;
 
-    ensure!(problem.metrics.len() <= 1, "No support for multiple metrics.");
     let action_costs = problem
         .metrics
         .iter()
@@ -237,11 +225,13 @@ pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chroni
         ActionCosts {
             costs: metric.action_costs.clone(),
             default: metric.default_action_cost.clone(),
+            static_values: static_int_values(problem)?,
         }
     } else {
         ActionCosts {
             costs: HashMap::new(),
             default: None,
+            static_values: HashMap::new(),
         }
     };
 
@@ -275,12 +265,191 @@ pub fn problem_to_chronicles(problem: &Problem) -> Result<aries_planning::chroni
     //     Printer::print_chronicle(&ch.chronicle, &problem.context.model);
     // }
 
-    Ok(problem)
+    Ok((problem, metric))
+}
+
+/// Time scale of the problem: the least common multiple of the default [TIME_SCALE] and of the denominators of all
+/// rational time values of the problem (delays of the timings and durations of the actions), so that they are all
+/// represented exactly instead of being rounded.
+fn time_scale(problem: &Problem) -> Result<IntCst, Error> {
+    let mut timings: Vec<&up::Timing> = Vec::new();
+    let mut intervals: Vec<&up::TimeInterval> = Vec::new();
+    let mut durations: Vec<&Expression> = Vec::new();
+    for action in &problem.actions {
+        timings.extend(action.effects.iter().filter_map(|eff| eff.occurrence_time.as_ref()));
+        intervals.extend(action.conditions.iter().filter_map(|cond| cond.span.as_ref()));
+        if let Some(bounds) = action.duration.as_ref().and_then(|d| d.controllable_in_bounds.as_ref()) {
+            durations.extend(bounds.lower.iter().chain(&bounds.upper));
+        }
+    }
+    timings.extend(
+        problem
+            .timed_effects
+            .iter()
+            .filter_map(|eff| eff.occurrence_time.as_ref()),
+    );
+    intervals.extend(problem.goals.iter().filter_map(|goal| goal.timing.as_ref()));
+    if let Some(hierarchy) = &problem.hierarchy {
+        for method in &hierarchy.methods {
+            intervals.extend(method.conditions.iter().filter_map(|cond| cond.span.as_ref()));
+        }
+    }
+    for interval in intervals {
+        timings.extend(interval.lower.iter().chain(&interval.upper));
+    }
+
+    let delays = timings.iter().filter_map(|timing| timing.delay.as_ref());
+    let durations = durations
+        .iter()
+        .filter_map(|e| match e.atom.as_ref().and_then(|a| a.content.as_ref()) {
+            Some(Content::Real(r)) => Some(r),
+            _ => None,
+        });
+    let mut scale = TIME_SCALE as i64;
+    for value in delays.chain(durations) {
+        ensure!(
+            value.denominator > 0,
+            "Invalid time value {}/{}",
+            value.numerator,
+            value.denominator
+        );
+        scale = lcm(scale, value.denominator)
+            .filter(|&scale| scale <= INT_CST_MAX as i64)
+            .with_context(|| {
+                format!(
+                    "Time scale overflow: the time value {}/{} cannot be represented together with the other time values of the problem",
+                    value.numerator, value.denominator
+                )
+            })?;
+    }
+    Ok(scale as IntCst)
+}
+
+/// Least common multiple of two positive integers, or `None` if it overflows.
+fn lcm(a: i64, b: i64) -> Option<i64> {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a.checked_mul(b / x)
+}
+
+/// Converts the rational `num / denom` into the numerator of a time value whose denominator is `time_scale`,
+/// failing if it cannot be represented exactly.
+fn scaled_time(num: i64, denom: i64, time_scale: IntCst) -> Result<IntCst, Error> {
+    let scale = time_scale as i64;
+    ensure!(
+        denom > 0 && scale % denom == 0,
+        "The time value {num}/{denom} cannot be represented with a time scale of {time_scale}"
+    );
+    num.checked_mul(scale / denom)
+        .filter(|n| n.abs() <= INT_CST_MAX as i64)
+        .map(|n| n as IntCst)
+        .with_context(|| format!("The time value {num}/{denom} overflows with a time scale of {time_scale}"))
 }
 
 struct ActionCosts {
     costs: HashMap<String, Expression>,
     default: Option<Expression>,
+    /// Values of the static integer state variables, used to evaluate the costs that depend on action parameters.
+    static_values: StaticValues,
+}
+
+/// Values of state variables, indexed by the name of the fluent followed by the names of its arguments.
+type StaticValues = HashMap<Vec<String>, IntCst>;
+
+/// Collects the value of all integer state variables in the initial state whose fluent is never modified by an action.
+fn static_int_values(problem: &Problem) -> Result<StaticValues, Error> {
+    let mut modified_fluents = HashSet::new();
+    for action in &problem.actions {
+        for eff in action.effects.iter().filter_map(|eff| eff.effect.as_ref()) {
+            if let Some(fluent) = eff.fluent.as_ref().and_then(|sv| sv.list.first()) {
+                modified_fluents.insert(as_symbol(fluent)?);
+            }
+        }
+    }
+    let mut values = HashMap::new();
+    for assignment in &problem.initial_state {
+        let (Some(sv), Some(value)) = (&assignment.fluent, &assignment.value) else {
+            continue;
+        };
+        let Result::Ok(value) = as_int(value) else {
+            continue; // not an integer state variable
+        };
+        ensure!(!sv.list.is_empty(), "Empty state variable expression");
+        if modified_fluents.contains(as_symbol(&sv.list[0])?) {
+            continue;
+        }
+        let key = sv
+            .list
+            .iter()
+            .map(|e| as_symbol(e).map(|s| s.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        values.insert(key, value);
+    }
+    Ok(values)
+}
+
+/// Evaluates an integer expression, where `parameters` gives the object bound to each parameter of the expression.
+/// State variables are only allowed on static fluents, whose value is given by `static_values`.
+fn evaluate_int(
+    expr: &Expression,
+    parameters: &HashMap<&str, &str>,
+    static_values: &StaticValues,
+) -> Result<IntCst, Error> {
+    match kind(expr)? {
+        ExpressionKind::Constant => as_int(expr),
+        ExpressionKind::StateVariable => {
+            ensure!(!expr.list.is_empty(), "Empty state variable expression");
+            let mut key = Vec::with_capacity(expr.list.len());
+            key.push(as_symbol(&expr.list[0])?.to_string());
+            for arg in &expr.list[1..] {
+                let arg = match kind(arg)? {
+                    ExpressionKind::Parameter => {
+                        let param = as_symbol(arg)?;
+                        *parameters
+                            .get(param)
+                            .with_context(|| format!("Unknown parameter: {param}"))?
+                    }
+                    _ => as_symbol(arg)?,
+                };
+                key.push(arg.to_string());
+            }
+            static_values
+                .get(&key)
+                .copied()
+                .with_context(|| format!("No static integer value for the state variable {key:?}"))
+        }
+        ExpressionKind::FunctionApplication => {
+            ensure!(!expr.list.is_empty(), "Empty function application");
+            let operator = as_function_symbol(&expr.list[0])?;
+            let args = expr.list[1..]
+                .iter()
+                .map(|arg| evaluate_int(arg, parameters, static_values))
+                .collect::<Result<Vec<_>, _>>()?;
+            match (operator, args.as_slice()) {
+                ("up:plus", args) => Ok(args.iter().sum()),
+                ("up:times", args) => Ok(args.iter().product()),
+                ("up:minus", &[a, b]) => Ok(a - b),
+                _ => bail!("Unsupported operator in integer expression: {operator}"),
+            }
+        }
+        kind => bail!("Unsupported expression kind in integer expression: {kind:?}"),
+    }
+}
+
+/// Appends to `out` the name of all parameters appearing in the expression (without duplicates).
+fn parameters_of<'a>(expr: &'a Expression, out: &mut Vec<&'a str>) -> Result<(), Error> {
+    if kind(expr)? == ExpressionKind::Parameter {
+        let param = as_symbol(expr)?;
+        if !out.contains(&param) {
+            out.push(param);
+        }
+    }
+    for e in &expr.list {
+        parameters_of(e, out)?;
+    }
+    Ok(())
 }
 
 fn str_to_symbol(name: &str, symbol_table: &SymbolTable) -> anyhow::Result<SAtom> {
@@ -389,7 +558,7 @@ impl<'a> ChronicleFactory<'a> {
         let tp = self.context.model.new_optional_fvar(
             0,
             INT_CST_MAX,
-            TIME_SCALE,
+            self.context.time_scale(),
             self.chronicle.presence,
             self.container / vartype,
         );
@@ -462,6 +631,15 @@ impl<'a> ChronicleFactory<'a> {
         var
     }
 
+    fn create_int_variable(&mut self, lb: IntCst, ub: IntCst, label: VarType) -> IVar {
+        let var = self
+            .context
+            .model
+            .new_optional_ivar(lb, ub, self.chronicle.presence, self.container / label);
+        self.variables.push(var.into());
+        var
+    }
+
     fn create_bool_variable(&mut self, label: VarType) -> Lit {
         let var = self
             .context
@@ -516,17 +694,93 @@ impl<'a> ChronicleFactory<'a> {
         Ok(())
     }
 
-    fn set_cost(&mut self, cost: &Expression) -> Result<(), Error> {
-        ensure!(kind(cost)? == ExpressionKind::Constant);
-        ensure!(cost.r#type == "up:integer");
-        let cost = match cost.atom.as_ref().unwrap().content.as_ref().unwrap() {
-            Content::Int(i) => *i as IntCst,
-            _ => bail!("Unexpected cost type."),
-        };
-        self.chronicle.cost = Some(cost);
+    /// Sets the cost of the chronicle to the value of the `cost` expression.
+    ///
+    /// If the cost refers to parameters of the chronicle, it is evaluated for each possible instantiation
+    /// of these parameters and the cost of the chronicle is a variable, constrained by a table that associates
+    /// each instantiation to its cost.
+    fn set_cost(&mut self, cost: &Expression, static_values: &StaticValues) -> Result<(), Error> {
+        ensure!(cost.r#type == "up:integer", "Only integer costs are supported.");
+        let mut param_names = Vec::new();
+        parameters_of(cost, &mut param_names)?;
+        if param_names.is_empty() {
+            let cost = evaluate_int(cost, &HashMap::new(), static_values)?;
+            self.chronicle.cost = Some(cost.into());
+            return Ok(());
+        }
+
+        let params = param_names
+            .iter()
+            .map(|&name| match self.parameter(name)? {
+                Atom::Sym(SAtom::Var(var)) => Ok(var),
+                _ => bail!("Parameter `{name}` of the cost expression is not an object."),
+            })
+            .collect::<Result<Vec<SVar>, _>>()?;
+
+        let symbols = self.context.model.get_symbol_table();
+        let mut types: Vec<Type> = params.iter().map(|p| Type::Sym(p.tpe)).collect();
+        types.push(Type::Int);
+        let mut table = Table::new("cost".to_string(), types);
+        let (mut lb, mut ub) = (INT_CST_MAX, INT_CST_MIN);
+        for instantiation in params
+            .iter()
+            .map(|p| symbols.instances_of_type(p.tpe))
+            .multi_cartesian_product()
+        {
+            let binding = param_names
+                .iter()
+                .zip(&instantiation)
+                .map(|(&name, &sym)| (name, symbols.symbol(sym).canonical_str()))
+                .collect();
+            let value = evaluate_int(cost, &binding, static_values)
+                .with_context(|| format!("When evaluating the cost with parameters {binding:?}"))?;
+            let mut line: Vec<DiscreteValue> = instantiation.iter().map(|sym| sym.int_value()).collect();
+            line.push(value);
+            table.push(&line);
+            lb = lb.min(value);
+            ub = ub.max(value);
+        }
+        if lb > ub {
+            // no possible instantiation, the table is empty and the chronicle can never be present
+            (lb, ub) = (0, 0);
+        }
+
+        let cost_var = self.create_int_variable(lb, ub, VarType::Cost);
+        let mut variables: Vec<Atom> = params.iter().map(|&p| Atom::from(p)).collect();
+        variables.push(cost_var.into());
+        self.chronicle.constraints.push(Constraint {
+            variables,
+            tpe: ConstraintType::InTable(Arc::new(table)),
+            value: None,
+        });
+        self.chronicle.cost = Some(cost_var.into());
         Ok(())
     }
 
+    /// Reads the metric of the problem, where expressions on the final state are evaluated at the end of the chronicle.
+    fn read_metric(&mut self, metric: &up::Metric) -> Result<Metric, Error> {
+        let kind = MetricKind::from_i32(metric.kind);
+        let metric = match kind {
+            Some(MetricKind::MinimizeActionCosts) => Metric::ActionCosts,
+            Some(MetricKind::MinimizeSequentialPlanLength) => Metric::PlanLength,
+            Some(MetricKind::MinimizeMakespan) => Metric::Makespan,
+            Some(MetricKind::MinimizeExpressionOnFinalState | MetricKind::MaximizeExpressionOnFinalState) => {
+                let expr = metric.expression.as_ref().context("Missing expression of the metric")?;
+                let value = self
+                    .reify(expr, Some(Span::instant(self.chronicle.end)))
+                    .with_context(|| format!("In metric expression {expr}"))?;
+                let value =
+                    IAtom::try_from(value).map_err(|_| anyhow!("Metric expression {expr} is not an integer"))?;
+                Metric::FinalValue {
+                    value,
+                    maximize: kind == Some(MetricKind::MaximizeExpressionOnFinalState),
+                }
+            }
+            _ => bail!("Unsupported metric kind with ID: {}", metric.kind),
+        };
+        Ok(metric)
+    }
+
     fn add_subtask(&mut self, subtask: &up::Task) -> Result<(), Error> {
         let task_index = self.chronicle.subtasks.len() as u32;
         let start = self.create_timepoint(VarType::TaskStart(task_index));
@@ -580,6 +834,17 @@ impl<'a> ChronicleFactory<'a> {
                 let params = &expr.list[1..];
 
                 match operator {
+                    "up:equals" if value == Atom::TRUE && is_state_variable_equality(params) => {
+                        // equality between a state variable (e.g. an object fluent) and another expression:
+                        // the value of the state variable is directly required to be the one of the other expression
+                        let (sv, other) = if kind(&params[0])? == ExpressionKind::StateVariable {
+                            (&params[0], &params[1])
+                        } else {
+                            (&params[1], &params[0])
+                        };
+                        let other = self.reify(other, span)?;
+                        self.bind_to(sv, other, span)?;
+                    }
                     "up:equals" => {
                         ensure!(params.len() == 2, "`=` operator should have exactly 2 arguments");
                         let params: Vec<Atom> = params
@@ -775,21 +1040,9 @@ impl<'a> ChronicleFactory<'a> {
     }
 
     fn read_timing(&self, timing: &up::Timing) -> Result<FAtom, Error> {
-        let (delay_num, delay_denom) = {
-            let (num, denom) = if let Some(delay) = timing.delay.as_ref() {
-                (delay.numerator, delay.denominator)
-            } else {
-                (0, 1)
-            };
-            let num: IntCst = num
-                .try_into()
-                .context("Only 32 bits integers supported in Rational numbers")?;
-            let denom: IntCst = denom
-                .try_into()
-                .context("Only 32 bits integers supported in Rational numbers")?;
-            ensure!(TIME_SCALE % denom == 0, "Time scale beyond what is supported.");
-            let scale = TIME_SCALE / denom;
-            (num * scale, denom * scale)
+        let delay = match timing.delay.as_ref() {
+            Some(delay) => scaled_time(delay.numerator, delay.denominator, self.context.time_scale())?,
+            None => 0,
         };
         let kind = if let Some(timepoint) = timing.timepoint.as_ref() {
             TimepointKind::from_i32(timepoint.kind).context("Unsupported timepoint kind")?
@@ -803,8 +1056,8 @@ impl<'a> ChronicleFactory<'a> {
             TimepointKind::Start => self.chronicle.start,
             TimepointKind::End => self.chronicle.end,
         };
-        assert_eq!(tp.denom, delay_denom);
-        Ok(FAtom::new(tp.num + delay_num, tp.denom))
+        debug_assert_eq!(tp.denom, self.context.time_scale());
+        Ok(FAtom::new(tp.num + delay, tp.denom))
     }
 
     /// Returns the corresponding start and end timepoints representing the interval.
@@ -835,6 +1088,12 @@ impl<'a> ChronicleFactory<'a> {
     }
 }
 
+/// Returns true if the parameters are those of an equality between exactly one state variable and another expression.
+fn is_state_variable_equality(params: &[Expression]) -> bool {
+    let is_sv = |e: &Expression| kind(e).ok() == Some(ExpressionKind::StateVariable);
+    params.len() == 2 && is_sv(&params[0]) != is_sv(&params[1])
+}
+
 fn as_function_symbol(expr: &Expression) -> Result<&str, Error> {
     ensure!(
         kind(expr)? == ExpressionKind::FunctionSymbol,
@@ -857,14 +1116,15 @@ fn as_symbol(expr: &Expression) -> Result<&str, Error> {
     }
 }
 
-/// If the action has a fixed duration, returns it otherwise returns None
-fn get_fixed_duration(action: &Action) -> Option<IntCst> {
+/// If the action has a fixed duration, returns it (as the numerator of a time value with denominator `time_scale`)
+/// otherwise returns None
+fn get_fixed_duration(action: &Action, time_scale: IntCst) -> Option<IntCst> {
     let duration = action.duration.as_ref()?;
     let ctl = duration.controllable_in_bounds.as_ref()?;
     let min = ctl.lower.as_ref()?;
     let max = ctl.upper.as_ref()?;
     if min == max && !ctl.is_left_open && !ctl.is_right_open {
-        as_int(min).ok()
+        as_time(min, time_scale).ok()
     } else {
         None
     }
@@ -888,22 +1148,26 @@ fn read_action(
     variables.push(prez_var.into());
     let prez = prez_var.true_lit();
 
-    let start = context
-        .model
-        .new_optional_fvar(0, INT_CST_MAX, TIME_SCALE, prez, container / VarType::ChronicleStart);
+    let start = context.model.new_optional_fvar(
+        0,
+        INT_CST_MAX,
+        context.time_scale(),
+        prez,
+        container / VarType::ChronicleStart,
+    );
     variables.push(start.into());
     let start = FAtom::from(start);
 
     let end: FAtom = match action_kind {
         ChronicleKind::Problem | ChronicleKind::Method => unreachable!(),
         ChronicleKind::DurativeAction => {
-            if let Some(dur) = get_fixed_duration(action) {
-                start + dur
+            if let Some(dur) = get_fixed_duration(action, context.time_scale()) {
+                FAtom::new(start.num + dur, start.denom)
             } else {
                 let end = context.model.new_optional_fvar(
                     0,
                     INT_CST_MAX,
-                    TIME_SCALE,
+                    context.time_scale(),
                     prez,
                     container / VarType::ChronicleEnd,
                 );
@@ -1023,27 +1287,28 @@ fn read_action(
     if let Some(duration) = action.duration.as_ref() {
         let start = factory.chronicle.start;
         let end = factory.chronicle.end;
+        let time_scale = factory.context.time_scale();
         if let Some(interval) = duration.controllable_in_bounds.as_ref() {
             if let Some(min) = interval.lower.as_ref() {
-                let min = as_int(min)?;
+                let min = FAtom::new(start.num + as_time(min, time_scale)?, start.denom);
                 if interval.is_left_open {
-                    factory.chronicle.constraints.push(Constraint::lt(start + min, end))
+                    factory.chronicle.constraints.push(Constraint::lt(min, end))
                 } else {
                     factory
                         .chronicle
                         .constraints
-                        .push(Constraint::lt(start + min - FAtom::EPSILON, end))
+                        .push(Constraint::lt(min - FAtom::EPSILON, end))
                 }
             }
             if let Some(max) = interval.upper.as_ref() {
-                let max = as_int(max)?;
+                let max = FAtom::new(start.num + as_time(max, time_scale)?, start.denom);
                 if interval.is_right_open {
-                    factory.chronicle.constraints.push(Constraint::lt(end, start + max))
+                    factory.chronicle.constraints.push(Constraint::lt(end, max))
                 } else {
                     factory
                         .chronicle
                         .constraints
-                        .push(Constraint::lt(end, start + max + FAtom::EPSILON))
+                        .push(Constraint::lt(end, max + FAtom::EPSILON))
                 }
             }
         }
@@ -1051,7 +1316,9 @@ fn read_action(
 
     let cost_expr = costs.costs.get(&action.name).or(costs.default.as_ref());
     if let Some(cost) = cost_expr {
-        factory.set_cost(cost)?;
+        factory
+            .set_cost(cost, &costs.static_values)
+            .with_context(|| format!("In cost of action `{}`", action.name))?;
     }
 
     factory.build_template(action.name.clone())
@@ -1079,18 +1346,26 @@ fn read_method(container: Container, method: &up::Method, context: &mut Ctx) ->
     variables.push(prez_var.into());
     let prez = prez_var.true_lit();
 
-    let start = context
-        .model
-        .new_optional_fvar(0, INT_CST_MAX, TIME_SCALE, prez, container / VarType::ChronicleStart);
+    let start = context.model.new_optional_fvar(
+        0,
+        INT_CST_MAX,
+        context.time_scale(),
+        prez,
+        container / VarType::ChronicleStart,
+    );
     variables.push(start.into());
     let start = FAtom::from(start);
 
     let end: FAtom = if method.subtasks.is_empty() {
         start // no subtasks, the method is instantaneous
     } else {
-        let end = context
-            .model
-            .new_optional_fvar(0, INT_CST_MAX, TIME_SCALE, prez, container / VarType::ChronicleEnd);
+        let end = context.model.new_optional_fvar(
+            0,
+            INT_CST_MAX,
+            context.time_scale(),
+            prez,
+            container / VarType::ChronicleEnd,
+        );
         variables.push(end.into());
         end.into()
     };
@@ -1171,6 +1446,20 @@ fn kind(e: &Expression) -> Result<ExpressionKind, Error> {
     ExpressionKind::from_i32(e.kind).with_context(|| format!("Unknown expression kind id: {}", e.kind))
 }
 
+/// Reads a constant time value (integer or rational), returned as the numerator of a time value whose denominator
+/// is `time_scale`.
+fn as_time(e: &Expression, time_scale: IntCst) -> Result<IntCst, Error> {
+    ensure!(
+        kind(e)? == ExpressionKind::Constant,
+        "Expression is not a constant time value"
+    );
+    match e.atom.as_ref().and_then(|atom| atom.content.as_ref()) {
+        Some(Content::Int(i)) => scaled_time(*i, 1, time_scale),
+        Some(Content::Real(r)) => scaled_time(r.numerator, r.denominator, time_scale),
+        _ => bail!("Expression is not a constant time value"),
+    }
+}
+
 fn as_int(e: &Expression) -> Result<i32, Error> {
     if kind(e)? == ExpressionKind::Constant && e.r#type.starts_with("up:integer") {
         match e.atom.as_ref().unwrap().content.as_ref().unwrap() {
@@ -0,0 +1,130 @@
+//! Rejection of the problems that use features the engine does not support.
+//!
+//! Rather than failing somewhere in the conversion of the problem with a generic error, each unsupported feature
+//! is reported together with the elements of the problem (actions, fluents, metrics, ...) in which it occurs.
+use std::fmt::{Display, Formatter};
+use unified_planning as up;
+use up::atom::Content;
+use up::effect_expression::EffectKind;
+use up::metric::MetricKind;
+use up::{EffectExpression, Expression, Problem};
+
+/// Occurrence of a feature that is not supported by the engine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedFeature {
+    pub feature: up::Feature,
+    /// Elements of the problem in which the feature occurs (e.g. "action `move`").
+    /// Empty if the feature is only declared in the features of the problem.
+    pub locations: Vec<String>,
+}
+
+impl Display for UnsupportedFeature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unsupported feature {}", self.feature.as_str_name())?;
+        if self.locations.is_empty() {
+            write!(f, " (declared by the problem)")
+        } else {
+            write!(f, " in {}", self.locations.join(", "))
+        }
+    }
+}
+
+/// Error reporting all unsupported features of a problem.
+#[derive(Clone, Debug)]
+pub struct UnsupportedFeatures(pub Vec<UnsupportedFeature>);
+
+impl Display for UnsupportedFeatures {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, feature) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{feature}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsupportedFeatures {}
+
+/// Features that are never supported by the engine.
+const UNSUPPORTED: [up::Feature; 6] = [
+    up::Feature::SimulatedEffects,
+    up::Feature::ContinuousNumbers,
+    up::Feature::ConditionalEffects,
+    up::Feature::IncreaseEffects,
+    up::Feature::DecreaseEffects,
+    up::Feature::Oversubscription,
+];
+
+/// Checks that the problem does not use any unsupported feature, returning all the ones it uses otherwise.
+///
+/// A feature is considered used if it is found in the problem, regardless of the features declared by the problem
+/// (that might be over-approximated, e.g., for a conditional effect whose condition is always true).
+/// Simulated effects do not appear in the problem message and are thus only detected from the declared features.
+pub fn check_features(problem: &Problem) -> Result<(), UnsupportedFeatures> {
+    let mut unsupported = Vec::new();
+    for feature in UNSUPPORTED {
+        let locations = locations(problem, feature);
+        let declared = problem.features.contains(&(feature as i32));
+        if !locations.is_empty() || (feature == up::Feature::SimulatedEffects && declared) {
+            unsupported.push(UnsupportedFeature { feature, locations });
+        }
+    }
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(UnsupportedFeatures(unsupported))
+    }
+}
+
+/// Returns the elements of the problem in which the feature occurs.
+fn locations(problem: &Problem, feature: up::Feature) -> Vec<String> {
+    let mut locations = Vec::new();
+    let effect_uses = |eff: &EffectExpression| match feature {
+        up::Feature::ConditionalEffects => eff.condition.as_ref().map_or(false, |c| !is_true(c)),
+        up::Feature::IncreaseEffects => eff.kind == EffectKind::Increase as i32,
+        up::Feature::DecreaseEffects => eff.kind == EffectKind::Decrease as i32,
+        _ => false,
+    };
+    let is_real = |tpe: &str| feature == up::Feature::ContinuousNumbers && tpe.starts_with("up:real");
+
+    for fluent in &problem.fluents {
+        if is_real(&fluent.value_type) || fluent.parameters.iter().any(|p| is_real(&p.r#type)) {
+            locations.push(format!("fluent `{}`", fluent.name));
+        }
+    }
+    for action in &problem.actions {
+        let uses_effect = action
+            .effects
+            .iter()
+            .filter_map(|eff| eff.effect.as_ref())
+            .any(effect_uses);
+        if uses_effect || action.parameters.iter().any(|p| is_real(&p.r#type)) {
+            locations.push(format!("action `{}`", action.name));
+        }
+    }
+    for (i, timed_effect) in problem.timed_effects.iter().enumerate() {
+        if timed_effect.effect.as_ref().map_or(false, effect_uses) {
+            locations.push(format!("timed effect #{i}"));
+        }
+    }
+    for (i, metric) in problem.metrics.iter().enumerate() {
+        let uses = matches!(
+            (feature, MetricKind::from_i32(metric.kind)),
+            (up::Feature::Oversubscription, Some(MetricKind::Oversubscription))
+        );
+        if uses {
+            locations.push(format!("metric #{i}"));
+        }
+    }
+    locations
+}
+
+/// Returns true if the expression is the `true` constant.
+fn is_true(expr: &Expression) -> bool {
+    matches!(
+        expr.atom.as_ref().and_then(|a| a.content.as_ref()),
+        Some(Content::Boolean(true))
+    )
+}
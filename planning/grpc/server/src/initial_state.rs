@@ -0,0 +1,176 @@
+//! Completion of the initial state of a problem with the default values of its fluents.
+//!
+//! A fluent with a `default_value` implicitly assigns this value to all its groundings that are not explicitly
+//! assigned in the initial state (e.g. `false` for a predicate, mimicking the closed-world assumption).
+//! The groundings are enumerated lazily, so that the (potentially huge) set of ground state variables is never
+//! materialized at once.
+use anyhow::{bail, Context, Error};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::rc::Rc;
+use unified_planning as up;
+use up::atom::Content;
+use up::{Expression, ExpressionKind, Fluent, Problem};
+
+/// A ground state variable, identified by the name of its fluent and the names of its arguments.
+type Grounding = (String, Vec<String>);
+
+/// Returns all assignments `(state variable, value)` of the initial state of the problem: the explicit ones,
+/// followed by the default value of each fluent for all its groundings that are not explicitly assigned.
+///
+/// Fails if a state variable is explicitly assigned two different values, or if the groundings of a fluent with
+/// a default value cannot be enumerated.
+pub fn initial_state(problem: &Problem) -> Result<impl Iterator<Item = (Expression, Expression)> + '_, Error> {
+    let mut explicit: Vec<(&Expression, &Expression)> = Vec::with_capacity(problem.initial_state.len());
+    let mut assigned: HashMap<Grounding, &Expression> = HashMap::with_capacity(problem.initial_state.len());
+    for assignment in &problem.initial_state {
+        let state_var = assignment
+            .fluent
+            .as_ref()
+            .context("Initial state assignment has no valid fluent")?;
+        let value = assignment
+            .value
+            .as_ref()
+            .context("Initial state assignment has no valid value")?;
+        let grounding =
+            grounding_of(state_var).with_context(|| format!("In initial state assignment of {state_var}"))?;
+        match assigned.get(&grounding) {
+            Some(&previous) if !same_value(previous, value) => {
+                bail!("Conflicting initial values for {state_var}: {previous} and {value}")
+            }
+            Some(_) => {} // duplicated assignment, ignore
+            None => {
+                assigned.insert(grounding, value);
+                explicit.push((state_var, value));
+            }
+        }
+    }
+
+    // check upfront that the groundings of all fluents with a default value can be enumerated
+    let mut defaults = Vec::new();
+    for fluent in &problem.fluents {
+        if let Some(default) = &fluent.default_value {
+            let domains = fluent
+                .parameters
+                .iter()
+                .map(|param| instances(problem, &param.r#type))
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("Unable to complete the default values of fluent `{}`", fluent.name))?;
+            defaults.push((fluent, default, domains));
+        }
+    }
+
+    let assigned = Rc::new(assigned);
+    let explicit = explicit.into_iter().map(|(sv, value)| (sv.clone(), value.clone()));
+    let completed = defaults.into_iter().flat_map(move |(fluent, default, domains)| {
+        // the product of no parameter domains is empty, while a fluent without parameters has a single grounding
+        let groundings: Box<dyn Iterator<Item = Vec<Expression>>> = if domains.is_empty() {
+            Box::new(std::iter::once(Vec::new()))
+        } else {
+            Box::new(domains.into_iter().multi_cartesian_product())
+        };
+        let assigned = assigned.clone();
+        groundings
+            .filter(move |args| {
+                let grounding = (
+                    fluent.name.clone(),
+                    args.iter()
+                        .map(|arg| atom_name(arg).expect("non-constant argument"))
+                        .collect(),
+                );
+                !assigned.contains_key(&grounding)
+            })
+            .map(move |args| (state_variable(fluent, args), default.clone()))
+    });
+    Ok(explicit.chain(completed))
+}
+
+/// Returns the constants of the given type: the objects of this type or one of its subtypes, or the two booleans.
+fn instances(problem: &Problem, tpe: &str) -> Result<Vec<Expression>, Error> {
+    if tpe == "up:bool" {
+        return Ok([true, false].map(|b| constant(Content::Boolean(b), tpe)).to_vec());
+    }
+    if tpe.starts_with("up:") {
+        bail!("Cannot enumerate the values of type `{tpe}`");
+    }
+    let parent_of: HashMap<&str, &str> = problem
+        .types
+        .iter()
+        .map(|t| (t.type_name.as_str(), t.parent_type.as_str()))
+        .collect();
+    let is_subtype = |t: &str| {
+        let mut t = t;
+        loop {
+            if t == tpe {
+                return true;
+            }
+            match parent_of.get(t) {
+                Some(&parent) if !parent.is_empty() => t = parent,
+                _ => return false,
+            }
+        }
+    };
+    Ok(problem
+        .objects
+        .iter()
+        .filter(|obj| is_subtype(&obj.r#type))
+        .map(|obj| constant(Content::Symbol(obj.name.clone()), &obj.r#type))
+        .collect())
+}
+
+fn constant(content: Content, tpe: &str) -> Expression {
+    Expression {
+        atom: Some(up::Atom { content: Some(content) }),
+        list: vec![],
+        r#type: tpe.to_string(),
+        kind: ExpressionKind::Constant as i32,
+    }
+}
+
+/// Builds the state variable expression applying the fluent to the given arguments.
+fn state_variable(fluent: &Fluent, args: Vec<Expression>) -> Expression {
+    let mut list = Vec::with_capacity(1 + args.len());
+    list.push(Expression {
+        atom: Some(up::Atom {
+            content: Some(Content::Symbol(fluent.name.clone())),
+        }),
+        list: vec![],
+        r#type: "".to_string(),
+        kind: ExpressionKind::FluentSymbol as i32,
+    });
+    list.extend(args);
+    Expression {
+        atom: None,
+        list,
+        r#type: fluent.value_type.clone(),
+        kind: ExpressionKind::StateVariable as i32,
+    }
+}
+
+/// Returns the grounding of a state variable whose arguments are all constants.
+fn grounding_of(state_var: &Expression) -> Result<Grounding, Error> {
+    let (fluent, args) = state_var
+        .list
+        .split_first()
+        .context("Empty state variable expression")?;
+    let args = args.iter().map(atom_name).collect::<Result<_, _>>()?;
+    Ok((atom_name(fluent)?, args))
+}
+
+fn atom_name(expr: &Expression) -> Result<String, Error> {
+    match expr.atom.as_ref().and_then(|atom| atom.content.as_ref()) {
+        Some(Content::Symbol(s)) => Ok(s.clone()),
+        Some(Content::Int(i)) => Ok(i.to_string()),
+        Some(Content::Boolean(b)) => Ok(b.to_string()),
+        Some(Content::Real(r)) => Ok(format!("{}/{}", r.numerator, r.denominator)),
+        None => bail!("Expected a constant but got: {expr}"),
+    }
+}
+
+/// Returns true if the two expressions denote the same value, ignoring their declared types.
+fn same_value(a: &Expression, b: &Expression) -> bool {
+    match (&a.atom, &b.atom) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
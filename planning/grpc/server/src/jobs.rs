@@ -0,0 +1,206 @@
+//! Plan requests solved as jobs, whose solving is not tied to the connection of the client that submitted them.
+//!
+//! All results produced by a job are recorded, so that a client can attach to the job at any time (e.g. after its
+//! connection dropped in the middle of a long optimization) and receive the results it has not seen yet.
+//! Jobs are forgotten some time after they are over (see [Jobs::new] and [Jobs::evict_expired_periodically]).
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Status;
+use unified_planning::aries::{job_status, JobStatus};
+use unified_planning::PlanGenerationResult;
+
+/// Results of a job, in the order in which they were produced.
+#[derive(Default)]
+struct JobResults {
+    /// Intermediate results of the job, followed by its final result once it is over.
+    results: Vec<PlanGenerationResult>,
+    /// Time at which the final result was recorded.
+    over_since: Option<Instant>,
+    cancelled: bool,
+}
+
+/// A plan request that is being solved, or whose solving is over.
+pub struct Job {
+    id: String,
+    results: Mutex<JobResults>,
+    /// Notifies the streams attached to the job of the number of results recorded so far.
+    updates: watch::Sender<usize>,
+    /// Flag raised on cancellation, to be given to the planner (see `PlannerConfig::interrupt`).
+    interrupt: Arc<AtomicBool>,
+}
+
+impl Job {
+    fn new(id: String) -> Self {
+        Job {
+            id,
+            results: Default::default(),
+            updates: watch::channel(0).0,
+            interrupt: Default::default(),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Flag that is raised when the job is cancelled.
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        self.interrupt.clone()
+    }
+
+    /// Called when the solving of the job starts, which may be delayed until the engine is available.
+    /// Lowers the interrupt flag, so that the solver does not inherit a stale interruption, unless the job was
+    /// cancelled before it started, in which case it should not be solved and false is returned.
+    pub fn start(&self) -> bool {
+        let results = self.results.lock().unwrap();
+        if results.cancelled {
+            return false;
+        }
+        self.interrupt.store(false, Ordering::Relaxed);
+        true
+    }
+
+    /// Records an intermediate result of the job.
+    pub fn push_result(&self, result: PlanGenerationResult) {
+        let mut results = self.results.lock().unwrap();
+        debug_assert!(results.over_since.is_none(), "Result of a job that is over");
+        results.results.push(result);
+        self.updates.send_replace(results.results.len());
+    }
+
+    /// Records the final result of the job, after which it is over.
+    pub fn finish(&self, result: PlanGenerationResult) {
+        let mut results = self.results.lock().unwrap();
+        results.results.push(result);
+        results.over_since = Some(Instant::now());
+        self.updates.send_replace(results.results.len());
+    }
+
+    /// Requests the planner to stop, unless the job is already over.
+    pub fn cancel(&self) {
+        let mut results = self.results.lock().unwrap();
+        if results.over_since.is_none() {
+            results.cancelled = true;
+            self.interrupt.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn status(&self) -> JobStatus {
+        let results = self.results.lock().unwrap();
+        let state = if results.cancelled {
+            job_status::State::Cancelled
+        } else if results.over_since.is_some() {
+            job_status::State::Completed
+        } else {
+            job_status::State::Running
+        };
+        JobStatus {
+            job_id: self.id.clone(),
+            state: state as i32,
+            num_results: results.results.len() as u64,
+            result: results.over_since.and(results.results.last().cloned()),
+        }
+    }
+
+    /// Returns the results of the job starting from the given index, and whether the job is over.
+    fn results_from(&self, first: usize) -> (Vec<PlanGenerationResult>, bool) {
+        let results = self.results.lock().unwrap();
+        let from = first.min(results.results.len());
+        (results.results[from..].to_vec(), results.over_since.is_some())
+    }
+
+    /// Returns a stream of the results of the job, starting from the one of index `first_result`, that ends after
+    /// the final result of the job. Dropping the stream does not affect the job.
+    pub fn attach(self: &Arc<Self>, first_result: usize) -> ReceiverStream<Result<PlanGenerationResult, Status>> {
+        let (tx, rx) = mpsc::channel(32);
+        let job = self.clone();
+        // subscribe before reading the results, so that no later result is missed
+        let mut updates = self.updates.subscribe();
+        tokio::spawn(async move {
+            let mut next = first_result;
+            loop {
+                let (results, over) = job.results_from(next);
+                next += results.len();
+                for result in results {
+                    if tx.send(Ok(result)).await.is_err() {
+                        return; // the client detached
+                    }
+                }
+                if over {
+                    return;
+                }
+                tokio::select! {
+                    _ = updates.changed() => {}
+                    _ = tx.closed() => return,
+                }
+            }
+        });
+        ReceiverStream::new(rx)
+    }
+}
+
+/// Jobs known to the server, identified by their ID.
+pub struct Jobs {
+    jobs: Mutex<HashMap<String, Arc<Job>>>,
+    /// Time during which a job remains available after it is over.
+    retention: Duration,
+}
+
+impl Jobs {
+    /// Registry where each job is forgotten once it has been over for the `retention` time.
+    pub fn new(retention: Duration) -> Self {
+        Jobs {
+            jobs: Default::default(),
+            retention,
+        }
+    }
+
+    /// Registers a new job, under a random ID so that a client cannot guess the IDs of the jobs of other clients.
+    pub fn create(&self) -> Arc<Job> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let id = loop {
+            let id = format!("{:032x}", rand::random::<u128>());
+            if !jobs.contains_key(&id) {
+                break id;
+            }
+        };
+        let job = Arc::new(Job::new(id.clone()));
+        jobs.insert(id, job.clone());
+        job
+    }
+
+    /// Forgets the jobs whose retention time has elapsed.
+    pub fn evict_expired(&self) {
+        self.jobs.lock().unwrap().retain(|_, job| {
+            let over_since = job.results.lock().unwrap().over_since;
+            over_since.map_or(true, |time| time.elapsed() < self.retention)
+        });
+    }
+
+    /// Forgets the expired jobs at regular intervals (at most a minute), so that they do not accumulate
+    /// on an idle server. Never returns.
+    pub async fn evict_expired_periodically(self: Arc<Self>) {
+        let period = self.retention.clamp(Duration::from_secs(1), Duration::from_secs(60));
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            self.evict_expired();
+        }
+    }
+
+    /// Returns the job of the given ID, if it was not forgotten.
+    pub fn get(&self, id: &str) -> Option<Arc<Job>> {
+        self.jobs.lock().unwrap().get(id).cloned()
+    }
+}
+
+impl Default for Jobs {
+    /// Registry retaining the jobs for one hour after they are over.
+    fn default() -> Self {
+        Jobs::new(Duration::from_secs(3600))
+    }
+}
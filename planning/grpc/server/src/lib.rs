@@ -2,4 +2,10 @@
 // Use of this source code is governed by a BSD-style
 // license that can be found in the LICENSE file.
 pub mod chronicles;
+pub mod features;
+pub mod initial_state;
+pub mod jobs;
+pub mod metrics;
+pub mod security;
 pub mod serialize;
+pub mod stats;
@@ -0,0 +1,63 @@
+//! Features of the problem computed by the engine (heuristic estimates, structure of the problem) that are reported in the
+//! `metrics` of the plan generation results, allowing benchmarking scripts to correlate the behavior of the engine with them.
+use aries_planning::chronicles::analysis::{causal_graph_decomposition, goal_orders};
+use aries_planning::chronicles::Problem;
+use aries_planning::classical::heuristics::{hadd, landmarks, LiteralCost};
+use aries_planning::classical::{from_chronicles, grounded_problem, LiftedProblem};
+use std::collections::HashMap;
+
+/// Number of ground operators above which the heuristic estimates are not computed, as grounding would be too expensive.
+const MAX_GROUND_OPERATORS: u64 = 100_000;
+
+/// Computes the metrics of the problem:
+///
+///  - `aries_causal_graph_components`: number of weakly connected components of the causal graph
+///  - `aries_causal_graph_fluents`: number of state functions appearing in the causal graph
+///  - `aries_goal_orders`: number of reasonable orders between the goals
+///  - `aries_relaxed_plan_cost`: h_add estimate of the cost of reaching the goals from the initial state
+///  - `aries_landmarks`: number of fact landmarks of the delete relaxation
+///
+/// The last two are only available for classical problems of reasonable size.
+pub fn problem_metrics(pb: &Problem) -> HashMap<String, String> {
+    let mut metrics = HashMap::new();
+    let causal_graph = causal_graph_decomposition(pb);
+    let num_fluents: usize = (0..causal_graph.num_components())
+        .map(|i| causal_graph.component(i).len())
+        .sum();
+    metrics.insert(
+        "aries_causal_graph_components".to_string(),
+        causal_graph.num_components().to_string(),
+    );
+    metrics.insert("aries_causal_graph_fluents".to_string(), num_fluents.to_string());
+    metrics.insert("aries_goal_orders".to_string(), goal_orders(pb).len().to_string());
+
+    // heuristic estimates, on the grounded classical problem
+    let Ok(lifted) = from_chronicles(pb) else {
+        return metrics; // not a classical problem
+    };
+    if num_ground_operators(&lifted) > MAX_GROUND_OPERATORS {
+        return metrics;
+    }
+    let Ok(grounded) = grounded_problem(&lifted) else {
+        return metrics;
+    };
+    let state = &grounded.initial_state;
+    let cost = hadd(state, &grounded.operators).conjunction_cost(&grounded.goals);
+    metrics.insert("aries_relaxed_plan_cost".to_string(), cost.to_string());
+    let num_landmarks = landmarks(state, &grounded.operators, &grounded.goals).len();
+    metrics.insert("aries_landmarks".to_string(), num_landmarks.to_string());
+    metrics
+}
+
+/// Number of operators that grounding the problem would produce.
+fn num_ground_operators(lifted: &LiftedProblem) -> u64 {
+    lifted
+        .actions
+        .iter()
+        .map(|action| {
+            action.params.iter().fold(1u64, |n, (tpe, _)| {
+                n.saturating_mul(lifted.world.table.instances_of_type(*tpe).size() as u64)
+            })
+        })
+        .fold(0, u64::saturating_add)
+}
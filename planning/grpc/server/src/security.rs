@@ -0,0 +1,64 @@
+//! Securing the access to the server, for deployments where it is exposed on a shared network:
+//! TLS encryption of the connections and authentication of the clients with a shared token.
+use anyhow::{Context, Error};
+use std::path::Path;
+use tonic::service::Interceptor;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+use tonic::{Request, Status};
+
+/// Builds the TLS configuration of the server from PEM files containing its certificate and private key.
+///
+/// If the certificate of a certificate authority is given, clients must authenticate with a certificate signed by it.
+pub fn tls_config(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<ServerTlsConfig, Error> {
+    let read = |path: &Path| std::fs::read(path).with_context(|| format!("Unable to read {}", path.display()));
+    let mut config = ServerTlsConfig::new().identity(Identity::from_pem(read(cert)?, read(key)?));
+    if let Some(client_ca) = client_ca {
+        config = config.client_ca_root(Certificate::from_pem(read(client_ca)?));
+    }
+    Ok(config)
+}
+
+/// Interceptor that rejects the requests that do not carry the expected token in an `authorization: Bearer <token>`
+/// metadata entry. If no token is expected, all requests are accepted.
+#[derive(Clone, Default)]
+pub struct TokenAuth {
+    token: Option<String>,
+}
+
+impl TokenAuth {
+    pub fn new(token: Option<String>) -> Self {
+        TokenAuth { token }
+    }
+
+    /// Reads the expected token from a file, ignoring any leading or trailing whitespace.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let token = std::fs::read_to_string(path).with_context(|| format!("Unable to read {}", path.display()))?;
+        let token = token.trim();
+        anyhow::ensure!(!token.is_empty(), "The token file {} is empty", path.display());
+        Ok(TokenAuth::new(Some(token.to_string())))
+    }
+}
+
+impl Interceptor for TokenAuth {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(expected) = &self.token else {
+            return Ok(request);
+        };
+        let provided = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match provided {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(request),
+            Some(_) => Err(Status::unauthenticated("Invalid token")),
+            None => Err(Status::unauthenticated("Missing token")),
+        }
+    }
+}
+
+/// Compares two byte strings in a time that does not depend on the position of their first difference,
+/// so that the expected token cannot be guessed from the response times of the server.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
@@ -0,0 +1,172 @@
+//! Counters on the activity of the server, exposed in the Prometheus text format on an optional HTTP endpoint
+//! so that a deployed planning service can be monitored.
+//!
+//! The endpoint answers `GET /metrics` requests with the current value of the counters, e.g.
+//!
+//! ```text
+//! # HELP aries_plan_requests_total Number of plan requests served.
+//! # TYPE aries_plan_requests_total counter
+//! aries_plan_requests_total 12
+//! ```
+use aries::solver::parallel::signals::Progress;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Counters on the requests served by the server, shared by all of them.
+#[derive(Default, Debug)]
+pub struct ServiceStats {
+    plan_requests: AtomicU64,
+    validation_requests: AtomicU64,
+    /// Number of problems given to the planner, and total time spent solving them (in microseconds).
+    num_solved: AtomicU64,
+    solve_time_micros: AtomicU64,
+    conflicts: AtomicU64,
+}
+
+impl ServiceStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a plan request, counted once per problem in batch requests.
+    pub fn record_plan_request(&self) {
+        self.plan_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_validation_request(&self) {
+        self.validation_requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a run of the planner that took the given time.
+    pub fn record_solve(&self, solve_time: Duration) {
+        self.num_solved.fetch_add(1, Ordering::Relaxed);
+        self.solve_time_micros
+            .fetch_add(solve_time.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns a callback counting the conflicts announced by the progress reports of the solvers of one problem.
+    ///
+    /// The counts of a report are cumulative since its solver was started: only the increase since the previous report
+    /// of the same worker is recorded, unless the count decreased, meaning that the worker started a new solver
+    /// (e.g. for a larger depth). Conflicts occurring after the last report of a solver are not counted.
+    pub fn conflicts_recorder(self: &Arc<Self>) -> impl Fn(usize, &Progress) + Clone + Send + Sync + 'static {
+        let stats = self.clone();
+        let last_counts: Arc<Mutex<HashMap<usize, u64>>> = Default::default();
+        move |worker: usize, progress: &Progress| {
+            let mut last_counts = last_counts.lock().unwrap();
+            let last = last_counts.insert(worker, progress.num_conflicts).unwrap_or(0);
+            let new_conflicts = if progress.num_conflicts >= last {
+                progress.num_conflicts - last
+            } else {
+                progress.num_conflicts
+            };
+            stats.conflicts.fetch_add(new_conflicts, Ordering::Relaxed);
+        }
+    }
+
+    /// Current value of the counters, in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let mut out = String::new();
+        write_metric(
+            &mut out,
+            "aries_plan_requests_total",
+            "counter",
+            "Number of plan requests served.",
+            load(&self.plan_requests),
+        );
+        write_metric(
+            &mut out,
+            "aries_validation_requests_total",
+            "counter",
+            "Number of plan validation requests served.",
+            load(&self.validation_requests),
+        );
+        // a summary, whose sum divided by its count is the average solving time
+        let solve_time = Duration::from_micros(load(&self.solve_time_micros)).as_secs_f64();
+        writeln!(out, "# HELP aries_solve_duration_seconds Time spent solving problems.").unwrap();
+        writeln!(out, "# TYPE aries_solve_duration_seconds summary").unwrap();
+        writeln!(out, "aries_solve_duration_seconds_sum {solve_time}").unwrap();
+        writeln!(out, "aries_solve_duration_seconds_count {}", load(&self.num_solved)).unwrap();
+        write_metric(
+            &mut out,
+            "aries_conflicts_total",
+            "counter",
+            "Number of conflicts encountered by the solvers.",
+            load(&self.conflicts),
+        );
+        if let Some(memory) = memory_high_water_mark() {
+            write_metric(
+                &mut out,
+                "aries_memory_high_water_mark_bytes",
+                "gauge",
+                "Peak resident memory of the process.",
+                memory,
+            );
+        }
+        out
+    }
+}
+
+/// Writes a metric with a single value, preceded by its description and type.
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} {kind}").unwrap();
+    writeln!(out, "{name} {value}").unwrap();
+}
+
+/// Peak resident memory of the process (in bytes), read from `/proc/self/status`.
+/// Returns `None` on platforms without this file.
+pub fn memory_high_water_mark() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmHWM:"))?;
+    // e.g. `VmHWM:     12345 kB`
+    let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Serves the counters on `GET /metrics` requests to the given address, until an error occurs on the listening socket.
+pub async fn serve_metrics(address: SocketAddr, stats: Arc<ServiceStats>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let stats = stats.clone();
+        tokio::spawn(async move {
+            if let Err(e) = answer_metrics_request(stream, &stats).await {
+                eprintln!("Could not answer a metrics request: {e}");
+            }
+        });
+    }
+}
+
+/// Reads an HTTP request on the stream and answers it, before closing the connection.
+async fn answer_metrics_request(mut stream: TcpStream, stats: &ServiceStats) -> std::io::Result<()> {
+    // only the request line is of interest, the headers are ignored
+    let mut buffer = [0u8; 1024];
+    let mut len = 0;
+    while len < buffer.len() && !buffer[..len].windows(2).any(|w| w == b"\r\n") {
+        let read = stream.read(&mut buffer[len..]).await?;
+        if read == 0 {
+            break;
+        }
+        len += read;
+    }
+    let request = String::from_utf8_lossy(&buffer[..len]);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let (status, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", stats.to_prometheus()),
+        _ => ("404 Not Found", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,107 @@
+use anyhow::Result;
+use aries_planners::solver::{solve, PlannerConfig, SolverResult};
+use aries_planning::generator::{BruteForceResult, GeneratorParams, RandomProblem};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+/// Differential testing of the planner on small random problems.
+///
+/// For each seed, a random problem is generated and solved by the planner. The result is compared with
+/// the one of a brute force search on the problem. On disagreement, the domain and problem are written
+/// to the output directory.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "fuzz", rename_all = "kebab-case")]
+struct Opt {
+    /// Number of problems to generate.
+    #[structopt(long, short, default_value = "100")]
+    num: u64,
+    /// Seed of the first problem, the following ones have consecutive seeds.
+    #[structopt(long, default_value = "0")]
+    first_seed: u64,
+    /// If set, the generated problems are hierarchical.
+    #[structopt(long)]
+    htn: bool,
+    /// Maximum depth explored by the planner on problems without plans (ignored for hierarchical problems).
+    #[structopt(long, default_value = "3")]
+    max_depth: u32,
+    /// Maximum number of search nodes explored by the brute force search.
+    #[structopt(long, default_value = "100000")]
+    max_states: usize,
+    /// Time allowed to the planner on each problem, in seconds.
+    #[structopt(long, default_value = "10")]
+    timeout: u64,
+    /// Directory where the problems on which the planner is wrong are written.
+    #[structopt(long, short, default_value = ".")]
+    output: PathBuf,
+}
+
+fn main() -> Result<()> {
+    let opt: Opt = Opt::from_args();
+    let params = GeneratorParams {
+        hierarchical: opt.htn,
+        ..Default::default()
+    };
+
+    let mut failures = Vec::new();
+    let mut skipped = 0;
+    for seed in opt.first_seed..opt.first_seed + opt.num {
+        let pb = RandomProblem::generate(seed, &params);
+        let expected = pb.brute_force(opt.max_states);
+        let (min_depth, max_depth) = match expected {
+            BruteForceResult::Unknown => {
+                skipped += 1;
+                continue;
+            }
+            // non-recursive hierarchy: go directly to the full depth
+            _ if opt.htn => (u32::MAX, u32::MAX),
+            BruteForceResult::Plan(length) => (0, length as u32),
+            BruteForceResult::NoPlan => (0, opt.max_depth),
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(opt.timeout);
+        let result = pb.to_chronicles().and_then(|spec| {
+            solve(
+                spec,
+                min_depth,
+                max_depth,
+                &[],
+                None,
+                opt.htn,
+                &PlannerConfig::default(),
+                |_, _| {},
+                |_, _| {},
+                |_, _| {},
+                Some(deadline),
+            )
+        });
+        let error = match (&result, expected) {
+            (Ok(SolverResult::Sol(_)), BruteForceResult::Plan(_))
+            | (Ok(SolverResult::Unsat), BruteForceResult::NoPlan) => continue,
+            (Ok(SolverResult::Timeout(_)), _) => {
+                skipped += 1;
+                continue;
+            }
+            (Ok(SolverResult::Sol(_)), _) => "found a plan for a problem without any".to_string(),
+            (Ok(SolverResult::Unsat), _) => format!("found no plan but the brute force search found {expected:?}"),
+            (Err(e), _) => format!("failed with: {e:?}"),
+        };
+        let domain_file = opt.output.join(format!("rnd-{seed}.dom.pddl"));
+        let problem_file = opt.output.join(format!("rnd-{seed}.pb.pddl"));
+        std::fs::write(&domain_file, pb.domain())?;
+        std::fs::write(&problem_file, pb.problem())?;
+        failures.push(format!("seed {seed}: planner {error} ({})", problem_file.display()));
+    }
+
+    println!("\n===== Differential testing =====");
+    println!(
+        "Problems: {}    skipped: {skipped}    failures: {}",
+        opt.num,
+        failures.len()
+    );
+    for failure in &failures {
+        println!("  {failure}");
+    }
+    anyhow::ensure!(failures.is_empty(), "The planner disagrees with the brute force search");
+    Ok(())
+}
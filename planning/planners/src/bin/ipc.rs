@@ -0,0 +1,186 @@
+//! Entry point following the conventions of the International Planning Competition (IPC).
+//!
+//! The planner is invoked as `ipc DOMAIN PROBLEM [PLAN_FILE]`. Each time a better plan is found, it is written
+//! to a new file `PLAN_FILE.1`, `PLAN_FILE.2`, ... so that the last file always contains the best plan found so far,
+//! even if the planner is killed when reaching the time or memory limit.
+
+use anyhow::{Context, Result};
+use aries::model::extensions::SavedAssignment;
+use aries::utils::input::Input;
+use aries_planners::fmt::{format_hddl_plan, format_partial_assignment, format_pddl_plan};
+use aries_planners::solver::{solve, Metric, PlannerConfig, SolverResult, Strat};
+use aries_planning::chronicles::analysis::hierarchical_is_non_recursive;
+use aries_planning::chronicles::FiniteProblem;
+use aries_planning::parsing::pddl::{parse_pddl_domain, parse_pddl_problem, PddlFeature};
+use aries_planning::parsing::pddl_to_chronicles;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+
+/// An automated planner for PDDL and HDDL problems, following the IPC conventions for its inputs and outputs.
+#[derive(Debug, Clone, StructOpt)]
+#[structopt(name = "ipc", rename_all = "kebab-case")]
+pub struct Opt {
+    /// Path to the domain file.
+    domain: PathBuf,
+    /// Path to the problem file.
+    problem: PathBuf,
+    /// Base name of the plan files. The i-th plan found is written to `PLAN_FILE.i`.
+    #[structopt(default_value = "plan")]
+    plan_file: PathBuf,
+    /// Time limit (in seconds) after which the planner stops, leaving its best plan in the last plan file.
+    #[structopt(long, env = "IPC_TIME_LIMIT")]
+    time_limit: Option<f64>,
+    /// Memory limit (in MB). When the resident memory of the planner exceeds it, the planner stops,
+    /// leaving its best plan in the last plan file. Only enforced on linux.
+    #[structopt(long, env = "IPC_MEMORY_LIMIT")]
+    memory_limit: Option<u64>,
+    /// Metric to optimize. Possible values: "makespan", "plan-length", "action-costs".
+    /// Defaults to the makespan for temporal domains and to the action costs otherwise.
+    #[structopt(long = "optimize")]
+    optimize: Option<Metric>,
+    /// If provided, the solver will only run the specified strategy instead of default set of strategies.
+    /// When repeated, several strategies will be run in parallel.
+    #[structopt(long = "strategy", short = "s")]
+    strategies: Vec<Strat>,
+}
+
+/// Writes successive plans to the files `<base>.1`, `<base>.2`, ...
+struct PlanWriter {
+    base: PathBuf,
+    htn_mode: bool,
+    num_written: usize,
+    last_plan: Option<String>,
+}
+
+impl PlanWriter {
+    fn new(base: PathBuf, htn_mode: bool) -> Self {
+        PlanWriter {
+            base,
+            htn_mode,
+            num_written: 0,
+            last_plan: None,
+        }
+    }
+
+    /// Writes the plan to a new file, unless it is identical to the last written one.
+    fn write(&mut self, problem: &FiniteProblem, assignment: &SavedAssignment) -> Result<()> {
+        let plan = if self.htn_mode {
+            format_hddl_plan(problem, assignment)?
+        } else {
+            format_pddl_plan(problem, assignment)?
+        };
+        if self.last_plan.as_ref() == Some(&plan) {
+            return Ok(());
+        }
+        self.num_written += 1;
+        let path = plan_path(&self.base, self.num_written);
+        std::fs::write(&path, &plan).with_context(|| format!("Failed to write plan to {}", path.display()))?;
+        println!("Plan written to {}", path.display());
+        self.last_plan = Some(plan);
+        Ok(())
+    }
+}
+
+fn plan_path(base: &Path, index: usize) -> PathBuf {
+    let mut path = base.as_os_str().to_owned();
+    path.push(format!(".{index}"));
+    PathBuf::from(path)
+}
+
+/// Resident memory of the current process in MB, if it can be determined.
+fn resident_memory_mb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+/// Starts a thread that terminates the process as soon as its resident memory exceeds `limit_mb`.
+/// Plans are written as soon as they are found, so the best plan is already available when this occurs.
+fn watch_memory(limit_mb: u64) {
+    if resident_memory_mb().is_none() {
+        eprintln!("Warning: unable to measure memory usage, the memory limit will not be enforced");
+        return;
+    }
+    std::thread::spawn(move || loop {
+        if let Some(used) = resident_memory_mb() {
+            if used > limit_mb {
+                println!("\nMemory limit reached ({used} MB > {limit_mb} MB)");
+                std::process::exit(0);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    });
+}
+
+fn main() -> Result<()> {
+    let start = Instant::now();
+    let opt: Opt = Opt::from_args();
+
+    if let Some(limit) = opt.memory_limit {
+        watch_memory(limit);
+    }
+    let deadline = opt
+        .time_limit
+        .map(|secs| start + Duration::from_secs_f64(secs.max(0.0)));
+
+    let dom = Input::from_file(&opt.domain)?;
+    let prob = Input::from_file(&opt.problem)?;
+    let dom = parse_pddl_domain(dom)?;
+    let prob = parse_pddl_problem(prob)?;
+    let spec = pddl_to_chronicles(&dom, &prob)?;
+
+    let htn_mode = dom.features.contains(&PddlFeature::Hierarchy);
+    let metric = opt
+        .optimize
+        .unwrap_or(if dom.features.contains(&PddlFeature::DurativeAction) {
+            Metric::Makespan
+        } else {
+            Metric::ActionCosts
+        });
+    let max_depth = u32::MAX;
+    let min_depth = if htn_mode && hierarchical_is_non_recursive(&spec) {
+        max_depth // non recursive htn: bounded size, go directly to max
+    } else {
+        0
+    };
+
+    let writer = Arc::new(Mutex::new(PlanWriter::new(opt.plan_file.clone(), htn_mode)));
+    let on_new_sol = {
+        let writer = writer.clone();
+        move |pb: &FiniteProblem, sol: Arc<SavedAssignment>| {
+            if let Err(e) = writer.lock().unwrap().write(pb, &sol) {
+                eprintln!("{e:#}");
+            }
+        }
+    };
+
+    let result = solve(
+        spec,
+        min_depth,
+        max_depth,
+        &opt.strategies,
+        Some(metric),
+        htn_mode,
+        &PlannerConfig::default(),
+        on_new_sol,
+        |_, _| {},
+        |pb, ass| match format_partial_assignment(pb, &ass) {
+            Ok(partial) => println!("\nDeepest partial plan:\n{partial}"),
+            Err(e) => eprintln!("{e:#}"),
+        },
+        deadline,
+    )?;
+    match result {
+        SolverResult::Sol((finite_problem, assignment)) | SolverResult::Timeout(Some((finite_problem, assignment))) => {
+            // make sure the final plan is written, even if it was not reported as an intermediate solution
+            writer.lock().unwrap().write(&finite_problem, &assignment)?;
+        }
+        SolverResult::Unsat => println!("\nNo plan found"),
+        SolverResult::Timeout(None) => println!("\nTimeout"),
+    }
+
+    Ok(())
+}
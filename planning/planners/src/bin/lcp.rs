@@ -1,7 +1,8 @@
 use anyhow::{Context, Result};
 use aries::utils::input::Input;
+use aries_planners::deorder::deorder;
 use aries_planners::solver::{format_plan, solve, SolverResult};
-use aries_planners::solver::{Metric, Strat};
+use aries_planners::solver::{Metric, PlannerConfig, Strat};
 use aries_planning::chronicles::analysis::hierarchical_is_non_recursive;
 use aries_planning::parsing::pddl::{find_domain_of, parse_pddl_domain, parse_pddl_problem, PddlFeature};
 use aries_planning::parsing::pddl_to_chronicles;
@@ -35,6 +36,9 @@ pub struct Opt {
     /// When repeated, several strategies will be run in parallel.
     #[structopt(long = "strategy", short = "s")]
     strategies: Vec<Strat>,
+    /// If set, the plan is also printed as a partial order, keeping only the orderings between interfering actions.
+    #[structopt(long)]
+    partial_order: bool,
 }
 
 fn main() -> Result<()> {
@@ -81,6 +85,9 @@ fn main() -> Result<()> {
         &opt.strategies,
         opt.optimize,
         htn_mode,
+        &PlannerConfig::default(),
+        |_, _| {},
+        |_, _| {},
         |_, _| {},
         None,
     )?;
@@ -88,6 +95,9 @@ fn main() -> Result<()> {
         SolverResult::Sol((finite_problem, assignment)) => {
             let plan_out = format_plan(&finite_problem, &assignment, htn_mode)?;
             println!("{plan_out}");
+            if opt.partial_order {
+                println!("**** Partial order ****\n\n{}", deorder(&finite_problem, &assignment));
+            }
 
             // Write the output to a file if requested
             if let Some(plan_out_file) = opt.plan_out_file.clone() {
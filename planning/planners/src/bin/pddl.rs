@@ -1,8 +1,13 @@
 use anyhow::*;
+use std::fs::File;
+use std::io::BufWriter;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
 use aries::utils::input::Input;
+use aries_planning::classical::pddl::write_pddl;
+use aries_planning::classical::sas::write_sas;
+use aries_planning::classical::{from_chronicles, grounded_problem};
 use aries_planning::parsing::pddl::{find_domain_of, parse_pddl_domain, parse_pddl_problem};
 use aries_planning::parsing::pddl_to_chronicles;
 
@@ -16,6 +21,14 @@ struct Opt {
     #[structopt(long, short)]
     domain: Option<PathBuf>,
     problem: PathBuf,
+    /// If set, the grounded problem will be written to this file in the SAS+ format of Fast Downward.
+    /// Only supported for classical planning problems.
+    #[structopt(long)]
+    sas: Option<PathBuf>,
+    /// If set, the grounded problem will be written to the `domain.pddl` and `problem.pddl` files of this directory.
+    /// Only supported for classical planning problems.
+    #[structopt(long)]
+    ground_pddl: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
@@ -43,7 +56,25 @@ fn main() -> Result<()> {
     let prob = parse_pddl_problem(prob)?;
     println!("==== Problem ====\n{}", &prob);
 
-    let _chronicles = pddl_to_chronicles(&dom, &prob)?;
+    let chronicles = pddl_to_chronicles(&dom, &prob)?;
+
+    if opt.sas.is_some() || opt.ground_pddl.is_some() {
+        let lifted = from_chronicles(&chronicles)?;
+        let grounded = grounded_problem(&lifted)?;
+        if let Some(sas_file) = opt.sas {
+            let mut file = BufWriter::new(File::create(&sas_file)?);
+            write_sas(&lifted.world, &grounded, &mut file)?;
+            println!("==== SAS+ problem written to {} ====", sas_file.display());
+        }
+        if let Some(dir) = opt.ground_pddl {
+            std::fs::create_dir_all(&dir)?;
+            let mut domain = BufWriter::new(File::create(dir.join("domain.pddl"))?);
+            let mut problem = BufWriter::new(File::create(dir.join("problem.pddl"))?);
+            let name = format!("{}-ground", dom.name);
+            write_pddl(&lifted.world, &grounded, &name, &mut domain, &mut problem)?;
+            println!("==== Ground PDDL problem written to {} ====", dir.display());
+        }
+    }
 
     Ok(())
 }
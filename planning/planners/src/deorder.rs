@@ -0,0 +1,176 @@
+//! Lifting of a solved plan into a partial order (deordering), for execution systems that dispatch the actions
+//! of a plan as soon as the actions they depend on are done rather than at their scheduled time.
+//!
+//! Two actions of the plan *interfere* if they access the same ground state variable and at least one of the accesses
+//! is an effect. This covers both the causal links (an effect supporting a condition) and the mutexes (an effect
+//! threatening a condition or another effect). Only the orderings of interfering actions are kept, and among them
+//! only those that are not implied by the others.
+
+use aries::core::state::Domains;
+use aries::core::IntCst;
+use aries::model::extensions::AssignmentExt;
+use aries::model::lang::SAtom;
+use aries::model::symbols::SymId;
+use aries_planning::chronicles::{Chronicle, ChronicleKind, ChronicleOrigin, FiniteProblem};
+use std::fmt::{Display, Formatter};
+
+/// An action of the plan, with its time in the schedule of the plan.
+///
+/// Times are numerators of rationals whose denominator is the time scale of the problem.
+#[derive(Clone, Debug)]
+pub struct ScheduledAction {
+    /// Index of the chronicle of the action in the problem.
+    pub instance_id: usize,
+    /// Name of the action followed by its arguments, e.g. `(move r1 l1 l2)`.
+    pub name: String,
+    pub start: IntCst,
+    pub end: IntCst,
+}
+
+/// An ordering between two interfering actions of a partial-order plan, identified by their index in the plan.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Precedence {
+    pub before: usize,
+    pub after: usize,
+    /// If true, the first action must end before the second one starts. Otherwise, the two actions overlap
+    /// in the schedule and the first one must only start before the second.
+    pub sequential: bool,
+}
+
+/// A plan whose actions are only ordered by the precedences required by their interferences.
+///
+/// Dispatching the actions in any order compatible with the precedences (with the same delays between the timepoints
+/// of each action) yields a valid plan, provided that the constraints on absolute times (e.g. deadlines or timed
+/// effects of the problem), that are not represented here, are met. The start times of the schedule meet them.
+#[derive(Clone, Debug)]
+pub struct PartialOrderPlan {
+    /// Actions of the plan, sorted by their start time in the schedule.
+    pub actions: Vec<ScheduledAction>,
+    /// Precedences between the actions, none of which is implied by the others.
+    pub precedences: Vec<Precedence>,
+    /// Denominator of all times of the plan.
+    pub time_scale: IntCst,
+}
+
+/// Computes a least-constrained partial order over the actions of a plan, i.e., the precedences between its interfering
+/// actions that are not implied by others.
+pub fn deorder(pb: &FiniteProblem, plan: &Domains) -> PartialOrderPlan {
+    let mut actions: Vec<(ScheduledAction, Vec<Access>)> = Vec::new();
+    for (instance_id, ch) in pb.chronicles.iter().enumerate() {
+        // actions of the problem itself are already being executed (see `from_execution_state`)
+        if plan.value(ch.chronicle.presence) != Some(true) || ch.origin == ChronicleOrigin::Original {
+            continue;
+        }
+        if !matches!(ch.chronicle.kind, ChronicleKind::Action | ChronicleKind::DurativeAction) {
+            continue;
+        }
+        let syms: Vec<SymId> = ch.chronicle.name.iter().filter_map(|&x| ground(x, plan)).collect();
+        let action = ScheduledAction {
+            instance_id,
+            name: pb.model.shape.symbols.format(&syms),
+            start: plan.f_domain(ch.chronicle.start).num.lb,
+            end: plan.f_domain(ch.chronicle.end).num.lb,
+        };
+        actions.push((action, accesses(&ch.chronicle, plan)));
+    }
+    actions.sort_by_key(|(a, _)| (a.start, a.end, a.instance_id));
+
+    // successors of each action among the interfering actions scheduled after it, in increasing order
+    let n = actions.len();
+    let mut successors: Vec<Vec<(usize, bool)>> = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if interfere(&actions[i].1, &actions[j].1) {
+                let sequential = actions[i].0.end <= actions[j].0.start;
+                successors[i].push((j, sequential));
+            }
+        }
+    }
+
+    // Transitive reduction, from the last action to the first. A chain of precedences from `i` to `j` implies
+    // that `i` starts before `j`, and that `i` ends before `j` starts if its first precedence is sequential.
+    let mut reachable = vec![vec![false; n]; n];
+    let mut precedences = Vec::new();
+    for i in (0..n).rev() {
+        let mut kept: Vec<(usize, bool)> = Vec::new();
+        for &(j, sequential) in &successors[i] {
+            let implied = kept.iter().any(|&(k, seq_k)| reachable[k][j] && (seq_k || !sequential));
+            if !implied {
+                kept.push((j, sequential));
+            }
+        }
+        for &(j, sequential) in &kept {
+            // i < j: all actions reachable from `j` are reachable from `i`
+            let (head, tail) = reachable.split_at_mut(j);
+            head[i][j] = true;
+            for (from_i, &from_j) in head[i].iter_mut().zip(&tail[0]) {
+                *from_i |= from_j;
+            }
+            precedences.push(Precedence {
+                before: i,
+                after: j,
+                sequential,
+            });
+        }
+    }
+    precedences.sort_by_key(|p| (p.before, p.after));
+
+    PartialOrderPlan {
+        actions: actions.into_iter().map(|(a, _)| a).collect(),
+        precedences,
+        time_scale: pb.time_scale(),
+    }
+}
+
+/// An access of an action to a ground state variable, whose value is `None` if it could not be determined.
+struct Access {
+    state_var: Option<Vec<SymId>>,
+    is_effect: bool,
+}
+
+fn ground(x: SAtom, plan: &Domains) -> Option<SymId> {
+    plan.sym_domain_of(x).into_singleton()
+}
+
+fn accesses(ch: &Chronicle, plan: &Domains) -> Vec<Access> {
+    let ground_sv = |sv: &[SAtom]| sv.iter().map(|&x| ground(x, plan)).collect::<Option<Vec<_>>>();
+    let effects = ch.effects.iter().map(|eff| Access {
+        state_var: ground_sv(&eff.state_var),
+        is_effect: true,
+    });
+    let conditions = ch.conditions.iter().map(|cond| Access {
+        state_var: ground_sv(&cond.state_var),
+        is_effect: false,
+    });
+    effects.chain(conditions).collect()
+}
+
+/// Returns true if the two actions access a same state variable, with at least one effect.
+/// Accesses to an undetermined state variable are assumed to interfere with all others.
+fn interfere(a: &[Access], b: &[Access]) -> bool {
+    a.iter().any(|x| {
+        b.iter().any(|y| {
+            (x.is_effect || y.is_effect)
+                && match (&x.state_var, &y.state_var) {
+                    (Some(sv1), Some(sv2)) => sv1 == sv2,
+                    _ => true,
+                }
+        })
+    })
+}
+
+impl Display for PartialOrderPlan {
+    /// Formats each action with its index and scheduled times, followed by the precedences:
+    /// `i -> j` if `i` ends before `j` starts, and `i ~> j` if `i` starts before `j` starts.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let time = |t: IntCst| t as f32 / self.time_scale as f32;
+        for (i, a) in self.actions.iter().enumerate() {
+            writeln!(f, "{i:>3}: {} [{:.3}, {:.3}]", a.name, time(a.start), time(a.end))?;
+        }
+        for p in &self.precedences {
+            let arrow = if p.sequential { "->" } else { "~>" };
+            writeln!(f, "{} {arrow} {}", p.before, p.after)?;
+        }
+        Ok(())
+    }
+}
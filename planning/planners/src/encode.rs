@@ -1,21 +1,28 @@
 //! Functions whose purpose is to encode a planning problem (represented with chronicles)
 //! into a combinatorial problem from Aries core.
 
-use crate::encoding::{conditions, effects, refinements_of, refinements_of_task, TaskRef, HORIZON, ORIGIN};
-use crate::solver::Metric;
+use crate::encoding::{
+    conditions, effects, fluent_key, interfering_effects, refinements_of, refinements_of_task, FluentLocality, TaskRef,
+    ORIGIN,
+};
+use crate::solver::{Metric, PlannerConfig};
 use crate::Model;
 use anyhow::{Context, Result};
+use aries::core::state::Cause;
 use aries::core::*;
 use aries::model::extensions::{AssignmentExt, Shaped};
 use aries::model::lang::expr::*;
+use aries::model::lang::interval::IntervalVar;
 use aries::model::lang::linear::{LinearSum, LinearTerm};
-use aries::model::lang::{FAtom, IAtom, Variable};
+use aries::model::lang::{Atom, FAtom, FVar, IAtom, IVar, SAtom, Variable};
+use aries_planning::chronicles::analysis::{instance_goal_orders, Goal};
 use aries_planning::chronicles::constraints::ConstraintType;
 use aries_planning::chronicles::*;
 use env_param::EnvParam;
 use std::convert::{TryFrom, TryInto};
+use std::sync::Arc;
 
-/// Parameter that defines the symmetry breaking strategy to use.
+/// Parameter that defines the default symmetry breaking strategy (see [PlannerConfig::symmetry_breaking]).
 /// The value of this parameter is loaded from the environment variable `ARIES_LCP_SYMMETRY_BREAKING`.
 /// Possible values are `none` and `simple` (default).
 pub static SYMMETRY_BREAKING: EnvParam<SymmetryBreakingType> = EnvParam::new("ARIES_LCP_SYMMETRY_BREAKING", "simple");
@@ -33,7 +40,7 @@ impl std::str::FromStr for SymmetryBreakingType {
 }
 
 /// The type of symmetry breaking to apply to problems.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 pub enum SymmetryBreakingType {
     /// no symmetry breaking
     None,
@@ -44,6 +51,65 @@ pub enum SymmetryBreakingType {
     Simple,
 }
 
+/// Parameter that defines the default encoding of the support of conditions (see [EncodingPipeline::new]).
+/// The value of this parameter is loaded from the environment variable `ARIES_LCP_SUPPORT_ENCODING`.
+/// Possible values are `state` (default) and `causal-links`.
+pub static SUPPORT_ENCODING: EnvParam<SupportEncodingType> = EnvParam::new("ARIES_LCP_SUPPORT_ENCODING", "state");
+
+impl std::str::FromStr for SupportEncodingType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "state" => Ok(SupportEncodingType::State),
+            "causal-links" => Ok(SupportEncodingType::CausalLinks),
+            x => Err(format!("Unknown support encoding: {x}")),
+        }
+    }
+}
+
+/// The encoding of the support of conditions.
+#[derive(Copy, Clone, Debug)]
+pub enum SupportEncodingType {
+    /// Each condition is supported by an effect whose persistence contains it, other effects on the same state
+    /// variable being excluded from this persistence by the coherence constraints (see [SupportEncoding]).
+    State,
+    /// Each condition is supported by an explicit causal link from an effect, and all effects threatening the link
+    /// are ordered before or after it (see [CausalLinkEncoding]).
+    CausalLinks,
+}
+
+/// Parameter that defines the default semantics of concurrent actions (see [PlannerConfig::concurrency]).
+/// The value of this parameter is loaded from the environment variable `ARIES_LCP_CONCURRENCY`.
+/// Possible values are `epsilon` (default), `no-overlap` and `sequential`.
+pub static CONCURRENCY: EnvParam<ConcurrencySemantics> = EnvParam::new("ARIES_LCP_CONCURRENCY", "epsilon");
+
+impl std::str::FromStr for ConcurrencySemantics {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "epsilon" => Ok(ConcurrencySemantics::Epsilon),
+            "no-overlap" => Ok(ConcurrencySemantics::NoOverlap),
+            "sequential" => Ok(ConcurrencySemantics::Sequential),
+            x => Err(format!("Unknown concurrency semantics: {x}")),
+        }
+    }
+}
+
+/// The semantics of the concurrent execution of actions (see [ConcurrencyEncoding]).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ConcurrencySemantics {
+    /// Actions may overlap, as long as their interfering effects and conditions are separated by at least an epsilon
+    /// (which follows from the coherence and support constraints).
+    Epsilon,
+    /// Two interfering actions cannot overlap, i.e., when one of them has an effect on a state variable that the other
+    /// one reads or changes (PDDL 2.1 no-moving-targets, applied to whole actions).
+    NoOverlap,
+    /// No two actions can overlap, the plan being sequential.
+    Sequential,
+}
+
 /// For each chronicle template into the `spec`, appends `num_instances` instances into the `pb`.
 pub fn populate_with_template_instances<F: Fn(&ChronicleTemplate) -> Option<u32>>(
     pb: &mut FiniteProblem,
@@ -105,7 +171,8 @@ pub fn instantiate(
         .copied()
         .expect("Presence variable not in parameters");
 
-    if !sub.contains(prez_template) {
+    let fresh_presence = !sub.contains(prez_template);
+    if fresh_presence {
         // the presence variable is in placed in the containing scope.
         // thus it can only be true if the containing scope is true as well
         let prez_instance = pb
@@ -118,6 +185,8 @@ pub fn instantiate(
     // the literal that indicates the presence of the chronicle we are building
     let prez_lit = sub.sub_lit(template.chronicle.presence);
 
+    // variables created for this instance, that are only present with it
+    let mut fresh_vars = Vec::with_capacity(template.parameters.len());
     for &v in &template.parameters {
         if sub.contains(v) {
             // we already add this variable, ignore it
@@ -134,14 +203,75 @@ pub fn instantiate(
                 let (lb, ub) = pb.model.int_bounds(f.num);
                 pb.model.new_optional_fvar(lb, ub, f.denom, prez_lit, label).into()
             }
-            Variable::Sym(s) => pb.model.new_optional_sym_var(s.tpe, prez_lit, label).into(),
+            Variable::Sym(s) => {
+                let fresh = pb.model.new_optional_sym_var(s.tpe, prez_lit, label);
+                // the domain of the parameter might be narrower than its type (see the relevance preprocessing)
+                let (lb, ub) = pb.model.int_bounds(s);
+                let _ = pb.model.state.set_lb(fresh.var, lb, Cause::Encoding);
+                let _ = pb.model.state.set_ub(fresh.var, ub, Cause::Encoding);
+                fresh.into()
+            }
         };
+        fresh_vars.push(VarRef::from(fresh));
         sub.add(v, fresh)?;
     }
 
+    if !restrict_to_tables(template, &sub, &fresh_vars, &mut pb.model) && fresh_presence {
+        // no tuple of a static relation is compatible with the parameters, the instance can never be present
+        let _ = pb.model.state.set(!prez_lit, Cause::Encoding);
+    }
+
     template.instantiate(sub, origin)
 }
 
+/// Restricts the domains of the `fresh_vars` of an instance to the values allowed by the table constraints
+/// of its `template` (i.e. the static relations between its parameters, see [statics_as_tables]),
+/// so that the instantiation is pruned upfront instead of leaving it to the solver.
+///
+/// Only the variables created for the instance are restricted, as they are present exactly when the instance is.
+/// Returns false if a table has no tuple compatible with the current domains, in which case the instance cannot be present.
+///
+/// [statics_as_tables]: aries_planning::chronicles::preprocessing::statics_as_tables
+fn restrict_to_tables(template: &ChronicleTemplate, sub: &Sub, fresh_vars: &[VarRef], model: &mut Model) -> bool {
+    for constraint in &template.chronicle.constraints {
+        let ConstraintType::InTable(table) = &constraint.tpe else {
+            continue;
+        };
+        if constraint.value.is_some() {
+            continue; // reified constraint, that may be violated
+        }
+        let Some(vars) = constraint
+            .variables
+            .iter()
+            .map(|&v| sub.sub(v).int_view())
+            .collect::<Option<Vec<IAtom>>>()
+        else {
+            continue;
+        };
+        let bounds: Vec<(IntCst, IntCst)> = vars.iter().map(|&v| model.int_bounds(v)).collect();
+        // union of the values of the compatible lines, for each column
+        let mut supported: Vec<Option<(IntCst, IntCst)>> = vec![None; vars.len()];
+        for line in table.lines() {
+            let compatible = line.iter().zip(&bounds).all(|(&val, &(lb, ub))| lb <= val && val <= ub);
+            if compatible {
+                for (col, &val) in supported.iter_mut().zip(line) {
+                    *col = Some(col.map_or((val, val), |(lb, ub)| (lb.min(val), ub.max(val))));
+                }
+            }
+        }
+        for (var, col) in vars.iter().zip(supported) {
+            let Some((lb, ub)) = col else {
+                return false;
+            };
+            if var.shift == 0 && fresh_vars.contains(&VarRef::from(var.var)) {
+                let _ = model.state.set_lb(var.var, lb, Cause::Encoding);
+                let _ = model.state.set_ub(var.var, ub, Cause::Encoding);
+            }
+        }
+    }
+    true
+}
+
 pub fn populate_with_task_network(pb: &mut FiniteProblem, spec: &Problem, max_depth: u32) -> Result<()> {
     struct Subtask {
         task_name: Task,
@@ -244,6 +374,28 @@ fn add_decomposition_constraints(pb: &FiniteProblem, model: &mut Model) {
     }
 }
 
+/// Enforces that each object of a pool is created iff it is the value of a parameter of a present chronicle.
+fn add_object_creation_constraints(pb: &FiniteProblem, model: &mut Model) {
+    for pool in &pb.object_pools {
+        for &(obj, created) in &pool.objects {
+            let obj = SAtom::new_constant(obj, pool.tpe);
+            let mut uses: Vec<Lit> = Vec::new();
+            for ch in &pb.chronicles {
+                let prez = ch.chronicle.presence;
+                for &param in &ch.parameters {
+                    if let Atom::Sym(param) = param {
+                        if model.unifiable(param, obj) {
+                            let is_obj = model.reify(eq(param, obj));
+                            uses.push(model.reify(and([prez, is_obj])));
+                        }
+                    }
+                }
+            }
+            model.bind(or(uses), created);
+        }
+    }
+}
+
 fn enforce_refinement(t: TaskRef, supporters: Vec<TaskRef>, model: &mut Model) {
     // if t is present then at least one supporter is present
     let mut clause: Vec<Lit> = Vec::with_capacity(supporters.len() + 1);
@@ -303,10 +455,12 @@ fn add_symmetry_breaking(pb: &FiniteProblem, model: &mut Model, tpe: SymmetryBre
     };
 }
 
-/// Encode a metric in the problem and returns an integer that should minimized in order to optimize the metric.
+/// Encode a metric in the problem and returns an integer that should be optimized in order to optimize the metric.
+/// It should be maximized if [Metric::is_maximization] holds and minimized otherwise.
 pub fn add_metric(pb: &FiniteProblem, model: &mut Model, metric: Metric) -> IAtom {
     match metric {
         Metric::Makespan => pb.horizon.num,
+        Metric::FinalValue { value, .. } => value,
         Metric::PlanLength => {
             // retrieve the presence variable of each action
             let mut action_presence = Vec::with_capacity(8);
@@ -340,9 +494,11 @@ pub fn add_metric(pb: &FiniteProblem, model: &mut Model, metric: Metric) -> IAto
         Metric::ActionCosts => {
             // retrieve the presence and cost of each chronicle
             let mut costs = Vec::with_capacity(8);
+            // lowest possible cost of a plan, reached when all chronicles with a negative cost are present
+            let mut min_plan_cost: IntCst = 0;
             for (ch_id, ch) in pb.chronicles.iter().enumerate() {
                 if let Some(cost) = ch.chronicle.cost {
-                    assert!(cost >= 0, "A chronicle has a negative cost");
+                    min_plan_cost = min_plan_cost.saturating_add(model.int_bounds(cost).0.min(0));
                     costs.push((ch_id, ch.chronicle.presence, cost));
                 }
             }
@@ -351,15 +507,23 @@ pub fn add_metric(pb: &FiniteProblem, model: &mut Model, metric: Metric) -> IAto
             let action_costs: Vec<LinearTerm> = costs
                 .iter()
                 .map(|&(ch_id, p, cost)| {
-                    model
-                        .new_optional_ivar(cost, cost, p, Container::Instance(ch_id).var(VarType::Cost))
-                        .or_zero()
+                    let (lb, ub) = model.int_bounds(cost);
+                    let action_cost = model.new_optional_ivar(lb, ub, p, Container::Instance(ch_id).var(VarType::Cost));
+                    if lb != ub {
+                        // the cost depends on the parameters of the chronicle
+                        model.enforce(eq(action_cost, cost), [p]);
+                    }
+                    action_cost.or_zero()
                 })
                 .collect();
             let action_costs = LinearSum::of(action_costs);
 
             // make the sum of the action costs equal a `plan_cost` variable.
-            let plan_cost = model.new_ivar(0, INT_CST_MAX, VarLabel(Container::Base, VarType::Cost));
+            let plan_cost = model.new_ivar(
+                min_plan_cost.max(INT_CST_MIN),
+                INT_CST_MAX,
+                VarLabel(Container::Base, VarType::Cost),
+            );
             model.enforce(action_costs.clone().leq(plan_cost), []);
             model.enforce(action_costs.geq(plan_cost), []);
             // plan cost is the metric that should be minimized.
@@ -368,67 +532,275 @@ pub fn add_metric(pb: &FiniteProblem, model: &mut Model, metric: Metric) -> IAto
     }
 }
 
-/// Encodes a finite problem.
-/// If a metric is given, it will return along with the model an `IAtom` that should be minimized
-pub fn encode(pb: &FiniteProblem, metric: Option<Metric>) -> anyhow::Result<(Model, Option<IAtom>)> {
-    let mut model = pb.model.clone();
-    let symmetry_breaking_tpe = SYMMETRY_BREAKING.get();
+/// Returns true if the two state variables are unifiable.
+fn unifiable_sv(model: &Model, sv1: &Sv, sv2: &Sv) -> bool {
+    sv1.len() == sv2.len() && sv1.iter().zip(sv2).all(|(&a, &b)| model.unifiable(a, b))
+}
 
-    let effs: Vec<_> = effects(pb).collect();
-    let conds: Vec<_> = conditions(pb).collect();
-    let eff_ends: Vec<_> = effs
-        .iter()
-        .map(|(instance_id, prez, _)| {
-            model.new_optional_fvar(
-                ORIGIN * TIME_SCALE,
-                HORIZON * TIME_SCALE,
-                TIME_SCALE,
-                *prez,
-                Container::Instance(*instance_id) / VarType::EffectEnd,
-            )
-        })
-        .collect();
+/// Returns true if `a > b` holds in any assignment, as can be determined statically from the initial domains
+/// of the two timepoints or, when they are defined on the same variable, from their offsets.
+fn provably_after(model: &Model, a: FAtom, b: FAtom) -> bool {
+    if a.denom != b.denom {
+        false
+    } else if a.num.var == b.num.var {
+        a.num.shift > b.num.shift
+    } else {
+        model.int_bounds(a).0 > model.int_bounds(b).1
+    }
+}
 
-    // for each condition, make sure the end is after the start
-    for &(prez_cond, cond) in &conds {
-        model.enforce(f_leq(cond.start, cond.end), [prez_cond]);
+/// Returns true if `a <= b` holds in any assignment, as can be determined statically from the initial domains
+/// of the two timepoints or, when they are defined on the same variable, from their offsets.
+fn provably_before_or_at(model: &Model, a: FAtom, b: FAtom) -> bool {
+    if a.denom != b.denom {
+        false
+    } else if a.num.var == b.num.var {
+        a.num.shift <= b.num.shift
+    } else {
+        model.int_bounds(a).1 <= model.int_bounds(b).0
     }
+}
 
-    // for each effect, make sure the three time points are ordered
-    for ieff in 0..effs.len() {
-        let (_, prez_eff, eff) = effs[ieff];
-        let persistence_end = eff_ends[ieff];
-        model.enforce(f_leq(eff.persistence_start, persistence_end), [prez_eff]);
-        model.enforce(f_leq(eff.transition_start, eff.persistence_start), [prez_eff]);
-        for &min_persistence_end in &eff.min_persistence_end {
-            model.enforce(f_leq(min_persistence_end, persistence_end), [prez_eff])
+/// A finite problem encoded as a combinatorial problem.
+pub struct EncodedProblem {
+    pub model: Model,
+    /// If a metric was given, an `IAtom` that should be optimized in the direction of the metric.
+    pub objective: Option<IAtom>,
+    /// Upper bound on the end of all effects (with the time scale of the problem as denominator).
+    /// It is unbounded in the model and meant to be bounded by an assumption of the solver,
+    /// that can be relaxed if it proves too small (see [HORIZON](crate::encoding::HORIZON)).
+    pub effects_bound: IVar,
+}
+
+/// Encodes a finite problem, with the constraint generators of the configuration (see [PlannerConfig::encoding]).
+pub fn encode(pb: &FiniteProblem, metric: Option<Metric>, config: &PlannerConfig) -> anyhow::Result<EncodedProblem> {
+    config.encoding.encode(pb, metric, config)
+}
+
+/// An encoding in progress, that constraint generators extend with their constraints.
+pub struct EncodingContext<'a> {
+    pub pb: &'a FiniteProblem,
+    pub config: &'a PlannerConfig,
+    pub metric: Option<Metric>,
+    /// Model being built, initially a copy of the one of the problem.
+    pub model: Model,
+    /// All effects of the problem, with the index of their chronicle instance and their presence,
+    /// numbered as given by `locality`.
+    pub effects: Vec<(usize, Lit, &'a Effect)>,
+    /// For each effect, the end of its persistence.
+    pub effect_ends: Vec<FVar>,
+    /// All conditions of the problem, with their presence, numbered as given by `locality`.
+    pub conditions: Vec<(Lit, &'a Condition)>,
+    /// Numbering of effects and conditions grouping them by fluent, which maps each of them back to its position in
+    /// the problem (see [effects] and [conditions]), e.g., to report them in a stable order.
+    pub locality: FluentLocality,
+    /// Upper bound on the end of all effects (see [EncodedProblem::effects_bound]).
+    pub effects_bound: IVar,
+    /// Integer to optimize, set by the generator handling the metric.
+    pub objective: Option<IAtom>,
+}
+
+impl<'a> EncodingContext<'a> {
+    /// Creates the variables shared by all constraint generators: the end of the persistence of each effect
+    /// and the bound on the end of all effects.
+    fn new(pb: &'a FiniteProblem, metric: Option<Metric>, config: &'a PlannerConfig) -> Self {
+        let mut model = pb.model.clone();
+        let time_scale = pb.time_scale();
+        let effects_bound = model.new_ivar(ORIGIN * time_scale, INT_CST_MAX, Container::Base / VarType::Horizon);
+        let effects: Vec<_> = effects(pb).collect();
+        let conditions: Vec<_> = conditions(pb).collect();
+        // the end of the effects are created in the new numbering, so that those on the same fluent are contiguous
+        let locality = FluentLocality::new(&effects, &conditions, &model);
+        let effects: Vec<_> = locality.effect_origins.iter().map(|&i| effects[i]).collect();
+        let conditions: Vec<_> = locality.condition_origins.iter().map(|&i| conditions[i]).collect();
+        let effect_ends: Vec<_> = effects
+            .iter()
+            .map(|(instance_id, prez, _)| {
+                model.new_optional_fvar(
+                    ORIGIN * time_scale,
+                    INT_CST_MAX,
+                    time_scale,
+                    *prez,
+                    Container::Instance(*instance_id) / VarType::EffectEnd,
+                )
+            })
+            .collect();
+        EncodingContext {
+            pb,
+            config,
+            metric,
+            model,
+            effects,
+            effect_ends,
+            conditions,
+            locality,
+            effects_bound,
+            objective: None,
         }
     }
+}
 
-    // are two state variables unifiable?
-    let unifiable_sv = |model: &Model, sv1: &Sv, sv2: &Sv| {
-        if sv1.len() != sv2.len() {
-            false
-        } else {
-            for (&a, &b) in sv1.iter().zip(sv2) {
-                if !model.unifiable(a, b) {
-                    return false;
-                }
-            }
-            true
+/// A pass of the encoding, that adds a family of constraints to the model (e.g. the support of conditions).
+pub trait ConstraintGenerator: Send + Sync {
+    /// Name identifying the pass in an [EncodingPipeline].
+    fn name(&self) -> &str;
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()>;
+}
+
+/// Sequence of constraint generators whose successive passes encode a finite problem.
+///
+/// The standard pipeline contains the following passes, in order:
+///  - `timepoints`: ordering of the timepoints of conditions and effects
+///  - `coherence`: no two effects on the same state variable overlap
+///  - `concurrency`: restrictions on overlapping actions (see [PlannerConfig::concurrency])
+///  - `support`: each condition is supported by an effect
+///  - `goal-orders`: orders between the achievement of goals that destroy each other (see [GoalOrderEncoding])
+///  - `chronicles`: constraints and temporal coherence of chronicles and their subtasks
+///  - `decomposition`: each task is refined by exactly one chronicle
+///  - `objects`: objects of a pool are created iff used by a chronicle
+///  - `symmetry`: symmetry breaking between instances of a template (see [PlannerConfig::symmetry_breaking])
+///  - `metric`: encoding of the metric to optimize, if any
+///
+/// Each of them can be removed or replaced, e.g., to use another encoding of the support of conditions.
+#[derive(Clone)]
+pub struct EncodingPipeline {
+    generators: Vec<Arc<dyn ConstraintGenerator>>,
+}
+
+impl EncodingPipeline {
+    /// The standard pipeline (see [EncodingPipeline]), with the given encoding of the support of conditions.
+    /// With [SupportEncodingType::CausalLinks], the `support` pass is replaced by a `causal-links` pass.
+    pub fn new(support: SupportEncodingType) -> Self {
+        let pipeline = EncodingPipeline::empty()
+            .with(TimepointsEncoding)
+            .with(CoherenceEncoding)
+            .with(ConcurrencyEncoding);
+        let pipeline = match support {
+            SupportEncodingType::State => pipeline.with(SupportEncoding),
+            SupportEncodingType::CausalLinks => pipeline.with(CausalLinkEncoding),
+        };
+        pipeline
+            .with(GoalOrderEncoding)
+            .with(ChroniclesEncoding)
+            .with(DecompositionEncoding)
+            .with(ObjectsEncoding)
+            .with(SymmetryEncoding)
+            .with(MetricEncoding)
+    }
+
+    /// A pipeline without any pass.
+    pub fn empty() -> Self {
+        EncodingPipeline { generators: Vec::new() }
+    }
+
+    /// Names of the passes of the pipeline, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.generators.iter().map(|g| g.name())
+    }
+
+    /// Appends a pass at the end of the pipeline.
+    pub fn with(mut self, generator: impl ConstraintGenerator + 'static) -> Self {
+        self.generators.push(Arc::new(generator));
+        self
+    }
+
+    /// Removes the pass with the given name, if any.
+    pub fn without(mut self, name: &str) -> Self {
+        self.generators.retain(|g| g.name() != name);
+        self
+    }
+
+    /// Replaces the pass with the given name, that must exist in the pipeline.
+    pub fn replace(mut self, name: &str, generator: impl ConstraintGenerator + 'static) -> Result<Self> {
+        let pass = self
+            .generators
+            .iter_mut()
+            .find(|g| g.name() == name)
+            .with_context(|| format!("No encoding pass named `{name}`"))?;
+        *pass = Arc::new(generator);
+        Ok(self)
+    }
+
+    /// Encodes a finite problem by running all passes in order.
+    pub fn encode(&self, pb: &FiniteProblem, metric: Option<Metric>, config: &PlannerConfig) -> Result<EncodedProblem> {
+        let mut ctx = EncodingContext::new(pb, metric, config);
+        for generator in &self.generators {
+            generator
+                .generate(&mut ctx)
+                .with_context(|| format!("Error in the encoding pass `{}`", generator.name()))?;
         }
-    };
+        Ok(EncodedProblem {
+            model: ctx.model,
+            objective: ctx.objective,
+            effects_bound: ctx.effects_bound,
+        })
+    }
+}
+
+impl Default for EncodingPipeline {
+    /// The standard pipeline, with the encoding of the support given by the `ARIES_LCP_SUPPORT_ENCODING` parameter.
+    fn default() -> Self {
+        EncodingPipeline::new(SUPPORT_ENCODING.get())
+    }
+}
+
+impl std::fmt::Debug for EncodingPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// Orders the start and end of each condition and the timepoints of each effect,
+/// whose persistence must end before the bound on all effects.
+pub struct TimepointsEncoding;
 
-    // for each pair of effects, enforce coherence constraints
-    let mut clause: Vec<Lit> = Vec::with_capacity(32);
-    for (i, &(_, p1, e1)) in effs.iter().enumerate() {
-        for j in i + 1..effs.len() {
-            let &(_, p2, e2) = &effs[j];
+impl ConstraintGenerator for TimepointsEncoding {
+    fn name(&self) -> &str {
+        "timepoints"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let model = &mut ctx.model;
+        let time_scale = ctx.pb.time_scale();
+        // for each condition, make sure the end is after the start
+        for &(prez_cond, cond) in &ctx.conditions {
+            model.enforce(f_leq(cond.start, cond.end), [prez_cond]);
+        }
 
-            // skip if they are trivially non-overlapping
-            if !unifiable_sv(&model, &e1.state_var, &e2.state_var) {
-                continue;
+        // for each effect, make sure the three time points are ordered
+        for (&(_, prez_eff, eff), &persistence_end) in ctx.effects.iter().zip(&ctx.effect_ends) {
+            model.enforce(f_leq(eff.persistence_start, persistence_end), [prez_eff]);
+            model.enforce(f_leq(eff.transition_start, eff.persistence_start), [prez_eff]);
+            for &min_persistence_end in &eff.min_persistence_end {
+                model.enforce(f_leq(min_persistence_end, persistence_end), [prez_eff])
             }
+            model.enforce(
+                f_leq(persistence_end, FAtom::new(ctx.effects_bound.into(), time_scale)),
+                [prez_eff],
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Coherence constraints: two effects on the same state variable cannot overlap.
+pub struct CoherenceEncoding;
+
+impl ConstraintGenerator for CoherenceEncoding {
+    fn name(&self) -> &str {
+        "coherence"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let (effs, eff_ends) = (&ctx.effects, &ctx.effect_ends);
+        let model = &mut ctx.model;
+        // for each pair of effects, enforce coherence constraints
+        let mut clause: Vec<Lit> = Vec::with_capacity(32);
+        // pairs that are statically known not to interfere are skipped
+        for (i, j) in interfering_effects(ctx.pb, effs, model) {
+            let (_, p1, e1) = effs[i];
+            let (_, p2, e2) = effs[j];
 
             clause.clear();
             assert_eq!(e1.state_var.len(), e2.state_var.len());
@@ -447,140 +819,682 @@ pub fn encode(pb: &FiniteProblem, metric: Option<Metric>) -> anyhow::Result<(Mod
             // add coherence constraint
             model.enforce(or(clause.as_slice()), [p1, p2]);
         }
+        Ok(())
+    }
+}
+
+/// Restrictions on the overlap of actions required by the concurrency semantics of the configuration
+/// (see [ConcurrencySemantics]): each pair of actions that must not overlap is ordered, one ending before the other starts.
+///
+/// Nothing is added with the [ConcurrencySemantics::Epsilon] semantics, that the other passes already enforce.
+pub struct ConcurrencyEncoding;
+
+impl ConstraintGenerator for ConcurrencyEncoding {
+    fn name(&self) -> &str {
+        "concurrency"
     }
 
-    // support constraints
-    for (_cond_id, &(prez_cond, cond)) in conds.iter().enumerate() {
-        let mut supported: Vec<Lit> = Vec::with_capacity(128);
-        for (eff_id, &(_, prez_eff, eff)) in effs.iter().enumerate() {
-            // quick check that the condition and effect are not trivially incompatible
-            if !unifiable_sv(&model, &cond.state_var, &eff.state_var) {
-                continue;
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let semantics = ctx.config.concurrency;
+        if semantics == ConcurrencySemantics::Epsilon {
+            return Ok(());
+        }
+        let model = &mut ctx.model;
+        let actions: Vec<&Chronicle> = ctx
+            .pb
+            .chronicles
+            .iter()
+            .map(|instance| &instance.chronicle)
+            .filter(|ch| matches!(ch.kind, ChronicleKind::Action | ChronicleKind::DurativeAction))
+            .filter(|ch| !model.entails(!ch.presence))
+            .collect();
+        let mut clause: Vec<Lit> = Vec::with_capacity(32);
+        for (i, a) in actions.iter().enumerate() {
+            for b in &actions[i + 1..] {
+                // pairs of state variables accessed by both actions and changed by at least one of them
+                let interferences = match semantics {
+                    ConcurrencySemantics::Sequential => vec![None],
+                    _ => interferences(model, a, b).into_iter().map(Some).collect(),
+                };
+                if interferences.is_empty() {
+                    continue;
+                }
+                let a_before_b = model.reify(f_leq(a.end, b.start));
+                let b_before_a = model.reify(f_leq(b.end, a.start));
+                for interference in interferences {
+                    clause.clear();
+                    if let Some((sv1, sv2)) = interference {
+                        // the actions may not interfere if they access different state variables
+                        for (&x, &y) in sv1.iter().zip(sv2) {
+                            if x != y {
+                                clause.push(model.reify(neq(x, y)));
+                            }
+                        }
+                    }
+                    clause.push(a_before_b);
+                    clause.push(b_before_a);
+                    model.enforce(or(clause.as_slice()), [a.presence, b.presence]);
+                }
             }
-            if !model.unifiable(cond.value, eff.value) {
-                continue;
+        }
+        Ok(())
+    }
+}
+
+/// Pairs of unifiable state variables accessed by the two chronicles (by a condition or an effect),
+/// where at least one of the two accesses is an effect.
+fn interferences<'a>(model: &Model, a: &'a Chronicle, b: &'a Chronicle) -> Vec<(&'a Sv, &'a Sv)> {
+    let mut pairs = Vec::new();
+    for eff in &a.effects {
+        let accesses_of_b = b.effects.iter().map(|e| &e.state_var);
+        for sv in accesses_of_b.chain(b.conditions.iter().map(|c| &c.state_var)) {
+            pairs.push((&eff.state_var, sv));
+        }
+    }
+    for eff in &b.effects {
+        for cond in &a.conditions {
+            pairs.push((&cond.state_var, &eff.state_var));
+        }
+    }
+    pairs.retain(|(sv1, sv2)| unifiable_sv(model, sv1, sv2));
+    pairs
+}
+
+/// Support constraints: each present condition is supported by an effect that establishes its value
+/// on the same state variable, and whose persistence contains the condition.
+/// Over-all conditions have a dedicated encoding of their support (see [over_all_support]).
+pub struct SupportEncoding;
+
+impl ConstraintGenerator for SupportEncoding {
+    fn name(&self) -> &str {
+        "support"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let (effs, eff_ends) = (&ctx.effects, &ctx.effect_ends);
+        let model = &mut ctx.model;
+
+        for &(prez_cond, cond) in &ctx.conditions {
+            let mut supported: Vec<Lit> = Vec::with_capacity(128);
+            for eff_id in ctx.locality.candidate_effects(fluent_key(&cond.state_var, model)) {
+                let (_, prez_eff, eff) = effs[eff_id];
+                // quick check that the condition and effect are not trivially incompatible
+                if !unifiable_sv(model, &cond.state_var, &eff.state_var) {
+                    continue;
+                }
+                if !model.unifiable(cond.value, eff.value) {
+                    continue;
+                }
+                // the persistence of the effect cannot start before the condition or the two cannot be present together
+                if provably_after(model, eff.persistence_start, cond.start)
+                    || model.state.exclusive(prez_cond, prez_eff)
+                {
+                    continue;
+                }
+                if cond.start != cond.end {
+                    supported.push(over_all_support(
+                        model,
+                        (prez_cond, cond),
+                        (prez_eff, eff),
+                        eff_ends[eff_id],
+                    ));
+                    continue;
+                }
+                // vector to store the AND clause
+                let mut supported_by_eff_conjunction: Vec<Lit> = Vec::with_capacity(32);
+                // support only possible if the effect is present
+                supported_by_eff_conjunction.push(prez_eff);
+
+                assert_eq!(cond.state_var.len(), eff.state_var.len());
+                // same state variable
+                for idx in 0..cond.state_var.len() {
+                    let a = cond.state_var[idx];
+                    let b = eff.state_var[idx];
+
+                    supported_by_eff_conjunction.push(model.reify(eq(a, b)));
+                }
+                // same value
+                let condition_value = cond.value;
+                let effect_value = eff.value;
+                supported_by_eff_conjunction.push(model.reify(eq(condition_value, effect_value)));
+
+                // effect's persistence contains condition
+                // (the constraint on the persistence start is omitted if it always holds, e.g., for an initial fact)
+                if !provably_before_or_at(model, eff.persistence_start, cond.start) {
+                    supported_by_eff_conjunction.push(model.reify(f_leq(eff.persistence_start, cond.start)));
+                }
+                supported_by_eff_conjunction.push(model.reify(f_leq(cond.end, eff_ends[eff_id])));
+
+                let support_lit = model.reify(and(supported_by_eff_conjunction));
+
+                debug_assert!(model
+                    .state
+                    .implies(prez_cond, model.presence_literal(support_lit.variable())));
+
+                // add this support expression to the support clause
+                supported.push(support_lit);
             }
-            // vector to store the AND clause
-            let mut supported_by_eff_conjunction: Vec<Lit> = Vec::with_capacity(32);
-            // support only possible if the effect is present
-            supported_by_eff_conjunction.push(prez_eff);
-
-            assert_eq!(cond.state_var.len(), eff.state_var.len());
-            // same state variable
-            for idx in 0..cond.state_var.len() {
-                let a = cond.state_var[idx];
-                let b = eff.state_var[idx];
-
-                supported_by_eff_conjunction.push(model.reify(eq(a, b)));
+
+            // enforce necessary conditions for condition's support
+            model.enforce(or(supported), [prez_cond]);
+        }
+        Ok(())
+    }
+}
+
+/// Returns a literal that, when true, requires the effect to support the over-all condition, i.e., to establish its
+/// value on its state variable for the whole duration of the condition.
+///
+/// Unlike the support of an instantaneous condition, the support is not the reification of a conjunction but a decision
+/// of the solver, that only implies the unification of the condition with the effect and a single pair of temporal
+/// constraints (`persistence_start <= cond.start` and `cond.end <= persistence_end`).
+/// The literal is thus refuted by the temporal network as soon as the persistence can no longer contain the condition,
+/// without the reverse implications of a reified conjunction.
+fn over_all_support(model: &mut Model, cond: (Lit, &Condition), eff: (Lit, &Effect), eff_end: FVar) -> Lit {
+    let ((prez_cond, cond), (prez_eff, eff)) = (cond, eff);
+    let support = model
+        .new_optional_bvar(prez_cond, VarLabel(Container::Base, VarType::Reification))
+        .true_lit();
+    let mut requirements: Vec<Lit> = Vec::with_capacity(cond.state_var.len() + 4);
+    requirements.push(prez_eff);
+    for (&a, &b) in cond.state_var.iter().zip(&eff.state_var) {
+        requirements.push(model.reify(eq(a, b)));
+    }
+    requirements.push(model.reify(eq(cond.value, eff.value)));
+    if !provably_before_or_at(model, eff.persistence_start, cond.start) {
+        requirements.push(model.reify(f_leq(eff.persistence_start, cond.start)));
+    }
+    requirements.push(model.reify(f_leq(cond.end, eff_end)));
+    for requirement in requirements {
+        model.bind(implies(support, requirement), Lit::TRUE);
+    }
+    support
+}
+
+/// Support constraints based on causal links, as in partial-order causal-link (POCL) planning:
+/// each present condition is supported by a causal link from an effect that establishes its value on the same state
+/// variable before it, and every other effect on this state variable (a threat to the link) is either before
+/// the supporting effect (promotion) or after the condition (demotion).
+///
+/// Unlike [SupportEncoding], the support does not rely on the end of the persistence of the supporting effect,
+/// which tends to propagate better on lifted, loosely coupled domains at the cost of more constraints.
+pub struct CausalLinkEncoding;
+
+impl ConstraintGenerator for CausalLinkEncoding {
+    fn name(&self) -> &str {
+        "causal-links"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let effs = &ctx.effects;
+        let model = &mut ctx.model;
+
+        for &(prez_cond, cond) in &ctx.conditions {
+            // effects that may be on the state variable of the condition, when both are present
+            let candidates: Vec<usize> = ctx
+                .locality
+                .candidate_effects(fluent_key(&cond.state_var, model))
+                .filter(|&i| {
+                    let (_, prez_eff, eff) = effs[i];
+                    unifiable_sv(model, &cond.state_var, &eff.state_var) && !model.state.exclusive(prez_cond, prez_eff)
+                })
+                .collect();
+
+            let mut links: Vec<Lit> = Vec::with_capacity(candidates.len());
+            for &eff_id in &candidates {
+                let (_, prez_eff, eff) = effs[eff_id];
+                // the effect must establish the value before the condition starts
+                if !model.unifiable(cond.value, eff.value) || provably_after(model, eff.persistence_start, cond.start) {
+                    continue;
+                }
+                // the link is a decision of the solver that, when made, requires the effect to establish the condition
+                let link = model
+                    .new_optional_bvar(prez_cond, VarLabel(Container::Base, VarType::Reification))
+                    .true_lit();
+                let mut requirements: Vec<Lit> = Vec::with_capacity(32);
+                requirements.push(prez_eff);
+                for (&a, &b) in cond.state_var.iter().zip(&eff.state_var) {
+                    requirements.push(model.reify(eq(a, b)));
+                }
+                requirements.push(model.reify(eq(cond.value, eff.value)));
+                if !provably_before_or_at(model, eff.persistence_start, cond.start) {
+                    requirements.push(model.reify(f_leq(eff.persistence_start, cond.start)));
+                }
+                for requirement in requirements {
+                    model.bind(implies(link, requirement), Lit::TRUE);
+                }
+
+                // threat resolution: any other effect on the state variable is before the supporting effect or after the condition
+                for &threat_id in &candidates {
+                    let (_, prez_threat, threat) = effs[threat_id];
+                    if threat_id == eff_id || model.state.exclusive(prez_eff, prez_threat) {
+                        continue;
+                    }
+                    let mut resolution: Vec<Lit> = Vec::with_capacity(8);
+                    resolution.push(!link);
+                    for (&a, &b) in cond.state_var.iter().zip(&threat.state_var) {
+                        if a != b {
+                            resolution.push(model.reify(neq(a, b)));
+                        }
+                    }
+                    resolution.push(model.reify(f_leq(threat.persistence_start, eff.transition_start)));
+                    resolution.push(model.reify(f_leq(cond.end, threat.transition_start)));
+                    // the resolution must hold wherever it is defined, the scope of its disjuncts depending on
+                    // which of the timepoints and parameters are optional
+                    model.bind(or(resolution), Lit::TRUE);
+                }
+                links.push(link);
             }
-            // same value
-            let condition_value = cond.value;
-            let effect_value = eff.value;
-            supported_by_eff_conjunction.push(model.reify(eq(condition_value, effect_value)));
 
-            // effect's persistence contains condition
-            supported_by_eff_conjunction.push(model.reify(f_leq(eff.persistence_start, cond.start)));
-            supported_by_eff_conjunction.push(model.reify(f_leq(cond.end, eff_ends[eff_id])));
+            // a present condition is supported by at least one causal link
+            model.enforce(or(links), [prez_cond]);
+        }
+        Ok(())
+    }
+}
+
+/// Orders between the goals of the problem (see [instance_goal_orders]).
+///
+/// When all achievers of a goal `g1` destroy another goal `g2`, any action achieving `g1` is followed by the effect
+/// that establishes `g2` until the end of the plan, unless the action destroys `g2` after the goal must hold.
+/// This is implied by the coherence and support constraints, but is only detected by them once
+/// the supporting effect of `g2` is known to be on the state variable destroyed by the action.
+pub struct GoalOrderEncoding;
+
+impl ConstraintGenerator for GoalOrderEncoding {
+    fn name(&self) -> &str {
+        "goal-orders"
+    }
 
-            let support_lit = model.reify(and(supported_by_eff_conjunction));
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let pb = ctx.pb;
+        let model = &mut ctx.model;
+        let goal = |g: Goal| &pb.chronicles[g.chronicle].chronicle.conditions[g.condition];
+        for order in instance_goal_orders(pb) {
+            let (first, second) = (goal(order.first), goal(order.second));
+            // effects that may establish the second goal until it must hold
+            let mut supports = Vec::new();
+            for (eff_id, &(_, prez_eff, eff)) in ctx.effects.iter().enumerate() {
+                if !unifiable_sv(model, &second.state_var, &eff.state_var) || !model.unifiable(second.value, eff.value)
+                {
+                    continue;
+                }
+                let mut conjunction = vec![prez_eff];
+                for (&a, &b) in second.state_var.iter().zip(&eff.state_var) {
+                    conjunction.push(model.reify(eq(a, b)));
+                }
+                conjunction.push(model.reify(eq(second.value, eff.value)));
+                conjunction.push(model.reify(f_leq(eff.persistence_start, second.start)));
+                conjunction.push(model.reify(f_leq(second.end, ctx.effect_ends[eff_id])));
+                supports.push((model.reify(and(conjunction)), prez_eff, eff));
+            }
 
-            debug_assert!(model
-                .state
-                .implies(prez_cond, model.presence_literal(support_lit.variable())));
+            for &(action_id, eff_id, destroyer) in &order.destroyers {
+                let action = &pb.chronicles[action_id].chronicle;
+                let achiever = &action.effects[eff_id];
+                let mut conjunction = vec![action.presence];
+                for (&a, &b) in first.state_var.iter().zip(&achiever.state_var) {
+                    conjunction.push(model.reify(eq(a, b)));
+                }
+                conjunction.push(model.reify(eq(first.value, achiever.value)));
+                let achieves = model.reify(and(conjunction));
 
-            // add this support expression to the support clause
-            supported.push(support_lit);
+                // interval in which the action makes the second goal false
+                let (destroyed_from, destroyed_until) = destroyer.interval(action);
+                for &(supports, prez_eff, eff) in &supports {
+                    let after = model.reify(f_leq(destroyed_until, eff.transition_start));
+                    let too_late = model.reify(f_leq(second.end, destroyed_from));
+                    model.enforce(or([!achieves, !supports, after, too_late]), [action.presence, prez_eff]);
+                }
+            }
         }
+        Ok(())
+    }
+}
 
-        // enforce necessary conditions for condition's support
-        model.enforce(or(supported), [prez_cond]);
+/// Constraints of the chronicles, and temporal coherence of the chronicles with their subtasks and the horizon.
+pub struct ChroniclesEncoding;
+
+impl ConstraintGenerator for ChroniclesEncoding {
+    fn name(&self) -> &str {
+        "chronicles"
     }
 
-    // chronicle constraints
-    for instance in &pb.chronicles {
-        let prez = instance.chronicle.presence;
-        for constraint in &instance.chronicle.constraints {
-            let value = match constraint.value {
-                // work around some dubious encoding of chronicle. The given value should have the appropriate scope
-                Some(Lit::TRUE) | None => model.get_tautology_of_scope(prez),
-                Some(Lit::FALSE) => !model.get_tautology_of_scope(prez),
-                Some(l) => l,
-            };
-            match &constraint.tpe {
-                ConstraintType::InTable(table) => {
-                    let mut supported_by_a_line: Vec<Lit> = Vec::with_capacity(256);
-
-                    let vars = &constraint.variables;
-                    for values in table.lines() {
-                        assert_eq!(vars.len(), values.len());
-                        let mut supported_by_this_line = Vec::with_capacity(16);
-                        for (&var, &val) in vars.iter().zip(values.iter()) {
-                            let var = var.int_view().unwrap();
-                            supported_by_this_line.push(model.reify(leq(var, val)));
-                            supported_by_this_line.push(model.reify(geq(var, val)));
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        let model = &mut ctx.model;
+        for instance in &ctx.pb.chronicles {
+            let prez = instance.chronicle.presence;
+            for constraint in &instance.chronicle.constraints {
+                // the constraint only holds if the chronicle is present
+                let value = model.scoped_value(constraint.value.unwrap_or(Lit::TRUE), prez);
+                match &constraint.tpe {
+                    ConstraintType::InTable(table) => {
+                        let mut supported_by_a_line: Vec<Lit> = Vec::with_capacity(256);
+
+                        let vars = &constraint.variables;
+                        for values in table.lines() {
+                            assert_eq!(vars.len(), values.len());
+                            let mut supported_by_this_line = Vec::with_capacity(16);
+                            for (&var, &val) in vars.iter().zip(values.iter()) {
+                                let var = var.int_view().unwrap();
+                                supported_by_this_line.push(model.reify(leq(var, val)));
+                                supported_by_this_line.push(model.reify(geq(var, val)));
+                            }
+                            supported_by_a_line.push(model.reify(and(supported_by_this_line)));
                         }
-                        supported_by_a_line.push(model.reify(and(supported_by_this_line)));
+                        assert!(model.entails(value)); // tricky to determine the appropriate validity scope, only support enforcing
+                        model.enforce(or(supported_by_a_line), [prez]);
                     }
-                    assert!(model.entails(value)); // tricky to determine the appropriate validity scope, only support enforcing
-                    model.enforce(or(supported_by_a_line), [prez]);
-                }
-                ConstraintType::Lt => match constraint.variables.as_slice() {
-                    &[a, b] => {
-                        let a: FAtom = a.try_into()?;
-                        let b: FAtom = b.try_into()?;
-                        model.bind(f_lt(a, b), value);
+                    ConstraintType::Lt => match constraint.variables.as_slice() {
+                        &[a, b] => {
+                            let a: FAtom = a.try_into()?;
+                            let b: FAtom = b.try_into()?;
+                            model.bind(f_lt(a, b), value);
+                        }
+                        x => anyhow::bail!("Invalid variable pattern for LT constraint: {:?}", x),
+                    },
+                    ConstraintType::Eq => {
+                        if constraint.variables.len() != 2 {
+                            anyhow::bail!(
+                                "Wrong number of parameters to equality constraint: {}",
+                                constraint.variables.len()
+                            );
+                        }
+                        model.bind(eq(constraint.variables[0], constraint.variables[1]), value);
                     }
-                    x => anyhow::bail!("Invalid variable pattern for LT constraint: {:?}", x),
-                },
-                ConstraintType::Eq => {
-                    if constraint.variables.len() != 2 {
-                        anyhow::bail!(
-                            "Wrong number of parameters to equality constraint: {}",
-                            constraint.variables.len()
-                        );
+                    ConstraintType::Neq => {
+                        if constraint.variables.len() != 2 {
+                            anyhow::bail!(
+                                "Wrong number of parameters to inequality constraint: {}",
+                                constraint.variables.len()
+                            );
+                        }
+                        model.bind(neq(constraint.variables[0], constraint.variables[1]), value);
                     }
-                    model.bind(eq(constraint.variables[0], constraint.variables[1]), value);
-                }
-                ConstraintType::Neq => {
-                    if constraint.variables.len() != 2 {
-                        anyhow::bail!(
-                            "Wrong number of parameters to inequality constraint: {}",
-                            constraint.variables.len()
-                        );
+                    ConstraintType::Duration(duration) => {
+                        // duration constraints are never reified and only hold when the chronicle is present
+                        debug_assert!(constraint.value.is_none());
+                        let ch = &instance.chronicle;
+                        let duration = FAtom::new(IAtom::from(*duration * ch.start.denom), ch.start.denom);
+                        IntervalVar::new(ch.start, duration, ch.end, prez).enforce_consistency(model);
                     }
-                    model.bind(neq(constraint.variables[0], constraint.variables[1]), value);
-                }
-                ConstraintType::Duration(duration) => {
-                    model.bind(eq(instance.chronicle.end, instance.chronicle.start + *duration), value);
-                }
-                ConstraintType::Or => {
-                    let mut disjuncts = Vec::with_capacity(constraint.variables.len());
-                    for v in &constraint.variables {
-                        let disjunct: Lit = Lit::try_from(*v)?;
-                        disjuncts.push(disjunct);
+                    ConstraintType::Or => {
+                        let mut disjuncts = Vec::with_capacity(constraint.variables.len());
+                        for v in &constraint.variables {
+                            let disjunct: Lit = Lit::try_from(*v)?;
+                            disjuncts.push(disjunct);
+                        }
+                        model.bind(or(disjuncts), value)
                     }
-                    model.bind(or(disjuncts), value)
                 }
             }
         }
+
+        for ch in &ctx.pb.chronicles {
+            // chronicle finishes before the horizon and has a non negative duration
+            let mut constraints = vec![
+                f_leq(ch.chronicle.end, ctx.pb.horizon),
+                f_leq(ch.chronicle.start, ch.chronicle.end),
+            ];
+            // enforce temporal coherence between the chronicle and its subtasks
+            for subtask in &ch.chronicle.subtasks {
+                constraints.push(f_leq(subtask.start, subtask.end));
+                constraints.push(f_leq(ch.chronicle.start, subtask.start));
+                constraints.push(f_leq(subtask.end, ch.chronicle.end));
+            }
+            model.enforce_all(constraints, [ch.chronicle.presence]);
+        }
+        model.seal();
+        Ok(())
+    }
+}
+
+/// Each task of a present chronicle is refined by exactly one chronicle, with the same parameters and timepoints.
+pub struct DecompositionEncoding;
+
+impl ConstraintGenerator for DecompositionEncoding {
+    fn name(&self) -> &str {
+        "decomposition"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        add_decomposition_constraints(ctx.pb, &mut ctx.model);
+        Ok(())
+    }
+}
+
+/// Each object of a pool is created iff it is the value of a parameter of a present chronicle.
+pub struct ObjectsEncoding;
+
+impl ConstraintGenerator for ObjectsEncoding {
+    fn name(&self) -> &str {
+        "objects"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        add_object_creation_constraints(ctx.pb, &mut ctx.model);
+        Ok(())
+    }
+}
+
+/// Symmetry breaking between instances of the same template, of the type given by the configuration.
+pub struct SymmetryEncoding;
+
+impl ConstraintGenerator for SymmetryEncoding {
+    fn name(&self) -> &str {
+        "symmetry"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        add_symmetry_breaking(ctx.pb, &mut ctx.model, ctx.config.symmetry_breaking);
+        Ok(())
+    }
+}
+
+/// Encoding of the metric, if any, that sets the objective of the encoded problem (see [add_metric]).
+pub struct MetricEncoding;
+
+impl ConstraintGenerator for MetricEncoding {
+    fn name(&self) -> &str {
+        "metric"
+    }
+
+    fn generate(&self, ctx: &mut EncodingContext) -> Result<()> {
+        ctx.objective = ctx.metric.map(|metric| add_metric(ctx.pb, &mut ctx.model, metric));
+        Ok(())
     }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::solver::{format_plan, solve, SolverResult};
+    use aries::utils::input::Input;
+    use aries_planning::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use aries_planning::parsing::pddl_to_chronicles;
 
-    for ch in &pb.chronicles {
-        let prez = ch.chronicle.presence;
-        // chronicle finishes before the horizon and has a non negative duration
-        model.enforce(f_leq(ch.chronicle.end, pb.horizon), [prez]);
-        model.enforce(f_leq(ch.chronicle.start, ch.chronicle.end), [prez]);
+    /// Parses a PDDL domain and problem into chronicles.
+    pub(crate) fn parse(domain: &str, problem: &str) -> Problem {
+        let dom = parse_pddl_domain(Input::from_string(domain)).unwrap();
+        let pb = parse_pddl_problem(Input::from_string(problem)).unwrap();
+        pddl_to_chronicles(&dom, &pb).unwrap()
+    }
+
+    /// Builds the finite problem with `depth` instances of each action of the PDDL problem.
+    pub(crate) fn finite_problem(domain: &str, problem: &str, depth: u32) -> FiniteProblem {
+        let spec = parse(domain, problem);
+        let mut pb = FiniteProblem {
+            model: spec.context.model.clone(),
+            origin: spec.context.origin(),
+            horizon: spec.context.horizon(),
+            chronicles: spec.chronicles.clone(),
+            object_pools: spec.context.object_pools.clone(),
+        };
+        populate_with_template_instances(&mut pb, &spec, |_| Some(depth)).unwrap();
+        pb
+    }
 
-        // enforce temporal coherence between the chronicle and its subtasks
-        for subtask in &ch.chronicle.subtasks {
-            model.enforce(f_leq(subtask.start, subtask.end), [prez]);
-            model.enforce(f_leq(ch.chronicle.start, subtask.start), [prez]);
-            model.enforce(f_leq(subtask.end, ch.chronicle.end), [prez]);
+    /// Solves the PDDL problem with at most `max_depth` actions, and returns the plan found, if any.
+    pub(crate) fn find_plan(domain: &str, problem: &str, max_depth: u32, config: &PlannerConfig) -> Option<String> {
+        let pb = parse(domain, problem);
+        let nop = |_: &_, _| {};
+        let result = solve(
+            pb,
+            0,
+            max_depth,
+            &[],
+            None,
+            false,
+            config,
+            nop,
+            |_, _: &_| {},
+            nop,
+            None,
+        )
+        .unwrap();
+        match result {
+            SolverResult::Sol((pb, assignment)) => Some(format_plan(&pb, &assignment, false).unwrap()),
+            _ => None,
         }
     }
-    add_decomposition_constraints(pb, &mut model);
-    add_symmetry_breaking(pb, &mut model, symmetry_breaking_tpe);
-    let metric = metric.map(|metric| add_metric(pb, &mut model, metric));
 
-    Ok((model, metric))
+    const LAMP: &str = "(define (domain lamp)
+      (:requirements :strips :typing :negative-preconditions :durative-actions)
+      (:types lamp)
+      (:predicates (plugged ?l - lamp) (lit ?l - lamp))
+      (:durative-action plug :parameters (?l - lamp) :duration (= ?duration 1)
+         :condition (at start (not (plugged ?l)))
+         :effect (at end (plugged ?l)))
+      (:durative-action unplug :parameters (?l - lamp) :duration (= ?duration 1)
+         :condition (at start (plugged ?l))
+         :effect (at end (not (plugged ?l))))
+      (:durative-action shine :parameters (?l - lamp) :duration (= ?duration 5)
+         :condition (and (at start (plugged ?l)) (over all (plugged ?l)))
+         :effect (at end (lit ?l))))";
+
+    const LAMP_PROBLEM: &str = "(define (problem p) (:domain lamp)
+      (:objects a b - lamp)
+      (:init (plugged a))
+      (:goal (and (lit a) (lit b) (not (plugged a)))))";
+
+    /// A classical domain where the two effects of a move on the robot's position are on different rooms.
+    pub(crate) const ROOMS: &str = "(define (domain rooms)
+      (:requirements :strips :typing :equality :negative-preconditions)
+      (:types room)
+      (:predicates (at ?r - room) (visited ?r - room) (moved))
+      (:action move :parameters (?from ?to - room)
+         :precondition (and (at ?from) (not (= ?from ?to)))
+         :effect (and (not (at ?from)) (at ?to) (visited ?to) (moved))))";
+
+    pub(crate) const ROOMS_PROBLEM: &str = "(define (problem p) (:domain rooms)
+      (:objects r1 r2 r3 - room)
+      (:init (at r1))
+      (:goal (and (visited r3) (at r2))))";
+
+    /// Start time and duration of each action of a plan formatted by [format_plan], with the action's name.
+    pub(crate) fn actions(plan: &str) -> Vec<(f64, &str, f64)> {
+        let parse = |x: &str| x.trim().parse::<f64>().unwrap();
+        plan.lines()
+            .filter_map(|line| line.split_once(": "))
+            .map(|(start, action)| {
+                let (name, duration) = action.split_once(" [").unwrap();
+                (parse(start), name, parse(duration.trim_end_matches(']')))
+            })
+            .collect()
+    }
+
+    /// Start time of the last occurrence of the action in a plan formatted by [format_plan].
+    pub(crate) fn start_of(plan: &str, action: &str) -> f64 {
+        let occurrences = actions(plan).into_iter().filter(|&(_, name, _)| name == action);
+        occurrences.map(|(start, _, _)| start).next_back().unwrap()
+    }
+
+    #[test]
+    fn test_over_all_conditions() {
+        let plan = find_plan(LAMP, LAMP_PROBLEM, 4, &PlannerConfig::default()).expect("no plan found");
+        // lamp `a` must stay plugged while it shines
+        assert!(
+            start_of(&plan, "(unplug a)") + 1.0 >= start_of(&plan, "(shine a)") + 5.0,
+            "{plan}"
+        );
+        // lamp `b` must be plugged before it shines
+        assert!(
+            start_of(&plan, "(plug b)") + 1.0 <= start_of(&plan, "(shine b)"),
+            "{plan}"
+        );
+    }
+
+    #[test]
+    fn test_support_time_windows() {
+        let pb = finite_problem(LAMP, LAMP_PROBLEM, 1);
+        let start = pb.chronicles.last().unwrap().chronicle.start;
+        assert!(provably_after(&pb.model, start + FAtom::EPSILON, start));
+        assert!(!provably_after(&pb.model, start, start));
+        assert!(!provably_after(&pb.model, start, pb.origin));
+        let mut model = pb.model.clone();
+        let late = model.new_fvar(5, 10, start.denom, VarLabel(Container::Base, VarType::Reification));
+        assert!(provably_after(&model, late.into(), pb.origin));
+        assert!(!provably_after(&model, late.into(), start));
+
+        // plans are still found, with effects whose persistence starts after the start of their action
+        assert!(find_plan(LAMP, LAMP_PROBLEM, 4, &PlannerConfig::default()).is_some());
+        assert!(find_plan(ROOMS, ROOMS_PROBLEM, 2, &PlannerConfig::default()).is_some());
+    }
+
+    #[test]
+    fn test_causal_links() {
+        let config = PlannerConfig {
+            encoding: EncodingPipeline::new(SupportEncodingType::CausalLinks),
+            ..Default::default()
+        };
+        assert!(find_plan(ROOMS, ROOMS_PROBLEM, 1, &config).is_none());
+        assert!(find_plan(ROOMS, ROOMS_PROBLEM, 2, &config).is_some());
+
+        let plan = find_plan(LAMP, LAMP_PROBLEM, 4, &config).expect("no plan found");
+        assert!(
+            start_of(&plan, "(unplug a)") + 1.0 >= start_of(&plan, "(shine a)") + 5.0,
+            "{plan}"
+        );
+        assert!(
+            start_of(&plan, "(plug b)") + 1.0 <= start_of(&plan, "(shine b)"),
+            "{plan}"
+        );
+    }
+
+    #[test]
+    fn test_interferences() {
+        let pb = finite_problem(LAMP, LAMP_PROBLEM, 1);
+        // a single instance of each action, following the initial chronicle
+        let [plug, unplug, shine] = [1, 2, 3].map(|i| &pb.chronicles[i].chronicle);
+        // effects of both actions on `plugged`, and the effect of each on the condition of the other
+        assert_eq!(interferences(&pb.model, plug, unplug).len(), 3);
+        // effect of plugging on the two conditions of shining on `plugged`, the effect on `lit` being independent
+        assert_eq!(interferences(&pb.model, plug, shine).len(), 2);
+        // effects of both actions on `lit`, the conditions on `plugged` being only read
+        assert_eq!(interferences(&pb.model, shine, shine).len(), 1);
+    }
+
+    #[test]
+    fn test_concurrency_semantics() {
+        for semantics in [ConcurrencySemantics::NoOverlap, ConcurrencySemantics::Sequential] {
+            let config = PlannerConfig {
+                concurrency: semantics,
+                ..Default::default()
+            };
+            let plan = find_plan(LAMP, LAMP_PROBLEM, 4, &config).expect("no plan found");
+            // shining on `a` interferes with unplugging it
+            assert!(
+                start_of(&plan, "(unplug a)") >= start_of(&plan, "(shine a)") + 5.0,
+                "{plan}"
+            );
+            if semantics == ConcurrencySemantics::Sequential {
+                let mut actions = actions(&plan);
+                actions.sort_by(|a, b| a.0.total_cmp(&b.0));
+                for (prev, next) in actions.iter().zip(&actions[1..]) {
+                    assert!(prev.0 + prev.2 <= next.0, "{plan}");
+                }
+            }
+            assert!(find_plan(ROOMS, ROOMS_PROBLEM, 2, &config).is_some());
+        }
+    }
 }
@@ -1,8 +1,13 @@
 //! Functions responsible for
 
-use aries::core::Lit;
-use aries::model::lang::FAtom;
+use crate::Model;
+use aries::core::{IntCst, Lit};
+use aries::model::extensions::AssignmentExt;
+use aries::model::lang::{Atom, FAtom, SAtom};
+use aries_planning::chronicles::constraints::ConstraintType;
 use aries_planning::chronicles::{ChronicleOrigin, ChronicleTemplate, Condition, Effect, FiniteProblem, Problem, Task};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 
 /// Iterator over all effects in an finite problem.
 ///
@@ -21,16 +26,197 @@ pub fn effects(pb: &FiniteProblem) -> impl Iterator<Item = (usize, Lit, &Effect)
 /// Iterates over all conditions in an finite problem.
 ///
 /// Each condition is associated with a literal that is true iff the effect is present in the solution.
+///
+/// Conditions that are implied by another condition of the same chronicle (see [implied_conditions]) are omitted.
+/// This is typically the case of the `at start` and `at end` conditions of a durative action that are repeated
+/// in its `over all` condition: a single support for the `over all` condition is then required.
 pub fn conditions(pb: &FiniteProblem) -> impl Iterator<Item = (Lit, &Condition)> {
     pb.chronicles.iter().flat_map(|ch| {
+        let implied = implied_conditions(&ch.chronicle.conditions);
         ch.chronicle
             .conditions
             .iter()
-            .map(move |cond| (ch.chronicle.presence, cond))
+            .enumerate()
+            .filter(move |(i, _)| !implied.contains(i))
+            .map(move |(_, cond)| (ch.chronicle.presence, cond))
     })
 }
 
+/// Returns the indices of the conditions that are implied by another condition of the set,
+/// i.e., that require the same value of the same state variable on an interval that is statically known to be
+/// contained in the interval of another condition.
+/// Among conditions that are identical, only the first one is considered as not implied.
+fn implied_conditions(conditions: &[Condition]) -> HashSet<usize> {
+    // true if the timepoint `a` is always before or at the same time as `b` (both being on the same variable)
+    let before = |a: FAtom, b: FAtom| a.denom == b.denom && a.num.var == b.num.var && a.num.shift <= b.num.shift;
+    let contains = |outer: &Condition, inner: &Condition| {
+        outer.state_var == inner.state_var
+            && outer.value == inner.value
+            && before(outer.start, inner.start)
+            && before(inner.end, outer.end)
+    };
+    let mut implied = HashSet::new();
+    for (i, inner) in conditions.iter().enumerate() {
+        for (j, outer) in conditions.iter().enumerate() {
+            if i != j && contains(outer, inner) && (j < i || !contains(inner, outer)) {
+                implied.insert(i);
+                break;
+            }
+        }
+    }
+    implied
+}
+
+/// Returns all pairs `(i, j)` (with `i < j`) of effects that might interfere with each other and thus
+/// require coherence constraints. The effects are identified by their index in `effs`, as given by [effects].
+///
+/// All other pairs are statically known to be mutex, because:
+///  - they act on different fluents, or on state variables whose parameters have disjoint domains,
+///  - they appear in the same chronicle instance, which requires two parameters in the same position of their state variables to be different, or
+///  - their presence literals are exclusive or one of them is known to be absent.
+///
+/// Effects are first grouped by fluent (and arity), so that only effects of the same group are ever compared.
+pub fn interfering_effects(pb: &FiniteProblem, effs: &[(usize, Lit, &Effect)], model: &Model) -> Vec<(usize, usize)> {
+    // group effects by arity and fluent
+    let mut groups: HashMap<FluentKey, Vec<usize>> = HashMap::new();
+    for (i, &(_, prez, eff)) in effs.iter().enumerate() {
+        if model.entails(!prez) {
+            continue;
+        }
+        groups.entry(fluent_key(&eff.state_var, model)).or_default().push(i);
+    }
+    let mut candidates = Vec::new();
+    for (&(arity, fluent), group) in &groups {
+        for (k, &i) in group.iter().enumerate() {
+            candidates.extend(group[k + 1..].iter().map(|&j| (i, j)));
+        }
+        // effects whose fluent is not fixed might interfere with any effect of the same arity
+        if fluent.is_some() {
+            if let Some(unknown_fluent) = groups.get(&(arity, None)) {
+                for &i in group {
+                    candidates.extend(unknown_fluent.iter().map(|&j| (i.min(j), i.max(j))));
+                }
+            }
+        }
+    }
+    candidates.sort_unstable();
+
+    // pairs of atoms that are required to be different in each chronicle instance
+    let mut different: HashMap<usize, HashSet<(Atom, Atom)>> = HashMap::new();
+    let mut provably_different = |instance: usize, a: Atom, b: Atom| {
+        different
+            .entry(instance)
+            .or_insert_with(|| {
+                let mut pairs = HashSet::new();
+                for c in &pb.chronicles[instance].chronicle.constraints {
+                    if let (ConstraintType::Neq, None, [x, y]) = (&c.tpe, c.value, c.variables.as_slice()) {
+                        pairs.insert((*x, *y));
+                        pairs.insert((*y, *x));
+                    }
+                }
+                pairs
+            })
+            .contains(&(a, b))
+    };
+
+    candidates.retain(|&(i, j)| {
+        let (instance1, prez1, e1) = effs[i];
+        let (instance2, prez2, e2) = effs[j];
+        if !model.unifiable_seq(&e1.state_var, &e2.state_var) || model.state.exclusive(prez1, prez2) {
+            return false;
+        }
+        let same_instance = instance1 == instance2;
+        !(same_instance
+            && e1
+                .state_var
+                .iter()
+                .zip(&e2.state_var)
+                .any(|(&a, &b)| provably_different(instance1, a.into(), b.into())))
+    });
+    candidates
+}
+
+/// Fluent accessed through a state variable, identified by the arity of the state variable and by the value of its
+/// first element, which is `None` if it is not fixed.
+pub type FluentKey = (usize, Option<IntCst>);
+
+/// Returns the fluent accessed through the state variable.
+pub fn fluent_key(state_var: &[SAtom], model: &Model) -> FluentKey {
+    let fluent = state_var
+        .first()
+        .map(|&fluent| model.int_bounds(fluent))
+        .filter(|(lb, ub)| lb == ub)
+        .map(|(lb, _)| lb);
+    (state_var.len(), fluent)
+}
+
+/// Numbering of the effects and conditions of a problem in which all accesses to the same fluent are contiguous,
+/// so that the loops of the encoding over the effects that may interfere with an access only visit a few ranges
+/// of effects. Within a fluent, effects and conditions keep their order in [effects] and [conditions].
+///
+/// Effects and conditions are sorted by arity, and within an arity those whose fluent is not fixed come first.
+#[derive(Clone, Debug)]
+pub struct FluentLocality {
+    /// For each effect in this numbering, its index in the order of [effects].
+    pub effect_origins: Vec<usize>,
+    /// For each condition in this numbering, its index in the order of [conditions].
+    pub condition_origins: Vec<usize>,
+    /// Range of the effects on each fluent, in this numbering.
+    effect_ranges: HashMap<FluentKey, Range<usize>>,
+    /// Range of the effects of each arity, in this numbering.
+    arity_ranges: HashMap<usize, Range<usize>>,
+}
+
+impl FluentLocality {
+    pub fn new(effs: &[(usize, Lit, &Effect)], conds: &[(Lit, &Condition)], model: &Model) -> Self {
+        let effect_keys: Vec<FluentKey> = effs
+            .iter()
+            .map(|(_, _, eff)| fluent_key(&eff.state_var, model))
+            .collect();
+        let condition_keys: Vec<FluentKey> = conds
+            .iter()
+            .map(|(_, cond)| fluent_key(&cond.state_var, model))
+            .collect();
+        // stable sorts, that preserve the original order within a fluent
+        let mut effect_origins: Vec<usize> = (0..effs.len()).collect();
+        effect_origins.sort_by_key(|&i| effect_keys[i]);
+        let mut condition_origins: Vec<usize> = (0..conds.len()).collect();
+        condition_origins.sort_by_key(|&i| condition_keys[i]);
+
+        let mut effect_ranges: HashMap<FluentKey, Range<usize>> = HashMap::new();
+        let mut arity_ranges: HashMap<usize, Range<usize>> = HashMap::new();
+        for (i, &origin) in effect_origins.iter().enumerate() {
+            let key = effect_keys[origin];
+            effect_ranges.entry(key).or_insert(i..i).end = i + 1;
+            arity_ranges.entry(key.0).or_insert(i..i).end = i + 1;
+        }
+        FluentLocality {
+            effect_origins,
+            condition_origins,
+            effect_ranges,
+            arity_ranges,
+        }
+    }
+
+    /// Effects (in this numbering) that may be on the same fluent as an access to the given fluent, in increasing
+    /// order: those on this fluent and those whose fluent is not fixed, or all effects of the same arity if the
+    /// given fluent is not fixed.
+    pub fn candidate_effects(&self, (arity, fluent): FluentKey) -> impl Iterator<Item = usize> {
+        let range = |r: Option<&Range<usize>>| r.cloned().unwrap_or(0..0);
+        let (first, second) = match fluent {
+            Some(_) => (
+                range(self.effect_ranges.get(&(arity, None))),
+                range(self.effect_ranges.get(&(arity, fluent))),
+            ),
+            None => (range(self.arity_ranges.get(&arity)), 0..0),
+        };
+        first.chain(second)
+    }
+}
+
 pub const ORIGIN: i32 = 0;
+/// Initial upper bound on the end of all effects.
+/// If a problem proves unsolvable only because of this bound, it is doubled and the problem solved again.
 pub const HORIZON: i32 = 999999;
 
 pub struct TaskRef<'a> {
@@ -70,3 +256,77 @@ pub fn refinements_of_task<'a>(task: &Task, pb: &FiniteProblem, spec: &'a Proble
     }
     candidates
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::tests::{find_plan, finite_problem, ROOMS, ROOMS_PROBLEM};
+    use crate::solver::PlannerConfig;
+    use aries_planning::chronicles::{Container, VarLabel, VarType};
+
+    #[test]
+    fn test_interfering_effects() {
+        let pb = finite_problem(ROOMS, ROOMS_PROBLEM, 2);
+        let model = &pb.model;
+        let effs: Vec<_> = effects(&pb).collect();
+        let interfering: HashSet<(usize, usize)> = interfering_effects(&pb, &effs, model).into_iter().collect();
+        let mut pruned_in_same_instance = 0;
+        for i in 0..effs.len() {
+            for j in i + 1..effs.len() {
+                let (instance1, prez1, e1) = effs[i];
+                let (instance2, prez2, e2) = effs[j];
+                let may_interfere = model.unifiable_seq(&e1.state_var, &e2.state_var)
+                    && !model.entails(!prez1)
+                    && !model.entails(!prez2);
+                if interfering.contains(&(i, j)) {
+                    assert!(may_interfere);
+                } else if may_interfere {
+                    // only the effects of a move on its two different rooms are mutex
+                    assert_eq!(instance1, instance2);
+                    pruned_in_same_instance += 1;
+                }
+            }
+        }
+        // `(not (at ?from))` and `(at ?to)` in each of the two instances of `move`
+        assert_eq!(pruned_in_same_instance, 2);
+
+        assert!(find_plan(ROOMS, ROOMS_PROBLEM, 2, &PlannerConfig::default()).is_some());
+    }
+
+    #[test]
+    fn test_candidate_effects() {
+        let pb = finite_problem(ROOMS, ROOMS_PROBLEM, 1);
+        let mut model = pb.model.clone();
+        let mut effs: Vec<_> = effects(&pb).collect();
+        let conds: Vec<_> = conditions(&pb).collect();
+
+        // an effect of the same arity as `at` and `visited`, whose fluent is not fixed
+        let (instance, prez, at) = *effs.iter().find(|(_, _, e)| e.state_var.len() == 2).unwrap();
+        let SAtom::Cst(fluent) = at.state_var[0] else {
+            panic!("fluent of an effect is not fixed")
+        };
+        let label = VarLabel(Container::Base, VarType::Parameter("fluent".to_string()));
+        let mut unknown = at.clone();
+        unknown.state_var[0] = model.new_sym_var(fluent.tpe, label).into();
+        effs.push((instance, prez, &unknown));
+
+        let locality = FluentLocality::new(&effs, &conds, &model);
+        let key = |k: usize| fluent_key(&effs[locality.effect_origins[k]].2.state_var, &model);
+        let mut keys: Vec<FluentKey> = effs.iter().map(|(_, _, e)| fluent_key(&e.state_var, &model)).collect();
+        keys.sort();
+        keys.dedup();
+        // fluents `at` and `visited` and the unknown fluent with a parameter, and `moved` without parameters
+        assert_eq!(keys.iter().filter(|(arity, _)| *arity == 2).count(), 3);
+        assert_eq!(keys.iter().filter(|(arity, _)| *arity == 1).count(), 1);
+        for (arity, fluent) in keys {
+            let candidates: Vec<usize> = locality.candidate_effects((arity, fluent)).collect();
+            let expected: Vec<usize> = (0..effs.len())
+                .filter(|&k| {
+                    let (other_arity, other_fluent) = key(k);
+                    other_arity == arity && (fluent.is_none() || other_fluent.is_none() || other_fluent == fluent)
+                })
+                .collect();
+            assert_eq!(candidates, expected, "{:?}", (arity, fluent));
+        }
+    }
+}
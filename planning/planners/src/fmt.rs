@@ -6,6 +6,7 @@ use std::fmt::Write;
 use crate::Model;
 use aries::model::extensions::{AssignmentExt, SavedAssignment, Shaped};
 use aries::model::lang::SAtom;
+use aries::model::symbols::SymId;
 use aries_planning::chronicles::{ChronicleInstance, ChronicleKind, ChronicleOrigin, FiniteProblem, SubTask};
 
 pub fn format_partial_symbol(x: &SAtom, ass: &Model, out: &mut String) {
@@ -137,6 +138,43 @@ pub fn format_partial_plan(problem: &FiniteProblem, ass: &Model) -> Result<Strin
     Ok(f)
 }
 
+/// Formats a partial assignment of the problem's variables (e.g. reached by a search that timed out), as in [format_partial_plan].
+pub fn format_partial_assignment(problem: &FiniteProblem, ass: &SavedAssignment) -> Result<String> {
+    let mut model = problem.model.clone();
+    model.state = ass.clone();
+    format_partial_plan(problem, &model)
+}
+
+/// Returns the number of actions (i.e. chronicles instantiated from a template) that are present
+/// and the number of actions whose presence is not yet decided in a partial assignment.
+pub fn count_partial_actions(problem: &FiniteProblem, ass: &SavedAssignment) -> (usize, usize) {
+    let actions = problem
+        .chronicles
+        .iter()
+        .filter(|ch| !matches!(ch.origin, ChronicleOrigin::Original));
+    let mut present = 0;
+    let mut undecided = 0;
+    for ch in actions {
+        match ass.boolean_value_of(ch.chronicle.presence) {
+            Some(true) => present += 1,
+            None => undecided += 1,
+            Some(false) => {}
+        }
+    }
+    (present, undecided)
+}
+
+/// Returns the objects of the problem's object pools that are created in the solution.
+pub fn created_objects(problem: &FiniteProblem, ass: &SavedAssignment) -> Vec<SymId> {
+    problem
+        .object_pools
+        .iter()
+        .flat_map(|pool| pool.objects.iter())
+        .filter(|(_, created)| ass.value(*created) == Some(true))
+        .map(|(obj, _)| *obj)
+        .collect()
+}
+
 pub fn format_pddl_plan(problem: &FiniteProblem, ass: &SavedAssignment) -> Result<String> {
     let fmt = |name: &[SAtom]| -> String {
         let syms: Vec<_> = name
@@ -147,9 +185,18 @@ pub fn format_pddl_plan(problem: &FiniteProblem, ass: &SavedAssignment) -> Resul
     };
 
     let mut out = String::new();
+    let created = created_objects(problem, ass);
+    if !created.is_empty() {
+        let created: Vec<_> = created
+            .iter()
+            .map(|&obj| problem.model.shape.symbols.symbol(obj).to_string())
+            .collect();
+        writeln!(out, "; created objects: {}", created.join(" "))?;
+    }
     let mut plan = Vec::new();
     for ch in &problem.chronicles {
-        if ass.value(ch.chronicle.presence) != Some(true) {
+        // actions of the problem itself are already being executed (see `from_execution_state`)
+        if ass.value(ch.chronicle.presence) != Some(true) || ch.origin == ChronicleOrigin::Original {
             continue;
         }
         match ch.chronicle.kind {
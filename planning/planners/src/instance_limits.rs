@@ -0,0 +1,118 @@
+//! Per-template limits on the number of instances of the templates of a non-hierarchical problem, raised only for the
+//! templates whose limit prevented finding a plan.
+//!
+//! Instead of instantiating all templates `depth` times at each step of the iterative deepening, each template is
+//! instantiated up to its own limit, plus one *slack* instance that the solver assumes to be absent.
+//! When the solver proves that no plan exists because of this assumption, the limit of the template was involved in
+//! the final conflict: its slack instance is released (in the same solver) and its limit will be raised at the next
+//! step. The limits of all templates are only raised when none of them is involved.
+
+use crate::encode::instantiate;
+use anyhow::Result;
+use aries::core::state::Domains;
+use aries::core::Lit;
+use aries_planning::chronicles::{ChronicleOrigin, FiniteProblem, Problem, Sub};
+
+/// Usage of the instances of a template.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TemplateUsage {
+    /// Number of instances of the template, excluding its slack instance.
+    pub limit: u32,
+    /// Largest number of instances of the template in the plans found so far.
+    pub max_used: u32,
+    /// Number of times the limit of the template was involved in a proof that no plan exists.
+    pub num_conflicts: u32,
+}
+
+/// Limits on the number of instances of each template of a problem, indexed by template.
+#[derive(Clone, Debug)]
+pub struct InstanceLimits {
+    usages: Vec<TemplateUsage>,
+}
+
+impl InstanceLimits {
+    /// Limits of the templates of the problem, all equal to `initial_limit`.
+    pub fn new(pb: &Problem, initial_limit: u32) -> Self {
+        let usage = TemplateUsage {
+            limit: initial_limit,
+            ..Default::default()
+        };
+        InstanceLimits {
+            usages: vec![usage; pb.templates.len()],
+        }
+    }
+
+    pub fn usages(&self) -> &[TemplateUsage] {
+        &self.usages
+    }
+
+    /// Smallest limit of a template, i.e., a number of actions such that all plans with at most this number of actions
+    /// fit within the limits.
+    pub fn min_limit(&self) -> u32 {
+        self.usages.iter().map(|u| u.limit).min().unwrap_or(u32::MAX)
+    }
+
+    /// Sum of the limits of all templates.
+    pub fn total(&self) -> u32 {
+        self.usages.iter().map(|u| u.limit).fold(0, u32::saturating_add)
+    }
+
+    /// Appends to `pb` the instances of each template of `spec` allowed by its limit, followed by its slack instance.
+    ///
+    /// Returns the presence literals of the slack instances, indexed by template.
+    pub fn populate(&self, pb: &mut FiniteProblem, spec: &Problem) -> Result<Vec<Lit>> {
+        let mut slacks = Vec::with_capacity(spec.templates.len());
+        for (template_id, template) in spec.templates.iter().enumerate() {
+            let limit = self.usages[template_id].limit as usize;
+            for generation_id in 0..=limit {
+                let origin = ChronicleOrigin::FreeAction {
+                    template_id,
+                    generation_id,
+                };
+                let instance_id = pb.chronicles.len();
+                let instance = instantiate(instance_id, template, origin, Lit::TRUE, Sub::empty(), pb)?;
+                if generation_id == limit {
+                    slacks.push(instance.chronicle.presence);
+                }
+                pb.chronicles.push(instance);
+            }
+        }
+        Ok(slacks)
+    }
+
+    /// Records the number of instances of each template that are present in a plan.
+    pub fn record_plan(&mut self, pb: &FiniteProblem, plan: &Domains) {
+        let mut used = vec![0; self.usages.len()];
+        for ch in &pb.chronicles {
+            if let ChronicleOrigin::FreeAction { template_id, .. } = ch.origin {
+                if plan.entails(ch.chronicle.presence) {
+                    used[template_id] += 1;
+                }
+            }
+        }
+        for (usage, used) in self.usages.iter_mut().zip(used) {
+            usage.max_used = usage.max_used.max(used);
+        }
+    }
+
+    /// Records that the limit of the template was involved in the proof that no plan exists.
+    pub fn record_conflict(&mut self, template_id: usize) {
+        self.usages[template_id].num_conflicts += 1;
+    }
+
+    /// Raises the limits after a proof that no plan exists within them, where `involved` are the templates whose limit
+    /// was involved in the proof. Only the limits of these templates are raised (by one, their slack instance being
+    /// already available), unless there is none in which case all limits are raised.
+    pub fn raise(&mut self, involved: &[usize]) {
+        if involved.is_empty() {
+            for usage in &mut self.usages {
+                usage.limit = usage.limit.saturating_add(1);
+            }
+        } else {
+            for &template_id in involved {
+                let usage = &mut self.usages[template_id];
+                usage.limit = usage.limit.saturating_add(1);
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use aries_planning::chronicles::VarLabel;
 
+pub mod deorder;
 pub mod encode;
 pub mod encoding;
 pub mod fmt;
 pub mod forward_search;
+pub mod instance_limits;
+pub mod postprocess;
 pub mod solver;
 
 pub type Model = aries::model::Model<VarLabel>;
@@ -0,0 +1,144 @@
+//! Post-processing of the plans found by the planner: the actions of a plan and their parameters are kept
+//! but the plan is re-solved to improve it, either by removing the actions that are not needed or by rescheduling
+//! the actions to minimize the makespan.
+use crate::solver::{atom_var, init_solver, Metric, PlannerConfig};
+use crate::ParSolver;
+use aries::core::state::Domains;
+use aries::core::{Lit, VarRef};
+use aries::model::lang::Atom;
+use aries::solver::parallel::SolverResult;
+use aries_planning::chronicles::{ChronicleKind, FiniteProblem};
+use env_param::EnvParam;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Default value of [PostProcessingConfig::remove_redundant_actions].
+static REMOVE_REDUNDANT_ACTIONS: EnvParam<bool> = EnvParam::new("ARIES_LCP_REMOVE_REDUNDANT_ACTIONS", "false");
+
+/// Default value of [PostProcessingConfig::reschedule].
+static RESCHEDULE: EnvParam<bool> = EnvParam::new("ARIES_LCP_RESCHEDULE", "false");
+
+/// Post-processing steps applied to the plan returned by the planner.
+///
+/// All steps are disabled by default, unless enabled by their environment parameter
+/// (e.g. `ARIES_LCP_RESCHEDULE=true`).
+#[derive(Clone, Debug)]
+pub struct PostProcessingConfig {
+    /// Removes, one at a time, the actions of the plan without which the plan can still be made valid.
+    pub remove_redundant_actions: bool,
+    /// Reschedules the actions of the plan to minimize its makespan.
+    pub reschedule: bool,
+}
+
+impl Default for PostProcessingConfig {
+    fn default() -> Self {
+        PostProcessingConfig {
+            remove_redundant_actions: REMOVE_REDUNDANT_ACTIONS.get(),
+            reschedule: RESCHEDULE.get(),
+        }
+    }
+}
+
+/// Applies the post-processing steps enabled in the configuration to the plan, returning the improved plan.
+///
+/// Each step is a search in which the actions of the plan are fixed, that is abandoned at the `deadline`,
+/// in which case the plan is returned as it was before the step.
+pub fn post_process(
+    pb: &FiniteProblem,
+    plan: Arc<Domains>,
+    config: &PlannerConfig,
+    deadline: Option<Instant>,
+) -> Arc<Domains> {
+    let mut plan = plan;
+    if config.post_processing.remove_redundant_actions {
+        plan = remove_redundant_actions(pb, plan, config, deadline);
+    }
+    if config.post_processing.reschedule {
+        plan = reschedule(pb, plan, config, deadline);
+    }
+    plan
+}
+
+/// Removes the actions of the plan that are not needed for the plan to be valid.
+///
+/// Actions are considered in turn, each being removed if the remaining ones (with the same parameters) can be scheduled
+/// into a valid plan. The resulting plan thus contains no action that could be removed on its own.
+pub fn remove_redundant_actions(
+    pb: &FiniteProblem,
+    plan: Arc<Domains>,
+    config: &PlannerConfig,
+    deadline: Option<Instant>,
+) -> Arc<Domains> {
+    let mut plan = plan;
+    for (instance_id, ch) in pb.chronicles.iter().enumerate() {
+        let is_action = matches!(ch.chronicle.kind, ChronicleKind::Action | ChronicleKind::DurativeAction);
+        if !is_action || plan.value(ch.chronicle.presence) != Some(true) {
+            continue;
+        }
+        if let Some(smaller_plan) = solve_with_fixed_actions(pb, &plan, Some(instance_id), None, config, deadline) {
+            println!("  Removed redundant action {instance_id}");
+            plan = smaller_plan;
+        }
+    }
+    plan
+}
+
+/// Reschedules the actions of the plan, with the same parameters, so that the makespan of the plan is minimal.
+pub fn reschedule(
+    pb: &FiniteProblem,
+    plan: Arc<Domains>,
+    config: &PlannerConfig,
+    deadline: Option<Instant>,
+) -> Arc<Domains> {
+    solve_with_fixed_actions(pb, &plan, None, Some(Metric::Makespan), config, deadline).unwrap_or(plan)
+}
+
+/// Solves the problem where the presence and the non-temporal parameters of all chronicles are fixed to their values
+/// in the plan, except for the `removed` chronicle that is made absent.
+/// Only the temporal variables of the chronicles are left to the solver, that minimizes the `metric` if one is given.
+///
+/// Returns `None` if no solution could be found before the deadline.
+fn solve_with_fixed_actions(
+    pb: &FiniteProblem,
+    plan: &Domains,
+    removed: Option<usize>,
+    metric: Option<Metric>,
+    config: &PlannerConfig,
+    deadline: Option<Instant>,
+) -> Option<Arc<Domains>> {
+    let mut fixed: Vec<Lit> = Vec::with_capacity(64);
+    for (instance_id, ch) in pb.chronicles.iter().enumerate() {
+        let prez = ch.chronicle.presence;
+        if removed == Some(instance_id) || plan.value(prez) != Some(true) {
+            fixed.push(!prez);
+            continue;
+        }
+        fixed.push(prez);
+        for &param in &ch.parameters {
+            let var = atom_var(param);
+            if matches!(param, Atom::Fixed(_)) || var == VarRef::ZERO || plan.present(var) != Some(true) {
+                continue; // temporal or constant parameter
+            }
+            let (lb, ub) = plan.bounds(var);
+            fixed.push(Lit::geq(var, lb));
+            fixed.push(Lit::leq(var, ub));
+        }
+    }
+
+    // the solver is given the fixed values as assumptions, which replace the bound on the end of the effects
+    // (the plan may have required to extend it)
+    let (mut solver, objective, _) = init_solver(pb, metric, config);
+    solver.set_assumptions(fixed);
+    let mut solver = ParSolver::new(solver, 1, |_, _| {});
+    if let Some(interrupt) = &config.interrupt {
+        solver.set_interrupt_flag(interrupt.clone());
+    }
+    let result = match objective {
+        Some(objective) => solver.minimize(objective, deadline),
+        None => solver.solve(deadline),
+    };
+    match result {
+        SolverResult::Sol(solution) | SolverResult::Timeout(Some(solution)) => Some(solution),
+        SolverResult::Unsat | SolverResult::Timeout(None) => None,
+    }
+}
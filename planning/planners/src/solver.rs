@@ -1,35 +1,167 @@
-use crate::encode::{encode, populate_with_task_network, populate_with_template_instances};
+use crate::encode::{
+    encode, populate_with_task_network, populate_with_template_instances, ConcurrencySemantics, EncodingPipeline,
+    SymmetryBreakingType, CONCURRENCY, SYMMETRY_BREAKING,
+};
+use crate::encoding::HORIZON;
 use crate::fmt::{format_hddl_plan, format_partial_plan, format_pddl_plan};
 use crate::forward_search::ForwardSearcher;
+use crate::instance_limits::InstanceLimits;
+use crate::postprocess::{post_process, PostProcessingConfig};
 use crate::Solver;
-use anyhow::Result;
-use aries::core::state::Domains;
-use aries::core::VarRef;
+use anyhow::{ensure, Result};
+use aries::core::state::{Cause, Domains};
+use aries::core::{IntCst, Lit, VarRef, INT_CST_MAX};
+use aries::model::equivalences::{merge_equivalent_literals, merge_equivalent_scopes};
 use aries::model::extensions::SavedAssignment;
-use aries::model::lang::IAtom;
+use aries::model::fixed_variables::eliminate_fixed_variables;
+use aries::model::lang::{Atom, IAtom, IVar};
+use aries::model::symmetry::break_symmetries;
 use aries::reasoners::stn::theory::{StnConfig, TheoryPropagationLevel};
+use aries::solver::parallel::signals::Progress;
 use aries::solver::parallel::Solution;
 use aries::solver::search::activity::*;
+use aries::solver::search::combinators::LimitedDiscrepancy;
+use aries::solver::search::default_brancher;
+use aries_planning::chronicles::analysis::{
+    causal_graph_decomposition, format_goal, goal_orders, CausalGraphDecomposition,
+};
+use aries_planning::chronicles::preprocessing::{preprocess_with, PreprocessingConfig};
 use aries_planning::chronicles::printer::Printer;
 use aries_planning::chronicles::Problem;
 use aries_planning::chronicles::*;
+use aries_planning::classical::search::plan_exists_upto;
+use aries_planning::classical::{from_chronicles, grounded_problem, GroundProblem};
 use env_param::EnvParam;
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Instant;
 
-/// If set to true, prints the result of the initial propagation at each depth.
+/// Default value of [PlannerConfig::print_initial_propagation].
 static PRINT_INITIAL_PROPAGATION: EnvParam<bool> = EnvParam::new("ARIES_PRINT_INITIAL_PROPAGATION", "false");
 
+/// Default value of [PlannerConfig::factored_solving].
+static FACTORED_SOLVING: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_FACTORED", "false");
+
+/// Default value of [PlannerConfig::merge_equivalent_literals].
+static MERGE_EQUIVALENT_LITERALS: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_MERGE_EQUIVALENT_LITERALS", "true");
+
+/// Default value of [PlannerConfig::merge_equivalent_scopes].
+static MERGE_EQUIVALENT_SCOPES: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_MERGE_EQUIVALENT_SCOPES", "true");
+
+/// Default value of [PlannerConfig::eliminate_fixed_variables].
+static ELIMINATE_FIXED_VARIABLES: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_ELIMINATE_FIXED_VARIABLES", "true");
+
+/// Default value of [PlannerConfig::break_symmetries].
+static BREAK_SYMMETRIES: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_BREAK_SYMMETRIES", "false");
+
+/// Default value of [PlannerConfig::adaptive_instance_limits].
+static ADAPTIVE_INSTANCE_LIMITS: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_ADAPTIVE_INSTANCE_LIMITS", "false");
+
+/// Default value of [PlannerConfig::check_unsat].
+static CHECK_UNSAT: EnvParam<usize> = EnvParam::new("ARIES_PLANNING_CHECK_UNSAT", "0");
+
+/// Default value of [PlannerConfig::dump_model]: the model is dumped if `ARIES_PLANNING_DUMP_MODEL` is set,
+/// restricted to the variables whose label contains `ARIES_PLANNING_DUMP_MODEL_FILTER` (if not empty).
+static DUMP_MODEL: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_DUMP_MODEL", "false");
+static DUMP_MODEL_FILTER: EnvParam<String> = EnvParam::new("ARIES_PLANNING_DUMP_MODEL_FILTER", "");
+
 pub type SolverResult<Sol> = aries::solver::parallel::SolverResult<Sol>;
 
+/// Configuration of the planner, passed explicitly to the planning functions so that differently configured
+/// planners can coexist in the same process.
+///
+/// The default configuration is read from the environment parameters (e.g. `ARIES_LCP_SYMMETRY_BREAKING`).
+#[derive(Clone, Debug)]
+pub struct PlannerConfig {
+    /// Preprocessing steps applied to the problem before solving it.
+    pub preprocessing: PreprocessingConfig,
+    /// Passes encoding the problem into constraints, that can be removed or replaced to customize the encoding.
+    pub encoding: EncodingPipeline,
+    /// Symmetry breaking constraints added to the encoding of the problem.
+    pub symmetry_breaking: SymmetryBreakingType,
+    /// Semantics of the concurrent execution of actions, e.g. to only accept sequential plans.
+    pub concurrency: ConcurrencySemantics,
+    /// If true, non-hierarchical problems whose causal graph has several components are solved component by component.
+    pub factored_solving: bool,
+    /// If true, the boolean variables of the encoding that are equivalent at the root are merged before solving.
+    pub merge_equivalent_literals: bool,
+    /// If true, the scopes of the encoding that are equivalent at the root (e.g. the presences of a task and of its
+    /// only possible refinement) are merged before solving.
+    pub merge_equivalent_scopes: bool,
+    /// If true, the variables of the encoding that are fixed by propagation at the root are replaced by their value
+    /// in the constraints before solving.
+    pub eliminate_fixed_variables: bool,
+    /// If true, the interchangeable variables of the encoding are detected and ordered to break the corresponding
+    /// symmetries (see [aries::model::symmetry::break_symmetries]), in addition to the ones of [Self::symmetry_breaking].
+    pub break_symmetries: bool,
+    /// If true, the iterative deepening of non-hierarchical problems only raises the number of instances of the
+    /// templates whose limit prevented finding a plan (see [InstanceLimits]), instead of those of all templates.
+    pub adaptive_instance_limits: bool,
+    /// If true, prints the result of the initial propagation at each depth.
+    pub print_initial_propagation: bool,
+    /// If set, prints the encoded model before solving (see [aries::model::Model::dump_pretty]), restricted to the variables
+    /// whose label contains the given string (if not empty) and to their constraints.
+    pub dump_model: Option<String>,
+    /// Post-processing steps applied to the plan found.
+    pub post_processing: PostProcessingConfig,
+    /// If positive, each time no plan is found with some number of actions `n`, a breadth-first search of the
+    /// grounded problem (see [aries_planning::classical::search::plan_exists_upto]) checks that there is indeed no
+    /// plan of `min(n, check_unsat)` steps, and an error is returned otherwise.
+    /// Only meant to debug the encoding on small classical instances.
+    pub check_unsat: usize,
+    /// If set, raising this flag (e.g. to cancel a request of a planning service) stops the planner as if its deadline
+    /// was reached: the best plan found so far, if any, is returned as on a timeout.
+    pub interrupt: Option<Arc<AtomicBool>>,
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        PlannerConfig {
+            preprocessing: PreprocessingConfig::default(),
+            encoding: EncodingPipeline::default(),
+            symmetry_breaking: SYMMETRY_BREAKING.get(),
+            concurrency: CONCURRENCY.get(),
+            factored_solving: FACTORED_SOLVING.get(),
+            merge_equivalent_literals: MERGE_EQUIVALENT_LITERALS.get(),
+            merge_equivalent_scopes: MERGE_EQUIVALENT_SCOPES.get(),
+            eliminate_fixed_variables: ELIMINATE_FIXED_VARIABLES.get(),
+            break_symmetries: BREAK_SYMMETRIES.get(),
+            adaptive_instance_limits: ADAPTIVE_INSTANCE_LIMITS.get(),
+            print_initial_propagation: PRINT_INITIAL_PROPAGATION.get(),
+            dump_model: DUMP_MODEL.get().then(|| DUMP_MODEL_FILTER.get_ref().clone()),
+            post_processing: PostProcessingConfig::default(),
+            check_unsat: CHECK_UNSAT.get(),
+            interrupt: None,
+        }
+    }
+}
+
+/// A plan, given as a finite problem and an assignment of its variables.
+type Plan = (Arc<FiniteProblem>, Arc<Domains>);
+
 #[derive(Copy, Clone, Debug)]
 pub enum Metric {
     Makespan,
     /// Number of actions in the plan
     PlanLength,
-    /// Sum of all chronicle costs
+    /// Sum of all chronicle costs, that may be negative
     ActionCosts,
+    /// Value of an integer expression at the end of the plan, to be either minimized or maximized.
+    FinalValue {
+        value: IAtom,
+        maximize: bool,
+    },
+}
+
+impl Metric {
+    /// Returns true if the metric should be maximized, and false if it should be minimized.
+    pub fn is_maximization(self) -> bool {
+        matches!(self, Metric::FinalValue { maximize: true, .. })
+    }
 }
 
 impl FromStr for Metric {
@@ -57,6 +189,12 @@ impl FromStr for Metric {
 ///
 /// When a plan is found, the solver returns the corresponding subproblem and the instantiation of
 /// its variables.
+///
+/// While running, the periodic progress reports of the underlying solvers are passed to `on_progress`,
+/// together with the index of the reporting solver.
+///
+/// If the search times out without finding any plan, the deepest consistent partial assignment reached by the solvers
+/// is passed to `on_partial_plan`, together with the subproblem it belongs to, for diagnostic purposes.
 #[allow(clippy::too_many_arguments)]
 pub fn solve(
     mut base_problem: Problem,
@@ -65,12 +203,70 @@ pub fn solve(
     strategies: &[Strat],
     metric: Option<Metric>,
     htn_mode: bool,
+    config: &PlannerConfig,
     on_new_sol: impl Fn(&FiniteProblem, Arc<SavedAssignment>) + Clone,
+    on_progress: impl Fn(usize, &Progress) + Clone + Send + 'static,
+    on_partial_plan: impl Fn(&FiniteProblem, Arc<SavedAssignment>) + Clone,
     deadline: Option<Instant>,
 ) -> Result<SolverResult<(Arc<FiniteProblem>, Arc<Domains>)>> {
+    let oracle = if config.check_unsat > 0 && !htn_mode {
+        unsat_oracle(&base_problem)
+    } else {
+        None
+    };
     println!("===== Preprocessing ======");
-    aries_planning::chronicles::preprocessing::preprocess(&mut base_problem);
+    preprocess_with(&mut base_problem, &config.preprocessing);
     println!("==========================");
+    let orders = goal_orders(&base_problem);
+    if !orders.is_empty() {
+        println!("Goal orders:");
+        for order in &orders {
+            let model = &base_problem.context.model;
+            let first = format_goal(order.first, &base_problem.chronicles, model);
+            let second = format_goal(order.second, &base_problem.chronicles, model);
+            println!("  {first} < {second}");
+        }
+    }
+
+    if !htn_mode && config.factored_solving {
+        let decomposition = causal_graph_decomposition(&base_problem);
+        if decomposition.num_components() > 1 {
+            let result = solve_factored(
+                &base_problem,
+                &decomposition,
+                min_depth,
+                max_depth,
+                strategies,
+                metric,
+                config,
+                on_new_sol.clone(),
+                on_progress.clone(),
+                on_partial_plan.clone(),
+                deadline,
+            )?;
+            if let Some(result) = result {
+                return Ok(post_process_result(result, config, deadline));
+            }
+            println!("Could not merge the plans of the components, solving the problem as a whole");
+        }
+    }
+
+    if !htn_mode && config.adaptive_instance_limits {
+        let result = solve_with_instance_limits(
+            &base_problem,
+            min_depth,
+            max_depth,
+            strategies,
+            metric,
+            config,
+            on_new_sol,
+            on_progress,
+            on_partial_plan,
+            deadline,
+            oracle.as_ref(),
+        )?;
+        return Ok(post_process_result(result, config, deadline));
+    }
 
     let start = Instant::now();
     for depth in min_depth..=max_depth {
@@ -79,6 +275,7 @@ pub fn solve(
             origin: base_problem.context.origin(),
             horizon: base_problem.context.horizon(),
             chronicles: base_problem.chronicles.clone(),
+            object_pools: base_problem.context.object_pools.clone(),
         };
         let depth_string = if depth == u32::MAX {
             "∞".to_string()
@@ -99,30 +296,440 @@ pub fn solve(
             move |ass: Arc<SavedAssignment>| on_new_sol(&pb, ass)
         };
         println!("  [{:.3}s] Populated", start.elapsed().as_secs_f32());
-        let result = solve_finite_problem(&pb, strategies, metric, htn_mode, on_new_valid_assignment, deadline);
+        let result = solve_finite_problem(
+            &pb,
+            strategies,
+            metric,
+            htn_mode,
+            config,
+            &[],
+            |_| {},
+            on_new_valid_assignment,
+            on_progress.clone(),
+            |ass| on_partial_plan(&pb, ass),
+            deadline,
+        );
         println!("  [{:.3}s] Solved", start.elapsed().as_secs_f32());
 
         let result = result.map(|assignment| (pb, assignment));
         match result {
-            SolverResult::Unsat => {} // continue (increase depth)
-            other => return Ok(other),
+            SolverResult::Unsat => {
+                if let Some(oracle) = &oracle {
+                    let steps = config.check_unsat.min(depth as usize);
+                    ensure!(
+                        !plan_exists_upto(oracle, steps),
+                        "No plan found with {depth} actions, but the grounded problem has a plan of at most {steps} steps"
+                    );
+                }
+                // continue (increase depth)
+            }
+            other => return Ok(post_process_result(other, config, deadline)),
         }
     }
     Ok(SolverResult::Unsat)
 }
 
+/// Iterative deepening where each template has its own limit on its number of instances (see [InstanceLimits]),
+/// only raised for the templates whose limit was involved in the proof that no plan exists.
+#[allow(clippy::too_many_arguments)]
+fn solve_with_instance_limits(
+    base_problem: &Problem,
+    min_depth: u32,
+    max_depth: u32,
+    strategies: &[Strat],
+    metric: Option<Metric>,
+    config: &PlannerConfig,
+    on_new_sol: impl Fn(&FiniteProblem, Arc<SavedAssignment>) + Clone,
+    on_progress: impl Fn(usize, &Progress) + Clone + Send + 'static,
+    on_partial_plan: impl Fn(&FiniteProblem, Arc<SavedAssignment>),
+    deadline: Option<Instant>,
+    oracle: Option<&GroundProblem>,
+) -> Result<SolverResult<Plan>> {
+    let start = Instant::now();
+    let limits = RefCell::new(InstanceLimits::new(base_problem, min_depth));
+    while limits.borrow().min_limit() <= max_depth {
+        let mut pb = FiniteProblem {
+            model: base_problem.context.model.clone(),
+            origin: base_problem.context.origin(),
+            horizon: base_problem.context.horizon(),
+            chronicles: base_problem.chronicles.clone(),
+            object_pools: base_problem.context.object_pools.clone(),
+        };
+        let slacks = limits.borrow().populate(&mut pb, base_problem)?;
+        // When the assumptions on several slack instances are together inconsistent, the last one to be decided
+        // is the one reported. The templates with the smallest limits are thus assumed last, to be raised first.
+        let mut order: Vec<usize> = (0..slacks.len()).collect();
+        order.sort_by_key(|&t| Reverse(limits.borrow().usages()[t].limit));
+        let slacks: Vec<Lit> = order.iter().map(|&t| slacks[t]).collect();
+        println!(
+            "{} Solving with at most {} actions (at least {} per template)",
+            limits.borrow().total(),
+            limits.borrow().total(),
+            limits.borrow().min_limit()
+        );
+        let pb = Arc::new(pb);
+
+        let on_new_valid_assignment = |ass: Arc<SavedAssignment>| {
+            limits.borrow_mut().record_plan(&pb, &ass);
+            on_new_sol(&pb, ass)
+        };
+        let mut involved = Vec::new();
+        println!("  [{:.3}s] Populated", start.elapsed().as_secs_f32());
+        let result = solve_finite_problem(
+            &pb,
+            strategies,
+            metric,
+            false,
+            config,
+            &slacks,
+            |i| {
+                limits.borrow_mut().record_conflict(order[i]);
+                involved.push(order[i]);
+            },
+            on_new_valid_assignment,
+            on_progress.clone(),
+            |ass| on_partial_plan(&pb, ass),
+            deadline,
+        );
+        println!("  [{:.3}s] Solved", start.elapsed().as_secs_f32());
+
+        match result {
+            SolverResult::Unsat => {
+                let steps = limits.borrow().min_limit();
+                if let Some(oracle) = oracle {
+                    let steps = config.check_unsat.min(steps as usize);
+                    ensure!(
+                        !plan_exists_upto(oracle, steps),
+                        "No plan found with {steps} actions per template, but the grounded problem has a plan of at most {steps} steps"
+                    );
+                }
+                if !involved.is_empty() {
+                    let names: Vec<String> = involved.iter().map(|&t| template_name(base_problem, t)).collect();
+                    println!("  Raising the limits of: {}", names.join(", "));
+                }
+                limits.borrow_mut().raise(&involved);
+            }
+            other => {
+                if let SolverResult::Sol(ass) | SolverResult::Timeout(Some(ass)) = &other {
+                    limits.borrow_mut().record_plan(&pb, ass);
+                }
+                println!("Instances (limit / used / conflicts):");
+                for (t, usage) in limits.borrow().usages().iter().enumerate() {
+                    println!(
+                        "  {:>3} / {:>3} / {:>3}  {}",
+                        usage.limit,
+                        usage.max_used,
+                        usage.num_conflicts,
+                        template_name(base_problem, t)
+                    );
+                }
+                return Ok(other.map(|assignment| (pb, assignment)));
+            }
+        }
+    }
+    Ok(SolverResult::Unsat)
+}
+
+/// Name of a template, for display purposes.
+fn template_name(pb: &Problem, template_id: usize) -> String {
+    let template = &pb.templates[template_id];
+    match &template.label {
+        Some(label) => label.clone(),
+        None => format!("template {template_id}"),
+    }
+}
+
+/// Grounds the problem, to check the absence of plans found by the planner (see [PlannerConfig::check_unsat]).
+/// Returns `None` if the problem cannot be grounded (e.g. because it is temporal).
+fn unsat_oracle(pb: &Problem) -> Option<GroundProblem> {
+    match from_chronicles(pb).and_then(|lifted| grounded_problem(&lifted)) {
+        Ok(grounded) => Some(grounded),
+        Err(e) => {
+            println!("Absence of plans will not be checked, the problem cannot be grounded: {e}");
+            None
+        }
+    }
+}
+
+/// Applies the post-processing steps of the configuration to the plan of the result, if any.
+fn post_process_result(
+    result: SolverResult<Plan>,
+    config: &PlannerConfig,
+    deadline: Option<Instant>,
+) -> SolverResult<Plan> {
+    result.map(|(pb, plan)| {
+        let plan = post_process(&pb, plan, config, deadline);
+        (pb, plan)
+    })
+}
+
+/// Builds a finite problem with the given chronicles, where each template is instantiated `depth(c)` times
+/// if it belongs to the component `c` of the causal graph.
+fn populate_components(
+    base_problem: &Problem,
+    decomposition: &CausalGraphDecomposition,
+    chronicles: Vec<ChronicleInstance>,
+    depth: impl Fn(usize) -> u32,
+) -> Result<FiniteProblem> {
+    let mut pb = FiniteProblem {
+        model: base_problem.context.model.clone(),
+        origin: base_problem.context.origin(),
+        horizon: base_problem.context.horizon(),
+        chronicles,
+        object_pools: base_problem.context.object_pools.clone(),
+    };
+    populate_with_template_instances(&mut pb, base_problem, |t| Some(depth(component_of(decomposition, t))))?;
+    Ok(pb)
+}
+
+/// Component of the causal graph in which the template is instantiated.
+/// Templates without conditions nor effects do not interact with any component and are attached to the first one.
+fn component_of(decomposition: &CausalGraphDecomposition, template: &ChronicleTemplate) -> usize {
+    decomposition.component_of_template(template).unwrap_or(0)
+}
+
+/// Deadline for the next of `remaining` steps that equally share the time left until the `deadline`.
+fn share_of_deadline(deadline: Option<Instant>, remaining: usize) -> Option<Instant> {
+    deadline.map(|deadline| {
+        let now = Instant::now();
+        now + deadline.saturating_duration_since(now) / remaining.max(1) as u32
+    })
+}
+
+/// Solves each component of the causal graph independently, with its own number of actions, and merges their plans.
+///
+/// Since the components do not share any state function, the plans can be executed concurrently
+/// and the merged plan is optimal if the plan of each component is.
+///
+/// The components are solved one after the other, each with an equal share of the time left until the `deadline`.
+/// A first plan is searched for each component, ignoring the metric, to determine its number of actions.
+/// With a metric, the plan of each component is then optimized in turn. Each merged plan, starting from the first one,
+/// is passed to `on_new_sol`.
+///
+/// Returns `None` if the plans of the components could not be merged, in which case the problem should be solved as a whole.
+#[allow(clippy::too_many_arguments)]
+fn solve_factored(
+    base_problem: &Problem,
+    decomposition: &CausalGraphDecomposition,
+    min_depth: u32,
+    max_depth: u32,
+    strategies: &[Strat],
+    metric: Option<Metric>,
+    config: &PlannerConfig,
+    on_new_sol: impl Fn(&FiniteProblem, Arc<SavedAssignment>),
+    on_progress: impl Fn(usize, &Progress) + Clone + Send + 'static,
+    on_partial_plan: impl Fn(&FiniteProblem, Arc<SavedAssignment>),
+    deadline: Option<Instant>,
+) -> Result<Option<SolverResult<Plan>>> {
+    let start = Instant::now();
+    let num_components = decomposition.num_components();
+    println!("Causal graph decomposes into {num_components} components");
+
+    let mut depths = Vec::with_capacity(num_components);
+    let mut problems = Vec::with_capacity(num_components);
+    let mut assignments = Vec::with_capacity(num_components);
+    let mut timeout = false;
+    for component in 0..num_components {
+        let component_deadline = share_of_deadline(deadline, num_components - component);
+        let chronicles: Vec<ChronicleInstance> = base_problem
+            .chronicles
+            .iter()
+            .map(|ch| ChronicleInstance {
+                chronicle: decomposition.restrict(&ch.chronicle, component),
+                ..ch.clone()
+            })
+            .collect();
+        let mut solution = None;
+        for depth in min_depth..=max_depth {
+            let pb = populate_components(base_problem, decomposition, chronicles.clone(), |c| {
+                if c == component {
+                    depth
+                } else {
+                    0
+                }
+            })?;
+            println!(
+                "Component {}/{num_components}: solving with {depth} actions",
+                component + 1
+            );
+            let result = solve_finite_problem(
+                &pb,
+                strategies,
+                None,
+                false,
+                config,
+                &[],
+                |_| {},
+                |_| {},
+                on_progress.clone(),
+                |ass| on_partial_plan(&pb, ass),
+                component_deadline,
+            );
+            println!("  [{:.3}s] Solved", start.elapsed().as_secs_f32());
+            match result {
+                SolverResult::Unsat => {} // continue (increase depth)
+                SolverResult::Sol(assignment) => {
+                    solution = Some((depth, pb, assignment));
+                    break;
+                }
+                SolverResult::Timeout(Some(assignment)) => {
+                    timeout = true;
+                    solution = Some((depth, pb, assignment));
+                    break;
+                }
+                SolverResult::Timeout(None) => return Ok(Some(SolverResult::Timeout(None))),
+            }
+        }
+        let Some((depth, pb, assignment)) = solution else {
+            return Ok(Some(SolverResult::Unsat));
+        };
+        depths.push(depth);
+        problems.push(pb);
+        assignments.push(assignment);
+    }
+
+    let Some(mut merged) = merge_component_plans(base_problem, decomposition, &depths, &problems, &assignments)? else {
+        return Ok(None);
+    };
+    on_new_sol(&merged.0, merged.1.clone());
+
+    if let Some(metric) = metric {
+        for component in 0..num_components {
+            if timeout {
+                break;
+            }
+            let component_deadline = share_of_deadline(deadline, num_components - component);
+            println!("Component {}/{num_components}: optimizing", component + 1);
+            // each improvement of the component is merged with the current plans of the other components
+            let on_improvement = |assignment: Arc<SavedAssignment>| {
+                let mut current = assignments.clone();
+                current[component] = assignment;
+                if let Ok(Some((pb, plan))) =
+                    merge_component_plans(base_problem, decomposition, &depths, &problems, &current)
+                {
+                    on_new_sol(&pb, plan);
+                }
+            };
+            let pb = &problems[component];
+            let result = solve_finite_problem(
+                pb,
+                strategies,
+                Some(metric),
+                false,
+                config,
+                &[],
+                |_| {},
+                on_improvement,
+                on_progress.clone(),
+                |ass| on_partial_plan(pb, ass),
+                component_deadline,
+            );
+            println!("  [{:.3}s] Optimized", start.elapsed().as_secs_f32());
+            match result {
+                SolverResult::Sol(assignment) => assignments[component] = assignment,
+                SolverResult::Timeout(Some(assignment)) => {
+                    timeout = true;
+                    assignments[component] = assignment;
+                }
+                SolverResult::Timeout(None) => timeout = true,
+                SolverResult::Unsat => {}
+            }
+        }
+        if let Some(optimized) = merge_component_plans(base_problem, decomposition, &depths, &problems, &assignments)? {
+            merged = optimized;
+        }
+    }
+    Ok(Some(if timeout {
+        SolverResult::Timeout(Some(merged))
+    } else {
+        SolverResult::Sol(merged)
+    }))
+}
+
+/// Builds the finite problem containing the actions of all components and an assignment combining
+/// the solutions of the components, the `i`-th component having been solved with `depths[i]` actions in the
+/// finite problem `problems[i]` whose solution is `solutions[i]`.
+///
+/// Returns `None` if the solutions disagree on the value of a variable of the problem's chronicles (other than the horizon).
+fn merge_component_plans(
+    base_problem: &Problem,
+    decomposition: &CausalGraphDecomposition,
+    depths: &[u32],
+    problems: &[FiniteProblem],
+    solutions: &[Arc<Domains>],
+) -> Result<Option<Plan>> {
+    let pb = populate_components(base_problem, decomposition, base_problem.chronicles.clone(), |c| {
+        depths[c]
+    })?;
+    let horizon = VarRef::from(pb.horizon.num.var);
+
+    // for each variable of the merged problem, the solution and variable from which its value should be taken
+    let mut sources: HashMap<VarRef, (&Domains, VarRef)> = HashMap::new();
+    let first = &solutions[0];
+    for v in base_problem.context.model.state.variables() {
+        sources.insert(v, (first, v));
+    }
+    for v in base_problem.chronicles.iter().flat_map(|ch| ch.chronicle.variables()) {
+        if v != horizon && solutions.iter().any(|sol| sol.bounds(v) != first.bounds(v)) {
+            return Ok(None);
+        }
+    }
+    for ch in &pb.chronicles {
+        if let ChronicleOrigin::FreeAction { template_id, .. } = ch.origin {
+            let component = component_of(decomposition, &base_problem.templates[template_id]);
+            let (sub_pb, sol) = (&problems[component], &solutions[component]);
+            let sub_ch = sub_pb
+                .chronicles
+                .iter()
+                .find(|sub_ch| sub_ch.origin == ch.origin)
+                .expect("Missing instance in the component");
+            for (&param, &sub_param) in ch.parameters.iter().zip(&sub_ch.parameters) {
+                sources.insert(atom_var(param), (sol.as_ref(), atom_var(sub_param)));
+            }
+        }
+    }
+
+    let mut assignment = pb.model.state.clone();
+    for (var, (sol, source)) in sources {
+        if sol.present(source) == Some(false) {
+            continue; // the variable is absent, its presence literal is set independently
+        }
+        let (lb, ub) = if var == horizon {
+            let latest = solutions.iter().map(|sol| sol.lb(horizon)).max().unwrap();
+            (latest, latest)
+        } else {
+            sol.bounds(source)
+        };
+        if assignment.set_lb(var, lb, Cause::Decision).is_err() || assignment.set_ub(var, ub, Cause::Decision).is_err()
+        {
+            return Ok(None);
+        }
+    }
+    Ok(Some((Arc::new(pb), Arc::new(assignment))))
+}
+
+/// Variable underlying an atom. For a constant atom, this is the `ZERO` variable.
+pub(crate) fn atom_var(atom: Atom) -> VarRef {
+    match atom {
+        Atom::Bool(l) => l.variable(),
+        Atom::Int(i) => i.var.into(),
+        Atom::Fixed(f) => f.num.var.into(),
+        Atom::Sym(s) => s.int_view().var.into(),
+    }
+}
+
 /// This function mimics the instantiation of the subproblem, run the propagation and prints the result.
 /// and exits immediately.
 ///
 /// Note that is meant to facilitate debugging of the planner during development.
 ///
 /// Returns true if the propagation succeeded.
-fn propagate_and_print(pb: &FiniteProblem) -> bool {
+fn propagate_and_print(pb: &FiniteProblem, config: &PlannerConfig) -> bool {
     for ch in &pb.chronicles {
         Printer::print_chronicle(&ch.chronicle, &pb.model);
     }
 
-    let (mut solver, _) = init_solver(pb, None);
+    let (mut solver, _, _) = init_solver(pb, None, config);
 
     println!("\n======== BEFORE INITIAL PROPAGATION ======\n");
     let str = format_partial_plan(pb, &solver.model).unwrap();
@@ -155,16 +762,61 @@ pub fn format_plan(problem: &FiniteProblem, plan: &Arc<Domains>, htn_mode: bool)
     Ok(plan)
 }
 
-pub fn init_solver(pb: &FiniteProblem, metric: Option<Metric>) -> (Box<Solver>, Option<IAtom>) {
-    let (model, metric) = encode(pb, metric).expect("Failed to encode the problem"); // TODO: report error
+/// Encodes the problem and builds a solver for it.
+///
+/// Returns the solver together with the objective to optimize (if a metric was given) and the upper bound
+/// on the end of all effects, which the solver assumes to be at most `HORIZON`.
+pub fn init_solver(
+    pb: &FiniteProblem,
+    metric: Option<Metric>,
+    config: &PlannerConfig,
+) -> (Box<Solver>, Option<IAtom>, IVar) {
+    let mut encoding = encode(pb, metric, config).expect("Failed to encode the problem"); // TODO: report error
+    if config.eliminate_fixed_variables {
+        let num_eliminated = eliminate_fixed_variables(&mut encoding.model);
+        if num_eliminated > 0 {
+            println!("Eliminated {num_eliminated} fixed variables");
+        }
+    }
+    if config.merge_equivalent_scopes {
+        let num_merged = merge_equivalent_scopes(&mut encoding.model);
+        if num_merged > 0 {
+            println!("Merged {num_merged} equivalent scopes");
+        }
+    }
+    if config.merge_equivalent_literals {
+        let num_merged = merge_equivalent_literals(&mut encoding.model);
+        if num_merged > 0 {
+            println!("Merged {num_merged} equivalent literals");
+        }
+    }
+    if config.break_symmetries {
+        // the objective and the end of effects are read after solving and must keep their values
+        let mut protected = vec![VarRef::from(encoding.effects_bound)];
+        protected.extend(encoding.objective.map(|obj| VarRef::from(obj.var)));
+        let num_posted = break_symmetries(&mut encoding.model, &protected);
+        if num_posted > 0 {
+            println!("Posted {num_posted} symmetry breaking constraints");
+        }
+    }
+    if let Some(filter) = &config.dump_model {
+        let filter = Some(filter.as_str()).filter(|f| !f.is_empty());
+        println!("{}", encoding.model.dump_pretty(filter));
+    }
     let stn_config = StnConfig {
         theory_propagation: TheoryPropagationLevel::Full,
         ..Default::default()
     };
 
-    let mut solver = Box::new(aries::solver::Solver::new(model));
+    let mut solver = Box::new(aries::solver::Solver::new(encoding.model));
     solver.reasoners.diff.config = stn_config;
-    (solver, metric)
+    solver.set_assumptions([effects_bound_lit(encoding.effects_bound, HORIZON, pb.time_scale())]);
+    (solver, encoding.objective, encoding.effects_bound)
+}
+
+/// Literal stating that all effects end before the `horizon`, which is capped to the largest representable time.
+fn effects_bound_lit(effects_bound: IVar, horizon: IntCst, time_scale: IntCst) -> Lit {
+    effects_bound.leq(horizon.saturating_mul(time_scale).min(INT_CST_MAX))
 }
 
 /// Default set of strategies for HTN problems
@@ -180,6 +832,9 @@ pub enum Strat {
     ActivityNonTemporalFirst,
     /// Mimics forward search in HTN problems.
     Forward,
+    /// Activity based search, exploring the branches with the fewest departures from the heuristic first
+    /// (limited discrepancy search). Intended to quickly find good first plans when optimizing.
+    LimitedDiscrepancy,
 }
 
 /// An activity-based variable selection heuristics that delays branching on temporal variables.
@@ -191,7 +846,7 @@ impl Heuristic<VarLabel> for ActivityNonTemporalFirstHeuristic {
             Some(VarLabel(_, tpe)) => match tpe {
                 VarType::Presence | VarType::Reification | VarType::Parameter(_) => 0,
                 VarType::ChronicleStart | VarType::ChronicleEnd | VarType::TaskStart(_) | VarType::TaskEnd(_) => 1,
-                VarType::Horizon | VarType::EffectEnd | VarType::Cost => 2,
+                VarType::Horizon | VarType::EffectEnd | VarType::Cost | VarType::ObjectCreation => 2,
             },
         }
     }
@@ -208,6 +863,7 @@ impl Strat {
                 solver.set_brancher(ActivityBrancher::new_with_heuristic(ActivityNonTemporalFirstHeuristic))
             }
             Strat::Forward => solver.set_brancher(ForwardSearcher::new(Arc::new(problem.clone()))),
+            Strat::LimitedDiscrepancy => solver.set_brancher(LimitedDiscrepancy::new(0, default_brancher())),
         }
     }
 }
@@ -220,6 +876,7 @@ impl FromStr for Strat {
             "1" | "act" | "activity" => Ok(Strat::Activity),
             "2" | "fwd" | "forward" => Ok(Strat::Forward),
             "3" | "act-no-time" | "activity-no-time" => Ok(Strat::ActivityNonTemporalFirst),
+            "4" | "lds" | "limited-discrepancy" => Ok(Strat::LimitedDiscrepancy),
             _ => Err(format!("Unknown search strategy: {s}")),
         }
     }
@@ -231,18 +888,29 @@ impl FromStr for Strat {
 /// If no strategy is given, then a default set of strategies will be automatically selected.
 ///
 /// If a valid solution of the subproblem is found, the solver will return a satisfying assignment.
+/// On a timeout without any solution, the deepest partial assignment reached is passed to `on_partial_plan`.
+///
+/// The presence literals of `slack_instances` are assumed false, in order. If the problem is proved unsatisfiable because
+/// of the assumption on the `i`-th of them, `on_slack_needed(i)` is called and the search resumes without this assumption.
+#[allow(clippy::too_many_arguments)]
 fn solve_finite_problem(
     pb: &FiniteProblem,
     strategies: &[Strat],
     metric: Option<Metric>,
     htn_mode: bool,
+    config: &PlannerConfig,
+    slack_instances: &[Lit],
+    mut on_slack_needed: impl FnMut(usize),
     on_new_solution: impl Fn(Arc<SavedAssignment>),
+    on_progress: impl Fn(usize, &Progress) + Send + 'static,
+    on_partial_plan: impl FnOnce(Arc<SavedAssignment>),
     deadline: Option<Instant>,
 ) -> SolverResult<Solution> {
-    if PRINT_INITIAL_PROPAGATION.get() {
-        propagate_and_print(pb);
+    if config.print_initial_propagation {
+        propagate_and_print(pb, config);
     }
-    let (solver, metric) = init_solver(pb, metric);
+    let maximize = metric.map_or(false, Metric::is_maximization);
+    let (solver, objective, effects_bound) = init_solver(pb, metric, config);
 
     // select the set of strategies, based on user-input or hard-coded defaults.
     let strats: &[Strat] = if !strategies.is_empty() {
@@ -254,15 +922,256 @@ fn solve_finite_problem(
     };
     let mut solver =
         aries::solver::parallel::ParSolver::new(solver, strats.len(), |id, s| strats[id].adapt_solver(s, pb));
+    solver.set_progress_callback(on_progress);
+    if let Some(interrupt) = &config.interrupt {
+        solver.set_interrupt_flag(interrupt.clone());
+    }
 
-    let result = if let Some(metric) = metric {
-        solver.minimize_with(metric, on_new_solution, deadline)
-    } else {
-        solver.solve(deadline)
+    let mut horizon = HORIZON;
+    // slack instances that are still assumed absent
+    let mut slacks: Vec<usize> = (0..slack_instances.len()).collect();
+    let assumptions = |horizon: IntCst, slacks: &[usize]| {
+        let mut assumptions = vec![effects_bound_lit(effects_bound, horizon, pb.time_scale())];
+        assumptions.extend(slacks.iter().map(|&i| !slack_instances[i]));
+        assumptions
+    };
+    if !slacks.is_empty() {
+        solver.set_assumptions(&assumptions(horizon, &slacks));
+    }
+    let result = loop {
+        let result = if let Some(objective) = objective {
+            if maximize {
+                solver.maximize_with(objective, &on_new_solution, deadline)
+            } else {
+                solver.minimize_with(objective, &on_new_solution, deadline)
+            }
+        } else {
+            solver.solve(deadline)
+        };
+        let failed = match (&result, solver.failed_assumption()) {
+            (SolverResult::Unsat, Some(failed)) => failed,
+            _ => break result,
+        };
+        if let Some(pos) = slacks.iter().position(|&i| !slack_instances[i] == failed) {
+            // unsolvable without a slack instance, release it (reusing the solvers)
+            on_slack_needed(slacks.remove(pos));
+            solver.set_assumptions(&assumptions(horizon, &slacks));
+            continue;
+        }
+        // unsolvable because of the bound on the effects, extend it (reusing the solvers) if possible
+        let extended = horizon
+            .checked_mul(2)
+            .filter(|h| h.checked_mul(pb.time_scale()).map_or(false, |t| t <= INT_CST_MAX));
+        let Some(extended) = extended else {
+            break result;
+        };
+        println!("  Horizon {horizon} is too small, extending it to {extended}");
+        horizon = extended;
+        solver.set_assumptions(&assumptions(horizon, &slacks));
     };
 
-    if let SolverResult::Sol(_) = result {
-        solver.print_stats()
+    match result {
+        SolverResult::Sol(_) => solver.print_stats(),
+        SolverResult::Timeout(None) => {
+            if let Some(partial) = solver.deepest_partial_assignment() {
+                on_partial_plan(partial)
+            }
+        }
+        _ => {}
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encode::tests::{actions, parse};
+    use aries_planning::generator::{BruteForceResult, GeneratorParams, RandomProblem};
+    use std::cell::Cell;
+
+    /// Two robots moving independently, and an action without conditions nor effects that belongs to no component.
+    const ROBOTS: &str = "(define (domain robots)
+        (:requirements :strips :typing)
+        (:types loc)
+        (:predicates (at-a ?l - loc) (road-a ?from ?to - loc) (at-b ?l - loc) (road-b ?from ?to - loc))
+        (:action move-a :parameters (?from ?to - loc)
+            :precondition (and (at-a ?from) (road-a ?from ?to))
+            :effect (and (not (at-a ?from)) (at-a ?to)))
+        (:action move-b :parameters (?from ?to - loc)
+            :precondition (and (at-b ?from) (road-b ?from ?to))
+            :effect (and (not (at-b ?from)) (at-b ?to)))
+        (:action wait :parameters () :precondition (and) :effect (and)))";
+
+    const ROBOTS_PROBLEM: &str = "(define (problem robots-1) (:domain robots)
+        (:objects l1 l2 l3 - loc)
+        (:init (at-a l1) (road-a l1 l2) (road-a l2 l3) (at-b l3) (road-b l3 l2) (road-b l2 l1))
+        (:goal (and (at-a l3) (at-b l1))))";
+
+    /// Checks that the plan, formatted by [format_pddl_plan], is applicable and achieves the goals of the problem.
+    fn validate(problem: &Problem, plan: &str) {
+        let lifted = from_chronicles(problem).unwrap();
+        let grounded = grounded_problem(&lifted).unwrap();
+        let ops = &grounded.operators;
+        let mut state = grounded.initial_state.clone();
+        for (_, name, _) in actions(plan) {
+            let op = ops
+                .iter()
+                .find(|&op| lifted.world.table.format(ops.name(op)) == name)
+                .unwrap_or_else(|| panic!("Unknown action {name}"));
+            assert!(state.entails_all(ops.preconditions(op)), "{name} is not applicable");
+            state.set_all(ops.effects(op));
+        }
+        assert!(
+            state.entails_all(&grounded.goals),
+            "The plan does not achieve the goals"
+        );
+    }
+
+    #[test]
+    fn factored_solving_of_independent_components() {
+        let problem = parse(ROBOTS, ROBOTS_PROBLEM);
+        let decomposition = causal_graph_decomposition(&problem);
+        assert_eq!(decomposition.num_components(), 2);
+
+        let num_solutions = Cell::new(0);
+        let result = solve_factored(
+            &problem,
+            &decomposition,
+            0,
+            5,
+            &[],
+            Some(Metric::PlanLength),
+            &PlannerConfig::default(),
+            |pb, ass| {
+                num_solutions.set(num_solutions.get() + 1);
+                validate(&problem, &format_pddl_plan(pb, &ass).unwrap());
+            },
+            |_, _: &_| {},
+            |_, _| {},
+            None,
+        )
+        .unwrap();
+        let Some(SolverResult::Sol((pb, ass))) = result else {
+            panic!("No plan found")
+        };
+        assert!(num_solutions.get() >= 1);
+
+        let plan = format_pddl_plan(&pb, &ass).unwrap();
+        validate(&problem, &plan);
+        let names: Vec<_> = actions(&plan).into_iter().map(|(_, name, _)| name).collect();
+        assert_eq!(names.len(), 4, "{plan}");
+        assert!(!names.contains(&"(wait)"), "{plan}");
+    }
+
+    /// Solves random problems with fixed seeds and checks that the planner agrees with a brute force search.
+    /// The plans found for non-hierarchical problems are validated.
+    fn check_random_problems(hierarchical: bool, config: &PlannerConfig) {
+        let params = GeneratorParams {
+            hierarchical,
+            ..Default::default()
+        };
+        for seed in 0..30 {
+            let pb = RandomProblem::generate(seed, &params);
+            let expected = pb.brute_force(100_000);
+            let (min_depth, max_depth) = match expected {
+                BruteForceResult::Unknown => continue,
+                // non-recursive hierarchy: go directly to the full depth
+                _ if hierarchical => (u32::MAX, u32::MAX),
+                BruteForceResult::Plan(length) => (0, length as u32),
+                BruteForceResult::NoPlan => (0, 3),
+            };
+            let problem = pb.to_chronicles().unwrap();
+            let result = solve(
+                problem.clone(),
+                min_depth,
+                max_depth,
+                &[],
+                None,
+                hierarchical,
+                config,
+                |_, _| {},
+                |_, _: &_| {},
+                |_, _| {},
+                None,
+            )
+            .unwrap();
+            let found = match (result, expected) {
+                (SolverResult::Sol((pb, ass)), BruteForceResult::Plan(_)) => {
+                    if !hierarchical {
+                        validate(&problem, &format_pddl_plan(&pb, &ass).unwrap());
+                    }
+                    continue;
+                }
+                (SolverResult::Unsat, BruteForceResult::NoPlan) => continue,
+                (SolverResult::Sol(_), _) => "a plan",
+                (SolverResult::Unsat, _) => "no plan",
+                (SolverResult::Timeout(_), _) => "nothing",
+            };
+            panic!(
+                "seed {seed}: the planner found {found} but brute force found {expected:?}\n{}\n{}",
+                pb.domain(),
+                pb.problem()
+            );
+        }
+    }
+
+    #[test]
+    fn random_problems_agree_with_brute_force() {
+        check_random_problems(false, &PlannerConfig::default());
+    }
+
+    #[test]
+    fn random_hierarchical_problems_agree_with_brute_force() {
+        check_random_problems(true, &PlannerConfig::default());
+    }
+
+    /// Configuration where none of the simplifications of the model is applied before solving.
+    fn without_simplifications() -> PlannerConfig {
+        PlannerConfig {
+            merge_equivalent_literals: false,
+            merge_equivalent_scopes: false,
+            eliminate_fixed_variables: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merging_equivalent_literals_preserves_plans() {
+        let config = PlannerConfig {
+            merge_equivalent_literals: true,
+            ..without_simplifications()
+        };
+        check_random_problems(false, &config);
+        check_random_problems(true, &config);
+    }
+
+    #[test]
+    fn merging_equivalent_scopes_preserves_plans() {
+        let config = PlannerConfig {
+            merge_equivalent_scopes: true,
+            ..without_simplifications()
+        };
+        check_random_problems(false, &config);
+        check_random_problems(true, &config);
+    }
+
+    #[test]
+    fn eliminating_fixed_variables_preserves_plans() {
+        let config = PlannerConfig {
+            eliminate_fixed_variables: true,
+            ..without_simplifications()
+        };
+        check_random_problems(false, &config);
+        check_random_problems(true, &config);
+    }
+
+    #[test]
+    fn symmetry_breaking_preserves_plans() {
+        let config = PlannerConfig {
+            break_symmetries: true,
+            ..Default::default()
+        };
+        check_random_problems(false, &config);
+        check_random_problems(true, &config);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::chronicles::{Chronicle, ChronicleTemplate, Problem};
+use aries::model::lang::SAtom;
+use aries::model::symbols::SymId;
+
+/// Partition of the state functions of a problem into the weakly connected components of its causal graph.
+///
+/// The causal graph has a node for each state function and an edge between two state functions if they both
+/// appear in the conditions or effects of the same template. Two components thus never interact:
+/// each template can only read and modify the state functions of a single component.
+///
+/// Note that the decomposition ignores the chronicles of the problem (that typically contain the initial state
+/// and the goals of all components) and is thus only meaningful for non-hierarchical problems.
+#[derive(Clone, Debug)]
+pub struct CausalGraphDecomposition {
+    /// State functions of each component.
+    components: Vec<Vec<SymId>>,
+}
+
+impl CausalGraphDecomposition {
+    pub fn num_components(&self) -> usize {
+        self.components.len()
+    }
+
+    /// State functions of the `i`-th component.
+    pub fn component(&self, i: usize) -> &[SymId] {
+        &self.components[i]
+    }
+
+    /// Component of the state variable, or `None` if the state function is not known (e.g. if it is not a constant).
+    pub fn component_of_sv(&self, sv: &[SAtom]) -> Option<usize> {
+        match sv.first() {
+            Some(SAtom::Cst(fluent)) => self.components.iter().position(|c| c.contains(&fluent.sym)),
+            _ => None,
+        }
+    }
+
+    /// Component whose state functions are manipulated by the template, or `None` if it has no condition nor effect.
+    pub fn component_of_template(&self, template: &ChronicleTemplate) -> Option<usize> {
+        state_variables(&template.chronicle)
+            .next()
+            .and_then(|sv| self.component_of_sv(sv))
+    }
+
+    /// Returns a copy of the chronicle, where only the conditions and effects on the `i`-th component are kept.
+    pub fn restrict(&self, chronicle: &Chronicle, i: usize) -> Chronicle {
+        let mut restricted = chronicle.clone();
+        restricted
+            .conditions
+            .retain(|c| self.component_of_sv(c.variable()) == Some(i));
+        restricted
+            .effects
+            .retain(|e| self.component_of_sv(e.variable()) == Some(i));
+        restricted
+    }
+}
+
+/// State variables appearing in the conditions and effects of the chronicle.
+fn state_variables(ch: &Chronicle) -> impl Iterator<Item = &[SAtom]> + '_ {
+    ch.conditions
+        .iter()
+        .map(|c| c.variable())
+        .chain(ch.effects.iter().map(|e| e.variable()))
+}
+
+/// Computes the weakly connected components of the causal graph of the problem.
+///
+/// Only the state functions appearing in a condition or an effect are placed in a component.
+/// If a state variable does not have a constant state function, it might refer to any of them
+/// and all state functions are placed in a single component.
+pub fn causal_graph_decomposition(pb: &Problem) -> CausalGraphDecomposition {
+    let fluents: Vec<SymId> = pb.context.state_functions.iter().map(|sf| sf.sym).collect();
+    let index_of = |sv: &[SAtom]| match sv.first() {
+        Some(SAtom::Cst(fluent)) => fluents.iter().position(|f| *f == fluent.sym),
+        _ => None,
+    };
+
+    let chronicles = pb
+        .templates
+        .iter()
+        .map(|t| &t.chronicle)
+        .chain(pb.chronicles.iter().map(|c| &c.chronicle));
+    let mut used = vec![false; fluents.len()];
+    for sv in chronicles.flat_map(state_variables) {
+        match index_of(sv) {
+            Some(i) => used[i] = true,
+            None => {
+                return CausalGraphDecomposition {
+                    components: vec![fluents],
+                }
+            }
+        }
+    }
+
+    // union-find over the state functions, merging all those appearing in the same template
+    let mut parents: Vec<usize> = (0..fluents.len()).collect();
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+    for template in &pb.templates {
+        let mut svs = state_variables(&template.chronicle).filter_map(index_of);
+        if let Some(first) = svs.next() {
+            for other in svs {
+                let (a, b) = (root(&mut parents, first), root(&mut parents, other));
+                parents[a] = b;
+            }
+        }
+    }
+
+    let mut components: Vec<Vec<SymId>> = Vec::new();
+    let mut component_of_root = vec![None; fluents.len()];
+    for (i, fluent) in fluents.iter().enumerate().filter(|(i, _)| used[*i]) {
+        let r = root(&mut parents, i);
+        let component = *component_of_root[r].get_or_insert_with(|| {
+            components.push(Vec::new());
+            components.len() - 1
+        });
+        components[component].push(*fluent);
+    }
+    CausalGraphDecomposition { components }
+}
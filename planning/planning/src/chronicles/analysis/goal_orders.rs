@@ -0,0 +1,210 @@
+use crate::chronicles::*;
+use aries::core::VarRef;
+use aries::model::extensions::AssignmentExt;
+use aries::model::lang::{IVar, SAtom};
+use std::collections::HashMap;
+
+/// A goal of the problem: a condition of a (non-action) chronicle on a ground state variable, with a constant value.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Goal {
+    /// Index of the chronicle in the chronicles of the problem.
+    pub chronicle: usize,
+    /// Index of the condition in the chronicle.
+    pub condition: usize,
+}
+
+/// Part of an action that requires a goal to be false: either an effect setting its state variable to another value,
+/// or a condition requiring another value.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Destroyer {
+    Effect(usize),
+    Condition(usize),
+}
+
+impl Destroyer {
+    /// Interval during which the destroyed goal is false.
+    pub fn interval(self, chronicle: &Chronicle) -> (Time, Time) {
+        match self {
+            Destroyer::Effect(i) => {
+                let eff = &chronicle.effects[i];
+                (eff.transition_start, eff.transition_start)
+            }
+            Destroyer::Condition(i) => {
+                let cond = &chronicle.conditions[i];
+                (cond.start, cond.end)
+            }
+        }
+    }
+}
+
+/// A (reasonable) ordering between two goals: any action achieving the `first` goal also destroys the `second` one,
+/// which must thus be (re-)achieved after it.
+#[derive(Clone, Debug)]
+pub struct GoalOrder {
+    pub first: Goal,
+    pub second: Goal,
+    /// For each potential achiever of the first goal, given as the index of its action and of its effect,
+    /// the part of the action that destroys the second goal when it achieves the first one.
+    pub destroyers: Vec<(usize, usize, Destroyer)>,
+}
+
+/// Computes the orders between the goals of the problem, based on the templates of the actions.
+///
+/// In the orders, the achievers of a goal refer to the indices of the templates.
+pub fn goal_orders(pb: &Problem) -> Vec<GoalOrder> {
+    let actions = pb.templates.iter().map(|tl| &tl.chronicle);
+    compute_goal_orders(&pb.context.model, &pb.chronicles, actions.enumerate().collect())
+}
+
+/// Computes the orders between the goals of a finite problem, whose actions are the chronicles
+/// instantiated from a template.
+///
+/// In the orders, the achievers of a goal refer to the indices of the chronicles.
+pub fn instance_goal_orders(pb: &FiniteProblem) -> Vec<GoalOrder> {
+    let actions = pb
+        .chronicles
+        .iter()
+        .enumerate()
+        .filter(|(_, ch)| !matches!(ch.origin, ChronicleOrigin::Original))
+        .map(|(i, ch)| (i, &ch.chronicle))
+        .collect();
+    compute_goal_orders(&pb.model, &pb.chronicles, actions)
+}
+
+/// Formats the goal as its state variable and value, e.g. `(on a b) = true`.
+pub fn format_goal(goal: Goal, chronicles: &[ChronicleInstance], model: &Model<VarLabel>) -> String {
+    let cond = &chronicles[goal.chronicle].chronicle.conditions[goal.condition];
+    let sv: Vec<String> = cond.state_var.iter().map(|&x| model.fmt(x).to_string()).collect();
+    format!("({}) = {}", sv.join(" "), model.fmt(cond.value))
+}
+
+fn compute_goal_orders(
+    model: &Model<VarLabel>,
+    chronicles: &[ChronicleInstance],
+    actions: Vec<(usize, &Chronicle)>,
+) -> Vec<GoalOrder> {
+    let mut goals = Vec::new();
+    for (chronicle, instance) in chronicles.iter().enumerate() {
+        let ch = &instance.chronicle;
+        // other chronicles of the problem may be actions being executed (see the `execution` module)
+        if !matches!(instance.origin, ChronicleOrigin::Original)
+            || ch.kind != ChronicleKind::Problem
+            || !model.entails(ch.presence)
+        {
+            continue;
+        }
+        for (condition, cond) in ch.conditions.iter().enumerate() {
+            let ground = cond.state_var.iter().all(|x| matches!(x, SAtom::Cst(_)));
+            if ground && is_constant(cond.value) {
+                goals.push(Goal { chronicle, condition });
+            }
+        }
+    }
+    let condition = |goal: Goal| &chronicles[goal.chronicle].chronicle.conditions[goal.condition];
+
+    let mut orders = Vec::new();
+    for &first in &goals {
+        let target = condition(first);
+        // achievers of the first goal, with the binding of their parameters to achieve it
+        let mut achievers = Vec::new();
+        for &(action_id, action) in &actions {
+            for (eff_id, eff) in action.effects.iter().enumerate() {
+                if let Some(binding) = achieving_binding(model, eff, target) {
+                    achievers.push((action_id, action, eff_id, binding));
+                }
+            }
+        }
+        if achievers.is_empty() {
+            continue;
+        }
+        for &second in &goals {
+            if second == first {
+                continue;
+            }
+            let destroyers: Option<Vec<_>> = achievers
+                .iter()
+                .map(|(action_id, action, eff_id, binding)| {
+                    let destroyer = find_destroyer(model, action, binding, condition(second))?;
+                    Some((*action_id, *eff_id, destroyer))
+                })
+                .collect();
+            if let Some(destroyers) = destroyers {
+                orders.push(GoalOrder {
+                    first,
+                    second,
+                    destroyers,
+                });
+            }
+        }
+    }
+    orders
+}
+
+fn is_constant(atom: Atom) -> bool {
+    match atom {
+        Atom::Bool(l) => l.variable() == VarRef::ZERO,
+        Atom::Int(i) => i.var == IVar::ZERO,
+        Atom::Fixed(f) => f.num.var == IVar::ZERO,
+        Atom::Sym(s) => matches!(s, SAtom::Cst(_)),
+    }
+}
+
+/// Value of the atom once the variables of the binding are replaced, if it is then a constant.
+fn resolve(atom: Atom, binding: &HashMap<VarRef, SAtom>) -> Option<Atom> {
+    match atom {
+        Atom::Sym(SAtom::Var(v)) => binding.get(&v.var).map(|&cst| cst.into()),
+        _ if is_constant(atom) => Some(atom),
+        _ => None,
+    }
+}
+
+/// If the effect may achieve the (ground) condition, returns the binding of its symbolic variables
+/// that is required to do so.
+fn achieving_binding(model: &Model<VarLabel>, eff: &Effect, target: &Condition) -> Option<HashMap<VarRef, SAtom>> {
+    if !model.unifiable_seq(&eff.state_var, &target.state_var) || !model.unifiable(eff.value, target.value) {
+        return None;
+    }
+    let mut binding = HashMap::new();
+    let pairs = eff
+        .state_var
+        .iter()
+        .map(|&x| Atom::from(x))
+        .zip(target.state_var.iter().map(|&x| Atom::from(x)));
+    for (x, cst) in pairs.chain([(eff.value, target.value)]) {
+        if let (Atom::Sym(SAtom::Var(v)), Atom::Sym(cst)) = (x, cst) {
+            if binding.insert(v.var, cst).map_or(false, |prev| prev != cst) {
+                return None;
+            }
+        }
+    }
+    Some(binding)
+}
+
+/// Returns a part of the action that necessarily makes the (ground) condition false, under the binding.
+fn find_destroyer(
+    model: &Model<VarLabel>,
+    action: &Chronicle,
+    binding: &HashMap<VarRef, SAtom>,
+    target: &Condition,
+) -> Option<Destroyer> {
+    let destroys = |state_var: &[SAtom], value: Atom| {
+        state_var.len() == target.state_var.len()
+            && state_var
+                .iter()
+                .zip(&target.state_var)
+                .all(|(&x, &cst)| resolve(x.into(), binding).map_or(false, |x| model.unifiable(x, cst)))
+            && resolve(value, binding).map_or(false, |value| !model.unifiable(value, target.value))
+    };
+    let effect = action
+        .effects
+        .iter()
+        .position(|eff| destroys(&eff.state_var, eff.value))
+        .map(Destroyer::Effect);
+    effect.or_else(|| {
+        action
+            .conditions
+            .iter()
+            .position(|cond| destroys(&cond.state_var, cond.value))
+            .map(Destroyer::Condition)
+    })
+}
@@ -1,3 +1,9 @@
+mod causal_graph;
+mod goal_orders;
+
+pub use causal_graph::{causal_graph_decomposition, CausalGraphDecomposition};
+pub use goal_orders::{format_goal, goal_orders, instance_goal_orders, Destroyer, Goal, GoalOrder};
+
 use crate::chronicles::Problem;
 use aries::model::extensions::AssignmentExt;
 use aries::model::lang::SAtom;
@@ -77,4 +83,73 @@ mod tests {
         assert!(is_acyclic(vec![0, 1], |i| (i + 1)..5, |x, y| x == y));
         assert!(!is_acyclic(vec![0, 1], |i| [(i + 1) % 5], |x, y| x == y));
     }
+
+    #[test]
+    fn test_causal_graph_decomposition() {
+        use crate::chronicles::analysis::causal_graph_decomposition;
+        use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+        use crate::parsing::pddl_to_chronicles;
+        use aries::utils::input::Input;
+
+        let dom = "(define (domain house)
+          (:requirements :strips :typing)
+          (:types light door)
+          (:predicates (on ?l - light) (closed ?d - door) (unlocked ?d - door))
+          (:action switch-on :parameters (?l - light) :effect (on ?l))
+          (:action unlock :parameters (?d - door) :precondition (closed ?d) :effect (unlocked ?d))
+          (:action open :parameters (?d - door)
+             :precondition (unlocked ?d) :effect (not (closed ?d))))";
+        let pb = "(define (problem p) (:domain house)
+          (:objects l - light d - door)
+          (:init (closed d))
+          (:goal (and (on l) (not (closed d)))))";
+        let dom = parse_pddl_domain(Input::from_string(dom)).unwrap();
+        let pb = parse_pddl_problem(Input::from_string(pb)).unwrap();
+        let pb = pddl_to_chronicles(&dom, &pb).unwrap();
+
+        let decomposition = causal_graph_decomposition(&pb);
+        assert_eq!(decomposition.num_components(), 2);
+        let components: Vec<_> = pb
+            .templates
+            .iter()
+            .map(|t| decomposition.component_of_template(t).unwrap())
+            .collect();
+        assert_ne!(components[0], components[1]);
+        assert_eq!(components[1], components[2]);
+    }
+
+    #[test]
+    fn test_goal_orders() {
+        use crate::chronicles::analysis::{format_goal, goal_orders};
+        use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+        use crate::parsing::pddl_to_chronicles;
+        use aries::utils::input::Input;
+
+        let dom = "(define (domain paint)
+          (:requirements :strips :typing)
+          (:types obj)
+          (:predicates (painted ?x - obj) (dry))
+          (:action paint :parameters (?x - obj) :effect (and (painted ?x) (not (dry))))
+          (:action wait :parameters () :effect (dry)))";
+        let pb = "(define (problem p) (:domain paint)
+          (:objects a - obj)
+          (:init (dry))
+          (:goal (and (painted a) (dry))))";
+        let dom = parse_pddl_domain(Input::from_string(dom)).unwrap();
+        let pb = parse_pddl_problem(Input::from_string(pb)).unwrap();
+        let pb = pddl_to_chronicles(&dom, &pb).unwrap();
+
+        // painting destroys the `dry` goal, that must thus be achieved last
+        let orders = goal_orders(&pb);
+        let model = &pb.context.model;
+        let orders: Vec<_> = orders
+            .iter()
+            .map(|o| {
+                let first = format_goal(o.first, &pb.chronicles, model);
+                let second = format_goal(o.second, &pb.chronicles, model);
+                format!("{first} < {second}")
+            })
+            .collect();
+        assert_eq!(orders, vec!["(painted a) = true < (dry) = true"]);
+    }
 }
@@ -3,7 +3,7 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 
 use crate::chronicles::constraints::Constraint;
-use aries::core::{IntCst, Lit, VarRef};
+use aries::core::{Lit, VarRef};
 use aries::model::lang::*;
 
 /// A state variable (`Sv`) is a sequence of symbolic expressions e.g. `(location-of robot1)` where:
@@ -433,7 +433,8 @@ pub struct Chronicle {
     /// expression on the start/end timepoint of these subtasks.
     pub subtasks: Vec<SubTask>,
     /// Cost of this chronicle. If left empty, it is interpreted as 0.
-    pub cost: Option<IntCst>,
+    /// The cost may be a variable (e.g. when it depends on the parameters of the chronicle).
+    pub cost: Option<IAtom>,
 }
 
 struct VarSet(HashSet<VarRef>);
@@ -537,7 +538,7 @@ impl Substitute for Chronicle {
             effects: self.effects.iter().map(|e| e.substitute(s)).collect(),
             constraints: self.constraints.iter().map(|c| c.substitute(s)).collect(),
             subtasks: self.subtasks.iter().map(|c| c.substitute(s)).collect(),
-            cost: self.cost,
+            cost: self.cost.map(|c| s.isub(c)),
         }
     }
 }
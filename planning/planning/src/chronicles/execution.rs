@@ -0,0 +1,286 @@
+//! Construction of the problem of completing a plan whose execution has started, for planning online:
+//! the planner is called again from a snapshot of the execution, instead of from the initial state.
+//!
+//! In the resulting problem, the origin is the time of the snapshot. Its initial state is the current one,
+//! the timed effects of the original problem that are still to come are shifted accordingly, and each action
+//! being executed is a chronicle of the problem (i.e. not part of the plan), with a fixed start in the past.
+//! The parts of the running actions that are over (e.g. their start conditions and effects) are removed,
+//! their effects being already reflected in the current state.
+
+use crate::chronicles::constraints::Constraint;
+use crate::chronicles::*;
+use anyhow::{bail, ensure, Context, Result};
+use aries::core::state::Cause;
+use aries::core::VarRef;
+use aries::model::extensions::AssignmentExt;
+use aries::model::lang::{IVar, SAtom};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// Snapshot of the execution of a plan.
+///
+/// All times are numerators of rationals whose denominator is the time scale of the problem,
+/// and are relative to the origin of the original problem.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionState {
+    /// Time at which the snapshot was taken.
+    pub time: IntCst,
+    /// Current values of ground state variables, e.g. `(at r1) = l2`.
+    /// A state variable that does not appear keeps its initial value in the original problem.
+    pub fluents: Vec<(Vec<SymId>, Atom)>,
+    /// Actions that have started and not ended yet.
+    pub running: Vec<RunningAction>,
+}
+
+/// An action being executed.
+#[derive(Clone, Debug)]
+pub struct RunningAction {
+    /// Name of the action followed by its arguments, e.g. `(move r1 l1 l2)`.
+    pub name: Vec<SymId>,
+    /// Time at which the action started.
+    pub start: IntCst,
+}
+
+/// Returns the problem of completing the execution from the given snapshot, whose origin is the time of the snapshot.
+pub fn from_execution_state(pb: &Problem, state: &ExecutionState) -> Result<Problem> {
+    let mut pb = pb.clone();
+    let origin = pb.context.origin();
+    // time of the snapshot, that becomes the origin
+    let now = state.time;
+    ensure!(now >= 0, "The snapshot was taken before the origin of the problem");
+
+    let fluents: Vec<Sv> = state
+        .fluents
+        .iter()
+        .map(|(sv, _)| sv.iter().map(|&sym| pb.context.typed_sym(sym).into()).collect())
+        .collect();
+    let initial_state = pb
+        .chronicles
+        .iter()
+        .position(|ch| ch.origin == ChronicleOrigin::Original && ch.chronicle.kind == ChronicleKind::Problem)
+        .context("No chronicle holds the initial state of the problem")?;
+    for instance in &mut pb.chronicles {
+        if instance.origin != ChronicleOrigin::Original {
+            continue;
+        }
+        let ch = &mut instance.chronicle;
+        // Effects that occurred before the snapshot are moved to the origin, unless their state variable is
+        // given a current value. Only the last of them is kept on each state variable.
+        let mut last_past_effects: HashMap<Sv, usize> = HashMap::new();
+        for (i, eff) in ch.effects.iter_mut().enumerate() {
+            let Some(transition_start) = shift(eff.transition_start, now) else {
+                continue;
+            };
+            if transition_start > 0 {
+                // effect still to come
+                eff.transition_start = at(transition_start, eff.transition_start.denom);
+            } else if !fluents.contains(&eff.state_var) {
+                eff.transition_start = origin;
+                last_past_effects.insert(eff.state_var.clone(), i);
+            }
+            if let Some(persistence_start) = shift(eff.persistence_start, now) {
+                eff.persistence_start = at(persistence_start.max(0), eff.persistence_start.denom);
+            }
+        }
+        let mut i = 0;
+        ch.effects.retain(|eff| {
+            i += 1;
+            eff.transition_start != origin || last_past_effects.get(&eff.state_var) == Some(&(i - 1))
+        });
+        // conditions are shifted, and removed if they are over
+        ch.conditions.retain_mut(|cond| {
+            if let Some(start) = shift(cond.start, now) {
+                cond.start = at(start.max(0), cond.start.denom);
+            }
+            match shift(cond.end, now) {
+                Some(end) => {
+                    cond.end = at(end, cond.end.denom);
+                    end >= 0
+                }
+                None => true,
+            }
+        });
+    }
+    for (sv, value) in &state.fluents {
+        let state_var = sv.iter().map(|&sym| pb.context.typed_sym(sym).into()).collect();
+        pb.chronicles[initial_state].chronicle.effects.push(Effect {
+            transition_start: origin,
+            persistence_start: origin,
+            min_persistence_end: Vec::new(),
+            state_var,
+            value: *value,
+        });
+    }
+
+    for action in &state.running {
+        let instance = running_instance(&mut pb, action, now)?;
+        pb.chronicles.push(instance);
+    }
+    Ok(pb)
+}
+
+/// If the timepoint is constant, returns its numerator once shifted back by the given time.
+fn shift(t: Time, time: IntCst) -> Option<IntCst> {
+    if t.num.var == IVar::ZERO {
+        Some(t.num.shift - time)
+    } else {
+        None
+    }
+}
+
+/// Constant timepoint of the given numerator.
+fn at(num: IntCst, denom: IntCst) -> Time {
+    FAtom::new(IAtom::from(num), denom)
+}
+
+/// Builds the chronicle of an action being executed, with fixed parameters and start, and without the parts
+/// that are over.
+fn running_instance(pb: &mut Problem, action: &RunningAction, now: IntCst) -> Result<ChronicleInstance> {
+    let name = |syms: &[SymId]| {
+        let symbols = pb.context.model.get_symbol_table();
+        syms.iter().map(|&sym| symbols.symbol(sym).to_string()).join(" ")
+    };
+    let action_name = name(&action.name);
+    let template = pb
+        .templates
+        .iter()
+        .find(|tl| {
+            matches!(tl.chronicle.kind, ChronicleKind::Action | ChronicleKind::DurativeAction)
+                && tl.chronicle.name.len() == action.name.len()
+                && tl.chronicle.name[0] == SAtom::Cst(pb.context.typed_sym(action.name[0]))
+        })
+        .with_context(|| format!("No action matches the running action ({action_name})"))?;
+    ensure!(
+        template.chronicle.subtasks.is_empty(),
+        "The running action ({action_name}) has subtasks"
+    );
+    let instance_id = pb.chronicles.len();
+    let start = template.chronicle.start;
+    let start_value = action.start - now - start.num.shift;
+    // arguments of the action, on its parameters
+    let mut values: HashMap<VarRef, IntCst> = HashMap::new();
+    values.insert(template.chronicle.presence.variable(), 1);
+    values.insert(start.num.var.into(), start_value);
+    for (&param, &arg) in template.chronicle.name.iter().zip(&action.name).skip(1) {
+        match param {
+            SAtom::Var(v) => {
+                values.insert(v.var, usize::from(arg) as IntCst);
+            }
+            SAtom::Cst(cst) if cst.sym == arg => {}
+            SAtom::Cst(_) => bail!("The running action ({action_name}) does not match its template"),
+        }
+    }
+
+    let model = &mut pb.context.model;
+    let mut sub = Sub::empty();
+    for &v in &template.parameters {
+        let label = model.get_label(v).unwrap().on_instance(instance_id);
+        let fresh: Variable = match v {
+            Variable::Bool(_) => model.new_bvar(label).into(),
+            Variable::Int(i) => {
+                let (lb, ub) = model.int_bounds(i);
+                model.new_ivar(lb, ub, label).into()
+            }
+            // the start of the action is in the past, and thus outside the domain of the parameter
+            Variable::Fixed(f) if VarRef::from(f.num) == VarRef::from(start.num.var) => {
+                model.new_fvar(start_value, start_value, f.denom, label).into()
+            }
+            Variable::Fixed(f) => {
+                let (lb, ub) = model.int_bounds(f.num);
+                model.new_fvar(lb, ub, f.denom, label).into()
+            }
+            Variable::Sym(s) => model.new_sym_var(s.tpe, label).into(),
+        };
+        if let Some(&value) = values.get(&VarRef::from(v)) {
+            let fresh = VarRef::from(fresh);
+            let fixed = model.state.set_lb(fresh, value, Cause::Decision);
+            let fixed = fixed.and_then(|_| model.state.set_ub(fresh, value, Cause::Decision));
+            if let Err(invalid) = fixed {
+                bail!(
+                    "Invalid argument of the running action ({action_name}): {}",
+                    model.fmt_invalid_update(&invalid)
+                );
+            }
+        }
+        sub.add(v, fresh)?;
+    }
+    let mut instance = template.instantiate(sub, ChronicleOrigin::Original)?;
+    // the action is necessarily present, like the other chronicles of the problem
+    instance.chronicle.presence = Lit::TRUE;
+
+    // remove the parts of the action that are over
+    let model = &pb.context.model;
+    let origin = pb.context.origin();
+    let over = |t: Time| model.int_bounds(t.num).1 < origin.num.shift;
+    let ch = &mut instance.chronicle;
+    ch.conditions.retain_mut(|cond| {
+        if over(cond.start) {
+            cond.start = origin;
+        }
+        !over(cond.end)
+    });
+    ch.effects.retain_mut(|eff| {
+        if over(eff.transition_start) {
+            eff.transition_start = origin;
+        }
+        !over(eff.persistence_start)
+    });
+    // the action is not over either
+    ch.constraints.push(Constraint::fleq(origin, ch.end));
+    Ok(instance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use crate::parsing::pddl_to_chronicles;
+    use aries::utils::input::Input;
+
+    #[test]
+    fn test_execution_state() {
+        let dom = "(define (domain move)
+          (:requirements :strips :typing :durative-actions)
+          (:types loc)
+          (:predicates (at ?l - loc) (visited ?l - loc))
+          (:durative-action move :parameters (?a ?b - loc) :duration (= ?duration 5)
+            :condition (and (at start (at ?a)))
+            :effect (and (at start (not (at ?a))) (at end (at ?b)) (at end (visited ?b)))))";
+        let pb = "(define (problem p) (:domain move)
+          (:objects l1 l2 l3 - loc)
+          (:init (at l1))
+          (:goal (and (visited l2) (at l3))))";
+        let dom = parse_pddl_domain(Input::from_string(dom)).unwrap();
+        let pb = parse_pddl_problem(Input::from_string(pb)).unwrap();
+        let pb = pddl_to_chronicles(&dom, &pb).unwrap();
+        let scale = pb.context.time_scale();
+        let sym = |name: &str| pb.context.model.get_symbol_table().id(name).unwrap();
+
+        // the robot left l1 for l2 two time units ago
+        let state = ExecutionState {
+            time: 3 * scale,
+            fluents: vec![(vec![sym("at"), sym("l1")], false.into())],
+            running: vec![RunningAction {
+                name: vec![sym("move"), sym("l1"), sym("l2")],
+                start: scale,
+            }],
+        };
+        let pb = from_execution_state(&pb, &state).unwrap();
+        let model = &pb.context.model;
+        assert_eq!(pb.chronicles.len(), 2);
+        let init = &pb.chronicles[0].chronicle;
+        assert_eq!(init.effects.len(), 1);
+        assert_eq!(init.effects[0].value, Atom::from(false));
+
+        let running = &pb.chronicles[1];
+        assert_eq!(running.origin, ChronicleOrigin::Original);
+        let ch = &running.chronicle;
+        assert!(model.entails(ch.presence));
+        assert_eq!(model.int_bounds(ch.start.num), (-2 * scale, -2 * scale));
+        assert_eq!(model.sym_value_of(ch.name[2]), Some(sym("l2")));
+        // only the end effects remain
+        assert!(ch.conditions.is_empty());
+        assert_eq!(ch.effects.len(), 2);
+        assert!(ch.effects.iter().all(|eff| eff.transition_start == ch.end));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod analysis;
 mod concrete;
 pub mod constraints;
+pub mod execution;
 pub mod preprocessing;
 pub mod printer;
 mod templates;
@@ -8,16 +9,20 @@ mod templates;
 pub use concrete::*;
 
 use self::constraints::Table;
-use aries::core::IntCst;
+use aries::core::{IntCst, Lit};
 use aries::model::extensions::Shaped;
 use aries::model::lang::{Atom, FAtom, IAtom, Type, Variable};
 use aries::model::symbols::{SymId, SymbolTable, TypedSym};
+use aries::model::types::TypeId;
 use aries::model::Model;
 use std::fmt::Formatter;
 use std::sync::Arc;
 
-/// Time being represented as a fixed point numeral, this is the denominator of any time numeral.
+/// Time being represented as a fixed point numeral, this is the default denominator of any time numeral.
 /// Having a time scale 100, will allow a resolution of `0.01` for time values.
+///
+/// A problem may use a finer time scale (see [Ctx::with_time_scale]), in which case all its time numerals
+/// share the denominator of its origin.
 pub const TIME_SCALE: IntCst = 10;
 
 /// Represents a discrete value (symbol, integer or boolean)
@@ -50,31 +55,69 @@ impl StateFun {
     }
 }
 
+/// A pool of objects of a given type that do not exist initially but may be created by the plan.
+///
+/// The objects of the pool are regular symbols of the symbol table, each associated to a literal
+/// that is true iff the object is created, i.e., iff it is used by a chronicle of the plan.
+#[derive(Clone, Debug)]
+pub struct ObjectPool {
+    /// Type of the objects in the pool.
+    pub tpe: TypeId,
+    /// Objects of the pool, together with the literal that holds iff they are created.
+    pub objects: Vec<(SymId, Lit)>,
+}
+
 #[derive(Clone)]
 pub struct Ctx {
     pub model: Model<VarLabel>,
     pub state_functions: Vec<StateFun>,
+    /// Pools of objects that may be created by the plan.
+    pub object_pools: Vec<ObjectPool>,
     origin: FAtom,
     horizon: FAtom,
 }
 
 impl Ctx {
     pub fn new(symbols: Arc<SymbolTable>, state_variables: Vec<StateFun>) -> Self {
+        Self::with_time_scale(symbols, state_variables, TIME_SCALE)
+    }
+
+    /// Creates a context where all time numerals have the denominator `time_scale`.
+    pub fn with_time_scale(symbols: Arc<SymbolTable>, state_variables: Vec<StateFun>, time_scale: IntCst) -> Self {
+        assert!(time_scale > 0, "Non-positive time scale");
         let mut model = Model::new_with_symbols(symbols);
 
-        let origin = FAtom::new(IAtom::ZERO, TIME_SCALE);
+        let origin = FAtom::new(IAtom::ZERO, time_scale);
         let horizon = model
-            .new_fvar(0, DiscreteValue::MAX, TIME_SCALE, Container::Base / VarType::Horizon)
+            .new_fvar(0, DiscreteValue::MAX, time_scale, Container::Base / VarType::Horizon)
             .into();
 
         Ctx {
             model,
             state_functions: state_variables,
+            object_pools: Vec::new(),
             origin,
             horizon,
         }
     }
 
+    /// Declares that the given objects (that must all be of type `tpe` in the symbol table) do not exist initially
+    /// and may be created by the plan. A creation literal is associated to each of them.
+    pub fn add_object_pool(&mut self, tpe: TypeId, objects: impl IntoIterator<Item = SymId>) {
+        let objects = objects
+            .into_iter()
+            .map(|sym| {
+                debug_assert_eq!(self.model.get_type_of(sym), tpe);
+                let created = self
+                    .model
+                    .new_bvar(Container::Base / VarType::ObjectCreation)
+                    .true_lit();
+                (sym, created)
+            })
+            .collect();
+        self.object_pools.push(ObjectPool { tpe, objects });
+    }
+
     pub fn origin(&self) -> FAtom {
         self.origin
     }
@@ -82,6 +125,11 @@ impl Ctx {
         self.horizon
     }
 
+    /// Denominator of all time numerals of the problem.
+    pub fn time_scale(&self) -> IntCst {
+        self.origin.denom
+    }
+
     /// Returns the variable with a singleton domain that represents this constant symbol.
     pub fn typed_sym(&self, sym: SymId) -> TypedSym {
         TypedSym {
@@ -239,6 +287,8 @@ pub enum VarType {
     Parameter(String),
     Reification,
     Cost,
+    /// Creation of an object of an [ObjectPool]
+    ObjectCreation,
 }
 
 #[derive(Clone)]
@@ -247,4 +297,13 @@ pub struct FiniteProblem {
     pub origin: Time,
     pub horizon: Time,
     pub chronicles: Vec<ChronicleInstance>,
+    /// Pools of objects that may be created by the plan.
+    pub object_pools: Vec<ObjectPool>,
+}
+
+impl FiniteProblem {
+    /// Denominator of all time numerals of the problem.
+    pub fn time_scale(&self) -> IntCst {
+        self.origin.denom
+    }
 }
@@ -1,4 +1,5 @@
 mod merge_conditions_effects;
+mod relevance;
 mod state_variables;
 mod statics;
 mod unused_effects;
@@ -8,27 +9,69 @@ use env_param::EnvParam;
 static PREPRO_STATIC: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_PREPRO_STATIC", "true");
 static PREPRO_STATE_VARS: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_PREPRO_STATE_VARS", "true");
 static PREPRO_UNUSABLE_EFFECTS: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_PREPRO_UNUSABLE_EFFECTS", "true");
+static PREPRO_RELEVANCE: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_PREPRO_RELEVANCE", "true");
 static PREPRO_MERGE_STATEMENTS: EnvParam<bool> = EnvParam::new("ARIES_PLANNING_PREPRO_MERGE_STATEMENTS", "true");
 
 use crate::chronicles::Problem;
 pub use merge_conditions_effects::merge_conditions_effects;
+pub use relevance::restrict_parameters_to_relevant;
 pub use state_variables::predicates_as_state_variables;
 pub use statics::statics_as_tables;
 pub use unused_effects::merge_unusable_effects;
 pub use unused_effects::remove_unusable_effects;
 
+/// Preprocessing steps to apply to a problem.
+///
+/// All steps are enabled by default, unless disabled by their environment parameter
+/// (e.g. `ARIES_PLANNING_PREPRO_STATIC=false`).
+#[derive(Clone, Debug)]
+pub struct PreprocessingConfig {
+    /// Removes the effects of templates that cannot support any condition.
+    pub unusable_effects: bool,
+    /// Converts predicates into state variables when possible.
+    pub state_variables: bool,
+    /// Converts static fluents into tables.
+    pub statics: bool,
+    /// Restricts the parameters of the templates to the objects that may contribute to a goal.
+    pub relevance: bool,
+    /// Merges the conditions into the effects that support them at their start, and removes unused effects that
+    /// immediately precede another one.
+    pub merge_statements: bool,
+}
+
+impl Default for PreprocessingConfig {
+    fn default() -> Self {
+        PreprocessingConfig {
+            unusable_effects: PREPRO_UNUSABLE_EFFECTS.get(),
+            state_variables: PREPRO_STATE_VARS.get(),
+            statics: PREPRO_STATIC.get(),
+            relevance: PREPRO_RELEVANCE.get(),
+            merge_statements: PREPRO_MERGE_STATEMENTS.get(),
+        }
+    }
+}
+
+/// Preprocesses the problem with the default configuration.
 pub fn preprocess(problem: &mut Problem) {
-    if PREPRO_UNUSABLE_EFFECTS.get() {
+    preprocess_with(problem, &PreprocessingConfig::default())
+}
+
+/// Preprocesses the problem, applying the steps enabled in the configuration.
+pub fn preprocess_with(problem: &mut Problem, config: &PreprocessingConfig) {
+    if config.unusable_effects {
         remove_unusable_effects(problem);
     }
-    if PREPRO_STATE_VARS.get() {
+    if config.state_variables {
         predicates_as_state_variables(problem);
     }
-    if PREPRO_STATIC.get() {
+    if config.statics {
         statics_as_tables(problem);
     }
+    if config.relevance {
+        restrict_parameters_to_relevant(problem);
+    }
 
-    if PREPRO_MERGE_STATEMENTS.get() {
+    if config.merge_statements {
         merge_conditions_effects(problem);
         merge_unusable_effects(problem);
     }
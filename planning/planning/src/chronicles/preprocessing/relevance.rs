@@ -0,0 +1,271 @@
+use crate::chronicles::*;
+use aries::core::state::Cause;
+use aries::core::{IntCst, VarRef};
+use aries::model::extensions::AssignmentExt;
+use aries::model::lang::SAtom;
+use std::collections::{HashMap, HashSet};
+
+/// Symbolic values that may appear at a given position (argument or value) of the relevant state variables of a fluent.
+type Relevant = HashMap<(SymId, usize), HashSet<IntCst>>;
+
+/// Restricts the domains of the (symbolic) parameters of the templates to the objects that may contribute to a goal.
+///
+/// The analysis is a backward relevance analysis, starting from the conditions of the chronicle instances (the goals):
+///  - an effect of a template is relevant if it is unifiable with a relevant state variable and value,
+///  - the conditions of a template with at least one relevant effect are themselves relevant.
+///
+/// A parameter that appears in all relevant effects of its template can be restricted to the objects for which
+/// one of these effects is relevant. Since the domain of a symbolic variable is an interval of the symbol table,
+/// it is restricted to the smallest interval containing these objects.
+///
+/// Hierarchical problems are left untouched as their templates are not only introduced to support conditions.
+/// So are problems with a condition on a non-constant fluent, that might make any effect relevant.
+pub fn restrict_parameters_to_relevant(pb: &mut Problem) {
+    let unsupported = |ch: &Chronicle| {
+        !ch.subtasks.is_empty()
+            || ch
+                .conditions
+                .iter()
+                .any(|cond| !matches!(cond.state_var[0], SAtom::Cst(_)))
+    };
+    if pb.chronicles.iter().any(|ch| unsupported(&ch.chronicle))
+        || pb.templates.iter().any(|tl| unsupported(&tl.chronicle))
+    {
+        return;
+    }
+    let model = &pb.context.model;
+
+    let mut relevant = Relevant::new();
+    for instance in &pb.chronicles {
+        if model.entails(!instance.chronicle.presence) {
+            continue;
+        }
+        for cond in &instance.chronicle.conditions {
+            add_relevant(&mut relevant, &cond.state_var, cond.value, |atom| values(model, atom));
+        }
+    }
+
+    // relevant domain of the parameters of each template, `None` if the template has no relevant effect
+    let mut domains: Vec<Option<HashMap<VarRef, HashSet<IntCst>>>> = vec![None; pb.templates.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (template, domains) in pb.templates.iter().zip(domains.iter_mut()) {
+            let Some(params) = relevant_domains(template, &relevant, model) else {
+                continue;
+            };
+            let values_of = |atom: SAtom| match atom {
+                SAtom::Var(v) if params.contains_key(&v.var) => params[&v.var].clone(),
+                _ => values(model, atom),
+            };
+            for cond in &template.chronicle.conditions {
+                changed |= add_relevant(&mut relevant, &cond.state_var, cond.value, values_of);
+            }
+            *domains = Some(params);
+        }
+    }
+
+    let mut num_restricted = 0;
+    for domains in domains.into_iter().flatten() {
+        for (var, values) in domains {
+            let (lb, ub) = pb.context.model.state.bounds(var);
+            let hull_lb = values.iter().copied().min().unwrap_or(ub + 1);
+            let hull_ub = values.iter().copied().max().unwrap_or(lb - 1);
+            if hull_lb > lb || hull_ub < ub {
+                // restricting the domain of an optional variable cannot fail, but might make it absent
+                let state = &mut pb.context.model.state;
+                let _ = state.set_lb(var, hull_lb, Cause::Encoding);
+                let _ = state.set_ub(var, hull_ub, Cause::Encoding);
+                num_restricted += 1;
+            }
+        }
+    }
+    if num_restricted > 0 {
+        println!("Restricted the domains of {num_restricted} template parameters to their relevant objects");
+    }
+}
+
+/// Possible values of a symbolic atom.
+fn values(model: &Model<VarLabel>, atom: SAtom) -> HashSet<IntCst> {
+    let (lb, ub) = model.int_bounds(atom);
+    (lb..=ub).collect()
+}
+
+/// Atoms at the tracked positions of a state variable and its value: all arguments, and the value if symbolic.
+fn positions(state_var: &[SAtom], value: Atom) -> impl Iterator<Item = (usize, SAtom)> + '_ {
+    let value = match value {
+        Atom::Sym(value) => Some((state_var.len() - 1, value)),
+        _ => None,
+    };
+    state_var[1..].iter().copied().enumerate().chain(value)
+}
+
+/// Records the state variable and value as relevant, and returns true if this extended the relevant values.
+fn add_relevant(
+    relevant: &mut Relevant,
+    state_var: &[SAtom],
+    value: Atom,
+    values_of: impl Fn(SAtom) -> HashSet<IntCst>,
+) -> bool {
+    let SAtom::Cst(fluent) = state_var[0] else {
+        return false;
+    };
+    let mut changed = false;
+    for (pos, atom) in positions(state_var, value) {
+        let known = relevant.entry((fluent.sym, pos)).or_default();
+        for v in values_of(atom) {
+            changed |= known.insert(v);
+        }
+    }
+    changed
+}
+
+/// Returns the relevant domain of each symbolic parameter of the template, or `None` if no effect is relevant.
+fn relevant_domains(
+    template: &ChronicleTemplate,
+    relevant: &Relevant,
+    model: &Model<VarLabel>,
+) -> Option<HashMap<VarRef, HashSet<IntCst>>> {
+    let params: Vec<VarRef> = template
+        .parameters
+        .iter()
+        .filter_map(|&p| match p {
+            Variable::Sym(v) => Some(v.var),
+            _ => None,
+        })
+        .collect();
+    let mut domains: HashMap<VarRef, HashSet<IntCst>> = HashMap::new();
+    // parameters that do not appear in all relevant effects, and thus cannot be restricted
+    let mut unrestricted: HashSet<VarRef> = HashSet::new();
+    let mut has_relevant_effect = false;
+    for eff in &template.chronicle.effects {
+        let SAtom::Cst(fluent) = eff.state_var[0] else {
+            // the effect might be on any fluent, and thus relevant whatever the values of the parameters
+            has_relevant_effect = true;
+            unrestricted.extend(params.iter().copied());
+            continue;
+        };
+        // for each position, the values that are both possible and relevant
+        let mut matching = Vec::new();
+        for (pos, atom) in positions(&eff.state_var, eff.value) {
+            let Some(known) = relevant.get(&(fluent.sym, pos)) else {
+                break;
+            };
+            let possible: HashSet<IntCst> = values(model, atom).intersection(known).copied().collect();
+            if possible.is_empty() {
+                break;
+            }
+            matching.push((atom, possible));
+        }
+        if matching.len() != positions(&eff.state_var, eff.value).count() {
+            continue; // not relevant
+        }
+        has_relevant_effect = true;
+        for &p in &params {
+            // values of the parameter for which the effect is relevant, intersected over all its positions
+            let mut allowed: Option<HashSet<IntCst>> = None;
+            for (atom, possible) in &matching {
+                if matches!(atom, SAtom::Var(v) if v.var == p) {
+                    allowed = Some(match allowed {
+                        Some(prev) => prev.intersection(possible).copied().collect(),
+                        None => possible.clone(),
+                    });
+                }
+            }
+            match allowed {
+                Some(allowed) => domains.entry(p).or_default().extend(allowed),
+                None => {
+                    unrestricted.insert(p);
+                }
+            }
+        }
+    }
+    if !has_relevant_effect {
+        return None;
+    }
+    for p in unrestricted {
+        let (lb, ub) = model.state.bounds(p);
+        domains.insert(p, (lb..=ub).collect());
+    }
+    Some(domains)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use crate::parsing::pddl_to_chronicles;
+    use aries::utils::input::Input;
+
+    #[test]
+    fn goal_relevance() {
+        let domain = "(define (domain paint) (:requirements :strips :typing) (:types obj)
+  (:predicates (painted ?x - obj) (ready ?x - obj))
+  (:action prepare :parameters (?x - obj) :effect (ready ?x))
+  (:action paint :parameters (?x - obj) :precondition (ready ?x) :effect (painted ?x)))";
+        let problem = "(define (problem p) (:domain paint)
+  (:objects a b c d - obj)
+  (:goal (and (painted b) (painted c))))";
+        let domain = parse_pddl_domain(Input::from_string(domain)).unwrap();
+        let problem = parse_pddl_problem(Input::from_string(problem)).unwrap();
+        let mut pb = pddl_to_chronicles(&domain, &problem).unwrap();
+        restrict_parameters_to_relevant(&mut pb);
+
+        let symbols = pb.context.model.get_symbol_table();
+        let id = |name: &str| usize::from(symbols.id(name).unwrap()) as IntCst;
+        for template in &pb.templates {
+            let params: Vec<_> = template
+                .parameters
+                .iter()
+                .filter_map(|&p| match p {
+                    Variable::Sym(v) => Some(pb.context.model.state.bounds(v.var)),
+                    _ => None,
+                })
+                .collect();
+            // the object to prepare must be one that can be painted
+            assert_eq!(params, vec![(id("b"), id("c"))]);
+        }
+    }
+
+    #[test]
+    fn effect_on_unknown_fluent() {
+        let domain = "(define (domain paint) (:requirements :strips :typing) (:types obj)
+  (:predicates (painted ?x - obj) (ready ?x - obj) (clean ?x - obj))
+  (:action prepare :parameters (?x - obj) :effect (and (ready ?x) (clean ?x)))
+  (:action paint :parameters (?x - obj) :precondition (ready ?x) :effect (painted ?x)))";
+        let problem = "(define (problem p) (:domain paint)
+  (:objects a b c d - obj)
+  (:goal (and (painted b) (clean b))))";
+        let domain = parse_pddl_domain(Input::from_string(domain)).unwrap();
+        let problem = parse_pddl_problem(Input::from_string(problem)).unwrap();
+        let mut pb = pddl_to_chronicles(&domain, &problem).unwrap();
+
+        // one of the effects of `prepare` is made to be on a fluent that is only known to be of the type of the original one
+        let prepare = &pb.templates[0];
+        let SAtom::Cst(original) = prepare.chronicle.effects[0].state_var[0] else {
+            panic!("fluent of a PDDL effect should be constant");
+        };
+        let fluent = pb
+            .context
+            .model
+            .new_sym_var(original.tpe, Container::Base / VarType::Parameter("f".to_string()));
+        pb.templates[0].chronicle.effects[0].state_var[0] = fluent.into();
+        // bounds of the only symbolic parameter of the template
+        let param = |pb: &Problem, template: usize| {
+            let params = &pb.templates[template].parameters;
+            let mut syms = params.iter().filter_map(|&p| match p {
+                Variable::Sym(v) => Some(pb.context.model.state.bounds(v.var)),
+                _ => None,
+            });
+            syms.next().unwrap()
+        };
+        let unrestricted = param(&pb, 0);
+
+        restrict_parameters_to_relevant(&mut pb);
+        let symbols = pb.context.model.get_symbol_table();
+        let id = |name: &str| usize::from(symbols.id(name).unwrap()) as IntCst;
+        // the modified effect might support any condition: the object to prepare is not restricted
+        assert_eq!(param(&pb, 0), unrestricted);
+        assert_eq!(param(&pb, 1), (id("b"), id("b")));
+    }
+}
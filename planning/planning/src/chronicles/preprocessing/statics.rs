@@ -2,7 +2,6 @@ use crate::chronicles::*;
 
 use crate::chronicles::constraints::{Constraint, ConstraintType};
 use aries::model::extensions::{AssignmentExt, Shaped};
-use aries::model::lang::{IAtom, SAtom};
 use std::convert::TryFrom;
 
 /// Detects state functions that are static (all of its state variable will take a single value over the entire planning window)
@@ -13,6 +12,9 @@ use std::convert::TryFrom;
 /// - for effects on it in the chronicle instances,
 ///   - all variables (in the state variable and the value) must be defined
 ///   - the effect should start support at the time origin
+///
+/// The values of the state function may be of any discrete type: booleans, integers or symbols (object fluents),
+/// a symbol being represented in the table by its integer value.
 pub fn statics_as_tables(pb: &mut Problem) {
     let context = &pb.context;
 
@@ -26,6 +28,10 @@ pub fn statics_as_tables(pb: &mut Problem) {
         {
             return false;
         }
+        // the value must be a constant as well
+        if constant_value(&context.model, eff.value).is_none() {
+            return false;
+        }
         eff.effective_start() == context.origin()
     };
     let unifiable = |var, sym| context.model.sym_domain_of(var).contains(sym);
@@ -116,10 +122,7 @@ pub fn statics_as_tables(pb: &mut Problem) {
                             line.push(sym.int_value());
                         }
 
-                        let (lb, ub) = pb.context.model.int_bounds(e.value);
-                        assert_eq!(lb, ub, "Not a constant");
-                        let int_value = lb;
-
+                        let int_value = constant_value(&pb.context.model, e.value).expect("Not a constant");
                         line.push(int_value);
                         table.push(&line);
 
@@ -142,14 +145,9 @@ pub fn statics_as_tables(pb: &mut Problem) {
                         assert!(unified(*x, sf.sym));
                         // debug_assert!(pb.context.domain(*x).as_singleton() == Some(sf.sym));
                         let c = instance.chronicle.conditions.remove(i);
-                        // get variables from the condition's state variable
-                        let mut vars: Vec<IAtom> = c.state_var.iter().copied().map(SAtom::int_view).collect();
-                        // remove the state function
-                        vars.remove(0);
-                        // add the value
-                        vars.push(c.value.int_view().unwrap());
+                        let vars = table_variables(&c);
                         instance.chronicle.constraints.push(Constraint {
-                            variables: vars.iter().map(|&i| Atom::from(i)).collect(),
+                            variables: vars,
                             tpe: ConstraintType::InTable(table.clone()),
                             value: None,
                         });
@@ -170,14 +168,9 @@ pub fn statics_as_tables(pb: &mut Problem) {
                         assert!(unified(*x, sf.sym));
                         // debug_assert!(pb.context.domain(*x).as_singleton() == Some(sf.sym));
                         let c = template.chronicle.conditions.remove(i);
-                        // get variables from the condition's state variable
-                        let mut vars: Vec<IAtom> = c.state_var.iter().copied().map(SAtom::int_view).collect();
-                        // remove the state function
-                        vars.remove(0);
-                        // add the value
-                        vars.push(c.value.int_view().unwrap());
+                        let vars = table_variables(&c);
                         template.chronicle.constraints.push(Constraint {
-                            variables: vars.iter().map(|&i| Atom::from(i)).collect(),
+                            variables: vars,
                             tpe: ConstraintType::InTable(table.clone()),
                             value: None,
                         });
@@ -192,3 +185,26 @@ pub fn statics_as_tables(pb: &mut Problem) {
         additional_tables.push(table);
     }
 }
+
+/// Returns the value of the atom, as represented in a table, if it is a constant.
+fn constant_value(model: &Model<VarLabel>, value: Atom) -> Option<DiscreteValue> {
+    let (lb, ub) = model.int_bounds(value);
+    if lb == ub {
+        Some(lb)
+    } else {
+        None
+    }
+}
+
+/// Returns the variables of the table constraint replacing the condition: the parameters of its state variable
+/// followed by its value.
+/// The atoms are kept with their original kind (e.g. symbols for object fluents) and are matched against the table
+/// through their integer view.
+fn table_variables(cond: &Condition) -> Vec<Atom> {
+    debug_assert!(cond.value.int_view().is_some());
+    cond.state_var[1..]
+        .iter()
+        .map(|&x| Atom::from(x))
+        .chain(std::iter::once(cond.value))
+        .collect()
+}
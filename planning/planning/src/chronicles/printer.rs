@@ -88,7 +88,9 @@ impl<'a> Printer<'a> {
         }
 
         if let Some(cost) = ch.cost {
-            println!("  cost: {cost}")
+            print!("  cost: ");
+            self.iatom(cost);
+            println!()
         }
 
         println!()
@@ -175,6 +177,7 @@ impl<'a> Printer<'a> {
                 VarType::Parameter(name) => print!("{name}"),
                 VarType::Reification => print!("reif_{v:?}"),
                 VarType::Cost => print!("cost_{v:?}"),
+                VarType::ObjectCreation => print!("created_{v:?}"),
             }
         } else if v == VarRef::ZERO {
             print!("0");
@@ -99,3 +99,43 @@ pub fn hadd(state: &State, ops: &Operators) -> HAddResult {
         applicable,
     }
 }
+
+/// Computes fact landmarks of the delete relaxation of the problem: literals that must hold at some point
+/// of any plan reaching the goals from the state (and that do not hold in the state).
+///
+/// Landmarks are found by backchaining from the goals: the preconditions shared by all reachable achievers
+/// of a landmark are themselves landmarks (Hoffmann, Porteous & Sebastia, 2004).
+/// Returns an empty set if the goals are not reachable.
+pub fn landmarks(state: &State, ops: &Operators, goals: &[Lit]) -> Vec<Lit> {
+    let costs = hadd(state, ops);
+    if costs.conjunction_cost(goals).is_infinite() {
+        return Vec::new();
+    }
+    let mut landmarks: Vec<Lit> = Vec::new();
+    let mut queue: Vec<Lit> = goals.iter().copied().filter(|&g| !state.entails(g)).collect();
+    while let Some(landmark) = queue.pop() {
+        if landmarks.contains(&landmark) {
+            continue;
+        }
+        landmarks.push(landmark);
+        // preconditions shared by all achievers that may appear in a plan
+        let mut shared: Option<Vec<Lit>> = None;
+        for &op in ops.achievers_of(landmark) {
+            if costs.operator_cost(op).is_infinite() {
+                continue;
+            }
+            let pre = ops.preconditions(op);
+            shared = Some(match shared {
+                None => pre.to_vec(),
+                Some(shared) => shared.into_iter().filter(|l| pre.contains(l)).collect(),
+            });
+        }
+        queue.extend(
+            shared
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&l| !state.entails(l) && !landmarks.contains(&l)),
+        );
+    }
+    landmarks
+}
@@ -14,6 +14,8 @@ use std::ops::Deref;
 use streaming_iterator::StreamingIterator;
 
 pub mod heuristics;
+pub mod pddl;
+pub mod sas;
 pub mod search;
 pub mod state;
 
@@ -0,0 +1,163 @@
+//! Export of a ground classical problem back into PDDL, e.g. to check the output of the grounder or to run
+//! other planners on the very same ground instance.
+//!
+//! Each operator is written as an action without parameters, whose conditions and effects are ground atoms
+//! on the original predicates. The objects appearing in these atoms are declared as constants of the domain.
+
+use crate::classical::state::{Lit, SvId, World};
+use crate::classical::GroundProblem;
+use aries::model::symbols::SymId;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{Result, Write};
+
+/// Ground atom of a state variable, e.g. `(at bob kitchen)`.
+fn atom(sv: SvId, world: &World) -> String {
+    let symbols: Vec<&str> = world
+        .sv_of(sv)
+        .iter()
+        .map(|&sym| world.table.symbol(sym).as_ref())
+        .collect();
+    format!("({})", symbols.join(" "))
+}
+
+fn literal(lit: Lit, world: &World) -> String {
+    if lit.val() {
+        atom(lit.var(), world)
+    } else {
+        format!("(not {})", atom(lit.var(), world))
+    }
+}
+
+/// Writes the ground problem as a PDDL domain, named `name`, and a PDDL problem.
+///
+/// Operators with contradictory preconditions, that can never be applied, are omitted.
+/// As in STRIPS, an add effect takes precedence over a delete effect on the same atom.
+pub fn write_pddl(
+    world: &World,
+    pb: &GroundProblem,
+    name: &str,
+    domain: &mut impl Write,
+    problem: &mut impl Write,
+) -> Result<()> {
+    let variables: Vec<SvId> = pb.initial_state.state_variables().collect();
+    // arity of each predicate and objects appearing as arguments
+    let mut predicates: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut objects: BTreeSet<SymId> = BTreeSet::new();
+    for &sv in &variables {
+        let sv = world.sv_of(sv);
+        predicates.insert(world.table.symbol(sv[0]).as_ref(), sv.len() - 1);
+        objects.extend(sv[1..].iter().copied());
+    }
+    let negative_preconditions = pb
+        .operators
+        .iter()
+        .flat_map(|op| pb.operators.preconditions(op))
+        .chain(&pb.goals)
+        .any(|lit| !lit.val());
+
+    writeln!(domain, "(define (domain {name})")?;
+    let requirements = if negative_preconditions {
+        ":strips :negative-preconditions"
+    } else {
+        ":strips"
+    };
+    writeln!(domain, "  (:requirements {requirements})")?;
+    let objects: Vec<&str> = objects.iter().map(|&sym| world.table.symbol(sym).as_ref()).collect();
+    writeln!(domain, "  (:constants {})", objects.join(" "))?;
+    writeln!(domain, "  (:predicates")?;
+    for (pred, arity) in predicates {
+        let params: String = (0..arity).map(|i| format!(" ?x{i}")).collect();
+        writeln!(domain, "    ({pred}{params})")?;
+    }
+    writeln!(domain, "  )")?;
+    for op in pb.operators.iter() {
+        let pre = pb.operators.preconditions(op);
+        if pre.iter().any(|&l| pre.contains(&!l)) {
+            continue;
+        }
+        let effects = pb.operators.effects(op);
+        let effects = effects.iter().filter(|&&l| l.val() || !effects.contains(&!l));
+        let op_name: Vec<&str> = pb
+            .operators
+            .name(op)
+            .iter()
+            .map(|&sym| world.table.symbol(sym).as_ref())
+            .collect();
+        writeln!(domain, "  (:action {}", op_name.join("_"))?;
+        let pre: Vec<String> = pre.iter().map(|&l| literal(l, world)).collect();
+        writeln!(domain, "    :precondition (and {})", pre.join(" "))?;
+        let effects: Vec<String> = effects.map(|&l| literal(l, world)).collect();
+        writeln!(domain, "    :effect (and {}))", effects.join(" "))?;
+    }
+    writeln!(domain, ")")?;
+
+    writeln!(problem, "(define (problem {name}-problem) (:domain {name})")?;
+    writeln!(problem, "  (:init")?;
+    for sv in pb.initial_state.entailed_variables() {
+        writeln!(problem, "    {}", atom(sv, world))?;
+    }
+    writeln!(problem, "  )")?;
+    let goals: Vec<String> = pb.goals.iter().map(|&l| literal(l, world)).collect();
+    writeln!(problem, "  (:goal (and {}))", goals.join(" "))?;
+    writeln!(problem, ")")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classical::{from_chronicles, grounded_problem};
+    use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use crate::parsing::pddl_to_chronicles;
+    use aries::utils::input::Input;
+
+    fn ground(domain: &str, problem: &str) -> (World, GroundProblem) {
+        let domain = parse_pddl_domain(Input::from_string(domain)).unwrap();
+        let problem = parse_pddl_problem(Input::from_string(problem)).unwrap();
+        let lifted = from_chronicles(&pddl_to_chronicles(&domain, &problem).unwrap()).unwrap();
+        let grounded = grounded_problem(&lifted).unwrap();
+        (lifted.world, grounded)
+    }
+
+    #[test]
+    fn ground_pddl_roundtrip() {
+        let domain = "(define (domain rooms) (:requirements :strips :typing)
+          (:types room)
+          (:predicates (at ?r - room) (connected ?a ?b - room))
+          (:action move :parameters (?a ?b - room)
+             :precondition (and (at ?a) (connected ?a ?b)) :effect (and (at ?b) (not (at ?a)))))";
+        let problem = "(define (problem p) (:domain rooms)
+          (:objects r1 r2 r3 - room)
+          (:init (at r1) (connected r1 r2) (connected r2 r3))
+          (:goal (at r3)))";
+        let (world, grounded) = ground(domain, problem);
+        let mut ground_domain = Vec::new();
+        let mut ground_problem = Vec::new();
+        write_pddl(
+            &world,
+            &grounded,
+            "rooms-ground",
+            &mut ground_domain,
+            &mut ground_problem,
+        )
+        .unwrap();
+        let ground_domain = String::from_utf8(ground_domain).unwrap();
+        let ground_problem = String::from_utf8(ground_problem).unwrap();
+        assert!(ground_domain.contains("(:action move_r1_r2"), "{ground_domain}");
+
+        // the ground problem has the same operators, initial state and goals
+        let (world2, regrounded) = ground(&ground_domain, &ground_problem);
+        assert_eq!(regrounded.operators.size(), grounded.operators.size());
+        let init = |world: &World, pb: &GroundProblem| {
+            let mut atoms: Vec<String> = pb
+                .initial_state
+                .entailed_variables()
+                .map(|sv| atom(sv, world))
+                .collect();
+            atoms.sort();
+            atoms
+        };
+        assert_eq!(init(&world2, &regrounded), init(&world, &grounded));
+        assert_eq!(regrounded.goals.len(), 1);
+        assert_eq!(literal(regrounded.goals[0], &world2), "(at r3)");
+    }
+}
@@ -0,0 +1,364 @@
+//! Export of a ground classical problem into the SAS+ format of Fast Downward's translator
+//! (see https://www.fast-downward.org/TranslatorOutputFormat).
+//!
+//! Each boolean state variable of the problem is exported as a binary SAS+ variable whose value `0` represents
+//! the atom and value `1` its negation, which is how Fast Downward represents fluents that are not part of any
+//! mutex group. No mutex group is exported.
+
+use crate::classical::state::{Lit, State, SvId, World};
+use crate::classical::GroundProblem;
+use std::collections::BTreeMap;
+use std::io::{Result, Write};
+
+/// Value of a binary SAS+ variable making the literal true.
+fn sas_value(lit: Lit) -> u32 {
+    if lit.val() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Name of the atom of a state variable, in the format of Fast Downward, e.g. `at(bob, kitchen)`.
+fn atom_name(sv: SvId, world: &World) -> String {
+    let symbols: Vec<&str> = world
+        .sv_of(sv)
+        .iter()
+        .map(|&sym| world.table.symbol(sym).as_ref())
+        .collect();
+    format!("{}({})", symbols[0], symbols[1..].join(", "))
+}
+
+/// Writes the ground problem in the SAS+ format.
+/// Operators with contradictory preconditions, that can never be applied, are omitted.
+pub fn write_sas(world: &World, pb: &GroundProblem, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "begin_version\n3\nend_version")?;
+    writeln!(out, "begin_metric\n0\nend_metric")?;
+
+    let variables: Vec<SvId> = pb.initial_state.state_variables().collect();
+    writeln!(out, "{}", variables.len())?;
+    for &sv in &variables {
+        let atom = atom_name(sv, world);
+        writeln!(out, "begin_variable\nvar{}\n-1\n2", usize::from(sv))?;
+        writeln!(out, "Atom {atom}\nNegatedAtom {atom}\nend_variable")?;
+    }
+    // mutex groups
+    writeln!(out, "0")?;
+
+    write_state(&pb.initial_state, out)?;
+
+    writeln!(out, "begin_goal\n{}", pb.goals.len())?;
+    for &goal in &pb.goals {
+        writeln!(out, "{} {}", usize::from(goal.var()), sas_value(goal))?;
+    }
+    writeln!(out, "end_goal")?;
+
+    let operators: Vec<String> = pb
+        .operators
+        .iter()
+        .filter_map(|op| {
+            let name = pb.operators.name(op);
+            let name: Vec<&str> = name.iter().map(|&sym| world.table.symbol(sym).as_ref()).collect();
+            sas_operator(
+                &name.join(" "),
+                pb.operators.preconditions(op),
+                pb.operators.effects(op),
+            )
+        })
+        .collect();
+    writeln!(out, "{}", operators.len())?;
+    for op in operators {
+        write!(out, "{op}")?;
+    }
+
+    // axioms
+    writeln!(out, "0")?;
+    Ok(())
+}
+
+fn write_state(state: &State, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "begin_state")?;
+    for lit in state.literals() {
+        writeln!(out, "{}", sas_value(lit))?;
+    }
+    writeln!(out, "end_state")
+}
+
+/// Representation of an [operator](crate::classical::state::Operator) in the SAS+ format,
+/// or `None` if its preconditions are contradictory.
+///
+/// Preconditions on variables that are not modified by the operator are prevail conditions.
+/// As in STRIPS, add effects take precedence over delete effects on the same variable.
+/// Effects that do not change the value of their variable are dropped.
+fn sas_operator(name: &str, preconditions: &[Lit], effects: &[Lit]) -> Option<String> {
+    let mut pre: BTreeMap<SvId, Lit> = BTreeMap::new();
+    for &lit in preconditions {
+        if *pre.entry(lit.var()).or_insert(lit) != lit {
+            return None;
+        }
+    }
+    let mut post: BTreeMap<SvId, Lit> = BTreeMap::new();
+    for &lit in effects {
+        let eff = post.entry(lit.var()).or_insert(lit);
+        if lit.val() {
+            *eff = lit;
+        }
+    }
+    // effects that do not change the value required by the preconditions are no-ops
+    post.retain(|sv, lit| pre.get(sv) != Some(lit));
+
+    let mut res = format!("begin_operator\n{name}\n");
+    let prevail: Vec<Lit> = pre.values().copied().filter(|l| !post.contains_key(&l.var())).collect();
+    res += &format!("{}\n", prevail.len());
+    for l in prevail {
+        res += &format!("{} {}\n", usize::from(l.var()), sas_value(l));
+    }
+    res += &format!("{}\n", post.len());
+    for (sv, l) in post {
+        let pre_value = pre.get(&sv).map(|&p| sas_value(p) as i32).unwrap_or(-1);
+        res += &format!("0 {} {} {}\n", usize::from(sv), pre_value, sas_value(l));
+    }
+    res += "1\nend_operator\n";
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classical::{from_chronicles, grounded_problem};
+    use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use crate::parsing::pddl_to_chronicles;
+    use aries::utils::input::Input;
+
+    const GRIPPER: &str = "(define (domain gripper) (:requirements :strips :typing)
+      (:types room ball gripper)
+      (:predicates (at-robby ?r - room) (at ?b - ball ?r - room) (free ?g - gripper) (carry ?b - ball ?g - gripper))
+      (:action move :parameters (?from ?to - room)
+         :precondition (at-robby ?from)
+         :effect (and (at-robby ?to) (not (at-robby ?from))))
+      (:action pick :parameters (?b - ball ?r - room ?g - gripper)
+         :precondition (and (at ?b ?r) (at-robby ?r) (free ?g))
+         :effect (and (carry ?b ?g) (not (at ?b ?r)) (not (free ?g))))
+      (:action drop :parameters (?b - ball ?r - room ?g - gripper)
+         :precondition (and (carry ?b ?g) (at-robby ?r))
+         :effect (and (at ?b ?r) (free ?g) (not (carry ?b ?g)))))";
+
+    const GRIPPER_PROBLEM: &str = "(define (problem gripper-2) (:domain gripper)
+      (:objects rooma roomb - room ball1 ball2 - ball left - gripper)
+      (:init (at-robby rooma) (free left) (at ball1 rooma) (at ball2 rooma))
+      (:goal (and (at ball1 roomb) (at ball2 roomb))))";
+
+    #[test]
+    fn test_write_sas() {
+        let domain = parse_pddl_domain(Input::from_string(GRIPPER)).unwrap();
+        let problem = parse_pddl_problem(Input::from_string(GRIPPER_PROBLEM)).unwrap();
+        let lifted = from_chronicles(&pddl_to_chronicles(&domain, &problem).unwrap()).unwrap();
+        let grounded = grounded_problem(&lifted).unwrap();
+        let mut out = Vec::new();
+        write_sas(&lifted.world, &grounded, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), EXPECTED);
+    }
+
+    /// Variables are the ground atoms, each operator has its preconditions on unmodified variables as prevail
+    /// conditions (e.g. `at-robby(rooma)` for `pick ball1 rooma left`), and no mutex group is exported.
+    const EXPECTED: &str = "begin_version
+3
+end_version
+begin_metric
+0
+end_metric
+9
+begin_variable
+var0
+-1
+2
+Atom at-robby(rooma)
+NegatedAtom at-robby(rooma)
+end_variable
+begin_variable
+var1
+-1
+2
+Atom at-robby(roomb)
+NegatedAtom at-robby(roomb)
+end_variable
+begin_variable
+var2
+-1
+2
+Atom at(ball1, rooma)
+NegatedAtom at(ball1, rooma)
+end_variable
+begin_variable
+var3
+-1
+2
+Atom at(ball1, roomb)
+NegatedAtom at(ball1, roomb)
+end_variable
+begin_variable
+var4
+-1
+2
+Atom at(ball2, rooma)
+NegatedAtom at(ball2, rooma)
+end_variable
+begin_variable
+var5
+-1
+2
+Atom at(ball2, roomb)
+NegatedAtom at(ball2, roomb)
+end_variable
+begin_variable
+var6
+-1
+2
+Atom free(left)
+NegatedAtom free(left)
+end_variable
+begin_variable
+var7
+-1
+2
+Atom carry(ball1, left)
+NegatedAtom carry(ball1, left)
+end_variable
+begin_variable
+var8
+-1
+2
+Atom carry(ball2, left)
+NegatedAtom carry(ball2, left)
+end_variable
+0
+begin_state
+0
+1
+0
+1
+0
+1
+0
+1
+1
+end_state
+begin_goal
+2
+3 0
+5 0
+end_goal
+12
+begin_operator
+move rooma rooma
+1
+0 0
+0
+1
+end_operator
+begin_operator
+move rooma roomb
+0
+2
+0 0 0 1
+0 1 -1 0
+1
+end_operator
+begin_operator
+move roomb rooma
+0
+2
+0 0 -1 0
+0 1 0 1
+1
+end_operator
+begin_operator
+move roomb roomb
+1
+1 0
+0
+1
+end_operator
+begin_operator
+pick ball1 rooma left
+1
+0 0
+3
+0 2 0 1
+0 6 0 1
+0 7 -1 0
+1
+end_operator
+begin_operator
+pick ball1 roomb left
+1
+1 0
+3
+0 3 0 1
+0 6 0 1
+0 7 -1 0
+1
+end_operator
+begin_operator
+pick ball2 rooma left
+1
+0 0
+3
+0 4 0 1
+0 6 0 1
+0 8 -1 0
+1
+end_operator
+begin_operator
+pick ball2 roomb left
+1
+1 0
+3
+0 5 0 1
+0 6 0 1
+0 8 -1 0
+1
+end_operator
+begin_operator
+drop ball1 rooma left
+1
+0 0
+3
+0 2 -1 0
+0 6 -1 0
+0 7 0 1
+1
+end_operator
+begin_operator
+drop ball1 roomb left
+1
+1 0
+3
+0 3 -1 0
+0 6 -1 0
+0 7 0 1
+1
+end_operator
+begin_operator
+drop ball2 rooma left
+1
+0 0
+3
+0 4 -1 0
+0 6 -1 0
+0 8 0 1
+1
+end_operator
+begin_operator
+drop ball2 roomb left
+1
+1 0
+3
+0 5 -1 0
+0 6 -1 0
+0 8 0 1
+1
+end_operator
+0
+";
+}
@@ -1,5 +1,6 @@
 use crate::classical::heuristics::*;
 use crate::classical::state::*;
+use crate::classical::GroundProblem;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashSet, VecDeque};
 use std::rc::Rc;
@@ -148,6 +149,60 @@ pub fn plan_search(initial_state: &State, ops: &Operators, goals: &[Lit], cfg: &
     None
 }
 
+/// Exhaustive breadth-first search of a plan of at most `max_steps` steps, returning a shortest one if any.
+///
+/// The search explores the whole state space reachable within `max_steps` steps and is thus only practical on
+/// small instances, where it serves as an oracle to check the other planners (e.g. that no plan exists when
+/// they report the problem as unsolvable).
+pub fn shortest_plan_upto(initial_state: &State, ops: &Operators, goals: &[Lit], max_steps: usize) -> Option<Vec<Op>> {
+    // all states reached so far, with the index of their parent state and the operator leading from it
+    let mut nodes: Vec<(State, Option<(usize, Op)>)> = vec![(initial_state.clone(), None)];
+    let mut visited: HashSet<State> = HashSet::from([initial_state.clone()]);
+    let extract_plan = |nodes: &[(State, Option<(usize, Op)>)], mut node: usize| {
+        let mut plan = Vec::new();
+        while let Some((parent, op)) = nodes[node].1 {
+            plan.push(op);
+            node = parent;
+        }
+        plan.reverse();
+        plan
+    };
+    if initial_state.entails_all(goals) {
+        return Some(Vec::new());
+    }
+    // states reached with the last number of steps
+    let mut layer = 0..1;
+    for _ in 0..max_steps {
+        let next_layer_start = nodes.len();
+        for node in layer {
+            for op in ops.iter() {
+                if !nodes[node].0.entails_all(ops.preconditions(op)) {
+                    continue;
+                }
+                let mut s = nodes[node].0.clone();
+                s.set_all(ops.effects(op));
+                if visited.insert(s.clone()) {
+                    let reached_goals = s.entails_all(goals);
+                    nodes.push((s, Some((node, op))));
+                    if reached_goals {
+                        return Some(extract_plan(&nodes, nodes.len() - 1));
+                    }
+                }
+            }
+        }
+        layer = next_layer_start..nodes.len();
+        if layer.is_empty() {
+            break; // all reachable states were explored
+        }
+    }
+    None
+}
+
+/// Returns true if the ground problem has a plan of at most `max_steps` steps (see [shortest_plan_upto]).
+pub fn plan_exists_upto(pb: &GroundProblem, max_steps: usize) -> bool {
+    shortest_plan_upto(&pb.initial_state, &pb.operators, &pb.goals, max_steps).is_some()
+}
+
 /// For a given node that is not already in the closed list:
 ///  - compute its heuristic value
 ///  - inserts it in the open and closed lists
@@ -360,3 +415,43 @@ fn lookahead(
     );
     (s, plan)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classical::{from_chronicles, grounded_problem};
+    use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use crate::parsing::pddl_to_chronicles;
+    use aries::utils::input::Input;
+
+    #[test]
+    fn test_plan_exists_upto() {
+        let domain = "(define (domain rooms) (:requirements :strips :typing)
+          (:types room)
+          (:predicates (at ?r - room) (connected ?a ?b - room))
+          (:action move :parameters (?a ?b - room)
+             :precondition (and (at ?a) (connected ?a ?b)) :effect (and (at ?b) (not (at ?a)))))";
+        let problem = "(define (problem p) (:domain rooms)
+          (:objects r1 r2 r3 r4 - room)
+          (:init (at r1) (connected r1 r2) (connected r2 r1) (connected r2 r3) (connected r1 r3) (connected r4 r1))
+          (:goal (at r3)))";
+        let domain = parse_pddl_domain(Input::from_string(domain)).unwrap();
+        let problem = parse_pddl_problem(Input::from_string(problem)).unwrap();
+        let lifted = from_chronicles(&pddl_to_chronicles(&domain, &problem).unwrap()).unwrap();
+        let pb = grounded_problem(&lifted).unwrap();
+
+        assert!(!plan_exists_upto(&pb, 0));
+        assert!(plan_exists_upto(&pb, 1));
+        let plan = shortest_plan_upto(&pb.initial_state, &pb.operators, &pb.goals, 5).unwrap();
+        assert_eq!(plan.len(), 1);
+
+        // r4 is unreachable
+        let mut unreachable = pb;
+        let r4 = lifted.world.sv_id(&[
+            lifted.world.table.id("at").unwrap(),
+            lifted.world.table.id("r4").unwrap(),
+        ]);
+        unreachable.goals = vec![Lit::new(r4.unwrap(), true)];
+        assert!(!plan_exists_upto(&unreachable, 10));
+    }
+}
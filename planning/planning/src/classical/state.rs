@@ -338,12 +338,18 @@ impl Operators {
 
     /// Returns all operators that have `lit` as a precondition.
     pub fn dependent_on(&self, lit: Lit) -> &[Op] {
-        self.watchers[lit].as_slice()
+        match self.watchers.last_key() {
+            Some(last) if last >= lit => self.watchers[lit].as_slice(),
+            _ => &[], // no entry for literals that appear in no precondition
+        }
     }
 
     /// Returns all operators that have `lit` as an effect.
     pub fn achievers_of(&self, lit: Lit) -> &[Op] {
-        self.achievers[lit].as_slice()
+        match self.achievers.last_key() {
+            Some(last) if last >= lit => self.achievers[lit].as_slice(),
+            _ => &[], // no entry for literals that appear in no effect
+        }
     }
 
     /// An iterator on all Operators in this data structure.
@@ -0,0 +1,627 @@
+//! Generation of small random planning problems, intended for the differential testing of planners.
+//!
+//! A [RandomProblem] is generated from a seed and can be exported as a PDDL (or HDDL) domain and problem,
+//! from which the corresponding chronicle problem can be built. It is well-typed by construction:
+//! all atoms only use parameters and objects of the expected types, and its task hierarchy (if any) is non-recursive.
+//!
+//! Because problems are kept tiny, the existence of a plan can be decided by brute force ([RandomProblem::brute_force]),
+//! independently of the parser and of the encoding of chronicles.
+
+use crate::chronicles::Problem;
+use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+use crate::parsing::pddl_to_chronicles;
+use aries::utils::input::Input;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+
+/// Bounds on the size of the generated problems.
+#[derive(Clone, Debug)]
+pub struct GeneratorParams {
+    pub max_types: usize,
+    /// Maximum number of objects, including the one that is created for each type.
+    pub max_objects: usize,
+    pub max_predicates: usize,
+    /// Maximum number of parameters of predicates, actions, tasks and methods.
+    pub max_arity: usize,
+    pub max_actions: usize,
+    pub max_conditions: usize,
+    pub max_effects: usize,
+    pub max_goals: usize,
+    /// Probability that a condition is negative.
+    pub negative_conditions: f64,
+    /// Probability that an atom holds in the initial state.
+    pub init_density: f64,
+    /// If true, the problem is hierarchical: it has an initial task network and no goals.
+    pub hierarchical: bool,
+    pub max_tasks: usize,
+    pub max_methods_per_task: usize,
+    pub max_subtasks: usize,
+}
+
+impl Default for GeneratorParams {
+    fn default() -> Self {
+        GeneratorParams {
+            max_types: 2,
+            max_objects: 4,
+            max_predicates: 3,
+            max_arity: 2,
+            max_actions: 3,
+            max_conditions: 2,
+            max_effects: 2,
+            max_goals: 2,
+            negative_conditions: 0.3,
+            init_density: 0.3,
+            hierarchical: false,
+            max_tasks: 2,
+            max_methods_per_task: 2,
+            max_subtasks: 2,
+        }
+    }
+}
+
+/// A (possibly negated) atom whose arguments are indices in the parameters of the enclosing action or method.
+#[derive(Clone, Debug)]
+struct Atom {
+    positive: bool,
+    predicate: usize,
+    args: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Predicate {
+    /// Type of each parameter
+    params: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Action {
+    params: Vec<usize>,
+    conditions: Vec<Atom>,
+    effects: Vec<Atom>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum TaskRef {
+    Action(usize),
+    Task(usize),
+}
+
+#[derive(Clone, Debug)]
+struct Subtask {
+    task: TaskRef,
+    /// Indices of the method parameters passed as arguments
+    args: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Method {
+    task: usize,
+    /// Types of the parameters. The first ones are the parameters of the task.
+    params: Vec<usize>,
+    conditions: Vec<Atom>,
+    subtasks: Vec<Subtask>,
+}
+
+/// A ground task or action, as an index and the objects of its arguments.
+type GroundTask = (TaskRef, Vec<usize>);
+
+/// A state of the brute force search, as the set of atoms that are true.
+type State = u128;
+
+/// Outcome of a brute force search for a plan.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BruteForceResult {
+    /// A plan exists, the number of actions of the shortest one is given.
+    Plan(usize),
+    NoPlan,
+    /// The search space was too large to be explored exhaustively.
+    Unknown,
+}
+
+/// A random, well-typed planning problem.
+#[derive(Clone, Debug)]
+pub struct RandomProblem {
+    pub seed: u64,
+    /// Type of each object
+    objects: Vec<usize>,
+    num_types: usize,
+    predicates: Vec<Predicate>,
+    actions: Vec<Action>,
+    /// Parameter types of each task
+    tasks: Vec<Vec<usize>>,
+    methods: Vec<Method>,
+    /// Initial state, as a set of ground atoms
+    init: State,
+    /// Ground atoms that must hold at the end of the plan
+    goals: Vec<(usize, Vec<usize>)>,
+    task_network: Vec<GroundTask>,
+}
+
+impl RandomProblem {
+    /// Generates a new random problem. The same seed and parameters always result in the same problem.
+    pub fn generate(seed: u64, params: &GeneratorParams) -> RandomProblem {
+        let rng = &mut StdRng::seed_from_u64(seed);
+        let num_types = rng.gen_range(1..=params.max_types);
+        // one object of each type, the others are of random types
+        let num_objects = rng.gen_range(num_types..=params.max_objects.max(num_types));
+        let objects: Vec<usize> = (0..num_objects)
+            .map(|i| if i < num_types { i } else { rng.gen_range(0..num_types) })
+            .collect();
+        let mut pb = RandomProblem {
+            seed,
+            objects,
+            num_types,
+            predicates: Vec::new(),
+            actions: Vec::new(),
+            tasks: Vec::new(),
+            methods: Vec::new(),
+            init: 0,
+            goals: Vec::new(),
+            task_network: Vec::new(),
+        };
+        // keep at most 128 ground atoms so that a state fits in a `u128`
+        let max_arity = params.max_arity.min(if num_objects > 5 { 1 } else { 2 });
+        for _ in 0..rng.gen_range(1..=params.max_predicates.min(5)) {
+            let params = random_types(rng, num_types, max_arity);
+            pb.predicates.push(Predicate { params });
+        }
+
+        for _ in 0..rng.gen_range(1..=params.max_actions) {
+            let action_params = random_types(rng, num_types, params.max_arity);
+            let conditions = (0..rng.gen_range(0..=params.max_conditions))
+                .filter_map(|_| pb.random_atom(rng, &action_params, params.negative_conditions))
+                .collect();
+            let effects = (0..rng.gen_range(1..=params.max_effects))
+                .filter_map(|_| pb.random_atom(rng, &action_params, 0.5))
+                .collect();
+            pb.actions.push(Action {
+                params: action_params,
+                conditions,
+                effects,
+            });
+        }
+
+        for (predicate, args) in pb.ground_atoms() {
+            if rng.gen_bool(params.init_density) {
+                pb.init |= 1 << pb.atom_index(predicate, &args);
+            }
+        }
+
+        if params.hierarchical {
+            let num_tasks = rng.gen_range(1..=params.max_tasks);
+            for _ in 0..num_tasks {
+                pb.tasks.push(random_types(rng, num_types, params.max_arity.min(1)));
+            }
+            for task in 0..num_tasks {
+                for _ in 0..rng.gen_range(1..=params.max_methods_per_task) {
+                    let method = pb.random_method(rng, task, params);
+                    pb.methods.push(method);
+                }
+            }
+            for _ in 0..rng.gen_range(1..=params.max_subtasks) {
+                let task = rng.gen_range(0..num_tasks);
+                let args = pb.tasks[task].iter().map(|&t| pb.random_object(rng, t)).collect();
+                pb.task_network.push((TaskRef::Task(task), args));
+            }
+        } else {
+            for _ in 0..rng.gen_range(1..=params.max_goals) {
+                let predicate = rng.gen_range(0..pb.predicates.len());
+                let args = pb.predicates[predicate]
+                    .params
+                    .iter()
+                    .map(|&t| pb.random_object(rng, t))
+                    .collect();
+                pb.goals.push((predicate, args));
+            }
+        }
+        pb
+    }
+
+    pub fn is_hierarchical(&self) -> bool {
+        !self.tasks.is_empty()
+    }
+
+    fn random_object(&self, rng: &mut StdRng, tpe: usize) -> usize {
+        let candidates: Vec<usize> = (0..self.objects.len()).filter(|&o| self.objects[o] == tpe).collect();
+        candidates[rng.gen_range(0..candidates.len())]
+    }
+
+    /// Picks a random parameter of the given type, if any.
+    fn random_param(rng: &mut StdRng, params: &[usize], tpe: usize) -> Option<usize> {
+        let candidates: Vec<usize> = (0..params.len()).filter(|&p| params[p] == tpe).collect();
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[rng.gen_range(0..candidates.len())])
+        }
+    }
+
+    /// Builds an atom on a random predicate whose arguments can be taken from the parameters.
+    fn random_atom(&self, rng: &mut StdRng, params: &[usize], negative: f64) -> Option<Atom> {
+        let predicate = rng.gen_range(0..self.predicates.len());
+        let args: Option<Vec<usize>> = self.predicates[predicate]
+            .params
+            .iter()
+            .map(|&t| Self::random_param(rng, params, t))
+            .collect();
+        Some(Atom {
+            positive: !rng.gen_bool(negative),
+            predicate,
+            args: args?,
+        })
+    }
+
+    /// Builds a method for the task, whose subtasks are either actions or tasks of higher index,
+    /// which ensures that the hierarchy is non-recursive.
+    fn random_method(&self, rng: &mut StdRng, task: usize, params: &GeneratorParams) -> Method {
+        let mut method_params = self.tasks[task].clone();
+        method_params.extend(random_types(rng, self.num_types, 1));
+        let conditions = (0..rng.gen_range(0..=1))
+            .filter_map(|_| self.random_atom(rng, &method_params, params.negative_conditions))
+            .collect();
+        let mut subtasks = Vec::new();
+        for _ in 0..rng.gen_range(1..=params.max_subtasks) {
+            let num_choices = self.actions.len() + self.tasks.len() - task - 1;
+            let choice = rng.gen_range(0..num_choices);
+            let (subtask, types) = if choice < self.actions.len() {
+                (TaskRef::Action(choice), &self.actions[choice].params)
+            } else {
+                let t = task + 1 + choice - self.actions.len();
+                (TaskRef::Task(t), &self.tasks[t])
+            };
+            // arguments are taken from the parameters of the method, that are extended if no parameter has the required type
+            let args = types
+                .iter()
+                .map(|&t| {
+                    Self::random_param(rng, &method_params, t).unwrap_or_else(|| {
+                        method_params.push(t);
+                        method_params.len() - 1
+                    })
+                })
+                .collect();
+            subtasks.push(Subtask { task: subtask, args });
+        }
+        Method {
+            task,
+            params: method_params,
+            conditions,
+            subtasks,
+        }
+    }
+
+    fn num_groundings(&self, predicate: usize) -> usize {
+        self.objects.len().pow(self.predicates[predicate].params.len() as u32)
+    }
+
+    /// Index of a ground atom in the state bitset.
+    fn atom_index(&self, predicate: usize, args: &[usize]) -> usize {
+        let offset: usize = (0..predicate).map(|p| self.num_groundings(p)).sum();
+        offset + args.iter().fold(0, |acc, &a| acc * self.objects.len() + a)
+    }
+
+    // ========== Export ==========
+
+    /// Representation of the domain in PDDL, or in HDDL for hierarchical problems.
+    pub fn domain(&self) -> String {
+        let mut out = String::new();
+        let hierarchy = if self.is_hierarchical() { " :hierarchy" } else { "" };
+        writeln!(out, "(define (domain rnd)").unwrap();
+        writeln!(
+            out,
+            "  (:requirements :strips :typing :negative-preconditions{hierarchy})"
+        )
+        .unwrap();
+        let types: Vec<String> = (0..self.num_types).map(|t| format!("t{t}")).collect();
+        writeln!(out, "  (:types {} - object)", types.join(" ")).unwrap();
+        writeln!(out, "  (:predicates").unwrap();
+        for (i, p) in self.predicates.iter().enumerate() {
+            writeln!(out, "    (p{i}{})", typed_params(&p.params)).unwrap();
+        }
+        writeln!(out, "  )").unwrap();
+        for (i, task) in self.tasks.iter().enumerate() {
+            writeln!(out, "  (:task task{i} :parameters ({}))", typed_params(task).trim()).unwrap();
+        }
+        for (i, m) in self.methods.iter().enumerate() {
+            writeln!(out, "  (:method m{i}").unwrap();
+            writeln!(out, "    :parameters ({})", typed_params(&m.params).trim()).unwrap();
+            let task_args: Vec<usize> = (0..self.tasks[m.task].len()).collect();
+            writeln!(out, "    :task (task{}{})", m.task, lifted_args(&task_args)).unwrap();
+            writeln!(out, "    :precondition (and {})", lifted_atoms(&m.conditions)).unwrap();
+            let subtasks: Vec<String> = m
+                .subtasks
+                .iter()
+                .map(|s| format!("({}{})", task_name(s.task), lifted_args(&s.args)))
+                .collect();
+            writeln!(out, "    :ordered-subtasks (and {}))", subtasks.join(" ")).unwrap();
+        }
+        for (i, a) in self.actions.iter().enumerate() {
+            writeln!(out, "  (:action a{i}").unwrap();
+            writeln!(out, "    :parameters ({})", typed_params(&a.params).trim()).unwrap();
+            writeln!(out, "    :precondition (and {})", lifted_atoms(&a.conditions)).unwrap();
+            writeln!(out, "    :effect (and {}))", lifted_atoms(&a.effects)).unwrap();
+        }
+        writeln!(out, ")").unwrap();
+        out
+    }
+
+    /// Representation of the problem in PDDL, or in HDDL for hierarchical problems.
+    pub fn problem(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "(define (problem rnd-{}) (:domain rnd)", self.seed).unwrap();
+        let objects: Vec<String> = (0..self.objects.len())
+            .map(|o| format!("o{o} - t{}", self.objects[o]))
+            .collect();
+        writeln!(out, "  (:objects {})", objects.join(" ")).unwrap();
+        if self.is_hierarchical() {
+            let tasks: Vec<String> = self
+                .task_network
+                .iter()
+                .map(|(t, args)| format!("({}{})", task_name(*t), ground_args(args)))
+                .collect();
+            writeln!(
+                out,
+                "  (:htn :parameters () :ordered-subtasks (and {}))",
+                tasks.join(" ")
+            )
+            .unwrap();
+        }
+        let mut init = Vec::new();
+        for (predicate, args) in self.ground_atoms() {
+            if self.init & (1 << self.atom_index(predicate, &args)) != 0 {
+                init.push(format!("(p{predicate}{})", ground_args(&args)));
+            }
+        }
+        writeln!(out, "  (:init {})", init.join(" ")).unwrap();
+        if !self.is_hierarchical() {
+            let goals: Vec<String> = self
+                .goals
+                .iter()
+                .map(|(p, args)| format!("(p{p}{})", ground_args(args)))
+                .collect();
+            writeln!(out, "  (:goal (and {}))", goals.join(" ")).unwrap();
+        }
+        writeln!(out, ")").unwrap();
+        out
+    }
+
+    /// Parses the PDDL representation of the problem into a chronicle problem.
+    pub fn to_chronicles(&self) -> anyhow::Result<Problem> {
+        let dom = parse_pddl_domain(Input::from_string(self.domain()))?;
+        let prob = parse_pddl_problem(Input::from_string(self.problem()))?;
+        pddl_to_chronicles(&dom, &prob)
+    }
+
+    /// All ground atoms, regardless of the types of their arguments.
+    fn ground_atoms(&self) -> Vec<(usize, Vec<usize>)> {
+        let mut atoms = Vec::new();
+        for (p, pred) in self.predicates.iter().enumerate() {
+            for args in self.groundings(&pred.params) {
+                atoms.push((p, args));
+            }
+        }
+        atoms
+    }
+
+    /// All well-typed assignments of objects to the given parameter types.
+    fn groundings(&self, params: &[usize]) -> Vec<Vec<usize>> {
+        let mut groundings = vec![Vec::new()];
+        for &tpe in params {
+            groundings = groundings
+                .into_iter()
+                .flat_map(|g| {
+                    (0..self.objects.len())
+                        .filter(move |&o| self.objects[o] == tpe)
+                        .map(move |o| {
+                            let mut g = g.clone();
+                            g.push(o);
+                            g
+                        })
+                })
+                .collect();
+        }
+        groundings
+    }
+
+    // ========== Brute force search ==========
+
+    /// Masks of the atoms that must be true and false for the conditions to hold.
+    fn condition_masks(&self, conditions: &[Atom], binding: &[usize]) -> (State, State) {
+        let mut pos = 0;
+        let mut neg = 0;
+        for c in conditions {
+            let args: Vec<usize> = c.args.iter().map(|&a| binding[a]).collect();
+            let bit = 1 << self.atom_index(c.predicate, &args);
+            if c.positive {
+                pos |= bit;
+            } else {
+                neg |= bit;
+            }
+        }
+        (pos, neg)
+    }
+
+    /// Applies the ground action to the state, returning `None` if it is not applicable.
+    /// As in PDDL, delete effects are applied before add effects.
+    fn apply(&self, state: State, action: usize, binding: &[usize]) -> Option<State> {
+        let a = &self.actions[action];
+        let (pos, neg) = self.condition_masks(&a.conditions, binding);
+        if state & pos != pos || state & neg != 0 {
+            return None;
+        }
+        let (add, del) = self.condition_masks(&a.effects, binding);
+        Some((state & !del) | add)
+    }
+
+    /// Decides whether the problem has a plan by exhaustively exploring its state space (or its decompositions for
+    /// hierarchical problems). Gives up if more than `max_states` search nodes are explored.
+    pub fn brute_force(&self, max_states: usize) -> BruteForceResult {
+        if self.is_hierarchical() {
+            self.brute_force_htn(max_states)
+        } else {
+            self.brute_force_classical(max_states)
+        }
+    }
+
+    /// Breadth first search, that returns the length of the shortest plan.
+    fn brute_force_classical(&self, max_states: usize) -> BruteForceResult {
+        let ground_actions: Vec<(usize, Vec<usize>)> = (0..self.actions.len())
+            .flat_map(|a| {
+                self.groundings(&self.actions[a].params)
+                    .into_iter()
+                    .map(move |b| (a, b))
+            })
+            .collect();
+        let goal: State = self
+            .goals
+            .iter()
+            .fold(0, |acc, (p, args)| acc | 1 << self.atom_index(*p, args));
+
+        let mut visited = HashSet::from([self.init]);
+        let mut queue = VecDeque::from([(self.init, 0)]);
+        while let Some((state, length)) = queue.pop_front() {
+            if state & goal == goal {
+                return BruteForceResult::Plan(length);
+            }
+            for (action, binding) in &ground_actions {
+                if let Some(next) = self.apply(state, *action, binding) {
+                    if visited.insert(next) {
+                        if visited.len() > max_states {
+                            return BruteForceResult::Unknown;
+                        }
+                        queue.push_back((next, length + 1));
+                    }
+                }
+            }
+        }
+        BruteForceResult::NoPlan
+    }
+
+    /// Depth first search over the decompositions of the task network, from left to right.
+    fn brute_force_htn(&self, max_states: usize) -> BruteForceResult {
+        // agenda of remaining tasks, with the next one last
+        let agenda: Vec<GroundTask> = self.task_network.iter().rev().cloned().collect();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(self.init, agenda, 0)];
+        let mut shortest: Option<usize> = None;
+        while let Some((state, mut agenda, length)) = stack.pop() {
+            if !visited.insert((state, agenda.clone(), length)) {
+                continue;
+            }
+            if visited.len() > max_states {
+                return BruteForceResult::Unknown;
+            }
+            let Some((task, args)) = agenda.pop() else {
+                shortest = Some(shortest.map_or(length, |s| s.min(length)));
+                continue;
+            };
+            match task {
+                TaskRef::Action(a) => {
+                    if let Some(next) = self.apply(state, a, &args) {
+                        stack.push((next, agenda, length + 1));
+                    }
+                }
+                TaskRef::Task(t) => {
+                    for m in self.methods.iter().filter(|m| m.task == t) {
+                        let num_task_params = self.tasks[t].len();
+                        for binding in self.groundings(&m.params[num_task_params..]) {
+                            let binding: Vec<usize> = args.iter().copied().chain(binding).collect();
+                            let (pos, neg) = self.condition_masks(&m.conditions, &binding);
+                            if state & pos != pos || state & neg != 0 {
+                                continue;
+                            }
+                            let mut agenda = agenda.clone();
+                            for s in m.subtasks.iter().rev() {
+                                agenda.push((s.task, s.args.iter().map(|&a| binding[a]).collect()));
+                            }
+                            stack.push((state, agenda, length));
+                        }
+                    }
+                }
+            }
+        }
+        match shortest {
+            Some(length) => BruteForceResult::Plan(length),
+            None => BruteForceResult::NoPlan,
+        }
+    }
+}
+
+fn random_types(rng: &mut StdRng, num_types: usize, max_arity: usize) -> Vec<usize> {
+    (0..rng.gen_range(0..=max_arity))
+        .map(|_| rng.gen_range(0..num_types))
+        .collect()
+}
+
+fn task_name(task: TaskRef) -> String {
+    match task {
+        TaskRef::Action(a) => format!("a{a}"),
+        TaskRef::Task(t) => format!("task{t}"),
+    }
+}
+
+fn typed_params(types: &[usize]) -> String {
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, t)| format!(" ?x{i} - t{t}"))
+        .collect()
+}
+
+fn lifted_args(args: &[usize]) -> String {
+    args.iter().map(|a| format!(" ?x{a}")).collect()
+}
+
+fn ground_args(args: &[usize]) -> String {
+    args.iter().map(|o| format!(" o{o}")).collect()
+}
+
+fn lifted_atoms(atoms: &[Atom]) -> String {
+    let atoms: Vec<String> = atoms
+        .iter()
+        .map(|a| {
+            let atom = format!("(p{}{})", a.predicate, lifted_args(&a.args));
+            if a.positive {
+                atom
+            } else {
+                format!("(not {atom})")
+            }
+        })
+        .collect();
+    atoms.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_problems_parse() {
+        for hierarchical in [false, true] {
+            let params = GeneratorParams {
+                hierarchical,
+                ..Default::default()
+            };
+            for seed in 0..50 {
+                let pb = RandomProblem::generate(seed, &params);
+                assert_eq!(pb.is_hierarchical(), hierarchical);
+                if let Err(e) = pb.to_chronicles() {
+                    panic!("seed {seed}: {e}\n{}\n{}", pb.domain(), pb.problem());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let params = GeneratorParams::default();
+        for seed in 0..10 {
+            let a = RandomProblem::generate(seed, &params);
+            let b = RandomProblem::generate(seed, &params);
+            assert_eq!(a.domain(), b.domain());
+            assert_eq!(a.problem(), b.problem());
+            assert_eq!(a.brute_force(100_000), b.brute_force(100_000));
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod chronicles;
 pub mod classical;
+pub mod generator;
 pub mod parsing;
@@ -11,8 +11,8 @@ use anyhow::{Context, Result};
 use aries::core::*;
 use aries::model::extensions::Shaped;
 use aries::model::lang::*;
-use aries::model::symbols::SymbolTable;
-use aries::model::types::TypeHierarchy;
+use aries::model::symbols::{SymId, SymbolTable};
+use aries::model::types::{TypeHierarchy, TypeId};
 use aries::utils::input::{ErrLoc, Loc, Sym};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
@@ -68,6 +68,8 @@ pub fn pddl_to_chronicles(dom: &pddl::Domain, prob: &pddl::Problem) -> Result<Pb
 
     let ts = TypeHierarchy::new(types)?;
     let mut symbols: Vec<TypedSymbol> = prob.objects.clone();
+    // existential objects are regular symbols, whose creation is decided when solving
+    symbols.extend(prob.existential_objects.iter().cloned());
     for c in &dom.constants {
         symbols.push(c.clone());
     }
@@ -135,6 +137,24 @@ pub fn pddl_to_chronicles(dom: &pddl::Domain, prob: &pddl::Problem) -> Result<Pb
 
     let mut context = Ctx::new(Arc::new(symbol_table), state_variables);
 
+    // group the existential objects into a pool for each type
+    let mut pools: Vec<(TypeId, Vec<SymId>)> = Vec::new();
+    for o in &prob.existential_objects {
+        let sym = context
+            .model
+            .get_symbol_table()
+            .id(&o.symbol)
+            .ok_or_else(|| o.symbol.invalid("Unknown symbol"))?;
+        let tpe = context.model.get_type_of(sym);
+        match pools.iter_mut().find(|(t, _)| *t == tpe) {
+            Some((_, objects)) => objects.push(sym),
+            None => pools.push((tpe, vec![sym])),
+        }
+    }
+    for (tpe, objects) in pools {
+        context.add_object_pool(tpe, objects);
+    }
+
     let init_container = Container::Instance(0);
     // Initial chronicle construction
     let mut init_ch = Chronicle {
@@ -167,7 +187,7 @@ pub fn pddl_to_chronicles(dom: &pddl::Domain, prob: &pddl::Problem) -> Result<Pb
         //  - `(and (= sv1 v1) (= sv2 = v2))`
         //  - `(= sv1 v1)`
         //  - `()`
-        let goals = read_conjunction(goal, as_model_atom)?;
+        let goals = read_conjunction(goal, as_model_atom).with_context(|| located("goal", goal))?;
         for TermLoc(goal, loc) in goals {
             match goal {
                 Term::Binding(sv, value) => init_ch.conditions.push(Condition {
@@ -210,21 +230,27 @@ pub fn pddl_to_chronicles(dom: &pddl::Domain, prob: &pddl::Problem) -> Result<Pb
         chronicle: init_ch,
     };
 
-    let mut templates = Vec::new();
+    // templates are all converted before reporting errors, so that all unsupported constructs are reported at once
+    let mut results = Vec::new();
     for a in &dom.actions {
-        let cont = Container::Template(templates.len());
-        let template = read_chronicle_template(cont, a, &mut context)?;
-        templates.push(template);
+        let cont = Container::Template(results.len());
+        results.push(read_located_chronicle_template(cont, a, &mut context));
     }
     for a in &dom.durative_actions {
-        let cont = Container::Template(templates.len());
-        let template = read_chronicle_template(cont, a, &mut context)?;
-        templates.push(template);
+        let cont = Container::Template(results.len());
+        results.push(read_located_chronicle_template(cont, a, &mut context));
     }
     for m in &dom.methods {
-        let cont = Container::Template(templates.len());
-        let template = read_chronicle_template(cont, m, &mut context)?;
-        templates.push(template);
+        let cont = Container::Template(results.len());
+        results.push(read_located_chronicle_template(cont, m, &mut context));
+    }
+    let (templates, mut errors): (Vec<_>, Vec<_>) = results.into_iter().partition_result();
+    if errors.len() == 1 {
+        return Err(errors.remove(0));
+    } else if !errors.is_empty() {
+        // one error per line, each with its chain of contexts
+        let errors = errors.iter().map(|e| format!("{e:#}")).join("\n");
+        anyhow::bail!("{errors}");
     }
 
     let problem = Problem {
@@ -387,7 +413,7 @@ fn read_chronicle_template(
     // TODO: here the cost is simply 1 for any primitive action
     let cost = match pddl.kind() {
         ChronicleKind::Problem | ChronicleKind::Method => None,
-        ChronicleKind::Action | ChronicleKind::DurativeAction => Some(1),
+        ChronicleKind::Action | ChronicleKind::DurativeAction => Some(IAtom::from(1)),
     };
 
     let mut ch = Chronicle {
@@ -408,7 +434,7 @@ fn read_chronicle_template(
         if pddl.kind() != ChronicleKind::Action && pddl.kind() != ChronicleKind::DurativeAction {
             return Err(eff.invalid("Unexpected instantaneous effect").into());
         }
-        let effects = read_conjunction(eff, as_chronicle_atom)?;
+        let effects = read_conjunction(eff, as_chronicle_atom).with_context(|| located("effect", eff))?;
         for TermLoc(term, loc) in effects {
             match term {
                 Term::Binding(sv, val) => ch.effects.push(Effect {
@@ -428,7 +454,7 @@ fn read_chronicle_template(
             return Err(eff.invalid("Unexpected effect").into());
         }
         // conjunction of effects of the form `(and (at-start (= sv1 v1)) (at-end (= sv2 v2)))`
-        let effects = read_temporal_conjunction(eff, as_chronicle_atom)?;
+        let effects = read_temporal_conjunction(eff, as_chronicle_atom).with_context(|| located("effect", eff))?;
         for TemporalTerm(qualification, term) in effects {
             match term.0 {
                 Term::Binding(state_var, value) => match qualification {
@@ -475,10 +501,14 @@ fn read_chronicle_template(
         e.value != Atom::from(false)
             || !positive_effects.contains(&(e.state_var.clone(), e.persistence_start, e.transition_start))
     });
+    // identical effects would otherwise be required not to overlap, which is impossible
+    let mut unique_effects = HashSet::new();
+    ch.effects
+        .retain(|e| unique_effects.insert((e.state_var.clone(), e.value, e.persistence_start, e.transition_start)));
 
     // TODO : check if work around still needed
     for cond in pddl.preconditions() {
-        let conditions = read_conjunction(cond, as_chronicle_atom)?;
+        let conditions = read_conjunction(cond, as_chronicle_atom).with_context(|| located("condition", cond))?;
         for TermLoc(term, _) in conditions {
             match term {
                 Term::Binding(sv, val) => {
@@ -513,7 +543,9 @@ fn read_chronicle_template(
     if let Some(dur) = pddl.duration() {
         // currently, we only support constraint of the form `(= ?duration <i32>)`
         // TODO: extend durations constraints, to support the full PDDL spec
-        let mut dur = dur.as_list_iter().unwrap();
+        let mut dur = dur
+            .as_list_iter()
+            .ok_or_else(|| dur.invalid("Expected a duration constraint of the form `(= ?duration <int>)`"))?;
         //Check for first two elements
         dur.pop_known_atom("=")?;
         dur.pop_known_atom("?duration")?;
@@ -522,8 +554,7 @@ fn read_chronicle_template(
         let duration = dur_atom
             .canonical_str()
             .parse::<i32>()
-            .map_err(|_| dur_atom.invalid("Expected an integer"))
-            .unwrap();
+            .map_err(|_| dur_atom.invalid("Expected an integer"))?;
         ch.constraints.push(Constraint::duration(duration));
         if let Ok(x) = dur.pop() {
             return Err(x.invalid("Unexpected").into());
@@ -532,7 +563,8 @@ fn read_chronicle_template(
 
     //Handling temporal conditions
     for cond in pddl.timed_conditions() {
-        let conditions = read_temporal_conjunction(cond, as_chronicle_atom)?;
+        let conditions =
+            read_temporal_conjunction(cond, as_chronicle_atom).with_context(|| located("condition", cond))?;
         //let duration = read_duration()?;
 
         for TemporalTerm(qualification, term) in conditions {
@@ -581,10 +613,33 @@ fn read_chronicle_template(
     Ok(template)
 }
 
+/// Transforms a PDDL construct into a chronicle template (see [read_chronicle_template]),
+/// annotating any error with the name and location of the construct.
+fn read_located_chronicle_template(
+    c: Container,
+    pddl: impl ChronicleTemplateView,
+    context: &mut Ctx,
+) -> Result<ChronicleTemplate> {
+    let kind = match pddl.kind() {
+        ChronicleKind::Problem => "problem",
+        ChronicleKind::Method => "method",
+        ChronicleKind::Action => "action",
+        ChronicleKind::DurativeAction => "durative action",
+    };
+    let name = pddl.base_name().clone();
+    let source = pddl.source().cloned();
+    read_chronicle_template(c, pddl, context).with_context(|| match source {
+        Some(loc) => format!("In {kind} `{name}` ({})", loc.position()),
+        None => format!("In {kind} `{name}`"),
+    })
+}
+
 /// An adapter to allow treating pddl actions and hddl methods identically
 trait ChronicleTemplateView {
     fn kind(&self) -> ChronicleKind;
     fn base_name(&self) -> &Sym;
+    /// Location of the construct in its source file, if known.
+    fn source(&self) -> Option<&Loc>;
     fn parameters(&self) -> &[TypedSymbol];
     fn task(&self) -> Option<&pddl::Task>;
     fn duration(&self) -> Option<&SExpr>;
@@ -601,6 +656,9 @@ impl ChronicleTemplateView for &pddl::Action {
     fn base_name(&self) -> &Sym {
         &self.name
     }
+    fn source(&self) -> Option<&Loc> {
+        self.source.as_ref()
+    }
     fn parameters(&self) -> &[TypedSymbol] {
         &self.args
     }
@@ -633,6 +691,9 @@ impl ChronicleTemplateView for &pddl::DurativeAction {
     fn base_name(&self) -> &Sym {
         &self.name
     }
+    fn source(&self) -> Option<&Loc> {
+        self.source.as_ref()
+    }
     fn parameters(&self) -> &[TypedSymbol] {
         &self.args
     }
@@ -665,6 +726,9 @@ impl ChronicleTemplateView for &pddl::Method {
     fn base_name(&self) -> &Sym {
         &self.name
     }
+    fn source(&self) -> Option<&Loc> {
+        self.source.as_ref()
+    }
     fn parameters(&self) -> &[TypedSymbol] {
         &self.parameters
     }
@@ -803,6 +867,11 @@ impl std::str::FromStr for TemporalQualification {
     }
 }
 
+/// Describes the location of a goal, condition or effect, to annotate errors raised while converting it.
+fn located(kind: &str, e: &SExpr) -> String {
+    format!("In {kind} at {}", e.loc().position())
+}
+
 fn read_conjunction(e: &SExpr, t: impl Fn(&sexpr::SAtom) -> Result<SAtom>) -> Result<Vec<TermLoc>> {
     let mut result = Vec::new();
     read_conjunction_impl(e, &t, &mut result)?;
@@ -989,3 +1058,32 @@ fn read_sv(e: &SExpr, desc: &World) -> Result<SvId> {
         )
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::pddl::{parse_pddl_domain, parse_pddl_problem};
+    use aries::utils::input::Input;
+
+    #[test]
+    fn test_identical_effects() {
+        let dom = "(define (domain move)
+          (:requirements :strips :typing)
+          (:types loc)
+          (:predicates (at ?l - loc) (visited ?l - loc))
+          (:action move :parameters (?a ?b - loc)
+            :precondition (and (at ?a))
+            :effect (and (not (at ?a)) (at ?b) (visited ?b) (at ?b) (not (at ?a)))))";
+        let pb = "(define (problem p) (:domain move)
+          (:objects l1 l2 - loc)
+          (:init (at l1))
+          (:goal (and (visited l2))))";
+        let dom = parse_pddl_domain(Input::from_string(dom)).unwrap();
+        let pb = parse_pddl_problem(Input::from_string(pb)).unwrap();
+        let pb = pddl_to_chronicles(&dom, &pb).unwrap();
+
+        // the duplicated effects are kept only once: (not (at ?a)), (at ?b) and (visited ?b)
+        let effects = &pb.templates[0].chronicle.effects;
+        assert_eq!(effects.len(), 3);
+    }
+}
@@ -173,12 +173,15 @@ pub struct Tpe {
 pub struct TypedSymbol {
     pub symbol: Sym,
     pub tpe: Option<Sym>,
+    /// Location of the declaration in its source file (the symbol and its type), if known.
+    pub source: Option<Loc>,
 }
 impl TypedSymbol {
     pub fn new(symbol: impl Into<Sym>, tpe: impl Into<Sym>) -> TypedSymbol {
         TypedSymbol {
             symbol: symbol.into(),
             tpe: Some(tpe.into()),
+            source: None,
         }
     }
 }
@@ -197,6 +200,7 @@ impl Display for TypedSymbol {
 pub struct Predicate {
     pub name: Sym,
     pub args: Vec<TypedSymbol>,
+    pub source: Option<Loc>,
 }
 impl Display for Predicate {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
@@ -206,11 +210,12 @@ impl Display for Predicate {
     }
 }
 
-/// /// A PDDL function, i.e., state function whose codomain is the set of reals.
+/// A PDDL function, i.e., state function whose codomain is the set of reals.
 #[derive(Debug, Clone)]
 pub struct Function {
     pub name: Sym,
     pub args: Vec<TypedSymbol>,
+    pub source: Option<Loc>,
 }
 impl Display for Function {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
@@ -224,7 +229,7 @@ impl Display for Function {
 pub struct TaskDef {
     pub name: Sym,
     pub args: Vec<TypedSymbol>,
-    source: Option<Loc>,
+    pub source: Option<Loc>,
 }
 
 impl Display for TaskDef {
@@ -245,7 +250,7 @@ pub struct Task {
     pub id: Option<TaskId>,
     pub name: Sym,
     pub arguments: Vec<Sym>,
-    source: Option<Loc>,
+    pub source: Option<Loc>,
 }
 impl std::fmt::Display for Task {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -262,7 +267,7 @@ pub struct Method {
     pub task: Task,
     pub precondition: Vec<SExpr>,
     pub subtask_network: TaskNetwork,
-    source: Option<Loc>,
+    pub source: Option<Loc>,
 }
 
 impl std::fmt::Display for Method {
@@ -276,6 +281,7 @@ pub struct TaskNetwork {
     pub ordered_tasks: Vec<Task>,
     pub unordered_tasks: Vec<Task>,
     pub orderings: Vec<Ordering>,
+    pub source: Option<Loc>,
 }
 
 /// Constraint specifying that the task identified by `first_task_id` should end
@@ -284,7 +290,7 @@ pub struct TaskNetwork {
 pub struct Ordering {
     pub first_task_id: TaskId,
     pub second_task_id: TaskId,
-    source: Option<Loc>,
+    pub source: Option<Loc>,
 }
 
 #[derive(Clone, Debug)]
@@ -293,6 +299,7 @@ pub struct Action {
     pub args: Vec<TypedSymbol>,
     pub pre: Vec<SExpr>,
     pub eff: Vec<SExpr>,
+    pub source: Option<Loc>,
 }
 
 impl Display for Action {
@@ -310,6 +317,7 @@ pub struct DurativeAction {
     pub duration: SExpr,
     pub conditions: Vec<SExpr>,
     pub effects: Vec<SExpr>,
+    pub source: Option<Loc>,
 }
 
 impl Display for DurativeAction {
@@ -331,9 +339,14 @@ fn consume_typed_symbols(input: &mut ListIter) -> std::result::Result<Vec<TypedS
         let next = input.pop_atom()?;
         if next.canonical_str() == "-" {
             let tpe = input.pop_atom()?;
+            let tpe_loc = tpe.loc();
             untyped
                 .drain(..)
-                .map(|name| TypedSymbol::new(name, tpe))
+                .map(|name| TypedSymbol {
+                    source: Some(name.loc().join(&tpe_loc)),
+                    symbol: name,
+                    tpe: Some(tpe.into()),
+                })
                 .for_each(|a| args.push(a));
         } else {
             untyped.push(next.into());
@@ -343,6 +356,7 @@ fn consume_typed_symbols(input: &mut ListIter) -> std::result::Result<Vec<TypedS
     untyped
         .drain(..)
         .map(|name| TypedSymbol {
+            source: Some(name.loc()),
             symbol: name,
             tpe: None,
         })
@@ -382,180 +396,213 @@ fn read_domain(dom: SExpr) -> std::result::Result<Domain, ErrLoc> {
         durative_actions: vec![],
     };
 
+    // errors are accumulated so that all invalid properties of the domain are reported at once
+    let mut errors = Vec::new();
     for current in dom {
-        // a property associates a key (e.g. `:predicates`) to a value or a sequence of values
-        let mut property = current
-            .as_list_iter()
-            .ok_or_else(|| current.invalid("expected a property list"))?;
-
-        match property.pop_atom()?.canonical_str() {
-            ":requirements" => {
-                for feature in property {
-                    let feature = feature
-                        .as_atom()
-                        .ok_or_else(|| feature.invalid("Expected feature name but got list"))?;
-                    let f = PddlFeature::from_str(feature.canonical_str()).map_err(|e| feature.invalid(e))?;
-
-                    res.features.push(f);
-                }
+        if let Err(e) = read_domain_property(current, &mut res) {
+            errors.push(e);
+        }
+    }
+    match ErrLoc::merge(errors) {
+        Some(errors) => Err(errors),
+        None => Ok(res),
+    }
+}
+
+/// Reads a property of the domain (e.g. `(:predicates ...)` or `(:action ...)`) and adds its content to the domain.
+fn read_domain_property(current: &SExpr, res: &mut Domain) -> R<()> {
+    // a property associates a key (e.g. `:predicates`) to a value or a sequence of values
+    let mut property = current
+        .as_list_iter()
+        .ok_or_else(|| current.invalid("expected a property list"))?;
+
+    match property.pop_atom()?.canonical_str() {
+        ":requirements" => {
+            for feature in property {
+                let feature = feature
+                    .as_atom()
+                    .ok_or_else(|| feature.invalid("Expected feature name but got list"))?;
+                let f = PddlFeature::from_str(feature.canonical_str()).map_err(|e| feature.invalid(e))?;
+
+                res.features.push(f);
             }
-            ":predicates" => {
-                for pred in property {
-                    let mut pred = pred.as_list_iter().ok_or_else(|| pred.invalid("Expected a list"))?;
-                    let name = pred.pop_atom()?.clone();
-                    let args = consume_typed_symbols(&mut pred)?;
-                    res.predicates.push(Predicate { name, args });
-                }
+        }
+        ":predicates" => {
+            for pred in property {
+                let pred_loc = pred.loc();
+                let mut pred = pred.as_list_iter().ok_or_else(|| pred.invalid("Expected a list"))?;
+                let name = pred.pop_atom()?.clone();
+                let args = consume_typed_symbols(&mut pred)?;
+                res.predicates.push(Predicate {
+                    name,
+                    args,
+                    source: Some(pred_loc),
+                });
             }
-            ":types" => {
-                if !res.types.is_empty() {
-                    return Err(current.invalid("More than one ':types' section definition"));
-                }
-                let types = consume_typed_symbols(&mut property)?;
-                res.types = types;
+        }
+        ":types" => {
+            if !res.types.is_empty() {
+                return Err(current.invalid("More than one ':types' section definition"));
             }
-            ":constants" => {
-                if !res.constants.is_empty() {
-                    return Err(current.invalid("More than one ':constants' section definition"));
-                }
-                let constants = consume_typed_symbols(&mut property)?;
-                res.constants = constants;
+            let types = consume_typed_symbols(&mut property)?;
+            res.types = types;
+        }
+        ":constants" => {
+            if !res.constants.is_empty() {
+                return Err(current.invalid("More than one ':constants' section definition"));
             }
-            ":functions" => {
-                for func in property {
-                    let mut func = func.as_list_iter().ok_or_else(|| func.invalid("Expected a list"))?;
-                    let name = func.pop_atom()?.clone();
-                    let args = consume_typed_symbols(&mut func)?;
-                    res.functions.push(Function { name, args });
-                }
+            let constants = consume_typed_symbols(&mut property)?;
+            res.constants = constants;
+        }
+        ":functions" => {
+            for func in property {
+                let func_loc = func.loc();
+                let mut func = func.as_list_iter().ok_or_else(|| func.invalid("Expected a list"))?;
+                let name = func.pop_atom()?.clone();
+                let args = consume_typed_symbols(&mut func)?;
+                res.functions.push(Function {
+                    name,
+                    args,
+                    source: Some(func_loc),
+                });
             }
-            ":action" => {
-                let name = property.pop_atom()?.clone();
-                let mut args = Vec::new();
-                let mut pre = Vec::new();
-                let mut eff = Vec::new();
-                while !property.is_empty() {
-                    let key_expr = property.pop_atom()?;
-                    let key_loc = key_expr.loc();
-                    let key = key_expr.to_string();
-                    let value = property.pop().ctx(format!("No value associated to arg: {key}"))?;
-                    match key.as_str() {
-                        ":parameters" => {
-                            if !args.is_empty() {
-                                return Err(key_loc.invalid("Duplicated ':parameters' tag is not allowed"));
-                            }
-                            let mut value = value
-                                .as_list_iter()
-                                .ok_or_else(|| value.invalid("Expected a parameter list"))?;
-                            for a in consume_typed_symbols(&mut value)? {
-                                args.push(a);
-                            }
-                        }
-                        ":precondition" => {
-                            pre.push(value.clone());
+        }
+        ":action" => {
+            let name = property.pop_atom()?.clone();
+            let mut args = Vec::new();
+            let mut pre = Vec::new();
+            let mut eff = Vec::new();
+            while !property.is_empty() {
+                let key_expr = property.pop_atom()?;
+                let key_loc = key_expr.loc();
+                let key = key_expr.to_string();
+                let value = property.pop().ctx(format!("No value associated to arg: {key}"))?;
+                match key.as_str() {
+                    ":parameters" => {
+                        if !args.is_empty() {
+                            return Err(key_loc.invalid("Duplicated ':parameters' tag is not allowed"));
                         }
-                        ":effect" => {
-                            eff.push(value.clone());
+                        let mut value = value
+                            .as_list_iter()
+                            .ok_or_else(|| value.invalid("Expected a parameter list"))?;
+                        for a in consume_typed_symbols(&mut value)? {
+                            args.push(a);
                         }
-                        _ => return Err(key_loc.invalid(format!("unsupported key in action: {key}"))),
                     }
+                    ":precondition" => {
+                        pre.push(value.clone());
+                    }
+                    ":effect" => {
+                        eff.push(value.clone());
+                    }
+                    _ => return Err(key_loc.invalid(format!("unsupported key in action: {key}"))),
                 }
-                res.actions.push(Action { name, args, pre, eff })
             }
-            ":durative-action" => {
-                let name = property.pop_atom()?.clone();
-                let mut args = Vec::new();
-                let mut duration = None;
-                let mut conditions = Vec::new();
-                let mut effects = Vec::new();
-
-                while let Ok(key_expr) = property.pop_atom() {
-                    let key_loc = key_expr.loc();
-                    let key = key_expr.to_string();
-                    let value = property.pop().ctx(format!("No value associated to arg: {key}"))?;
-                    match key.as_str() {
-                        ":parameters" => {
-                            if !args.is_empty() {
-                                return Err(key_loc.invalid("Duplicated ':parameters' tag is not allowed"));
-                            }
-                            let mut value = value
-                                .as_list_iter()
-                                .ok_or_else(|| value.invalid("Expected a parameter list"))?;
-                            for a in consume_typed_symbols(&mut value)? {
-                                args.push(a);
-                            }
-                        }
-                        ":duration" => {
-                            if duration.is_some() {
-                                return Err(key_loc.invalid("Duration was previously set."));
-                            }
-                            duration = Some(value.clone());
+            res.actions.push(Action {
+                name,
+                args,
+                pre,
+                eff,
+                source: Some(current.loc()),
+            })
+        }
+        ":durative-action" => {
+            let name = property.pop_atom()?.clone();
+            let mut args = Vec::new();
+            let mut duration = None;
+            let mut conditions = Vec::new();
+            let mut effects = Vec::new();
+
+            while let Ok(key_expr) = property.pop_atom() {
+                let key_loc = key_expr.loc();
+                let key = key_expr.to_string();
+                let value = property.pop().ctx(format!("No value associated to arg: {key}"))?;
+                match key.as_str() {
+                    ":parameters" => {
+                        if !args.is_empty() {
+                            return Err(key_loc.invalid("Duplicated ':parameters' tag is not allowed"));
                         }
-                        ":condition" => {
-                            conditions.push(value.clone());
+                        let mut value = value
+                            .as_list_iter()
+                            .ok_or_else(|| value.invalid("Expected a parameter list"))?;
+                        for a in consume_typed_symbols(&mut value)? {
+                            args.push(a);
                         }
-                        ":effect" => {
-                            effects.push(value.clone());
+                    }
+                    ":duration" => {
+                        if duration.is_some() {
+                            return Err(key_loc.invalid("Duration was previously set."));
                         }
-                        _ => return Err(key_loc.invalid(format!("unsupported key in action: {key}"))),
+                        duration = Some(value.clone());
+                    }
+                    ":condition" => {
+                        conditions.push(value.clone());
+                    }
+                    ":effect" => {
+                        effects.push(value.clone());
                     }
+                    _ => return Err(key_loc.invalid(format!("unsupported key in action: {key}"))),
                 }
-                let duration = duration.ok_or_else(|| current.invalid("Action has no duration field"))?;
-                let durative_action = DurativeAction {
-                    name,
-                    args,
-                    duration,
-                    conditions,
-                    effects,
-                };
-                res.durative_actions.push(durative_action)
-            }
-            ":task" => {
-                check_feature_presence(PddlFeature::Hierarchy, &res, current)?;
-                let name = property.pop_atom().ctx("Missing task name")?.clone();
-                property.pop_known_atom(":parameters")?;
-                let params = property.pop_list().ctx("Expected a parameter list")?;
-                let params = consume_typed_symbols(&mut params.iter())?;
-                let task = TaskDef {
-                    name,
-                    args: params,
-                    source: Some(current.loc().clone()),
-                };
-                res.tasks.push(task);
             }
-            ":method" => {
-                check_feature_presence(PddlFeature::Hierarchy, &res, current)?;
-                let name = property.pop_atom().ctx("Missing task name")?.clone();
-                property.pop_known_atom(":parameters")?;
-                let params = property.pop_list().ctx("Expected a parameter list")?;
-                let parameters = consume_typed_symbols(&mut params.iter())?;
-                property.pop_known_atom(":task")?;
-                let task = parse_task(property.pop()?, false)?;
-                let precondition = if property.peek().map_or(false, |e| e.is_atom(":precondition")) {
-                    property.pop_known_atom(":precondition").unwrap();
-                    vec![property.pop()?.clone()]
-                } else {
-                    Vec::new()
-                };
-                let method = Method {
-                    name,
-                    parameters,
-                    task,
-                    precondition,
-                    subtask_network: parse_task_network(property)?,
-                    source: Some(current.loc()),
-                };
-                res.methods.push(method);
-            }
-
-            _ => return Err(current.invalid("unsupported block")),
+            let duration = duration.ok_or_else(|| current.invalid("Action has no duration field"))?;
+            let durative_action = DurativeAction {
+                name,
+                args,
+                duration,
+                conditions,
+                effects,
+                source: Some(current.loc()),
+            };
+            res.durative_actions.push(durative_action)
         }
+        ":task" => {
+            check_feature_presence(PddlFeature::Hierarchy, res, current)?;
+            let name = property.pop_atom().ctx("Missing task name")?.clone();
+            property.pop_known_atom(":parameters")?;
+            let params = property.pop_list().ctx("Expected a parameter list")?;
+            let params = consume_typed_symbols(&mut params.iter())?;
+            let task = TaskDef {
+                name,
+                args: params,
+                source: Some(current.loc().clone()),
+            };
+            res.tasks.push(task);
+        }
+        ":method" => {
+            check_feature_presence(PddlFeature::Hierarchy, res, current)?;
+            let name = property.pop_atom().ctx("Missing task name")?.clone();
+            property.pop_known_atom(":parameters")?;
+            let params = property.pop_list().ctx("Expected a parameter list")?;
+            let parameters = consume_typed_symbols(&mut params.iter())?;
+            property.pop_known_atom(":task")?;
+            let task = parse_task(property.pop()?, false)?;
+            let precondition = if matches!(property.peek(), Some(e) if e.is_atom(":precondition")) {
+                property.pop_known_atom(":precondition").unwrap();
+                vec![property.pop()?.clone()]
+            } else {
+                Vec::new()
+            };
+            let method = Method {
+                name,
+                parameters,
+                task,
+                precondition,
+                subtask_network: parse_task_network(property)?,
+                source: Some(current.loc()),
+            };
+            res.methods.push(method);
+        }
+
+        _ => return Err(current.invalid("unsupported block")),
     }
-    Ok(res)
+    Ok(())
 }
 
 fn parse_task_network(mut key_values: ListIter) -> R<TaskNetwork> {
-    let mut tn = TaskNetwork::default();
+    let mut tn = TaskNetwork {
+        source: Some(key_values.loc()),
+        ..Default::default()
+    };
     while !key_values.is_empty() {
         let key = key_values.pop_atom()?;
         let key_loc = key.loc();
@@ -683,6 +730,9 @@ pub struct Problem {
     pub problem_name: Sym,
     pub domain_name: Sym,
     pub objects: Vec<TypedSymbol>,
+    /// Objects that do not exist initially but that the plan may create, declared with the
+    /// `(:existential-objects ...)` extension. They are declared as typed symbols, as regular objects.
+    pub existential_objects: Vec<TypedSymbol>,
     pub init: Vec<SExpr>,
     pub task_network: Option<TaskNetwork>,
     pub goal: Vec<SExpr>,
@@ -693,6 +743,10 @@ impl Display for Problem {
         write!(f, "# Problem {} (domain: {})", &self.problem_name, &self.domain_name)?;
         write!(f, "\n# Objects \n  ")?;
         disp_iter(f, self.objects.as_slice(), "\n  ")?;
+        if !self.existential_objects.is_empty() {
+            write!(f, "\n# Existential objects \n  ")?;
+            disp_iter(f, self.existential_objects.as_slice(), "\n  ")?;
+        }
         write!(f, "\n# Init \n  ")?;
         disp_iter(f, self.init.as_slice(), "\n  ")?;
         write!(f, "\n# Goal \n  ")?;
@@ -728,48 +782,65 @@ fn read_problem(problem: SExpr) -> std::result::Result<Problem, ErrLoc> {
         problem_name,
         domain_name,
         objects: vec![],
+        existential_objects: vec![],
         init: vec![],
         task_network: None,
         goal: vec![],
     };
 
+    // errors are accumulated so that all invalid properties of the problem are reported at once
+    let mut errors = Vec::new();
     for current in problem {
-        // a property associates a key (e.g. `:objects`) to a value or a sequence of values
-        let mut property = current
-            .as_list_iter()
-            .ok_or_else(|| current.invalid("Expected a list"))?;
-        match property.pop_atom()?.canonical_str() {
-            ":objects" => {
-                let objects = consume_typed_symbols(&mut property)?;
-                for o in objects {
-                    res.objects.push(o);
-                }
-            }
-            ":init" => {
-                for fact in property {
-                    res.init.push(fact.clone());
-                }
+        if let Err(e) = read_problem_property(current, &mut res) {
+            errors.push(e);
+        }
+    }
+    match ErrLoc::merge(errors) {
+        Some(errors) => Err(errors),
+        None => Ok(res),
+    }
+}
+
+/// Reads a property of the problem (e.g. `(:objects ...)` or `(:init ...)`) and adds its content to the problem.
+fn read_problem_property(current: &SExpr, res: &mut Problem) -> R<()> {
+    // a property associates a key (e.g. `:objects`) to a value or a sequence of values
+    let mut property = current
+        .as_list_iter()
+        .ok_or_else(|| current.invalid("Expected a list"))?;
+    match property.pop_atom()?.canonical_str() {
+        ":objects" => {
+            let objects = consume_typed_symbols(&mut property)?;
+            for o in objects {
+                res.objects.push(o);
             }
-            ":goal" => {
-                for goal in property {
-                    res.goal.push(goal.clone());
-                }
+        }
+        ":existential-objects" => {
+            let objects = consume_typed_symbols(&mut property)?;
+            res.existential_objects.extend(objects);
+        }
+        ":init" => {
+            for fact in property {
+                res.init.push(fact.clone());
             }
-            ":htn" => {
-                if res.task_network.is_some() {
-                    return Err(current.invalid("More than one task network specified"));
-                }
-                res.task_network = Some(parse_task_network(property)?);
+        }
+        ":goal" => {
+            for goal in property {
+                res.goal.push(goal.clone());
             }
-            ":metric" => {
-                // TODO: Complete support of metrics
-                println!("WARNING: ':metrics' is not supported. Skipping for now.");
+        }
+        ":htn" => {
+            if res.task_network.is_some() {
+                return Err(current.invalid("More than one task network specified"));
             }
-            _ => return Err(current.invalid("unsupported block")),
+            res.task_network = Some(parse_task_network(property)?);
+        }
+        ":metric" => {
+            // TODO: Complete support of metrics
+            println!("WARNING: ':metrics' is not supported. Skipping for now.");
         }
+        _ => return Err(current.invalid("unsupported block")),
     }
-
-    Ok(res)
+    Ok(())
 }
 
 #[cfg(test)]
@@ -813,6 +884,77 @@ mod tests {
 
         Result::Ok(())
     }
+    #[test]
+    fn source_locations() {
+        let domain = "(define (domain test)
+  (:predicates (at ?x))
+  (:functions (cost ?x))
+  (:action move :parameters (?x) :precondition (at ?x))
+  (:types loc - object))";
+        let dom = read_domain(parse(domain).unwrap()).unwrap();
+        let line = |loc: &Option<Loc>| loc.clone().unwrap().span().start.line;
+        assert_eq!(line(&dom.predicates[0].source), 1);
+        assert_eq!(line(&dom.functions[0].source), 2);
+        assert_eq!(line(&dom.actions[0].source), 3);
+        assert_eq!(dom.actions[0].source.as_ref().unwrap().position(), "4:3");
+        // the span of a typed symbol covers both the symbol and its type
+        let tpe = dom.types[0].source.clone().unwrap();
+        assert_eq!(tpe.position(), "5:11");
+        assert_eq!(format!("{tpe:?}"), "loc - object");
+
+        let problem = "(define (problem p) (:domain test)
+  (:objects a b - loc c)
+  (:goal (at a)))";
+        let pb = read_problem(parse(problem).unwrap()).unwrap();
+        let positions: Vec<_> = pb
+            .objects
+            .iter()
+            .map(|o| o.source.as_ref().unwrap().position())
+            .collect();
+        assert_eq!(positions, ["2:13", "2:15", "2:23"]);
+        assert_eq!(pb.goal[0].loc().position(), "3:10");
+    }
+
+    #[test]
+    fn existential_objects() {
+        let problem = "(define (problem p) (:domain test)
+  (:objects a - block)
+  (:existential-objects b c - block)
+  (:init (clear a))
+  (:goal (clear b)))";
+        let pb = read_problem(parse(problem).unwrap()).unwrap();
+        assert_eq!(pb.objects.len(), 1);
+        let created: Vec<_> = pb.existential_objects.iter().map(|o| o.symbol.to_string()).collect();
+        assert_eq!(created, ["b", "c"]);
+        assert_eq!(pb.existential_objects[0].tpe.as_ref().unwrap().to_string(), "block");
+    }
+
+    #[test]
+    fn multiple_errors() {
+        let domain = "(define (domain test)
+  (:requirements :strips :unknown-feature)
+  (:predicates (at ?x))
+  (:action move :parameters (?x) :unknown-key ())
+  (:action stay :parameters (?x) :precondition (at ?x))
+  (:unknown-block))";
+        let err = read_domain(parse(domain).unwrap()).unwrap_err();
+        // all three invalid properties are reported, in order
+        assert_eq!(err.num_errors(), 3);
+        assert_eq!(err.loc().unwrap().clone().span().start.line, 1);
+        let message = err.to_string();
+        assert!(message.contains("Unknown feature `:unknown-feature`"));
+        assert!(message.contains("unsupported key in action: :unknown-key"));
+        assert!(message.contains("unsupported block"));
+
+        let problem = "(define (problem p) (:domain test)
+  (:objects a b)
+  (:unknown-block)
+  (:init (at a))
+  (:htn)
+  (:htn))";
+        let err = read_problem(parse(problem).unwrap()).unwrap_err();
+        assert_eq!(err.num_errors(), 2);
+    }
     //
     // #[test]
     // fn parsing_pddl_domain() -> Result<(), String> {
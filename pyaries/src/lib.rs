@@ -0,0 +1,472 @@
+//! Python bindings for the aries solver, exposed as the `pyaries` module.
+//!
+//! The module gives access to the construction of models (integer and boolean variables, constraints),
+//! to their resolution (with an optional callback invoked on each new solution of an optimization problem)
+//! and to the incremental Simple Temporal Network.
+//!
+//! ```python
+//! import pyaries
+//! m = pyaries.Model()
+//! x = m.new_int_var(0, 10, "x")
+//! y = m.new_int_var(0, 10, "y")
+//! m.enforce(m.leq(x + 3, y))
+//! s = pyaries.Solver(m)
+//! sol = s.minimize(y, lambda cost, sol: print("new solution with cost", cost))
+//! assert sol.value(y) == 3
+//! ```
+
+// false positive on the code generated by the pyo3 macros
+#![allow(clippy::useless_conversion)]
+
+use aries::core::{IntCst, Lit as CoreLit};
+use aries::model::extensions::{AssignmentExt, SavedAssignment};
+use aries::model::lang::expr::{and, eq, leq, lt, neq, or};
+use aries::model::lang::linear::{LinearSum, LinearTerm};
+use aries::model::lang::IAtom;
+use aries::reasoners::stn::theory::Timepoint as CoreTimepoint;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+type Model = aries::model::Model<String>;
+type Solver = aries::solver::Solver<String>;
+
+/// A boolean literal, that can be negated with the `~` operator.
+#[pyclass(frozen)]
+#[derive(Copy, Clone)]
+struct Lit(CoreLit);
+
+#[pymethods]
+impl Lit {
+    fn __invert__(&self) -> Lit {
+        Lit(!self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// An integer expression of the form `var + cst`, that can be shifted by adding or subtracting constants.
+#[pyclass(frozen)]
+#[derive(Copy, Clone)]
+struct Int(IAtom);
+
+#[pymethods]
+impl Int {
+    fn __add__(&self, cst: IntCst) -> Int {
+        Int(self.0 + cst)
+    }
+
+    fn __sub__(&self, cst: IntCst) -> Int {
+        Int(self.0 - cst)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// Either an integer expression or an integer constant.
+#[derive(FromPyObject)]
+enum IntArg {
+    Expr(Int),
+    Cst(IntCst),
+}
+
+impl From<IntArg> for IAtom {
+    fn from(arg: IntArg) -> Self {
+        match arg {
+            IntArg::Expr(Int(atom)) => atom,
+            IntArg::Cst(cst) => cst.into(),
+        }
+    }
+}
+
+fn lits(lits: Vec<Lit>) -> Vec<CoreLit> {
+    lits.into_iter().map(|l| l.0).collect()
+}
+
+/// A model to which variables and constraints can be added before handing it to a `Solver`.
+///
+/// Constraints are built in two steps: the methods building a constraint (e.g. `leq`) return a literal
+/// that is true iff the constraint holds, that can then be required to hold with `enforce`.
+#[pyclass(name = "Model")]
+#[derive(Clone)]
+struct PyModel(Model);
+
+#[pymethods]
+impl PyModel {
+    #[new]
+    fn new() -> Self {
+        PyModel(Model::new())
+    }
+
+    #[pyo3(signature = (lb, ub, label = String::new()))]
+    fn new_int_var(&mut self, lb: IntCst, ub: IntCst, label: String) -> PyResult<Int> {
+        if lb > ub {
+            return Err(PyValueError::new_err(format!("Empty domain [{lb}, {ub}]")));
+        }
+        Ok(Int(self.0.new_ivar(lb, ub, label).into()))
+    }
+
+    #[pyo3(signature = (label = String::new()))]
+    fn new_bool_var(&mut self, label: String) -> Lit {
+        Lit(self.0.new_bvar(label).true_lit())
+    }
+
+    fn leq(&mut self, lhs: IntArg, rhs: IntArg) -> Lit {
+        Lit(self.0.reify(leq(IAtom::from(lhs), IAtom::from(rhs))))
+    }
+
+    fn lt(&mut self, lhs: IntArg, rhs: IntArg) -> Lit {
+        Lit(self.0.reify(lt(IAtom::from(lhs), IAtom::from(rhs))))
+    }
+
+    fn eq(&mut self, lhs: IntArg, rhs: IntArg) -> Lit {
+        Lit(self.0.reify(eq(IAtom::from(lhs), IAtom::from(rhs))))
+    }
+
+    fn neq(&mut self, lhs: IntArg, rhs: IntArg) -> Lit {
+        Lit(self.0.reify(neq(IAtom::from(lhs), IAtom::from(rhs))))
+    }
+
+    fn or_(&mut self, disjuncts: Vec<Lit>) -> Lit {
+        Lit(self.0.reify(or(lits(disjuncts))))
+    }
+
+    fn and_(&mut self, conjuncts: Vec<Lit>) -> Lit {
+        Lit(self.0.reify(and(lits(conjuncts))))
+    }
+
+    /// Requires the literal to be true.
+    fn enforce(&mut self, lit: Lit) {
+        self.0.enforce(lit.0, []);
+    }
+
+    /// Requires at least one of the literals to be true.
+    fn enforce_or(&mut self, disjuncts: Vec<Lit>) {
+        self.0.enforce(or(lits(disjuncts)), []);
+    }
+
+    /// Requires the linear sum of the `(factor, variable)` terms to be lower than or equal to `ub`.
+    fn enforce_linear_leq(&mut self, terms: Vec<(IntCst, Int)>, ub: IntCst) -> PyResult<()> {
+        let mut sum = LinearSum::zero();
+        for (factor, Int(atom)) in terms {
+            sum += LinearTerm::new(factor, atom.var, false);
+            sum = sum + factor * atom.shift;
+        }
+        self.0.enforce(sum.leq(ub), []);
+        Ok(())
+    }
+}
+
+/// Final assignment of the variables in a solution.
+#[pyclass(frozen)]
+struct Solution(Arc<SavedAssignment>);
+
+#[pymethods]
+impl Solution {
+    /// Value of an integer expression in the solution.
+    fn value(&self, expr: IntArg) -> IntCst {
+        let (lb, _) = self.0.domain_of(IAtom::from(expr));
+        lb
+    }
+
+    /// Value of a literal in the solution.
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        self.0.value_of_literal(lit.0)
+    }
+}
+
+/// Solver for a `Model`, the model is copied so that it can be further extended for other resolutions.
+#[pyclass(name = "Solver", unsendable)]
+struct PySolver(Solver);
+
+#[pymethods]
+impl PySolver {
+    #[new]
+    fn new(model: &PyModel) -> Self {
+        PySolver(Solver::new(model.0.clone()))
+    }
+
+    /// Returns a solution to the problem, or None if it has none.
+    fn solve(&mut self) -> PyResult<Option<Solution>> {
+        match self.0.solve() {
+            Ok(sol) => Ok(sol.map(Solution)),
+            Err(e) => Err(PyRuntimeError::new_err(e.to_string())),
+        }
+    }
+
+    /// Returns an optimal solution minimizing the objective, or None if the problem has no solution.
+    /// If provided, the callback is invoked with the cost and the solution for each improving solution.
+    #[pyo3(signature = (objective, on_solution = None))]
+    fn minimize(&mut self, py: Python, objective: IntArg, on_solution: Option<PyObject>) -> PyResult<Option<Solution>> {
+        self.optimize(py, objective.into(), true, on_solution)
+    }
+
+    /// Returns an optimal solution maximizing the objective, or None if the problem has no solution.
+    /// If provided, the callback is invoked with the cost and the solution for each improving solution.
+    #[pyo3(signature = (objective, on_solution = None))]
+    fn maximize(&mut self, py: Python, objective: IntArg, on_solution: Option<PyObject>) -> PyResult<Option<Solution>> {
+        self.optimize(py, objective.into(), false, on_solution)
+    }
+
+    fn print_stats(&self) {
+        self.0.print_stats()
+    }
+}
+
+impl PySolver {
+    fn optimize(
+        &mut self,
+        py: Python,
+        objective: IAtom,
+        minimize: bool,
+        on_solution: Option<PyObject>,
+    ) -> PyResult<Option<Solution>> {
+        // an error raised by the callback cannot interrupt the search, it is raised once the search completes
+        let mut callback_error = None;
+        let on_new_solution = |cost: IntCst, ass: &SavedAssignment| {
+            if let Some(callback) = &on_solution {
+                if callback_error.is_none() {
+                    let sol = Solution(Arc::new(ass.clone()));
+                    if let Err(e) = callback.call1(py, (cost, sol)) {
+                        callback_error = Some(e);
+                    }
+                }
+            }
+        };
+        let result = if minimize {
+            self.0.minimize_with(objective, on_new_solution)
+        } else {
+            self.0.maximize_with(objective, on_new_solution)
+        };
+        if let Some(e) = callback_error {
+            return Err(e);
+        }
+        match result {
+            Ok(sol) => Ok(sol.map(|(_, ass)| Solution(ass))),
+            Err(e) => Err(PyRuntimeError::new_err(e.to_string())),
+        }
+    }
+}
+
+/// A timepoint of a `Stn`.
+#[pyclass(frozen)]
+#[derive(Copy, Clone)]
+struct Timepoint(CoreTimepoint);
+
+/// An incremental Simple Temporal Network.
+///
+/// Edges `source -- weight --> target` encode the constraint `target - source <= weight`.
+/// Edges can be added as inactive and later activated, and the network supports backtracking.
+#[pyclass(name = "Stn")]
+#[derive(Clone)]
+struct PyStn(aries::reasoners::stn::Stn);
+
+#[pymethods]
+impl PyStn {
+    #[new]
+    fn new() -> Self {
+        PyStn(aries::reasoners::stn::Stn::new())
+    }
+
+    fn add_timepoint(&mut self, lb: IntCst, ub: IntCst) -> Timepoint {
+        Timepoint(self.0.add_timepoint(lb, ub))
+    }
+
+    /// Raises a `ValueError` if the domain of the timepoint would become empty.
+    fn set_lb(&mut self, timepoint: Timepoint, lb: IntCst) -> PyResult<()> {
+        if lb > self.ub(timepoint) {
+            return Err(PyValueError::new_err("The domain of the timepoint would become empty."));
+        }
+        self.0.set_lb(timepoint.0, lb);
+        Ok(())
+    }
+
+    /// Raises a `ValueError` if the domain of the timepoint would become empty.
+    fn set_ub(&mut self, timepoint: Timepoint, ub: IntCst) -> PyResult<()> {
+        if ub < self.lb(timepoint) {
+            return Err(PyValueError::new_err("The domain of the timepoint would become empty."));
+        }
+        self.0.set_ub(timepoint.0, ub);
+        Ok(())
+    }
+
+    fn lb(&self, timepoint: Timepoint) -> IntCst {
+        self.0.model.state.lb(timepoint.0)
+    }
+
+    fn ub(&self, timepoint: Timepoint) -> IntCst {
+        self.0.model.state.ub(timepoint.0)
+    }
+
+    fn add_edge(&mut self, source: Timepoint, target: Timepoint, weight: IntCst) {
+        self.0.add_edge(source.0, target.0, weight)
+    }
+
+    /// Adds an edge that will only be taken into account once its literal is activated with `mark_active`.
+    fn add_inactive_edge(&mut self, source: Timepoint, target: Timepoint, weight: IntCst) -> Lit {
+        Lit(self.0.add_inactive_edge(source.0, target.0, weight))
+    }
+
+    fn mark_active(&mut self, edge: Lit) -> PyResult<()> {
+        if self.0.model.state.entails(!edge.0) {
+            return Err(PyValueError::new_err("The edge cannot be activated."));
+        }
+        self.0.mark_active(edge.0);
+        Ok(())
+    }
+
+    /// Propagates all constraints, returning false if the network is inconsistent.
+    fn propagate(&mut self) -> bool {
+        self.0.propagate_all().is_ok()
+    }
+
+    /// Sum of the widths `ub - lb` of the domains of all timepoints.
+    fn naive_flexibility(&self) -> i64 {
+        self.0.naive_flexibility()
+    }
+
+    /// Root mean square of the widths `ub - lb` of the domains of all timepoints.
+    fn rms_flexibility(&self) -> f64 {
+        self.0.rms_flexibility()
+    }
+
+    /// Sum of the slacks of all active edges when each timepoint takes its lower bound.
+    fn sum_of_slacks(&self) -> i64 {
+        self.0.sum_of_slacks()
+    }
+
+    fn set_backtrack_point(&mut self) {
+        self.0.set_backtrack_point()
+    }
+
+    fn undo_to_last_backtrack_point(&mut self) {
+        self.0.undo_to_last_backtrack_point()
+    }
+
+    fn reset_to_root(&mut self) {
+        self.0.reset_to_root()
+    }
+}
+
+#[pymodule]
+fn pyaries(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Lit>()?;
+    m.add_class::<Int>()?;
+    m.add_class::<PyModel>()?;
+    m.add_class::<Solution>()?;
+    m.add_class::<PySolver>()?;
+    m.add_class::<Timepoint>()?;
+    m.add_class::<PyStn>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+
+    /// Runs the python code with the `pyaries` module in scope.
+    fn run(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "pyaries").unwrap();
+            pyaries(&module).unwrap();
+            let globals = [("pyaries", module)].into_py_dict_bound(py);
+            if let Err(e) = py.run_bound(code, Some(&globals), None) {
+                let traceback = e.traceback_bound(py).and_then(|tb| tb.format().ok());
+                panic!("{}{e}", traceback.unwrap_or_default());
+            }
+        })
+    }
+
+    #[test]
+    fn test_model() {
+        run(r#"
+m = pyaries.Model()
+x = m.new_int_var(0, 10, "x")
+y = m.new_int_var(0, 10, "y")
+b = m.new_bool_var("b")
+m.enforce(m.leq(x + 3, y))
+m.enforce_linear_leq([(1, x), (2, y)], 25)
+m.enforce_or([b, m.eq(x, 5)])
+m.enforce(~b)
+
+sol = pyaries.Solver(m).solve()
+assert sol.value(x) == 5
+assert sol.value(y) >= 8
+assert sol.lit_value(b) is False
+
+try:
+    m.new_int_var(3, 2)
+    assert False, "empty domain accepted"
+except ValueError:
+    pass
+
+m.enforce(m.lt(y, x))
+assert pyaries.Solver(m).solve() is None
+"#);
+    }
+
+    #[test]
+    fn test_optimization() {
+        run(r#"
+m = pyaries.Model()
+x = m.new_int_var(0, 10, "x")
+y = m.new_int_var(0, 10, "y")
+m.enforce(m.leq(x + 3, y))
+m.enforce(m.neq(y, 3))
+
+costs = []
+sol = pyaries.Solver(m).minimize(y, lambda cost, sol: costs.append((cost, sol.value(y))))
+assert sol.value(y) == 4
+assert costs and costs[-1] == (4, 4)
+assert all(a[0] > b[0] for a, b in zip(costs, costs[1:]))
+
+sol = pyaries.Solver(m).maximize(x)
+assert sol.value(x) == 7
+
+def fail(cost, sol):
+    raise KeyError("callback")
+try:
+    pyaries.Solver(m).minimize(y, fail)
+    assert False, "callback error swallowed"
+except KeyError:
+    pass
+"#);
+    }
+
+    #[test]
+    fn test_stn() {
+        run(r#"
+stn = pyaries.Stn()
+a = stn.add_timepoint(0, 10)
+b = stn.add_timepoint(0, 10)
+stn.add_edge(b, a, -3)  # a - b <= -3
+assert stn.propagate()
+assert (stn.lb(b), stn.ub(a)) == (3, 7)
+
+e = stn.add_inactive_edge(a, b, 4)  # b - a <= 4
+stn.set_backtrack_point()
+stn.mark_active(e)
+stn.set_lb(a, 5)
+stn.set_ub(a, 5)
+assert stn.propagate()
+assert (stn.lb(b), stn.ub(b)) == (8, 9)
+try:
+    stn.set_ub(b, 7)
+    assert False, "empty domain accepted"
+except ValueError:
+    pass
+stn.add_edge(a, b, 2)  # b - a <= 2
+assert not stn.propagate()
+
+stn.undo_to_last_backtrack_point()
+assert stn.propagate()
+assert (stn.lb(a), stn.ub(b)) == (0, 10)
+"#);
+    }
+}
@@ -0,0 +1,52 @@
+//! Micro-benchmarks of the core operations of the solver, meant to detect slowdowns of the propagation.
+//!
+//! Run with `cargo bench -p aries --features bench`, both before and after a change: criterion reports the
+//! change in performance with respect to the previous run.
+use aries::microbench::{DomainsWorkload, StnWorkload};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+/// Propagation of the STN, measured per activated edge.
+fn stn_propagation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stn-propagation");
+    for n in [10, 100, 1000] {
+        let workloads = [
+            ("chain", StnWorkload::chain(n)),
+            ("random", StnWorkload::random(n, 4 * n, 79837224973)),
+        ];
+        for (name, mut workload) in workloads {
+            group.throughput(Throughput::Elements(workload.num_edges() as u64));
+            group.bench_function(BenchmarkId::new(name, n), |b| b.iter(|| workload.activate_all()));
+        }
+    }
+    group.finish();
+}
+
+/// Explanation of an inference of the STN, by resolution down to a clause on the activated edges.
+fn stn_explanation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stn-explanation");
+    for n in [10, 100, 1000] {
+        let mut workload = StnWorkload::chain(n);
+        workload.activate_all();
+        group.bench_function(BenchmarkId::new("chain", n), |b| {
+            b.iter(|| black_box(workload.explain_latest()))
+        });
+    }
+    group.finish();
+}
+
+/// Throughput of the updates of the domains, including the restoration of the initial domains.
+fn domain_updates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("domain-updates");
+    for num_vars in [10, 1000] {
+        let mut workload = DomainsWorkload::new(num_vars);
+        let rounds = 100;
+        group.throughput(Throughput::Elements(workload.num_updates(rounds) as u64));
+        group.bench_function(BenchmarkId::from_parameter(num_vars), |b| {
+            b.iter(|| workload.update_all(black_box(rounds)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, stn_propagation, stn_explanation, domain_updates);
+criterion_main!(benches);
@@ -0,0 +1,231 @@
+use crate::backtrack::{Backtrack, DecLvl};
+use std::any::Any;
+use std::marker::PhantomData;
+
+/// Handle on a trail registered in a [CompositeTrail], whose events are of type `Event`.
+pub struct TrailId<Event> {
+    index: usize,
+    _phantom: PhantomData<fn(Event)>,
+}
+
+impl<Event> Clone for TrailId<Event> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Event> Copy for TrailId<Event> {}
+
+/// Type-erased sequence of the events of a registered trail.
+trait Events: Send {
+    fn discard_last(&mut self);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn as_any(&self) -> &dyn Any;
+    fn clone_box(&self) -> Box<dyn Events>;
+}
+
+impl<Event: Clone + Send + 'static> Events for Vec<Event> {
+    fn discard_last(&mut self) {
+        self.pop().expect("No event left");
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_box(&self) -> Box<dyn Events> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Events> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// A set of trails with heterogeneous event types, that share a single sequence of save points.
+///
+/// Components register their own typed trail and push their events through the returned [TrailId].
+/// Saving and restoring is done once for all trails: events are undone in the reverse order in which they
+/// were pushed, regardless of the trail they belong to, which guarantees that components sharing the composite trail
+/// never get out of sync (e.g. one being backtracked while the other one is not).
+#[derive(Clone, Default)]
+pub struct CompositeTrail {
+    /// Events of each registered trail.
+    trails: Vec<Box<dyn Events>>,
+    /// Index of the trail of each event, in the order in which they were pushed.
+    order: Vec<u32>,
+    /// Length of `order` at each save point.
+    saved_states: Vec<usize>,
+}
+
+/// An event being undone by [CompositeTrail::restore_last_with].
+/// It can be extracted with the handle of the trail it belongs to.
+pub struct UndoneEvent<'a> {
+    trail: usize,
+    events: &'a mut dyn Events,
+    taken: bool,
+}
+
+impl UndoneEvent<'_> {
+    /// Returns the event if it belongs to the given trail, and `None` otherwise.
+    pub fn take<Event: 'static>(&mut self, id: TrailId<Event>) -> Option<Event> {
+        if self.trail != id.index || self.taken {
+            return None;
+        }
+        let events = self.events.as_any_mut().downcast_mut::<Vec<Event>>().unwrap();
+        self.taken = true;
+        events.pop()
+    }
+}
+
+impl CompositeTrail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new trail, whose events are of type `Event`.
+    /// Since the trail starts empty, it can be registered at any decision level.
+    pub fn register<Event: Clone + Send + 'static>(&mut self) -> TrailId<Event> {
+        self.trails.push(Box::new(Vec::<Event>::new()));
+        TrailId {
+            index: self.trails.len() - 1,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Records an event on the given trail, in the current decision level.
+    pub fn push<Event: 'static>(&mut self, id: TrailId<Event>, event: Event) {
+        let events = self.trails[id.index]
+            .as_any_mut()
+            .downcast_mut::<Vec<Event>>()
+            .expect("Trail of another composite trail");
+        events.push(event);
+        self.order.push(id.index as u32);
+    }
+
+    /// Returns the events of the given trail, from the oldest to the most recent.
+    pub fn events<Event: 'static>(&self, id: TrailId<Event>) -> &[Event] {
+        self.trails[id.index]
+            .as_any()
+            .downcast_ref::<Vec<Event>>()
+            .expect("Trail of another composite trail")
+    }
+
+    /// Number of events in all trails.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Creates a new save point for all trails and returns the new decision level.
+    pub fn save_state(&mut self) -> DecLvl {
+        self.saved_states.push(self.order.len());
+        DecLvl::from(self.saved_states.len())
+    }
+
+    pub fn num_saved(&self) -> u32 {
+        self.saved_states.len() as u32
+    }
+
+    pub fn current_decision_level(&self) -> DecLvl {
+        DecLvl::from(self.num_saved())
+    }
+
+    /// Undoes all events of the last decision level, in all trails, from the most recent one to the oldest one.
+    ///
+    /// Each event is passed to `undo`, where it can be extracted with the handle of its trail (see [UndoneEvent::take]).
+    /// Events that are not extracted are discarded.
+    pub fn restore_last_with(&mut self, mut undo: impl FnMut(&mut UndoneEvent)) {
+        let last_index = self.saved_states.pop().expect("No saved state");
+        while self.order.len() > last_index {
+            let trail = self.order.pop().unwrap() as usize;
+            let mut event = UndoneEvent {
+                trail,
+                events: self.trails[trail].as_mut(),
+                taken: false,
+            };
+            undo(&mut event);
+            if !event.taken {
+                self.trails[trail].discard_last();
+            }
+        }
+    }
+
+    /// Restores all trails to the given decision level, that must not be above the current one.
+    pub fn restore_with(&mut self, saved_id: DecLvl, mut undo: impl FnMut(&mut UndoneEvent)) {
+        debug_assert!(
+            saved_id <= self.current_decision_level(),
+            "Restoring to level {} from level {}",
+            saved_id.to_int(),
+            self.num_saved()
+        );
+        while self.current_decision_level() > saved_id {
+            self.restore_last_with(&mut undo);
+        }
+    }
+
+    /// Checks (in debug mode) that another backtrackable component is at the same decision level as the trails.
+    ///
+    /// Components that are not backtracked through the composite trail should call it whenever they are saved or
+    /// restored together with it, to detect as early as possible any desynchronization.
+    pub fn debug_assert_synchronized(&self, other: &impl Backtrack) {
+        debug_assert_eq!(
+            self.num_saved(),
+            other.num_saved(),
+            "Component at a different decision level than the composite trail"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtrack::DecisionLevelTracker;
+
+    #[test]
+    fn test_composite_trail() {
+        let mut trail = CompositeTrail::new();
+        let ints = trail.register::<i32>();
+        let strs = trail.register::<String>();
+
+        trail.push(ints, 1);
+        trail.save_state();
+        trail.push(strs, "a".to_string());
+        trail.push(ints, 2);
+        trail.save_state();
+        trail.push(ints, 3);
+        trail.push(strs, "b".to_string());
+        assert_eq!(trail.events(ints), &[1, 2, 3]);
+        assert_eq!(trail.len(), 5);
+
+        // events are undone in the reverse order in which they were pushed, across all trails
+        let mut undone = Vec::new();
+        trail.restore_with(DecLvl::ROOT, |ev| {
+            if let Some(i) = ev.take(ints) {
+                undone.push(i.to_string());
+            } else if let Some(s) = ev.take(strs) {
+                undone.push(s);
+            }
+        });
+        assert_eq!(undone, vec!["b", "3", "2", "a"]);
+        assert_eq!(trail.events(ints), &[1]);
+        assert!(trail.events(strs).is_empty());
+        assert_eq!(trail.current_decision_level(), DecLvl::ROOT);
+
+        // events that are not extracted are discarded
+        trail.save_state();
+        trail.push(strs, "c".to_string());
+        trail.restore_last_with(|_| {});
+        assert!(trail.events(strs).is_empty());
+
+        let mut tracker = DecisionLevelTracker::new();
+        trail.save_state();
+        tracker.save_state();
+        trail.debug_assert_synchronized(&tracker);
+    }
+}
@@ -1,7 +1,9 @@
 mod backtrack_trait;
+mod composite_trail;
 mod queues;
 mod trail;
 
 pub use backtrack_trait::*;
+pub use composite_trail::{CompositeTrail, TrailId, UndoneEvent};
 pub use queues::*;
 pub use trail::Trail;
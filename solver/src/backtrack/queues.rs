@@ -106,6 +106,7 @@ impl std::fmt::Debug for DecLvl {
     }
 }
 
+/// A backtrack of a queue, after which the events from `next_read` onward were cancelled.
 #[derive(Copy, Clone)]
 struct LastBacktrack {
     next_read: EventIndex,
@@ -173,7 +174,10 @@ pub struct ObsTrail<V> {
     events: Vec<V>,
     /// Maps each decision level [DecLvl] with the index of its first event.
     backtrack_points: Vec<EventIndex>,
-    last_backtrack: Option<LastBacktrack>,
+    /// Backtracks of the queue, in increasing order of both their `id` and `next_read`.
+    /// A backtrack is removed once a later one cancels the same events (or more), so that the first backtrack
+    /// with an `id` greater than some `i` is the one that cancelled the earliest event among all backtracks after `i`.
+    backtracks: Vec<LastBacktrack>,
 }
 impl<V> Default for ObsTrail<V> {
     fn default() -> Self {
@@ -185,7 +189,7 @@ impl<V> ObsTrail<V> {
         ObsTrail {
             events: Default::default(),
             backtrack_points: Default::default(),
-            last_backtrack: None,
+            backtracks: Default::default(),
         }
     }
     pub fn len(&self) -> usize {
@@ -226,8 +230,11 @@ impl<V> ObsTrail<V> {
             f(ev)
         }
         self.events.drain(id..);
-        let bt_id = self.last_backtrack.as_ref().map_or(0, |bt| bt.id + 1);
-        self.last_backtrack = Some(LastBacktrack {
+        let bt_id = self.backtracks.last().map_or(0, |bt| bt.id + 1);
+        while self.backtracks.last().map_or(false, |bt| bt.next_read >= after_last) {
+            self.backtracks.pop();
+        }
+        self.backtracks.push(LastBacktrack {
             next_read: after_last,
             id: bt_id,
         });
@@ -244,6 +251,17 @@ impl<V> ObsTrail<V> {
         DecLvl::new(self.backtrack_points.len() as u32)
     }
 
+    /// Returns the number of events that were in the trail at the end of the decision level `dl`,
+    /// i.e. just before the first event of the next decision level.
+    /// If `dl` is the current decision level (or higher), this is the current number of events.
+    pub fn num_events_at_level(&self, dl: DecLvl) -> u32 {
+        if dl < self.current_decision_level() {
+            u32::from(self.backtrack_points[dl + 1])
+        } else {
+            self.num_events()
+        }
+    }
+
     pub fn decision_level(&self, id: EventIndex) -> DecLvl {
         let mut dl = self.current_decision_level();
         while dl > DecLvl::ROOT {
@@ -436,20 +454,31 @@ impl<V> ObsTrailCursor<V> {
         }
     }
 
-    // TODO: check correctness if more than one backtrack occurred between two synchronisations
-    fn sync_backtrack(&mut self, queue: &ObsTrail<V>) {
-        if let Some(x) = &queue.last_backtrack {
+    /// Catches up with the backtracks of the queue that occurred since the last synchronisation, and returns the
+    /// earliest event they cancelled, if any.
+    fn sync_backtrack(&mut self, queue: &ObsTrail<V>) -> Option<EventIndex> {
+        let mut earliest_cancelled = None;
+        if let Some(x) = queue.backtracks.last() {
             // a backtrack has already happened in the queue, check if we are in sync
             if self.last_backtrack != Some(x.id) {
-                // we have not handled this backtrack, backtrack now if have have read some
-                // cancelled output
-                if self.next_read > x.next_read {
-                    self.next_read = x.next_read;
+                // we have not handled the latest backtracks. Several of them may have occurred since the last
+                // synchronisation, the queue growing in between: the earliest cancelled event is the one of
+                // the first backtrack that we have not handled.
+                let first_unhandled = match self.last_backtrack {
+                    Some(handled) => queue.backtracks.partition_point(|bt| bt.id <= handled),
+                    None => 0,
+                };
+                let earliest = queue.backtracks[first_unhandled].next_read;
+                // backtrack now if have have read some cancelled output
+                if self.next_read > earliest {
+                    self.next_read = earliest;
                 }
                 self.last_backtrack = Some(x.id);
+                earliest_cancelled = Some(earliest);
             }
         }
         debug_assert!(self.next_read <= queue.next_slot());
+        earliest_cancelled
     }
 
     pub fn num_pending(&mut self, queue: &ObsTrail<V>) -> usize {
@@ -480,6 +509,93 @@ impl<V> ObsTrailCursor<V> {
     }
 }
 
+/// A filter on the events of a queue, deciding which of them are read by a [FilteredObsTrailCursor].
+pub trait EventFilter<V> {
+    fn accepts(&self, event: &V) -> bool;
+}
+
+impl<V, F: Fn(&V) -> bool> EventFilter<V> for F {
+    fn accepts(&self, event: &V) -> bool {
+        self(event)
+    }
+}
+
+/// A cursor on a queue that only reads the events accepted by its filter, e.g., to ignore the events
+/// produced by the reader itself.
+///
+/// Rejected events are skipped while iterating, without being returned. The number of pending events is maintained
+/// incrementally: each event is checked against the filter once when counting, unless a backtrack cancels it.
+#[derive(Clone)]
+pub struct FilteredObsTrailCursor<V, F> {
+    cursor: ObsTrailCursor<V>,
+    filter: F,
+    /// End of the events already counted: the events between the next one to read and this one were checked
+    /// against the filter, and `num_accepted` of them were accepted.
+    counted_until: EventIndex,
+    num_accepted: usize,
+}
+
+impl<V, F: EventFilter<V>> FilteredObsTrailCursor<V, F> {
+    pub fn new(filter: F) -> Self {
+        FilteredObsTrailCursor {
+            cursor: ObsTrailCursor::new(),
+            filter,
+            counted_until: EventIndex::from(0u32),
+            num_accepted: 0,
+        }
+    }
+
+    fn sync_backtrack(&mut self, queue: &ObsTrail<V>) {
+        match self.cursor.sync_backtrack(queue) {
+            Some(earliest) if earliest < self.counted_until => {
+                // some counted events were cancelled, count again from the next event to read
+                self.counted_until = self.cursor.next_read;
+                self.num_accepted = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the number of events accepted by the filter that remain to be read.
+    pub fn num_pending(&mut self, queue: &ObsTrail<V>) -> usize {
+        self.sync_backtrack(queue);
+        let end = queue.next_slot();
+        while self.counted_until < end {
+            if self.filter.accepts(&queue.events[self.counted_until]) {
+                self.num_accepted += 1;
+            }
+            self.counted_until += 1;
+        }
+        self.num_accepted
+    }
+
+    /// Returns the next event accepted by the filter, if any.
+    pub fn pop<'q>(&mut self, queue: &'q ObsTrail<V>) -> Option<&'q V> {
+        self.sync_backtrack(queue);
+        loop {
+            let next = self.cursor.next_read;
+            let ev = self.cursor.pop(queue)?;
+            let accepted = self.filter.accepts(ev);
+            if next < self.counted_until {
+                if accepted {
+                    self.num_accepted -= 1;
+                }
+            } else {
+                self.counted_until = self.cursor.next_read;
+            }
+            if accepted {
+                return Some(ev);
+            }
+        }
+    }
+
+    pub fn move_to_end(&mut self, queue: &ObsTrail<V>) {
+        self.cursor.move_to_end(queue);
+        self.counted_until = self.cursor.next_read;
+        self.num_accepted = 0;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -511,6 +627,59 @@ mod tests {
         assert_eq!(r2.pop(&q), None);
     }
 
+    #[test]
+    fn test_successive_backtracks() {
+        let mut q = ObsTrail::new();
+        q.push(1);
+        q.save_state();
+        q.push(2);
+        q.save_state();
+        q.push(3);
+        let mut r = q.reader();
+        assert_eq!(r.pop(&q), Some(&1));
+        assert_eq!(r.pop(&q), Some(&2));
+        assert_eq!(r.pop(&q), Some(&3));
+
+        // backtrack to the root, then to a later point after the queue grew, without reading in between
+        q.restore_last();
+        q.restore_last();
+        q.push(4);
+        q.save_state();
+        q.push(5);
+        q.save_state();
+        q.push(6);
+        q.restore_last();
+        // the cursor had read up to the third event, but the second one was cancelled by the first backtrack
+        assert_eq!(r.pop(&q), Some(&4));
+        assert_eq!(r.pop(&q), Some(&5));
+        assert_eq!(r.pop(&q), None);
+    }
+
+    #[test]
+    fn test_filtered_cursor() {
+        let mut q = ObsTrail::new();
+        let mut r = FilteredObsTrailCursor::new(|n: &i32| n % 2 == 0);
+        q.push(1);
+        q.push(2);
+        q.save_state();
+        q.push(3);
+        q.push(4);
+        q.push(6);
+        assert_eq!(r.num_pending(&q), 3);
+        assert_eq!(r.pop(&q), Some(&2));
+        assert_eq!(r.num_pending(&q), 2);
+        assert_eq!(r.pop(&q), Some(&4));
+
+        // the counted event 6 is cancelled
+        q.restore_last();
+        q.push(5);
+        q.push(8);
+        assert_eq!(r.num_pending(&q), 1);
+        assert_eq!(r.pop(&q), Some(&8));
+        assert_eq!(r.num_pending(&q), 0);
+        assert_eq!(r.pop(&q), None);
+    }
+
     #[test]
     fn test_backtracks() {
         let mut q = ObsTrail::new();
@@ -84,6 +84,35 @@ impl Disjunction {
     pub fn contains(&self, lit: Lit) -> bool {
         self.literals.contains(&lit)
     }
+
+    /// Returns true if this disjunction subsumes the `other` one, i.e., if each of its literals entails a literal of `other`.
+    /// In this case, `other` is implied by this disjunction and redundant with it.
+    ///
+    /// Because the literals of both disjunctions are sorted and deduplicated, each disjunction has at most one
+    /// literal per signed variable and the check is done in a single pass over both disjunctions.
+    pub fn subsumes(&self, other: &Disjunction) -> bool {
+        if self.len() > other.len() {
+            return false;
+        }
+        let mut others = other.literals.iter().copied();
+        'literals: for &l in &self.literals {
+            for o in others.by_ref() {
+                if o.svar() == l.svar() {
+                    if l.entails(o) {
+                        continue 'literals;
+                    } else {
+                        return false;
+                    }
+                } else if o.svar() > l.svar() {
+                    // `other` has no literal on this signed variable
+                    return false;
+                }
+            }
+            // reached the end of `other` without finding a literal entailed by `l`
+            return false;
+        }
+        true
+    }
 }
 
 impl<'a> IntoIterator for &'a Disjunction {
@@ -213,4 +242,27 @@ mod tests {
         assert!(Disjunction::new(vec![leq(a, 0), geq(a, 1)]).is_tautology());
         assert!(Disjunction::new(vec![leq(a, 0), leq(b, 0), geq(b, 2), !leq(a, 0)]).is_tautology());
     }
+
+    #[test]
+    fn test_subsumption() {
+        let a = VarRef::from(0usize);
+        let b = VarRef::from(1usize);
+        let c = VarRef::from(2usize);
+        let subsumes = |x: Vec<Lit>, y: Vec<Lit>| Disjunction::new(x).subsumes(&Disjunction::new(y));
+
+        assert!(subsumes(vec![leq(a, 0)], vec![leq(a, 0), geq(b, 1)]));
+        // (a <= 0) entails (a <= 2)
+        assert!(subsumes(
+            vec![leq(a, 0), geq(c, 3)],
+            vec![leq(a, 2), geq(b, 1), geq(c, 2)]
+        ));
+        assert!(subsumes(vec![leq(a, 0), geq(b, 1)], vec![leq(a, 0), geq(b, 1)]));
+        assert!(!subsumes(vec![leq(a, 2)], vec![leq(a, 0), geq(b, 1)]));
+        assert!(!subsumes(vec![leq(a, 0), geq(b, 1)], vec![leq(a, 0)]));
+        assert!(!subsumes(
+            vec![leq(a, 0), geq(b, 1)],
+            vec![leq(a, 0), leq(b, 1), geq(c, 1)]
+        ));
+        assert!(!subsumes(vec![geq(c, 1)], vec![leq(a, 0), geq(b, 1)]));
+    }
 }
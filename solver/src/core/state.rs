@@ -4,6 +4,7 @@ mod domains;
 mod event;
 mod explanation;
 mod int_domains;
+mod view;
 
 pub use cause::*;
 pub use domain::*;
@@ -11,9 +12,36 @@ pub use domains::*;
 pub use event::*;
 pub use explanation::*;
 pub use int_domains::*;
+pub use view::*;
 
-use crate::core::Lit;
+use crate::core::{Lit, VarRef};
 
-/// Represents a triggered event of setting a conflicting literal.
+/// Represents a triggered event of setting a conflicting literal, i.e., an update that would have left the domain
+/// of its variable empty.
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
-pub struct InvalidUpdate(pub Lit, pub Origin);
+pub struct InvalidUpdate {
+    /// Literal whose enforcement was attempted, i.e., the new bound of the variable.
+    pub lit: Lit,
+    /// Cause of the attempted update, e.g. the reasoner that made the inference.
+    pub cause: Origin,
+    /// Domain of the variable when the update was attempted, that does not contain any value satisfying `lit`.
+    pub domain: IntDomain,
+}
+
+impl InvalidUpdate {
+    /// The variable whose domain would have been emptied.
+    pub fn variable(&self) -> VarRef {
+        self.lit.variable()
+    }
+}
+
+impl std::fmt::Display for InvalidUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Empty domain: cannot enforce `{:?}` on the domain {} of its variable (cause: {})",
+            self.lit, self.domain, self.cause
+        )
+    }
+}
+impl std::error::Error for InvalidUpdate {}
@@ -88,6 +88,17 @@ impl Origin {
     }
 }
 
+impl std::fmt::Display for Origin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Origin::Direct(origin) => write!(f, "{origin}"),
+            Origin::PresenceOfEmptyDomain(lit, origin) => {
+                write!(f, "absence implied by the invalid update `{lit:?}`, from {origin}")
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum DirectOrigin {
     Decision,
@@ -102,3 +113,20 @@ pub enum DirectOrigin {
     /// The given literal triggered an implication propagation.
     ImplicationPropagation(Lit),
 }
+
+impl std::fmt::Display for DirectOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DirectOrigin::Decision => write!(f, "decision"),
+            DirectOrigin::Encoding => write!(f, "encoding"),
+            DirectOrigin::ExternalInference(cause) => {
+                write!(
+                    f,
+                    "inference of the {} reasoner (payload: {})",
+                    cause.writer, cause.payload
+                )
+            }
+            DirectOrigin::ImplicationPropagation(lit) => write!(f, "implication of `{lit:?}`"),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::core::IntCst;
 use std::fmt::Formatter;
 
-#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Debug)]
 pub struct IntDomain {
     pub lb: IntCst,
     pub ub: IntCst,
@@ -3,8 +3,8 @@ use crate::collections::ref_store::RefMap;
 use crate::core::literals::{Disjunction, ImplicationGraph, LitSet};
 use crate::core::state::cause::{DirectOrigin, Origin};
 use crate::core::state::event::Event;
-use crate::core::state::int_domains::IntDomains;
-use crate::core::state::{Cause, Explainer, Explanation, InvalidUpdate, OptDomain};
+use crate::core::state::int_domains::{strongest_updates, IntDomains};
+use crate::core::state::{Cause, DomainsSnapshot, Explainer, Explanation, InvalidUpdate, OptDomain};
 use crate::core::*;
 use std::collections::BinaryHeap;
 use std::fmt::{Debug, Formatter};
@@ -95,6 +95,21 @@ impl Domains {
         var
     }
 
+    /// Replaces the presence literal of an optional variable, e.g. by an equivalent one to merge scopes.
+    ///
+    /// The new presence literal must be equivalent to the previous one at the root, which is not checked.
+    pub fn set_presence(&mut self, var: VarRef, presence: Lit) {
+        assert!(
+            self.presence.contains(var),
+            "Only the presence of an optional variable can be replaced"
+        );
+        assert!(
+            !self.presence.contains(presence.variable()),
+            "The presence literal of an optional variable should not be based on an optional variable"
+        );
+        self.presence.insert(var, presence);
+    }
+
     pub fn presence(&self, term: impl Term) -> Lit {
         self.presence.get(term.variable()).copied().unwrap_or(Lit::TRUE)
     }
@@ -106,6 +121,18 @@ impl Domains {
         self.implications.implies(prez_a, prez_b)
     }
 
+    /// Returns true if the literal or its negation appears in an implication (see [Self::add_implication]).
+    pub fn in_implications(&self, lit: Lit) -> bool {
+        self.implications.direct_implications_of(lit).next().is_some()
+            || self.implications.direct_implications_of(!lit).next().is_some()
+    }
+
+    /// Returns the literals that are directly implied by `lit` in the implications between presence variables
+    /// (see [Self::add_implication]).
+    pub fn direct_implications_of(&self, lit: Lit) -> impl Iterator<Item = Lit> + '_ {
+        self.implications.direct_implications_of(lit)
+    }
+
     /// Returns true if `a` is known to imply `b`
     pub fn implies(&self, a: Lit, b: Lit) -> bool {
         self.implications.implies(a, b)
@@ -160,6 +187,23 @@ impl Domains {
         self.doms.lb(var)
     }
 
+    /// Returns the width of the current domain of the variable (`ub - lb`).
+    pub fn domain_width(&self, var: VarRef) -> IntCst {
+        self.doms.domain_width(var)
+    }
+
+    /// Returns the statistics accumulated on the variable (number of bound updates and of conflicts involving it).
+    #[cfg(feature = "var_stats")]
+    pub fn var_stats(&self, var: VarRef) -> crate::core::state::VarStats {
+        self.doms.var_stats(var)
+    }
+
+    /// Records a conflict whose clause is made of the given literals, in the statistics of their variables.
+    #[cfg(feature = "var_stats")]
+    pub fn record_conflict(&mut self, clause: &[Lit]) {
+        self.doms.record_conflict(clause)
+    }
+
     /// Returns true if the integer domain of the variable is a singleton or an empty set.
     ///
     /// Note that an empty set is valid for optional variables and implies that
@@ -203,8 +247,8 @@ impl Domains {
     ///  - `Ok(true)` if the bound was changed and it results in a valid (non-empty) domain.
     ///  - `Ok(false)` if no modification of the domain was carried out. This might occur if the
     ///     provided bound is less constraining than the existing one.
-    ///  - `Err(EmptyDomain(v))` if the change resulted in the variable `v` having an empty domain.
-    ///     In general, it cannot be assumed that `v` is the same as the variable passed as parameter.
+    ///  - `Err(InvalidUpdate)` if the change resulted in a variable having an empty domain.
+    ///     In general, it cannot be assumed that it is the same as the variable passed as parameter.
     #[inline]
     pub fn set_lb(&mut self, var: impl Into<VarRef>, new_lb: IntCst, cause: Cause) -> Result<bool, InvalidUpdate> {
         self.set_bound(SignedVar::minus(var.into()), UpperBound::lb(new_lb), cause)
@@ -218,8 +262,8 @@ impl Domains {
     ///  - `Ok(true)` if the bound was changed and it results in a valid (non-empty) domain
     ///  - `Ok(false)` if no modification of the domain was carried out. This might occur if the
     ///     provided bound is less constraining than the existing one.
-    ///  - `Err(EmptyDomain(v))` if the change resulted in the variable `v` having an empty domain.
-    ///     In general, it cannot be assumed that `v` is the same as the variable passed as parameter.
+    ///  - `Err(InvalidUpdate)` if the change resulted in a variable having an empty domain.
+    ///     In general, it cannot be assumed that it is the same as the variable passed as parameter.
     #[inline]
     pub fn set_ub(&mut self, var: impl Into<VarRef>, new_ub: IntCst, cause: Cause) -> Result<bool, InvalidUpdate> {
         self.set_bound(SignedVar::plus(var.into()), UpperBound::ub(new_ub), cause)
@@ -239,6 +283,22 @@ impl Domains {
         self.set_bound_impl(affected, new, cause.into())
     }
 
+    /// Applies all updates with the same cause, emitting at most one event per variable bound.
+    /// This is intended for propagators that compute all their updates before applying them.
+    ///
+    /// Returns `Ok(true)` if at least one bound was changed.
+    pub fn set_bounds_batch(
+        &mut self,
+        updates: &[(SignedVar, UpperBound)],
+        cause: Cause,
+    ) -> Result<bool, InvalidUpdate> {
+        let mut changed = false;
+        for (affected, new) in strongest_updates(updates) {
+            changed |= self.set_bound_impl(affected, new, cause.into())?;
+        }
+        Ok(changed)
+    }
+
     fn set_bound_impl(&mut self, affected: SignedVar, new: UpperBound, cause: Origin) -> Result<bool, InvalidUpdate> {
         match self.presence(affected.variable()) {
             Lit::TRUE => self.set_bound_non_optional(affected, new, cause),
@@ -325,10 +385,10 @@ impl Domains {
                 Ok(true)
             }
             Ok(false) => Ok(false),
-            Err(InvalidUpdate(lit, fail_cause)) => {
-                debug_assert_eq!(lit, Lit::from_parts(affected, new));
-                debug_assert_eq!(fail_cause, cause);
-                Err(InvalidUpdate(lit, fail_cause))
+            Err(invalid) => {
+                debug_assert_eq!(invalid.lit, Lit::from_parts(affected, new));
+                debug_assert_eq!(invalid.cause, cause);
+                Err(invalid)
             }
         }
     }
@@ -376,6 +436,32 @@ impl Domains {
         self.doms.trail()
     }
 
+    /// Returns the value that the bound had when the trail only contained its first `num_events` events.
+    /// This is a read-only query that does not require backtracking.
+    pub fn get_bound_at(&self, var_bound: SignedVar, num_events: u32) -> UpperBound {
+        self.doms.bound_value_at(var_bound, num_events)
+    }
+
+    /// Returns the value that the bound had at the end of the decision level `dl`.
+    pub fn get_bound_at_level(&self, var_bound: SignedVar, dl: DecLvl) -> UpperBound {
+        self.doms.bound_value_at_level(var_bound, dl)
+    }
+
+    /// Returns true if the literal was entailed when the trail only contained its first `num_events` events.
+    pub fn entailed_at(&self, lit: Lit, num_events: u32) -> bool {
+        self.doms.entailed_at(lit, num_events)
+    }
+
+    /// Returns true if the literal was entailed at the end of the decision level `dl`.
+    pub fn entailed_at_level(&self, lit: Lit, dl: DecLvl) -> bool {
+        self.entailed_at(lit, self.trail().num_events_at_level(dl))
+    }
+
+    /// Returns a read-only view of the domains as they were when the trail only contained its first `num_events` events.
+    pub fn snapshot(&self, num_events: u32) -> DomainsSnapshot<'_> {
+        DomainsSnapshot::new(self, num_events)
+    }
+
     pub fn entailing_level(&self, lit: Lit) -> DecLvl {
         debug_assert!(self.entails(lit));
         match self.implying_event(lit) {
@@ -405,7 +491,29 @@ impl Domains {
     /// The update of `l` must not directly originate from a decision as it is necessarily the case that
     /// `!l` holds in the current state. It is thus considered a logic error to impose an obviously wrong decision.
     pub fn clause_for_invalid_update(&mut self, failed: InvalidUpdate, explainer: &mut impl Explainer) -> Conflict {
-        let InvalidUpdate(literal, cause) = failed;
+        self.clause_for_invalid_update_impl(failed, explainer, None)
+    }
+
+    /// Same as [Domains::clause_for_invalid_update] but also records in `trace` all resolution steps
+    /// that led to the clause.
+    pub fn clause_for_invalid_update_with_trace(
+        &mut self,
+        failed: InvalidUpdate,
+        explainer: &mut impl Explainer,
+        trace: &mut Vec<ResolutionStep>,
+    ) -> Conflict {
+        self.clause_for_invalid_update_impl(failed, explainer, Some(trace))
+    }
+
+    fn clause_for_invalid_update_impl(
+        &mut self,
+        failed: InvalidUpdate,
+        explainer: &mut impl Explainer,
+        mut trace: Option<&mut Vec<ResolutionStep>>,
+    ) -> Conflict {
+        let InvalidUpdate {
+            lit: literal, cause, ..
+        } = failed;
         debug_assert!(!self.entails(literal));
 
         // an update is invalid iff its negation holds AND the affected variable is present
@@ -423,11 +531,18 @@ impl Domains {
 
         self.add_implying_literals_to_explanation(literal, cause, &mut explanation, explainer);
         debug_assert!(explanation.lits.iter().copied().all(|l| self.entails(l)));
+        if let Some(trace) = &mut trace {
+            trace.push(ResolutionStep {
+                literal,
+                cause,
+                antecedents: explanation.lits[1..].to_vec(),
+            });
+        }
 
         // explanation = `!literal v x_1 v ... v x_m`, where all disjuncts hold in the current state
         // we then transform this clause to be in the first unique implication point (1UIP) form.
 
-        self.refine_explanation(explanation, explainer)
+        self.refine_explanation_impl(explanation, explainer, trace)
     }
 
     /// Refines an explanation into an asserting clause.
@@ -435,6 +550,29 @@ impl Domains {
     /// Note that a partial backtrack (within the current decision level) will occur in the process.
     /// This is necessary to provide explainers with the exact state in which their decisions were made.
     pub fn refine_explanation(&mut self, explanation: Explanation, explainer: &mut impl Explainer) -> Conflict {
+        self.refine_explanation_impl(explanation, explainer, None)
+    }
+
+    /// Same as [Domains::refine_explanation] but also records in `trace` all resolution steps that led to the clause,
+    /// i.e., each literal that was replaced by its own explanation, in the order in which they were resolved.
+    ///
+    /// This is intended for debugging and for presenting explanations to users, as the construction
+    /// of the trace has a significant overhead.
+    pub fn refine_explanation_with_trace(
+        &mut self,
+        explanation: Explanation,
+        explainer: &mut impl Explainer,
+        trace: &mut Vec<ResolutionStep>,
+    ) -> Conflict {
+        self.refine_explanation_impl(explanation, explainer, Some(trace))
+    }
+
+    fn refine_explanation_impl(
+        &mut self,
+        explanation: Explanation,
+        explainer: &mut impl Explainer,
+        mut trace: Option<&mut Vec<ResolutionStep>>,
+    ) -> Conflict {
         debug_assert!(explanation.literals().iter().all(|&l| self.entails(l)));
         let mut explanation = explanation;
 
@@ -544,6 +682,13 @@ impl Domains {
             resolved.insert(l.lit);
             // in the explanation, add a set of literal whose conjunction implies `l.lit`
             self.add_implying_literals_to_explanation(l.lit, cause, &mut explanation, explainer);
+            if let Some(trace) = &mut trace {
+                trace.push(ResolutionStep {
+                    literal: l.lit,
+                    cause,
+                    antecedents: explanation.lits.clone(),
+                });
+            }
         }
     }
 
@@ -691,6 +836,18 @@ impl PartialOrd for InQueueLit {
     }
 }
 
+/// A step in the derivation of a clause: a literal of the explanation that was replaced by its own explanation.
+#[derive(Clone, Debug)]
+pub struct ResolutionStep {
+    /// The resolved literal.
+    pub literal: Lit,
+    /// Origin of the event that made the literal true.
+    /// For an inference, it identifies the reasoner that made it (and the inference itself through its payload).
+    pub cause: Origin,
+    /// Literals whose conjunction implies `literal`, that replaced it in the explanation.
+    pub antecedents: Vec<Lit>,
+}
+
 /// Data resulting from a conflict, of which the most important is the learnt clause.
 pub struct Conflict {
     /// Clause associated to the conflict.
@@ -793,7 +950,7 @@ mod tests {
         check_doms(&domains, 1, 1, 0, 0, 5, 5);
 
         // make p2 have an empty domain, this should imply that p1 = false which is a contradiction with our previous decision
-        assert!(matches!(domains.set(p2, Cause::Decision), Err(InvalidUpdate(_, _))));
+        assert!(matches!(domains.set(p2, Cause::Decision), Err(InvalidUpdate { .. })));
     }
 
     #[test]
@@ -852,15 +1009,28 @@ mod tests {
         assert_eq!(model.set_lb(a, 9, Cause::Decision), Ok(true));
         assert_eq!(
             model.set_lb(a, 10, Cause::Decision),
-            Err(InvalidUpdate(Lit::geq(a, 10), Origin::DECISION))
+            Err(InvalidUpdate {
+                lit: Lit::geq(a, 10),
+                cause: Origin::DECISION,
+                domain: IntDomain::new(9, 9)
+            })
         );
 
         model.restore_last();
         assert_eq!(model.bounds(a), (1, 9));
         assert_eq!(model.set_ub(a, 1, Cause::Decision), Ok(true));
+        let invalid = InvalidUpdate {
+            lit: Lit::leq(a, 0),
+            cause: Origin::DECISION,
+            domain: IntDomain::new(1, 1),
+        };
+        assert_eq!(model.set_ub(a, 0, Cause::Decision), Err(invalid));
         assert_eq!(
-            model.set_ub(a, 0, Cause::Decision),
-            Err(InvalidUpdate(Lit::leq(a, 0), Origin::DECISION))
+            invalid.to_string(),
+            format!(
+                "Empty domain: cannot enforce `{:?}` on the domain 1 of its variable (cause: decision)",
+                Lit::leq(a, 0)
+            )
         );
     }
 
@@ -935,9 +1105,17 @@ mod tests {
             _ => panic!(),
         };
 
-        let clause = model.clause_for_invalid_update(err, &mut network);
+        let mut trace = Vec::new();
+        let clause = model.clause_for_invalid_update_with_trace(err, &mut network, &mut trace);
         let clause: HashSet<_> = clause.literals().iter().copied().collect();
 
+        // the only resolved literal is (n >= 5), inferred from b by the constraint 1
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0].literal, Lit::geq(n, 5));
+        let cause = trace[0].cause.as_external_inference().unwrap();
+        assert_eq!((cause.writer, cause.payload), (writer, 1));
+        assert_eq!(trace[0].antecedents, vec![b]);
+
         // we have three rules
         //  -  !(n <= 4) || !(n >= 5)   (conflict)
         //  -  !a || (n <= 4)           (clause a)
@@ -2,7 +2,7 @@ use crate::backtrack::{Backtrack, BacktrackWith, DecLvl, EventIndex, ObsTrail};
 use crate::collections::ref_store::RefVec;
 use crate::core::state::cause::Origin;
 use crate::core::state::event::{ChangeIndex, Event};
-use crate::core::state::InvalidUpdate;
+use crate::core::state::{IntDomain, InvalidUpdate};
 use crate::core::*;
 
 /// Represents a the value of an upper/lower bound of a particular variable.
@@ -23,6 +23,15 @@ impl ValueCause {
     }
 }
 
+/// Statistics on the history of a variable, only maintained if the `var_stats` feature is enabled.
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
+pub struct VarStats {
+    /// Number of updates of the lower or upper bound of the variable.
+    pub num_updates: u64,
+    /// Number of conflicts whose clause involves the variable.
+    pub num_conflicts: u64,
+}
+
 /// Associates every variable to the literals of its domain.
 /// In addition, it maintains the history of changes that caused the literals to be in this state,
 /// which enables explanations and backtracking.
@@ -36,6 +45,9 @@ pub struct IntDomains {
     /// All events that updated the bound values.
     /// Used for explanation and backtracking.
     events: ObsTrail<Event>,
+    /// Statistics of each variable, that are never undone by backtracking.
+    #[cfg(feature = "var_stats")]
+    stats: RefVec<VarRef, VarStats>,
 }
 
 impl IntDomains {
@@ -43,6 +55,8 @@ impl IntDomains {
         let mut uninitialized = IntDomains {
             bounds: Default::default(),
             events: Default::default(),
+            #[cfg(feature = "var_stats")]
+            stats: Default::default(),
         };
         let zero = uninitialized.new_var(0, 0);
         debug_assert_eq!(zero, VarRef::ZERO);
@@ -57,6 +71,8 @@ impl IntDomains {
         debug_assert_eq!(var_lb.variable(), var_ub.variable());
         debug_assert!(var_lb.is_minus());
         debug_assert!(var_ub.is_plus());
+        #[cfg(feature = "var_stats")]
+        self.stats.push(VarStats::default());
         var_lb.variable()
     }
 
@@ -81,7 +97,7 @@ impl IntDomains {
     /// Results:
     ///  - Ok(true): The model was updated ans is consistent.
     ///  - Ok(false): The change is as no-op (was previously entailed) and nothing changed. The model is consistent.
-    ///  - Err(EmptyDom(var)): update was not carried out as it would have resulted in an empty domain.
+    ///  - Err(InvalidUpdate): update was not carried out as it would have resulted in an empty domain.
     #[allow(clippy::if_same_then_else)]
     pub fn set_bound(&mut self, affected: SignedVar, new: UpperBound, cause: Origin) -> Result<bool, InvalidUpdate> {
         let current = self.bounds[affected];
@@ -101,14 +117,59 @@ impl IntDomains {
                     previous: current,
                 };
                 self.events.push(event);
+                #[cfg(feature = "var_stats")]
+                {
+                    self.stats[affected.variable()].num_updates += 1;
+                }
                 // update occurred and is consistent
                 Ok(true)
             } else {
-                Err(InvalidUpdate(lit, cause))
+                let var = affected.variable();
+                Err(InvalidUpdate {
+                    lit,
+                    cause,
+                    domain: IntDomain::new(self.lb(var), self.ub(var)),
+                })
             }
         }
     }
 
+    /// Applies all updates with the same cause, emitting at most one event per variable bound:
+    /// when several updates target the same bound, only the strongest one is applied.
+    ///
+    /// Returns `Ok(true)` if at least one bound was changed. On an invalid update, the updates that
+    /// precede it may have been applied.
+    pub fn set_bounds_batch(
+        &mut self,
+        updates: &[(SignedVar, UpperBound)],
+        cause: Origin,
+    ) -> Result<bool, InvalidUpdate> {
+        let mut changed = false;
+        for (affected, new) in strongest_updates(updates) {
+            changed |= self.set_bound(affected, new, cause)?;
+        }
+        Ok(changed)
+    }
+
+    /// Returns the width of the current domain of the variable (`ub - lb`).
+    pub fn domain_width(&self, var: VarRef) -> IntCst {
+        self.ub(var) - self.lb(var)
+    }
+
+    /// Returns the statistics accumulated on the variable since its creation.
+    #[cfg(feature = "var_stats")]
+    pub fn var_stats(&self, var: VarRef) -> VarStats {
+        self.stats[var]
+    }
+
+    /// Records a conflict whose clause is made of the given literals.
+    #[cfg(feature = "var_stats")]
+    pub fn record_conflict(&mut self, clause: &[Lit]) {
+        for lit in clause {
+            self.stats[lit.variable()].num_conflicts += 1;
+        }
+    }
+
     // ============= Variables =================
 
     /// Returns the number of variables declared.
@@ -157,6 +218,31 @@ impl IntDomains {
         self.events.num_events()
     }
 
+    /// Returns the value that the bound had when the trail only contained its first `num_events` events.
+    ///
+    /// This does not modify the current state: the history of the bound is followed backward from its current
+    /// value, with a cost linear in the number of updates to this bound after the requested position.
+    pub fn bound_value_at(&self, var_bound: SignedVar, num_events: u32) -> UpperBound {
+        let mut current = self.bounds[var_bound];
+        while let Some(loc) = current.cause {
+            if u32::from(loc) < num_events {
+                break;
+            }
+            current = self.events.get_event(loc).previous;
+        }
+        current.value
+    }
+
+    /// Returns the value that the bound had at the end of the decision level `dl`.
+    pub fn bound_value_at_level(&self, var_bound: SignedVar, dl: DecLvl) -> UpperBound {
+        self.bound_value_at(var_bound, self.events.num_events_at_level(dl))
+    }
+
+    /// Returns true if the literal was entailed when the trail only contained its first `num_events` events.
+    pub fn entailed_at(&self, lit: Lit, num_events: u32) -> bool {
+        self.bound_value_at(lit.svar(), num_events).stronger(lit.bound_value())
+    }
+
     pub fn last_event(&self) -> Option<&Event> {
         self.events.peek()
     }
@@ -179,6 +265,15 @@ impl IntDomains {
     }
 }
 
+/// Returns the given updates, keeping only the strongest one for each bound.
+pub(crate) fn strongest_updates(updates: &[(SignedVar, UpperBound)]) -> Vec<(SignedVar, UpperBound)> {
+    let mut merged = updates.to_vec();
+    // sort by bound and from the strongest to the weakest value, so that only the first update of each bound is kept
+    merged.sort_by_key(|&(affected, new)| (affected, new.as_int()));
+    merged.dedup_by_key(|(affected, _)| *affected);
+    merged
+}
+
 impl Default for IntDomains {
     fn default() -> Self {
         Self::new()
@@ -227,6 +322,84 @@ mod tests {
         assert!(!m.entails(a.leq(0)));
     }
 
+    #[test]
+    fn test_bounds_history() {
+        let mut m = IntDomains::default();
+        let a = m.new_var(0, 10);
+        let b = m.new_var(0, 10);
+        m.set_bound(SignedVar::plus(a), UpperBound::ub(8), Origin::DECISION)
+            .unwrap();
+        m.save_state();
+        m.set_bound(SignedVar::minus(b), UpperBound::lb(2), Origin::DECISION)
+            .unwrap();
+        let num_events = m.num_events();
+        m.set_bound(SignedVar::plus(a), UpperBound::ub(5), Origin::DECISION)
+            .unwrap();
+        m.save_state();
+        m.set_bound(SignedVar::plus(a), UpperBound::ub(3), Origin::DECISION)
+            .unwrap();
+
+        let ub_a = |dl: u32| m.bound_value_at_level(SignedVar::plus(a), DecLvl::from(dl)).as_int();
+        let lb_b = |dl: u32| -m.bound_value_at_level(SignedVar::minus(b), DecLvl::from(dl)).as_int();
+        assert_eq!([ub_a(0), ub_a(1), ub_a(2), ub_a(3)], [8, 5, 3, 3]);
+        assert_eq!([lb_b(0), lb_b(1), lb_b(2)], [0, 2, 2]);
+
+        assert!(m.entailed_at(a.leq(8), 1));
+        assert!(!m.entailed_at(a.leq(8), 0));
+        assert!(m.entailed_at(b.geq(2), num_events));
+        assert!(!m.entailed_at(a.leq(5), num_events));
+        assert_eq!(m.bound_value_at(SignedVar::plus(a), num_events + 1).as_int(), 5);
+
+        // the current state is left untouched
+        assert_eq!((m.lb(a), m.ub(a)), (0, 3));
+        assert_eq!((m.lb(b), m.ub(b)), (2, 10));
+    }
+
+    #[test]
+    fn test_domain_width() {
+        let mut m = IntDomains::default();
+        let a = m.new_var(0, 10);
+        assert_eq!(m.domain_width(a), 10);
+        m.set_bound(SignedVar::minus(a), UpperBound::lb(4), Origin::DECISION)
+            .unwrap();
+        assert_eq!(m.domain_width(a), 6);
+        assert_eq!(m.domain_width(VarRef::ZERO), 0);
+    }
+
+    #[cfg(feature = "var_stats")]
+    #[test]
+    fn test_var_stats() {
+        let mut m = IntDomains::default();
+        let a = m.new_var(0, 10);
+        let b = m.new_var(0, 10);
+        m.save_state();
+        m.set_bound(SignedVar::plus(a), UpperBound::ub(8), Origin::DECISION)
+            .unwrap();
+        m.set_bound(SignedVar::minus(a), UpperBound::lb(2), Origin::DECISION)
+            .unwrap();
+        // no-op update
+        m.set_bound(SignedVar::plus(a), UpperBound::ub(9), Origin::DECISION)
+            .unwrap();
+        m.record_conflict(&[a.leq(3), b.geq(1)]);
+        m.restore_last();
+
+        // statistics are not affected by backtracking
+        assert_eq!(
+            m.var_stats(a),
+            VarStats {
+                num_updates: 2,
+                num_conflicts: 1
+            }
+        );
+        assert_eq!(
+            m.var_stats(b),
+            VarStats {
+                num_updates: 0,
+                num_conflicts: 1
+            }
+        );
+    }
+
     #[test]
     fn test_variable_iter() {
         let mut m = IntDomains::default();
@@ -237,4 +410,33 @@ mod tests {
         let vars: Vec<VarRef> = m.variables().collect();
         assert_eq!(vars, vec![VarRef::ZERO, a, b, c]);
     }
+
+    #[test]
+    fn test_batch_updates() {
+        let mut m = IntDomains::default();
+        let a = m.new_var(0, 10);
+        let b = m.new_var(0, 10);
+        let cause = Origin::DECISION;
+        let num_events = m.num_events();
+
+        let updates = [
+            (SignedVar::plus(a), UpperBound::ub(8)),
+            (SignedVar::minus(b), UpperBound::lb(2)),
+            (SignedVar::plus(a), UpperBound::ub(5)),
+            (SignedVar::plus(a), UpperBound::ub(7)),
+        ];
+        assert_eq!(m.set_bounds_batch(&updates, cause), Ok(true));
+        assert_eq!(m.ub(a), 5);
+        assert_eq!(m.lb(b), 2);
+        // a single event per bound
+        assert_eq!(m.num_events(), num_events + 2);
+
+        // no-op
+        assert_eq!(m.set_bounds_batch(&updates, cause), Ok(false));
+        assert_eq!(m.num_events(), num_events + 2);
+
+        // empty domain
+        let updates = [(SignedVar::minus(a), UpperBound::lb(6))];
+        assert!(m.set_bounds_batch(&updates, cause).is_err());
+    }
 }
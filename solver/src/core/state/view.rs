@@ -0,0 +1,120 @@
+use crate::core::state::Domains;
+use crate::core::*;
+
+/// Read-only access to the domains of variables.
+///
+/// This allows reasoners to run the same algorithms (typically explanations) either on the current state
+/// or on a past one, as provided by a [DomainsSnapshot].
+pub trait DomainsView {
+    /// Returns the value of the given bound.
+    fn get_bound(&self, var_bound: SignedVar) -> UpperBound;
+
+    /// Returns the literal that is true iff the variable is present.
+    fn presence(&self, var: VarRef) -> Lit;
+
+    fn entails(&self, lit: Lit) -> bool {
+        self.get_bound(lit.svar()).stronger(lit.bound_value())
+    }
+
+    fn ub(&self, var: VarRef) -> IntCst {
+        self.get_bound(SignedVar::plus(var)).as_int()
+    }
+
+    fn lb(&self, var: VarRef) -> IntCst {
+        -self.get_bound(SignedVar::minus(var)).as_int()
+    }
+
+    /// Returns `Some(true)` if the variable is known to be present, `Some(false)` if it is known to be absent
+    /// and `None` if its presence is not determined yet.
+    fn present(&self, var: VarRef) -> Option<bool> {
+        let presence = self.presence(var);
+        if self.entails(presence) {
+            Some(true)
+        } else if self.entails(!presence) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl DomainsView for Domains {
+    fn get_bound(&self, var_bound: SignedVar) -> UpperBound {
+        Domains::get_bound(self, var_bound)
+    }
+
+    fn presence(&self, var: VarRef) -> Lit {
+        Domains::presence(self, var)
+    }
+
+    fn entails(&self, lit: Lit) -> bool {
+        Domains::entails(self, lit)
+    }
+}
+
+/// A view of the domains as they were when the trail only contained its first `num_events` events.
+///
+/// The snapshot does not modify the domains: each query follows the history of the bound backward
+/// from its current value (see [Domains::get_bound_at]).
+/// It is only meaningful as long as the domains are not backtracked beyond this position.
+#[derive(Copy, Clone)]
+pub struct DomainsSnapshot<'a> {
+    domains: &'a Domains,
+    num_events: u32,
+}
+
+impl<'a> DomainsSnapshot<'a> {
+    pub fn new(domains: &'a Domains, num_events: u32) -> Self {
+        debug_assert!(num_events <= domains.num_events());
+        DomainsSnapshot { domains, num_events }
+    }
+
+    /// Number of events of the trail that are visible in this snapshot.
+    pub fn num_events(&self) -> u32 {
+        self.num_events
+    }
+}
+
+impl DomainsView for DomainsSnapshot<'_> {
+    fn get_bound(&self, var_bound: SignedVar) -> UpperBound {
+        self.domains.get_bound_at(var_bound, self.num_events)
+    }
+
+    fn presence(&self, var: VarRef) -> Lit {
+        self.domains.presence(var)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backtrack::Backtrack;
+    use crate::core::state::*;
+
+    #[test]
+    fn test_snapshot() {
+        let mut domains = Domains::new();
+        let p = domains.new_var(0, 1).geq(1);
+        let a = domains.new_optional_var(0, 10, p);
+        let start = domains.num_events();
+        domains.set_lb(a, 3, Cause::Decision).unwrap();
+        domains.save_state();
+        let before_presence = domains.num_events();
+        domains.set(p, Cause::Decision).unwrap();
+        domains.set_ub(a, 5, Cause::Decision).unwrap();
+
+        let initial = domains.snapshot(start);
+        assert_eq!((initial.lb(a), initial.ub(a)), (0, 10));
+        assert_eq!(initial.present(a), None);
+
+        let past = domains.snapshot(before_presence);
+        assert_eq!((past.lb(a), past.ub(a)), (3, 10));
+        assert!(past.entails(a.geq(3)));
+        assert!(!past.entails(a.leq(5)));
+        assert_eq!(past.present(a), None);
+
+        let current = domains.snapshot(domains.num_events());
+        assert_eq!((current.lb(a), current.ub(a)), (3, 5));
+        assert_eq!(current.present(a), Some(true));
+        assert_eq!(DomainsView::present(&domains, a), Some(true));
+    }
+}
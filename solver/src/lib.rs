@@ -1,6 +1,8 @@
 pub mod backtrack;
 pub mod collections;
 pub mod core;
+#[cfg(feature = "bench")]
+pub mod microbench;
 pub mod model;
 pub mod reasoners;
 pub(crate) mod reif;
@@ -0,0 +1,128 @@
+//! Workloads for the micro-benchmarks of the core operations of the solver (see `benches/`),
+//! available with the `bench` feature.
+//!
+//! Benchmarks only rely on the API of this module, that is kept stable when the internals of the solver change,
+//! so that the same benchmarks can be run before and after a change to detect slowdowns of the propagation.
+use crate::backtrack::Backtrack;
+use crate::core::state::{Cause, Domains};
+use crate::core::{IntCst, Lit, VarRef};
+use crate::reasoners::stn::theory::Timepoint;
+use crate::reasoners::stn::Stn;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// An STN whose edges are all inactive at the root, and are activated one at a time by the benchmark.
+///
+/// Edges are delays `t_j >= t_i + d` with `i < j`, which ensures that the network remains consistent
+/// whatever the edges activated.
+pub struct StnWorkload {
+    stn: Stn,
+    timepoints: Vec<Timepoint>,
+    edges: Vec<Lit>,
+}
+
+impl StnWorkload {
+    /// Largest delay of an edge.
+    const MAX_DELAY: IntCst = 10;
+
+    fn new(num_timepoints: usize, delays: impl IntoIterator<Item = (usize, usize, IntCst)>) -> Self {
+        let horizon = Self::MAX_DELAY * num_timepoints as IntCst;
+        let mut stn = Stn::new();
+        let timepoints: Vec<Timepoint> = (0..num_timepoints).map(|_| stn.add_timepoint(0, horizon)).collect();
+        let edges = delays
+            .into_iter()
+            .map(|(i, j, delay)| stn.add_inactive_edge(timepoints[j], timepoints[i], -delay))
+            .collect();
+        stn.propagate_all().expect("Inconsistent network");
+        StnWorkload { stn, timepoints, edges }
+    }
+
+    /// A chain of `num_timepoints` timepoints, where each timepoint is at least one unit after the previous one.
+    pub fn chain(num_timepoints: usize) -> Self {
+        Self::new(num_timepoints, (1..num_timepoints).map(|j| (j - 1, j, 1)))
+    }
+
+    /// A network with `num_edges` delays between randomly selected timepoints.
+    pub fn random(num_timepoints: usize, num_edges: usize, seed: u64) -> Self {
+        assert!(num_timepoints >= 2);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let delays: Vec<_> = (0..num_edges)
+            .map(|_| {
+                let i = rng.gen_range(0..num_timepoints - 1);
+                let j = rng.gen_range(i + 1..num_timepoints);
+                (i, j, rng.gen_range(0..=Self::MAX_DELAY))
+            })
+            .collect();
+        Self::new(num_timepoints, delays)
+    }
+
+    /// Number of edges of the network, that are all activated by [StnWorkload::activate_all].
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Resets the network to the root, and activates all edges, each one in a new decision level and followed by
+    /// a propagation. Edges are activated in the reverse order of their creation: in a chain, each activation thus
+    /// updates all the following timepoints.
+    pub fn activate_all(&mut self) {
+        self.stn.reset_to_root();
+        for &edge in self.edges.iter().rev() {
+            self.stn.set_backtrack_point();
+            self.stn.mark_active(edge);
+            self.stn.propagate_all().expect("Inconsistent network");
+        }
+    }
+
+    /// Explains the lower bound of the latest timepoint by a clause on the activated edges, obtained by resolution
+    /// of the explanations of the network up to the first unique implication point.
+    /// Returns the number of literals in the clause.
+    ///
+    /// Must be called after [StnWorkload::activate_all].
+    pub fn explain_latest(&mut self) -> usize {
+        let domains = &self.stn.model.state;
+        let latest = self
+            .timepoints
+            .iter()
+            .copied()
+            .max_by_key(|&tp| domains.lb(tp))
+            .unwrap();
+        let lit = Lit::geq(latest, domains.lb(latest));
+        self.stn.explain_literal(lit).len()
+    }
+}
+
+/// A set of variables, whose bounds are repeatedly updated and restored.
+pub struct DomainsWorkload {
+    domains: Domains,
+    vars: Vec<VarRef>,
+}
+
+impl DomainsWorkload {
+    /// Upper bound of the domain of the variables.
+    const UB: IntCst = 1000;
+
+    pub fn new(num_vars: usize) -> Self {
+        let mut domains = Domains::new();
+        let vars = (0..num_vars).map(|_| domains.new_var(0, Self::UB)).collect();
+        DomainsWorkload { domains, vars }
+    }
+
+    /// Number of bound updates made by [DomainsWorkload::update_all].
+    pub fn num_updates(&self, rounds: usize) -> usize {
+        rounds * self.vars.len() * 2
+    }
+
+    /// In a new decision level, makes `rounds` rounds of updates of the lower and upper bounds of all variables,
+    /// before restoring the initial domains.
+    pub fn update_all(&mut self, rounds: usize) {
+        assert!((2 * rounds as IntCst) < Self::UB);
+        self.domains.save_state();
+        for round in 1..=rounds as IntCst {
+            for &var in &self.vars {
+                self.domains.set_lb(var, round, Cause::Decision).unwrap();
+                self.domains.set_ub(var, Self::UB - round, Cause::Decision).unwrap();
+            }
+        }
+        self.domains.restore_last();
+    }
+}
@@ -0,0 +1,399 @@
+//! Detection of equivalent literals from the binary implications that hold at the root, and their substitution
+//! in the constraints of a model.
+//!
+//! Encodings (e.g. of planning problems) often introduce many boolean variables that are pairwise equivalent,
+//! typically because the same condition is reified several times through different expressions.
+//! Each cycle of implications between literals is a set of equivalent literals, that are found as the
+//! strongly connected components of the implication graph.
+
+use crate::core::{Lit, VarRef};
+use crate::model::lang::Type;
+use crate::model::symmetry::{map_expr, map_lit};
+use crate::model::{Constraint, Label, Model};
+use crate::reif::ReifExpr;
+use std::collections::{HashMap, HashSet};
+
+/// Binary implications between literals that hold at the root.
+#[derive(Clone, Default)]
+pub struct ImplicationGraph {
+    succs: HashMap<Lit, Vec<Lit>>,
+}
+
+impl ImplicationGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the implication graph of the binary implications entailed by the constraints of the model.
+    ///
+    /// Implications between optional literals are only recorded if the literals have the same presence,
+    /// so that the literals of each equivalence class are always present together.
+    pub fn from_model<Lbl: Label>(model: &Model<Lbl>) -> Self {
+        let presence = |l: Lit| model.state.presence(l.variable());
+        let always_true = |l: Lit| model.state.entails(l) && presence(l) == Lit::TRUE;
+        let mut graph = Self::new();
+        for c in &model.shape.constraints {
+            let Constraint::Reified(expr, value) = c;
+            let value = *value;
+            match expr {
+                ReifExpr::Lit(l) if presence(*l) == presence(value) => {
+                    graph.add_implication(*l, value);
+                    graph.add_implication(value, *l);
+                }
+                ReifExpr::Or(lits) | ReifExpr::And(lits) => {
+                    let is_or = matches!(expr, ReifExpr::Or(_));
+                    if is_or && always_true(value) && lits.iter().all(|&l| presence(l) == Lit::TRUE) {
+                        graph.add_clause(lits);
+                    }
+                    for &l in lits {
+                        if presence(l) != presence(value) {
+                            continue;
+                        }
+                        // each disjunct implies the disjunction, each conjunct is implied by the conjunction
+                        if is_or {
+                            graph.add_implication(l, value);
+                        } else {
+                            graph.add_implication(value, l);
+                        }
+                        if lits.len() == 1 {
+                            graph.add_implication(value, l);
+                            graph.add_implication(l, value);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        graph
+    }
+
+    /// Records the implication `from => to`, together with its contrapositive `!to => !from`.
+    /// Implications involving a constant literal are ignored.
+    pub fn add_implication(&mut self, from: Lit, to: Lit) {
+        if from == to || from.variable() == VarRef::ZERO || to.variable() == VarRef::ZERO {
+            return;
+        }
+        self.succs.entry(from).or_default().push(to);
+        self.succs.entry(!to).or_default().push(!from);
+    }
+
+    /// Records a clause that holds at the root (e.g. a learnt clause).
+    /// Only binary clauses are recorded, as the two implications they are equivalent to.
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        if let &[a, b] = clause {
+            self.add_implication(!a, b);
+        }
+    }
+
+    /// Returns the sets of (at least two) equivalent literals, each sorted in increasing order.
+    ///
+    /// For each returned set, the set of the negations of its literals is also returned.
+    /// A set that contains both a literal and its negation denotes an inconsistency.
+    pub fn equivalence_classes(&self) -> Vec<Vec<Lit>> {
+        let no_succs = Vec::new();
+        let succs = |l: Lit| self.succs.get(&l).unwrap_or(&no_succs);
+        let mut search = SccSearch::default();
+        let mut classes = Vec::new();
+
+        let mut roots: Vec<Lit> = self.succs.keys().copied().collect();
+        roots.sort();
+        for root in roots {
+            if search.index.contains_key(&root) {
+                continue;
+            }
+            // each call frame is a literal with the index of its next successor to visit
+            let mut calls: Vec<(Lit, usize)> = vec![(root, 0)];
+            search.discover(root);
+            while let Some((l, next)) = calls.pop() {
+                if let Some(&succ) = succs(l).get(next) {
+                    calls.push((l, next + 1));
+                    if !search.index.contains_key(&succ) {
+                        search.discover(succ);
+                        calls.push((succ, 0));
+                    } else if search.on_stack.contains(&succ) {
+                        search.lower(l, search.index[&succ]);
+                    }
+                    continue;
+                }
+                // all successors of `l` were visited
+                if let Some(&(parent, _)) = calls.last() {
+                    search.lower(parent, search.lowlink[&l]);
+                }
+                if let Some(class) = search.component(l) {
+                    classes.push(class);
+                }
+            }
+        }
+        classes.sort();
+        classes
+    }
+}
+
+/// State of an iterative version of Tarjan's algorithm for strongly connected components.
+#[derive(Default)]
+struct SccSearch {
+    index: HashMap<Lit, usize>,
+    lowlink: HashMap<Lit, usize>,
+    stack: Vec<Lit>,
+    on_stack: HashSet<Lit>,
+}
+
+impl SccSearch {
+    fn discover(&mut self, l: Lit) {
+        let i = self.index.len();
+        self.index.insert(l, i);
+        self.lowlink.insert(l, i);
+        self.stack.push(l);
+        self.on_stack.insert(l);
+    }
+
+    fn lower(&mut self, l: Lit, low: usize) {
+        let current = self.lowlink.get_mut(&l).unwrap();
+        *current = (*current).min(low);
+    }
+
+    /// Once all successors of `l` are visited, returns its component if `l` is its root and it has
+    /// at least two literals.
+    fn component(&mut self, l: Lit) -> Option<Vec<Lit>> {
+        if self.lowlink[&l] != self.index[&l] {
+            return None;
+        }
+        let mut class = Vec::new();
+        loop {
+            let member = self.stack.pop().unwrap();
+            self.on_stack.remove(&member);
+            class.push(member);
+            if member == l {
+                break;
+            }
+        }
+        class.sort();
+        (class.len() > 1).then_some(class)
+    }
+}
+
+/// Merges the boolean variables of the model that are equivalent at the root, according to the
+/// binary implications of its constraints (see [ImplicationGraph::from_model]).
+///
+/// In each equivalence class, the boolean variables with the same presence are replaced by the smallest one in all
+/// constraints of the model. The replaced variables remain in the model, bound to their representative,
+/// so that they keep their value in solutions.
+/// Constraints that become trivial are removed, as well as duplicated constraints.
+///
+/// Returns the number of replaced variables.
+pub fn merge_equivalent_literals<Lbl: Label>(model: &mut Model<Lbl>) -> usize {
+    model.seal();
+    let is_bool_var =
+        |var: VarRef| model.shape.types.get(var) == Some(&Type::Bool) && model.state.bounds(var) == (0, 1);
+    let mut representatives: HashMap<VarRef, VarRef> = HashMap::new();
+    for class in ImplicationGraph::from_model(model).equivalence_classes() {
+        if class.iter().any(|l| class.contains(&!*l)) {
+            continue; // inconsistent, left for the solver to detect
+        }
+        // variables whose true literal is in the class, grouped by presence
+        let mut groups: HashMap<Lit, Vec<VarRef>> = HashMap::new();
+        for l in class {
+            let var = l.variable();
+            if is_bool_var(var) && l == var.geq(1) {
+                groups.entry(model.state.presence(var)).or_default().push(var);
+            }
+        }
+        for mut vars in groups.into_values() {
+            vars.sort();
+            for &var in &vars[1..] {
+                representatives.insert(var, vars[0]);
+            }
+        }
+    }
+    if representatives.is_empty() {
+        return 0;
+    }
+
+    let repr = |v: VarRef| representatives.get(&v).copied().unwrap_or(v);
+    let mut seen: HashSet<(ReifExpr, Lit)> = HashSet::new();
+    let mut constraints = Vec::with_capacity(model.shape.constraints.len());
+    for c in &model.shape.constraints {
+        let Constraint::Reified(expr, value) = c;
+        let mut mapped = map_expr(expr, repr);
+        match &mut mapped {
+            ReifExpr::Or(lits) | ReifExpr::And(lits) => lits.dedup(),
+            // the literals of a channel must remain on distinct variables
+            ReifExpr::Channel(ch)
+                if ch.lits.iter().map(|l| l.variable()).collect::<HashSet<_>>().len() < ch.lits.len() =>
+            {
+                mapped = expr.clone()
+            }
+            _ => {}
+        }
+        let value = map_lit(*value, repr);
+        if mapped == ReifExpr::Lit(value) || !seen.insert((mapped.clone(), value)) {
+            continue;
+        }
+        constraints.push(Constraint::Reified(mapped, value));
+    }
+    let mut merged: Vec<(VarRef, VarRef)> = representatives.into_iter().collect();
+    merged.sort();
+    for &(var, representative) in &merged {
+        constraints.push(Constraint::Reified(ReifExpr::Lit(representative.geq(1)), var.geq(1)));
+    }
+    model.shape.constraints = constraints;
+    merged.len()
+}
+
+/// Merges the scopes of the model that are equivalent at the root, where a scope is the presence literal of
+/// some optional variables.
+///
+/// Equivalences are detected from the binary implications of the constraints (see [ImplicationGraph::from_model]),
+/// from the literals required by constraints enforced in a scope, and from the implications between
+/// presence variables of the domains.
+/// In each equivalence class, all optional variables are made optional on the smallest scope, so that they are
+/// known to be present together. The other scopes are declared equivalent to it in the domains, and their presence
+/// variables are no longer the presence of any variable (they may then be replaced in constraints
+/// by [merge_equivalent_literals]).
+///
+/// Returns the number of replaced scopes.
+pub fn merge_equivalent_scopes<Lbl: Label>(model: &mut Model<Lbl>) -> usize {
+    model.seal();
+    let vars: Vec<VarRef> = model.state.variables().collect();
+    let scopes: HashSet<Lit> = vars
+        .iter()
+        .map(|&v| model.state.presence(v))
+        .filter(|&p| p != Lit::TRUE)
+        .collect();
+    if scopes.len() < 2 {
+        return 0;
+    }
+    let mut graph = ImplicationGraph::from_model(model);
+    for &v in &vars {
+        if model.state.presence(v) != Lit::TRUE || model.shape.types.get(v) != Some(&Type::Bool) {
+            continue;
+        }
+        for l in [v.geq(1), v.leq(0)] {
+            for implied in model.state.direct_implications_of(l) {
+                graph.add_implication(l, implied);
+            }
+        }
+    }
+
+    // constraints enforced in a scope (see [Model::enforce]) are reified by a literal that is true when present:
+    // each literal they require gives an implication from the scope (e.g., a task requires its only refinement)
+    for c in &model.shape.constraints {
+        let Constraint::Reified(expr, value) = c;
+        let scope = model.state.presence(value.variable());
+        if scope == Lit::TRUE || !model.state.entails(*value) {
+            continue;
+        }
+        let required: &[Lit] = match expr {
+            ReifExpr::Lit(l) => std::slice::from_ref(l),
+            ReifExpr::Or(lits) if lits.len() == 1 => lits,
+            ReifExpr::And(lits) => lits,
+            _ => &[],
+        };
+        for &l in required {
+            if model.state.presence(l.variable()) == Lit::TRUE {
+                graph.add_implication(scope, l);
+            }
+        }
+    }
+
+    let mut representatives: HashMap<Lit, Lit> = HashMap::new();
+    for class in graph.equivalence_classes() {
+        if class.iter().any(|l| class.contains(&!*l)) {
+            continue; // inconsistent, left for the solver to detect
+        }
+        let mut class_scopes = class.iter().filter(|l| scopes.contains(l));
+        if let Some(&representative) = class_scopes.next() {
+            for &scope in class_scopes {
+                let value = |l: Lit| model.state.value(l);
+                if value(scope).is_some() && value(representative).is_some() && value(scope) != value(representative) {
+                    continue; // inconsistent, left for the solver to detect
+                }
+                representatives.insert(scope, representative);
+            }
+        }
+    }
+    for &v in &vars {
+        if let Some(&representative) = representatives.get(&model.state.presence(v)) {
+            model.state.set_presence(v, representative);
+        }
+    }
+    // make the equivalences known to the domains, so that variables that were only known to be present
+    // with (or without) a merged scope are also known to be with its representative
+    let mut merged: Vec<(Lit, Lit)> = representatives.into_iter().collect();
+    merged.sort();
+    for &(scope, representative) in &merged {
+        model.state.add_implication(scope, representative);
+        model.state.add_implication(representative, scope);
+    }
+    merged.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::extensions::AssignmentExt;
+    use crate::model::lang::expr::{eq, implies, leq, or};
+    use crate::model::lang::IVar;
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    #[test]
+    fn equivalent_literals() {
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let a = model.new_bvar("a").true_lit();
+        let b = model.new_bvar("b").true_lit();
+        let c = model.new_bvar("c").true_lit();
+        let d = model.new_bvar("d").true_lit();
+        // a => b => c => a
+        model.enforce(implies(a, b), []);
+        model.enforce(implies(b, c), []);
+        model.enforce(implies(c, a), []);
+        // d <=> !a
+        model.bind(or([!a]), d);
+        model.bind(leq(x, 5), b);
+
+        let graph = ImplicationGraph::from_model(&model);
+        assert!(graph.equivalence_classes().contains(&vec![Lit::leq(x, 5), a, b, c, !d]));
+        assert_eq!(merge_equivalent_literals(&mut model), 2);
+
+        for x_value in [3, 7] {
+            let mut model = model.clone();
+            model.enforce(eq(x, x_value), []);
+            let mut solver = Solver::new(model);
+            let sol = solver.solve().unwrap().unwrap();
+            let value = |l: Lit| sol.entails(l);
+            assert_eq!(value(a), x_value <= 5);
+            assert!(value(a) == value(b) && value(b) == value(c) && value(c) != value(d));
+        }
+    }
+
+    #[test]
+    fn equivalent_scopes() {
+        let mut model = Model::new();
+        let task = model.new_presence_variable(Lit::TRUE, "task").true_lit();
+        let method = model.new_presence_variable(task, "method").true_lit();
+        let other = model.new_presence_variable(Lit::TRUE, "other").true_lit();
+        // the task is refined by its only method
+        model.enforce(or([method]), [task]);
+        let x = model.new_optional_ivar(0, 10, task, "x");
+        let y = model.new_optional_ivar(0, 10, method, "y");
+        let z = model.new_optional_ivar(0, 10, other, "z");
+        model.enforce(eq(x, y), [task, method]);
+        let present_together =
+            |model: &Model, a: IVar, b: IVar| model.state.always_present_together(a.into(), b.into());
+        assert!(!present_together(&model, x, y));
+
+        assert_eq!(merge_equivalent_scopes(&mut model), 1);
+        assert!(present_together(&model, x, y));
+        assert!(!present_together(&model, x, z));
+        assert!(model.state.implies(task, method) && model.state.implies(method, task));
+
+        model.enforce(method, []);
+        model.enforce(eq(y, 4), [method]);
+        let mut solver = Solver::new(model);
+        let sol = solver.solve().unwrap().unwrap();
+        assert_eq!(sol.var_domain(x).lb, 4);
+    }
+}
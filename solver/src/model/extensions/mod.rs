@@ -4,6 +4,7 @@
 //! - [DisjunctionExt] allows querying the value of a disjunction,
 //! whether it is currently unit, ...
 //! - [AssignmentExt] provides methods to query the value of expressions.
+//! - [SavedAssignment::project_to_decisions] extracts the values of the decision variables of a solution.
 
 mod assignments;
 mod disjunction;
@@ -15,6 +16,7 @@ pub use format::*;
 
 use crate::core::state::{Domains, IntDomain};
 use crate::core::*;
+use crate::model::label::Label;
 use crate::model::lang::IAtom;
 
 pub trait PartialAssignment {
@@ -60,3 +62,33 @@ impl AssignmentExt for SavedAssignment {
         todo!()
     }
 }
+
+/// Value of a decision variable in a solution, as given by [SavedAssignment::project_to_decisions].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecisionValue<Lbl> {
+    pub var: VarRef,
+    pub label: Option<Lbl>,
+    /// Value of the variable, or `None` if the variable is absent.
+    pub value: Option<IntCst>,
+}
+
+impl SavedAssignment {
+    /// Returns the values of the variables tagged as decisions in the model, ignoring all auxiliary variables
+    /// (see [Model::tag_decision](crate::model::Model::tag_decision)).
+    ///
+    /// The assignment is expected to be a solution, where all present variables are fixed.
+    pub fn project_to_decisions<Lbl: Label>(&self, model: &impl Shaped<Lbl>) -> Vec<DecisionValue<Lbl>> {
+        model
+            .get_shape()
+            .decision_variables()
+            .map(|var| DecisionValue {
+                var,
+                label: model.get_label(var).cloned(),
+                value: match self.present(var) {
+                    Some(true) => Some(self.lb(var)),
+                    _ => None,
+                },
+            })
+            .collect()
+    }
+}
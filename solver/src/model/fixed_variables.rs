@@ -0,0 +1,193 @@
+//! Elimination of the variables that are fixed at the root, by substituting their value in the constraints of a model.
+//!
+//! Encodings (e.g. of planning problems) typically create many variables whose value is fixed once the constraints
+//! are propagated at the root: parameters of chronicles with a single possible value, timepoints of the initial state...
+//! Replacing them by constants shrinks the constraints posted to the reasoners, e.g. a difference constraint with a
+//! fixed extremity becomes a bound on the other one instead of an edge of the STN.
+
+use crate::core::state::Cause;
+use crate::core::{IntCst, Lit, VarRef};
+use crate::model::lang::linear::NFLinearLeq;
+use crate::model::{Constraint, Label, Model};
+use crate::reif::ReifExpr;
+use crate::solver::Solver;
+use std::collections::{HashMap, HashSet};
+
+/// Returns the non-optional variables that are fixed once the constraints of the model are propagated at the root,
+/// together with their value, or `None` if propagation proves the model inconsistent.
+///
+/// Variables whose presence is only entailed are ignored, as their scope must remain known to be consistent with the
+/// one of the expressions they appear in.
+fn fixed_at_root<Lbl: Label>(model: &Model<Lbl>) -> Option<HashMap<VarRef, IntCst>> {
+    let mut solver = Solver::new(model.clone());
+    solver.propagate().ok()?;
+    let state = &solver.model.state;
+    let fixed = model
+        .state
+        .variables()
+        .filter(|&v| v != VarRef::ZERO && state.presence(v) == Lit::TRUE && state.lb(v) == state.ub(v))
+        .map(|v| (v, state.lb(v)))
+        .collect();
+    Some(fixed)
+}
+
+/// Replaces the variables of the model that are fixed by the propagation of its constraints at the root
+/// (non-optional variables with a single value in their domain) by their value in all constraints of the model.
+///
+/// Literals on fixed variables are replaced by `true` or `false`, and disjunctions, conjunctions, difference
+/// constraints and linear sums are simplified accordingly. Other expressions are kept unchanged.
+/// Constraints that become trivially satisfied are removed, as well as duplicated constraints.
+/// The eliminated variables remain in the model with their label, and their domain is reduced to their value
+/// so that they keep it in solutions. The domains of the other variables are left unchanged.
+///
+/// Returns the number of eliminated variables. Nothing is done if the model is proven inconsistent at the root,
+/// which is left for the solver to report.
+pub fn eliminate_fixed_variables<Lbl: Label>(model: &mut Model<Lbl>) -> usize {
+    model.seal();
+    let Some(values) = fixed_at_root(model) else {
+        return 0;
+    };
+    for (&v, &value) in &values {
+        let fixed = model.state.set_lb(v, value, Cause::Decision).is_ok()
+            && model.state.set_ub(v, value, Cause::Decision).is_ok();
+        debug_assert!(fixed);
+    }
+    let state = &model.state;
+    let fixed = |v: VarRef| values.contains_key(&v);
+    let mut eliminated: HashSet<VarRef> = HashSet::new();
+
+    let mut seen: HashSet<(ReifExpr, Lit)> = HashSet::new();
+    let mut constraints = Vec::with_capacity(model.shape.constraints.len());
+    for c in &model.shape.constraints {
+        let Constraint::Reified(expr, value) = c;
+        let mut substitute_lit = |l: Lit| {
+            let var = l.variable();
+            if fixed(var) {
+                eliminated.insert(var);
+                if state.entails(l) {
+                    Lit::TRUE
+                } else {
+                    Lit::FALSE
+                }
+            } else {
+                l
+            }
+        };
+        let value = substitute_lit(*value);
+        let mapped = match expr {
+            ReifExpr::Lit(l) => ReifExpr::Lit(substitute_lit(*l)),
+            ReifExpr::Or(lits) => {
+                let lits: Vec<Lit> = lits.iter().map(|&l| substitute_lit(l)).collect();
+                if lits.contains(&Lit::TRUE) {
+                    ReifExpr::Lit(Lit::TRUE)
+                } else {
+                    ReifExpr::Or(lits.into_iter().filter(|&l| l != Lit::FALSE).collect())
+                }
+            }
+            ReifExpr::And(lits) => {
+                let lits: Vec<Lit> = lits.iter().map(|&l| substitute_lit(l)).collect();
+                if lits.contains(&Lit::FALSE) {
+                    ReifExpr::Lit(Lit::FALSE)
+                } else {
+                    ReifExpr::And(lits.into_iter().filter(|&l| l != Lit::TRUE).collect())
+                }
+            }
+            ReifExpr::MaxDiff(diff) => match (fixed(diff.b), fixed(diff.a)) {
+                (false, false) => expr.clone(),
+                (true, true) => {
+                    eliminated.extend([diff.b, diff.a]);
+                    let holds = state.lb(diff.b) - state.lb(diff.a) <= diff.ub;
+                    ReifExpr::Lit(if holds { Lit::TRUE } else { Lit::FALSE })
+                }
+                (true, false) => {
+                    // b - a <= ub  <=>  a >= b - ub
+                    eliminated.insert(diff.b);
+                    ReifExpr::Lit(diff.a.geq(state.lb(diff.b) - diff.ub))
+                }
+                (false, true) => {
+                    // b - a <= ub  <=>  b <= a + ub
+                    eliminated.insert(diff.a);
+                    ReifExpr::Lit(diff.b.leq(state.lb(diff.a) + diff.ub))
+                }
+            },
+            ReifExpr::Linear(lin) => {
+                let mut upper_bound = lin.upper_bound;
+                let mut sum = Vec::with_capacity(lin.sum.len());
+                for item in &lin.sum {
+                    if fixed(item.var) {
+                        eliminated.insert(item.var);
+                        upper_bound -= item.factor * state.lb(item.var);
+                    } else {
+                        sum.push(*item);
+                    }
+                }
+                if sum.is_empty() {
+                    ReifExpr::Lit(if 0 <= upper_bound { Lit::TRUE } else { Lit::FALSE })
+                } else {
+                    ReifExpr::Linear(NFLinearLeq { sum, upper_bound })
+                }
+            }
+            _ => expr.clone(),
+        };
+        let trivial = match mapped {
+            ReifExpr::Lit(l) => l == value,
+            _ => false,
+        };
+        if trivial || !seen.insert((mapped.clone(), value)) {
+            continue;
+        }
+        constraints.push(Constraint::Reified(mapped, value));
+    }
+    model.shape.constraints = constraints;
+    eliminated.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtrack::Backtrack;
+    use crate::model::extensions::AssignmentExt;
+    use crate::model::lang::expr::{leq, or};
+    use crate::model::lang::linear::LinearSum;
+
+    fn fixed_value(model: &Model<&'static str>, v: VarRef) -> Option<IntCst> {
+        let (lb, ub) = model.state.bounds(v);
+        (lb == ub).then_some(lb)
+    }
+
+    #[test]
+    fn test_fixed_variables() {
+        let mut model: Model<&'static str> = Model::new();
+        let x = model.new_ivar(0, 10, "x");
+        let y = model.new_ivar(0, 10, "y");
+        let z = model.new_ivar(0, 10, "z");
+        model.enforce(leq(x, 3), []);
+        model.enforce(leq(3, x), []);
+        // becomes y >= 5
+        model.enforce(leq(x + 2, y), []);
+        // becomes z <= 4
+        model.enforce(or([x.leq(2), z.leq(4)]), []);
+        // becomes y + z <= 9
+        model.enforce((LinearSum::from(x) + y + z).leq(12), []);
+        let num_constraints = model.shape.constraints.len();
+
+        assert_eq!(fixed_value(&model, x.into()), None);
+        assert_eq!(eliminate_fixed_variables(&mut model), 1);
+        assert_eq!(fixed_value(&model, x.into()), Some(3));
+        // the domains of other variables are left unchanged
+        assert_eq!(fixed_value(&model, y.into()), None);
+        assert!(model.shape.constraints.len() < num_constraints);
+        let dump = model.dump_pretty(None);
+        let (_, constraints) = dump.split_once("===== Constraints").unwrap();
+        assert!(!constraints.contains('x'), "{dump}");
+
+        // the simplified model has the same solutions
+        let mut solver = Solver::new(model);
+        let (cost, sol) = solver.minimize(y).unwrap().unwrap();
+        assert_eq!(cost, 5);
+        assert_eq!(sol.var_domain(x).lb, 3);
+        solver.reset();
+        let (cost, _) = solver.maximize(z).unwrap().unwrap();
+        assert_eq!(cost, 4);
+    }
+}
@@ -45,6 +45,14 @@ impl<Lbl> VariableLabels<Lbl> {
     {
         self.labeled_variables.get(label).map(|v| v.as_ref()).unwrap_or(&[])
     }
+
+    /// Iterates over all labels, together with the variables that carry them.
+    pub fn iter(&self) -> impl Iterator<Item = (&Lbl, &[VarRef])> + '_ {
+        self.labeled_variables
+            .iter()
+            .filter(|(_, vars)| !vars.is_empty())
+            .map(|(label, vars)| (label.as_ref(), vars.as_slice()))
+    }
 }
 
 impl<Lbl> Default for VariableLabels<Lbl> {
@@ -1,11 +1,17 @@
+pub mod arith;
 mod atom;
 mod boolean;
+pub mod channel;
 pub mod expr;
 mod fixed;
 mod int;
+pub mod interval;
 pub mod linear;
+pub mod max;
 pub mod reification;
+pub mod soft;
 mod sym;
+pub mod typing;
 mod validity_scope;
 mod variables;
 
@@ -13,6 +19,7 @@ pub use atom::Atom;
 pub use boolean::BVar;
 pub use fixed::{FAtom, FVar};
 pub use int::{IAtom, IVar};
+pub use interval::IntervalVar;
 pub use validity_scope::*;
 
 use crate::core::IntCst;
@@ -0,0 +1,146 @@
+use crate::core::{IntCst, Lit, VarRef};
+use crate::model::lang::{IAtom, ValidityScope};
+use crate::reif::ReifExpr;
+
+/// Checks that the two atoms are on distinct variables (unless they are constants).
+///
+/// Propagators rely on each variable having a single role in the constraint to produce explanations.
+fn assert_distinct(lhs: IAtom, arg: IAtom) {
+    assert!(
+        lhs.var != arg.var || VarRef::from(lhs.var) == VarRef::ZERO,
+        "The same variable appears on both sides of an arithmetic constraint"
+    );
+}
+
+fn scope(lhs: IAtom, arg: IAtom, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+    ValidityScope::new([presence(lhs.var.into()), presence(arg.var.into())], [])
+}
+
+/// Constraint `lhs = |arg|`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EqAbs {
+    pub lhs: IAtom,
+    pub arg: IAtom,
+}
+
+impl EqAbs {
+    pub fn new(lhs: impl Into<IAtom>, arg: impl Into<IAtom>) -> EqAbs {
+        let (lhs, arg) = (lhs.into(), arg.into());
+        assert_distinct(lhs, arg);
+        EqAbs { lhs, arg }
+    }
+
+    pub(crate) fn validity_scope(&self, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+        scope(self.lhs, self.arg, presence)
+    }
+}
+
+impl From<EqAbs> for ReifExpr {
+    fn from(value: EqAbs) -> Self {
+        ReifExpr::EqAbs(value)
+    }
+}
+
+/// Constraint `lhs = numerator / denominator` where `denominator` is a non-zero constant.
+///
+/// The division is truncated toward zero, as with Rust's `/` operator or FlatZinc's `int_div`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EqDiv {
+    pub lhs: IAtom,
+    pub numerator: IAtom,
+    pub denominator: IntCst,
+}
+
+impl EqDiv {
+    pub fn new(lhs: impl Into<IAtom>, numerator: impl Into<IAtom>, denominator: IntCst) -> EqDiv {
+        let (lhs, numerator) = (lhs.into(), numerator.into());
+        assert_ne!(denominator, 0, "Division by zero");
+        assert_distinct(lhs, numerator);
+        EqDiv {
+            lhs,
+            numerator,
+            denominator,
+        }
+    }
+
+    pub(crate) fn validity_scope(&self, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+        scope(self.lhs, self.numerator, presence)
+    }
+}
+
+impl From<EqDiv> for ReifExpr {
+    fn from(value: EqDiv) -> Self {
+        ReifExpr::EqDiv(value)
+    }
+}
+
+/// Constraint `lhs = numerator % denominator` where `denominator` is a non-zero constant.
+///
+/// The remainder has the sign of the numerator, as with Rust's `%` operator or FlatZinc's `int_mod`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EqMod {
+    pub lhs: IAtom,
+    pub numerator: IAtom,
+    pub denominator: IntCst,
+}
+
+impl EqMod {
+    pub fn new(lhs: impl Into<IAtom>, numerator: impl Into<IAtom>, denominator: IntCst) -> EqMod {
+        let (lhs, numerator) = (lhs.into(), numerator.into());
+        assert_ne!(denominator, 0, "Division by zero");
+        assert_distinct(lhs, numerator);
+        EqMod {
+            lhs,
+            numerator,
+            denominator,
+        }
+    }
+
+    pub(crate) fn validity_scope(&self, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+        scope(self.lhs, self.numerator, presence)
+    }
+}
+
+impl From<EqMod> for ReifExpr {
+    fn from(value: EqMod) -> Self {
+        ReifExpr::EqMod(value)
+    }
+}
+
+/// Constraint `lhs = x * y`, where either factor may be a constant (multiplication by a constant) or a variable
+/// (bilinear case).
+///
+/// Products are computed with extended precision, bounds that are not representable being weakened
+/// to the closest representable value.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EqMul {
+    pub lhs: IAtom,
+    pub x: IAtom,
+    pub y: IAtom,
+}
+
+impl EqMul {
+    pub fn new(lhs: impl Into<IAtom>, x: impl Into<IAtom>, y: impl Into<IAtom>) -> EqMul {
+        let (lhs, x, y) = (lhs.into(), x.into(), y.into());
+        assert_distinct(lhs, x);
+        assert_distinct(lhs, y);
+        EqMul { lhs, x, y }
+    }
+
+    pub(crate) fn validity_scope(&self, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+        ValidityScope::new(
+            [
+                presence(self.lhs.var.into()),
+                presence(self.x.var.into()),
+                presence(self.y.var.into()),
+            ],
+            [],
+        )
+    }
+}
+
+impl From<EqMul> for ReifExpr {
+    fn from(value: EqMul) -> Self {
+        ReifExpr::EqMul(value)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::core::{IntCst, Lit, VarRef};
+use crate::model::lang::{IAtom, ValidityScope};
+use crate::reif::ReifExpr;
+
+/// Constraint channeling an integer atom with the literals of its values: `var = first + i <=> lits[i]`.
+///
+/// The atom takes one of the channeled values, i.e., `first <= var < first + lits.len()` and
+/// exactly one of the literals is true.
+/// This provides a boolean view of the integer (e.g. for table constraints or for the branching on literals)
+/// that is maintained natively, instead of reifying each equality separately.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Channel {
+    pub var: IAtom,
+    pub first: IntCst,
+    pub lits: Vec<Lit>,
+}
+
+impl Channel {
+    pub fn new(var: impl Into<IAtom>, first: IntCst, lits: impl IntoIterator<Item = Lit>) -> Channel {
+        let var = var.into();
+        let lits: Vec<Lit> = lits.into_iter().collect();
+        assert!(!lits.is_empty(), "A channeled variable must have at least one value");
+        // the propagator identifies the literal of an inference by its variable
+        let mut vars: Vec<VarRef> = lits.iter().map(|l| l.variable()).collect();
+        vars.sort();
+        vars.dedup();
+        assert_eq!(
+            vars.len(),
+            lits.len(),
+            "The literals of a channel must be on distinct variables"
+        );
+        assert!(
+            !vars.contains(&var.var.into()) && !vars.contains(&VarRef::ZERO),
+            "The literals of a channel must be on variables distinct from the channeled one"
+        );
+        Channel { var, first, lits }
+    }
+
+    /// Value of the atom denoted by the `i`-th literal.
+    pub fn value(&self, i: usize) -> IntCst {
+        self.first + i as IntCst
+    }
+
+    pub(crate) fn validity_scope(&self, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+        ValidityScope::new(
+            std::iter::once(presence(self.var.var.into())).chain(self.lits.iter().map(|l| presence(l.variable()))),
+            [],
+        )
+    }
+}
+
+impl From<Channel> for ReifExpr {
+    fn from(value: Channel) -> Self {
+        ReifExpr::Channel(value)
+    }
+}
@@ -1,5 +1,9 @@
 use crate::core::literals::Disjunction;
 use crate::core::*;
+use crate::model::lang::arith::{EqAbs, EqDiv, EqMod, EqMul};
+use crate::model::lang::channel::Channel;
+use crate::model::lang::max::{EqMax, EqMin};
+use crate::model::lang::typing::check_same_kind;
 use crate::model::lang::{Atom, FAtom, IAtom};
 use crate::model::{Label, Model};
 use crate::reif::{DifferenceExpression, ReifExpr, Reifiable};
@@ -36,17 +40,56 @@ pub fn f_lt(lhs: impl Into<FAtom>, rhs: impl Into<FAtom>) -> Leq {
 pub fn eq(lhs: impl Into<Atom>, rhs: impl Into<Atom>) -> Eq {
     let lhs = lhs.into();
     let rhs = rhs.into();
-    assert_eq!(lhs.kind(), rhs.kind());
+    if let Err(e) = check_same_kind("=", lhs, rhs) {
+        panic!("{e}")
+    }
     Eq(lhs, rhs)
 }
 
 pub fn neq(lhs: impl Into<Atom>, rhs: impl Into<Atom>) -> Neq {
     let lhs = lhs.into();
     let rhs = rhs.into();
-    assert_eq!(lhs.kind(), rhs.kind());
+    if let Err(e) = check_same_kind("!=", lhs, rhs) {
+        panic!("{e}")
+    }
     Neq(lhs, rhs)
 }
 
+/// Constraint `lhs = max(rhs)`
+pub fn eq_max<T: Into<IAtom>>(lhs: impl Into<IAtom>, rhs: impl IntoIterator<Item = T>) -> EqMax {
+    EqMax::new(lhs, rhs)
+}
+
+/// Constraint `lhs = min(rhs)`
+pub fn eq_min<T: Into<IAtom>>(lhs: impl Into<IAtom>, rhs: impl IntoIterator<Item = T>) -> EqMin {
+    EqMin::new(lhs, rhs)
+}
+
+/// Constraint `var = first + i <=> lits[i]`, where `var` takes one of the values denoted by the literals.
+pub fn channel(var: impl Into<IAtom>, first: IntCst, lits: impl IntoIterator<Item = Lit>) -> Channel {
+    Channel::new(var, first, lits)
+}
+
+/// Constraint `lhs = |arg|`
+pub fn eq_abs(lhs: impl Into<IAtom>, arg: impl Into<IAtom>) -> EqAbs {
+    EqAbs::new(lhs, arg)
+}
+
+/// Constraint `lhs = x * y`, where each factor is either a variable or a constant.
+pub fn eq_mul(lhs: impl Into<IAtom>, x: impl Into<IAtom>, y: impl Into<IAtom>) -> EqMul {
+    EqMul::new(lhs, x, y)
+}
+
+/// Constraint `lhs = numerator / denominator`, with the division truncated toward zero.
+pub fn eq_div(lhs: impl Into<IAtom>, numerator: impl Into<IAtom>, denominator: IntCst) -> EqDiv {
+    EqDiv::new(lhs, numerator, denominator)
+}
+
+/// Constraint `lhs = numerator % denominator`, where the remainder has the sign of the numerator.
+pub fn eq_mod(lhs: impl Into<IAtom>, numerator: impl Into<IAtom>, denominator: IntCst) -> EqMod {
+    EqMod::new(lhs, numerator, denominator)
+}
+
 pub fn or(disjuncts: impl Into<Box<[Lit]>>) -> Or {
     Or(disjuncts.into())
 }
@@ -0,0 +1,145 @@
+use crate::core::{IntCst, Lit};
+use crate::model::lang::expr::{and, eq, f_leq, f_lt, Leq};
+use crate::model::lang::linear::LinearSum;
+use crate::model::lang::{FAtom, IVar};
+use crate::model::{Label, Model};
+use crate::reif::{ReifExpr, Reifiable};
+
+/// An interval `[start, end]` of the given `duration` that only exists if `presence` is true.
+///
+/// The three timepoints are fixed-point atoms sharing the same denominator.
+/// An interval var is typically created with [Model::new_optional_interval_var] that also enforces
+/// its [consistency](IntervalVar::enforce_consistency), i.e., `start + duration = end`.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct IntervalVar {
+    pub start: FAtom,
+    pub duration: FAtom,
+    pub end: FAtom,
+    pub presence: Lit,
+}
+
+impl IntervalVar {
+    pub fn new(start: FAtom, duration: FAtom, end: FAtom, presence: Lit) -> IntervalVar {
+        assert!(
+            start.denom == duration.denom && start.denom == end.denom,
+            "The timepoints of an interval must have the same denominator"
+        );
+        IntervalVar {
+            start,
+            duration,
+            end,
+            presence,
+        }
+    }
+
+    pub fn denom(&self) -> IntCst {
+        self.start.denom
+    }
+
+    /// Enforces the consistency of the interval (`start + duration = end`) whenever it is present.
+    ///
+    /// A constant duration is encoded as a difference constraint. A variable duration requires a linear constraint,
+    /// in which case the three timepoints must be variables (without any offset) that are all absent with the interval.
+    pub fn enforce_consistency<Lbl: Label>(&self, model: &mut Model<Lbl>) {
+        let (start, duration, end) = (self.start.num, self.duration.num, self.end.num);
+        if duration.var == IVar::ZERO {
+            let end_from_start = FAtom::new(start + duration.shift, self.denom());
+            model.enforce(eq(self.end, end_from_start), [self.presence]);
+        } else {
+            assert!(
+                start.shift == 0 && duration.shift == 0 && end.shift == 0,
+                "Interval with a variable duration should be made of pure variables"
+            );
+            // start + duration - end = 0, where each term is zero when the interval is absent
+            let sum = LinearSum::of(vec![start.var.or_zero(), duration.var.or_zero(), -end.var.or_zero()]);
+            model.enforce(sum.clone().leq(0), []);
+            model.enforce(sum.geq(0), []);
+        }
+    }
+}
+
+/// Constraint enforcing that the interval `a` ends before the interval `b` starts.
+///
+/// The constraint is only meaningful when both intervals are present, and should thus typically be
+/// enforced with the scope `[a.presence, b.presence]`.
+pub fn end_before_start(a: IntervalVar, b: IntervalVar) -> Leq {
+    f_leq(a.end, b.start)
+}
+
+/// Expression that is true if the two intervals have a non-empty intersection,
+/// i.e., each interval starts strictly before the end of the other one.
+///
+/// As for [end_before_start], the expression is only defined when both intervals are present.
+pub fn overlaps(a: IntervalVar, b: IntervalVar) -> Overlaps {
+    assert_eq!(a.denom(), b.denom());
+    Overlaps(a, b)
+}
+
+/// Expression that is true if two intervals overlap (see [overlaps]).
+#[derive(Copy, Clone, Debug)]
+pub struct Overlaps(IntervalVar, IntervalVar);
+
+impl<Lbl: Label> Reifiable<Lbl> for Overlaps {
+    fn decompose(self, model: &mut Model<Lbl>) -> ReifExpr {
+        let (a, b) = (self.0, self.1);
+        let a_first = model.reify(f_lt(a.start, b.end));
+        let b_first = model.reify(f_lt(b.start, a.end));
+        and([a_first, b_first]).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::extensions::AssignmentExt;
+    use crate::model::lang::IAtom;
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    #[test]
+    fn interval_variables() {
+        let mut model = Model::new();
+        let p = model.new_presence_variable(Lit::TRUE, "p").true_lit();
+        let a = model.new_optional_interval_var((0, 20), (3, 5), 1, Lit::TRUE, "a");
+        let b = model.new_optional_interval_var((0, 20), (4, 6), 1, p, "b");
+        let c = model.new_optional_interval_var((0, 20), (2, 6), 1, Lit::TRUE, "c");
+
+        model.enforce(p, []);
+        model.enforce(end_before_start(a, b), [a.presence, b.presence]);
+        model.enforce(overlaps(a, c), [a.presence, c.presence]);
+        model.enforce(f_leq(FAtom::new(IAtom::from(10), 1), c.start), []);
+
+        let mut solver = Solver::new(model.clone());
+        // `a` must end after 10 to overlap with `c`, and `b` takes at least 4 time units after `a`
+        let (makespan, sol) = solver.minimize(b.end.num).unwrap().unwrap();
+        assert_eq!(makespan, 15);
+        for itv in [a, b, c] {
+            let (start, duration, end) = (
+                sol.domain_of(itv.start.num).0,
+                sol.domain_of(itv.duration.num).0,
+                sol.domain_of(itv.end.num).0,
+            );
+            assert_eq!(start + duration, end);
+        }
+        assert!(sol.domain_of(a.end.num).0 >= 11);
+
+        // `a` cannot both overlap and precede `b`
+        model.enforce(overlaps(a, b), [a.presence, b.presence]);
+        let mut solver = Solver::new(model);
+        assert!(solver.solve().unwrap().is_none());
+    }
+
+    #[test]
+    fn constant_duration() {
+        let mut model = Model::new();
+        let a = model.new_optional_interval_var((0, 20), (4, 4), 1, Lit::TRUE, "a");
+        // no variable is created for a constant duration
+        assert_eq!(a.duration, FAtom::new(IAtom::from(4), 1));
+        model.enforce(f_leq(FAtom::new(IAtom::from(7), 1), a.start), []);
+
+        let mut solver = Solver::new(model);
+        let (end, _) = solver.minimize(a.end.num).unwrap().unwrap();
+        assert_eq!(end, 11);
+    }
+}
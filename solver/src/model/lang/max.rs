@@ -0,0 +1,126 @@
+use crate::core::{IntCst, Lit, SignedVar, VarRef};
+use crate::model::lang::{IAtom, ValidityScope};
+use crate::reif::ReifExpr;
+use std::collections::BTreeMap;
+
+/// Constraint equivalent to `lhs = max { e | e \in rhs }`.
+///
+/// Elements of `rhs` may be optional, in which case they only participate in the maximum if present.
+/// At least one element of `rhs` must be present for the constraint to be satisfied.
+#[derive(Clone, Debug)]
+pub struct EqMax {
+    lhs: IAtom,
+    rhs: Vec<IAtom>,
+}
+
+impl EqMax {
+    pub fn new<T: Into<IAtom>>(lhs: impl Into<IAtom>, rhs: impl IntoIterator<Item = T>) -> EqMax {
+        EqMax {
+            lhs: lhs.into(),
+            rhs: rhs.into_iter().map(|t| t.into()).collect(),
+        }
+    }
+}
+
+/// Constraint equivalent to `lhs = min { e | e \in rhs }`.
+///
+/// Elements of `rhs` may be optional, in which case they only participate in the minimum if present.
+/// At least one element of `rhs` must be present for the constraint to be satisfied.
+#[derive(Clone, Debug)]
+pub struct EqMin {
+    lhs: IAtom,
+    rhs: Vec<IAtom>,
+}
+
+impl EqMin {
+    pub fn new<T: Into<IAtom>>(lhs: impl Into<IAtom>, rhs: impl IntoIterator<Item = T>) -> EqMin {
+        EqMin {
+            lhs: lhs.into(),
+            rhs: rhs.into_iter().map(|t| t.into()).collect(),
+        }
+    }
+}
+
+/// A term `var + cst` where `var` is a signed view of a variable.
+/// This allows representing both `x + c` and `-x + c`.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub struct NFEqMaxItem {
+    pub var: SignedVar,
+    pub cst: IntCst,
+}
+
+impl NFEqMaxItem {
+    fn positive(atom: IAtom) -> NFEqMaxItem {
+        NFEqMaxItem {
+            var: SignedVar::plus(atom.var.into()),
+            cst: atom.shift,
+        }
+    }
+
+    fn negative(atom: IAtom) -> NFEqMaxItem {
+        NFEqMaxItem {
+            var: SignedVar::minus(atom.var.into()),
+            cst: -atom.shift,
+        }
+    }
+}
+
+/// Normal form of a max constraint `lhs = max { e | e \in rhs }`
+/// where both the left and right hand side are signed views of variables.
+///
+/// A min constraint is represented by negating all its terms: `min(x_i) = y <=> max(-x_i) = -y`.
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+pub struct NFEqMax {
+    pub lhs: NFEqMaxItem,
+    /// Elements of the maximum, sorted and with at most one element per signed variable.
+    pub rhs: Vec<NFEqMaxItem>,
+}
+
+impl NFEqMax {
+    fn new(lhs: NFEqMaxItem, rhs: impl IntoIterator<Item = NFEqMaxItem>) -> NFEqMax {
+        // only keep the largest constant for each signed variable, as the others can never be the maximum
+        let mut elems: BTreeMap<SignedVar, IntCst> = BTreeMap::new();
+        for e in rhs {
+            elems
+                .entry(e.var)
+                .and_modify(|cst| *cst = (*cst).max(e.cst))
+                .or_insert(e.cst);
+        }
+        let rhs: Vec<NFEqMaxItem> = elems.into_iter().map(|(var, cst)| NFEqMaxItem { var, cst }).collect();
+        // propagators rely on each variable having a single role in the constraint to produce explanations
+        // (the constant variable is exempted as its bounds never change)
+        let lhs_var = lhs.var.variable();
+        assert!(
+            lhs_var == VarRef::ZERO || rhs.iter().all(|e| e.var.variable() != lhs_var),
+            "The left hand side of a max constraint cannot appear on its right hand side"
+        );
+        assert!(
+            rhs.windows(2)
+                .all(|w| w[0].var.variable() == VarRef::ZERO || w[0].var.variable() != w[1].var.variable()),
+            "A variable appears with opposite signs in a max constraint"
+        );
+        NFEqMax { lhs, rhs }
+    }
+
+    pub(crate) fn validity_scope(&self, presence: impl Fn(VarRef) -> Lit) -> ValidityScope {
+        // elements of the right hand side are allowed to be absent
+        ValidityScope::new([presence(self.lhs.var.variable())], [])
+    }
+}
+
+impl From<EqMax> for ReifExpr {
+    fn from(value: EqMax) -> Self {
+        let lhs = NFEqMaxItem::positive(value.lhs);
+        let rhs = value.rhs.into_iter().map(NFEqMaxItem::positive);
+        ReifExpr::EqMax(NFEqMax::new(lhs, rhs))
+    }
+}
+
+impl From<EqMin> for ReifExpr {
+    fn from(value: EqMin) -> Self {
+        // min(x_i) = y  <=>  max(-x_i) = -y
+        let lhs = NFEqMaxItem::negative(value.lhs);
+        let rhs = value.rhs.into_iter().map(NFEqMaxItem::negative);
+        ReifExpr::EqMax(NFEqMax::new(lhs, rhs))
+    }
+}
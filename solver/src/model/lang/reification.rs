@@ -1,3 +1,4 @@
+use crate::collections::set::RefSet;
 use crate::core::*;
 use crate::reif::ReifExpr;
 use std::collections::HashMap;
@@ -5,10 +6,13 @@ use std::collections::HashMap;
 /// A structure to keep track of all reification of expressions.
 ///
 /// A correspondence between canonical expressions and the literal that they have reified to is maintained.
+/// As expressions are hashed on their canonical form, structurally equal expressions are only reified once.
 #[derive(Default, Clone)]
 pub struct Reification {
     /// Associates each canonical atom to a single literal.
     map: HashMap<ReifExpr, Lit>,
+    /// Variables that were created for the sole purpose of reifying an expression.
+    definitions: RefSet<VarRef>,
 }
 
 impl Reification {
@@ -25,7 +29,41 @@ impl Reification {
     pub fn intern_as(&mut self, e: ReifExpr, lit: Lit) {
         assert!(!self.map.contains_key(&e));
         self.map.insert(e.clone(), lit);
-        self.map.insert(!e, !lit);
+        if e.negatable() {
+            self.map.insert(!e, !lit);
+        }
+    }
+
+    /// Interns the expression as the definition of the literal, whose variable was created to reify it
+    /// and has no other meaning.
+    /// Panics, if the expression is already interned.
+    pub fn intern_as_definition(&mut self, e: ReifExpr, lit: Lit) {
+        self.definitions.insert(lit.variable());
+        self.intern_as(e, lit);
+    }
+
+    /// Returns true if the variable was created to reify an expression (see [Self::intern_as_definition]).
+    pub fn is_definition(&self, var: VarRef) -> bool {
+        self.definitions.contains(var)
+    }
+
+    /// Removes the expression (and its negation) from the interned ones.
+    pub fn remove(&mut self, e: &ReifExpr) {
+        if let Some(lit) = self.map.remove(e) {
+            self.definitions.remove(lit.variable());
+        }
+        if e.negatable() {
+            self.map.remove(&!e.clone());
+        }
+    }
+
+    /// Number of interned expressions, counting negations separately.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
     }
 }
 
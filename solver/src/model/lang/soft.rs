@@ -0,0 +1,150 @@
+use crate::core::IntCst;
+use crate::model::extensions::AssignmentExt;
+use crate::model::lang::expr::leq;
+use crate::model::lang::linear::{LinearSum, LinearTerm};
+use crate::model::lang::{IAtom, IVar};
+use crate::model::{Label, Model};
+
+/// A soft temporal constraint `b <= a + ub` that may be violated, at the cost of `weight` per unit of violation.
+///
+/// The constraint is relaxed into `b <= relaxed`, where the `relaxed` bound is a variable in `[a + ub, a + ub + max_violation]`.
+/// All three constraints are difference constraints, and are thus handled by the STN.
+/// The violation of the constraint is `relaxed - (a + ub)`.
+#[derive(Copy, Clone, Debug)]
+pub struct SoftLeq {
+    pub a: IAtom,
+    pub b: IAtom,
+    pub ub: IntCst,
+    pub weight: IntCst,
+    pub max_violation: IntCst,
+    /// Relaxation variable, i.e., the (possibly delayed) bound that `b` must respect.
+    pub relaxed: IVar,
+}
+
+impl SoftLeq {
+    /// Weighted violation of the constraint: `weight * (relaxed - (a + ub))`.
+    pub fn weighted_violation(&self) -> LinearSum {
+        let mut violation = LinearSum::from(LinearTerm::new(self.weight, self.relaxed, false));
+        if self.a.var != IVar::ZERO {
+            violation += LinearTerm::new(-self.weight, self.a.var, false);
+        }
+        violation - self.weight * (self.a.shift + self.ub)
+    }
+}
+
+/// A set of soft temporal constraints, whose weighted violations are summed into a penalty to minimize,
+/// so that over-constrained temporal problems (e.g. with preferred deadlines) can be solved for a minimal total penalty
+/// instead of being inconsistent.
+///
+/// # Usage
+/// ```
+/// use aries::model::Model;
+/// use aries::model::lang::soft::SoftConstraints;
+/// let mut model = Model::<&'static str>::new();
+/// let end = model.new_ivar(10, 100, "end");
+/// let mut soft = SoftConstraints::new();
+/// // the task should end before 5 (which is impossible), each unit of delay costing 3
+/// soft.add_leq(&mut model, 0, end, 5, 3, 100, "deadline");
+/// let penalty = soft.penalty(&mut model, "penalty");
+/// let mut solver = aries::solver::Solver::new(model);
+/// let (cost, _) = solver.minimize(penalty).unwrap().unwrap();
+/// assert_eq!(cost, 15);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SoftConstraints {
+    constraints: Vec<SoftLeq>,
+}
+
+impl SoftConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the soft constraint `b <= a + ub`, violated by at most `max_violation` and whose violation costs `weight`
+    /// per unit. Both `a` and `b` must be non-optional.
+    ///
+    /// Returns the soft constraint, whose relaxation variable is labeled with `label`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_leq<Lbl: Label>(
+        &mut self,
+        model: &mut Model<Lbl>,
+        a: impl Into<IAtom>,
+        b: impl Into<IAtom>,
+        ub: IntCst,
+        weight: IntCst,
+        max_violation: IntCst,
+        label: impl Into<Lbl>,
+    ) -> SoftLeq {
+        assert!(weight >= 0, "Negative weight of a soft constraint");
+        assert!(max_violation >= 0, "Negative maximal violation of a soft constraint");
+        let (a, b) = (a.into(), b.into());
+        let bound = a + ub;
+        let (lb, ub_bound) = model.int_bounds(bound);
+        let relaxed = model.new_ivar(lb, ub_bound + max_violation, label);
+        model.enforce(leq(bound, relaxed), []);
+        model.enforce(leq(relaxed, bound + max_violation), []);
+        model.enforce(leq(b, relaxed), []);
+        let soft = SoftLeq {
+            a,
+            b,
+            ub,
+            weight,
+            max_violation,
+            relaxed,
+        };
+        self.constraints.push(soft);
+        soft
+    }
+
+    pub fn constraints(&self) -> &[SoftLeq] {
+        &self.constraints
+    }
+
+    /// Creates a variable equal to the total penalty of the soft constraints, i.e., the sum of their weighted violations.
+    pub fn penalty<Lbl: Label>(&self, model: &mut Model<Lbl>, label: impl Into<Lbl>) -> IVar {
+        let max_penalty: IntCst = self
+            .constraints
+            .iter()
+            .map(|c| c.weight.saturating_mul(c.max_violation))
+            .fold(0, IntCst::saturating_add);
+        let penalty = model.new_ivar(0, max_penalty, label);
+        let sum = self
+            .constraints
+            .iter()
+            .fold(LinearSum::zero(), |sum, c| sum + c.weighted_violation());
+        model.enforce(sum.clone().leq(penalty), []);
+        model.enforce(sum.geq(penalty), []);
+        penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::expr::eq;
+
+    type Model = crate::model::Model<String>;
+    type Solver = crate::solver::Solver<String>;
+
+    #[test]
+    fn soft_temporal_constraints() {
+        let mut model = Model::new();
+        let start = model.new_ivar(0, 100, "start");
+        let end = model.new_ivar(0, 100, "end");
+        model.enforce(eq(end, start + 10), []);
+        let mut soft = SoftConstraints::new();
+        // preferred deadline: end <= 5, each unit of delay costs 3
+        let deadline = soft.add_leq(&mut model, 0, end, 5, 3, 100, "deadline".to_string());
+        // preferred release date: 4 <= start, each unit of advance costs 5
+        let release = soft.add_leq(&mut model, start, 4, 0, 5, 100, "release".to_string());
+        let penalty = soft.penalty(&mut model, "penalty".to_string());
+
+        let mut solver = Solver::new(model);
+        let (cost, sol) = solver.minimize(penalty).unwrap().unwrap();
+        // starting at 4 violates the deadline by 9, which is cheaper than starting earlier
+        assert_eq!(cost, 27);
+        assert_eq!(sol.var_domain(start).lb, 4);
+        assert_eq!(sol.var_domain(deadline.relaxed).lb, 14);
+        assert_eq!(sol.var_domain(release.relaxed).lb, 4);
+    }
+}
@@ -0,0 +1,205 @@
+//! Type checking of expressions, allowing to reject ill-typed expressions when they are built
+//! (e.g. the comparison of a symbol with an integer) rather than failing deep inside their decomposition.
+
+use crate::core::IntCst;
+use crate::model::lang::expr::{Eq, Neq};
+use crate::model::lang::linear::{LinearSum, LinearTerm};
+use crate::model::lang::{Atom, IVar, Kind};
+use crate::model::types::{TypeHierarchy, TypeId};
+use std::fmt::{Display, Formatter};
+
+/// Error raised when building an ill-typed expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeError {
+    /// The operands of the operation have different kinds (e.g. a symbol and an integer).
+    KindMismatch {
+        operation: &'static str,
+        lhs: Kind,
+        rhs: Kind,
+    },
+    /// The operands are symbols whose types have no common instance.
+    UnrelatedTypes {
+        operation: &'static str,
+        lhs: String,
+        rhs: String,
+    },
+    /// An operand of an arithmetic operation is not a number.
+    NotNumeric { operation: &'static str, operand: Kind },
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeError::KindMismatch { operation, lhs, rhs } => write!(
+                f,
+                "type error in `{operation}`: an expression of kind {lhs:?} cannot be combined with one of kind {rhs:?}"
+            ),
+            TypeError::UnrelatedTypes { operation, lhs, rhs } => write!(
+                f,
+                "type error in `{operation}`: the types `{lhs}` and `{rhs}` have no common instance"
+            ),
+            TypeError::NotNumeric { operation, operand } => write!(
+                f,
+                "type error in `{operation}`: an expression of kind {operand:?} is not numeric"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TypeError {}
+
+/// Checks that the two atoms have the same kind.
+pub fn check_same_kind(operation: &'static str, lhs: Atom, rhs: Atom) -> Result<(), TypeError> {
+    if lhs.kind() == rhs.kind() {
+        Ok(())
+    } else {
+        Err(TypeError::KindMismatch {
+            operation,
+            lhs: lhs.kind(),
+            rhs: rhs.kind(),
+        })
+    }
+}
+
+/// Checks that the two atoms can be compared: they must have the same kind and, if they are symbols,
+/// one of their types must be a subtype of the other (otherwise they are necessarily different).
+pub fn check_comparable(types: &TypeHierarchy, operation: &'static str, lhs: Atom, rhs: Atom) -> Result<(), TypeError> {
+    check_same_kind(operation, lhs, rhs)?;
+    if let (Atom::Sym(l), Atom::Sym(r)) = (lhs, rhs) {
+        let (l, r) = (l.tpe(), r.tpe());
+        if l != r && !(is_subtype(types, l, r) || is_subtype(types, r, l)) {
+            let name = |tpe: TypeId| {
+                if is_known(types, tpe) {
+                    types.from_id(tpe).to_string()
+                } else {
+                    format!("{tpe:?}")
+                }
+            };
+            return Err(TypeError::UnrelatedTypes {
+                operation,
+                lhs: name(l),
+                rhs: name(r),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if `sub` is a subtype of `tpe`, and false if any of them is unknown to the hierarchy.
+fn is_subtype(types: &TypeHierarchy, tpe: TypeId, sub: TypeId) -> bool {
+    is_known(types, tpe) && is_known(types, sub) && types.is_subtype(tpe, sub)
+}
+
+fn is_known(types: &TypeHierarchy, tpe: TypeId) -> bool {
+    types.types().any(|t| t == tpe)
+}
+
+/// Builds the constraint `lhs = rhs`, after checking that both sides can be compared.
+pub fn try_eq(types: &TypeHierarchy, lhs: impl Into<Atom>, rhs: impl Into<Atom>) -> Result<Eq, TypeError> {
+    let (lhs, rhs) = (lhs.into(), rhs.into());
+    check_comparable(types, "=", lhs, rhs)?;
+    Ok(crate::model::lang::expr::eq(lhs, rhs))
+}
+
+/// Builds the constraint `lhs != rhs`, after checking that both sides can be compared.
+pub fn try_neq(types: &TypeHierarchy, lhs: impl Into<Atom>, rhs: impl Into<Atom>) -> Result<Neq, TypeError> {
+    let (lhs, rhs) = (lhs.into(), rhs.into());
+    check_comparable(types, "!=", lhs, rhs)?;
+    Ok(crate::model::lang::expr::neq(lhs, rhs))
+}
+
+/// Builds the sum of the `(factor, atom)` terms, after checking that all atoms are numbers of the same kind.
+///
+/// For fixed-point numbers, the sum is on the numerators (all atoms sharing the same denominator).
+pub fn try_sum(terms: impl IntoIterator<Item = (IntCst, Atom)>) -> Result<LinearSum, TypeError> {
+    let mut sum = LinearSum::zero();
+    let mut first: Option<Atom> = None;
+    for (factor, atom) in terms {
+        let value = match atom {
+            Atom::Int(i) => i,
+            Atom::Fixed(f) => f.num,
+            Atom::Bool(_) | Atom::Sym(_) => {
+                return Err(TypeError::NotNumeric {
+                    operation: "+",
+                    operand: atom.kind(),
+                })
+            }
+        };
+        match first {
+            Some(first) => check_same_kind("+", first, atom)?,
+            None => first = Some(atom),
+        }
+        if value.var != IVar::ZERO {
+            sum += LinearTerm::new(factor, value.var, false);
+        }
+        sum = sum + factor * value.shift;
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::SAtom;
+    use crate::model::symbols::SymId;
+    use crate::utils::input::Sym;
+
+    fn types() -> TypeHierarchy {
+        // object > {vehicle > truck, location}
+        let types = vec![
+            ("object".into(), None),
+            ("vehicle".into(), Some("object".into())),
+            ("truck".into(), Some("vehicle".into())),
+            ("location".into(), Some("object".into())),
+        ];
+        TypeHierarchy::new(types).unwrap()
+    }
+
+    fn sym(types: &TypeHierarchy, tpe: &str) -> Atom {
+        let tpe = types.id_of(&Sym::from(tpe)).unwrap();
+        SAtom::new_constant(SymId::from(0usize), tpe).into()
+    }
+
+    #[test]
+    fn comparisons() {
+        let th = types();
+        let (object, vehicle, truck, location) = (
+            sym(&th, "object"),
+            sym(&th, "vehicle"),
+            sym(&th, "truck"),
+            sym(&th, "location"),
+        );
+        assert!(try_eq(&th, truck, vehicle).is_ok());
+        assert!(try_eq(&th, object, truck).is_ok());
+        assert!(try_neq(&th, location, object).is_ok());
+        assert!(matches!(
+            try_eq(&th, truck, location),
+            Err(TypeError::UnrelatedTypes { lhs, rhs, .. }) if lhs == "truck" && rhs == "location"
+        ));
+        assert!(matches!(
+            try_neq(&th, truck, Atom::from(3)),
+            Err(TypeError::KindMismatch {
+                lhs: Kind::Sym,
+                rhs: Kind::Int,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn sums() {
+        let th = types();
+        let mut model = crate::model::Model::<&str>::new();
+        let x = model.new_ivar(0, 10, "x") + 2;
+        assert!(try_sum([(1, Atom::from(x)), (2, Atom::from(4))]).is_ok());
+        assert!(matches!(
+            try_sum([(1, Atom::from(x)), (1, sym(&th, "truck"))]),
+            Err(TypeError::NotNumeric { operand: Kind::Sym, .. })
+        ));
+        let f = crate::model::lang::FAtom::new(x, 10);
+        assert!(matches!(
+            try_sum([(1, Atom::from(x)), (1, Atom::from(f))]),
+            Err(TypeError::KindMismatch { .. })
+        ));
+    }
+}
@@ -4,7 +4,10 @@ mod model_impl;
 pub use label::Label;
 pub use model_impl::*;
 
+pub mod equivalences;
 pub mod extensions;
+pub mod fixed_variables;
 pub mod lang;
 pub mod symbols;
+pub mod symmetry;
 pub mod types;
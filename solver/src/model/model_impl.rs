@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::sync::Arc;
 
 use crate::backtrack::{Backtrack, DecLvl};
 use crate::collections::ref_store::RefMap;
+use crate::collections::set::RefSet;
 use crate::core::literals::StableLitSet;
 use crate::core::state::*;
 use crate::core::*;
@@ -16,6 +18,7 @@ use crate::model::symbols::SymbolTable;
 use crate::model::types::TypeId;
 use crate::reif::{ReifExpr, Reifiable};
 
+mod dump;
 mod scopes;
 
 #[derive(Clone)]
@@ -33,6 +36,8 @@ pub struct ModelShape<Lbl> {
     pub constraints: Vec<Constraint>,
     pub labels: VariableLabels<Lbl>,
     pub conjunctive_scopes: Scopes,
+    /// Variables tagged as decision variables, all others being auxiliary (e.g. introduced by the encoding).
+    decisions: RefSet<VarRef>,
 }
 
 impl<Lbl: Label> ModelShape<Lbl> {
@@ -48,6 +53,7 @@ impl<Lbl: Label> ModelShape<Lbl> {
             constraints: Default::default(),
             labels: Default::default(),
             conjunctive_scopes: Default::default(),
+            decisions: Default::default(),
         }
     }
 
@@ -61,6 +67,16 @@ impl<Lbl: Label> ModelShape<Lbl> {
             _ => panic!("More than one variable with label: {label:?}"),
         }
     }
+    /// Returns true if the variable was tagged as a decision variable with [Model::tag_decision].
+    pub fn is_decision(&self, var: impl Into<VarRef>) -> bool {
+        self.decisions.contains(var.into())
+    }
+
+    /// Iterates over all variables tagged as decision variables, in increasing order.
+    pub fn decision_variables(&self) -> impl Iterator<Item = VarRef> + '_ {
+        self.decisions.iter()
+    }
+
     fn set_type(&mut self, var: VarRef, typ: Type) {
         self.types.insert(var, typ);
     }
@@ -115,6 +131,9 @@ pub struct Model<Lbl> {
     pub shape: ModelShape<Lbl>,
     /// Domain of all variables, defining the current state of the Model.
     pub state: Domains,
+    /// Decomposed expressions posted with [Self::enforce_all], together with their scope,
+    /// whose reification is deferred until the next call to [Self::seal].
+    deferred: Vec<(ReifExpr, Lit)>,
 }
 
 impl<Lbl: Label> Model<Lbl> {
@@ -126,6 +145,7 @@ impl<Lbl: Label> Model<Lbl> {
         Model {
             shape: ModelShape::new_with_symbols(symbols),
             state: Domains::new(),
+            deferred: Vec::new(),
         }
     }
 
@@ -166,12 +186,13 @@ impl<Lbl: Label> Model<Lbl> {
         self.new_conjunctive_presence_variable(scope)
     }
 
-    /// Returns a literal whose presence is `scope` and that is always true.
+    /// Returns a literal whose presence is `scope` and that is always true, i.e., a literal that holds
+    /// exactly in the scope. Its negation is thus a literal that is false in the scope.
     ///
-    /// THis is functionnaly equivalent to creating a new optional boolean variable
-    /// with domain `[1,1]` with `presence=scope` but will ensure that only one such
-    /// variable is created in this scope.
-    pub fn get_tautology_of_scope(&mut self, scope: Lit) -> Lit {
+    /// This is functionally equivalent to creating a new optional boolean variable
+    /// with domain `[1,1]` with `presence=scope` but ensures that only one such
+    /// variable is created for each scope. The literal of the empty scope (`Lit::TRUE`) is `Lit::TRUE` itself.
+    pub fn scoped_lit(&mut self, scope: Lit) -> Lit {
         self.shape
             .conjunctive_scopes
             .get_tautology_of_scope(scope)
@@ -184,6 +205,26 @@ impl<Lbl: Label> Model<Lbl> {
             })
     }
 
+    /// Deprecated alias of [Self::scoped_lit].
+    #[deprecated(note = "use scoped_lit instead")]
+    pub fn get_tautology_of_scope(&mut self, scope: Lit) -> Lit {
+        self.scoped_lit(scope)
+    }
+
+    /// Returns a literal that has the same value as `value` but is only defined in `scope`.
+    ///
+    /// The constants `Lit::TRUE` and `Lit::FALSE` are replaced by the [scoped literal](Self::scoped_lit) of
+    /// `scope` (or its negation). Any other literal is assumed to be already defined in the scope and is returned unchanged.
+    pub fn scoped_value(&mut self, value: Lit, scope: Lit) -> Lit {
+        if value == Lit::TRUE {
+            self.scoped_lit(scope)
+        } else if value == Lit::FALSE {
+            !self.scoped_lit(scope)
+        } else {
+            value
+        }
+    }
+
     fn new_conjunctive_presence_variable(&mut self, set: impl Into<StableLitSet>) -> Lit {
         let set = set.into();
         if let Some(l) = self.shape.conjunctive_scopes.get(&set) {
@@ -270,6 +311,34 @@ impl<Lbl: Label> Model<Lbl> {
         self.create_ivar(lb, ub, Some(presence), label)
     }
 
+    /// Creates a new interval variable that is present iff `presence` is true.
+    /// Its start and end are in the given `bounds` and its duration in `duration_bounds`,
+    /// all expressed as numerators of fixed-point numbers with denominator `denom`.
+    ///
+    /// The duration is a constant if its bounds are equal. Otherwise, it is a new variable.
+    /// All variables created are given the same `label`.
+    /// The consistency of the interval (`start + duration = end`) is enforced whenever it is present.
+    pub fn new_optional_interval_var(
+        &mut self,
+        bounds: (IntCst, IntCst),
+        duration_bounds: (IntCst, IntCst),
+        denom: IntCst,
+        presence: Lit,
+        label: impl Into<Lbl>,
+    ) -> IntervalVar {
+        let label = label.into();
+        let (lb, ub) = bounds;
+        let start = self.new_optional_fvar(lb, ub, denom, presence, label.clone());
+        let end = self.new_optional_fvar(lb, ub, denom, presence, label.clone());
+        let duration = match duration_bounds {
+            (min, max) if min == max => FAtom::new(IAtom::from(min), denom),
+            (min, max) => self.new_optional_fvar(min, max, denom, presence, label).into(),
+        };
+        let itv = IntervalVar::new(start.into(), duration, end.into(), presence);
+        itv.enforce_consistency(self);
+        itv
+    }
+
     fn create_ivar(&mut self, lb: IntCst, ub: IntCst, presence: Option<Lit>, label: impl Into<Lbl>) -> IVar {
         let dvar = if let Some(presence) = presence {
             self.state.new_optional_var(lb, ub, presence)
@@ -281,6 +350,15 @@ impl<Lbl: Label> Model<Lbl> {
         IVar::new(dvar)
     }
 
+    /// Tags the variable as a decision variable, i.e., one whose value is part of the solutions of the problem.
+    /// Variables that are not tagged are considered auxiliary: their value is determined by the decision variables
+    /// or irrelevant to the user of the model.
+    ///
+    /// The values of decision variables in a solution can be extracted with [SavedAssignment::project_to_decisions].
+    pub fn tag_decision(&mut self, var: impl Into<VarRef>) {
+        self.shape.decisions.insert(var.into())
+    }
+
     pub fn new_sym_var(&mut self, tpe: TypeId, label: impl Into<Lbl>) -> SVar {
         self.create_sym_var(tpe, None, label)
     }
@@ -348,6 +426,17 @@ impl<Lbl: Label> Model<Lbl> {
         }
     }
 
+    /// Builds the constraint `a = b`, returning an error if `a` and `b` cannot be compared
+    /// (e.g. a symbol and an integer, or two symbols of unrelated types).
+    pub fn try_eq(&self, a: impl Into<Atom>, b: impl Into<Atom>) -> Result<expr::Eq, typing::TypeError> {
+        typing::try_eq(&self.shape.symbols.types, a, b)
+    }
+
+    /// Builds the constraint `a != b`, returning an error if `a` and `b` cannot be compared.
+    pub fn try_neq(&self, a: impl Into<Atom>, b: impl Into<Atom>) -> Result<expr::Neq, typing::TypeError> {
+        typing::try_neq(&self.shape.symbols.types, a, b)
+    }
+
     /// Interns the given expression and returns an equivalent literal.
     /// The returned literal is *optional* and defined such that it is
     /// present iff the expression is valid (typically meaning that all
@@ -373,7 +462,7 @@ impl<Lbl: Label> Model<Lbl> {
             let var = self.state.new_optional_var(0, 1, scope);
             let lit = var.geq(1);
             self.shape.set_type(var, Type::Bool);
-            self.shape.expressions.intern_as(expr.clone(), lit);
+            self.shape.expressions.intern_as_definition(expr.clone(), lit);
             self.shape.add_reification_constraint(lit, expr);
 
             lit
@@ -388,8 +477,12 @@ impl<Lbl: Label> Model<Lbl> {
     pub fn enforce<Expr: Reifiable<Lbl>>(&mut self, expr: Expr, scope: impl IntoIterator<Item = Lit>) {
         debug_assert_eq!(self.state.current_decision_level(), DecLvl::ROOT);
         let expr = expr.decompose(self);
-
         let scope = self.new_conjunctive_presence_variable(scope);
+        self.enforce_in_scope(expr, scope);
+    }
+
+    /// Enforces the decomposed expression to be true whenever the `scope` presence literal is true.
+    fn enforce_in_scope(&mut self, expr: ReifExpr, scope: Lit) {
         debug_assert!(
             {
                 // compute the scope in which the expression is valid
@@ -405,25 +498,59 @@ impl<Lbl: Label> Model<Lbl> {
         );
 
         // retrieve or create an optional variable that is always true in the scope
-        let tauto = self.get_tautology_of_scope(scope);
+        let tauto = self.scoped_lit(scope);
 
-        self.bind(expr, tauto);
+        self.bind_core(expr, tauto);
     }
 
+    /// Enforces all given expressions to be true whenever all literals of the scope are true, as [Self::enforce].
+    ///
+    /// The expressions are decomposed immediately, but their reification is deferred until the next call to
+    /// [Self::seal], that processes all of them at once. Until then, they do not appear in the constraints of the model.
+    /// This avoids interleaving many small reifications with the construction of the model,
+    /// and computes the scope only once for all expressions.
     pub fn enforce_all<Expr: Reifiable<Lbl>>(
         &mut self,
         bools: impl IntoIterator<Item = Expr>,
-        scope: impl IntoIterator<Item = Lit> + Clone,
+        scope: impl IntoIterator<Item = Lit>,
     ) {
+        debug_assert_eq!(self.state.current_decision_level(), DecLvl::ROOT);
+        let scope = self.new_conjunctive_presence_variable(scope);
         for b in bools {
-            self.enforce(b, scope.clone());
+            let expr = b.decompose(self);
+            self.deferred.push((expr, scope));
+        }
+    }
+
+    /// Reifies all expressions posted with [Self::enforce_all] since the last call, adding them to the constraints
+    /// of the model.
+    ///
+    /// The model is sealed when given to a [Solver](crate::solver::Solver) and before its constraints are posted,
+    /// as well as by the functions that simplify its constraints. Other readers of the constraints of the model
+    /// (e.g. [Self::dump_pretty]) ignore the deferred expressions until it is sealed.
+    pub fn seal(&mut self) {
+        if self.deferred.is_empty() {
+            return;
         }
+        let deferred = std::mem::take(&mut self.deferred);
+        self.shape.constraints.reserve(deferred.len());
+        for (expr, scope) in deferred {
+            self.enforce_in_scope(expr, scope);
+        }
+    }
+
+    /// Returns true if no expression posted with [Self::enforce_all] awaits its reification by [Self::seal].
+    pub fn is_sealed(&self) -> bool {
+        self.deferred.is_empty()
     }
 
     /// Record that `b <=> literal`
     pub fn bind<Expr: Reifiable<Lbl>>(&mut self, expr: Expr, value: Lit) {
         let expr = expr.decompose(self);
+        self.bind_core(expr, value)
+    }
 
+    fn bind_core(&mut self, expr: ReifExpr, value: Lit) {
         // compute the validity scope of the expression, which be larger than the one of the value
         let expression_scope = expr.scope(|var| self.state.presence(var));
         let expression_scope = expression_scope.to_conjunction(
@@ -431,6 +558,8 @@ impl<Lbl: Label> Model<Lbl> {
             |l| self.state.entails(l),
         );
         let expression_scope = self.new_conjunctive_presence_variable(expression_scope);
+        // a constant value is interpreted as holding wherever the expression is defined
+        let value = self.scoped_value(value, expression_scope);
         debug_assert!(
             self.state
                 .implies(self.presence_literal(value.variable()), expression_scope),
@@ -458,6 +587,83 @@ impl<Lbl: Label> Model<Lbl> {
         }
     }
 
+    /// Removes from the model the reified expressions that are no longer needed, to be called between solves of an
+    /// incremental session that repeatedly reifies new expressions.
+    ///
+    /// An expression reified with [Self::reify] is needed if its literal appears in another constraint that is needed,
+    /// in the presence of a variable, in an implication or in the `roots`, the literals that the caller still refers to.
+    /// Constraints posted with [Self::enforce] or [Self::bind] are always needed.
+    /// Removed expressions are reified to a new literal if they are reified again.
+    ///
+    /// Returns the indices of the removed constraints, in increasing order.
+    ///
+    /// # Panics
+    ///
+    /// If the model is not at the root level.
+    pub fn collect_garbage(&mut self, roots: impl IntoIterator<Item = Lit>) -> Vec<usize> {
+        assert_eq!(self.state.current_decision_level(), DecLvl::ROOT);
+        self.seal();
+        let expressions = &self.shape.expressions;
+        let constraints = &self.shape.constraints;
+
+        // for each variable created to reify an expression, the constraint defining it,
+        // unless it appears on the value side of several constraints (e.g. when later bound to another expression)
+        let mut definition_of: HashMap<VarRef, Option<usize>> = HashMap::new();
+        for (i, Constraint::Reified(_, value)) in constraints.iter().enumerate() {
+            let var = value.variable();
+            if expressions.is_definition(var) {
+                definition_of
+                    .entry(var)
+                    .and_modify(|def| *def = None)
+                    .or_insert(Some(i));
+            }
+        }
+
+        // mark: all variables reachable from the roots and from the constraints that cannot be removed
+        let mut needed = vec![false; constraints.len()];
+        let mut reached: RefSet<VarRef> = RefSet::new();
+        let mut queue: Vec<VarRef> = roots.into_iter().map(|l| l.variable()).collect();
+        for var in self.state.variables() {
+            queue.push(self.state.presence(var).variable());
+            if expressions.is_definition(var) && self.state.in_implications(var.geq(1)) {
+                queue.push(var);
+            }
+        }
+        for (i, Constraint::Reified(expr, value)) in constraints.iter().enumerate() {
+            if definition_of.get(&value.variable()) != Some(&Some(i)) {
+                needed[i] = true;
+                queue.push(value.variable());
+                queue.extend(expr.variables());
+            }
+        }
+        while let Some(var) = queue.pop() {
+            if reached.contains(var) {
+                continue;
+            }
+            reached.insert(var);
+            if let Some(&Some(i)) = definition_of.get(&var) {
+                if !needed[i] {
+                    needed[i] = true;
+                    let Constraint::Reified(expr, _) = &constraints[i];
+                    queue.extend(expr.variables());
+                }
+            }
+        }
+
+        // sweep: remove the expressions that are not needed
+        let removed: Vec<usize> = (0..constraints.len()).filter(|&i| !needed[i]).collect();
+        for &i in &removed {
+            let Constraint::Reified(expr, _) = &self.shape.constraints[i];
+            self.shape.expressions.remove(expr);
+        }
+        let mut index = 0;
+        self.shape.constraints.retain(|_| {
+            index += 1;
+            needed[index - 1]
+        });
+        removed
+    }
+
     // =========== Formatting ==============
 
     pub fn fmt(&self, atom: impl Into<Atom>) -> impl std::fmt::Display + '_ {
@@ -465,6 +671,16 @@ impl<Lbl: Label> Model<Lbl> {
         crate::model::extensions::fmt(atom, self)
     }
 
+    /// Describes an update that would have emptied the domain of a variable, in terms of the labels of the model.
+    pub fn fmt_invalid_update(&self, invalid: &InvalidUpdate) -> String {
+        format!(
+            "Empty domain: cannot enforce `{}` on the domain {} of its variable (cause: {})",
+            self.fmt(invalid.lit),
+            invalid.domain,
+            invalid.cause
+        )
+    }
+
     pub fn print_state(&self) {
         for v in self.state.variables() {
             let prez = format!("[{:?}]", self.presence_literal(v));
@@ -0,0 +1,221 @@
+use crate::core::{IntCst, Lit, SignedVar, VarRef};
+use crate::model::extensions::Shaped;
+use crate::model::lang::linear::NFLinearSumItem;
+use crate::model::lang::max::NFEqMaxItem;
+use crate::model::lang::{IAtom, IVar, Type};
+use crate::model::symbols::SymId;
+use crate::model::{Constraint, Label, Model};
+use crate::reif::ReifExpr;
+use std::fmt::Write;
+
+impl<Lbl: Label> Model<Lbl> {
+    /// Returns a human-readable description of all variables of the model (with their label, domain and presence)
+    /// and of all its constraints, written in an algebraic syntax, e.g.
+    ///
+    /// ```text
+    /// ===== Variables (2/2) =====
+    /// var1       x : int in [0, 10]
+    /// var2       b : bool                      [present if x > 1]
+    /// ===== Constraints (1/1) =====
+    /// b <=> x - y <= 3
+    /// ```
+    ///
+    /// If a filter is given, only the variables whose label contains it are printed, together with the constraints
+    /// involving at least one of them.
+    /// Terms of a linear sum that evaluate to zero when their variable is absent are marked with a `?`.
+    pub fn dump_pretty(&self, filter: Option<&str>) -> String {
+        let selected = |v: VarRef| match filter {
+            None => true,
+            Some(filter) => self.get_label(v).map_or(false, |lbl| lbl.to_string().contains(filter)),
+        };
+        let mut out = String::new();
+
+        let vars: Vec<VarRef> = self.state.variables().filter(|&v| v != VarRef::ZERO).collect();
+        let selected_vars: Vec<VarRef> = vars.iter().copied().filter(|&v| selected(v)).collect();
+        writeln!(out, "===== Variables ({}/{}) =====", selected_vars.len(), vars.len()).unwrap();
+        for v in selected_vars {
+            let var = format!("{:<10} {} : {}", format!("{v:?}"), self.var_name(v), self.domain_str(v));
+            let prez = self.state.presence(v);
+            if prez == Lit::TRUE {
+                writeln!(out, "{var}").unwrap();
+            } else {
+                writeln!(out, "{var:<40} [present if {}]", self.fmt(prez)).unwrap();
+            }
+        }
+
+        let constraints = &self.shape.constraints;
+        let selected_constraints: Vec<&Constraint> = constraints
+            .iter()
+            .filter(|Constraint::Reified(expr, value)| {
+                filter.is_none() || selected(value.variable()) || expr.variables().into_iter().any(selected)
+            })
+            .collect();
+        writeln!(
+            out,
+            "===== Constraints ({}/{}) =====",
+            selected_constraints.len(),
+            constraints.len()
+        )
+        .unwrap();
+        for Constraint::Reified(expr, value) in selected_constraints {
+            let expr = self.expr_str(expr);
+            if *value == Lit::TRUE {
+                writeln!(out, "{expr}").unwrap();
+            } else {
+                writeln!(out, "{} <=> {expr}", self.fmt(*value)).unwrap();
+            }
+        }
+        out
+    }
+
+    fn var_name(&self, v: VarRef) -> String {
+        match self.get_type(v) {
+            Some(Type::Bool) => self.fmt(v.geq(1)).to_string(),
+            _ => self.fmt(IVar::new(v)).to_string(),
+        }
+    }
+
+    fn domain_str(&self, v: VarRef) -> String {
+        let (lb, ub) = self.state.bounds(v);
+        match self.get_type(v) {
+            Some(Type::Bool) if lb == ub => format!("bool = {}", lb == 1),
+            Some(Type::Bool) => "bool".to_string(),
+            Some(Type::Fixed(denom)) => format!("fixed in [{lb}/{denom}, {ub}/{denom}]"),
+            Some(Type::Sym(_)) if lb <= ub => {
+                let sym = |i: IntCst| self.get_symbol(SymId::from(i as usize)).to_string();
+                format!("sym in [{}, {}]", sym(lb), sym(ub))
+            }
+            Some(Type::Sym(_)) => "sym in []".to_string(),
+            Some(Type::Int) | None => format!("int in [{lb}, {ub}]"),
+        }
+    }
+
+    fn iatom_str(&self, atom: IAtom) -> String {
+        if atom.var == IVar::ZERO {
+            atom.shift.to_string()
+        } else if atom.shift == 0 {
+            self.var_name(atom.var.into())
+        } else {
+            let sign = if atom.shift > 0 { '+' } else { '-' };
+            format!("{} {sign} {}", self.var_name(atom.var.into()), atom.shift.abs())
+        }
+    }
+
+    fn signed_var_str(&self, svar: SignedVar) -> String {
+        let name = self.var_name(svar.variable());
+        if svar.is_plus() {
+            name
+        } else {
+            format!("-{name}")
+        }
+    }
+
+    fn expr_str(&self, expr: &ReifExpr) -> String {
+        let lits = |lits: &[Lit], sep: &str| {
+            let lits: Vec<String> = lits.iter().map(|&l| self.fmt(l).to_string()).collect();
+            format!("({})", lits.join(sep))
+        };
+        match expr {
+            ReifExpr::Lit(l) => self.fmt(*l).to_string(),
+            ReifExpr::MaxDiff(diff) => format!("{} - {} <= {}", self.var_name(diff.b), self.var_name(diff.a), diff.ub),
+            ReifExpr::Or(disjuncts) if disjuncts.is_empty() => "false".to_string(),
+            ReifExpr::Or(disjuncts) => lits(disjuncts, " | "),
+            ReifExpr::And(conjuncts) if conjuncts.is_empty() => "true".to_string(),
+            ReifExpr::And(conjuncts) => lits(conjuncts, " & "),
+            ReifExpr::Linear(lin) => {
+                let term = |item: &NFLinearSumItem| {
+                    let opt = if item.or_zero { "?" } else { "" };
+                    match item.factor {
+                        1 => format!("{}{opt}", self.var_name(item.var)),
+                        factor => format!("{}*{}{opt}", factor.abs(), self.var_name(item.var)),
+                    }
+                };
+                let mut sum = String::new();
+                for (i, item) in lin.sum.iter().enumerate() {
+                    let sign = match (i, item.factor < 0) {
+                        (0, true) => "-",
+                        (0, false) => "",
+                        (_, true) => " - ",
+                        (_, false) => " + ",
+                    };
+                    let item = NFLinearSumItem {
+                        factor: item.factor.abs(),
+                        ..*item
+                    };
+                    write!(sum, "{sign}{}", term(&item)).unwrap();
+                }
+                if sum.is_empty() {
+                    sum.push('0');
+                }
+                format!("{sum} <= {}", lin.upper_bound)
+            }
+            ReifExpr::EqMax(em) => {
+                let item = |item: &NFEqMaxItem| match item.cst {
+                    0 => self.signed_var_str(item.var),
+                    cst if cst > 0 => format!("{} + {cst}", self.signed_var_str(item.var)),
+                    cst => format!("{} - {}", self.signed_var_str(item.var), -cst),
+                };
+                let elems: Vec<String> = em.rhs.iter().map(item).collect();
+                format!("{} = max({})", item(&em.lhs), elems.join(", "))
+            }
+            ReifExpr::EqAbs(abs) => format!("{} = |{}|", self.iatom_str(abs.lhs), self.iatom_str(abs.arg)),
+            ReifExpr::EqDiv(div) => format!(
+                "{} = ({}) / {}",
+                self.iatom_str(div.lhs),
+                self.iatom_str(div.numerator),
+                div.denominator
+            ),
+            ReifExpr::EqMod(rem) => format!(
+                "{} = ({}) % {}",
+                self.iatom_str(rem.lhs),
+                self.iatom_str(rem.numerator),
+                rem.denominator
+            ),
+            ReifExpr::EqMul(mul) => format!(
+                "{} = ({}) * ({})",
+                self.iatom_str(mul.lhs),
+                self.iatom_str(mul.x),
+                self.iatom_str(mul.y)
+            ),
+            ReifExpr::Channel(ch) => {
+                let cases: Vec<String> = ch
+                    .lits
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &l)| format!("{} <=> {}", ch.value(i), self.fmt(l)))
+                    .collect();
+                format!("{} in {{{}}}", self.iatom_str(ch.var), cases.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::lang::expr::{leq, or};
+    use crate::model::Model;
+
+    #[test]
+    fn test_dump_pretty() {
+        let mut model: Model<String> = Model::new();
+        let x = model.new_ivar(0, 10, "x".to_string());
+        let y = model.new_ivar(0, 10, "y".to_string());
+        let a = model.new_bvar("a".to_string()).true_lit();
+        let b = model.new_optional_bvar(y.geq(2), "b".to_string()).true_lit();
+        model.enforce(or([a, x.leq(5)]), []);
+        model.bind(leq(x, y + 3), b);
+
+        let dump = model.dump_pretty(None);
+        assert!(dump.contains("===== Variables (5/5) ====="), "{dump}");
+        assert!(dump.contains(" x : int in [0, 10]"), "{dump}");
+        assert!(dump.contains(" b : bool"), "{dump}");
+        assert!(dump.contains("[present if y > 1]"), "{dump}");
+        assert!(dump.contains("(x <= 5 | a)"), "{dump}");
+        assert!(dump.contains("x - y <= 3"), "{dump}");
+
+        let dump = model.dump_pretty(Some("a"));
+        assert!(dump.contains("===== Variables (1/5) ====="), "{dump}");
+        assert!(dump.contains("===== Constraints (1/3) ====="), "{dump}");
+        assert!(!dump.contains(" x : "), "{dump}");
+    }
+}
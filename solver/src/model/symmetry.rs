@@ -0,0 +1,437 @@
+//! Automatic detection of interchangeable variables in a model, and breaking of the corresponding symmetries.
+//!
+//! Two variables are interchangeable if swapping them in all constraints of the model results in the same
+//! set of constraints (possibly after swapping the literals reifying the constraints that involve them).
+//! Any solution can then be transformed into another one by exchanging the values of the two variables,
+//! and it is sufficient to only consider the solutions where the first one is smaller than the second one.
+//!
+//! Candidates are first grouped with a light-weight color refinement on the constraint graph: two variables
+//! can only be interchangeable if they have the same domain, presence and the same (recursive) neighborhood
+//! in the constraints. Each candidate transposition is then checked against the full set of constraints.
+
+use crate::core::{IntCst, Lit, SignedVar, VarRef};
+use crate::model::lang::channel::Channel;
+use crate::model::lang::expr::leq;
+use crate::model::lang::linear::{NFLinearLeq, NFLinearSumItem};
+use crate::model::lang::max::{NFEqMax, NFEqMaxItem};
+use crate::model::lang::{IAtom, IVar};
+use crate::model::{Constraint, Label, Model};
+use crate::reif::{DifferenceExpression, ReifExpr};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Number of rounds of color refinement used to group candidate variables.
+const REFINEMENT_ROUNDS: usize = 3;
+
+/// Role of a variable in a constraint: a kind and up to two constants (factor, bound, offset, ...).
+type Role = (u8, IntCst, IntCst);
+
+/// Detects groups of interchangeable variables in the model.
+///
+/// In each group, the variables are sorted and any two consecutive variables are interchangeable.
+/// Hence, any permutation of a group maps solutions to solutions.
+/// Variables in `protected` (typically the objective) are never part of a group, so that the
+/// symmetries found also preserve the value of these variables.
+///
+/// Variables that define the value of a reified constraint or the presence of other variables are not considered,
+/// nor are the variables whose value is already fixed.
+pub fn interchangeable_variables<Lbl: Label>(model: &Model<Lbl>, protected: &[VarRef]) -> Vec<Vec<VarRef>> {
+    let detector = Detector::new(model);
+    let mut groups: HashMap<u64, Vec<VarRef>> = HashMap::new();
+    for (var, color) in detector.candidate_colors(protected) {
+        groups.entry(color).or_default().push(var);
+    }
+    let mut groups: Vec<Vec<VarRef>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    groups.iter_mut().for_each(|g| g.sort());
+    groups.sort();
+
+    // split each group into maximal chains of interchangeable variables
+    let mut result = Vec::new();
+    for group in groups {
+        let mut chain = vec![group[0]];
+        for &var in &group[1..] {
+            if detector.is_symmetry(*chain.last().unwrap(), var) {
+                chain.push(var);
+            } else {
+                if chain.len() > 1 {
+                    result.push(chain);
+                }
+                chain = vec![var];
+            }
+        }
+        if chain.len() > 1 {
+            result.push(chain);
+        }
+    }
+    result
+}
+
+/// Detects the interchangeable variables of the model (see [interchangeable_variables]) and posts
+/// lex-leader constraints to break the corresponding symmetries:
+/// for each group `x_1, ..., x_n` of interchangeable variables, it enforces `x_1 <= ... <= x_n`.
+///
+/// Returns the number of constraints posted.
+///
+/// This should be used on models whose symmetries are not already broken by construction
+/// (e.g. through an explicit ordering of instances of the same template).
+pub fn break_symmetries<Lbl: Label>(model: &mut Model<Lbl>, protected: &[VarRef]) -> usize {
+    model.seal();
+    let mut num_posted = 0;
+    for group in interchangeable_variables(model, protected) {
+        // all variables of the group share the same presence
+        let presence = model.state.presence(group[0]);
+        for pair in group.windows(2) {
+            model.enforce(leq(IAtom::from(pair[0]), IAtom::from(pair[1])), [presence]);
+            num_posted += 1;
+        }
+    }
+    num_posted
+}
+
+struct Detector<'a, Lbl> {
+    model: &'a Model<Lbl>,
+    /// Normal form of each constraint of the model, with the literal it is reified as.
+    constraints: Vec<(ReifExpr, Lit)>,
+    /// Reification literals of each constraint, indexed by their normal form.
+    index: HashMap<ReifExpr, Vec<Lit>>,
+    /// Variables defining the value of a reified constraint.
+    defined: HashSet<VarRef>,
+}
+
+impl<'a, Lbl: Label> Detector<'a, Lbl> {
+    fn new(model: &'a Model<Lbl>) -> Self {
+        let mut constraints = Vec::with_capacity(model.shape.constraints.len());
+        let mut index: HashMap<ReifExpr, Vec<Lit>> = HashMap::new();
+        let mut defined = HashSet::new();
+        for c in &model.shape.constraints {
+            let Constraint::Reified(expr, value) = c;
+            let expr = map_expr(expr, |v| v);
+            index.entry(expr.clone()).or_default().push(*value);
+            if value.variable() != VarRef::ZERO {
+                defined.insert(value.variable());
+            }
+            constraints.push((expr, *value));
+        }
+        Detector {
+            model,
+            constraints,
+            index,
+            defined,
+        }
+    }
+
+    /// Returns the candidate variables, together with their color after refinement.
+    fn candidate_colors(&self, protected: &[VarRef]) -> Vec<(VarRef, u64)> {
+        let state = &self.model.state;
+        let presence_vars: HashSet<VarRef> = state.variables().map(|v| state.presence(v).variable()).collect();
+        let mut colors: HashMap<VarRef, u64> = state
+            .variables()
+            .map(|v| {
+                let (lb, ub) = state.bounds(v);
+                let color = hash(&(lb, ub, state.presence(v), self.defined.contains(&v)));
+                (v, color)
+            })
+            .collect();
+
+        let occurrences: Vec<(u64, Vec<(VarRef, Role)>)> = self
+            .constraints
+            .iter()
+            .map(|(expr, value)| {
+                let mut roles = roles(expr);
+                roles.push((value.variable(), lit_role(14, *value)));
+                (constant_signature(expr), roles)
+            })
+            .collect();
+
+        for _ in 0..REFINEMENT_ROUNDS {
+            let mut neighborhoods: HashMap<VarRef, Vec<u64>> = HashMap::new();
+            for (signature, roles) in &occurrences {
+                let mut content: Vec<(Role, u64)> = roles.iter().map(|(v, r)| (*r, colors[v])).collect();
+                content.sort_unstable();
+                let constraint_color = hash(&(signature, content));
+                for (v, role) in roles {
+                    neighborhoods
+                        .entry(*v)
+                        .or_default()
+                        .push(hash(&(role, constraint_color)));
+                }
+            }
+            for (v, color) in colors.iter_mut() {
+                let mut neighborhood = neighborhoods.remove(v).unwrap_or_default();
+                neighborhood.sort_unstable();
+                *color = hash(&(*color, neighborhood));
+            }
+        }
+
+        colors
+            .into_iter()
+            .filter(|(v, _)| {
+                *v != VarRef::ZERO
+                    && !state.is_bound(*v)
+                    && !self.defined.contains(v)
+                    && !presence_vars.contains(v)
+                    && !protected.contains(v)
+            })
+            .collect()
+    }
+
+    /// Returns true if swapping the two variables (and the literals reifying the constraints in which they appear)
+    /// leaves the model unchanged.
+    fn is_symmetry(&self, x: VarRef, y: VarRef) -> bool {
+        let mut perm: HashMap<VarRef, VarRef> = HashMap::new();
+        perm.insert(x, y);
+        perm.insert(y, x);
+
+        // extend the permutation to the reification literals of the constraints, in the order in which
+        // the constraints were created (so that the literal of a sub-expression is processed before its parent)
+        for (expr, value) in &self.constraints {
+            let f = |v: VarRef| perm.get(&v).copied().unwrap_or(v);
+            let image = map_expr(expr, f);
+            let Some(image_values) = self.index.get(&image) else {
+                return false;
+            };
+            if image_values.contains(&map_lit(*value, f)) {
+                continue;
+            }
+            let var = value.variable();
+            let candidate = image_values.iter().find(|l| {
+                l.svar().is_plus() == value.svar().is_plus()
+                    && l.bound_value() == value.bound_value()
+                    && !perm.contains_key(&l.variable())
+            });
+            match candidate {
+                Some(image_value) if !perm.contains_key(&var) && self.defined.contains(&var) => {
+                    perm.insert(var, image_value.variable());
+                    perm.insert(image_value.variable(), var);
+                }
+                _ => return false,
+            }
+        }
+
+        // check that the complete permutation preserves all constraints and domains
+        let f = |v: VarRef| perm.get(&v).copied().unwrap_or(v);
+        let constraints_preserved = self.constraints.iter().all(|(expr, value)| {
+            self.index
+                .get(&map_expr(expr, f))
+                .map_or(false, |values| values.contains(&map_lit(*value, f)))
+        });
+        let state = &self.model.state;
+        let domains_preserved = perm
+            .iter()
+            .all(|(&v, &w)| state.bounds(v) == state.bounds(w) && state.presence(w) == map_lit(state.presence(v), f));
+        constraints_preserved && domains_preserved
+    }
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn map_svar(svar: SignedVar, f: impl Fn(VarRef) -> VarRef) -> SignedVar {
+    if svar.is_plus() {
+        SignedVar::plus(f(svar.variable()))
+    } else {
+        SignedVar::minus(f(svar.variable()))
+    }
+}
+
+pub(crate) fn map_lit(lit: Lit, f: impl Fn(VarRef) -> VarRef) -> Lit {
+    map_svar(lit.svar(), f).with_upper_bound(lit.bound_value())
+}
+
+fn map_atom(atom: IAtom, f: impl Fn(VarRef) -> VarRef) -> IAtom {
+    IAtom::new(IVar::new(f(atom.var.into())), atom.shift)
+}
+
+/// Replaces each variable `v` of the expression by `f(v)`, and returns the result in a normal form
+/// where the elements of commutative expressions (disjunctions, sums, ...) are sorted.
+pub(crate) fn map_expr(expr: &ReifExpr, f: impl Fn(VarRef) -> VarRef + Copy) -> ReifExpr {
+    let lits = |lits: &[Lit]| {
+        let mut lits: Vec<Lit> = lits.iter().map(|l| map_lit(*l, f)).collect();
+        lits.sort();
+        lits
+    };
+    let max_item = |item: &NFEqMaxItem| NFEqMaxItem {
+        var: map_svar(item.var, f),
+        cst: item.cst,
+    };
+    match expr {
+        ReifExpr::Lit(l) => ReifExpr::Lit(map_lit(*l, f)),
+        ReifExpr::MaxDiff(diff) => ReifExpr::MaxDiff(DifferenceExpression {
+            b: f(diff.b),
+            a: f(diff.a),
+            ub: diff.ub,
+        }),
+        ReifExpr::Or(l) => ReifExpr::Or(lits(l)),
+        ReifExpr::And(l) => ReifExpr::And(lits(l)),
+        ReifExpr::Linear(lin) => {
+            let mut sum: Vec<NFLinearSumItem> = lin
+                .sum
+                .iter()
+                .map(|item| NFLinearSumItem {
+                    var: f(item.var),
+                    ..*item
+                })
+                .collect();
+            sum.sort();
+            ReifExpr::Linear(NFLinearLeq {
+                sum,
+                upper_bound: lin.upper_bound,
+            })
+        }
+        ReifExpr::EqMax(em) => {
+            let mut rhs: Vec<NFEqMaxItem> = em.rhs.iter().map(max_item).collect();
+            rhs.sort();
+            ReifExpr::EqMax(NFEqMax {
+                lhs: max_item(&em.lhs),
+                rhs,
+            })
+        }
+        ReifExpr::EqAbs(abs) => {
+            let mut abs = *abs;
+            abs.lhs = map_atom(abs.lhs, f);
+            abs.arg = map_atom(abs.arg, f);
+            ReifExpr::EqAbs(abs)
+        }
+        ReifExpr::EqDiv(div) => {
+            let mut div = *div;
+            div.lhs = map_atom(div.lhs, f);
+            div.numerator = map_atom(div.numerator, f);
+            ReifExpr::EqDiv(div)
+        }
+        ReifExpr::EqMod(rem) => {
+            let mut rem = *rem;
+            rem.lhs = map_atom(rem.lhs, f);
+            rem.numerator = map_atom(rem.numerator, f);
+            ReifExpr::EqMod(rem)
+        }
+        ReifExpr::EqMul(mul) => {
+            let mut mul = *mul;
+            mul.lhs = map_atom(mul.lhs, f);
+            mul.x = map_atom(mul.x, f);
+            mul.y = map_atom(mul.y, f);
+            ReifExpr::EqMul(mul)
+        }
+        // the literals are not sorted, as each one is attached to a value
+        ReifExpr::Channel(ch) => ReifExpr::Channel(Channel {
+            var: map_atom(ch.var, f),
+            first: ch.first,
+            lits: ch.lits.iter().map(|l| map_lit(*l, f)).collect(),
+        }),
+    }
+}
+
+fn lit_role(kind: u8, lit: Lit) -> Role {
+    (kind, lit.svar().is_plus() as IntCst, lit.bound_value().as_int())
+}
+
+fn atom_role(kind: u8, atom: IAtom, cst: IntCst) -> Role {
+    (kind, atom.shift, cst)
+}
+
+/// Returns each variable of the expression with its role in it.
+fn roles(expr: &ReifExpr) -> Vec<(VarRef, Role)> {
+    let item_role =
+        |kind: u8, item: &NFEqMaxItem| (item.var.variable(), (kind, item.var.is_plus() as IntCst, item.cst));
+    match expr {
+        ReifExpr::Lit(l) => vec![(l.variable(), lit_role(0, *l))],
+        ReifExpr::MaxDiff(diff) => vec![(diff.b, (1, diff.ub, 0)), (diff.a, (2, diff.ub, 0))],
+        ReifExpr::Or(lits) => lits.iter().map(|l| (l.variable(), lit_role(3, *l))).collect(),
+        ReifExpr::And(lits) => lits.iter().map(|l| (l.variable(), lit_role(4, *l))).collect(),
+        ReifExpr::Linear(lin) => lin
+            .sum
+            .iter()
+            .map(|item| (item.var, (5, item.factor, item.or_zero as IntCst)))
+            .collect(),
+        ReifExpr::EqMax(em) => std::iter::once(item_role(6, &em.lhs))
+            .chain(em.rhs.iter().map(|item| item_role(7, item)))
+            .collect(),
+        ReifExpr::EqAbs(abs) => vec![
+            (abs.lhs.var.into(), atom_role(8, abs.lhs, 0)),
+            (abs.arg.var.into(), atom_role(9, abs.arg, 0)),
+        ],
+        ReifExpr::EqDiv(div) => vec![
+            (div.lhs.var.into(), atom_role(10, div.lhs, div.denominator)),
+            (div.numerator.var.into(), atom_role(11, div.numerator, div.denominator)),
+        ],
+        ReifExpr::EqMod(rem) => vec![
+            (rem.lhs.var.into(), atom_role(12, rem.lhs, rem.denominator)),
+            (rem.numerator.var.into(), atom_role(13, rem.numerator, rem.denominator)),
+        ],
+        // the two factors have the same role, as the multiplication is commutative
+        ReifExpr::EqMul(mul) => vec![
+            (mul.lhs.var.into(), atom_role(14, mul.lhs, 0)),
+            (mul.x.var.into(), atom_role(15, mul.x, 0)),
+            (mul.y.var.into(), atom_role(15, mul.y, 0)),
+        ],
+        // each literal has a distinct role, given by the value it denotes
+        ReifExpr::Channel(ch) => std::iter::once((ch.var.var.into(), atom_role(16, ch.var, ch.first)))
+            .chain(
+                ch.lits
+                    .iter()
+                    .enumerate()
+                    .map(|(i, l)| (l.variable(), (17, ch.value(i), l.svar().is_plus() as IntCst))),
+            )
+            .collect(),
+    }
+}
+
+/// A signature of the constants of the expression that are not attached to a variable.
+fn constant_signature(expr: &ReifExpr) -> u64 {
+    match expr {
+        ReifExpr::Linear(lin) => hash(&(5u8, lin.upper_bound)),
+        ReifExpr::Lit(_) => hash(&0u8),
+        ReifExpr::MaxDiff(_) => hash(&1u8),
+        ReifExpr::Or(_) => hash(&3u8),
+        ReifExpr::And(_) => hash(&4u8),
+        ReifExpr::EqMax(_) => hash(&6u8),
+        ReifExpr::EqAbs(_) => hash(&8u8),
+        ReifExpr::EqDiv(_) => hash(&10u8),
+        ReifExpr::EqMod(_) => hash(&12u8),
+        ReifExpr::EqMul(_) => hash(&14u8),
+        ReifExpr::Channel(ch) => hash(&(16u8, ch.lits.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::extensions::AssignmentExt;
+    use crate::model::lang::expr::{lt, neq};
+    use itertools::Itertools;
+
+    type Model = crate::model::Model<String>;
+    type Solver = crate::solver::Solver<String>;
+
+    #[test]
+    fn symmetry_detection() {
+        let mut model = Model::new();
+        let x: Vec<IVar> = (0..4).map(|i| model.new_ivar(0, 5, format!("x{i}"))).collect();
+        let z = model.new_ivar(0, 5, "z");
+        for (i, j) in (0..4).tuple_combinations() {
+            model.enforce(neq(x[i], x[j]), []);
+        }
+        // x0 is distinguished from the others
+        model.enforce(lt(x[0], z), []);
+        // variables with a fixed value are trivially interchangeable and ignored
+        model.new_ivar(3, 3, "c1");
+        model.new_ivar(3, 3, "c2");
+
+        let var = |v: IVar| VarRef::from(v);
+        let groups = interchangeable_variables(&model, &[]);
+        assert_eq!(groups, vec![vec![var(x[1]), var(x[2]), var(x[3])]]);
+        // protecting a variable removes it from the groups
+        let groups = interchangeable_variables(&model, &[var(x[3])]);
+        assert_eq!(groups, vec![vec![var(x[1]), var(x[2])]]);
+
+        assert_eq!(break_symmetries(&mut model, &[]), 2);
+        let mut solver = Solver::new(model);
+        let sol = solver.solve().unwrap().unwrap();
+        let value = |v: IVar| sol.domain_of(v).0;
+        assert!(value(x[1]) < value(x[2]) && value(x[2]) < value(x[3]));
+        assert!(value(x[0]) < value(z));
+    }
+}
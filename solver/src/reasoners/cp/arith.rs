@@ -0,0 +1,387 @@
+use crate::core::state::{Cause, Domains, Explanation};
+use crate::core::{IntCst, Lit, INT_CST_MAX, INT_CST_MIN};
+use crate::model::lang::arith;
+use crate::reasoners::cp::{Propagator, PropagatorId, SignedTerm, Watches};
+use crate::reasoners::Contradiction;
+use num_integer::{div_ceil, div_floor};
+
+/// Converts a value computed with extended precision back to the range of representable bounds.
+/// Bounds outside of this range are weakened to the closest representable value.
+fn clamp(value: i64) -> IntCst {
+    value.clamp(INT_CST_MIN as i64, INT_CST_MAX as i64) as IntCst
+}
+
+fn watch_both_bounds(term: SignedTerm, id: PropagatorId, context: &mut Watches) {
+    context.add_watch(term.var, id);
+    context.add_watch(term.var.neg(), id);
+}
+
+// =========== Abs ===========
+
+/// Propagator for the constraint `lhs = |arg|`
+#[derive(Clone, Debug)]
+pub(super) struct EqAbs {
+    lhs: SignedTerm,
+    arg: SignedTerm,
+}
+
+impl EqAbs {
+    pub fn new(abs: &arith::EqAbs) -> EqAbs {
+        EqAbs {
+            lhs: abs.lhs.into(),
+            arg: abs.arg.into(),
+        }
+    }
+}
+
+impl Propagator for EqAbs {
+    fn setup(&self, id: PropagatorId, context: &mut Watches) {
+        watch_both_bounds(self.lhs, id, context);
+        watch_both_bounds(self.arg, id, context);
+    }
+
+    fn propagate(&self, domains: &mut Domains, cause: Cause) -> Result<(), Contradiction> {
+        let (lhs, arg) = (self.lhs, self.arg);
+
+        // lhs from arg
+        domains.set(lhs.leq(arg.ub(domains).max(-arg.lb(domains))), cause)?;
+        let lb = if arg.lb(domains) >= 0 {
+            arg.lb(domains)
+        } else if arg.ub(domains) <= 0 {
+            -arg.ub(domains)
+        } else {
+            0
+        };
+        domains.set(lhs.geq(lb), cause)?;
+
+        // arg from lhs
+        let lhs_ub = lhs.ub(domains);
+        domains.set(arg.leq(lhs_ub), cause)?;
+        domains.set(arg.geq(-lhs_ub), cause)?;
+        let lhs_lb = lhs.lb(domains);
+        if lhs_lb > 0 {
+            // arg is outside of ]-lhs_lb, lhs_lb[, if one side is excluded it must be on the other one
+            if arg.lb(domains) > -lhs_lb {
+                domains.set(arg.geq(lhs_lb), cause)?;
+            } else if arg.ub(domains) < lhs_lb {
+                domains.set(arg.leq(-lhs_lb), cause)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn explain(&self, literal: Lit, state: &Domains, out_explanation: &mut Explanation) {
+        let (lhs, arg) = (self.lhs, self.arg);
+        let svar = literal.svar();
+        let bound = literal.bound_value().as_int();
+        if svar == lhs.var {
+            // lhs <= u  <=  -u <= arg <= u
+            let u = bound + lhs.cst;
+            out_explanation.push(arg.leq(u));
+            out_explanation.push(arg.geq(-u));
+        } else if svar == lhs.var.neg() {
+            // lhs >= l  <=  arg >= l  \/  arg <= -l
+            let l = lhs.cst - bound;
+            if l > 0 {
+                if state.entails(arg.geq(l)) {
+                    out_explanation.push(arg.geq(l));
+                } else {
+                    out_explanation.push(arg.leq(-l));
+                }
+            }
+        } else if svar == arg.var {
+            // arg <= u  <=  lhs <= u  \/  (lhs >= -u /\ arg < -u)
+            let u = bound + arg.cst;
+            if state.entails(lhs.leq(u)) {
+                out_explanation.push(lhs.leq(u));
+            } else {
+                out_explanation.push(lhs.geq(-u));
+                out_explanation.push(arg.leq(-u - 1));
+            }
+        } else {
+            debug_assert_eq!(svar, arg.var.neg());
+            // arg >= l  <=  lhs <= -l  \/  (lhs >= l /\ arg > -l)
+            let l = arg.cst - bound;
+            if state.entails(lhs.leq(-l)) {
+                out_explanation.push(lhs.leq(-l));
+            } else {
+                out_explanation.push(lhs.geq(l));
+                out_explanation.push(arg.geq(-l + 1));
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Propagator> {
+        Box::new(self.clone())
+    }
+}
+
+// =========== Div ===========
+
+/// Propagator for the constraint `lhs = numerator / denominator` where the division is truncated toward zero.
+///
+/// A negative denominator is handled by negating the left hand side, so that the propagator only
+/// deals with a non-decreasing function of the numerator.
+#[derive(Clone, Debug)]
+pub(super) struct EqDiv {
+    lhs: SignedTerm,
+    numerator: SignedTerm,
+    /// Strictly positive denominator
+    denominator: IntCst,
+}
+
+impl EqDiv {
+    pub fn new(div: &arith::EqDiv) -> EqDiv {
+        let lhs: SignedTerm = div.lhs.into();
+        // x / d = y   <=>   x / (-d) = -y
+        let (lhs, denominator) = if div.denominator > 0 {
+            (lhs, div.denominator)
+        } else {
+            (lhs.neg(), -div.denominator)
+        };
+        EqDiv {
+            lhs,
+            numerator: div.numerator.into(),
+            denominator,
+        }
+    }
+
+    /// Value of the left hand side for the given value of the numerator.
+    fn quotient(&self, numerator: IntCst) -> IntCst {
+        numerator / self.denominator
+    }
+
+    /// Smallest numerator whose quotient is greater than or equal to `quotient`.
+    fn min_numerator(&self, quotient: IntCst) -> IntCst {
+        let (q, d) = (quotient as i64, self.denominator as i64);
+        clamp(if q > 0 { q * d } else { (q - 1) * d + 1 })
+    }
+
+    /// Largest numerator whose quotient is less than or equal to `quotient`.
+    fn max_numerator(&self, quotient: IntCst) -> IntCst {
+        // truncated division is symmetric around zero
+        -self.min_numerator(-quotient)
+    }
+}
+
+impl Propagator for EqDiv {
+    fn setup(&self, id: PropagatorId, context: &mut Watches) {
+        watch_both_bounds(self.lhs, id, context);
+        watch_both_bounds(self.numerator, id, context);
+    }
+
+    fn propagate(&self, domains: &mut Domains, cause: Cause) -> Result<(), Contradiction> {
+        let (lhs, num) = (self.lhs, self.numerator);
+        domains.set(lhs.geq(self.quotient(num.lb(domains))), cause)?;
+        domains.set(lhs.leq(self.quotient(num.ub(domains))), cause)?;
+        domains.set(num.geq(self.min_numerator(lhs.lb(domains))), cause)?;
+        domains.set(num.leq(self.max_numerator(lhs.ub(domains))), cause)?;
+        Ok(())
+    }
+
+    fn explain(&self, literal: Lit, _state: &Domains, out_explanation: &mut Explanation) {
+        let (lhs, num) = (self.lhs, self.numerator);
+        let svar = literal.svar();
+        let bound = literal.bound_value().as_int();
+        if svar == lhs.var {
+            let u = bound + lhs.cst;
+            out_explanation.push(num.leq(self.max_numerator(u)));
+        } else if svar == lhs.var.neg() {
+            let l = lhs.cst - bound;
+            out_explanation.push(num.geq(self.min_numerator(l)));
+        } else if svar == num.var {
+            // any numerator above u would have a quotient of at least `quotient(u + 1)`
+            let u = bound + num.cst;
+            out_explanation.push(lhs.leq(self.quotient(u + 1) - 1));
+        } else {
+            debug_assert_eq!(svar, num.var.neg());
+            let l = num.cst - bound;
+            out_explanation.push(lhs.geq(self.quotient(l - 1) + 1));
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Propagator> {
+        Box::new(self.clone())
+    }
+}
+
+// =========== Mod ===========
+
+/// Propagator for the constraint `lhs = numerator % denominator` where the remainder has the sign of the numerator.
+#[derive(Clone, Debug)]
+pub(super) struct EqMod {
+    lhs: SignedTerm,
+    numerator: SignedTerm,
+    /// Absolute value of the denominator (its sign has no impact on the remainder).
+    modulus: IntCst,
+}
+
+impl EqMod {
+    pub fn new(rem: &arith::EqMod) -> EqMod {
+        EqMod {
+            lhs: rem.lhs.into(),
+            numerator: rem.numerator.into(),
+            modulus: rem.denominator.abs(),
+        }
+    }
+}
+
+impl Propagator for EqMod {
+    fn setup(&self, id: PropagatorId, context: &mut Watches) {
+        watch_both_bounds(self.lhs, id, context);
+        watch_both_bounds(self.numerator, id, context);
+    }
+
+    fn propagate(&self, domains: &mut Domains, cause: Cause) -> Result<(), Contradiction> {
+        let (lhs, num) = (self.lhs, self.numerator);
+        let m = self.modulus;
+
+        // the remainder is strictly smaller than the modulus and has the sign of the numerator,
+        // its absolute value never exceeds the one of the numerator
+        domains.set(lhs.leq((m - 1).min(num.ub(domains).max(0))), cause)?;
+        domains.set(lhs.geq((1 - m).max(num.lb(domains).min(0))), cause)?;
+
+        // a non-zero remainder has the sign of the numerator and is smaller in absolute value
+        if lhs.lb(domains) > 0 {
+            domains.set(num.geq(lhs.lb(domains)), cause)?;
+        }
+        if lhs.ub(domains) < 0 {
+            domains.set(num.leq(lhs.ub(domains)), cause)?;
+        }
+
+        // if all values of the numerator have the same quotient, the remainder is a shifted copy of the numerator
+        let (num_lb, num_ub) = (num.lb(domains), num.ub(domains));
+        let quotient = num_lb / m;
+        if quotient == num_ub / m && (num_lb >= 0 || num_ub <= 0) {
+            let shift = quotient * m;
+            domains.set(lhs.geq(num_lb - shift), cause)?;
+            domains.set(lhs.leq(num_ub - shift), cause)?;
+            domains.set(num.geq(lhs.lb(domains) + shift), cause)?;
+            domains.set(num.leq(lhs.ub(domains) + shift), cause)?;
+        }
+        Ok(())
+    }
+
+    fn explain(&self, _literal: Lit, state: &Domains, out_explanation: &mut Explanation) {
+        // All inferences are monotonic in the bounds of the two terms: the current bounds
+        // (that were the ones available at propagation time) are sufficient to entail the literal.
+        for term in [self.lhs, self.numerator] {
+            out_explanation.push(term.geq(term.lb(state)));
+            out_explanation.push(term.leq(term.ub(state)));
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Propagator> {
+        Box::new(self.clone())
+    }
+}
+
+// =========== Mul ===========
+
+/// Propagator for the constraint `lhs = x * y`, that enforces bound consistency on each term with respect to the
+/// bounds of the two others. Bounds are computed with extended precision to avoid overflows.
+#[derive(Clone, Debug)]
+pub(super) struct EqMul {
+    lhs: SignedTerm,
+    x: SignedTerm,
+    y: SignedTerm,
+}
+
+/// Interval of values `[lb, ub]`, with extended precision.
+type Interval = (i64, i64);
+
+fn bounds(term: SignedTerm, domains: &Domains) -> Interval {
+    (term.lb(domains) as i64, term.ub(domains) as i64)
+}
+
+/// Smallest interval containing the products `a * b` for all `a` and `b` in the given intervals.
+fn product(a: Interval, b: Interval) -> Interval {
+    let corners = [a.0 * b.0, a.0 * b.1, a.1 * b.0, a.1 * b.1];
+    (*corners.iter().min().unwrap(), *corners.iter().max().unwrap())
+}
+
+/// Smallest interval containing all integers `x` such that `x * y = z` for some `z` and `y` in the given intervals,
+/// or `None` if there are no such integers. Returns the unbounded interval if `z` and `y` may both be zero.
+fn quotient(z: Interval, y: Interval) -> Option<Interval> {
+    if z.0 <= 0 && 0 <= z.1 && y.0 <= 0 && 0 <= y.1 {
+        return Some((i64::MIN, i64::MAX));
+    }
+    // the quotient is monotonic on each side of zero, consider the negative and positive parts of `y` separately
+    let parts = [(y.0, y.1.min(-1)), (y.0.max(1), y.1)];
+    parts
+        .into_iter()
+        .filter(|(lb, ub)| lb <= ub)
+        .filter_map(|(lb, ub)| {
+            let corners = [(z.0, lb), (z.0, ub), (z.1, lb), (z.1, ub)];
+            let min = corners.iter().map(|&(n, d)| div_ceil(n, d)).min().unwrap();
+            let max = corners.iter().map(|&(n, d)| div_floor(n, d)).max().unwrap();
+            // with a single value of `z` and `y` the product may not be divisible, leaving no integer
+            (min <= max).then_some((min, max))
+        })
+        .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+}
+
+impl EqMul {
+    pub fn new(mul: &arith::EqMul) -> EqMul {
+        EqMul {
+            lhs: mul.lhs.into(),
+            x: mul.x.into(),
+            y: mul.y.into(),
+        }
+    }
+
+    /// Restricts the term to the given interval, that is empty if `None`.
+    fn restrict(
+        term: SignedTerm,
+        interval: Option<Interval>,
+        domains: &mut Domains,
+        cause: Cause,
+    ) -> Result<(), Contradiction> {
+        let (lb, ub) = interval.unwrap_or((1, 0));
+        if lb > term.lb(domains) as i64 {
+            domains.set(term.geq(clamp(lb)), cause)?;
+        }
+        if ub < term.ub(domains) as i64 {
+            domains.set(term.leq(clamp(ub)), cause)?;
+        }
+        Ok(())
+    }
+}
+
+impl Propagator for EqMul {
+    fn setup(&self, id: PropagatorId, context: &mut Watches) {
+        watch_both_bounds(self.lhs, id, context);
+        watch_both_bounds(self.x, id, context);
+        watch_both_bounds(self.y, id, context);
+    }
+
+    fn propagate(&self, domains: &mut Domains, cause: Cause) -> Result<(), Contradiction> {
+        let (lhs, x, y) = (self.lhs, self.x, self.y);
+        let lhs_bounds = product(bounds(x, domains), bounds(y, domains));
+        Self::restrict(lhs, Some(lhs_bounds), domains, cause)?;
+        let x_bounds = quotient(bounds(lhs, domains), bounds(y, domains));
+        Self::restrict(x, x_bounds, domains, cause)?;
+        let y_bounds = quotient(bounds(lhs, domains), bounds(x, domains));
+        Self::restrict(y, y_bounds, domains, cause)?;
+        Ok(())
+    }
+
+    fn explain(&self, literal: Lit, state: &Domains, out_explanation: &mut Explanation) {
+        // Each term is only restricted from the bounds of the two other ones, that are sufficient to entail the
+        // literal as the inferences are monotonic in these bounds.
+        // If the variable of the literal appears in another term (`x * x`), the bounds of this term are needed as well.
+        let terms = [self.lhs, self.x, self.y];
+        let var = literal.variable();
+        let occurrences = terms.iter().filter(|t| t.var.variable() == var).count();
+        for term in terms {
+            if term.var.variable() != var || occurrences > 1 {
+                out_explanation.push(term.geq(term.lb(state)));
+                out_explanation.push(term.leq(term.ub(state)));
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Propagator> {
+        Box::new(self.clone())
+    }
+}
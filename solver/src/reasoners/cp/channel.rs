@@ -0,0 +1,145 @@
+use crate::core::state::{Cause, Domains, Explanation};
+use crate::core::{IntCst, Lit, SignedVar};
+use crate::model::lang::channel;
+use crate::reasoners::cp::{Propagator, PropagatorId, SignedTerm, Watches};
+use crate::reasoners::Contradiction;
+
+/// Propagator for the constraint `var = first + i <=> lits[i]`.
+///
+/// The bounds of `var` skip the values whose literal is false, the literals of values outside the bounds
+/// are made false, and the literal of the value is made true once `var` is fixed (and reciprocally).
+#[derive(Clone, Debug)]
+pub(super) struct Channel {
+    var: SignedTerm,
+    first: IntCst,
+    lits: Vec<Lit>,
+}
+
+impl Channel {
+    pub fn new(ch: &channel::Channel) -> Channel {
+        Channel {
+            var: ch.var.into(),
+            first: ch.first,
+            lits: ch.lits.clone(),
+        }
+    }
+
+    fn last(&self) -> IntCst {
+        self.first + self.lits.len() as IntCst - 1
+    }
+
+    /// Literal denoting the given value, that must be in `[first, last]`
+    fn lit(&self, value: IntCst) -> Lit {
+        self.lits[(value - self.first) as usize]
+    }
+}
+
+impl Propagator for Channel {
+    fn setup(&self, id: PropagatorId, context: &mut Watches) {
+        context.add_watch(self.var.var, id);
+        context.add_watch(self.var.var.neg(), id);
+        for l in &self.lits {
+            context.add_watch(SignedVar::plus(l.variable()), id);
+            context.add_watch(SignedVar::minus(l.variable()), id);
+        }
+    }
+
+    fn propagate(&self, domains: &mut Domains, cause: Cause) -> Result<(), Contradiction> {
+        let (var, first, last) = (self.var, self.first, self.last());
+        domains.set(var.geq(first), cause)?;
+        domains.set(var.leq(last), cause)?;
+
+        // a true literal fixes the value
+        for value in first..=last {
+            if domains.entails(self.lit(value)) {
+                domains.set(var.geq(value), cause)?;
+                domains.set(var.leq(value), cause)?;
+            }
+        }
+
+        // the bounds skip the values whose literal is false
+        let mut lb = var.lb(domains);
+        while lb <= last && domains.entails(!self.lit(lb)) {
+            lb += 1;
+        }
+        domains.set(var.geq(lb), cause)?;
+        let mut ub = var.ub(domains);
+        while ub >= first && domains.entails(!self.lit(ub)) {
+            ub -= 1;
+        }
+        domains.set(var.leq(ub), cause)?;
+
+        // the values outside the bounds are excluded
+        let (lb, ub) = (var.lb(domains), var.ub(domains));
+        for value in (first..lb).chain(ub + 1..=last) {
+            domains.set(!self.lit(value), cause)?;
+        }
+        if lb == ub {
+            domains.set(self.lit(lb), cause)?;
+        }
+        Ok(())
+    }
+
+    fn explain(&self, literal: Lit, state: &Domains, out_explanation: &mut Explanation) {
+        let (var, first, last) = (self.var, self.first, self.last());
+        let svar = literal.svar();
+        let bound = literal.bound_value().as_int();
+        if svar == var.var.neg() {
+            // var >= l  <=  lit(v) for some v >= l  \/  (var >= m  /\  !lit(m) /\ ... /\ !lit(l-1))
+            let l = var.cst - bound;
+            if l <= first {
+                return;
+            }
+            if let Some(value) = (l..=last).find(|&v| state.entails(self.lit(v))) {
+                out_explanation.push(self.lit(value));
+                return;
+            }
+            let m = var.lb(state).max(first);
+            if m > first {
+                out_explanation.push(var.geq(m));
+            }
+            for value in m..l.min(last + 1) {
+                out_explanation.push(!self.lit(value));
+            }
+        } else if svar == var.var {
+            // var <= u  <=  lit(v) for some v <= u  \/  (var <= m  /\  !lit(u+1) /\ ... /\ !lit(m))
+            let u = bound + var.cst;
+            if u >= last {
+                return;
+            }
+            if let Some(value) = (first..=u).find(|&v| state.entails(self.lit(v))) {
+                out_explanation.push(self.lit(value));
+                return;
+            }
+            let m = var.ub(state).min(last);
+            if m < last {
+                out_explanation.push(var.leq(m));
+            }
+            for value in (u + 1).max(first)..=m {
+                out_explanation.push(!self.lit(value));
+            }
+        } else {
+            let i = self
+                .lits
+                .iter()
+                .position(|l| l.variable() == literal.variable())
+                .expect("Literal not in the channel");
+            let value = first + i as IntCst;
+            if self.lits[i].entails(literal) {
+                // lit(v)  <=  var = v
+                out_explanation.push(var.geq(value));
+                out_explanation.push(var.leq(value));
+            } else if state.entails(var.leq(value - 1)) {
+                // !lit(v)  <=  var < v
+                out_explanation.push(var.leq(value - 1));
+            } else {
+                // !lit(v)  <=  var > v
+                out_explanation.push(var.geq(value + 1));
+            }
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Propagator> {
+        Box::new(self.clone())
+    }
+}
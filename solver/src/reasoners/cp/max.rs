@@ -0,0 +1,151 @@
+use crate::core::state::{Cause, Domains, Explanation};
+use crate::core::{IntCst, Lit};
+use crate::model::lang::max::NFEqMax;
+use crate::reasoners::cp::{Propagator, PropagatorId, SignedTerm, Watches};
+use crate::reasoners::Contradiction;
+
+/// Propagator for the constraint `lhs = max { e | e \in elements }` where the elements might be optional.
+///
+/// It assumes that no variable appears twice in the constraint (as enforced by [NFEqMax]).
+#[derive(Clone, Debug)]
+pub(super) struct EqMax {
+    lhs: SignedTerm,
+    elements: Vec<SignedTerm>,
+}
+
+impl EqMax {
+    pub fn new(em: &NFEqMax) -> EqMax {
+        EqMax {
+            lhs: em.lhs.into(),
+            elements: em.rhs.iter().copied().map(SignedTerm::from).collect(),
+        }
+    }
+
+    /// Adds to the explanation a set of literals that imply that no element other than `except` can reach `value`.
+    /// Absent elements are explained by their absence.
+    fn explain_all_below(&self, value: IntCst, except: Option<usize>, domains: &Domains, out: &mut Explanation) {
+        for (i, &e) in self.elements.iter().enumerate() {
+            if Some(i) == except {
+                continue;
+            }
+            if e.is_absent(domains) {
+                out.push(!e.presence(domains));
+            } else {
+                out.push(e.leq(value - 1));
+            }
+        }
+    }
+}
+
+impl Propagator for EqMax {
+    fn setup(&self, id: PropagatorId, context: &mut Watches) {
+        context.add_watch(self.lhs.var, id);
+        context.add_watch(self.lhs.var.neg(), id);
+        for e in &self.elements {
+            context.add_watch(e.var, id);
+            context.add_watch(e.var.neg(), id);
+        }
+    }
+
+    fn propagate(&self, domains: &mut Domains, cause: Cause) -> Result<(), Contradiction> {
+        // lhs is bounded by the bounds of the elements that may be present
+        let mut max_ub = None;
+        let mut max_lb = None;
+        for &e in &self.elements {
+            if e.is_absent(domains) {
+                continue;
+            }
+            max_ub = max_ub.max(Some(e.ub(domains)));
+            if e.is_present(domains) {
+                max_lb = max_lb.max(Some(e.lb(domains)));
+            }
+        }
+        if let Some(ub) = max_ub {
+            domains.set(self.lhs.leq(ub), cause)?;
+        }
+        if let Some(lb) = max_lb {
+            domains.set(self.lhs.geq(lb), cause)?;
+        }
+
+        // no element may be greater than lhs
+        let lhs_ub = self.lhs.ub(domains);
+        for &e in &self.elements {
+            if !e.is_absent(domains) {
+                domains.set(e.leq(lhs_ub), cause)?;
+            }
+        }
+
+        // at least one element must reach the lower bound of lhs
+        let lhs_lb = self.lhs.lb(domains);
+        let mut supports = self
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| !e.is_absent(domains) && e.ub(domains) >= lhs_lb);
+        match (supports.next(), supports.next()) {
+            (None, _) => {
+                let mut expl = Explanation::new();
+                expl.push(self.lhs.geq(lhs_lb));
+                self.explain_all_below(lhs_lb, None, domains, &mut expl);
+                Err(Contradiction::Explanation(expl))
+            }
+            (Some((_, &e)), None) => {
+                domains.set(e.presence(domains), cause)?;
+                domains.set(e.geq(lhs_lb), cause)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn explain(&self, literal: Lit, state: &Domains, out_explanation: &mut Explanation) {
+        let svar = literal.svar();
+        let bound = literal.bound_value().as_int();
+        if svar == self.lhs.var {
+            // lhs <= u : all elements are either absent or below u
+            let u = bound + self.lhs.cst;
+            for &e in &self.elements {
+                if e.is_absent(state) {
+                    out_explanation.push(!e.presence(state));
+                } else {
+                    out_explanation.push(e.leq(u));
+                }
+            }
+        } else if svar == self.lhs.var.neg() {
+            // lhs >= l : some present element is above l
+            let l = self.lhs.cst - bound;
+            let e = self
+                .elements
+                .iter()
+                .copied()
+                .find(|&e| e.is_present(state) && e.lb(state) >= l)
+                .expect("No element supporting the lower bound of lhs");
+            out_explanation.push(e.presence(state));
+            out_explanation.push(e.geq(l));
+        } else if let Some(&e) = self.elements.iter().find(|e| e.var == svar) {
+            // e <= u : lhs is below u
+            let u = bound + e.cst;
+            out_explanation.push(self.lhs.leq(u));
+        } else if let Some((i, &e)) = self.elements.iter().enumerate().find(|(_, e)| e.var.neg() == svar) {
+            // e >= l : e is the only element that can reach the lower bound of lhs
+            let l = e.cst - bound;
+            out_explanation.push(self.lhs.geq(l));
+            self.explain_all_below(l, Some(i), state, out_explanation);
+        } else {
+            // presence of an element: it is the only element that can reach the lower bound of lhs
+            let (i, _) = self
+                .elements
+                .iter()
+                .enumerate()
+                .find(|(_, e)| e.presence(state) == literal)
+                .expect("Literal not related to this constraint");
+            let l = self.lhs.lb(state);
+            out_explanation.push(self.lhs.geq(l));
+            self.explain_all_below(l, Some(i), state, out_explanation);
+        }
+    }
+
+    fn clone_box(&self) -> Box<dyn Propagator> {
+        Box::new(self.clone())
+    }
+}
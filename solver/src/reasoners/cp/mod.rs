@@ -1,12 +1,21 @@
 #![allow(unused)] // TODO: remove once stabilized
 
+mod arith;
+mod channel;
+mod max;
+
 use crate::backtrack::{Backtrack, DecLvl, ObsTrailCursor};
 use crate::collections::ref_store::RefVec;
 use crate::collections::*;
 use crate::core::state::{Cause, Domains, Event, Explanation, InvalidUpdate};
-use crate::core::{IntCst, Lit, SignedVar, VarRef};
+use crate::core::{IntCst, Lit, Relation, SignedVar, UpperBound, VarRef};
 use crate::create_ref_type;
+use crate::model::lang::arith::{EqAbs, EqDiv, EqMod, EqMul};
+use crate::model::lang::channel::Channel;
 use crate::model::lang::linear::NFLinearLeq;
+use crate::model::lang::max::{NFEqMax, NFEqMaxItem};
+use crate::model::lang::IAtom;
+use crate::reasoners::cp::max::EqMax;
 use crate::reasoners::{Contradiction, ReasonerId, Theory};
 use num_integer::{div_ceil, div_floor};
 use std::cmp::Ordering;
@@ -55,19 +64,61 @@ impl LinearSumLeq {
             None => 0.max(int_part),
         }
     }
-    fn set_ub(&self, elem: SumElem, ub: IntCst, domains: &mut Domains, cause: Cause) -> Result<bool, InvalidUpdate> {
+    /// Lower bound of the element given only the root domains of its variable and presence.
+    fn get_root_lower_bound(&self, elem: SumElem, domains: &Domains) -> IntCst {
+        let root_bound = |svar: SignedVar| domains.get_bound_at_level(svar, DecLvl::ROOT).as_int();
+        let int_part = match elem.factor.cmp(&0) {
+            Ordering::Less => root_bound(SignedVar::plus(elem.var)) * elem.factor,
+            Ordering::Equal => 0,
+            Ordering::Greater => -root_bound(SignedVar::minus(elem.var)) * elem.factor,
+        };
+        let prez = domains.presence(elem.var);
+        if domains.entailed_at_level(prez, DecLvl::ROOT) {
+            int_part
+        } else if domains.entailed_at_level(!prez, DecLvl::ROOT) {
+            0
+        } else {
+            0.min(int_part)
+        }
+    }
+
+    /// Returns the amount by which the lower bounds of the elements exceed what is needed to entail `literal`
+    /// (or a contradiction if it is `Lit::FALSE`), or 0 if `literal` is not on the variable of a single element.
+    ///
+    /// The explanation of `literal` remains valid when the lower bounds of the elements are decreased by this amount.
+    fn slack(&self, literal: Lit, domains: &Domains) -> IntCst {
+        let others_lb: IntCst = self
+            .elements
+            .iter()
+            .filter(|e| e.var != literal.variable())
+            .map(|&e| self.get_lower_bound(e, domains))
+            .sum();
+        if literal == Lit::FALSE {
+            // the sum must exceed the upper bound
+            return (others_lb - self.ub - 1).max(0);
+        }
+        let mut explained = self.elements.iter().filter(|e| e.var == literal.variable());
+        let (Some(elem), None) = (explained.next(), explained.next()) else {
+            return 0;
+        };
+        // upper bound `c` on the term `factor * var` that the literal enforces
+        let c = match (elem.factor.cmp(&0), literal.relation()) {
+            (Ordering::Greater, Relation::Leq) => elem.factor * literal.value(),
+            (Ordering::Less, Relation::Gt) => elem.factor * (literal.value() + 1),
+            _ => return 0,
+        };
+        // the term is at most `ub - others_lb`, which entails the literal iff `ub - others_lb < c + |factor|`
+        let slack = others_lb - (self.ub - c - elem.factor.abs() + 1);
+        slack.max(0)
+    }
+
+    /// Returns the bound update that enforces `elem <= ub`.
+    fn ub_update(&self, elem: SumElem, ub: IntCst, domains: &Domains) -> (SignedVar, UpperBound) {
         debug_assert!(elem.or_zero || domains.present(elem.var) == Some(true));
-        // println!(
-        //     "  {:?} : [{}, {}]    ub: {ub}   -> {}",
-        //     elem.var,
-        //     domains.lb(elem.var),
-        //     domains.ub(elem.var),
-        //     ub / elem.factor,
-        // );
         match elem.factor.cmp(&0) {
-            Ordering::Less => domains.set_lb(elem.var, div_ceil(ub, elem.factor), cause),
+            Ordering::Less => (SignedVar::minus(elem.var), UpperBound::lb(div_ceil(ub, elem.factor))),
             Ordering::Equal => unreachable!(),
-            Ordering::Greater => domains.set_ub(elem.var, div_floor(ub, elem.factor), cause),
+            Ordering::Greater => (SignedVar::plus(elem.var), UpperBound::ub(div_floor(ub, elem.factor))),
         }
     }
 
@@ -119,40 +170,71 @@ impl Propagator for LinearSumLeq {
             self.explain(Lit::FALSE, domains, &mut expl);
             return Err(Contradiction::Explanation(expl));
         }
+        // all updates are computed first and applied at once, so that a variable appearing in several
+        // elements is only updated once
+        let mut updates = Vec::new();
         for &e in &self.elements {
             let lb = self.get_lower_bound(e, domains);
             let ub = self.get_upper_bound(e, domains);
             debug_assert!(lb <= ub);
             if ub - lb > f {
-                // println!("  problem on: {e:?} {lb} {ub}");
-                match self.set_ub(e, f + lb, domains, cause) {
-                    Ok(true) => {} // println!("    propagated: {e:?} <= {}", f + lb),
-                    Ok(false) => {}
-                    Err(e) => {
-                        // println!("    invalid update");
-                        return Err(e.into());
-                    }
-                }
+                updates.push(self.ub_update(e, f + lb, domains));
             }
         }
-        // println!("AFTER PROP");
-        // self.print(domains);
-        // println!();
+        domains.set_bounds_batch(&updates, cause)?;
         Ok(())
     }
 
+    /// The explanation is generalized using the slack of the constraint (see [LinearSumLeq::slack]).
+    /// The elements whose lower bound is the smallest increase on their root lower bound are left out of
+    /// the explanation while the slack allows it, and the bound of the next one is weakened with the remaining slack.
+    /// For instance, a conflict on an upper bound of the number of present actions is explained by only as many of
+    /// them as needed to exceed it, which generalizes the cuts on the objective learned when optimizing.
     fn explain(&self, literal: Lit, domains: &Domains, out_explanation: &mut Explanation) {
-        for e in &self.elements {
+        let mut slack = self.slack(literal, domains);
+        // elements that can be left out of the explanation, by increasing cost
+        let mut omitted = vec![false; self.elements.len()];
+        let mut weakened = vec![0; self.elements.len()];
+        if slack > 0 {
+            let mut candidates: Vec<(IntCst, usize)> = self
+                .elements
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| e.var != literal.variable())
+                .map(|(i, &e)| {
+                    (
+                        self.get_lower_bound(e, domains) - self.get_root_lower_bound(e, domains),
+                        i,
+                    )
+                })
+                .collect();
+            candidates.sort_unstable();
+            for (cost, i) in candidates {
+                if cost <= slack {
+                    omitted[i] = true;
+                    slack -= cost;
+                } else {
+                    let e = self.elements[i];
+                    if domains.present(e.var) == Some(true) && e.factor != 0 {
+                        weakened[i] = slack / e.factor.abs();
+                    }
+                    break;
+                }
+            }
+        }
+        for (i, e) in self.elements.iter().enumerate() {
+            if omitted[i] {
+                continue;
+            }
             if e.var != literal.variable() {
                 match e.factor.cmp(&0) {
-                    Ordering::Less => out_explanation.push(Lit::leq(e.var, domains.ub(e.var))),
+                    Ordering::Less => out_explanation.push(Lit::leq(e.var, domains.ub(e.var) + weakened[i])),
                     Ordering::Equal => {}
-                    Ordering::Greater => out_explanation.push(Lit::geq(e.var, domains.lb(e.var))),
+                    Ordering::Greater => out_explanation.push(Lit::geq(e.var, domains.lb(e.var) - weakened[i])),
                 }
             }
             if e.or_zero {
                 let prez = domains.presence(e.var);
-                // println!("{prez:?}, {:?}", domains.value(prez));
                 match domains.value(prez) {
                     Some(true) => out_explanation.push(prez),
                     Some(false) => out_explanation.push(!prez),
@@ -160,8 +242,6 @@ impl Propagator for LinearSumLeq {
                 }
             }
         }
-        // dbg!(&self);
-        // dbg!(&out_explanation.lits);
     }
 
     fn clone_box(&self) -> Box<dyn Propagator> {
@@ -169,6 +249,66 @@ impl Propagator for LinearSumLeq {
     }
 }
 
+// ========== Terms ===========
+
+/// A term `var + cst` where `var` is a signed view of a variable.
+#[derive(Clone, Copy, Debug)]
+struct SignedTerm {
+    var: SignedVar,
+    cst: IntCst,
+}
+
+impl SignedTerm {
+    /// Returns the term `-(var + cst)`
+    fn neg(self) -> Self {
+        SignedTerm {
+            var: self.var.neg(),
+            cst: -self.cst,
+        }
+    }
+    fn ub(self, domains: &Domains) -> IntCst {
+        domains.get_bound(self.var).as_int() + self.cst
+    }
+    fn lb(self, domains: &Domains) -> IntCst {
+        -domains.get_bound(self.var.neg()).as_int() + self.cst
+    }
+    /// Literal `var + cst <= value`
+    fn leq(self, value: IntCst) -> Lit {
+        Lit::from_parts(self.var, UpperBound::ub(value - self.cst))
+    }
+    /// Literal `var + cst >= value`
+    fn geq(self, value: IntCst) -> Lit {
+        Lit::from_parts(self.var.neg(), UpperBound::ub(self.cst - value))
+    }
+    fn presence(self, domains: &Domains) -> Lit {
+        domains.presence(self.var.variable())
+    }
+    fn is_absent(self, domains: &Domains) -> bool {
+        domains.entails(!self.presence(domains))
+    }
+    fn is_present(self, domains: &Domains) -> bool {
+        domains.entails(self.presence(domains))
+    }
+}
+
+impl From<NFEqMaxItem> for SignedTerm {
+    fn from(item: NFEqMaxItem) -> Self {
+        SignedTerm {
+            var: item.var,
+            cst: item.cst,
+        }
+    }
+}
+
+impl From<IAtom> for SignedTerm {
+    fn from(atom: IAtom) -> Self {
+        SignedTerm {
+            var: SignedVar::plus(atom.var.into()),
+            cst: atom.shift,
+        }
+    }
+}
+
 // ========== Constraint ===========
 
 create_ref_type!(PropagatorId);
@@ -266,6 +406,30 @@ impl Cp {
         self.add_propagator(propagator);
     }
 
+    pub fn add_eq_max_constraint(&mut self, em: &NFEqMax) {
+        self.add_propagator(EqMax::new(em));
+    }
+
+    pub fn add_eq_abs_constraint(&mut self, abs: &EqAbs) {
+        self.add_propagator(arith::EqAbs::new(abs));
+    }
+
+    pub fn add_eq_div_constraint(&mut self, div: &EqDiv) {
+        self.add_propagator(arith::EqDiv::new(div));
+    }
+
+    pub fn add_eq_mod_constraint(&mut self, rem: &EqMod) {
+        self.add_propagator(arith::EqMod::new(rem));
+    }
+
+    pub fn add_eq_mul_constraint(&mut self, mul: &EqMul) {
+        self.add_propagator(arith::EqMul::new(mul));
+    }
+
+    pub fn add_channel_constraint(&mut self, ch: &Channel) {
+        self.add_propagator(channel::Channel::new(ch));
+    }
+
     fn add_propagator(&mut self, propagator: impl Into<DynPropagator>) {
         // TODO: handle validity scopes
         let propagator = propagator.into();
@@ -362,3 +526,51 @@ impl Backtrack for Cp {
 //         BindingResult::Unsupported
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum_of(vars: &[VarRef], ub: IntCst) -> LinearSumLeq {
+        let elements = vars
+            .iter()
+            .map(|&var| SumElem {
+                factor: 1,
+                var,
+                or_zero: false,
+            })
+            .collect();
+        LinearSumLeq { elements, ub }
+    }
+
+    #[test]
+    fn test_generalized_explanations() {
+        let mut domains = Domains::new();
+        let vars: Vec<VarRef> = (0..5).map(|_| domains.new_var(0, 1)).collect();
+        let sum = sum_of(&vars, 2);
+        domains.save_state();
+        for &v in &vars[..4] {
+            domains.set_lb(v, 1, Cause::Decision).unwrap();
+        }
+        // the sum exceeds its bound, which only requires three of the variables
+        let mut expl = Explanation::new();
+        sum.explain(Lit::FALSE, &domains, &mut expl);
+        assert_eq!(expl.lits.len(), 3);
+        assert!(expl.lits.iter().all(|&l| domains.entails(l)));
+
+        // two of the variables are enough to force the last one to 0
+        let mut expl = Explanation::new();
+        sum.explain(Lit::leq(vars[4], 0), &domains, &mut expl);
+        assert_eq!(expl.lits.len(), 2);
+
+        // the lower bound of a variable is weakened with the slack: `x >= 5` entails `y <= 5` but `x >= 3` suffices
+        // to entail `y <= 7`
+        let x = domains.new_var(0, 10);
+        let y = domains.new_var(0, 10);
+        let sum = sum_of(&[x, y], 10);
+        domains.set_lb(x, 5, Cause::Decision).unwrap();
+        let mut expl = Explanation::new();
+        sum.explain(Lit::leq(y, 7), &domains, &mut expl);
+        assert_eq!(expl.lits, vec![Lit::geq(x, 3)]);
+    }
+}
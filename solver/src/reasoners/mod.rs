@@ -1,4 +1,5 @@
 use crate::backtrack::Backtrack;
+use crate::core::literals::Disjunction;
 use crate::core::state::{Cause, Explainer, InferenceCause};
 use crate::core::state::{Domains, Explanation, InvalidUpdate};
 use crate::core::Lit;
@@ -46,8 +47,29 @@ pub trait Theory: Backtrack + Send + 'static {
 
     fn propagate(&mut self, model: &mut Domains) -> Result<(), Contradiction>;
 
+    /// Returns true if the last propagation was interrupted before reaching a fixpoint (e.g. to give other
+    /// reasoners an opportunity to detect a conflict early), in which case the theory must be propagated
+    /// again even if no new event occurred.
+    fn has_deferred_propagation(&self) -> bool {
+        false
+    }
+
     fn explain(&mut self, literal: Lit, context: u32, model: &Domains, out_explanation: &mut Explanation);
 
+    /// Returns an estimate of the immediate impact that making `literal` true would have on this theory
+    /// (typically the magnitude of the resulting bound changes), or `None` if the literal has no known impact.
+    ///
+    /// This is intended to be used by search heuristics and should be cheap to compute.
+    fn decision_impact(&self, _literal: Lit, _model: &Domains) -> Option<u64> {
+        None
+    }
+
+    /// Moves to `out` the lemmas (clauses entailed by the constraints of the theory) that the theory found
+    /// since the last call and deems valuable enough to be permanently kept in the clause database.
+    ///
+    /// This is called by the solver after each propagation of the theory.
+    fn take_permanent_lemmas(&mut self, _model: &Domains, _out: &mut Vec<Disjunction>) {}
+
     fn print_stats(&self);
 
     fn clone_box(&self) -> Box<dyn Theory>;
@@ -58,6 +80,15 @@ pub enum Contradiction {
     InvalidUpdate(InvalidUpdate),
     Explanation(Explanation),
 }
+impl Display for Contradiction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Contradiction::InvalidUpdate(invalid) => write!(f, "{invalid}"),
+            Contradiction::Explanation(expl) => write!(f, "Inconsistent literals: {:?}", expl.lits),
+        }
+    }
+}
+impl std::error::Error for Contradiction {}
 impl From<InvalidUpdate> for Contradiction {
     fn from(empty: InvalidUpdate) -> Self {
         Contradiction::InvalidUpdate(empty)
@@ -110,6 +141,14 @@ impl Reasoners {
     pub fn theories(&self) -> impl Iterator<Item = (ReasonerId, &dyn Theory)> + '_ {
         self.writers().iter().map(|w| (*w, self.reasoner(*w)))
     }
+
+    /// Sums the estimated impacts of making `literal` true over all theories (see [Theory::decision_impact]).
+    /// Returns `None` if no theory has an estimate for this literal.
+    pub fn decision_impact(&self, literal: Lit, model: &Domains) -> Option<u64> {
+        self.theories()
+            .filter_map(|(_, th)| th.decision_impact(literal, model))
+            .reduce(|a, b| a + b)
+    }
 }
 
 impl Default for Reasoners {
@@ -21,11 +21,23 @@ impl Default for ClausesParams {
     }
 }
 
+/// Origin of a clause, which determines whether it may be removed from the database.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ClauseKind {
+    /// Clause of the problem definition, that is never removed.
+    Model,
+    /// Clause learnt during search, that may be removed when the database grows too large.
+    Learnt,
+    /// Clause entailed by the problem (e.g. a lemma provided by a theory) that is deemed valuable enough
+    /// to never be removed. It does not count toward the number of learnt clauses allowed in the database.
+    Permanent,
+}
+
 #[derive(Copy, Clone)]
 struct ClauseMetadata {
     pub activity: f64,
     pub lbd: u32,
-    pub learnt: bool,
+    pub kind: ClauseKind,
 }
 
 /// A clause represents a disjunction of literals `a || b || c`. It may also contain a `scope` literal defining
@@ -251,7 +263,7 @@ impl Display for ClauseId {
 #[derive(Clone)]
 pub struct ClauseDb {
     params: ClausesParams,
-    /// Number of clauses that are not learnt and cannot be removed from the database.
+    /// Number of clauses that cannot be removed from the database (model and permanent clauses).
     num_fixed: usize,
     /// Total number of clauses.
     num_clauses: usize,
@@ -260,7 +272,7 @@ pub struct ClauseDb {
     /// Unassigned clause ids point to a tautological clause in order to always point to valid one.
     /// This is to avoid having invalid data in this array and resorting to unsafe code to skip validation.
     clauses: RefVec<ClauseId, Clause>,
-    /// Metadata for the clause, including its activity and whether it is a learnt clause or not.
+    /// Metadata for the clause, including its activity and its kind.
     /// A clause id appears in this map if and only if it has been assigned.
     metadata: RefMap<ClauseId, ClauseMetadata>,
     /// A tautological that should be always true in the model.
@@ -291,16 +303,16 @@ impl ClauseDb {
             && clause.unwatched.is_empty()
     }
 
-    pub fn add_clause(&mut self, cl: Clause, learnt: bool) -> ClauseId {
+    pub fn add_clause(&mut self, cl: Clause, kind: ClauseKind) -> ClauseId {
         self.num_clauses += 1;
-        if !learnt {
+        if kind != ClauseKind::Learnt {
             self.num_fixed += 1;
         }
 
         let meta = ClauseMetadata {
             activity: 0f64,
             lbd: 0,
-            learnt,
+            kind,
         };
 
         // too costly to check when the number of clause grows
@@ -336,13 +348,19 @@ impl ClauseDb {
         id
     }
 
+    pub fn kind(&self, clause: ClauseId) -> ClauseKind {
+        self.metadata[clause].kind
+    }
+
+    /// Returns true if the clause was learnt and may be removed from the database.
     pub fn is_learnt(&self, clause: ClauseId) -> bool {
-        self.metadata[clause].learnt
+        self.kind(clause) == ClauseKind::Learnt
     }
 
     pub fn num_clauses(&self) -> usize {
         self.num_clauses
     }
+    /// Number of learnt clauses in the database, that may be removed from it.
     pub fn num_learnt(&self) -> usize {
         self.num_clauses - self.num_fixed
     }
@@ -373,12 +391,14 @@ impl ClauseDb {
         self.params.cla_inc *= 1e-100_f64;
     }
 
+    /// Removes the least active half of the learnt clauses that are not locked.
+    /// Model and permanent clauses are never removed.
     pub fn reduce_db<F: Fn(ClauseId) -> bool>(&mut self, locked: F, remove_watch: &mut impl FnMut(ClauseId, Lit)) {
         let mut clauses: Vec<_> = self
             .metadata
             .entries()
             .filter_map(|(id, meta)| {
-                if meta.learnt && !locked(id) {
+                if meta.kind == ClauseKind::Learnt && !locked(id) {
                     // let score = meta.activity / ((meta.lbd) as f64);
                     let score = meta.activity;
                     Some((id, score))
@@ -391,22 +411,30 @@ impl ClauseDb {
         clauses.sort_by(|&a, &b| a.1.partial_cmp(&b.1).unwrap_or(Equal));
         // remove half removable
         clauses.iter().take(clauses.len() / 2).for_each(|&(id, _)| {
-            let cl = &self.clauses[id];
-            if !cl.is_empty() {
-                remove_watch(id, !cl.watch1);
-            }
-            if cl.len() >= 2 {
-                remove_watch(id, !cl.watch2);
-            }
-            self.clauses[id] = self.tautological_clause.clone();
-            self.metadata.remove(id);
-            self.num_clauses -= 1;
+            self.remove_clause(id, remove_watch);
         });
 
         // make sure we search for free spots from the beginning
         self.first_possibly_free = 0;
     }
 
+    /// Removes a learnt clause from the database, invoking `remove_watch` on the watches it had on its literals.
+    /// The caller must ensure that the clause is not locked.
+    pub fn remove_clause(&mut self, id: ClauseId, remove_watch: &mut impl FnMut(ClauseId, Lit)) {
+        debug_assert!(self.is_learnt(id));
+        let cl = &self.clauses[id];
+        if !cl.is_empty() {
+            remove_watch(id, !cl.watch1);
+        }
+        if cl.len() >= 2 {
+            remove_watch(id, !cl.watch2);
+        }
+        self.clauses[id] = self.tautological_clause.clone();
+        self.metadata.remove(id);
+        self.num_clauses -= 1;
+        self.first_possibly_free = self.first_possibly_free.min(usize::from(id));
+    }
+
     /// Returns true is the clause id is assigned to a clause
     /// Any publicly available clause id should be assigned.
     pub fn is_in_db(&self, clause: ClauseId) -> bool {
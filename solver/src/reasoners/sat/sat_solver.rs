@@ -1,14 +1,21 @@
 use crate::backtrack::{Backtrack, DecLvl, ObsTrailCursor, Trail};
+use crate::collections::ref_store::RefVec;
 use crate::collections::set::RefSet;
 use crate::core::literals::{Disjunction, WatchSet, Watches};
 use crate::core::state::{Domains, Event, Explanation};
 use crate::core::*;
+use crate::create_ref_type;
 use crate::model::extensions::{AssignmentExt, DisjunctionExt};
 use crate::reasoners::sat::clauses::*;
 use crate::reasoners::{Contradiction, ReasonerId, Theory};
+use env_param::EnvParam;
 use itertools::Itertools;
 use smallvec::alloc::collections::VecDeque;
 
+/// Number of the most recently learnt clauses that are checked for subsumption when learning a new clause.
+/// A value of 0 disables subsumption checks.
+pub static SAT_SUBSUMPTION_WINDOW: EnvParam<usize> = EnvParam::new("ARIES_SAT_SUBSUMPTION_WINDOW", "16");
+
 /// Keeps track of which clauses are locked.
 /// Clauses are locked when used for unit propagation as they must remain available
 /// for explanations.
@@ -95,6 +102,8 @@ impl Default for SearchState {
 pub struct Stats {
     pub conflicts: u64,
     pub propagations: u64,
+    /// Number of learnt clauses removed because they were subsumed by a more recent one.
+    pub subsumed: u64,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -103,6 +112,7 @@ impl Default for Stats {
         Stats {
             conflicts: 0,
             propagations: 0,
+            subsumed: 0,
         }
     }
 }
@@ -118,6 +128,23 @@ struct PendingClause {
     asserted_literal: Option<Lit>,
 }
 
+create_ref_type!(LazyImplicationId);
+
+/// A set of implications `(or antecedents...) => consequent`, equivalent to the binary clauses `(!a | consequent)`
+/// for each antecedent `a`, that should only hold when the `scope` literal is true.
+///
+/// The binary clauses are generated lazily: the clause of an antecedent is only added to the database the first time
+/// it may propagate, i.e., when the antecedent becomes true or the consequent becomes false.
+/// Until then, it is only represented by a watch on the antecedent.
+#[derive(Clone)]
+struct LazyImplication {
+    antecedents: Vec<Lit>,
+    consequent: Lit,
+    scope: Lit,
+    /// Whether the clause of each antecedent has already been added to the database.
+    generated: Vec<bool>,
+}
+
 #[derive(Clone)]
 pub struct SatSolver {
     clauses: ClauseDb,
@@ -136,6 +163,14 @@ pub struct SatSolver {
     stats: Stats,
     /// A working data structure to avoid allocations during propagation
     working_watches: WatchSet<ClauseId>,
+    lazy_implications: RefVec<LazyImplicationId, LazyImplication>,
+    /// Watches of the lazy implications: on each antecedent whose clause was not generated yet and on the negated consequent.
+    lazy_watches: Watches<LazyImplicationId>,
+    /// Lazy implications that have been added but for which no watch has been set yet.
+    pending_lazy_implications: Vec<LazyImplicationId>,
+    /// The most recently learnt clauses (at most [SAT_SUBSUMPTION_WINDOW]) together with their sorted literals,
+    /// against which new learnt clauses are checked for subsumption.
+    recent_learnt: VecDeque<(ClauseId, Disjunction)>,
 }
 impl SatSolver {
     pub fn new(identity: ReasonerId) -> SatSolver {
@@ -151,13 +186,17 @@ impl SatSolver {
             state: Default::default(),
             stats: Default::default(),
             working_watches: Default::default(),
+            lazy_implications: Default::default(),
+            lazy_watches: Default::default(),
+            pending_lazy_implications: Vec::new(),
+            recent_learnt: Default::default(),
         }
     }
 
     /// Adds a new clause that will be part of the problem definition.
     /// Returns a unique and stable identifier for the clause.
     pub fn add_clause(&mut self, clause: impl Into<Disjunction>) -> ClauseId {
-        self.add_clause_impl(Clause::new(clause.into()), false)
+        self.add_clause_impl(Clause::new(clause.into()), ClauseKind::Model)
     }
 
     /// Adds a new clause that only needs to be active when the scope literal is true.
@@ -165,7 +204,7 @@ impl SatSolver {
     /// Invariant: All literals in scoped clauses must only be present if the scope literal is true.
     /// This invariant allows scoped clauses to be eagerly propagated even when the scope literal is unknown.
     pub fn add_clause_scoped(&mut self, clause: impl Into<Disjunction>, scope: Lit) -> ClauseId {
-        self.add_clause_impl(Clause::new_scoped(clause.into(), scope), false)
+        self.add_clause_impl(Clause::new_scoped(clause.into(), scope), ClauseKind::Model)
     }
 
     /// Adds a new clause representing `from => to`.
@@ -173,10 +212,38 @@ impl SatSolver {
         self.add_clause([!from, to])
     }
 
+    /// Adds the constraint `(or antecedents...) => consequent` that only needs to hold when the scope literal is true.
+    ///
+    /// This is equivalent to adding the scoped clause `(!a | consequent)` for each antecedent `a` but the clauses are
+    /// only generated when they become relevant to propagation (see [LazyImplication]).
+    pub fn add_lazy_implications(&mut self, antecedents: &[Lit], consequent: Lit, scope: Lit) {
+        let id = self.lazy_implications.push(LazyImplication {
+            antecedents: antecedents.to_vec(),
+            consequent,
+            scope,
+            generated: vec![false; antecedents.len()],
+        });
+        self.pending_lazy_implications.push(id);
+    }
+
     /// Adds a clause that is implied by the other clauses and that the solver is allowed to forget if
     /// it judges that its constraint database is bloated and that this clause is not helpful in resolution.
     pub fn add_forgettable_clause(&mut self, clause: impl Into<Disjunction>) {
-        self.add_clause_impl(Clause::new(clause.into()), true);
+        self.add_clause_impl(Clause::new(clause.into()), ClauseKind::Learnt);
+    }
+
+    /// Adds a clause that is implied by the other clauses (e.g. a lemma provided by a theory)
+    /// and that should never be removed from the database, regardless of its activity.
+    pub fn add_permanent_clause(&mut self, clause: impl Into<Disjunction>) -> ClauseId {
+        self.add_clause_impl(Clause::new(clause.into()), ClauseKind::Permanent)
+    }
+
+    /// Iterates over the learnt clauses that are currently in the database.
+    pub fn learnt_clauses(&self) -> impl Iterator<Item = Disjunction> + '_ {
+        self.clauses
+            .all_clauses()
+            .filter(|&id| self.clauses.is_learnt(id))
+            .map(|id| Disjunction::new(self.clauses[id].literals().collect()))
     }
 
     /// Adds an asserting clause that was learnt.
@@ -186,15 +253,53 @@ impl SatSolver {
         self.stats.conflicts += 1;
         let clause = clause.into();
         debug_assert!(clause.contains(asserted));
-        let cl_id = self.clauses.add_clause(Clause::new(clause), true);
+        let window = SAT_SUBSUMPTION_WINDOW.get();
+        if window > 0 {
+            self.remove_subsumed_learnt_clauses(&clause);
+        }
+        let cl_id = self.clauses.add_clause(Clause::new(clause.clone()), ClauseKind::Learnt);
         self.pending_clauses.push_back(PendingClause {
             clause: cl_id,
             asserted_literal: Some(asserted),
         });
+        if window > 0 {
+            if self.recent_learnt.len() >= window {
+                self.recent_learnt.pop_front();
+            }
+            self.recent_learnt.push_back((cl_id, clause));
+        }
+    }
+
+    /// Removes from the database all recently learnt clauses that are subsumed by the given `clause`.
+    ///
+    /// Near-duplicate clauses tend to be learnt in a row (e.g. on temporal problems where the same conflict
+    /// reappears with slightly different bounds), so only the most recent ones are checked.
+    /// Clauses that are locked (because they may be needed to explain a literal) or not propagated yet are kept.
+    ///
+    /// The converse case, where `clause` is subsumed by a recent clause, is not checked: as `clause` is derived from a
+    /// conflict, this would mean that the recent clause was violated before the conflict.
+    fn remove_subsumed_learnt_clauses(&mut self, clause: &Disjunction) {
+        let mut i = 0;
+        while i < self.recent_learnt.len() {
+            let (id, previous) = &self.recent_learnt[i];
+            let id = *id;
+            if clause.subsumes(previous)
+                && !self.locks.contains(id)
+                && self.pending_clauses.iter().all(|pending| pending.clause != id)
+            {
+                let watches = &mut self.watches;
+                self.clauses
+                    .remove_clause(id, &mut |cl, watched| watches.remove_watch(cl, watched));
+                self.recent_learnt.remove(i);
+                self.stats.subsumed += 1;
+            } else {
+                i += 1;
+            }
+        }
     }
 
-    fn add_clause_impl(&mut self, clause: Clause, learnt: bool) -> ClauseId {
-        let cl_id = self.clauses.add_clause(clause, learnt);
+    fn add_clause_impl(&mut self, clause: Clause, kind: ClauseKind) -> ClauseId {
+        let cl_id = self.clauses.add_clause(clause, kind);
         self.pending_clauses.push_back(PendingClause {
             clause: cl_id,
             asserted_literal: None,
@@ -220,7 +325,7 @@ impl SatSolver {
                     None
                 }
                 Some(true) => None,
-                Some(false) => Some(cl_id),
+                Some(false) => self.deactivate_violated_clause(cl_id, model),
             };
         }
         debug_assert!(clause.len() >= 2);
@@ -360,6 +465,23 @@ impl SatSolver {
                 }
             }
         }
+        // set up the watches of the lazy implications added since last propagation
+        while let Some(id) = self.pending_lazy_implications.pop() {
+            let imp = &self.lazy_implications[id];
+            if model.entails(!imp.consequent) {
+                self.generate_lazy_clauses(id, None, model)?;
+                continue;
+            }
+            self.lazy_watches.add_watch(id, !imp.consequent);
+            for i in 0..imp.antecedents.len() {
+                let antecedent = self.lazy_implications[id].antecedents[i];
+                if model.entails(antecedent) {
+                    self.generate_lazy_clauses(id, Some(antecedent), model)?;
+                } else {
+                    self.lazy_watches.add_watch(id, antecedent);
+                }
+            }
+        }
         // grow or shrink database. Placed here to be as close as possible to initial minisat
         // implementation where this appeared in search
         self.scale_database();
@@ -415,6 +537,10 @@ impl SatSolver {
                 }
             }
 
+            if contradicting_clause.is_none() {
+                contradicting_clause = self.propagate_lazy_implications(new_lit, model).err();
+            }
+
             if let Some(violated) = contradicting_clause {
                 // give up ownership of the working data structure
                 std::mem::swap(&mut self.working_watches, &mut working_watches);
@@ -427,6 +553,21 @@ impl SatSolver {
         Ok(())
     }
 
+    /// Handles a clause whose literals are all false, by deactivating it if its scope is not entailed.
+    /// Returns the clause if it is necessarily active, i.e., in conflict.
+    fn deactivate_violated_clause(&mut self, cl_id: ClauseId, model: &mut Domains) -> Option<ClauseId> {
+        let active = self.clauses[cl_id].scope;
+        match model.value(active) {
+            Some(true) => Some(cl_id), // necessarily active: conflict
+            Some(false) => None,       // already inactive
+            None => {
+                // undefined status: deactivate
+                self.set_from_unit_propagation(!active, cl_id, model);
+                None
+            }
+        }
+    }
+
     /// Propagate a clause that is watching literal `p` became true.
     /// `p` should be one of the literals watched by the clause.
     /// If the clause is:
@@ -442,9 +583,9 @@ impl SatSolver {
         let clause = &mut self.clauses[clause_id];
         if clause.has_single_literal() {
             debug_assert!(p.entails(!clause.watch1));
-            // only one literal that is false, the clause is in conflict
+            // only one literal that is false, the clause is violated
             self.watches.add_watch(clause_id, p);
-            return false;
+            return self.deactivate_violated_clause(clause_id, model).is_none();
         }
         if p.entails(!clause.watch1) {
             clause.swap_watches();
@@ -490,6 +631,75 @@ impl SatSolver {
         }
     }
 
+    /// Generates the clauses of all lazy implications that are watching a literal made true by `new_lit`.
+    /// Watches are not restored, as the generated clauses take over the propagation.
+    fn propagate_lazy_implications(&mut self, new_lit: Lit, model: &mut Domains) -> Result<(), ClauseId> {
+        let mut triggered = WatchSet::new();
+        self.lazy_watches.move_watches_to(new_lit, &mut triggered);
+        let mut watches = triggered.all_watches();
+        while let Some(watch) = watches.next() {
+            let watched = watch.to_lit(new_lit.svar());
+            let imp = &self.lazy_implications[watch.watcher];
+            let antecedent = if watched == !imp.consequent {
+                None
+            } else {
+                Some(watched)
+            };
+            if let Err(violated) = self.generate_lazy_clauses(watch.watcher, antecedent, model) {
+                // restore the watches that were not processed
+                for w in watches {
+                    self.lazy_watches.add_watch(w.watcher, w.to_lit(new_lit.svar()));
+                }
+                return Err(violated);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds to the database the clause of the given antecedent of a lazy implication, or the clauses of all
+    /// its antecedents if `None`, and processes them immediately.
+    /// Returns the first violated clause if any.
+    /// In this case, the watch on the consequent is restored if some clauses remain to be generated.
+    fn generate_lazy_clauses(
+        &mut self,
+        id: LazyImplicationId,
+        antecedent: Option<Lit>,
+        model: &mut Domains,
+    ) -> Result<(), ClauseId> {
+        let imp = &self.lazy_implications[id];
+        let (consequent, scope) = (imp.consequent, imp.scope);
+        for i in 0..imp.antecedents.len() {
+            let imp = &mut self.lazy_implications[id];
+            let a = imp.antecedents[i];
+            if imp.generated[i] || antecedent.map_or(false, |antecedent| antecedent != a) {
+                continue;
+            }
+            imp.generated[i] = true;
+            let Some(clause) = Disjunction::new_non_tautological(vec![!a, consequent]) else {
+                continue;
+            };
+            // the clause can only be eagerly propagated if its literals are only present in the scope
+            let clause = if clause
+                .literals()
+                .iter()
+                .all(|&l| model.implies(model.presence(l), scope))
+            {
+                Clause::new_scoped(clause, scope)
+            } else {
+                Clause::new(Disjunction::new(vec![!a, consequent, !scope]))
+            };
+            let cl_id = self.clauses.add_clause(clause, ClauseKind::Model);
+            if let Some(violated) = self.process_arbitrary_clause(cl_id, model) {
+                let imp = &self.lazy_implications[id];
+                if antecedent.is_none() && imp.generated.iter().any(|generated| !generated) {
+                    self.lazy_watches.add_watch(id, !consequent);
+                }
+                return Err(violated);
+            }
+        }
+        Ok(())
+    }
+
     fn set_from_unit_propagation(&mut self, literal: Lit, propagating_clause: ClauseId, model: &mut Domains) {
         // Set the literal to false.
         // We know that no inconsistency will occur (from the invariants of unit propagation.
@@ -579,6 +789,7 @@ impl SatSolver {
 
     /// Function responsible for scaling the size of the clause Database.
     /// The database has a limited number of slots for learnt clauses.
+    /// Model and permanent clauses never occupy these slots and are never removed.
     /// If all slots are taken, this function can:
     ///  - expand the database with more slots. This occurs if a certain number of conflicts occurred
     ///    since the last expansion.
@@ -614,13 +825,28 @@ impl SatSolver {
                     watches.remove_watch(clause, watched);
                 };
                 self.clauses.reduce_db(|cl| locks.contains(cl), &mut remove_watch);
+                // identifiers of the removed clauses may be reused
+                self.recent_learnt.clear();
             }
         }
     }
 
     pub fn print_stats(&self) {
         println!("DB size              : {}", self.clauses.num_clauses());
+        let num_generated = self
+            .lazy_implications
+            .values()
+            .flat_map(|imp| imp.generated.iter())
+            .filter(|generated| **generated)
+            .count();
+        let num_lazy = self
+            .lazy_implications
+            .values()
+            .map(|imp| imp.antecedents.len())
+            .sum::<usize>();
+        println!("Lazy clauses         : {num_generated} / {num_lazy}");
         println!("Num unit propagations: {}", self.stats.propagations);
+        println!("Subsumed clauses     : {}", self.stats.subsumed);
     }
 }
 
@@ -756,6 +982,47 @@ mod tests {
         check_values(model, [Some(false), Some(false), Some(false), Some(true)]);
     }
 
+    #[test]
+    fn test_lazy_implications() {
+        let writer = ReasonerId::Sat;
+        let model = &mut Model::new();
+        let a = model.new_bvar("a").true_lit();
+        let b = model.new_bvar("b").true_lit();
+        let c = model.new_bvar("c").true_lit();
+        let l = model.new_bvar("l").true_lit();
+
+        let mut sat = SatSolver::new(writer);
+        sat.add_lazy_implications(&[a, b, c], l, Lit::TRUE);
+        sat.propagate(&mut model.state).unwrap();
+        assert_eq!(sat.clauses.num_clauses(), 0);
+
+        // b => l is generated when b becomes true
+        model.save_state();
+        sat.save_state();
+        model.state.decide(b).unwrap();
+        sat.propagate(&mut model.state).unwrap();
+        assert!(model.state.entails(l));
+        assert_eq!(sat.clauses.num_clauses(), 1);
+        model.restore_last();
+        sat.restore_last();
+
+        // the clause remains after backtracking
+        model.save_state();
+        sat.save_state();
+        model.state.decide(b).unwrap();
+        sat.propagate(&mut model.state).unwrap();
+        assert!(model.state.entails(l));
+        assert_eq!(sat.clauses.num_clauses(), 1);
+        model.restore_last();
+        sat.restore_last();
+
+        // all remaining clauses are generated when l becomes false
+        model.state.decide(!l).unwrap();
+        sat.propagate(&mut model.state).unwrap();
+        assert!(model.state.entails(!a) && model.state.entails(!b) && model.state.entails(!c));
+        assert_eq!(sat.clauses.num_clauses(), 3);
+    }
+
     #[test]
     fn test_propagation_failure() {
         let writer = ReasonerId::Sat;
@@ -826,6 +1093,78 @@ mod tests {
         assert!(sat.propagate(&mut model.state).is_err());
     }
 
+    #[test]
+    fn test_learnt_clause_subsumption() {
+        let model = &mut Model::new();
+        let a = model.new_bvar("a");
+        let b = model.new_bvar("b");
+        let c = model.new_bvar("c");
+        let d = model.new_bvar("d");
+        let mut sat = SatSolver::new(ReasonerId::Sat);
+        sat.propagate(&mut model.state).unwrap();
+
+        // learn (a | b | c | d) after deciding !a, !b and !c
+        model.save_state();
+        sat.save_state();
+        for v in [a, b, c] {
+            model.state.set_ub(v, 0, Cause::Decision).unwrap();
+        }
+        sat.add_learnt_clause(
+            vec![a.true_lit(), b.true_lit(), c.true_lit(), d.true_lit()],
+            d.true_lit(),
+        );
+        sat.propagate(&mut model.state).unwrap();
+        assert_eq!(model.boolean_value_of(d), Some(true));
+        model.restore_last();
+        sat.restore_last();
+
+        // learn (a | d), that subsumes the previous clause
+        model.save_state();
+        sat.save_state();
+        model.state.set_ub(a, 0, Cause::Decision).unwrap();
+        sat.add_learnt_clause(vec![a.true_lit(), d.true_lit()], d.true_lit());
+        assert_eq!(sat.clauses.num_learnt(), 1);
+        assert_eq!(sat.stats.subsumed, 1);
+        sat.propagate(&mut model.state).unwrap();
+        assert_eq!(model.boolean_value_of(d), Some(true));
+
+        // (a | c) does not subsume (a | d), which is moreover locked as it explains d
+        sat.add_learnt_clause(vec![a.true_lit(), c.true_lit()], c.true_lit());
+        assert_eq!(sat.clauses.num_learnt(), 2);
+        sat.propagate(&mut model.state).unwrap();
+        assert_eq!(model.boolean_value_of(c), Some(true));
+    }
+
+    #[test]
+    fn test_reduce_db_keeps_permanent_clauses() {
+        let model = &mut Model::new();
+        let a = model.new_bvar("a").true_lit();
+        let b = model.new_bvar("b").true_lit();
+        let c = model.new_bvar("c").true_lit();
+        let d = model.new_bvar("d").true_lit();
+        let mut sat = SatSolver::new(ReasonerId::Sat);
+        let model_clauses = [sat.add_clause([a, b]), sat.add_clause([c, d])];
+        let permanent_clauses = [sat.add_permanent_clause([a, c]), sat.add_permanent_clause([b, d])];
+        for clause in [[a, d], [!a, b], [!b, c], [!c, d]] {
+            sat.add_forgettable_clause(clause);
+        }
+        sat.propagate(&mut model.state).unwrap();
+        assert_eq!(sat.clauses.num_learnt(), 4);
+
+        // half of the learnt clauses are removed, and only them
+        let watches = &mut sat.watches;
+        sat.clauses
+            .reduce_db(|_| false, &mut |cl, watched| watches.remove_watch(cl, watched));
+        assert_eq!(sat.clauses.num_learnt(), 2);
+        assert_eq!(sat.clauses.num_clauses(), 6);
+        for cl in model_clauses {
+            assert_eq!(sat.clauses.kind(cl), ClauseKind::Model);
+        }
+        for cl in permanent_clauses {
+            assert_eq!(sat.clauses.kind(cl), ClauseKind::Permanent);
+        }
+    }
+
     #[test]
     fn test_int_propagation() {
         let writer = ReasonerId::Sat;
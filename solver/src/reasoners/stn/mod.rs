@@ -1,3 +1,5 @@
+#[cfg(test)]
+mod scenario;
 mod stn_impl;
 pub mod theory;
 
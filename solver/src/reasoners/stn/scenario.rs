@@ -0,0 +1,341 @@
+//! A small DSL to describe STN scenarios (timepoints, edges and a script of decisions), used to test the STN
+//! on hand-written scenarios as well as on randomly generated ones, with `proptest`.
+//!
+//! ```text
+//! let network = Scenario::new()
+//!     .timepoint(0, 10)
+//!     .timepoint(0, 10)
+//!     .edge(0, 1, 5)
+//!     .inactive_edge(1, 0, -3)
+//!     .build();
+//! ```
+
+use crate::backtrack::Backtrack;
+use crate::core::state::Cause;
+use crate::core::{IntCst, Lit};
+use crate::reasoners::stn::theory::{Timepoint, W};
+use crate::reasoners::stn::Stn;
+use crate::reasoners::Contradiction;
+
+/// Edge `target - source <= weight` of a scenario, that is initially active or must be activated by a script.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct EdgeSpec {
+    pub source: usize,
+    pub target: usize,
+    pub weight: W,
+    pub active: bool,
+}
+
+/// Step of the script of a scenario. Indices refer to the timepoints and inactive edges of the scenario,
+/// in their order of declaration, and are taken modulo their number.
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum Step {
+    /// Activates the i-th inactive edge (ignored if its activation literal is already set).
+    Activate(usize),
+    /// Sets the lower bound of a timepoint (ignored if not within its current domain).
+    SetLb(usize, W),
+    /// Sets the upper bound of a timepoint (ignored if not within its current domain).
+    SetUb(usize, W),
+    /// Saves the current state as a backtrack point.
+    Save,
+    /// Backtracks to the last saved state (ignored if there is none).
+    Undo,
+}
+
+/// Description of an STN: its timepoints, edges, and a script of decisions.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Scenario {
+    pub timepoints: Vec<(W, W)>,
+    pub edges: Vec<EdgeSpec>,
+    pub script: Vec<Step>,
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timepoint(mut self, lb: W, ub: W) -> Self {
+        self.timepoints.push((lb, ub));
+        self
+    }
+
+    pub fn edge(mut self, source: usize, target: usize, weight: W) -> Self {
+        self.edges.push(EdgeSpec {
+            source,
+            target,
+            weight,
+            active: true,
+        });
+        self
+    }
+
+    pub fn inactive_edge(mut self, source: usize, target: usize, weight: W) -> Self {
+        self.edges.push(EdgeSpec {
+            source,
+            target,
+            weight,
+            active: false,
+        });
+        self
+    }
+
+    pub fn then(mut self, step: Step) -> Self {
+        self.script.push(step);
+        self
+    }
+
+    /// Builds the network, without running the script.
+    pub fn build(&self) -> Network {
+        let mut stn = Stn::new();
+        let timepoints: Vec<Timepoint> = self
+            .timepoints
+            .iter()
+            .map(|&(lb, ub)| stn.add_timepoint(lb, ub))
+            .collect();
+        let mut inactive_edges = Vec::new();
+        for e in &self.edges {
+            let (source, target) = (timepoints[e.source], timepoints[e.target]);
+            if e.active {
+                stn.add_edge(source, target, e.weight);
+            } else {
+                inactive_edges.push(stn.add_inactive_edge(source, target, e.weight));
+            }
+        }
+        Network {
+            stn,
+            timepoints,
+            inactive_edges,
+        }
+    }
+}
+
+/// A network built from a [Scenario].
+pub(crate) struct Network {
+    pub stn: Stn,
+    pub timepoints: Vec<Timepoint>,
+    /// Activation literals of the inactive edges of the scenario.
+    pub inactive_edges: Vec<Lit>,
+}
+
+/// Bounds of all timepoints and values of the activation literals of a network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Snapshot {
+    bounds: Vec<(IntCst, IntCst)>,
+    edges: Vec<Option<bool>>,
+}
+
+impl Network {
+    pub fn bounds(&self, timepoint: usize) -> (W, W) {
+        let tp = self.timepoints[timepoint];
+        (self.stn.model.state.lb(tp), self.stn.model.state.ub(tp))
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            bounds: (0..self.timepoints.len()).map(|i| self.bounds(i)).collect(),
+            edges: self
+                .inactive_edges
+                .iter()
+                .map(|&e| self.stn.model.state.value(e))
+                .collect(),
+        }
+    }
+
+    /// Applies a step of a script, returning false if it was ignored.
+    /// Propagation is left to the caller.
+    pub fn apply(&mut self, step: Step) -> bool {
+        let state = &self.stn.model.state;
+        match step {
+            Step::Activate(i) if !self.inactive_edges.is_empty() => {
+                let edge = self.inactive_edges[i % self.inactive_edges.len()];
+                if state.value(edge).is_some() {
+                    return false;
+                }
+                self.stn.mark_active(edge);
+            }
+            Step::SetLb(i, lb) if !self.timepoints.is_empty() => {
+                let tp = self.timepoints[i % self.timepoints.len()];
+                if lb <= state.lb(tp) || lb > state.ub(tp) {
+                    return false;
+                }
+                self.stn.set_lb(tp, lb);
+            }
+            Step::SetUb(i, ub) if !self.timepoints.is_empty() => {
+                let tp = self.timepoints[i % self.timepoints.len()];
+                if ub >= state.ub(tp) || ub < state.lb(tp) {
+                    return false;
+                }
+                self.stn.set_ub(tp, ub);
+            }
+            Step::Save => self.stn.set_backtrack_point(),
+            Step::Undo if self.stn.model.state.num_saved() > 0 => self.stn.undo_to_last_backtrack_point(),
+            _ => return false,
+        }
+        true
+    }
+
+    pub fn propagate(&mut self) -> Result<(), Contradiction> {
+        self.stn.propagate_all()
+    }
+
+    /// Literals inferred by the STN in the current state, that have an explanation:
+    /// bounds of the timepoints and values of the activation literals of the inactive edges.
+    pub fn inferred_literals(&mut self) -> Vec<(Lit, Vec<Lit>)> {
+        let mut lits: Vec<Lit> = self
+            .timepoints
+            .iter()
+            .flat_map(|&tp| {
+                let state = &self.stn.model.state;
+                [Lit::geq(tp, state.lb(tp)), Lit::leq(tp, state.ub(tp))]
+            })
+            .collect();
+        for &e in &self.inactive_edges {
+            match self.stn.model.state.value(e) {
+                Some(true) => lits.push(e),
+                Some(false) => lits.push(!e),
+                None => {}
+            }
+        }
+        lits.into_iter()
+            .filter_map(|l| self.stn.implying_literals(l).map(|expl| (l, expl)))
+            .collect()
+    }
+}
+
+/// Returns true if, in the network of the scenario, the `antecedents` entail the `literal`
+/// (or are inconsistent with the network).
+pub(crate) fn entails(scenario: &Scenario, antecedents: &[Lit], literal: Lit) -> bool {
+    let mut network = scenario.build();
+    for &l in antecedents {
+        if network.stn.model.state.set(l, Cause::Decision).is_err() {
+            return true;
+        }
+    }
+    match network.propagate() {
+        Ok(()) => network.stn.model.state.entails(literal),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_dsl() {
+        let scenario = Scenario::new()
+            .timepoint(0, 10)
+            .timepoint(0, 10)
+            .edge(0, 1, 5)
+            .inactive_edge(1, 0, -3)
+            .then(Step::SetUb(0, 2))
+            .then(Step::Save)
+            .then(Step::Activate(0));
+        let mut network = scenario.build();
+        network.propagate().unwrap();
+        assert_eq!(network.bounds(1), (0, 10));
+        for &step in &scenario.script {
+            assert!(network.apply(step));
+            network.propagate().unwrap();
+        }
+        assert_eq!(network.bounds(0), (0, 2));
+        assert_eq!(network.bounds(1), (3, 7));
+        // the edge cannot be activated twice
+        assert!(!network.apply(Step::Activate(0)));
+
+        assert!(network.apply(Step::Undo));
+        assert_eq!(network.bounds(1), (0, 7));
+        // no backtrack point left
+        assert!(!network.apply(Step::Undo));
+    }
+
+    fn step(num_timepoints: usize) -> impl Strategy<Value = Step> {
+        prop_oneof![
+            (0..4usize).prop_map(Step::Activate),
+            (0..num_timepoints, 0..30).prop_map(|(i, v)| Step::SetLb(i, v)),
+            (0..num_timepoints, 0..30).prop_map(|(i, v)| Step::SetUb(i, v)),
+            Just(Step::Save),
+            Just(Step::Undo),
+        ]
+    }
+
+    fn scenario() -> impl Strategy<Value = Scenario> {
+        (2..6usize).prop_flat_map(|n| {
+            let timepoints = prop::collection::vec((0..10, 0..20).prop_map(|(lb, width)| (lb, lb + width)), n);
+            let edge = (0..n, 0..n, -10..15, any::<bool>()).prop_map(|(source, target, weight, active)| EdgeSpec {
+                source,
+                target,
+                weight,
+                active,
+            });
+            let edges = prop::collection::vec(edge, 0..10);
+            let script = prop::collection::vec(step(n), 0..15);
+            (timepoints, edges, script).prop_map(|(timepoints, edges, script)| Scenario {
+                timepoints,
+                edges,
+                script,
+            })
+        })
+    }
+
+    /// Checks the invariants of the STN in a consistent state of the network.
+    fn check_consistent_state(scenario: &Scenario, network: &mut Network) -> Result<(), TestCaseError> {
+        // propagation is idempotent
+        let snapshot = network.snapshot();
+        prop_assert!(network.propagate().is_ok());
+        prop_assert_eq!(&network.snapshot(), &snapshot);
+
+        // explanations are valid
+        for (literal, explanation) in network.inferred_literals() {
+            prop_assert!(
+                entails(scenario, &explanation, literal),
+                "Invalid explanation {:?} for {:?}",
+                explanation,
+                literal
+            );
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn random_scenarios(scenario in scenario()) {
+            let mut network = scenario.build();
+            // snapshots of the saved states
+            let mut saved: Vec<Snapshot> = Vec::new();
+            if network.propagate().is_err() {
+                return Ok(()); // inconsistent at the root
+            }
+            for &step in &scenario.script {
+                let snapshot = network.snapshot();
+                if !network.apply(step) {
+                    continue;
+                }
+                match step {
+                    Step::Save => saved.push(snapshot),
+                    Step::Undo => {
+                        // backtracking restores the exact bounds of the saved state
+                        let expected = saved.pop().unwrap();
+                        prop_assert_eq!(&network.snapshot(), &expected);
+                        continue;
+                    }
+                    _ => {}
+                }
+                if network.propagate().is_ok() {
+                    check_consistent_state(&scenario, &mut network)?;
+                } else {
+                    // backtrack to the last consistent state
+                    match saved.pop() {
+                        Some(expected) => {
+                            network.apply(Step::Undo);
+                            prop_assert_eq!(&network.snapshot(), &expected);
+                        }
+                        None => return Ok(()),
+                    }
+                }
+            }
+        }
+    }
+}
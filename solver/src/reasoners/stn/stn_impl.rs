@@ -2,6 +2,7 @@ use crate::backtrack::Backtrack;
 use crate::core::literals::Disjunction;
 use crate::core::state::{Cause, Domains, Explainer, Explanation, InferenceCause};
 use crate::core::Lit;
+use crate::model::lang::Type;
 use crate::model::Model;
 use crate::reasoners::stn::theory::{StnConfig, StnTheory, Timepoint, W};
 use crate::reasoners::{Contradiction, Theory};
@@ -37,7 +38,7 @@ impl Stn {
 
     pub fn add_edge(&mut self, source: Timepoint, target: Timepoint, weight: W) {
         let valid_edge = self.get_conjunctive_scope(source, target);
-        let active_edge = self.model.get_tautology_of_scope(valid_edge);
+        let active_edge = self.model.scoped_lit(valid_edge);
         debug_assert!(self.model.state.entails(active_edge));
         self.stn
             .add_reified_edge(active_edge, source, target, weight, &self.model.state)
@@ -55,6 +56,34 @@ impl Stn {
         active_edge
     }
 
+    /// Adds the edge `target - source <= (if guard { weight_if_true } else { weight_if_false })`
+    /// and returns the guard, whose value is not yet decided.
+    pub fn add_conditional_edge(
+        &mut self,
+        source: Timepoint,
+        target: Timepoint,
+        weight_if_true: W,
+        weight_if_false: W,
+    ) -> Lit {
+        let valid_edge = self.get_conjunctive_scope(source, target);
+        let guard = self
+            .model
+            .new_optional_bvar(
+                valid_edge,
+                format!("guard({source:?} -- {weight_if_true}|{weight_if_false} --> {target:?})"),
+            )
+            .true_lit();
+        self.stn.add_conditional_edge(
+            guard,
+            source,
+            target,
+            weight_if_true,
+            weight_if_false,
+            &self.model.state,
+        );
+        guard
+    }
+
     // add delay between optional variables
     pub fn add_delay(&mut self, a: impl Into<Timepoint>, b: impl Into<Timepoint>, delay: W) {
         self.add_edge(b.into(), a.into(), -delay);
@@ -72,7 +101,7 @@ impl Stn {
     }
 
     pub fn propagate_all(&mut self) -> Result<(), Contradiction> {
-        self.stn.propagate_all(&mut self.model.state)
+        self.stn.propagate_to_fixpoint(&mut self.model.state)
     }
 
     pub fn set_backtrack_point(&mut self) {
@@ -85,6 +114,62 @@ impl Stn {
         self.stn.undo_to_last_backtrack_point();
     }
 
+    pub fn reset_to_root(&mut self) {
+        self.model.reset();
+        self.stn.reset_to_root();
+    }
+
+    /// Returns a set of literals that imply the given literal, explained in the current state.
+    /// The literal must be true in the current state, and None is returned if it is a decision.
+    pub fn implying_literals(&mut self, literal: Lit) -> Option<Vec<Lit>> {
+        self.model
+            .state
+            .implying_literals(literal, &mut StnExplainer { stn: &mut self.stn })
+    }
+
+    /// Naive flexibility of the network (Wilson et al., 2014): the sum of the widths `ub - lb` of the domains
+    /// of all timepoints that are not known to be absent, as of the last propagation.
+    pub fn naive_flexibility(&self) -> i64 {
+        self.timepoint_widths().sum()
+    }
+
+    /// Root mean square of the widths `ub - lb` of the domains of all timepoints that are not known to be absent,
+    /// as of the last propagation. Unlike the naive flexibility, it penalizes a few very flexible timepoints
+    /// compensating for many rigid ones. Returns 0 if the network has no timepoint.
+    pub fn rms_flexibility(&self) -> f64 {
+        let (count, sum_of_squares) = self.timepoint_widths().fold((0, 0.0), |(count, sum), width| {
+            (count + 1, sum + (width as f64).powi(2))
+        });
+        if count == 0 {
+            0.0
+        } else {
+            (sum_of_squares / count as f64).sqrt()
+        }
+    }
+
+    /// Sum of the slacks of all active edges in the earliest schedule, where each timepoint takes its lower bound.
+    /// The slack of an edge `target - source <= weight` is `weight - (lb(target) - lb(source))`,
+    /// i.e., how much its target could be delayed without violating it.
+    pub fn sum_of_slacks(&self) -> i64 {
+        let state = &self.model.state;
+        self.stn
+            .active_edges()
+            .filter(|&(source, target, _)| state.present(source) != Some(false) && state.present(target) != Some(false))
+            .map(|(source, target, weight)| weight as i64 - (state.lb(target) as i64 - state.lb(source) as i64))
+            .sum()
+    }
+
+    /// Widths of the domains of all timepoints that are not known to be absent.
+    fn timepoint_widths(&self) -> impl Iterator<Item = i64> + '_ {
+        let state = &self.model.state;
+        self.model
+            .shape
+            .types
+            .entries()
+            .filter(|&(var, tpe)| *tpe == Type::Int && state.present(var) != Some(false))
+            .map(|(var, _)| state.ub(var) as i64 - state.lb(var) as i64)
+    }
+
     // ------ Private method for testing purposes -------
 
     #[allow(unused)]
@@ -97,26 +182,29 @@ impl Stn {
         assert!(self.propagate_all().is_err());
     }
 
-    #[allow(unused)]
-    pub(crate) fn explain_literal(&mut self, literal: Lit) -> Disjunction {
-        struct Exp<'a> {
-            stn: &'a mut StnTheory,
-        }
-        impl<'a> Explainer for Exp<'a> {
-            fn explain(&mut self, cause: InferenceCause, literal: Lit, model: &Domains, explanation: &mut Explanation) {
-                assert_eq!(cause.writer, self.stn.identity.writer_id);
-                self.stn.explain(literal, cause.payload, model, explanation);
-            }
-        }
+    /// Returns a clause that explains the given literal, obtained by resolution of the explanations of the network
+    /// up to the first unique implication point. The literal must be true in the current state.
+    pub fn explain_literal(&mut self, literal: Lit) -> Disjunction {
         let mut explanation = Explanation::new();
         explanation.push(literal);
         self.model
             .state
-            .refine_explanation(explanation, &mut Exp { stn: &mut self.stn })
+            .refine_explanation(explanation, &mut StnExplainer { stn: &mut self.stn })
             .clause
     }
 }
 
+struct StnExplainer<'a> {
+    stn: &'a mut StnTheory,
+}
+
+impl Explainer for StnExplainer<'_> {
+    fn explain(&mut self, cause: InferenceCause, literal: Lit, model: &Domains, explanation: &mut Explanation) {
+        assert_eq!(cause.writer, self.stn.identity.writer_id);
+        self.stn.explain(literal, cause.payload, model, explanation);
+    }
+}
+
 impl Default for Stn {
     fn default() -> Self {
         Self::new()
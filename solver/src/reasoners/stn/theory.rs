@@ -1,19 +1,26 @@
 mod contraint_db;
 mod distances;
 mod edges;
+mod explanation_cache;
+mod overflow;
+mod symmetry;
 
 use crate::backtrack::Backtrack;
-use crate::backtrack::{DecLvl, ObsTrailCursor, Trail};
+use crate::backtrack::{DecLvl, EventFilter, FilteredObsTrailCursor, Trail};
 use crate::collections::ref_store::{RefMap, RefVec};
 use crate::collections::set::RefSet;
+use crate::core::literals::Disjunction;
 use crate::core::state::*;
 use crate::core::*;
 use crate::reasoners::stn::theory::Event::EdgeActivated;
 use crate::reasoners::{Contradiction, ReasonerId, Theory};
 use contraint_db::*;
-use distances::DijkstraState;
+use distances::{DijkstraState, MultiDijkstraState};
 use edges::*;
 use env_param::EnvParam;
+use explanation_cache::ExplanationCache;
+use overflow::OverflowGuard;
+pub use overflow::{OverflowError, OverflowPolicy, MAX_HORIZON};
 use std::collections::VecDeque;
 use std::convert::*;
 use std::marker::PhantomData;
@@ -30,7 +37,14 @@ pub type W = IntCst;
 pub static STN_THEORY_PROPAGATION: EnvParam<TheoryPropagationLevel> =
     EnvParam::new("ARIES_STN_THEORY_PROPAGATION", "bounds");
 pub static STN_DEEP_EXPLANATION: EnvParam<bool> = EnvParam::new("ARIES_STN_DEEP_EXPLANATION", "false");
+pub static STN_COMPRESSED_EXPLANATION: EnvParam<bool> = EnvParam::new("ARIES_STN_COMPRESSED_EXPLANATION", "false");
 pub static STN_EXTENSIVE_TESTS: EnvParam<bool> = EnvParam::new("ARIES_STN_EXTENSIVE_TESTS", "false");
+pub static STN_EXPLANATION_CACHE_SIZE: EnvParam<usize> = EnvParam::new("ARIES_STN_EXPLANATION_CACHE_SIZE", "32");
+pub static STN_PROPAGATION_BUDGET: EnvParam<u64> = EnvParam::new("ARIES_STN_PROPAGATION_BUDGET", "0");
+pub static STN_OVERFLOW_POLICY: EnvParam<OverflowPolicy> = EnvParam::new("ARIES_STN_OVERFLOW_POLICY", "saturate");
+
+/// Maximum number of one-to-all distances kept in memory during a round of theory propagation on edges.
+const MAX_CACHED_DISTANCES: usize = 16;
 
 /// Describes which part of theory propagation should be enabled.
 #[derive(Copy, Clone, Debug)]
@@ -90,8 +104,24 @@ pub struct StnConfig {
     /// of constraints by recursively looking at the propagation chain that caused the literal to be set
     /// and adding the enabler of each constraint along this path.
     pub deep_explanation: bool,
+    /// If true, explanations of bound propagations are compressed by skipping the edges that are always active
+    /// (i.e. whose enabler holds in the initial domains): the propagation chain is followed through them and summarized
+    /// by the bound at its start, and their enablers are omitted from the explanation.
+    /// This results in shorter learned clauses when a bound change floods the network.
+    pub compressed_explanation: bool,
     /// If true, extensive and very expensive tests will be made in debug mode.
     pub extensive_tests: bool,
+    /// Maximum number of explanations of theory propagations (i.e. of negative cycles that would be created by
+    /// an edge) that are cached to avoid recomputing them when they are requested again. 0 disables the cache.
+    pub explanation_cache_size: usize,
+    /// Maximum number of bound updates made by a single call to `propagate_all` before deferring the propagation
+    /// of the remaining bound changes to the next call. 0 means that the propagation is never interrupted.
+    ///
+    /// The propagation of a newly activated edge is always run to completion, as it is needed to detect negative cycles.
+    pub propagation_budget: u64,
+    /// Behavior of the STN when the sum of a bound and of a weight overflows, or when the weight of an inserted edge
+    /// is outside the safe range of the horizon (see [StnTheory::with_horizon]).
+    pub overflow_policy: OverflowPolicy,
 }
 
 impl Default for StnConfig {
@@ -99,7 +129,11 @@ impl Default for StnConfig {
         StnConfig {
             theory_propagation: STN_THEORY_PROPAGATION.get(),
             deep_explanation: STN_DEEP_EXPLANATION.get(),
+            compressed_explanation: STN_COMPRESSED_EXPLANATION.get(),
             extensive_tests: STN_EXTENSIVE_TESTS.get(),
+            explanation_cache_size: STN_EXPLANATION_CACHE_SIZE.get(),
+            propagation_budget: STN_PROPAGATION_BUDGET.get(),
+            overflow_policy: STN_OVERFLOW_POLICY.get(),
         }
     }
 }
@@ -116,8 +150,40 @@ enum Event {
 struct Stats {
     num_propagations: u64,
     distance_updates: u64,
+    /// Number of times the propagation was interrupted because its budget was exhausted.
+    num_deferrals: u64,
+    /// Number of theory propagations whose path could not be reconstructed, and that were explained by all edges
+    /// active at the time of the propagation instead.
+    num_fallback_explanations: u64,
+}
+
+/// Error raised when no path can be found between two nodes of the network, e.g. when reconstructing the path
+/// that caused a theory propagation. It indicates an invalid state of the network, and gives some context to debug it.
+#[derive(Clone, Debug)]
+pub(crate) struct PathNotFound {
+    source: SignedVar,
+    target: SignedVar,
+    /// Bounds of the source and target when the path was searched for.
+    source_bound: UpperBound,
+    target_bound: UpperBound,
+    /// Number of nodes reachable from the source.
+    num_reached: usize,
+    /// Only the edges activated before this event of the STN's trail were considered.
+    stn_position: u32,
 }
 
+impl std::fmt::Display for PathNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no path from {:?} ({:?}) to {:?} ({:?}) among the {} nodes reachable with the edges activated before event {}",
+            self.source, self.source_bound, self.target, self.target_bound, self.num_reached, self.stn_position
+        )
+    }
+}
+
+impl std::error::Error for PathNotFound {}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct Identity<Cause>
 where
@@ -157,10 +223,9 @@ where
 /// operations have an undefined behavior.
 ///
 /// Requirement for weight : a i32 is used internally to represent both delays
-/// (weight on edges) and absolute times (bound on nodes). It is the responsibility
-/// of the caller to ensure that no overflow occurs when adding an absolute and relative time,
-/// either by the choice of an appropriate type (e.g. saturating add) or by the choice of
-/// appropriate initial literals.
+/// (weight on edges) and absolute times (bound on nodes). An overflow when adding an absolute and relative time
+/// is handled according to the [OverflowPolicy] of the configuration. It cannot occur if the STN is created
+/// with [StnTheory::with_horizon] and all timepoints remain within the horizon.
 #[derive(Clone)]
 pub struct StnTheory {
     pub config: StnConfig,
@@ -173,29 +238,54 @@ pub struct StnTheory {
     pending_activations: VecDeque<ActivationEvent>,
     stats: Stats,
     pub(crate) identity: Identity<ModelUpdateCause>,
-    model_events: ObsTrailCursor<ModelEvent>,
+    /// Events of the model, except for the bound updates made by the propagation of edges.
+    model_events: FilteredObsTrailCursor<ModelEvent, NotEdgePropagation>,
+    /// Literals made true by the propagation of edges, for which the edges they enable and the theory propagation
+    /// of bounds remain to be handled. They replace the corresponding events of the model, that are ignored.
+    edge_propagations: Vec<Lit>,
     /// Internal data structure to construct explanations as negative cycles.
     /// When encountering an inconsistency, this vector will be cleared and
     /// a negative cycle will be constructed in it. The explanation returned
     /// will be a slice of this vector to avoid any allocation.
     explanation: Vec<PropagatorId>,
     theory_propagation_causes: Vec<TheoryPropagationCause>,
+    /// Theory propagations on paths made at the root decision level (index of their cause and propagated literal)
+    /// that have not been turned into permanent lemmas yet (see [Theory::take_permanent_lemmas]).
+    root_propagations: Vec<(u32, Lit)>,
+    /// Explanations of the most recent theory propagations on paths, keyed on the fingerprint of their cause.
+    explanation_cache: ExplanationCache<TheoryPropagationCause>,
     /// Internal data structure used by the `propagate` method to keep track of pending work.
     internal_propagate_queue: VecDeque<SignedVar>,
-    /// Internal data structures used for distance computation.
-    internal_dijkstra_states: [DijkstraState; 2],
+    /// Signed variables whose bound changes have not been propagated yet because the propagation budget was exhausted.
+    /// They are propagated first in the next call to `propagate_all`.
+    deferred_updates: Vec<SignedVar>,
+    /// Number of bound updates that can still be made in the current call to `propagate_all`.
+    remaining_budget: u64,
+    /// Distances computed for the theory propagation of the edges activated in the current round
+    /// (see [Self::theory_propagate_edge]), that remain valid until another edge is activated.
+    distance_cache: MultiDijkstraState,
+    /// Checks the weights of inserted edges and the additions of bounds and weights made by the propagation.
+    overflow: OverflowGuard,
 }
 
 /// Indicates the source and target of an active shortest path that caused a propagation
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum TheoryPropagationCause {
     /// Theory propagation was triggered by a path from source to target in the graph of active constraints
     /// The activation of `triggering_edge` was the one that caused the propagation, meaning that the
     /// shortest path goes through it.
+    ///
+    /// The context of the propagation is recorded so that the path can be reconstructed without undoing
+    /// any later change: only edges activated before `stn_position` are considered, on the domains as they were
+    /// at `model_position`.
     Path {
         source: SignedVar,
         target: SignedVar,
         triggering_edge: PropagatorId,
+        /// Number of events in the STN's trail when the propagation occurred.
+        stn_position: u32,
+        /// Number of events in the model's trail when the propagation occurred.
+        model_position: u32,
     },
     /// Theory propagation was triggered by the incompatibility of the two literals with an edge in the graph.
     Bounds { source: Lit, target: Lit },
@@ -235,6 +325,23 @@ struct InlinedPropagator {
     target: SignedVar,
     weight: BoundValueAdd,
     id: PropagatorId,
+    /// Index of the event in the STN's trail that activated the propagator.
+    activation: u32,
+}
+
+/// Rejects the events of the model that result from the propagation of an edge by the STN of the given identity.
+#[derive(Copy, Clone)]
+struct NotEdgePropagation(ReasonerId);
+
+impl EventFilter<ModelEvent> for NotEdgePropagation {
+    fn accepts(&self, event: &ModelEvent) -> bool {
+        match event.cause.as_external_inference() {
+            Some(x) => {
+                x.writer != self.0 || !matches!(ModelUpdateCause::from(x.payload), ModelUpdateCause::EdgePropagation(_))
+            }
+            None => true,
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -257,12 +364,50 @@ impl StnTheory {
             pending_activations: VecDeque::new(),
             stats: Default::default(),
             identity: Identity::new(ReasonerId::Diff),
-            model_events: ObsTrailCursor::new(),
+            model_events: FilteredObsTrailCursor::new(NotEdgePropagation(ReasonerId::Diff)),
+            edge_propagations: Vec::new(),
             explanation: vec![],
             theory_propagation_causes: Default::default(),
+            root_propagations: Vec::new(),
+            explanation_cache: Default::default(),
             internal_propagate_queue: Default::default(),
-            internal_dijkstra_states: Default::default(),
+            deferred_updates: Vec::new(),
+            remaining_budget: u64::MAX,
+            distance_cache: Default::default(),
+            overflow: OverflowGuard::new(INT_CST_MAX),
+        }
+    }
+
+    /// Creates a new STN whose timepoints are expected to take their values in `[-horizon, horizon]`.
+    ///
+    /// The weight of each inserted edge whose timepoints have their domains within the horizon is checked to be
+    /// in `[-2*horizon - 1, 2*horizon]`, outside of which the edge is either trivially satisfied or trivially violated
+    /// (see [OverflowPolicy]). This ensures that the propagation never overflows, provided that the horizon does not
+    /// exceed [MAX_HORIZON]. Edges on timepoints that may be out of the horizon are kept as is, and their propagation
+    /// is subject to the overflow policy.
+    pub fn with_horizon(config: StnConfig, horizon: W) -> Result<Self, OverflowError> {
+        if !(0..=MAX_HORIZON).contains(&horizon) {
+            return Err(OverflowError::InvalidHorizon(horizon));
         }
+        let mut stn = Self::new(config);
+        stn.overflow = OverflowGuard::new(horizon);
+        Ok(stn)
+    }
+
+    /// Bound on the absolute value of all timepoints (the largest representable value if no horizon was given).
+    pub fn horizon(&self) -> W {
+        self.overflow.horizon()
+    }
+
+    /// Returns true if the domains of both timepoints are within the horizon, in which case the weight of an edge
+    /// between them can be clamped to the safe range of the horizon.
+    fn within_horizon(&self, source: Timepoint, target: Timepoint, domains: &Domains) -> bool {
+        self.overflow.within_horizon(domains.bounds(source)) && self.overflow.within_horizon(domains.bounds(target))
+    }
+
+    /// First overflow that occurred in the STN, only recorded with the [OverflowPolicy::Error] policy.
+    pub fn overflow_error(&self) -> Option<OverflowError> {
+        self.overflow.error()
     }
     pub fn num_nodes(&self) -> u32 {
         (self.active_propagators.len() / 2) as u32
@@ -285,36 +430,16 @@ impl StnTheory {
     ) {
         let source = source.into();
         let target = target.into();
+        let within_horizon = self.within_horizon(source, target, domains);
+        let weight = self
+            .overflow
+            .weight(self.config.overflow_policy, weight, within_horizon);
         while u32::from(source) >= self.num_nodes() || u32::from(target) >= self.num_nodes() {
             self.reserve_timepoint();
         }
 
-        // literal that is true if the edge is within its validity scope (i.e. both timepoints are present)
-        // edge_valid <=> presence(source) & presence(target)
-        let edge_valid = domains.presence(literal.variable());
-        debug_assert!(domains.implies(edge_valid, domains.presence(source)));
-        debug_assert!(domains.implies(edge_valid, domains.presence(target)));
-
-        // the propagator is valid when `presence(target) => edge_valid`.
-        // This is because in this case, the modification to the target's domain are only meaningful if the edge is present.
-        // Once the propagator is valid, it can be propagated as soon as its `active` literal becomes true.
-
-        // determine a literal that is true iff a source to target propagator is valid
-        let target_propagator_valid = if domains.implies(domains.presence(target), edge_valid) {
-            // it is statically known that `presence(target) => edge_valid`,
-            // the propagator is always valid
-            Lit::TRUE
-        } else {
-            // given that `presence(source) & presence(target) <=> edge_valid`, we can infer that the propagator becomes valid
-            // (i.e. `presence(target) => edge_valid` holds) when `presence(source)` becomes true
-            domains.presence(source)
-        };
-        // determine a literal that is true iff a target to source is valid
-        let source_propagator_valid = if domains.implies(domains.presence(source), edge_valid) {
-            Lit::TRUE
-        } else {
-            domains.presence(target)
-        };
+        let (target_propagator_valid, source_propagator_valid) =
+            Self::propagators_validity(literal, source, target, domains);
         let propagators = [
             // normal edge:  active <=> source ---(weight)---> target
             Propagator {
@@ -349,6 +474,103 @@ impl StnTheory {
         }
     }
 
+    /// Adds an edge whose weight depends on the value of the `guard` literal:
+    ///
+    /// `target - source <= (if guard { weight_if_true } else { weight_if_false })`
+    ///
+    /// The edge is represented internally by two mutually exclusive edges over the same pair of timepoints,
+    /// respectively enabled by `guard` and `!guard`. Unlike a reified edge, the guard is not entailed by
+    /// the satisfaction of the constraint but may be made false if the first edge would create a negative cycle
+    /// (and true if the second one would).
+    ///
+    /// The constraint only holds if both timepoints are present, which must be implied by the presence of the guard.
+    pub fn add_conditional_edge(
+        &mut self,
+        guard: Lit,
+        source: impl Into<Timepoint>,
+        target: impl Into<Timepoint>,
+        weight_if_true: W,
+        weight_if_false: W,
+        domains: &Domains,
+    ) {
+        let source = source.into();
+        let target = target.into();
+        while u32::from(source) >= self.num_nodes() || u32::from(target) >= self.num_nodes() {
+            self.reserve_timepoint();
+        }
+        let (target_propagator_valid, source_propagator_valid) =
+            Self::propagators_validity(guard, source, target, domains);
+
+        let within_horizon = self.within_horizon(source, target, domains);
+        for (active, weight) in [(guard, weight_if_true), (!guard, weight_if_false)] {
+            let weight = self
+                .overflow
+                .weight(self.config.overflow_policy, weight, within_horizon);
+            // active => source ---(weight)---> target
+            self.record_propagator(
+                Propagator {
+                    source: SignedVar::plus(source),
+                    target: SignedVar::plus(target),
+                    weight: BoundValueAdd::on_ub(weight),
+                    enabler: Enabler::new(active, target_propagator_valid),
+                },
+                domains,
+            );
+            self.record_propagator(
+                Propagator {
+                    source: SignedVar::minus(target),
+                    target: SignedVar::minus(source),
+                    weight: BoundValueAdd::on_lb(-weight),
+                    enabler: Enabler::new(active, source_propagator_valid),
+                },
+                domains,
+            );
+        }
+    }
+
+    /// Returns the literals that are true iff the `source -> target` and the `target -> source` propagators
+    /// of an edge enabled by `literal` are valid.
+    fn propagators_validity(literal: Lit, source: Timepoint, target: Timepoint, domains: &Domains) -> (Lit, Lit) {
+        // literal that is true if the edge is within its validity scope (i.e. both timepoints are present)
+        // edge_valid <=> presence(source) & presence(target)
+        let edge_valid = domains.presence(literal.variable());
+        // the scope of an edge posted after a propagation at the root (e.g. by a simplification of the model)
+        // omits the presence literals that were already entailed
+        let in_scope = |presence: Lit| {
+            domains.implies(edge_valid, presence)
+                || (domains.entails(presence) && domains.entailing_level(presence) == DecLvl::ROOT)
+        };
+        debug_assert!(in_scope(domains.presence(source)));
+        debug_assert!(in_scope(domains.presence(target)));
+
+        // the propagator is valid when `presence(target) => edge_valid`.
+        // This is because in this case, the modification to the target's domain are only meaningful if the edge is present.
+        // Once the propagator is valid, it can be propagated as soon as its `active` literal becomes true.
+
+        // determine a literal that is true iff a source to target propagator is valid
+        let target_propagator_valid = if domains.implies(domains.presence(target), edge_valid)
+            || domains.implies(domains.presence(target), domains.presence(source))
+        {
+            // it is statically known that `presence(target) => edge_valid`, either directly or because
+            // `presence(target) => presence(source)` (e.g. for the timepoints of a subtask and of its parent),
+            // the propagator is always valid and bounds can be propagated before the presence of the timepoints is known
+            Lit::TRUE
+        } else {
+            // given that `presence(source) & presence(target) <=> edge_valid`, we can infer that the propagator becomes valid
+            // (i.e. `presence(target) => edge_valid` holds) when `presence(source)` becomes true
+            domains.presence(source)
+        };
+        // determine a literal that is true iff a target to source is valid
+        let source_propagator_valid = if domains.implies(domains.presence(source), edge_valid)
+            || domains.implies(domains.presence(source), domains.presence(target))
+        {
+            Lit::TRUE
+        } else {
+            domains.presence(target)
+        };
+        (target_propagator_valid, source_propagator_valid)
+    }
+
     /// Creates and record a new propagator associated with the given [DirEdge], making sure
     /// to set up the watches to enable it when it becomes active and valid.
     fn record_propagator(&mut self, prop: Propagator, domains: &Domains) {
@@ -418,51 +640,69 @@ impl StnTheory {
         debug_assert_eq!(event.svar(), c.target);
 
         let enabler = self.constraints[propagator].enabler.expect("inactive constraint");
-        out_explanation.push(enabler.active);
-        out_explanation.push(model.presence(enabler.active.variable()));
+        self.push_enabler(enabler, model, out_explanation);
 
-        let cause = Lit::from_parts(c.source, val - c.weight);
+        let mut cause = Lit::from_parts(c.source, val - c.weight);
         debug_assert!(model.entails(cause));
 
-        if self.config.deep_explanation {
-            // function that return the stn propagator responsible for this literal being set,
-            // of None if it was not set by a bound propagation of the STN.
-            let propagator_of = |lit: Lit, model: &Domains| -> Option<PropagatorId> {
-                if let Some(event_index) = model.implying_event(lit) {
-                    let event = model.get_event(event_index);
-                    match event.cause.as_external_inference() {
-                        Some(InferenceCause { writer, payload }) if writer == self.identity.writer_id => {
-                            match ModelUpdateCause::from(payload) {
-                                ModelUpdateCause::EdgePropagation(edge) => Some(edge),
-                                ModelUpdateCause::TheoryPropagation(_) => None,
-                            }
-                        }
-                        _ => None,
-                    }
-                } else {
-                    None
-                }
-            };
-            let mut latest_trigger = cause;
-            while let Some(propagator) = propagator_of(latest_trigger, model) {
+        if self.config.deep_explanation || self.config.compressed_explanation {
+            // follow the propagation chain that caused the literal to be set, either up to its start (deep explanation)
+            // or up to the first edge that is not always active (compressed explanation)
+            while let Some(propagator) = self.propagator_of(cause, model) {
                 let c = &self.constraints[propagator];
                 let propagator_enabler = c.enabler.expect("inactive edge");
-                out_explanation.push(propagator_enabler.active);
-                out_explanation.push(model.presence(propagator_enabler.active.variable()));
-                debug_assert_eq!(latest_trigger.svar(), c.target);
-                latest_trigger = Lit::from_parts(c.source, latest_trigger.bound_value() - c.weight);
-                debug_assert!(model.entails(latest_trigger));
+                if !self.config.deep_explanation && !self.always_active(propagator_enabler, model) {
+                    break;
+                }
+                self.push_enabler(propagator_enabler, model, out_explanation);
+                debug_assert_eq!(cause.svar(), c.target);
+                cause = Lit::from_parts(c.source, cause.bound_value() - c.weight);
+                debug_assert!(model.entails(cause));
             }
-            out_explanation.push(latest_trigger);
-        } else {
-            out_explanation.push(cause);
+        }
+        out_explanation.push(cause);
+    }
+
+    /// Returns the STN propagator responsible for this literal being set,
+    /// or None if it was not set by a bound propagation of the STN.
+    fn propagator_of(&self, lit: Lit, model: &Domains) -> Option<PropagatorId> {
+        let event = model.get_event(model.implying_event(lit)?);
+        match event.cause.as_external_inference() {
+            Some(InferenceCause { writer, payload }) if writer == self.identity.writer_id => {
+                match ModelUpdateCause::from(payload) {
+                    ModelUpdateCause::EdgePropagation(edge) => Some(edge),
+                    ModelUpdateCause::TheoryPropagation(_) => None,
+                }
+            }
+            _ => None,
         }
     }
 
+    /// Returns true if the edge with this enabler is active in all branches, i.e., if its enabler holds in the
+    /// initial domains, independently of any decision.
+    fn always_active(&self, enabler: Enabler, model: &Domains) -> bool {
+        let prez = model.presence(enabler.active.variable());
+        let always_true = |l: Lit| model.entails(l) && model.implying_event(l).is_none();
+        always_true(enabler.active) && always_true(prez)
+    }
+
+    /// Adds the enabler of an active edge to the explanation.
+    /// With compressed explanations, the enablers of always active edges are omitted as they hold in all branches.
+    fn push_enabler(&self, enabler: Enabler, model: &Domains, out_explanation: &mut Explanation) {
+        if self.config.compressed_explanation && self.always_active(enabler, model) {
+            return;
+        }
+        out_explanation.push(enabler.active);
+        out_explanation.push(model.presence(enabler.active.variable()));
+    }
+
     /// Explains a model update that was caused by theory propagation, either on edge addition or bound update.
-    #[allow(unused)]
+    ///
+    /// Explaining a propagation on a path requires reconstructing the path, which is costly. As the same propagation
+    /// is typically explained several times (once for each edge it deactivated and in successive conflicts),
+    /// these explanations are cached until backtracking past the decision level at which they were computed.
     fn explain_theory_propagation(
-        &self,
+        &mut self,
         cause: TheoryPropagationCause,
         model: &Domains,
         out_explanation: &mut Explanation,
@@ -472,14 +712,44 @@ impl StnTheory {
                 source,
                 target,
                 triggering_edge,
+                stn_position,
+                model_position,
             } => {
-                let path = self.theory_propagation_path(source, target, triggering_edge, model);
+                // the cause fully identifies the path, and thus the negative cycle it would form
+                if let Some(explanation) = self.explanation_cache.get(&cause) {
+                    for &l in explanation {
+                        out_explanation.push(l);
+                    }
+                    return;
+                }
+
+                // place ourselves in the context in which the propagation occurred, which differs from
+                // the current one if later changes were made at the same decision level.
+                let context = model.snapshot(model_position);
+                let path = match self.theory_propagation_path(source, target, triggering_edge, &context, stn_position) {
+                    Ok(path) => path,
+                    Err(_) => {
+                        // The path cannot be reconstructed, which should not happen on a valid network.
+                        // Rather than failing in the middle of conflict analysis, fall back to a weaker explanation:
+                        // the path was made of edges that were active at the time of the propagation.
+                        self.stats.num_fallback_explanations += 1;
+                        self.edges_active_at(stn_position, triggering_edge)
+                    }
+                };
 
+                let mut explanation = Vec::with_capacity(path.len() * 2);
                 for edge in path {
-                    let enabler = self.constraints[edge].enabler.expect("inactive constraint");
-                    out_explanation.push(enabler.active);
-                    out_explanation.push(model.presence(enabler.active.variable()));
+                    if let Some(enabler) = self.constraints[edge].enabler {
+                        explanation.push(enabler.active);
+                        explanation.push(model.presence(enabler.active.variable()));
+                    }
+                }
+                for &l in &explanation {
+                    out_explanation.push(l);
                 }
+                let level = self.trail.current_decision_level();
+                self.explanation_cache
+                    .insert(cause, level, &explanation, self.config.explanation_cache_size);
             }
             TheoryPropagationCause::Bounds { source, target } => {
                 debug_assert!(model.entails(source) && model.entails(target));
@@ -490,7 +760,22 @@ impl StnTheory {
     }
 
     /// Propagates all edges that have been marked as active since the last propagation.
+    ///
+    /// If a propagation budget is set in the configuration, the propagation of bound changes is interrupted once the
+    /// budget is exhausted and the remaining work is deferred to the next call (see [Self::has_deferred_propagation]).
     pub fn propagate_all(&mut self, model: &mut Domains) -> Result<(), Contradiction> {
+        self.remaining_budget = match self.config.propagation_budget {
+            0 => u64::MAX,
+            budget => budget,
+        };
+        // complete the work deferred by the previous call before handling any new change
+        if !self.deferred_updates.is_empty() {
+            self.run_deferred_propagation(model)?;
+            if !self.deferred_updates.is_empty() {
+                return Ok(());
+            }
+        }
+
         // in first propagation, process each edge once to check if it can be added to the model based on the literals
         // of its extremities. If it is not the case, make its enablers false.
         // This step is equivalent to "bound theory propagation" but need to be made independently because
@@ -499,7 +784,7 @@ impl StnTheory {
             while let Some(c) = self.constraints.next_new_constraint() {
                 // ignore enabled edges, they are dealt with by normal propagation
                 if c.enabler.is_none() {
-                    let new_lb = model.get_bound(c.source) + c.weight;
+                    let new_lb = self.overflow.saturating_add(model.get_bound(c.source), c.weight);
                     let current_ub = model.get_bound(c.target.neg());
                     if !new_lb.compatible_with_symmetric(current_ub) {
                         // the edge is invalid, build a cause to allow explanation
@@ -522,33 +807,31 @@ impl StnTheory {
             }
         }
 
-        while self.model_events.num_pending(model.trail()) > 0 || !self.pending_activations.is_empty() {
+        while self.model_events.num_pending(model.trail()) > 0
+            || !self.edge_propagations.is_empty()
+            || !self.pending_activations.is_empty()
+        {
+            // the bound changes made by edge propagation were already propagated through the STN
+            while let Some(literal) = self.edge_propagations.pop() {
+                // the update may have made its variable absent instead
+                if model.entails(literal) {
+                    self.handle_new_literal(literal, model)?;
+                }
+            }
             // start by propagating all literals changes before considering the new edges.
             // This is necessary because cycle detection on the insertion of a new edge requires
             // a consistent STN and no interference of external bound updates.
             while let Some(ev) = self.model_events.pop(model.trail()).copied() {
                 let literal = ev.new_literal();
-                for (enabler, edge) in self.constraints.enabled_by(literal) {
-                    // mark active
-                    if model.entails(enabler.active) && model.entails(enabler.valid) {
-                        self.pending_activations
-                            .push_back(ActivationEvent::ToEnable(edge, enabler));
-                    }
-                }
-                if self.config.theory_propagation.bounds() {
-                    self.theory_propagate_bound(literal, model)?;
-                }
-                if let Some(x) = ev.cause.as_external_inference() {
-                    if x.writer == self.identity.writer_id
-                        && matches!(ModelUpdateCause::from(x.payload), ModelUpdateCause::EdgePropagation(_))
-                    {
-                        // we generated this event ourselves by edge propagation, we can safely ignore it as it would have been handled
-                        // immediately
-                        continue;
-                    }
-                }
+                self.handle_new_literal(literal, model)?;
                 self.propagate_bound_change(literal, model)?;
+                if !self.deferred_updates.is_empty() {
+                    // budget exhausted, the remaining events will be handled in the next call
+                    return Ok(());
+                }
             }
+            // edges activated in this round, that are theory propagated once all of them are active
+            let mut activated = Vec::new();
             while let Some(event) = self.pending_activations.pop_front() {
                 let ActivationEvent::ToEnable(edge, enabler) = event;
                 let c = &mut self.constraints[edge];
@@ -574,24 +857,48 @@ impl StnTheory {
                             target: c.target,
                             weight: c.weight,
                             id: edge,
+                            activation: self.trail.trail.len() as u32,
                         });
                         self.trail.push(EdgeActivated(edge));
                         self.propagate_new_edge(edge, model)?;
-
-                        if self.config.theory_propagation.edges() {
-                            self.theory_propagate_edge(edge, model)?;
-                        }
+                        activated.push(edge);
                     }
                 }
             }
+            if self.config.theory_propagation.edges() && !activated.is_empty() {
+                // all edges are propagated on the same network, sharing the computation of their distances
+                self.distance_cache.clear();
+                for edge in activated {
+                    self.theory_propagate_edge(edge, model)?;
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Propagates all edges until reaching a fixpoint, regardless of the propagation budget.
+    pub fn propagate_to_fixpoint(&mut self, model: &mut Domains) -> Result<(), Contradiction> {
+        self.propagate_all(model)?;
+        while self.has_deferred_propagation() {
+            self.propagate_all(model)?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if the last call to `propagate_all` exhausted its budget and left some work undone.
+    /// In this case, the network may not be at fixpoint even if no new event occurred.
+    pub fn has_deferred_propagation(&self) -> bool {
+        !self.deferred_updates.is_empty()
+    }
+
     /// Creates a new backtrack point that represents the STN at the point of the method call,
     /// just before the insertion of the backtrack point.
     pub fn set_backtrack_point(&mut self) -> BacktrackLevel {
+        debug_assert!(
+            self.deferred_updates.is_empty(),
+            "Cannot set a backtrack point if some propagation was deferred."
+        );
         assert!(
             self.pending_activations.is_empty(),
             "Cannot set a backtrack point if a propagation is pending. \
@@ -602,9 +909,11 @@ impl StnTheory {
     }
 
     pub fn undo_to_last_backtrack_point(&mut self) -> Option<BacktrackLevel> {
-        // remove pending activations
-        // invariant: there are no pending activation when saving the state
+        // remove pending activations and deferred updates
+        // invariant: there are no pending activation or deferred updates when saving the state
         self.pending_activations.clear();
+        self.deferred_updates.clear();
+        self.edge_propagations.clear();
 
         // undo changes since the last backtrack point
         self.trail.restore_last_with(|ev| match ev {
@@ -618,10 +927,77 @@ impl StnTheory {
             }
         });
         self.constraints.restore_last();
+        self.explanation_cache
+            .invalidate_above(self.trail.current_decision_level());
 
         None
     }
 
+    /// Restores the STN to its state at the root decision level.
+    ///
+    /// This has the same effect as calling `undo_to_last_backtrack_point` until reaching the root,
+    /// but does not undo edge activations one by one: the adjacency lists are instead truncated to
+    /// the edges that were activated at the root level, which always form a prefix of each list.
+    /// The cost is thus linear in the number of active edges rather than in the number of events to undo.
+    pub fn reset_to_root(&mut self) {
+        if self.trail.num_saved() == 0 {
+            return;
+        }
+        // invariant: there are no pending activation or deferred updates when saving the state
+        self.pending_activations.clear();
+        self.deferred_updates.clear();
+        self.edge_propagations.clear();
+
+        let root_len = self.trail.saved_states[0];
+        let mut num_root_edges: RefMap<SignedVar, usize> = Default::default();
+        let mut num_root_causes = 0;
+        for ev in &self.trail.trail[..root_len] {
+            match *ev {
+                EdgeActivated(e) => {
+                    let source = self.constraints[e].source;
+                    let count = num_root_edges.get(source).copied().unwrap_or(0);
+                    num_root_edges.insert(source, count + 1);
+                }
+                Event::AddedTheoryPropagationCause => num_root_causes += 1,
+            }
+        }
+
+        for source in self.active_propagators.keys() {
+            let num_kept = num_root_edges.get(source).copied().unwrap_or(0);
+            let edges = &mut self.active_propagators[source];
+            for e in &edges[num_kept..] {
+                self.constraints[e.id].enabler = None;
+            }
+            edges.truncate(num_kept);
+        }
+        self.theory_propagation_causes.truncate(num_root_causes);
+        self.trail.trail.truncate(root_len);
+        self.trail.saved_states.clear();
+        self.explanation_cache.invalidate_above(DecLvl::ROOT);
+
+        // constraints are rarely added beyond the root, undo them incrementally
+        self.constraints.restore(DecLvl::ROOT);
+    }
+
+    /// Estimates the impact of making `literal` true, as the sum of the bound changes that would immediately result
+    /// from the activation of the edges it enables (ignoring their transitive propagation).
+    /// Returns `None` if the literal does not enable any inactive edge.
+    pub fn activation_impact(&self, literal: Lit, model: &Domains) -> Option<u64> {
+        let mut impact = None;
+        for (enabler, prop) in self.constraints.enabled_by(literal) {
+            // the literal is also watched as the validity condition of some propagators
+            if enabler.active != literal || self.active(prop) || !model.entails(enabler.valid) {
+                continue;
+            }
+            let c = &self.constraints[prop];
+            let new_bound = self.overflow.saturating_add(model.get_bound(c.source), c.weight);
+            let current = model.get_bound(c.target);
+            let change = (current.as_int() as i64 - new_bound.as_int() as i64).max(0);
+            *impact.get_or_insert(0) += change as u64;
+        }
+        impact
+    }
+
     fn active(&self, e: PropagatorId) -> bool {
         self.constraints[e].enabler.is_some()
     }
@@ -641,6 +1017,21 @@ impl StnTheory {
         self.internal_propagate_queue.clear(); // reset to make sure that we are not in a dirty state
     }
 
+    /// Marks as pending the activation of the edges enabled by a new literal, and performs its theory propagation.
+    fn handle_new_literal(&mut self, literal: Lit, model: &mut Domains) -> Result<(), Contradiction> {
+        for (enabler, edge) in self.constraints.enabled_by(literal) {
+            // mark active
+            if model.entails(enabler.active) && model.entails(enabler.valid) {
+                self.pending_activations
+                    .push_back(ActivationEvent::ToEnable(edge, enabler));
+            }
+        }
+        if self.config.theory_propagation.bounds() {
+            self.theory_propagate_bound(literal, model)?;
+        }
+        Ok(())
+    }
+
     fn propagate_bound_change(&mut self, bound: Lit, model: &mut Domains) -> Result<(), Contradiction> {
         if !self.has_edges(bound.variable()) {
             return Ok(());
@@ -657,14 +1048,28 @@ impl StnTheory {
         let source = c.source;
         let target = c.target;
         let weight = c.weight;
-        let source_bound = model.get_bound(source);
-        if model.set_bound(target, source_bound + weight, cause)? {
+        let candidate = self
+            .overflow
+            .add(self.config.overflow_policy, model.get_bound(source), weight);
+        if model.set_bound(target, candidate, cause)? {
+            self.edge_propagations.push(Lit::from_parts(target, candidate));
             self.run_propagation_loop(target, model, true)?;
         }
 
         Ok(())
     }
 
+    /// Resumes the propagation of the bound changes that were deferred by the previous call to `propagate_all`.
+    fn run_deferred_propagation(&mut self, model: &mut Domains) -> Result<(), Contradiction> {
+        self.clean_up_propagation_state();
+        self.stats.num_propagations += 1;
+        for vb in std::mem::take(&mut self.deferred_updates) {
+            self.internal_propagate_queue.push_back(vb);
+            self.pending_updates.insert(vb);
+        }
+        self.run_propagation_queue(None, model)
+    }
+
     fn run_propagation_loop(
         &mut self,
         original: SignedVar,
@@ -677,7 +1082,33 @@ impl StnTheory {
         self.internal_propagate_queue.push_back(original);
         self.pending_updates.insert(original);
 
+        let cycle_origin = if cycle_on_update { Some(original) } else { None };
+        self.run_propagation_queue(cycle_origin, model)
+    }
+
+    /// Propagates the bound changes of all signed variables in the internal queue.
+    ///
+    /// If `cycle_origin` is set, we are propagating a newly inserted edge from its target and any update to
+    /// the bound of the target indicates a negative cycle. Such a propagation is never interrupted.
+    /// Otherwise, the propagation stops when the budget is exhausted and the remaining queue is deferred.
+    fn run_propagation_queue(
+        &mut self,
+        cycle_origin: Option<SignedVar>,
+        model: &mut Domains,
+    ) -> Result<(), Contradiction> {
         while let Some(source) = self.internal_propagate_queue.pop_front() {
+            if cycle_origin.is_none() && self.remaining_budget == 0 {
+                // budget exhausted, defer the propagation of all pending updates
+                self.internal_propagate_queue.push_front(source);
+                for vb in self.internal_propagate_queue.drain(..) {
+                    if self.pending_updates.contains(vb) {
+                        self.pending_updates.remove(vb);
+                        self.deferred_updates.push(vb);
+                    }
+                }
+                self.stats.num_deferrals += 1;
+                return Ok(());
+            }
             let source_bound = model.get_bound(source);
             if !self.pending_updates.contains(source) {
                 // bound was already updated
@@ -692,11 +1123,13 @@ impl StnTheory {
                 let cause = self.identity.inference(ModelUpdateCause::EdgePropagation(e.id));
                 let target = e.target;
                 debug_assert_ne!(source, target);
-                let candidate = source_bound + e.weight;
+                let candidate = self.overflow.add(self.config.overflow_policy, source_bound, e.weight);
 
                 if model.set_bound(target, candidate, cause)? {
+                    self.edge_propagations.push(Lit::from_parts(target, candidate));
                     self.stats.distance_updates += 1;
-                    if cycle_on_update && target == original {
+                    self.remaining_budget = self.remaining_budget.saturating_sub(1);
+                    if cycle_origin == Some(target) {
                         return Err(self.extract_cycle(target, model).into());
                     }
                     self.internal_propagate_queue.push_back(target);
@@ -744,6 +1177,13 @@ impl StnTheory {
         println!("# propagators: {}", self.constraints.num_propagator_groups());
         println!("# propagations: {}", self.stats.num_propagations);
         println!("# domain updates: {}", self.stats.distance_updates);
+        println!("# deferred propagations: {}", self.stats.num_deferrals);
+        if self.stats.num_fallback_explanations > 0 {
+            println!("# fallback explanations: {}", self.stats.num_fallback_explanations);
+        }
+        if self.explanation_cache.num_hits > 0 {
+            println!("# cached explanations: {}", self.explanation_cache.num_hits);
+        }
     }
 
     /******** Distances ********/
@@ -776,20 +1216,24 @@ impl StnTheory {
                 let y_sym = y_sym.with_upper_bound(model.get_bound(y_sym));
                 let dist_y_o = dist_to_origin(y_sym);
 
-                let cycle_length = dist_o_x + w + dist_y_o;
+                // computed on 64 bits as the sum of a bound and of a weight might not be representable
+                let int = |d: BoundValueAdd| d.raw_value() as i64;
+                let cycle_length = int(dist_o_x) + int(w) + int(dist_y_o);
 
-                if cycle_length.raw_value() < 0 {
+                if cycle_length < 0 {
                     // Record the cause so that we can retrieve it if an explanation is needed.
                     // The update of `bound` triggered the propagation. However it is possible that
                     // a less constraining bound would have triggered the propagation as well.
                     // We thus replace `bound` with the smallest update that would have triggered the propagation.
                     // The consequence is that the clauses inferred through explanation will be stronger.
-                    let relaxed_bound = Lit::from_parts(
-                        bound.svar(),
-                        bound.bound_value() - cycle_length - BoundValueAdd::on_ub(1),
+                    // The relaxed bound is capped to the largest representable one, that also triggers the propagation.
+                    let relaxed = (bound.bound_value().as_int() as i64 - cycle_length - 1).min(INT_CST_MAX as i64);
+                    let relaxed_bound = Lit::from_parts(bound.svar(), UpperBound::ub(relaxed as IntCst));
+                    // check that the relaxed bound would have triggered a propagation with the cycle having exactly length -1
+                    debug_assert!(
+                        relaxed == INT_CST_MAX as i64
+                            || int(dist_to_origin(relaxed_bound)) + int(w) + int(dist_y_o) == -1
                     );
-                    // check that the relaxed bound would have triggered a propagation with teh cycle having exactly length -1
-                    debug_assert_eq!((dist_to_origin(relaxed_bound) + w + dist_y_o).raw_value(), -1);
                     let cause = TheoryPropagationCause::Bounds {
                         source: relaxed_bound,
                         target: y_sym,
@@ -820,19 +1264,19 @@ impl StnTheory {
         let target = constraint.target;
         let source = constraint.source;
 
-        // get ownership of data structures used by dijkstra's algorithm.
-        // we let empty place holder that will need to be swapped back.
-        let mut successors = DijkstraState::default();
-        let mut predecessors = DijkstraState::default();
-        std::mem::swap(&mut successors, &mut self.internal_dijkstra_states[0]);
-        std::mem::swap(&mut predecessors, &mut self.internal_dijkstra_states[1]);
-
-        // find all nodes reachable from target(edge), including itself
-        self.distances_from(target, model, &mut successors);
-
-        // find all nodes that can reach source(edge), including itself
-        // predecessors nodes and edge are in the inverse direction
-        self.distances_from(source.neg(), model, &mut predecessors);
+        // get ownership of the distances computed so far in this round, that must be restored afterwards
+        let mut distances = std::mem::take(&mut self.distance_cache);
+        // we need all nodes reachable from target(edge), including itself (successors),
+        // and all nodes that can reach source(edge), including itself (predecessors).
+        // Predecessors nodes and edge are in the inverse direction
+        if distances.search(target).is_none() || distances.search(source.neg()).is_none() {
+            if distances.num_origins() + 2 > MAX_CACHED_DISTANCES {
+                distances.clear();
+            }
+            self.distances_from_all([target, source.neg()], model, &mut distances);
+        }
+        let successors = distances.search(target).unwrap();
+        let predecessors = distances.search(source.neg()).unwrap();
 
         // iterate through all predecessors, they will constitute the source of our shortest paths
         let mut predecessor_entries = predecessors.distances();
@@ -864,8 +1308,8 @@ impl StnTheory {
                             potential.target.neg(),
                             edge,
                             model,
-                            &successors,
-                            &predecessors,
+                            successors,
+                            predecessors,
                         );
                         if !active {
                             // the shortest path was made inactive, ignore this update
@@ -879,10 +1323,15 @@ impl StnTheory {
                             source: pred.neg(),
                             target: potential.target.neg(),
                             triggering_edge: edge,
+                            stn_position: self.trail.trail.len() as u32,
+                            model_position: model.num_events(),
                         };
                         let cause_index = self.theory_propagation_causes.len();
                         self.theory_propagation_causes.push(cause);
                         self.trail.push(Event::AddedTheoryPropagationCause);
+                        if self.trail.current_decision_level() == DecLvl::ROOT {
+                            self.root_propagations.push((cause_index as u32, !potential.presence));
+                        }
 
                         // update the model to force this edge to be inactive
                         if let Err(x) = model.set(
@@ -893,8 +1342,7 @@ impl StnTheory {
                             // inconsistent model after propagation,
                             // restore the dijkstra state entries for future use
                             std::mem::forget(predecessor_entries);
-                            self.internal_dijkstra_states[0] = successors;
-                            self.internal_dijkstra_states[1] = predecessors;
+                            self.distance_cache = distances;
                             return Err(x.into());
                         }
                     }
@@ -903,8 +1351,7 @@ impl StnTheory {
         }
         // restore the dijkstra state entries for future use
         std::mem::forget(predecessor_entries);
-        self.internal_dijkstra_states[0] = successors;
-        self.internal_dijkstra_states[1] = predecessors;
+        self.distance_cache = distances;
 
         // finished propagation without any inconsistency
         Ok(())
@@ -955,16 +1402,29 @@ impl StnTheory {
             path_active(e.target, target, successors) && path_active(e.source.neg(), source.neg(), predecessors);
 
         debug_assert!(
-            !active || {
-                self.theory_propagation_path(source, target, through_edge, model);
-                true
-            },
-            "A panic indicates that we were unable to reconstruct the path, meaning this implementation is invalid."
+            !active
+                || self
+                    .theory_propagation_path(source, target, through_edge, model, u32::MAX)
+                    .is_ok(),
+            "Unable to reconstruct the path, meaning this implementation is invalid."
         );
 
         active
     }
 
+    /// Iterates over all active edges of the network, as `(source, target, weight)` triples
+    /// representing the constraint `target - source <= weight`.
+    pub fn active_edges(&self) -> impl Iterator<Item = (VarRef, VarRef, W)> + '_ {
+        self.active_propagators
+            .entries()
+            .filter(|(source, _)| source.is_plus())
+            .flat_map(|(source, propagators)| {
+                propagators
+                    .iter()
+                    .map(move |p| (source.variable(), p.target.variable(), p.weight.as_ub_add()))
+            })
+    }
+
     pub fn forward_dist(&self, var: VarRef, model: &Domains) -> RefMap<VarRef, W> {
         let mut dists = DijkstraState::default();
         self.distances_from(SignedVar::plus(var), model, &mut dists);
@@ -977,6 +1437,84 @@ impl StnTheory {
         dists.distances().map(|(v, d)| (v.variable(), d.as_lb_add())).collect()
     }
 
+    /// Optimistic version of [forward_dist](Self::forward_dist) where all edges that might still be activated are
+    /// considered, in addition to the active ones.
+    ///
+    /// The distance to each node is a lower bound of its distance in any consistent extension of the network
+    /// (i.e. after the activation of any subset of the undecided edges), which makes it an admissible estimate
+    /// of the maximal separation between the two timepoints.
+    pub fn relaxed_forward_dist(&self, var: VarRef, model: &Domains) -> RefMap<VarRef, W> {
+        self.relaxed_distances_from(SignedVar::plus(var), model)
+            .entries()
+            .map(|(v, d)| (v.variable(), d.as_ub_add()))
+            .collect()
+    }
+
+    /// Optimistic version of [backward_dist](Self::backward_dist), see [relaxed_forward_dist](Self::relaxed_forward_dist).
+    pub fn relaxed_backward_dist(&self, var: VarRef, model: &Domains) -> RefMap<VarRef, W> {
+        self.relaxed_distances_from(SignedVar::minus(var), model)
+            .entries()
+            .map(|(v, d)| (v.variable(), d.as_lb_add()))
+            .collect()
+    }
+
+    /// Computes the one-to-all distances in the graph made of both the active and the potential edges,
+    /// i.e., edges whose enabling literal is not entailed false.
+    ///
+    /// Undecided edges might form negative cycles, in which case shortest paths are not defined.
+    /// Instead, we run the Bellman-Ford algorithm for at most as many rounds as there are nodes,
+    /// which gives distances that are no greater than the one of any simple path.
+    /// Since a shortest path in a consistent extension of the network is simple, the result is admissible.
+    /// Each distance is further bounded by the domains: in any solution, the value of the
+    /// node `n` minus the one of the origin `o` is at least `lb(n) - ub(o)`.
+    fn relaxed_distances_from(&self, origin: SignedVar, model: &Domains) -> RefMap<SignedVar, BoundValueAdd> {
+        // computations are made on i64 as long chains of negative edges might overflow
+        let origin_bound = model.get_bound(origin).as_int() as i64;
+        let min_dist = |node: SignedVar| -(model.get_bound(node.neg()).as_int() as i64) - origin_bound;
+
+        let mut dists: RefMap<SignedVar, i64> = Default::default();
+        dists.insert(origin, 0);
+        let mut frontier = vec![origin];
+        let mut updated = RefSet::new();
+        let mut rounds = 0;
+        while !frontier.is_empty() && rounds < 2 * self.num_nodes() {
+            rounds += 1;
+            let mut next = Vec::new();
+            updated.clear();
+            for curr in frontier {
+                if model.present(curr.variable()) == Some(false) {
+                    continue;
+                }
+                let curr_dist = dists[curr];
+                let active = self.active_propagators[curr].iter().map(|p| (p.target, p.weight));
+                let potential = self
+                    .constraints
+                    .potential_out_edges(curr)
+                    .iter()
+                    .filter(|e| !model.entails(!e.presence))
+                    .map(|e| (e.target, e.weight));
+                for (target, weight) in active.chain(potential) {
+                    if model.present(target.variable()) == Some(false) {
+                        continue;
+                    }
+                    let dist = (curr_dist + weight.raw_value() as i64).max(min_dist(target));
+                    if dists.get(target).map_or(true, |&previous| dist < previous) {
+                        dists.insert(target, dist);
+                        if !updated.contains(target) {
+                            updated.insert(target);
+                            next.push(target);
+                        }
+                    }
+                }
+            }
+            frontier = next;
+        }
+        dists
+            .entries()
+            .map(|(v, &d)| (v, BoundValueAdd::on_ub(d as IntCst)))
+            .collect()
+    }
+
     /// Computes the one-to-all shortest paths in an STN.
     /// The shortest paths are:
     ///  - in the forward graph if the origin is the upper bound of a variable
@@ -1011,7 +1549,7 @@ impl StnTheory {
         state.enqueue(origin, BoundValueAdd::ZERO, None);
 
         // run dijkstra until exhaustion to find all reachable nodes
-        self.run_dijkstra(model, state, |_| false);
+        self.run_dijkstra(model, state, |_| false, u32::MAX);
 
         // convert all reduced distances to true distances.
         for (curr_node, (dist, _)) in state.distances.entries_mut() {
@@ -1021,32 +1559,104 @@ impl StnTheory {
         }
     }
 
+    /// Computes the one-to-all shortest paths from each of the `origins` that does not have a search
+    /// in the `state` yet (see [Self::distances_from]).
+    ///
+    /// All searches progress simultaneously in a single sweep, with a shared queue: whenever several of them
+    /// reach the same node, its outgoing edges have just been loaded and are cheap to process again.
+    fn distances_from_all(
+        &self,
+        origins: impl IntoIterator<Item = SignedVar>,
+        model: &Domains,
+        state: &mut MultiDijkstraState,
+    ) {
+        let first = state.num_origins();
+        for origin in origins {
+            if state.search(origin).is_none() {
+                state.add_origin(origin);
+            }
+        }
+
+        while let Some((search, curr_node, curr_rdist)) = state.dequeue() {
+            if model.present(curr_node.variable()) == Some(false) {
+                continue;
+            }
+            let curr_bound = model.get_bound(curr_node);
+            for prop in &self.active_propagators[curr_node] {
+                if !state.is_final(search, prop.target) && model.present(prop.target.variable()) != Some(false) {
+                    // rdist(orig, tgt) = rdist(orig, curr) + rcost(curr, tgt)   (see run_dijkstra)
+                    let reduced_cost = prop.weight + (curr_bound - model.get_bound(prop.target));
+                    debug_assert!(reduced_cost.raw_value() >= 0);
+                    state.enqueue(search, prop.target, curr_rdist + reduced_cost, Some(prop.id));
+                }
+            }
+        }
+
+        // convert all reduced distances of the new searches to true distances.
+        for search in first..state.num_origins() {
+            let (origin, search) = state.search_mut(search);
+            let origin_bound = model.get_bound(origin);
+            for (curr_node, (dist, _)) in search.distances.entries_mut() {
+                *dist = *dist + (model.get_bound(curr_node) - origin_bound);
+            }
+        }
+    }
+
     /// Appends to `out` a set of edges that constitute a shortest path from `from` to `to`.
     /// The edges are append in no particular order.
+    /// Returns an error, leaving `out` unchanged, if `to` is not reachable from `from`.
     ///
     /// The `state` parameter is provided to avoid allocating memory and will be cleared before usage.
     fn shortest_path_from_to(
         &self,
         from: SignedVar,
         to: SignedVar,
-        model: &Domains,
+        model: &impl DomainsView,
         state: &mut DijkstraState,
+        stn_position: u32,
         out: &mut Vec<PropagatorId>,
-    ) {
+    ) -> Result<(), PathNotFound> {
         state.clear();
         state.enqueue(from, BoundValueAdd::ZERO, None);
 
         // run dijkstra until exhaustion to find all reachable nodes
-        self.run_dijkstra(model, state, |curr| curr == to);
+        self.run_dijkstra(model, state, |curr| curr == to, stn_position);
 
         // go up the predecessors chain to extract the shortest path and append the edge to `out`
+        let path_start = out.len();
         let mut curr = to;
         while curr != from {
-            let edge = state.predecessor(curr).unwrap();
+            let Some(edge) = state.distance(curr).and_then(|_| state.predecessor(curr)) else {
+                out.truncate(path_start);
+                return Err(PathNotFound {
+                    source: from,
+                    target: to,
+                    source_bound: model.get_bound(from),
+                    target_bound: model.get_bound(to),
+                    num_reached: state.distances().count(),
+                    stn_position,
+                });
+            };
             out.push(edge);
             debug_assert_eq!(self.constraints[edge].target, curr);
             curr = self.constraints[edge].source;
         }
+        Ok(())
+    }
+
+    /// Returns all edges activated before the `stn_position`-th event of the trail, together with the `triggering_edge`.
+    fn edges_active_at(&self, stn_position: u32, triggering_edge: PropagatorId) -> Vec<PropagatorId> {
+        let mut edges: Vec<PropagatorId> = self
+            .active_propagators
+            .entries()
+            .flat_map(|(_, propagators)| propagators.iter())
+            .filter(|prop| prop.activation < stn_position)
+            .map(|prop| prop.id)
+            .chain([triggering_edge])
+            .collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
     }
 
     /// Run the Dijkstra algorithm from a pre-initialized queue.
@@ -1054,9 +1664,18 @@ impl StnTheory {
     /// The algorithm will once the queue is exhausted or the predicate `stop` returns true when given
     /// the next node to expand.
     ///
+    /// Only the edges activated before the `stn_position`-th event of the trail are considered,
+    /// which allows running the algorithm on a past version of the network.
+    ///
     /// At the end of the method, the `state` will contain the distances and predecessors of all nodes
     /// reached by the algorithm.
-    fn run_dijkstra(&self, model: &Domains, state: &mut DijkstraState, stop: impl Fn(SignedVar) -> bool) {
+    fn run_dijkstra(
+        &self,
+        model: &impl DomainsView,
+        state: &mut DijkstraState,
+        stop: impl Fn(SignedVar) -> bool,
+        stn_position: u32,
+    ) {
         while let Some((curr_node, curr_rdist)) = state.dequeue() {
             if stop(curr_node) {
                 return;
@@ -1066,8 +1685,11 @@ impl StnTheory {
             }
             let curr_bound = model.get_bound(curr_node);
 
-            // process all outgoing edges
-            for prop in &self.active_propagators[curr_node] {
+            // process all outgoing edges, that are ordered by activation time
+            let active_edges = self.active_propagators[curr_node]
+                .iter()
+                .take_while(|prop| prop.activation < stn_position);
+            for prop in active_edges {
                 if !state.is_final(prop.target) && model.present(prop.target.variable()) != Some(false) {
                     // we do not have a shortest path to this node yet.
                     // compute the reduced_cost of the the edge
@@ -1096,13 +1718,16 @@ impl StnTheory {
     /// The theory propagation was initially triggered by the activation of `through_edge` and
     /// the resulting path was conflicting with an edge `target -> source` that would have form
     /// a negative cycle if activated.
+    ///
+    /// Only the edges activated before the `stn_position`-th event of the trail are considered.
     fn theory_propagation_path(
         &self,
         source: SignedVar,
         target: SignedVar,
         through_edge: PropagatorId,
-        model: &Domains,
-    ) -> Vec<PropagatorId> {
+        model: &impl DomainsView,
+        stn_position: u32,
+    ) -> Result<Vec<PropagatorId>, PathNotFound> {
         let mut path = Vec::with_capacity(8);
 
         let e = &self.constraints[through_edge];
@@ -1112,11 +1737,11 @@ impl StnTheory {
         path.push(through_edge);
 
         // add `e.target ----> target` subpath to path
-        self.shortest_path_from_to(e.target, target, model, &mut dij, &mut path);
+        self.shortest_path_from_to(e.target, target, model, &mut dij, stn_position, &mut path)?;
         // add `source ----> e.source` subpath to path, computed in the reverse direction
-        self.shortest_path_from_to(e.source.neg(), source.neg(), model, &mut dij, &mut path);
+        self.shortest_path_from_to(e.source.neg(), source.neg(), model, &mut dij, stn_position, &mut path)?;
 
-        path
+        Ok(path)
     }
 }
 
@@ -1129,6 +1754,10 @@ impl Theory for StnTheory {
         self.propagate_all(model)
     }
 
+    fn has_deferred_propagation(&self) -> bool {
+        self.has_deferred_propagation()
+    }
+
     fn explain(&mut self, event: Lit, context: u32, model: &Domains, out_explanation: &mut Explanation) {
         match ModelUpdateCause::from(context) {
             ModelUpdateCause::EdgePropagation(edge_id) => {
@@ -1137,37 +1766,33 @@ impl Theory for StnTheory {
             ModelUpdateCause::TheoryPropagation(cause_index) => {
                 let cause = self.theory_propagation_causes[cause_index as usize];
 
-                if matches!(cause, TheoryPropagationCause::Path { .. }) {
-                    // We need to replace ourselves in exactly the context in which this theory propagation occurred.
-                    // Undo all events until we are back in the state where this theory propagation cause
-                    // had not occurred yet.
-                    // KNOWN PROBLEM: this prevents the explanation of arbitrary literals which is required by some heuristics (e.g. LRB)
-                    while (cause_index as usize) < self.theory_propagation_causes.len() {
-                        // get an event to undo
-                        let ev = self
-                            .trail
-                            .pop_within_level()
-                            .expect("Could not restore state, with undoing a decision.");
-
-                        // undo changes
-                        // FIXME: this is copied from the restore_last method and only partially undoes the trail
-                        match ev {
-                            EdgeActivated(e) => {
-                                let c = &mut self.constraints[e];
-                                self.active_propagators[c.source].pop();
-                                c.enabler = None;
-                            }
-                            Event::AddedTheoryPropagationCause => {
-                                self.theory_propagation_causes.pop();
-                            }
-                        }
-                    }
-                }
                 self.explain_theory_propagation(cause, model, out_explanation)
             }
         }
     }
 
+    fn decision_impact(&self, literal: Lit, model: &Domains) -> Option<u64> {
+        self.activation_impact(literal, model)
+    }
+
+    /// Theory propagations on paths made at the root are turned into permanent lemmas `!l_1 | ... | !l_n | !e`,
+    /// where `l_1 ... l_n` are the enablers of the path and `e` the enabler of the edge that would close a negative
+    /// cycle. As they only depend on the structure of the network, these lemmas hold in any context
+    /// and are expensive to rediscover, as it requires a shortest path computation.
+    fn take_permanent_lemmas(&mut self, model: &Domains, out: &mut Vec<Disjunction>) {
+        let mut explanation = Explanation::new();
+        for (cause_index, propagated) in std::mem::take(&mut self.root_propagations) {
+            let cause = self.theory_propagation_causes[cause_index as usize];
+            explanation.clear();
+            self.explain_theory_propagation(cause, model, &mut explanation);
+            let mut lemma: Vec<Lit> = explanation.literals().iter().map(|&l| !l).collect();
+            lemma.push(propagated);
+            if let Some(lemma) = Disjunction::new_non_tautological(lemma) {
+                out.push(lemma);
+            }
+        }
+    }
+
     fn print_stats(&self) {
         self.print_stats()
     }
@@ -1189,6 +1814,16 @@ impl Backtrack for StnTheory {
     fn restore_last(&mut self) {
         self.undo_to_last_backtrack_point();
     }
+
+    fn restore(&mut self, saved_id: DecLvl) {
+        if saved_id == DecLvl::ROOT {
+            self.reset_to_root();
+        } else {
+            while self.current_decision_level() > saved_id {
+                self.restore_last();
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1228,6 +1863,72 @@ mod tests {
         assert_bounds(s, 0, 1, 0, 4);
     }
 
+    #[test]
+    fn test_propagation_budget() {
+        let s = &mut Stn::new_with_config(StnConfig {
+            propagation_budget: 1,
+            ..Default::default()
+        });
+        // chain of timepoints where each one is at least one unit after the previous one
+        let tps: Vec<Timepoint> = (0..5).map(|_| s.add_timepoint(0, 20)).collect();
+        for i in 1..tps.len() {
+            s.add_delay(tps[i - 1], tps[i], 1);
+        }
+        s.propagate_all().unwrap();
+        let lbs = |s: &Stn| tps.iter().map(|&tp| s.model.state.lb(tp)).collect::<Vec<_>>();
+        assert_eq!(lbs(s), vec![0, 1, 2, 3, 4]);
+
+        s.set_backtrack_point();
+        s.set_lb(tps[0], 5);
+        // each call makes a single update and defers the rest
+        s.stn.propagate_all(&mut s.model.state).unwrap();
+        assert!(s.stn.has_deferred_propagation());
+        assert_eq!(lbs(s), vec![5, 6, 2, 3, 4]);
+        s.stn.propagate_all(&mut s.model.state).unwrap();
+        assert!(s.stn.has_deferred_propagation());
+        assert_eq!(lbs(s), vec![5, 6, 7, 3, 4]);
+
+        s.stn.propagate_to_fixpoint(&mut s.model.state).unwrap();
+        assert!(!s.stn.has_deferred_propagation());
+        assert_eq!(lbs(s), vec![5, 6, 7, 8, 9]);
+
+        // deferred work is discarded on backtrack
+        s.set_lb(tps[0], 6);
+        s.stn.propagate_all(&mut s.model.state).unwrap();
+        assert!(s.stn.has_deferred_propagation());
+        s.undo_to_last_backtrack_point();
+        assert!(!s.stn.has_deferred_propagation());
+        assert_eq!(lbs(s), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_flexibility_metrics() {
+        let s = &mut Stn::new();
+        let a = s.add_timepoint(0, 10);
+        let b = s.add_timepoint(0, 10);
+        s.add_timepoint(0, 4);
+        assert_eq!(s.naive_flexibility(), 24);
+
+        // b >= a + 3 and b <= a + 5
+        s.add_edge(b, a, -3);
+        s.add_edge(a, b, 5);
+        s.assert_consistent();
+        // a in [0, 7], b in [3, 10], c in [0, 4]
+        assert_eq!(s.naive_flexibility(), 18);
+        assert!((s.rms_flexibility() - (114.0f64 / 3.0).sqrt()).abs() < 1e-9);
+        // in the earliest schedule, b - a = 3 leaves a slack of 2 on the second edge only
+        assert_eq!(s.sum_of_slacks(), 2);
+
+        // absent timepoints are ignored
+        let p = s.model.new_bvar("p").true_lit();
+        s.model.new_optional_ivar(0, 100, p, "d");
+        s.assert_consistent();
+        assert_eq!(s.naive_flexibility(), 118);
+        s.mark_active(!p);
+        s.assert_consistent();
+        assert_eq!(s.naive_flexibility(), 18);
+    }
+
     #[test]
     fn test_backtracking() {
         let s = &mut Stn::new();
@@ -1267,6 +1968,176 @@ mod tests {
         assert_bounds(s, 0, 1, 0, 6);
     }
 
+    #[test]
+    fn test_reset_to_root() {
+        let s = &mut Stn::new();
+        let a = s.add_timepoint(0, 10);
+        let b = s.add_timepoint(0, 10);
+        let c = s.add_timepoint(0, 10);
+
+        let assert_bounds = |stn: &Stn, a_bounds, b_bounds, c_bounds| {
+            assert_eq!(stn.model.int_bounds(IVar::new(a)), a_bounds);
+            assert_eq!(stn.model.int_bounds(IVar::new(b)), b_bounds);
+            assert_eq!(stn.model.int_bounds(IVar::new(c)), c_bounds);
+        };
+
+        s.add_edge(a, b, 5);
+        let bc = s.add_inactive_edge(b, c, 2);
+        let ca = s.add_inactive_edge(c, a, -1);
+        s.set_ub(a, 3);
+        s.assert_consistent();
+        assert_bounds(s, (0, 3), (0, 8), (0, 10));
+
+        s.set_backtrack_point();
+        s.mark_active(bc);
+        s.assert_consistent();
+        assert_bounds(s, (0, 3), (0, 8), (0, 10));
+        s.set_backtrack_point();
+        s.mark_active(ca);
+        s.assert_consistent();
+        assert_bounds(s, (0, 3), (0, 8), (1, 10));
+
+        // incremental undo and reset must lead to the same state
+        let mut undone = s.clone();
+        undone.undo_to_last_backtrack_point();
+        undone.undo_to_last_backtrack_point();
+        s.reset_to_root();
+        for stn in [&*s, &undone] {
+            assert_eq!(stn.stn.trail.num_saved(), 0);
+            assert_bounds(stn, (0, 3), (0, 8), (0, 10));
+        }
+        for source in s.stn.active_propagators.keys() {
+            let ids = |stn: &Stn| {
+                stn.stn.active_propagators[source]
+                    .iter()
+                    .map(|e| e.id)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(ids(s), ids(&undone));
+        }
+        assert_eq!(
+            s.stn.theory_propagation_causes.len(),
+            undone.stn.theory_propagation_causes.len()
+        );
+
+        // edges deactivated by the reset can be activated again
+        s.set_backtrack_point();
+        s.mark_active(ca);
+        s.assert_consistent();
+        assert_bounds(s, (0, 3), (0, 8), (1, 10));
+        s.mark_active(bc);
+        s.assert_consistent();
+        assert_bounds(s, (0, 3), (0, 8), (1, 10));
+    }
+
+    #[test]
+    fn test_conditional_edges() {
+        let s = &mut Stn::new();
+        let a = s.add_timepoint(0, 10);
+        let b = s.add_timepoint(0, 10);
+        let guard = s.add_conditional_edge(a, b, 2, 5);
+        s.set_ub(a, 1);
+        s.assert_consistent();
+        // no weight is in effect until the guard is decided
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (0, 10));
+
+        s.set_backtrack_point();
+        s.mark_active(guard);
+        s.assert_consistent();
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (0, 3));
+        s.undo_to_last_backtrack_point();
+
+        s.set_backtrack_point();
+        s.mark_active(!guard);
+        s.assert_consistent();
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (0, 6));
+        s.undo_to_last_backtrack_point();
+
+        // the guard is made false when its edge would be violated
+        s.set_backtrack_point();
+        s.set_lb(b, 4);
+        s.assert_consistent();
+        assert!(s.model.entails(!guard));
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (4, 6));
+    }
+
+    #[test]
+    fn test_overflow_policy() {
+        let config = |overflow_policy| StnConfig {
+            overflow_policy,
+            ..Default::default()
+        };
+        assert_eq!(
+            StnTheory::with_horizon(config(OverflowPolicy::Panic), MAX_HORIZON + 1).err(),
+            Some(OverflowError::InvalidHorizon(MAX_HORIZON + 1))
+        );
+
+        // weights beyond the horizon are clamped to an equivalent weight, and the first one is reported
+        let s = &mut Stn {
+            stn: StnTheory::with_horizon(config(OverflowPolicy::Error), 100).unwrap(),
+            model: crate::model::Model::new(),
+        };
+        let a = s.add_timepoint(0, 100);
+        let b = s.add_timepoint(0, 100);
+        s.add_edge(a, b, 1000);
+        s.assert_consistent();
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (0, 100));
+        assert_eq!(
+            s.stn.overflow_error(),
+            Some(OverflowError::WeightOutOfRange {
+                weight: 1000,
+                horizon: 100
+            })
+        );
+        s.add_edge(b, a, -1000);
+        assert!(s.propagate_all().is_err());
+
+        // weights are not clamped on timepoints that may be out of the horizon, which would remove solutions
+        let s = &mut Stn {
+            stn: StnTheory::with_horizon(config(OverflowPolicy::Error), 100).unwrap(),
+            model: crate::model::Model::new(),
+        };
+        let a = s.add_timepoint(0, 1000);
+        let b = s.add_timepoint(0, 1000);
+        s.add_edge(a, b, 1000);
+        s.add_edge(b, a, -1000);
+        s.assert_consistent();
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (1000, 1000));
+        assert_eq!(s.stn.overflow_error(), None);
+
+        // the sum of a bound and a weight is saturated in a way that preserves (in)consistency
+        let s = &mut Stn::new_with_config(config(OverflowPolicy::Saturate));
+        let a = s.add_timepoint(INT_CST_MIN, INT_CST_MAX);
+        let b = s.add_timepoint(INT_CST_MIN, INT_CST_MAX);
+        s.add_edge(a, b, 2 * INT_CST_MAX);
+        s.assert_consistent();
+        assert_eq!(s.model.int_bounds(IVar::new(b)), (INT_CST_MIN, INT_CST_MAX));
+        assert_eq!(s.stn.overflow_error(), None);
+        s.set_ub(a, -2);
+        s.add_edge(a, b, IntCst::MIN + 1);
+        assert!(s.propagate_all().is_err());
+    }
+
+    #[test]
+    fn test_activation_impact() {
+        let s = &mut Stn::new();
+        let a = s.add_timepoint(0, 10);
+        let b = s.add_timepoint(0, 10);
+        let ab = s.add_inactive_edge(a, b, -5);
+        s.assert_consistent();
+
+        // ub(b) <= 10 - 5  and  lb(a) >= 0 + 5
+        assert_eq!(s.stn.activation_impact(ab, &s.model.state), Some(10));
+        // a - b <= 4 is already entailed by the bounds
+        assert_eq!(s.stn.activation_impact(!ab, &s.model.state), Some(0));
+        let unrelated = s.model.new_bvar("x").true_lit();
+        assert_eq!(s.stn.activation_impact(unrelated, &s.model.state), None);
+
+        s.mark_active(ab);
+        s.assert_consistent();
+        assert_eq!(s.stn.activation_impact(ab, &s.model.state), None);
+    }
+
     #[test]
     fn test_explanation() -> Result<(), Contradiction> {
         let stn = &mut Stn::new();
@@ -1306,6 +2177,68 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_compressed_explanation() -> Result<(), Contradiction> {
+        for compressed_explanation in [false, true] {
+            let stn = &mut Stn::new_with_config(StnConfig {
+                compressed_explanation,
+                deep_explanation: false,
+                ..Default::default()
+            });
+            // chain "abcd" of always active edges
+            let a = stn.add_timepoint(0, 10);
+            let b = stn.add_timepoint(0, 10);
+            let c = stn.add_timepoint(0, 10);
+            let d = stn.add_timepoint(0, 10);
+            stn.add_delay(a, b, 1);
+            stn.add_delay(b, c, 1);
+            stn.add_delay(c, d, 1);
+            stn.propagate_all()?;
+
+            stn.set_backtrack_point();
+            stn.set_lb(a, 2);
+            stn.propagate_all()?;
+            assert_eq!(stn.model.state.bounds(d), (5, 10));
+
+            let implying = stn.implying_literals(Lit::geq(d, 5)).unwrap();
+            if compressed_explanation {
+                // the chain is summarized by the bound at its start
+                assert_eq!(implying, vec![Lit::geq(a, 2)]);
+            } else {
+                assert!(implying.contains(&Lit::geq(c, 4)));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_interchangeable_timepoints() {
+        let stn = &mut Stn::new();
+        // tasks with a start and an end, the last one being optional and the first ones having the same duration
+        let mut tasks = Vec::new();
+        for (duration, optional) in [(5, false), (5, false), (5, false), (3, false), (5, true), (5, true)] {
+            let presence = if optional {
+                stn.model.new_presence_variable(Lit::TRUE, "").true_lit()
+            } else {
+                Lit::TRUE
+            };
+            let start: Timepoint = stn.model.new_optional_ivar(0, 100, presence, "").into();
+            let end: Timepoint = stn.model.new_optional_ivar(0, 100, presence, "").into();
+            stn.add_delay(start, end, duration);
+            stn.add_delay(end, start, -duration);
+            tasks.push((start, end));
+        }
+        let starts = |ids: &[usize]| ids.iter().map(|&i| tasks[i].0).collect::<Vec<_>>();
+        let groups = stn.stn.interchangeable_timepoints(&stn.model.state);
+        // the ends are exchanged together with the starts and are not part of another group
+        assert_eq!(groups, vec![starts(&[0, 1, 2]), starts(&[4, 5])]);
+
+        // the third task must now end before the start of the fourth one
+        stn.add_delay(tasks[2].1, tasks[3].0, 0);
+        let groups = stn.stn.interchangeable_timepoints(&stn.model.state);
+        assert_eq!(groups, vec![starts(&[0, 1]), starts(&[4, 5])]);
+    }
+
     #[test]
     fn test_optionals() -> Result<(), Contradiction> {
         let stn = &mut Stn::new();
@@ -1376,6 +2309,39 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_implied_presence() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new();
+        let prez_a = stn.model.new_bvar("prez_a").true_lit();
+        let prez_b = stn.model.new_bvar("prez_b").true_lit();
+        let a = stn.model.new_optional_ivar(0, 10, prez_a, "a");
+        let b = stn.model.new_optional_ivar(0, 10, prez_b, "b");
+        // the scope of the edge is created before it is known that `prez_b => prez_a`,
+        // and is thus not simplified into `prez_b`
+        let scope = stn.model.get_conjunctive_scope(&[prez_a, prez_b]);
+        assert_ne!(scope, prez_b);
+        stn.model.state.add_implication(prez_b, prez_a);
+        let edge = stn.model.scoped_lit(scope);
+        // b >= a + 3
+        stn.stn
+            .add_reified_edge(edge, VarRef::from(b), VarRef::from(a), -3, &stn.model.state);
+
+        // the lower bound of `a` is propagated to `b` although neither presence is known
+        stn.propagate_all()?;
+        stn.model.state.set_lb(a, 5, Cause::Decision)?;
+        stn.propagate_all()?;
+        assert_eq!(stn.model.domain_of(b), (8, 10));
+        // but not the other way around, as `a` may be present without `b`
+        stn.model.state.set_ub(b, 9, Cause::Decision)?;
+        stn.propagate_all()?;
+        assert_eq!(stn.model.domain_of(a), (5, 10));
+        // `b` cannot be present with `a >= 8`
+        stn.model.state.set_lb(a, 8, Cause::Decision)?;
+        stn.propagate_all()?;
+        assert!(stn.model.entails(!prez_b));
+        Ok(())
+    }
+
     #[test]
     fn test_theory_propagation_edges_simple() -> Result<(), Contradiction> {
         let stn = &mut Stn::new_with_config(StnConfig {
@@ -1412,6 +2378,134 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_root_lemmas() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new_with_config(StnConfig {
+            theory_propagation: TheoryPropagationLevel::Edges,
+            ..Default::default()
+        });
+        // two copies of a triangle whose last edge would close a negative cycle
+        let mut triangle = || {
+            let a = stn.add_timepoint(0, 10);
+            let b = stn.add_timepoint(0, 10);
+            let c = stn.add_timepoint(0, 10);
+            [
+                stn.add_inactive_edge(a, b, -1),
+                stn.add_inactive_edge(b, c, -1),
+                stn.add_inactive_edge(c, a, 1),
+            ]
+        };
+        let [ab, bc, ca] = triangle();
+        let [de, ef, fd] = triangle();
+        let mut lemmas = Vec::new();
+
+        // deactivating `ca` at the root yields a permanent lemma
+        stn.model.state.set(ab, Cause::Decision)?;
+        stn.model.state.set(bc, Cause::Decision)?;
+        stn.propagate_all()?;
+        assert!(stn.model.entails(!ca));
+        stn.stn.take_permanent_lemmas(&stn.model.state, &mut lemmas);
+        assert_eq!(lemmas.len(), 1);
+        for l in [!ab, !bc, !ca] {
+            assert!(lemmas[0].contains(l));
+        }
+
+        // propagations beyond the root do not
+        lemmas.clear();
+        stn.set_backtrack_point();
+        stn.model.state.set(de, Cause::Decision)?;
+        stn.model.state.set(ef, Cause::Decision)?;
+        stn.propagate_all()?;
+        assert!(stn.model.entails(!fd));
+        stn.stn.take_permanent_lemmas(&stn.model.state, &mut lemmas);
+        assert!(lemmas.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_source_distances() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new();
+        let tps: Vec<Timepoint> = (0..6).map(|_| stn.add_timepoint(0, 20)).collect();
+        for (source, target, weight) in [
+            (0, 1, 5),
+            (1, 2, -2),
+            (0, 2, 4),
+            (2, 3, 1),
+            (3, 1, 6),
+            (4, 5, 3),
+            (5, 0, -1),
+        ] {
+            stn.add_edge(tps[source], tps[target], weight);
+        }
+        stn.propagate_all()?;
+
+        let sorted = |state: &DijkstraState| {
+            let mut distances: Vec<_> = state.distances().collect();
+            distances.sort_by_key(|(node, _)| usize::from(*node));
+            distances
+        };
+        let theory = &stn.stn;
+        let domains = &stn.model.state;
+        let origins = [
+            SignedVar::plus(tps[0]),
+            SignedVar::minus(tps[2]),
+            SignedVar::plus(tps[4]),
+            SignedVar::minus(tps[0]),
+        ];
+        let mut multi = MultiDijkstraState::default();
+        theory.distances_from_all(origins[..2].iter().copied(), domains, &mut multi);
+        // searches from new origins are added to the existing ones, each origin being searched only once
+        theory.distances_from_all(origins[1..].iter().copied(), domains, &mut multi);
+        assert_eq!(multi.num_origins(), origins.len());
+
+        let mut single = DijkstraState::default();
+        for origin in origins {
+            theory.distances_from(origin, domains, &mut single);
+            assert_eq!(sorted(multi.search(origin).unwrap()), sorted(&single));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_explanation_cache() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new_with_config(StnConfig {
+            theory_propagation: TheoryPropagationLevel::Edges,
+            explanation_cache_size: 4,
+            ..Default::default()
+        });
+        let a = stn.add_timepoint(0, 10);
+        let b = stn.add_timepoint(0, 10);
+        let c = stn.add_timepoint(0, 10);
+        let ab = stn.add_inactive_edge(a, b, -1);
+        let bc = stn.add_inactive_edge(b, c, -1);
+        let ca = stn.add_inactive_edge(c, a, 1);
+        stn.propagate_all()?;
+
+        stn.set_backtrack_point();
+        stn.model.state.set(ab, Cause::Decision)?;
+        stn.propagate_all()?;
+        stn.set_backtrack_point();
+        stn.model.state.set(bc, Cause::Decision)?;
+        stn.propagate_all()?;
+        // a -> b -> c -> a would be a negative cycle
+        assert!(stn.model.entails(!ca));
+
+        let explanation = stn.implying_literals(!ca).unwrap();
+        assert_eq!(stn.stn.explanation_cache.num_hits, 0);
+        assert_eq!(stn.implying_literals(!ca).unwrap(), explanation);
+        assert_eq!(stn.stn.explanation_cache.num_hits, 1);
+
+        // the explanation is dropped when backtracking past the level at which it was computed
+        stn.undo_to_last_backtrack_point();
+        stn.set_backtrack_point();
+        stn.model.state.set(bc, Cause::Decision)?;
+        stn.propagate_all()?;
+        assert_eq!(stn.implying_literals(!ca).unwrap(), explanation);
+        assert_eq!(stn.stn.explanation_cache.num_hits, 1);
+
+        Ok(())
+    }
+
     #[test]
     fn test_distances() -> Result<(), Contradiction> {
         let stn = &mut Stn::new();
@@ -1466,6 +2560,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_relaxed_distances() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new();
+        let a = stn.add_timepoint(0, 100);
+        let b = stn.add_timepoint(0, 100);
+        let c = stn.add_timepoint(0, 100);
+        stn.add_edge(a, b, 10);
+        let bc = stn.add_inactive_edge(b, c, 5);
+        let ac = stn.add_inactive_edge(a, c, 20);
+        stn.propagate_all()?;
+
+        let dists = stn.stn.forward_dist(a, &stn.model.state);
+        assert_eq!(dists.entries().count(), 2);
+        let relaxed = stn.stn.relaxed_forward_dist(a, &stn.model.state);
+        assert_eq!(relaxed.entries().count(), 3);
+        assert!(relaxed[b] <= 10);
+        assert!(relaxed[c] <= 15);
+        for (_, &d) in relaxed.entries() {
+            assert!(d >= -100);
+        }
+
+        // the relaxed distances are lower bounds of the distances in all extensions of the network
+        for lits in [[bc, ac], [bc, !ac], [!bc, ac], [!bc, !ac]] {
+            stn.set_backtrack_point();
+            let consistent = lits
+                .iter()
+                .all(|&lit| stn.model.state.set(lit, Cause::Decision).is_ok())
+                && stn.propagate_all().is_ok();
+            if !consistent {
+                stn.undo_to_last_backtrack_point();
+                continue;
+            }
+            for (v, &d) in stn.stn.forward_dist(a, &stn.model.state).entries() {
+                assert!(relaxed[v] <= d);
+            }
+            if lits == [!bc, !ac] {
+                // disabled edges are ignored
+                assert!(!stn.stn.relaxed_forward_dist(a, &stn.model.state).contains(c));
+            }
+            stn.undo_to_last_backtrack_point();
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_negative_self_loop() {
         let stn = &mut Stn::new();
@@ -1620,6 +2758,86 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_explanation_after_later_activations() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new_with_config(StnConfig {
+            theory_propagation: TheoryPropagationLevel::Edges,
+            ..Default::default()
+        });
+        let a = stn.add_timepoint(0, 10);
+        let b = stn.add_timepoint(0, 10);
+        let c = stn.add_timepoint(0, 10);
+        let d = stn.add_timepoint(0, 10);
+        let ab = stn.add_inactive_edge(a, b, 1);
+        let bc = stn.add_inactive_edge(b, c, 1);
+        let cd = stn.add_inactive_edge(c, d, 1);
+        let cd_shortcut = stn.add_inactive_edge(c, d, 0);
+        let da = stn.add_inactive_edge(d, a, -4);
+        stn.propagate_all()?;
+
+        for edge in [ab, cd, bc] {
+            stn.set_backtrack_point();
+            stn.mark_active(edge);
+            stn.propagate_all()?;
+        }
+        // a -> b -> c -> d -> a would be a negative cycle
+        assert_eq!(stn.model.state.value(da), Some(false));
+
+        // activate a shortcut in the same decision level, which should be ignored when explaining the previous inference
+        stn.mark_active(cd_shortcut);
+        stn.propagate_all()?;
+
+        let num_events = stn.stn.trail.trail.len();
+        let explanation = stn.implying_literals(!da).unwrap();
+        assert!(explanation.contains(&ab));
+        assert!(explanation.contains(&bc));
+        assert!(explanation.contains(&cd));
+        assert!(!explanation.contains(&cd_shortcut));
+        // the network was not modified
+        assert_eq!(stn.stn.trail.trail.len(), num_events);
+        assert_eq!(stn.stn.active_propagators[SignedVar::plus(c)].len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_not_found() -> Result<(), Contradiction> {
+        let stn = &mut Stn::new();
+        let a = stn.add_timepoint(0, 10);
+        let b = stn.add_timepoint(0, 10);
+        let c = stn.add_timepoint(0, 10);
+        stn.add_edge(a, b, 1);
+        stn.propagate_all()?;
+
+        let (a, b, c) = (SignedVar::plus(a), SignedVar::plus(b), SignedVar::plus(c));
+        let model = &stn.model.state;
+        let mut dij = DijkstraState::default();
+        let mut path = Vec::new();
+        assert!(stn
+            .stn
+            .shortest_path_from_to(a, b, model, &mut dij, u32::MAX, &mut path)
+            .is_ok());
+        assert_eq!(path.len(), 1);
+
+        // c is not reachable, and the path is left unchanged
+        let err = stn
+            .stn
+            .shortest_path_from_to(a, c, model, &mut dij, u32::MAX, &mut path)
+            .unwrap_err();
+        assert_eq!(err.num_reached, 2);
+        assert_eq!(err.target_bound, UpperBound::ub(10));
+        assert_eq!(path.len(), 1);
+        // neither is b when ignoring all activations
+        assert!(stn
+            .stn
+            .shortest_path_from_to(a, b, model, &mut dij, 0, &mut path)
+            .is_err());
+
+        // both directions of the edge were active
+        assert_eq!(stn.stn.edges_active_at(u32::MAX, path[0]).len(), 2);
+        Ok(())
+    }
+
     #[test]
     fn test_theory_propagation_bounds() -> Result<(), Contradiction> {
         let stn = &mut Stn::new_with_config(StnConfig {
@@ -51,13 +51,23 @@ impl DijkstraState {
     /// Add a node to the queue, indicating the distance from the origin and the latest edge
     /// on the path from the origin to this node.
     pub fn enqueue(&mut self, node: SignedVar, dist: BoundValueAdd, incoming_edge: Option<PropagatorId>) {
+        if self.record(node, dist, incoming_edge) {
+            self.queue.push(HeapElem { dist, node });
+        }
+    }
+
+    /// Records the distance of a node and the latest edge on the path to it, if the distance is smaller
+    /// than the one previously known. Returns true if the distance was recorded.
+    fn record(&mut self, node: SignedVar, dist: BoundValueAdd, incoming_edge: Option<PropagatorId>) -> bool {
         let previous_dist = match self.distances.get(node) {
             None => BoundValueAdd::MAX,
             Some((prev, _)) => *prev,
         };
         if dist < previous_dist {
             self.distances.insert(node, (dist, incoming_edge));
-            self.queue.push(HeapElem { dist, node });
+            true
+        } else {
+            false
         }
     }
 
@@ -65,22 +75,21 @@ impl DijkstraState {
     /// Nodes are removed by increasing distance to the origin.
     /// Each node can only be extracted once.
     pub fn dequeue(&mut self) -> Option<(SignedVar, BoundValueAdd)> {
-        match self.queue.pop() {
-            Some(e) => {
-                debug_assert!(self.latest <= e.dist);
-                debug_assert!(self.distances[e.node].0 <= e.dist);
-                self.latest = e.dist;
-                if self.distances[e.node].0 == e.dist {
-                    // node with the best distance from origin
-                    Some((e.node, e.dist))
-                } else {
-                    // a node with a better distance was previously extracted, ignore this one
-                    // as it can not contribute to a shortest path
-                    None
-                }
+        while let Some(e) = self.queue.pop() {
+            if self.settle(e) {
+                return Some((e.node, e.dist));
             }
-            None => None,
         }
+        None
+    }
+
+    /// Processes an element removed from a queue, returning false if a better distance to its node was
+    /// previously extracted, in which case it cannot contribute to a shortest path and should be ignored.
+    fn settle(&mut self, e: HeapElem) -> bool {
+        debug_assert!(self.latest <= e.dist);
+        debug_assert!(self.distances[e.node].0 <= e.dist);
+        self.latest = e.dist;
+        self.distances[e.node].0 == e.dist
     }
 
     /// Returns the distance from the origin to this node, or `None` if the node was not reached
@@ -121,3 +130,81 @@ impl Default for DijkstraState {
         }
     }
 }
+
+/// Distances from several origins, computed by Dijkstra searches that progress simultaneously in a single sweep,
+/// with a shared queue. It is intended to be reusable across multiple runs: searches from new origins
+/// can be added as long as the graph does not change.
+#[derive(Clone, Default)]
+pub(crate) struct MultiDijkstraState {
+    /// Origins of the searches, the i-th search being the one from the i-th origin.
+    origins: Vec<SignedVar>,
+    /// State of each search, whose own queue is unused. States beyond the number of origins are only kept
+    /// to reuse their memory.
+    searches: Vec<DijkstraState>,
+    /// Elements of the queue of all searches, together with the index of their search.
+    queue: BinaryHeap<(HeapElem, usize)>,
+}
+
+impl MultiDijkstraState {
+    pub fn clear(&mut self) {
+        self.origins.clear();
+        self.queue.clear();
+    }
+
+    pub fn num_origins(&self) -> usize {
+        self.origins.len()
+    }
+
+    /// Adds a search from the origin, returning its index.
+    pub fn add_origin(&mut self, origin: SignedVar) -> usize {
+        let search = self.origins.len();
+        self.origins.push(origin);
+        if self.searches.len() == search {
+            self.searches.push(DijkstraState::default());
+        }
+        self.searches[search].clear();
+        self.enqueue(search, origin, BoundValueAdd::ZERO, None);
+        search
+    }
+
+    /// Returns the state of the search from the origin, if any.
+    pub fn search(&self, origin: SignedVar) -> Option<&DijkstraState> {
+        let search = self.origins.iter().position(|&o| o == origin)?;
+        Some(&self.searches[search])
+    }
+
+    /// Returns the origin and the state of the search with the given index.
+    pub fn search_mut(&mut self, search: usize) -> (SignedVar, &mut DijkstraState) {
+        (self.origins[search], &mut self.searches[search])
+    }
+
+    /// Add a node to the queue of a search (see [DijkstraState::enqueue]).
+    pub fn enqueue(
+        &mut self,
+        search: usize,
+        node: SignedVar,
+        dist: BoundValueAdd,
+        incoming_edge: Option<PropagatorId>,
+    ) {
+        if self.searches[search].record(node, dist, incoming_edge) {
+            self.queue.push((HeapElem { dist, node }, search));
+        }
+    }
+
+    /// Removes the next element in the queue of any search, together with the index of this search.
+    /// In each search, nodes are removed by increasing distance to its origin and only once.
+    pub fn dequeue(&mut self) -> Option<(usize, SignedVar, BoundValueAdd)> {
+        while let Some((e, search)) = self.queue.pop() {
+            if self.searches[search].settle(e) {
+                return Some((search, e.node, e.dist));
+            }
+        }
+        None
+    }
+
+    /// Returns true if the node has a distance that is guaranteed not to change in subsequent iterations of
+    /// the search (see [DijkstraState::is_final]).
+    pub fn is_final(&self, search: usize, node: SignedVar) -> bool {
+        self.searches[search].is_final(node)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::backtrack::DecLvl;
+use crate::core::Lit;
+use std::collections::VecDeque;
+
+/// A small cache of the explanations of negative cycles, with a least-recently-used eviction policy.
+///
+/// Each explanation is keyed on the cause of the propagation it explains and is tagged with the decision level
+/// at which it was inserted. It must be invalidated (with [ExplanationCache::invalidate_above]) when backtracking
+/// past this level, as the cycle might not exist anymore.
+#[derive(Clone)]
+pub struct ExplanationCache<K> {
+    /// Cached explanations, from the least to the most recently used.
+    entries: VecDeque<Entry<K>>,
+    /// Number of explanations that were retrieved from the cache.
+    pub num_hits: u64,
+}
+
+impl<K> Default for ExplanationCache<K> {
+    fn default() -> Self {
+        ExplanationCache {
+            entries: VecDeque::new(),
+            num_hits: 0,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Entry<K> {
+    key: K,
+    level: DecLvl,
+    explanation: Vec<Lit>,
+}
+
+impl<K: Eq> ExplanationCache<K> {
+    /// Returns the explanation associated to the given key, if it is in the cache.
+    pub fn get(&mut self, key: &K) -> Option<&[Lit]> {
+        let index = self.entries.iter().position(|e| &e.key == key)?;
+        // move the entry to the back, as the most recently used one
+        let entry = self.entries.remove(index).unwrap();
+        self.entries.push_back(entry);
+        self.num_hits += 1;
+        self.entries.back().map(|e| e.explanation.as_slice())
+    }
+
+    /// Records an explanation, evicting the least recently used explanations
+    /// so that the cache holds at most `capacity` of them. A capacity of 0 disables the cache.
+    pub fn insert(&mut self, key: K, level: DecLvl, explanation: &[Lit], capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        while self.entries.len() >= capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            key,
+            level,
+            explanation: explanation.to_vec(),
+        });
+    }
+
+    /// Removes all explanations inserted after the given decision level.
+    pub fn invalidate_above(&mut self, level: DecLvl) {
+        self.entries.retain(|e| e.level <= level);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::core::{BoundValueAdd, IntCst, UpperBound, INT_CST_MAX};
+use crate::reasoners::stn::theory::W;
+use std::str::FromStr;
+
+/// Largest horizon for which the sum of a bound within the horizon and of a weight within its safe range
+/// (see [OverflowGuard::weight]) is always representable.
+pub const MAX_HORIZON: W = (IntCst::MAX - 1) / 3;
+
+/// Behavior of the STN when its arithmetic on bounds and weights would overflow.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// Panic with a description of the overflowing operation.
+    Panic,
+    /// Saturate the result at the horizon, in a way that neither misses nor invents an inconsistency
+    /// for timepoints within the horizon.
+    Saturate,
+    /// Saturate the result, and record the first overflow so that it can be reported as an error
+    /// (see [StnTheory::overflow_error](crate::reasoners::stn::theory::StnTheory::overflow_error)).
+    Error,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "panic" => Ok(OverflowPolicy::Panic),
+            "saturate" => Ok(OverflowPolicy::Saturate),
+            "error" => Ok(OverflowPolicy::Error),
+            x => Err(format!(
+                "Unknown overflow policy: {x}. Valid options: panic, saturate, error"
+            )),
+        }
+    }
+}
+
+/// An operation of the STN that exceeded the range in which its arithmetic is safe.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OverflowError {
+    /// The horizon is negative or larger than [MAX_HORIZON].
+    InvalidHorizon(W),
+    /// The weight of an inserted edge is outside the safe range of the horizon.
+    WeightOutOfRange { weight: W, horizon: W },
+    /// The sum of a bound and of the weight of an edge overflowed during propagation.
+    Propagation { bound: IntCst, weight: IntCst },
+}
+
+impl std::fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverflowError::InvalidHorizon(horizon) => {
+                write!(f, "invalid STN horizon {horizon}, not in [0, {MAX_HORIZON}]")
+            }
+            OverflowError::WeightOutOfRange { weight, horizon } => write!(
+                f,
+                "STN edge weight {weight} is out of the safe range [{}, {}] of the horizon {horizon}",
+                -2 * horizon - 1,
+                2 * horizon
+            ),
+            OverflowError::Propagation { bound, weight } => {
+                write!(
+                    f,
+                    "overflow in the STN when adding the weight {weight} to the bound {bound}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// Applies an [OverflowPolicy] to the arithmetic of the STN, given that all timepoints take their values
+/// in `[-horizon, horizon]`.
+#[derive(Clone, Debug)]
+pub struct OverflowGuard {
+    horizon: W,
+    /// First overflow that occurred, only recorded with [OverflowPolicy::Error].
+    error: Option<OverflowError>,
+}
+
+impl OverflowGuard {
+    /// Guard for the given horizon, which should not exceed [MAX_HORIZON] for the arithmetic to be safe.
+    pub fn new(horizon: W) -> Self {
+        debug_assert!((0..=INT_CST_MAX).contains(&horizon));
+        OverflowGuard { horizon, error: None }
+    }
+
+    pub fn horizon(&self) -> W {
+        self.horizon
+    }
+
+    pub fn error(&self) -> Option<OverflowError> {
+        self.error
+    }
+
+    fn report(&mut self, policy: OverflowPolicy, error: OverflowError) {
+        match policy {
+            OverflowPolicy::Panic => panic!("{error}"),
+            OverflowPolicy::Saturate => {}
+            OverflowPolicy::Error => {
+                self.error.get_or_insert(error);
+            }
+        }
+    }
+
+    /// Returns true if all values of the domain `[lb, ub]` of a timepoint are within the horizon.
+    pub fn within_horizon(&self, (lb, ub): (IntCst, IntCst)) -> bool {
+        -self.horizon <= lb && ub <= self.horizon
+    }
+
+    /// Checks that the weight of an edge `target - source <= weight` is in the safe range `[-2*horizon - 1, 2*horizon]`,
+    /// where `endpoints_within_horizon` indicates whether the domains of both timepoints are within the horizon.
+    ///
+    /// Out of this range, the edge is either always satisfied or never satisfied by timepoints within the horizon.
+    /// It is thus equivalent to the edge with the weight clamped to the range, which is returned unless the policy panics.
+    /// If one of the timepoints may be out of the horizon, the weight is returned unchanged as clamping it could
+    /// remove solutions.
+    pub fn weight(&mut self, policy: OverflowPolicy, weight: W, endpoints_within_horizon: bool) -> W {
+        let (min, max) = (-2 * self.horizon - 1, 2 * self.horizon);
+        if !endpoints_within_horizon {
+            return weight;
+        }
+        if weight < min || weight > max {
+            self.report(
+                policy,
+                OverflowError::WeightOutOfRange {
+                    weight,
+                    horizon: self.horizon,
+                },
+            );
+        }
+        weight.clamp(min, max)
+    }
+
+    /// Returns the sum of a bound and of the weight of an edge, i.e., the bound propagated to the target of the edge.
+    ///
+    /// On overflow, the sum is saturated (see [Self::saturating_add]) unless the policy panics.
+    pub fn add(&mut self, policy: OverflowPolicy, bound: UpperBound, weight: BoundValueAdd) -> UpperBound {
+        if bound.as_int().checked_add(weight.raw_value()).is_none() {
+            self.report(
+                policy,
+                OverflowError::Propagation {
+                    bound: bound.as_int(),
+                    weight: weight.raw_value(),
+                },
+            );
+        }
+        self.saturating_add(bound, weight)
+    }
+
+    /// Returns the sum of a bound and of the weight of an edge, saturated on overflow:
+    ///  - a positive overflow is weaker than any bound, and is saturated to the largest representable bound.
+    ///  - a negative overflow makes the domain of any timepoint within the horizon empty, and is saturated
+    ///    just beyond the horizon. The result is weaker than the actual sum, and thus remains a valid inference.
+    pub fn saturating_add(&self, bound: UpperBound, weight: BoundValueAdd) -> UpperBound {
+        match bound.as_int().checked_add(weight.raw_value()) {
+            Some(sum) => UpperBound::ub(sum),
+            None if weight.raw_value() > 0 => UpperBound::ub(INT_CST_MAX),
+            None => UpperBound::ub(-self.horizon - 1),
+        }
+    }
+}
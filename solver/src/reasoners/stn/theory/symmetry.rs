@@ -0,0 +1,324 @@
+//! Detection of interchangeable timepoints in the STN.
+//!
+//! Two timepoints are interchangeable if there is an automorphism of the network (a permutation of its variables
+//! that maps each edge to another edge with the same weight and enablers, and preserves the domains)
+//! that exchanges them. Unlike the detection of interchangeable variables of the model, the permutation
+//! may move other timepoints and literals together with the two timepoints (e.g. the ends of two identical tasks
+//! are exchanged together with their starts).
+//!
+//! Candidates are grouped with a color refinement on the network. For each candidate pair,
+//! an involution is built by matching the edges of the two timepoints and is then checked against the full network.
+
+use crate::core::state::Domains;
+use crate::core::{BoundValueAdd, Lit, SignedVar, VarRef};
+use crate::reasoners::stn::theory::{PropagatorId, StnTheory, Timepoint};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Number of rounds of color refinement used to group candidate timepoints.
+const REFINEMENT_ROUNDS: usize = 3;
+
+/// A propagator of the network with one of its enablers: `(source, target, weight, active, valid)`.
+type DirectedEdge = (SignedVar, SignedVar, BoundValueAdd, Lit, Lit);
+
+fn edge_vars(edge: &DirectedEdge) -> [VarRef; 4] {
+    [
+        edge.0.variable(),
+        edge.1.variable(),
+        edge.3.variable(),
+        edge.4.variable(),
+    ]
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn map_svar(svar: SignedVar, map: &HashMap<VarRef, VarRef>) -> SignedVar {
+    let var = map.get(&svar.variable()).copied().unwrap_or(svar.variable());
+    if svar.is_plus() {
+        SignedVar::plus(var)
+    } else {
+        SignedVar::minus(var)
+    }
+}
+
+fn map_lit(lit: Lit, map: &HashMap<VarRef, VarRef>) -> Lit {
+    map_svar(lit.svar(), map).with_upper_bound(lit.bound_value())
+}
+
+fn map_edge(edge: &DirectedEdge, map: &HashMap<VarRef, VarRef>) -> DirectedEdge {
+    (
+        map_svar(edge.0, map),
+        map_svar(edge.1, map),
+        edge.2,
+        map_lit(edge.3, map),
+        map_lit(edge.4, map),
+    )
+}
+
+struct Detector<'a> {
+    domains: &'a Domains,
+    edges: Vec<DirectedEdge>,
+    edge_set: HashSet<DirectedEdge>,
+    /// Index of the edges in which each variable appears.
+    edges_of: HashMap<VarRef, Vec<usize>>,
+    colors: HashMap<VarRef, u64>,
+}
+
+impl<'a> Detector<'a> {
+    fn new(stn: &StnTheory, domains: &'a Domains) -> Self {
+        let mut edges = Vec::new();
+        for id in 0..stn.constraints.num_propagator_groups() {
+            let group = &stn.constraints[PropagatorId::from(id)];
+            for enabler in &group.enablers {
+                edges.push((group.source, group.target, group.weight, enabler.active, enabler.valid));
+            }
+        }
+        edges.sort();
+        edges.dedup();
+        let mut edges_of: HashMap<VarRef, Vec<usize>> = HashMap::new();
+        for (i, edge) in edges.iter().enumerate() {
+            let mut vars = edge_vars(edge);
+            vars.sort();
+            for (j, &var) in vars.iter().enumerate() {
+                if j == 0 || vars[j - 1] != var {
+                    edges_of.entry(var).or_default().push(i);
+                }
+            }
+        }
+        let mut detector = Detector {
+            domains,
+            edge_set: edges.iter().copied().collect(),
+            edges,
+            colors: Default::default(),
+            edges_of,
+        };
+        detector.refine_colors();
+        detector
+    }
+
+    /// Colors the variables of the network so that two variables with different colors cannot be interchangeable.
+    fn refine_colors(&mut self) {
+        let presence_var = |var: VarRef| self.domains.presence(var).variable();
+        self.colors = self
+            .edges_of
+            .keys()
+            .map(|&v| (v, hash(&(self.domains.bounds(v), presence_var(v) == VarRef::ZERO))))
+            .collect();
+        for _ in 0..REFINEMENT_ROUNDS {
+            let colors = &self.colors;
+            let color = |v: VarRef| if v == VarRef::ZERO { 0 } else { colors[&v] };
+            let refined = self
+                .edges_of
+                .iter()
+                .map(|(&v, edges)| {
+                    let mut signature: Vec<u64> = edges
+                        .iter()
+                        .map(|&i| {
+                            let (src, tgt, weight, active, valid) = self.edges[i];
+                            // position of the variable in the edge and colors of the others
+                            let positions = edge_vars(&self.edges[i]).map(|x| x == v);
+                            let [c_src, c_tgt, c_active, c_valid] = edge_vars(&self.edges[i]).map(color);
+                            hash(&(
+                                positions,
+                                (c_src, src.is_plus()),
+                                (c_tgt, tgt.is_plus()),
+                                weight,
+                                (c_active, active.svar().is_plus(), active.bound_value()),
+                                (c_valid, valid.svar().is_plus(), valid.bound_value()),
+                            ))
+                        })
+                        .collect();
+                    signature.sort_unstable();
+                    (v, hash(&(color(v), signature)))
+                })
+                .collect();
+            self.colors = refined;
+        }
+    }
+
+    /// Attempts to build an involution of the network variables that exchanges `a` and `b`.
+    /// Returns the non-trivial pairs of the involution (in both directions) if it is an automorphism of the network.
+    fn involution(&self, a: Timepoint, b: Timepoint) -> Option<HashMap<VarRef, VarRef>> {
+        let mut map = HashMap::new();
+        let mut queue = Vec::new();
+        self.assign(a, b, &mut map, &mut queue)?;
+        while let Some(x) = queue.pop() {
+            let y = map[&x];
+            for &i in self.edges_of.get(&x).into_iter().flatten() {
+                let edge = &self.edges[i];
+                if edge_vars(edge)
+                    .iter()
+                    .all(|v| map.contains_key(v) || *v == VarRef::ZERO)
+                {
+                    continue; // fully determined, will be checked below
+                }
+                // edges of `y` that could be the image of `edge`
+                let candidates: Vec<&DirectedEdge> = self
+                    .edges_of
+                    .get(&y)
+                    .into_iter()
+                    .flatten()
+                    .map(|&j| &self.edges[j])
+                    .filter(|&other| self.is_compatible(edge, other, &map))
+                    .collect();
+                let image = if candidates.contains(&&map_edge(edge, &map)) {
+                    // leave the undetermined variables of the edge in place
+                    map_edge(edge, &map)
+                } else if candidates.len() == 1 {
+                    *candidates[0]
+                } else {
+                    return None;
+                };
+                for (v, w) in edge_vars(edge).into_iter().zip(edge_vars(&image)) {
+                    self.assign(v, w, &mut map, &mut queue)?;
+                }
+            }
+        }
+
+        // check that the involution is an automorphism of the network
+        map.retain(|x, y| x != y);
+        for (&x, &y) in &map {
+            if self.domains.bounds(x) != self.domains.bounds(y)
+                || map_lit(self.domains.presence(x), &map) != self.domains.presence(y)
+            {
+                return None;
+            }
+            for &i in self.edges_of.get(&x).into_iter().flatten() {
+                if !self.edge_set.contains(&map_edge(&self.edges[i], &map)) {
+                    return None;
+                }
+            }
+        }
+        Some(map)
+    }
+
+    /// Returns true if `other` has the same shape as `edge` and is compatible with the partial mapping.
+    fn is_compatible(&self, edge: &DirectedEdge, other: &DirectedEdge, map: &HashMap<VarRef, VarRef>) -> bool {
+        let same_signs = edge.0.is_plus() == other.0.is_plus()
+            && edge.1.is_plus() == other.1.is_plus()
+            && edge.2 == other.2
+            && edge.3.svar().is_plus() == other.3.svar().is_plus()
+            && edge.3.bound_value() == other.3.bound_value()
+            && edge.4.svar().is_plus() == other.4.svar().is_plus()
+            && edge.4.bound_value() == other.4.bound_value();
+        same_signs
+            && edge_vars(edge)
+                .into_iter()
+                .zip(edge_vars(other))
+                .all(|(v, w)| match map.get(&v) {
+                    Some(&image) => image == w,
+                    None if v == VarRef::ZERO => w == VarRef::ZERO,
+                    None => !map.contains_key(&w) && self.colors.get(&v) == self.colors.get(&w),
+                })
+    }
+
+    /// Records that `x` and `y` are exchanged by the involution, together with their presence variables.
+    fn assign(&self, x: VarRef, y: VarRef, map: &mut HashMap<VarRef, VarRef>, queue: &mut Vec<VarRef>) -> Option<()> {
+        if let Some(&image) = map.get(&x) {
+            return (image == y).then_some(());
+        }
+        if x == y {
+            map.insert(x, x);
+            queue.push(x);
+            return Some(());
+        }
+        if x == VarRef::ZERO || y == VarRef::ZERO || map.contains_key(&y) {
+            return None;
+        }
+        map.insert(x, y);
+        map.insert(y, x);
+        queue.push(x);
+        queue.push(y);
+        let (px, py) = (self.domains.presence(x), self.domains.presence(y));
+        if px.svar().is_plus() != py.svar().is_plus() || px.bound_value() != py.bound_value() {
+            return None;
+        }
+        self.assign(px.variable(), py.variable(), map, queue)
+    }
+}
+
+impl StnTheory {
+    /// Detects groups of interchangeable timepoints in the network, based on the edges recorded so far and on the
+    /// given domains (typically the ones of the root).
+    ///
+    /// In each group, the timepoints are sorted and any two consecutive ones are exchanged by an automorphism
+    /// of the network that leaves the other timepoints of the group in place. Hence, any permutation of a group
+    /// maps solutions to solutions and it is sufficient to consider those where the timepoints of the group are
+    /// ordered. Timepoints moved by the automorphisms of a group (e.g. the ends of tasks whose starts are
+    /// interchangeable) are not part of any other group, so that the orderings of all groups can be posted together.
+    ///
+    /// Only the constraints of the network are considered: it is up to the caller to make sure that the timepoints
+    /// and the literals of the edges do not appear in other constraints that would break these symmetries.
+    pub fn interchangeable_timepoints(&self, domains: &Domains) -> Vec<Vec<Timepoint>> {
+        let detector = Detector::new(self, domains);
+        let mut groups: HashMap<u64, Vec<Timepoint>> = HashMap::new();
+        for edge in &detector.edges {
+            for tp in [edge.0.variable(), edge.1.variable()] {
+                if tp != VarRef::ZERO {
+                    groups.entry(detector.colors[&tp]).or_default().push(tp);
+                }
+            }
+        }
+        let mut groups: Vec<Vec<Timepoint>> = groups
+            .into_values()
+            .map(|mut g| {
+                g.sort();
+                g.dedup();
+                g
+            })
+            .filter(|g| g.len() > 1)
+            .collect();
+        groups.sort();
+
+        // variables moved by the automorphisms of the groups found so far
+        let mut used: HashSet<VarRef> = HashSet::new();
+        let mut result = Vec::new();
+        for group in groups {
+            let mut chain: Vec<Timepoint> = Vec::new();
+            let mut automorphisms: Vec<HashMap<VarRef, VarRef>> = Vec::new();
+            for tp in group {
+                if used.contains(&tp) {
+                    continue;
+                }
+                let extension = chain.last().and_then(|&last| {
+                    let map = detector.involution(last, tp)?;
+                    let valid = map.keys().all(|v| !used.contains(v))
+                        && chain[..chain.len() - 1].iter().all(|t| !map.contains_key(t))
+                        && automorphisms.iter().all(|m| !m.contains_key(&tp));
+                    valid.then_some(map)
+                });
+                if let Some(map) = extension {
+                    automorphisms.push(map);
+                    chain.push(tp);
+                    continue;
+                }
+                close_chain(&mut chain, &mut automorphisms, &mut used, &mut result);
+                if !used.contains(&tp) {
+                    chain.push(tp);
+                }
+            }
+            close_chain(&mut chain, &mut automorphisms, &mut used, &mut result);
+        }
+        result
+    }
+}
+
+/// Records the chain as a group if it has at least two timepoints, and resets it.
+fn close_chain(
+    chain: &mut Vec<Timepoint>,
+    automorphisms: &mut Vec<HashMap<VarRef, VarRef>>,
+    used: &mut HashSet<VarRef>,
+    result: &mut Vec<Vec<Timepoint>>,
+) {
+    if chain.len() > 1 {
+        used.extend(automorphisms.iter().flat_map(|m| m.keys().copied()));
+        result.push(std::mem::take(chain));
+    }
+    chain.clear();
+    automorphisms.clear();
+}
@@ -1,8 +1,11 @@
 use crate::core::literals::Disjunction;
 use crate::core::state::{Domains, OptDomain};
 use crate::core::{IntCst, Lit, VarRef};
+use crate::model::lang::arith::{EqAbs, EqDiv, EqMod, EqMul};
+use crate::model::lang::channel::Channel;
 use crate::model::lang::linear::NFLinearLeq;
-use crate::model::lang::ValidityScope;
+use crate::model::lang::max::{NFEqMax, NFEqMaxItem};
+use crate::model::lang::{IAtom, ValidityScope};
 use crate::model::{Label, Model};
 use std::fmt::{Debug, Formatter};
 use std::ops::Not;
@@ -24,6 +27,12 @@ pub enum ReifExpr {
     Or(Vec<Lit>),
     And(Vec<Lit>),
     Linear(NFLinearLeq),
+    EqMax(NFEqMax),
+    EqAbs(EqAbs),
+    EqDiv(EqDiv),
+    EqMod(EqMod),
+    EqMul(EqMul),
+    Channel(Channel),
 }
 
 impl ReifExpr {
@@ -43,15 +52,58 @@ impl ReifExpr {
                     .filter(|l| presence(l.variable()) == Lit::TRUE),
             ),
             ReifExpr::Linear(lin) => lin.validity_scope(presence),
+            ReifExpr::EqMax(em) => em.validity_scope(presence),
+            ReifExpr::EqAbs(abs) => abs.validity_scope(presence),
+            ReifExpr::EqDiv(div) => div.validity_scope(presence),
+            ReifExpr::EqMod(rem) => rem.validity_scope(presence),
+            ReifExpr::EqMul(mul) => mul.validity_scope(presence),
+            ReifExpr::Channel(ch) => ch.validity_scope(presence),
         }
     }
 
+    /// Returns all variables appearing in the expression (possibly with duplicates).
+    pub fn variables(&self) -> Vec<VarRef> {
+        match self {
+            ReifExpr::Lit(l) => vec![l.variable()],
+            ReifExpr::MaxDiff(diff) => vec![diff.b, diff.a],
+            ReifExpr::Or(literals) | ReifExpr::And(literals) => literals.iter().map(|l| l.variable()).collect(),
+            ReifExpr::Linear(lin) => lin.sum.iter().map(|item| item.var).collect(),
+            ReifExpr::EqMax(em) => std::iter::once(&em.lhs)
+                .chain(&em.rhs)
+                .map(|item| item.var.variable())
+                .collect(),
+            ReifExpr::EqAbs(abs) => vec![abs.lhs.var.into(), abs.arg.var.into()],
+            ReifExpr::EqDiv(div) => vec![div.lhs.var.into(), div.numerator.var.into()],
+            ReifExpr::EqMod(rem) => vec![rem.lhs.var.into(), rem.numerator.var.into()],
+            ReifExpr::EqMul(mul) => vec![mul.lhs.var.into(), mul.x.var.into(), mul.y.var.into()],
+            ReifExpr::Channel(ch) => std::iter::once(ch.var.var.into())
+                .chain(ch.lits.iter().map(|l| l.variable()))
+                .collect(),
+        }
+    }
+
+    /// Returns true if the negation of this expression can be represented as a `ReifExpr`.
+    /// Attempting to negate an expression for which this is not the case will result in a panic.
+    pub fn negatable(&self) -> bool {
+        !matches!(
+            self,
+            ReifExpr::EqMax(_)
+                | ReifExpr::EqAbs(_)
+                | ReifExpr::EqDiv(_)
+                | ReifExpr::EqMod(_)
+                | ReifExpr::EqMul(_)
+                | ReifExpr::Channel(_)
+        )
+    }
+
     pub fn eval(&self, assignment: &Domains) -> Option<bool> {
         let prez = |var| assignment.present(var).unwrap();
         let value = |var| match assignment.domain(var) {
             OptDomain::Present(lb, ub) if lb == ub => lb,
             _ => panic!(),
         };
+        let atom_value = |atom: IAtom| value(atom.var.into()) + atom.shift;
+        let both_present = |a: IAtom, b: IAtom| prez(a.var.into()) && prez(b.var.into());
         match &self {
             ReifExpr::Lit(l) => {
                 if prez(l.variable()) {
@@ -91,6 +143,42 @@ impl ReifExpr {
                 }
                 Some(sum <= lin.upper_bound)
             }
+            ReifExpr::EqMax(em) => {
+                let value_of = |item: NFEqMaxItem| {
+                    let v = value(item.var.variable());
+                    let v = if item.var.is_plus() { v } else { -v };
+                    v + item.cst
+                };
+                if !prez(em.lhs.var.variable()) {
+                    return None;
+                }
+                let lhs = value_of(em.lhs);
+                let max = em
+                    .rhs
+                    .iter()
+                    .filter(|item| prez(item.var.variable()))
+                    .map(|&item| value_of(item))
+                    .max();
+                Some(max == Some(lhs))
+            }
+            ReifExpr::EqAbs(abs) => {
+                both_present(abs.lhs, abs.arg).then(|| atom_value(abs.lhs) == atom_value(abs.arg).abs())
+            }
+            ReifExpr::EqDiv(div) => both_present(div.lhs, div.numerator)
+                .then(|| atom_value(div.lhs) == atom_value(div.numerator) / div.denominator),
+            ReifExpr::EqMod(rem) => both_present(rem.lhs, rem.numerator)
+                .then(|| atom_value(rem.lhs) == atom_value(rem.numerator) % rem.denominator),
+            ReifExpr::EqMul(mul) => (both_present(mul.lhs, mul.x) && prez(mul.y.var.into()))
+                .then(|| atom_value(mul.lhs) as i64 == atom_value(mul.x) as i64 * atom_value(mul.y) as i64),
+            ReifExpr::Channel(ch) => {
+                if !prez(ch.var.var.into()) || ch.lits.iter().any(|l| !prez(l.variable())) {
+                    return None;
+                }
+                let value = atom_value(ch.var);
+                let in_range = (ch.first..ch.value(ch.lits.len())).contains(&value);
+                let channeled = (0..ch.lits.len()).all(|i| assignment.entails(ch.lits[i]) == (value == ch.value(i)));
+                Some(in_range && channeled)
+            }
         }
     }
 }
@@ -137,6 +225,11 @@ impl Not for ReifExpr {
                 ReifExpr::Or(lits)
             }
             ReifExpr::Linear(lin) => ReifExpr::Linear(!lin),
+            ReifExpr::EqMax(_) => panic!("Negation of a max constraint is not supported"),
+            ReifExpr::EqAbs(_) | ReifExpr::EqDiv(_) | ReifExpr::EqMod(_) | ReifExpr::EqMul(_) => {
+                panic!("Negation of an arithmetic constraint is not supported")
+            }
+            ReifExpr::Channel(_) => panic!("Negation of a channeling constraint is not supported"),
         }
     }
 }
@@ -0,0 +1,318 @@
+//! Utilities to benchmark the solver on a set of instances and to compare two of its configurations.
+//!
+//! Each instance is solved in-process with a time limit, and the statistics of the run are recorded
+//! in an [InstanceStats] that can be exported as CSV.
+//! Two runs on the same instances can be compared with [Comparison], based on their PAR-2 scores
+//! (average runtime where unsolved instances are counted as twice the time limit).
+
+use crate::core::IntCst;
+use crate::model::lang::IAtom;
+use crate::model::{Label, Model};
+use crate::solver::parallel::signals::InputSignal;
+use crate::solver::Solver;
+use crossbeam_channel::RecvTimeoutError;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Final status of the solver on an instance.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// A solution was found (for satisfaction problems).
+    Sat,
+    /// A solution was found and proved optimal.
+    Optimal,
+    /// The instance was proved to have no solution.
+    Unsat,
+    /// The time limit was reached before completing the search, a solution might have been found.
+    Timeout,
+    /// The instance could not be loaded.
+    Error,
+}
+
+impl Status {
+    /// True if the solver completed its search on the instance.
+    pub fn is_solved(self) -> bool {
+        matches!(self, Status::Sat | Status::Optimal | Status::Unsat)
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Status::Sat => "sat",
+            Status::Optimal => "optimal",
+            Status::Unsat => "unsat",
+            Status::Timeout => "timeout",
+            Status::Error => "error",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Statistics of the solver on a single instance.
+#[derive(Clone, Debug)]
+pub struct InstanceStats {
+    pub instance: String,
+    pub status: Status,
+    /// Value of the objective in the best solution found, if any.
+    pub objective: Option<IntCst>,
+    /// Wall clock time spent solving the instance, excluding its loading.
+    pub runtime: Duration,
+    pub num_decisions: u64,
+    pub num_conflicts: u64,
+    pub num_restarts: u64,
+    pub num_solutions: u64,
+}
+
+impl InstanceStats {
+    const CSV_HEADER: &'static str = "instance,status,objective,runtime,decisions,conflicts,restarts,solutions";
+
+    fn error(instance: String) -> Self {
+        InstanceStats {
+            instance,
+            status: Status::Error,
+            objective: None,
+            runtime: Duration::ZERO,
+            num_decisions: 0,
+            num_conflicts: 0,
+            num_restarts: 0,
+            num_solutions: 0,
+        }
+    }
+
+    /// Runtime counted in the PAR-2 score: twice the time limit if the instance was not solved.
+    pub fn par2_runtime(&self, time_limit: Duration) -> Duration {
+        if self.status.is_solved() {
+            self.runtime
+        } else {
+            time_limit * 2
+        }
+    }
+}
+
+/// Solves the problem with the given time limit, optionally minimizing an objective.
+/// The solver can be configured (e.g. to set its brancher) before the search starts.
+pub fn run_instance<Lbl: Label>(
+    instance: impl Into<String>,
+    model: Model<Lbl>,
+    objective: Option<IAtom>,
+    configure: impl Fn(&mut Solver<Lbl>),
+    time_limit: Duration,
+) -> InstanceStats {
+    let mut solver = Solver::new(model);
+    configure(&mut solver);
+
+    // interrupt the solver once the time limit is reached, unless it completes earlier
+    let input = solver.input_stream();
+    let (done, finished) = crossbeam_channel::bounded::<()>(1);
+    let timer = std::thread::spawn(move || {
+        if finished.recv_timeout(time_limit) == Err(RecvTimeoutError::Timeout) {
+            let _ = input.sender.send(InputSignal::Interrupt);
+        }
+    });
+
+    let start = Instant::now();
+    let (status, objective) = match objective {
+        Some(objective) => match solver.minimize(objective) {
+            Ok(Some((cost, _))) => (Status::Optimal, Some(cost)),
+            Ok(None) => (Status::Unsat, None),
+            Err(_) => (Status::Timeout, solver.stats.best_cost()),
+        },
+        None => match solver.solve() {
+            Ok(Some(_)) => (Status::Sat, None),
+            Ok(None) => (Status::Unsat, None),
+            Err(_) => (Status::Timeout, None),
+        },
+    };
+    let runtime = start.elapsed();
+    let _ = done.send(());
+    let _ = timer.join();
+
+    let stats = &solver.stats;
+    InstanceStats {
+        instance: instance.into(),
+        status,
+        objective,
+        runtime,
+        num_decisions: stats.num_decisions(),
+        num_conflicts: stats.num_conflicts(),
+        num_restarts: stats.num_restarts(),
+        num_solutions: stats.num_solutions(),
+    }
+}
+
+/// Runs the solver on all files of the directory, in alphabetical order.
+///
+/// Each file is loaded into a model and an optional objective to minimize by `load`.
+/// Files that cannot be loaded are reported with the [Status::Error] status.
+pub fn run_directory<Lbl: Label>(
+    dir: &Path,
+    load: impl Fn(&Path) -> anyhow::Result<(Model<Lbl>, Option<IAtom>)>,
+    configure: impl Fn(&mut Solver<Lbl>),
+    time_limit: Duration,
+) -> std::io::Result<Vec<InstanceStats>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    let mut results = Vec::with_capacity(files.len());
+    for file in files {
+        let instance = file.file_name().unwrap().to_string_lossy().to_string();
+        let stats = match load(&file) {
+            Ok((model, objective)) => run_instance(instance, model, objective, &configure, time_limit),
+            Err(_) => InstanceStats::error(instance),
+        };
+        results.push(stats);
+    }
+    Ok(results)
+}
+
+/// Writes the statistics in CSV format, with a header line.
+pub fn write_csv(results: &[InstanceStats], out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "{}", InstanceStats::CSV_HEADER)?;
+    for r in results {
+        let objective = r.objective.map_or(String::new(), |o| o.to_string());
+        writeln!(
+            out,
+            "{},{},{},{:.6},{},{},{},{}",
+            r.instance,
+            r.status,
+            objective,
+            r.runtime.as_secs_f64(),
+            r.num_decisions,
+            r.num_conflicts,
+            r.num_restarts,
+            r.num_solutions
+        )?;
+    }
+    Ok(())
+}
+
+/// PAR-2 score of a run: average runtime in seconds where unsolved instances count as twice the time limit.
+pub fn par2(results: &[InstanceStats], time_limit: Duration) -> f64 {
+    if results.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = results.iter().map(|r| r.par2_runtime(time_limit).as_secs_f64()).sum();
+    total / results.len() as f64
+}
+
+/// Comparison of two configurations (A and B) of the solver on the same instances.
+pub struct Comparison<'a> {
+    pub a: &'a [InstanceStats],
+    pub b: &'a [InstanceStats],
+    pub time_limit: Duration,
+}
+
+impl<'a> Comparison<'a> {
+    pub fn new(a: &'a [InstanceStats], b: &'a [InstanceStats], time_limit: Duration) -> Self {
+        Comparison { a, b, time_limit }
+    }
+
+    /// Pairs of statistics on the instances that appear in both runs.
+    pub fn common_instances(&self) -> impl Iterator<Item = (&'a InstanceStats, &'a InstanceStats)> + '_ {
+        self.a
+            .iter()
+            .filter_map(|a| self.b.iter().find(|b| b.instance == a.instance).map(|b| (a, b)))
+    }
+
+    /// PAR-2 scores of A and B, on their common instances.
+    pub fn par2(&self) -> (f64, f64) {
+        let (a, b): (Vec<InstanceStats>, Vec<InstanceStats>) =
+            self.common_instances().map(|(a, b)| (a.clone(), b.clone())).unzip();
+        (par2(&a, self.time_limit), par2(&b, self.time_limit))
+    }
+
+    /// Instances for which A and B do not agree on the satisfiability or the optimal objective value.
+    /// A non-empty result indicates a bug in one of the configurations.
+    pub fn inconsistencies(&self) -> Vec<&'a str> {
+        self.common_instances()
+            .filter(|(a, b)| match (a.status, b.status) {
+                (Status::Unsat, Status::Sat | Status::Optimal) | (Status::Sat | Status::Optimal, Status::Unsat) => true,
+                (Status::Optimal, Status::Optimal) => a.objective != b.objective,
+                _ => false,
+            })
+            .map(|(a, _)| a.instance.as_str())
+            .collect()
+    }
+}
+
+impl Display for Comparison<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<30} {:>10} {:>10} {:>10} {:>10}",
+            "instance", "A", "time A", "B", "time B"
+        )?;
+        for (a, b) in self.common_instances() {
+            writeln!(
+                f,
+                "{:<30} {:>10} {:>10.3} {:>10} {:>10.3}",
+                a.instance,
+                a.status,
+                a.runtime.as_secs_f64(),
+                b.status,
+                b.runtime.as_secs_f64()
+            )?;
+        }
+        let solved = |run: &[InstanceStats]| run.iter().filter(|r| r.status.is_solved()).count();
+        let (par2_a, par2_b) = self.par2();
+        writeln!(f, "solved: {} (A)  {} (B)", solved(self.a), solved(self.b))?;
+        writeln!(f, "PAR-2 : {par2_a:.3} (A)  {par2_b:.3} (B)")?;
+        let inconsistencies = self.inconsistencies();
+        if !inconsistencies.is_empty() {
+            writeln!(f, "INCONSISTENT RESULTS: {inconsistencies:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::expr::leq;
+
+    type Model = crate::model::Model<&'static str>;
+
+    #[test]
+    fn test_bench_comparison() {
+        let time_limit = Duration::from_secs(10);
+        let run = |configure: &dyn Fn(&mut Solver<&'static str>)| {
+            let mut m = Model::new();
+            let x = m.new_ivar(0, 10, "x");
+            let y = m.new_ivar(0, 10, "y");
+            m.enforce(leq(x + 3, y), []);
+            let optimal = run_instance("optim", m, Some(y.into()), configure, time_limit);
+
+            let mut m = Model::new();
+            let x = m.new_ivar(0, 10, "x");
+            m.enforce(leq(x + 11, x), []);
+            let unsat = run_instance("unsat", m, None, configure, time_limit);
+            vec![optimal, unsat]
+        };
+        let a = run(&|_| {});
+        let b = run(&|s| s.set_brancher(crate::solver::search::lexical::LexicalMinValue::new()));
+        assert_eq!(a[0].status, Status::Optimal);
+        assert_eq!(a[0].objective, Some(3));
+        assert_eq!(a[1].status, Status::Unsat);
+
+        let comparison = Comparison::new(&a, &b, time_limit);
+        assert!(comparison.inconsistencies().is_empty());
+        assert_eq!(comparison.common_instances().count(), 2);
+        let (par2_a, _) = comparison.par2();
+        assert!(par2_a < time_limit.as_secs_f64());
+
+        let mut csv = Vec::new();
+        write_csv(&a, &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with(InstanceStats::CSV_HEADER));
+        assert!(csv.contains("optim,optimal,3,"));
+    }
+}
@@ -0,0 +1,281 @@
+//! Exchange of lemmas with external tools (e.g. a landmark generator), over literals named after the labels
+//! of their variables rather than their internal [VarRef].
+//!
+//! A named literal is written `name` or `!name` for a boolean variable, and `name <= v`, `name < v`, `name >= v`
+//! or `name > v` for any other variable, where `v` is an integer value (the numerator of a fixed-point variable,
+//! or the ID of a symbol). A clause is a disjunction of literals separated by `|`, one per line, e.g.:
+//!
+//! ```text
+//! # at least one of the two actions is present, unless `x` is small
+//! prez_a | prez_b | x <= 3
+//! ```
+//!
+//! Lemmas are assumed to be implied by the constraints of the model. They are added to the clause database
+//! of the solver and never removed from it.
+
+use crate::core::literals::Disjunction;
+use crate::core::{IntCst, Lit, Relation, VarRef};
+use crate::model::extensions::Shaped;
+use crate::model::lang::Type;
+use crate::model::Label;
+use crate::solver::parallel::signals::InputSignal;
+use crossbeam_channel::Sender;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// An error in a named literal or clause.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LemmaError {
+    /// No variable has the given name.
+    UnknownName(String),
+    /// Several variables have the given name.
+    AmbiguousName(String),
+    /// The literal is not of the form `name`, `!name` or `name <op> value`.
+    InvalidLiteral(String),
+}
+
+impl Display for LemmaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LemmaError::UnknownName(name) => write!(f, "no variable is named `{name}`"),
+            LemmaError::AmbiguousName(name) => write!(f, "several variables are named `{name}`"),
+            LemmaError::InvalidLiteral(lit) => write!(f, "invalid literal `{lit}`"),
+        }
+    }
+}
+
+impl std::error::Error for LemmaError {}
+
+/// Correspondence between literals and their textual representation, where variables are named after
+/// their label (see [the module documentation](self)).
+#[derive(Clone, Debug)]
+pub struct LiteralNames {
+    /// Variable of each name, or `None` if several variables have the same name.
+    variables: HashMap<String, Option<VarRef>>,
+    /// Name of each uniquely named variable, and whether it is boolean.
+    names: HashMap<VarRef, (String, bool)>,
+}
+
+impl LiteralNames {
+    /// Names the variables of the model after their label.
+    pub fn new<Lbl: Label>(model: &impl Shaped<Lbl>) -> Self {
+        let mut variables: HashMap<String, Option<VarRef>> = HashMap::new();
+        for (label, vars) in model.get_shape().labels.iter() {
+            let var = if let [var] = vars { Some(*var) } else { None };
+            variables
+                .entry(label.to_string())
+                // two labels with the same representation
+                .and_modify(|v| *v = None)
+                .or_insert(var);
+        }
+        let names = variables
+            .iter()
+            .filter_map(|(name, &var)| {
+                let var = var?;
+                let is_bool = model.get_type(var) == Some(Type::Bool);
+                Some((var, (name.clone(), is_bool)))
+            })
+            .collect();
+        LiteralNames { variables, names }
+    }
+
+    fn variable(&self, name: &str) -> Result<VarRef, LemmaError> {
+        match self.variables.get(name) {
+            Some(Some(var)) => Ok(*var),
+            Some(None) => Err(LemmaError::AmbiguousName(name.to_string())),
+            None => Err(LemmaError::UnknownName(name.to_string())),
+        }
+    }
+
+    /// Parses a literal, e.g. `!prez_a` or `x >= 3`.
+    pub fn parse_lit(&self, lit: &str) -> Result<Lit, LemmaError> {
+        let lit = lit.trim();
+        let invalid = || LemmaError::InvalidLiteral(lit.to_string());
+        // operators, with the longest first so that `<=` is not read as `<`
+        for op in ["<=", ">=", "<", ">"] {
+            if let Some((name, value)) = lit.rsplit_once(op) {
+                let var = self.variable(name.trim())?;
+                let value: IntCst = value.trim().parse().map_err(|_| invalid())?;
+                return Ok(match op {
+                    "<=" => Lit::leq(var, value),
+                    ">=" => Lit::geq(var, value),
+                    "<" => Lit::lt(var, value),
+                    _ => Lit::gt(var, value),
+                });
+            }
+        }
+        match lit.strip_prefix('!') {
+            Some(name) => Ok(!Lit::geq(self.variable(name.trim())?, 1)),
+            None if !lit.is_empty() => Ok(Lit::geq(self.variable(lit)?, 1)),
+            None => Err(invalid()),
+        }
+    }
+
+    /// Parses a disjunction of literals separated by `|`.
+    pub fn parse_clause(&self, clause: &str) -> Result<Disjunction, LemmaError> {
+        let literals = clause
+            .split('|')
+            .map(|lit| self.parse_lit(lit))
+            .collect::<Result<_, _>>()?;
+        Ok(Disjunction::new(literals))
+    }
+
+    /// Parses one clause per line, ignoring empty lines and comments starting with `#`.
+    pub fn parse_clauses(&self, clauses: &str) -> Result<Vec<Disjunction>, LemmaError> {
+        clauses
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| self.parse_clause(line))
+            .collect()
+    }
+
+    /// Returns the textual representation of the literal, or `None` if its variable has no unique name.
+    pub fn format_lit(&self, lit: Lit) -> Option<String> {
+        let (name, is_bool) = self.names.get(&lit.variable())?;
+        let formatted = match lit.unpack() {
+            (_, Relation::Gt, 0) if *is_bool => name.clone(),
+            (_, Relation::Leq, 0) if *is_bool => format!("!{name}"),
+            (_, Relation::Leq, value) => format!("{name} <= {value}"),
+            (_, Relation::Gt, value) => format!("{name} > {value}"),
+        };
+        Some(formatted)
+    }
+
+    /// Returns the textual representation of the clause, or `None` if one of its literals cannot be formatted.
+    pub fn format_clause(&self, clause: &[Lit]) -> Option<String> {
+        let literals = clause
+            .iter()
+            .map(|&lit| self.format_lit(lit))
+            .collect::<Option<Vec<_>>>()?;
+        Some(literals.join(" | "))
+    }
+}
+
+/// A thread-safe handle to add lemmas to one or several solvers, before or while they are running.
+///
+/// The lemmas are received by each solver at the start of its next search, or during its current one.
+#[derive(Clone)]
+pub struct LemmaSender {
+    names: Arc<LiteralNames>,
+    solvers: Vec<Sender<InputSignal>>,
+}
+
+impl LemmaSender {
+    pub(crate) fn new(names: LiteralNames, solvers: Vec<Sender<InputSignal>>) -> Self {
+        LemmaSender {
+            names: Arc::new(names),
+            solvers,
+        }
+    }
+
+    /// Names of the literals of the solvers.
+    pub fn names(&self) -> &LiteralNames {
+        &self.names
+    }
+
+    /// Sends the given clauses to all solvers.
+    pub fn send(&self, lemmas: impl IntoIterator<Item = Disjunction>) {
+        for lemma in lemmas {
+            let lemma = Arc::new(lemma);
+            for solver in &self.solvers {
+                // ignore the solvers that no longer exist
+                let _ = solver.send(InputSignal::Lemma(lemma.clone()));
+            }
+        }
+    }
+
+    /// Parses the given clauses (see [LiteralNames::parse_clauses]) and sends them to all solvers.
+    /// Returns the number of clauses sent, or an error if any of them could not be parsed, in which case none is sent.
+    pub fn send_named(&self, lemmas: &str) -> Result<usize, LemmaError> {
+        let lemmas = self.names.parse_clauses(lemmas)?;
+        let num_lemmas = lemmas.len();
+        self.send(lemmas);
+        Ok(num_lemmas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::extensions::AssignmentExt;
+    use crate::model::lang::expr::{leq, or};
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    #[test]
+    fn test_literal_names() {
+        let mut m = Model::new();
+        let p = m.new_bvar("p").true_lit();
+        let x = m.new_ivar(0, 10, "x");
+        m.new_bvar("dup");
+        m.new_bvar("dup");
+        let names = LiteralNames::new(&m);
+
+        assert_eq!(names.parse_lit("p"), Ok(p));
+        assert_eq!(names.parse_lit(" !p "), Ok(!p));
+        assert_eq!(names.parse_lit("x <= 3"), Ok(Lit::leq(x, 3)));
+        assert_eq!(names.parse_lit("x<3"), Ok(Lit::leq(x, 2)));
+        assert_eq!(names.parse_lit("x >= 3"), Ok(Lit::geq(x, 3)));
+        assert_eq!(names.parse_lit("x > 3"), Ok(Lit::geq(x, 4)));
+        assert_eq!(names.parse_lit("y"), Err(LemmaError::UnknownName("y".to_string())));
+        assert_eq!(
+            names.parse_lit("!dup"),
+            Err(LemmaError::AmbiguousName("dup".to_string()))
+        );
+        assert_eq!(
+            names.parse_lit("x <= three"),
+            Err(LemmaError::InvalidLiteral("x <= three".to_string()))
+        );
+
+        let clauses = names.parse_clauses("# comment\n!p | x >= 3\n\np # fact\n").unwrap();
+        assert_eq!(
+            clauses,
+            vec![Disjunction::new(vec![!p, Lit::geq(x, 3)]), Disjunction::new(vec![p])]
+        );
+        for clause in clauses {
+            let formatted = names.format_clause(clause.literals()).unwrap();
+            assert_eq!(names.parse_clause(&formatted), Ok(clause));
+        }
+        let dup = m.shape.labels.variables_with_label(&"dup")[0];
+        assert_eq!(names.format_lit(Lit::geq(dup, 1)), None);
+    }
+
+    #[test]
+    fn test_lemma_exchange() {
+        let mut m = Model::new();
+        let p = m.new_bvar("p").true_lit();
+        let x = m.new_ivar(0, 10, "x");
+        let mut s = Solver::new(m);
+        let sender = s.lemma_sender();
+        assert_eq!(sender.send_named("p\n!p | x <= 3"), Ok(2));
+        assert!(sender.send_named("p | q").is_err());
+
+        let solution = s.solve().unwrap().unwrap();
+        assert_eq!(solution.value(p), Some(true));
+        assert!(solution.var_domain(x).ub <= 3);
+
+        // pigeonhole problem, whose proof of unsatisfiability requires learning clauses
+        let mut m = Model::new();
+        let names = ["h0", "h1", "h2", "h3", "h4"];
+        let holes = names.map(|name| m.new_ivar(0, 3, name));
+        for (i, &h1) in holes.iter().enumerate() {
+            for &h2 in &holes[(i + 1)..] {
+                let before = m.reify(leq(h1 + 1, h2));
+                let after = m.reify(leq(h2 + 1, h1));
+                m.enforce(or([before, after]), []);
+            }
+        }
+        let mut s = Solver::new(m);
+        assert!(s.solve().unwrap().is_none());
+        let names = LiteralNames::new(&s.model);
+        let lemmas = s.export_lemmas(usize::MAX);
+        assert!(!lemmas.is_empty());
+        for lemma in lemmas {
+            let clause = names.parse_clause(&lemma).unwrap();
+            assert_eq!(names.format_clause(clause.literals()), Some(lemma));
+        }
+    }
+}
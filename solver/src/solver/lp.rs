@@ -0,0 +1,366 @@
+//! Linear programming relaxation of a model, used to derive bounds on an objective during branch and bound.
+//!
+//! The relaxation only contains the linear and difference constraints that are enforced at the root and whose
+//! variables are all present. Each variable of these constraints is relaxed to a continuous variable
+//! in the bounds of its current domain.
+//!
+//! The relaxation is solved with a bounded-variable dual simplex on a dense tableau.
+//! Since every basis it goes through is dual feasible, the value of its current basic solution is always a valid
+//! bound on the objective, even when the iteration limit is reached before optimality.
+//! The tableau is kept from one call to the next, so that resolving the relaxation after a tightening of the
+//! domains (e.g. after a new solution was found) starts from the previous optimal basis.
+
+use crate::core::state::Domains;
+use crate::core::{IntCst, Lit, VarRef};
+use crate::model::lang::IAtom;
+use crate::model::{Constraint, Label, Model};
+use crate::reif::ReifExpr;
+use std::collections::HashMap;
+
+/// Maximal number of entries in the tableau. Larger relaxations are not built as the dense representation
+/// would make each iteration too expensive.
+const MAX_TABLEAU_SIZE: usize = 5_000_000;
+
+/// Tolerance under which a bound violation or a reduced cost is considered null.
+const EPSILON: f64 = 1e-9;
+
+/// Minimal absolute value of a pivot, to avoid numerically unstable iterations.
+const MIN_PIVOT: f64 = 1e-7;
+
+/// Tolerance used when rounding the bound of the relaxation to an integer.
+const ROUNDING_TOLERANCE: f64 = 1e-6;
+
+/// Result of the resolution of the relaxation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LpBound {
+    /// The relaxation has no solution in the current domains, and thus neither does the model.
+    Infeasible,
+    /// Bound on the objective: a lower bound when minimizing and an upper bound when maximizing.
+    Bound(IntCst),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ColStatus {
+    /// The column is basic in the given row.
+    Basic(usize),
+    AtLower,
+    AtUpper,
+}
+
+/// LP relaxation of a model for a given objective.
+///
+/// Its columns are the variables of the model that appear in the relaxed constraints, followed by one
+/// slack variable per constraint (row).
+pub struct LpRelaxation {
+    /// Variable of the model associated to each structural column.
+    vars: Vec<VarRef>,
+    /// Current tableau `B^-1 [A | I]`, one vector per row.
+    tableau: Vec<Vec<f64>>,
+    /// Current right hand side `B^-1 b`.
+    rhs: Vec<f64>,
+    /// Column that is basic in each row.
+    basis: Vec<usize>,
+    status: Vec<ColStatus>,
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    reduced_costs: Vec<f64>,
+    /// Column of the objective variable.
+    objective: IAtom,
+    objective_col: usize,
+    /// `1` when minimizing and `-1` when maximizing (the relaxation is always a minimization).
+    sign: f64,
+    /// Maximal number of pivots made in a single call to [LpRelaxation::bound].
+    pub max_pivots: usize,
+}
+
+impl LpRelaxation {
+    /// Builds the relaxation of the model, for minimizing or maximizing the objective.
+    ///
+    /// Returns `None` if the objective does not appear in any relaxed constraint (in which case the relaxation
+    /// would not provide anything better than the domain of the objective) or if the relaxation would be too large.
+    pub fn new<Lbl: Label>(model: &Model<Lbl>, objective: IAtom, minimize: bool) -> Option<Self> {
+        let domains = &model.state;
+        let present = |v: VarRef| domains.presence(v) == Lit::TRUE;
+
+        // rows of the relaxation, as a sum of `factor * var` that must be lower than or equal to the bound
+        let mut rows: Vec<(Vec<(VarRef, IntCst)>, IntCst)> = Vec::new();
+        for Constraint::Reified(expr, value) in &model.shape.constraints {
+            if !domains.entails(*value) {
+                continue;
+            }
+            match expr {
+                ReifExpr::Linear(lin) if lin.sum.iter().all(|item| present(item.var)) => {
+                    let sum = lin
+                        .sum
+                        .iter()
+                        .filter(|item| item.var != VarRef::ZERO)
+                        .map(|item| (item.var, item.factor))
+                        .collect();
+                    rows.push((sum, lin.upper_bound));
+                }
+                ReifExpr::MaxDiff(diff) if present(diff.a) && present(diff.b) => {
+                    rows.push((vec![(diff.b, 1), (diff.a, -1)], diff.ub));
+                }
+                _ => {}
+            }
+        }
+
+        let mut columns: HashMap<VarRef, usize> = HashMap::new();
+        let mut vars = Vec::new();
+        for (sum, _) in &rows {
+            for &(var, _) in sum {
+                columns.entry(var).or_insert_with(|| {
+                    vars.push(var);
+                    vars.len() - 1
+                });
+            }
+        }
+        let objective_col = *columns.get(&VarRef::from(objective.var))?;
+
+        let num_rows = rows.len();
+        let num_cols = vars.len() + num_rows;
+        if num_rows * num_cols > MAX_TABLEAU_SIZE {
+            return None;
+        }
+
+        let mut tableau = vec![vec![0.0; num_cols]; num_rows];
+        let mut rhs = vec![0.0; num_rows];
+        for (i, (sum, ub)) in rows.iter().enumerate() {
+            for &(var, factor) in sum {
+                tableau[i][columns[&var]] += factor as f64;
+            }
+            tableau[i][vars.len() + i] = 1.0;
+            rhs[i] = *ub as f64;
+        }
+
+        let sign = if minimize { 1.0 } else { -1.0 };
+        let mut reduced_costs = vec![0.0; num_cols];
+        reduced_costs[objective_col] = sign;
+        // start from the slack basis, which is dual feasible if each structural column is at the bound
+        // that minimizes its cost
+        let mut status = vec![ColStatus::AtLower; num_cols];
+        if sign < 0.0 {
+            status[objective_col] = ColStatus::AtUpper;
+        }
+        for i in 0..num_rows {
+            status[vars.len() + i] = ColStatus::Basic(i);
+        }
+        let mut lower = vec![0.0; num_cols];
+        let mut upper = vec![f64::INFINITY; num_cols];
+        for (j, &var) in vars.iter().enumerate() {
+            let (lb, ub) = domains.bounds(var);
+            lower[j] = lb as f64;
+            upper[j] = ub as f64;
+        }
+
+        Some(LpRelaxation {
+            vars,
+            tableau,
+            rhs,
+            basis: (0..num_rows).map(|i| num_cols - num_rows + i).collect(),
+            status,
+            lower,
+            upper,
+            reduced_costs,
+            objective,
+            objective_col,
+            sign,
+            max_pivots: 1000,
+        })
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.basis.len()
+    }
+
+    /// Resolves the relaxation with the variables in their current domains and returns the bound it provides
+    /// on the objective.
+    pub fn bound(&mut self, domains: &Domains) -> LpBound {
+        for (j, &var) in self.vars.iter().enumerate() {
+            let (lb, ub) = domains.bounds(var);
+            self.lower[j] = lb as f64;
+            self.upper[j] = ub as f64;
+            if lb > ub {
+                return LpBound::Infeasible;
+            }
+        }
+
+        let mut values = vec![0.0; self.status.len()];
+        for _ in 0..self.max_pivots {
+            self.compute_values(&mut values);
+            // leaving row: the basic variable with the largest bound violation
+            let mut leaving = None;
+            let mut max_violation = EPSILON;
+            for (row, &col) in self.basis.iter().enumerate() {
+                let violation = (self.lower[col] - values[col]).max(values[col] - self.upper[col]);
+                if violation > max_violation {
+                    max_violation = violation;
+                    leaving = Some(row);
+                }
+            }
+            let Some(row) = leaving else {
+                break; // primal feasible: optimal
+            };
+            let leaving_col = self.basis[row];
+            let to_lower = values[leaving_col] < self.lower[leaving_col];
+
+            match self.ratio_test(row, to_lower) {
+                Some(entering) => self.pivot(row, entering, to_lower),
+                None => return LpBound::Infeasible,
+            }
+        }
+        self.compute_values(&mut values);
+
+        // value of the relaxation, which is also the one of its dual and thus a bound on the objective
+        let value = values[self.objective_col];
+        if self.sign > 0.0 {
+            LpBound::Bound((value - ROUNDING_TOLERANCE).ceil() as IntCst + self.objective.shift)
+        } else {
+            LpBound::Bound((value + ROUNDING_TOLERANCE).floor() as IntCst + self.objective.shift)
+        }
+    }
+
+    /// Computes the value of all columns in the current basic solution.
+    fn compute_values(&self, values: &mut [f64]) {
+        for (j, status) in self.status.iter().enumerate() {
+            values[j] = match status {
+                ColStatus::AtLower => self.lower[j],
+                ColStatus::AtUpper => self.upper[j],
+                ColStatus::Basic(_) => 0.0,
+            };
+        }
+        for (row, &col) in self.basis.iter().enumerate() {
+            let coefs = &self.tableau[row];
+            let mut value = self.rhs[row];
+            for (j, status) in self.status.iter().enumerate() {
+                if coefs[j] != 0.0 && !matches!(status, ColStatus::Basic(_)) {
+                    value -= coefs[j] * values[j];
+                }
+            }
+            values[col] = value;
+        }
+    }
+
+    /// Selects the column entering the basis when the basic variable of the row leaves it
+    /// for its lower (`to_lower`) or upper bound, preserving dual feasibility.
+    /// Returns `None` if there is no such column, i.e. if the relaxation is infeasible.
+    fn ratio_test(&self, row: usize, to_lower: bool) -> Option<usize> {
+        let coefs = &self.tableau[row];
+        let mut best: Option<(usize, f64)> = None;
+        for (j, status) in self.status.iter().enumerate() {
+            let alpha = coefs[j];
+            if alpha.abs() < MIN_PIVOT {
+                continue;
+            }
+            // the basic variable must increase if it leaves for its lower bound, and decrease otherwise
+            let eligible = match status {
+                ColStatus::Basic(_) => false,
+                ColStatus::AtLower => (alpha < 0.0) == to_lower,
+                ColStatus::AtUpper => (alpha > 0.0) == to_lower,
+            };
+            if !eligible {
+                continue;
+            }
+            let ratio = (self.reduced_costs[j] / alpha).abs();
+            let better = match best {
+                None => true,
+                Some((b, best_ratio)) => {
+                    ratio < best_ratio - EPSILON || (ratio <= best_ratio + EPSILON && alpha.abs() > coefs[b].abs())
+                }
+            };
+            if better {
+                best = Some((j, ratio));
+            }
+        }
+        best.map(|(j, _)| j)
+    }
+
+    /// Makes the column basic in the row, the previously basic column being set to one of its bounds.
+    fn pivot(&mut self, row: usize, entering: usize, to_lower: bool) {
+        let pivot = self.tableau[row][entering];
+        for coef in self.tableau[row].iter_mut() {
+            *coef /= pivot;
+        }
+        self.rhs[row] /= pivot;
+
+        let pivot_row = std::mem::take(&mut self.tableau[row]);
+        for (i, coefs) in self.tableau.iter_mut().enumerate() {
+            let factor = if i == row { 0.0 } else { coefs[entering] };
+            if factor != 0.0 {
+                for (coef, p) in coefs.iter_mut().zip(&pivot_row) {
+                    *coef -= factor * p;
+                }
+                self.rhs[i] -= factor * self.rhs[row];
+            }
+        }
+        let factor = self.reduced_costs[entering];
+        for (d, p) in self.reduced_costs.iter_mut().zip(&pivot_row) {
+            *d -= factor * p;
+        }
+        self.tableau[row] = pivot_row;
+
+        let leaving = self.basis[row];
+        self.status[leaving] = if to_lower {
+            ColStatus::AtLower
+        } else {
+            ColStatus::AtUpper
+        };
+        self.status[entering] = ColStatus::Basic(row);
+        self.basis[row] = entering;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::expr::{leq, lt};
+    use crate::model::lang::linear::LinearSum;
+
+    type Model = crate::model::Model<&'static str>;
+
+    #[test]
+    fn test_lp_bound() {
+        let mut m = Model::new();
+        let x = m.new_ivar(0, 100, "x");
+        let y = m.new_ivar(0, 100, "y");
+        let z = m.new_ivar(0, 100, "z");
+        let makespan = m.new_ivar(0, 1000, "makespan");
+        // x + 3 <= y, x + 5 <= z, y <= makespan, z <= makespan, 2x + y + z >= 21
+        m.enforce(leq(x + 3, y), []);
+        m.enforce(leq(x + 5, z), []);
+        m.enforce(leq(y, makespan), []);
+        m.enforce(leq(z, makespan), []);
+        m.enforce(LinearSum::of(vec![x, x, y, z]).geq(21), []);
+
+        let mut lp = LpRelaxation::new(&m, makespan.into(), true).unwrap();
+        // x = 2.75, y = 7.75, z = 7.75
+        assert_eq!(lp.bound(&m.state), LpBound::Bound(8));
+        assert_eq!(
+            LpRelaxation::new(&m, makespan + 2, true).unwrap().bound(&m.state),
+            LpBound::Bound(10)
+        );
+        assert_eq!(
+            LpRelaxation::new(&m, x.into(), false).unwrap().bound(&m.state),
+            LpBound::Bound(95)
+        );
+
+        // resolve with tightened domains
+        m.state.set_lb(x, 10, crate::core::state::Cause::Decision).unwrap();
+        assert_eq!(lp.bound(&m.state), LpBound::Bound(15));
+        m.state
+            .set_ub(makespan, 14, crate::core::state::Cause::Decision)
+            .unwrap();
+        assert_eq!(lp.bound(&m.state), LpBound::Infeasible);
+
+        // an optional constraint is not part of the relaxation
+        let mut m = Model::new();
+        let x = m.new_ivar(0, 100, "x");
+        let y = m.new_ivar(0, 100, "y");
+        let p = m.new_presence_variable(Lit::TRUE, "p").true_lit();
+        m.enforce(lt(x, y), []);
+        m.enforce(leq(y + 10, x), [p]);
+        let mut lp = LpRelaxation::new(&m, y.into(), true).unwrap();
+        assert_eq!(lp.num_rows(), 1);
+        assert_eq!(lp.bound(&m.state), LpBound::Bound(1));
+    }
+}
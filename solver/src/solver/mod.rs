@@ -1,6 +1,10 @@
+pub mod bench;
+pub mod lemmas;
+pub mod lp;
 pub mod parallel;
 pub mod search;
 pub mod stats;
+pub mod trace;
 
 mod solver_impl;
 pub use solver_impl::*;
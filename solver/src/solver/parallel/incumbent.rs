@@ -0,0 +1,113 @@
+use crate::core::IntCst;
+use crate::solver::parallel::Solution;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Snapshot of the best known solution of a [ParSolver](crate::solver::parallel::ParSolver), as observed by an
+/// [IncumbentWatcher].
+#[derive(Clone, Default)]
+pub struct Incumbent {
+    /// Number of updates of the incumbent so far. It is incremented on each new solution, bound improvement
+    /// or start/end of the search and can be passed to [IncumbentWatcher::wait_update] to wait for the next one.
+    pub version: u64,
+    /// Best solution found in the current (or last) search, if any.
+    pub solution: Option<Solution>,
+    /// Objective value of the solution, if the search is an optimization.
+    pub objective: Option<IntCst>,
+    /// Best bound proved on the objective (a lower bound when minimizing), if any.
+    /// It is equal to the objective value once the solution is proved optimal.
+    pub objective_bound: Option<IntCst>,
+    /// True while the solver is running.
+    pub searching: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    incumbent: Mutex<Incumbent>,
+    /// Direction of the optimization of the current search (`None` for a satisfaction problem).
+    minimize: Mutex<Option<bool>>,
+    updated: Condvar,
+}
+
+/// A thread-safe handle on the best known solution of a [ParSolver](crate::solver::parallel::ParSolver).
+///
+/// The handle can be cloned and sent to other threads, that can either poll the current incumbent
+/// or wait for its next update while the solver is running.
+#[derive(Clone, Default)]
+pub struct IncumbentWatcher {
+    shared: Arc<Shared>,
+}
+
+impl IncumbentWatcher {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current incumbent.
+    pub fn poll(&self) -> Incumbent {
+        self.shared.incumbent.lock().unwrap().clone()
+    }
+
+    /// Blocks until the version of the incumbent is greater than `last_seen` or the timeout (if any) expires,
+    /// and returns the current incumbent.
+    pub fn wait_update(&self, last_seen: u64, timeout: Option<Duration>) -> Incumbent {
+        let incumbent = self.shared.incumbent.lock().unwrap();
+        let is_outdated = |inc: &mut Incumbent| inc.version <= last_seen;
+        let incumbent = match timeout {
+            Some(timeout) => {
+                self.shared
+                    .updated
+                    .wait_timeout_while(incumbent, timeout, is_outdated)
+                    .unwrap()
+                    .0
+            }
+            None => self.shared.updated.wait_while(incumbent, is_outdated).unwrap(),
+        };
+        incumbent.clone()
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Incumbent)) {
+        let mut incumbent = self.shared.incumbent.lock().unwrap();
+        f(&mut incumbent);
+        incumbent.version += 1;
+        self.shared.updated.notify_all();
+    }
+
+    /// Notifies the start of a new search, discarding the incumbent of the previous one.
+    /// `minimize` gives the direction of the optimization, or is `None` for a satisfaction problem.
+    pub(crate) fn start(&self, minimize: Option<bool>) {
+        *self.shared.minimize.lock().unwrap() = minimize;
+        self.update(|inc| {
+            *inc = Incumbent {
+                version: inc.version,
+                searching: true,
+                ..Default::default()
+            }
+        });
+    }
+
+    /// Notifies the end of the current search.
+    pub(crate) fn stop(&self) {
+        self.update(|inc| inc.searching = false);
+    }
+
+    /// Records a new solution, that is assumed to improve on the previous one.
+    pub(crate) fn set_solution(&self, solution: Solution, objective: Option<IntCst>) {
+        self.update(|inc| {
+            inc.solution = Some(solution);
+            inc.objective = objective;
+        });
+    }
+
+    /// Records a bound on the objective, ignored if it is not tighter than the previous one.
+    pub(crate) fn improve_bound(&self, bound: IntCst) {
+        let Some(minimize) = *self.shared.minimize.lock().unwrap() else {
+            return;
+        };
+        let is_tighter = |prev: IntCst| if minimize { bound > prev } else { bound < prev };
+        let current = self.shared.incumbent.lock().unwrap().objective_bound;
+        if current.map_or(true, is_tighter) {
+            self.update(|inc| inc.objective_bound = Some(bound));
+        }
+    }
+}
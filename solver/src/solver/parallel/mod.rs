@@ -1,4 +1,6 @@
+mod incumbent;
 mod parallel_solver;
 pub mod signals;
 
+pub use incumbent::{Incumbent, IncumbentWatcher};
 pub use parallel_solver::*;
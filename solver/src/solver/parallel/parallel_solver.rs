@@ -1,9 +1,13 @@
+use crate::core::Lit;
 use crate::model::extensions::{AssignmentExt, SavedAssignment, Shaped};
 use crate::model::lang::IAtom;
 use crate::model::{Label, ModelShape};
-use crate::solver::parallel::signals::{InputSignal, InputStream, OutputSignal, SolverOutput, ThreadID};
+use crate::solver::lemmas::{LemmaSender, LiteralNames};
+use crate::solver::parallel::incumbent::IncumbentWatcher;
+use crate::solver::parallel::signals::{InputSignal, InputStream, OutputSignal, Progress, SolverOutput, ThreadID};
 use crate::solver::{Exit, Solver};
 use crossbeam_channel::{select, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -11,10 +15,22 @@ use std::time::{Duration, Instant};
 pub struct ParSolver<Lbl> {
     base_model: ModelShape<Lbl>,
     solvers: Vec<Worker<Lbl>>,
+    /// Callback invoked with the index of a worker and its progress, each time it reports it.
+    on_progress: Option<ProgressCallback>,
+    /// Handle on the best solution found by the workers, shared with the watchers given out to other threads.
+    incumbent: IncumbentWatcher,
+    /// Flag that interrupts the running search when raised, checked every [INTERRUPT_POLL_INTERVAL].
+    interrupt: Option<Arc<AtomicBool>>,
 }
 
+/// Maximum delay between the raising of the interruption flag of a [ParSolver] and the interruption of its workers.
+const INTERRUPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub type Solution = Arc<SavedAssignment>;
 
+/// Callback receiving the progress reports of the workers, identified by their index.
+pub type ProgressCallback = Box<dyn FnMut(usize, &Progress) + Send>;
+
 pub enum SolverResult<Solution> {
     /// The solver terminated with a solution.
     Sol(Solution),
@@ -87,6 +103,9 @@ impl<Lbl: Label> ParSolver<Lbl> {
         let mut solver = ParSolver {
             base_model: base_solver.model.shape.clone(),
             solvers: Vec::with_capacity(num_workers),
+            on_progress: None,
+            incumbent: IncumbentWatcher::new(),
+            interrupt: None,
         };
         for i in 0..(num_workers - 1) {
             let mut s = base_solver.clone();
@@ -99,6 +118,82 @@ impl<Lbl: Label> ParSolver<Lbl> {
         solver
     }
 
+    /// Sets a callback that will be invoked (on the thread of the parallel solver) with the periodic progress reports
+    /// of the workers, identified by their index.
+    ///
+    /// Reports are throttled by each worker (see the `ARIES_PROGRESS_REPORT_INTERVAL_MS` environment variable),
+    /// except when a worker finds an improved solution.
+    pub fn set_progress_callback(&mut self, on_progress: impl FnMut(usize, &Progress) + Send + 'static) {
+        self.on_progress = Some(Box::new(on_progress));
+    }
+
+    /// Returns a thread-safe handle that can be used to poll or wait for the best solution found
+    /// (and the best bound proved on the objective) while the solver is running.
+    ///
+    /// The handle remains valid for all subsequent searches of this solver.
+    pub fn incumbent_watcher(&self) -> IncumbentWatcher {
+        self.incumbent.clone()
+    }
+
+    /// Sets a flag that, once raised (e.g. from another thread), stops the running and all subsequent searches
+    /// as if their deadline was reached.
+    pub fn set_interrupt_flag(&mut self, interrupt: Arc<AtomicBool>) {
+        self.interrupt = Some(interrupt);
+    }
+
+    /// Returns a handle to add lemmas over named literals to all workers (see [lemmas](crate::solver::lemmas)),
+    /// e.g. from another thread while they are running.
+    pub fn lemma_sender(&self) -> LemmaSender {
+        let solvers = self
+            .solvers
+            .iter()
+            .filter_map(|worker| match worker {
+                Worker::Running(input) => Some(input.sender.clone()),
+                Worker::Idle(solver) => Some(solver.input_stream().sender),
+                Worker::Halting => None,
+            })
+            .collect();
+        LemmaSender::new(LiteralNames::new(self), solvers)
+    }
+
+    /// Sets the assumptions of all solvers (see [Solver::set_assumptions]).
+    ///
+    /// Assumes that no worker is currently running.
+    pub fn set_assumptions(&mut self, assumptions: &[Lit]) {
+        for x in &mut self.solvers {
+            if let Worker::Idle(solver) = x {
+                solver.set_assumptions(assumptions.iter().copied());
+            } else {
+                panic!("A worker is not available")
+            }
+        }
+    }
+
+    /// If the last run was proved unsatisfiable under the assumptions, returns the assumption that was entailed false
+    /// (see [Solver::failed_assumption]).
+    pub fn failed_assumption(&self) -> Option<Lit> {
+        self.solvers.iter().find_map(|worker| match worker {
+            Worker::Idle(solver) => solver.failed_assumption(),
+            _ => None,
+        })
+    }
+
+    /// Returns the deepest consistent partial assignment reached by a worker in the last run
+    /// (see [Solver::deepest_partial_assignment]), e.g. to diagnose a timeout without any solution.
+    ///
+    /// Assumes that no worker is currently running.
+    pub fn deepest_partial_assignment(&mut self) -> Option<Solution> {
+        let deepest = self
+            .solvers
+            .iter_mut()
+            .filter_map(|worker| match worker {
+                Worker::Idle(solver) => Some(solver),
+                _ => None,
+            })
+            .max_by_key(|solver| solver.deepest_decision_level())?;
+        deepest.deepest_partial_assignment()
+    }
+
     /// Sets the output of all solvers to a particular channel and return its receiving end.
     ///
     /// Assumes that no worker is currently running.
@@ -116,21 +211,21 @@ impl<Lbl: Label> ParSolver<Lbl> {
 
     /// Solve the problem that was given on initialization using all available solvers.
     pub fn solve(&mut self, deadline: Option<Instant>) -> SolverResult<Solution> {
-        self.race_solvers(|s| s.solve(), |_| {}, deadline)
+        self.incumbent.start(None);
+        let incumbent = self.incumbent.clone();
+        let result = self.race_solvers(|s| s.solve(), |sol| incumbent.set_solution(sol, None), deadline);
+        self.incumbent.stop();
+        result
     }
 
     /// Minimize the value of the given expression.
     pub fn minimize(&mut self, objective: impl Into<IAtom>, deadline: Option<Instant>) -> SolverResult<Solution> {
-        let objective = objective.into();
-        self.race_solvers(
-            move |s| match s.minimize(objective) {
-                Ok(Some((_cost, sol))) => Ok(Some(sol)),
-                Ok(None) => Ok(None),
-                Err(x) => Err(x),
-            },
-            |_| {},
-            deadline,
-        )
+        self.optimize_with(objective.into(), true, |_| {}, deadline)
+    }
+
+    /// Maximize the value of the given expression.
+    pub fn maximize(&mut self, objective: impl Into<IAtom>, deadline: Option<Instant>) -> SolverResult<Solution> {
+        self.optimize_with(objective.into(), false, |_| {}, deadline)
     }
 
     /// Minimize the value of the given expression.
@@ -142,32 +237,70 @@ impl<Lbl: Label> ParSolver<Lbl> {
         on_improved_solution: impl Fn(Solution),
         deadline: Option<Instant>,
     ) -> SolverResult<Solution> {
-        let objective = objective.into();
+        self.optimize_with(objective.into(), true, on_improved_solution, deadline)
+    }
+
+    /// Maximize the value of the given expression.
+    /// Each time a new solution is found with an improved objective value, the corresponding
+    /// assignment will be passed to the given callback.
+    pub fn maximize_with(
+        &mut self,
+        objective: impl Into<IAtom>,
+        on_improved_solution: impl Fn(Solution),
+        deadline: Option<Instant>,
+    ) -> SolverResult<Solution> {
+        self.optimize_with(objective.into(), false, on_improved_solution, deadline)
+    }
+
+    fn optimize_with(
+        &mut self,
+        objective: IAtom,
+        minimize: bool,
+        on_improved_solution: impl Fn(Solution),
+        deadline: Option<Instant>,
+    ) -> SolverResult<Solution> {
         // cost of the best solution found so far
         let mut previous_best = None;
+        self.incumbent.start(Some(minimize));
+        let incumbent = self.incumbent.clone();
 
         // callback that checks if a new solution is a strict improvement over the previous one
         // and if that the case, invokes the user-provided callback
         let on_new_sol = |ass: Solution| {
             let obj_value = ass.var_domain(objective).lb;
             let is_improvement = match previous_best {
-                Some(prev) => prev > obj_value,
+                Some(prev) if minimize => prev > obj_value,
+                Some(prev) => prev < obj_value,
                 None => true,
             };
             if is_improvement {
+                incumbent.set_solution(ass.clone(), Some(obj_value));
                 on_improved_solution(ass);
                 previous_best = Some(obj_value)
             }
         };
-        self.race_solvers(
-            move |s| match s.minimize(objective) {
-                Ok(Some((_cost, sol))) => Ok(Some(sol)),
-                Ok(None) => Ok(None),
-                Err(x) => Err(x),
+        let result = self.race_solvers(
+            move |s| {
+                let result = if minimize {
+                    s.minimize(objective)
+                } else {
+                    s.maximize(objective)
+                };
+                match result {
+                    Ok(Some((_cost, sol))) => Ok(Some(sol)),
+                    Ok(None) => Ok(None),
+                    Err(x) => Err(x),
+                }
             },
             on_new_sol,
             deadline,
-        )
+        );
+        if let SolverResult::Sol(sol) = &result {
+            // the solution was proved optimal
+            self.incumbent.improve_bound(sol.var_domain(objective).lb);
+        }
+        self.incumbent.stop();
+        result
     }
 
     /// Generic function to run a lambda in parallel on all available solvers and return the result of the
@@ -215,6 +348,16 @@ impl<Lbl: Label> ParSolver<Lbl> {
             } else {
                 Duration::MAX
             };
+            // the interruption flag is only polled until a final result is known
+            let interrupted = match &self.interrupt {
+                Some(flag) if !matches!(status, SolverStatus::Final(_)) => Some(flag.load(Ordering::Relaxed)),
+                _ => None,
+            };
+            let time_left = match interrupted {
+                Some(true) => Duration::ZERO,
+                Some(false) => time_left.min(INTERRUPT_POLL_INTERVAL),
+                None => time_left,
+            };
             select! {
                 recv(result_rcv) -> res => { // solver termination
                     let WorkerResult {
@@ -227,7 +370,11 @@ impl<Lbl: Label> ParSolver<Lbl> {
                     if !matches!(status, SolverStatus::Final(_)) {
                         // this is the first result we got, store it and stop other solvers
                         let result = match result {
-                            Ok(Some(sol)) => SolverResult::Sol(sol),
+                            Ok(Some(sol)) => {
+                                // the final solution might not have been reported as an intermediate one
+                                on_new_sol(sol.clone());
+                                SolverResult::Sol(sol)
+                            }
                             Ok(None) => SolverResult::Unsat,
                             Err(_) => {
                                 eprintln!("Unexpected interruption of solver.");
@@ -242,6 +389,10 @@ impl<Lbl: Label> ParSolver<Lbl> {
                 }
                 recv(solvers_output) -> msg => { // solver intermediate result
                     if let Ok(msg) = msg {
+                        if let OutputSignal::Progress(progress) = &msg.msg {
+                            self.report_progress(&solvers_inputs, msg.emitter, progress);
+                            continue;
+                        }
                         self.share_among_solvers(&msg);
                         if !matches!(status, SolverStatus::Final(_)) {
                             if let OutputSignal::SolutionFound(assignment) = msg.msg {
@@ -252,6 +403,9 @@ impl<Lbl: Label> ParSolver<Lbl> {
                     }
                 }
                 default(time_left) => { // timeout
+                    if interrupted == Some(false) && deadline.map_or(true, |deadline| Instant::now() < deadline) {
+                        continue; // woke up to poll the interruption flag
+                    }
                     for s in &mut self.solvers {
                         // notify all threads that they should stop ASAP
                         s.interrupt()
@@ -267,15 +421,35 @@ impl<Lbl: Label> ParSolver<Lbl> {
             }
         }
 
+        // forward the progress reports that were emitted by the solvers before they stopped
+        for msg in solvers_output.try_iter() {
+            if let OutputSignal::Progress(progress) = &msg.msg {
+                self.report_progress(&solvers_inputs, msg.emitter, progress);
+            }
+        }
+
         match status {
             SolverStatus::Final(res) => res,
             _ => unreachable!(),
         }
     }
 
-    /// Returns true if there is at least one worker that is currently running.
+    /// Passes a progress report to the user-provided callback, if any, and records the bound it proved on the objective.
+    fn report_progress(&mut self, solvers_inputs: &[InputStream], emitter: ThreadID, progress: &Progress) {
+        if let Some(bound) = progress.objective_bound {
+            self.incumbent.improve_bound(bound);
+        }
+        let worker = solvers_inputs.iter().position(|input| input.id == emitter);
+        if let (Some(on_progress), Some(worker)) = (&mut self.on_progress, worker) {
+            on_progress(worker, progress);
+        }
+    }
+
+    /// Returns true if there is at least one worker that is currently running or has not yet stopped after an interruption.
     fn is_worker_running(&self) -> bool {
-        self.solvers.iter().any(|solver| matches!(&solver, Worker::Running(_)))
+        self.solvers
+            .iter()
+            .any(|solver| matches!(&solver, Worker::Running(_) | Worker::Halting))
     }
 
     /// Share an intermediate result with other running solvers that might be interested.
@@ -291,6 +465,7 @@ impl<Lbl: Label> ParSolver<Lbl> {
                     OutputSignal::SolutionFound(assignment) => {
                         let _ = input.sender.send(InputSignal::SolutionFound(assignment.clone()));
                     }
+                    OutputSignal::Progress(_) => { /* only of interest to the owner of the solvers */ }
                 },
                 _ => { /* Solver is not running or is the emitter, ignore */ }
             }
@@ -323,3 +498,158 @@ enum SolverStatus<Sol> {
     /// A final result was provided by at least one solver.
     Final(SolverResult<Sol>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::expr::{leq, or};
+    use crate::model::lang::IVar;
+    use std::sync::Mutex;
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    /// Scheduling of four jobs on a single machine, returning the model and its makespan (whose optimum is 14).
+    fn single_machine() -> (Model, IVar) {
+        let durations = [3, 5, 2, 4];
+        let mut model = Model::new();
+        let starts = durations.map(|_| model.new_ivar(0, 100, ""));
+        let makespan = model.new_ivar(0, 100, "makespan");
+        for (i, &s) in starts.iter().enumerate() {
+            model.enforce(leq(s + durations[i], makespan), []);
+            for j in (i + 1)..starts.len() {
+                let (t, d) = (starts[j], durations[j]);
+                let before = model.reify(leq(s + durations[i], t));
+                let after = model.reify(leq(t + d, s));
+                model.enforce(or([before, after]), []);
+            }
+        }
+        (model, makespan)
+    }
+
+    #[test]
+    fn progress_reports() {
+        let (model, makespan) = single_machine();
+        let mut solver = ParSolver::new(Box::new(Solver::new(model)), 2, |_, _| {});
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let reports_sink = reports.clone();
+        solver.set_progress_callback(move |worker, progress| {
+            reports_sink.lock().unwrap().push((worker, progress.clone()));
+        });
+        let solution = match solver.minimize(makespan, None) {
+            SolverResult::Sol(solution) => solution,
+            _ => panic!(),
+        };
+        assert_eq!(solution.var_domain(makespan).lb, 14);
+
+        // improved solutions are always reported, regardless of throttling
+        let reports = reports.lock().unwrap();
+        assert!(reports.iter().all(|(worker, _)| *worker < 2));
+        let best_costs = reports.iter().filter_map(|(_, progress)| progress.best_cost);
+        assert_eq!(best_costs.min(), Some(14));
+    }
+
+    #[test]
+    fn incumbent_watcher() {
+        let (model, makespan) = single_machine();
+        let mut solver = ParSolver::new(Box::new(Solver::new(model)), 2, |_, _| {});
+        let watcher = solver.incumbent_watcher();
+        assert!(watcher.poll().solution.is_none());
+
+        // collect the objective values observed by another thread until the end of the search
+        let observer = {
+            let watcher = watcher.clone();
+            std::thread::spawn(move || {
+                let mut incumbent = watcher.poll();
+                let mut objectives: Vec<_> = incumbent.objective.into_iter().collect();
+                while incumbent.searching || incumbent.solution.is_none() {
+                    incumbent = watcher.wait_update(incumbent.version, None);
+                    objectives.extend(incumbent.objective);
+                }
+                objectives
+            })
+        };
+        let solution = match solver.minimize(makespan, None) {
+            SolverResult::Sol(solution) => solution,
+            _ => panic!(),
+        };
+        assert_eq!(solution.var_domain(makespan).lb, 14);
+
+        let objectives = observer.join().unwrap();
+        assert!(objectives.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(objectives.last(), Some(&14));
+        let incumbent = watcher.poll();
+        assert!(!incumbent.searching);
+        assert_eq!(incumbent.objective, Some(14));
+        assert_eq!(incumbent.objective_bound, Some(14));
+        assert_eq!(incumbent.solution.unwrap().var_domain(makespan).lb, 14);
+    }
+
+    #[test]
+    fn interrupt_flag() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::time::{Duration, Instant};
+
+        // pigeonhole problem, whose proof of unsatisfiability is far too long to complete before the interruption
+        let num_pigeons = 14;
+        let mut model = Model::new();
+        let holes: Vec<_> = (0..num_pigeons)
+            .map(|_| model.new_ivar(0, num_pigeons - 2, ""))
+            .collect();
+        for (i, &h1) in holes.iter().enumerate() {
+            for &h2 in &holes[(i + 1)..] {
+                let before = model.reify(leq(h1 + 1, h2));
+                let after = model.reify(leq(h2 + 1, h1));
+                model.enforce(or([before, after]), []);
+            }
+        }
+
+        let mut solver = ParSolver::new(Box::new(Solver::new(model)), 2, |_, _| {});
+        let interrupt = Arc::new(AtomicBool::new(false));
+        solver.set_interrupt_flag(interrupt.clone());
+        let start = Instant::now();
+        let raiser = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            interrupt.store(true, Ordering::Relaxed);
+        });
+        let result = solver.solve(None);
+        raiser.join().unwrap();
+        assert!(matches!(result, SolverResult::Timeout(None)));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        // the flag remains raised, subsequent searches are interrupted as well
+        assert!(matches!(solver.solve(None), SolverResult::Timeout(None)));
+    }
+
+    #[test]
+    fn maximize_negative_coefficients() {
+        use crate::model::lang::linear::{LinearSum, LinearTerm};
+
+        // maximize `obj = x - 2y` with `x + y >= 3`, all values being possibly negative
+        let mut model = Model::new();
+        let x = model.new_ivar(-5, 5, "x");
+        let y = model.new_ivar(-5, 5, "y");
+        let obj = model.new_ivar(-100, 100, "obj");
+        let sum = LinearSum::of(vec![
+            LinearTerm::new(1, x, false),
+            LinearTerm::new(-2, y, false),
+            -LinearTerm::from(obj),
+        ]);
+        model.enforce(sum.clone().leq(0), []);
+        model.enforce(sum.geq(0), []);
+        model.enforce(LinearSum::of(vec![x, y]).geq(3), []);
+
+        let mut solver = ParSolver::new(Box::new(Solver::new(model)), 2, |_, _| {});
+        let values = Mutex::new(Vec::new());
+        let solution = match solver.maximize_with(obj, |sol| values.lock().unwrap().push(sol.var_domain(obj).lb), None)
+        {
+            SolverResult::Sol(solution) => solution,
+            _ => panic!(),
+        };
+        // x = 5, y = -2
+        assert_eq!(solution.var_domain(obj).lb, 9);
+        // intermediate solutions are reported in order of improvement
+        let values = values.into_inner().unwrap();
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+    }
+}
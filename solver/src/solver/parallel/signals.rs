@@ -1,14 +1,23 @@
 use crate::core::literals::Disjunction;
+use crate::core::IntCst;
 use crate::model::extensions::SavedAssignment;
 use crossbeam_channel::{Receiver, Sender};
 use env_param::EnvParam;
 use std::fmt::{Debug, Formatter};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// The maximum size of a clause that can be shared with other threads.
 static MAX_CLAUSE_SHARING_SIZE: EnvParam<usize> = EnvParam::new("ARIES_MAX_CLAUSE_SHARING_SIZE", "6");
 
+/// Minimal delay (in milliseconds) between two progress reports of a solver.
+static PROGRESS_REPORT_INTERVAL_MS: EnvParam<u64> = EnvParam::new("ARIES_PROGRESS_REPORT_INTERVAL_MS", "1000");
+
+/// Number of calls to [Synchro::notify_progress] between two readings of the clock, to keep the cost of
+/// throttling negligible when it is called at each step of the search.
+const PROGRESS_CHECK_PERIOD: u32 = 256;
+
 static THREAD_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
 pub type ThreadID = usize;
 fn get_next_thread_id() -> ThreadID {
@@ -23,6 +32,9 @@ pub enum InputSignal {
     LearnedClause(Arc<Disjunction>),
     /// A solution was found in another solver.
     SolutionFound(Arc<SavedAssignment>),
+    /// A clause provided by an external tool (see [LemmaSender](crate::solver::lemmas::LemmaSender)),
+    /// that is kept permanently in the clause database.
+    Lemma(Arc<Disjunction>),
 }
 
 pub struct InputStream {
@@ -45,6 +57,9 @@ impl Debug for SolverOutput {
             OutputSignal::SolutionFound(_) => {
                 write!(f, "solution")
             }
+            OutputSignal::Progress(progress) => {
+                write!(f, "progress {progress:?}")
+            }
         }
     }
 }
@@ -54,6 +69,30 @@ pub enum OutputSignal {
     LearntClause(Arc<Disjunction>),
     /// An intermediate solution was found, typically a solution that is valid but was not proven optimal yet.
     SolutionFound(Arc<SavedAssignment>),
+    /// A periodic report on the progress of the search.
+    Progress(Progress),
+}
+
+/// Summary of the state of the search of a solver.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// Time spent searching since the solver was started.
+    pub solve_time: Duration,
+    pub num_decisions: u64,
+    pub num_conflicts: u64,
+    pub num_restarts: u64,
+    pub num_solutions: u64,
+    /// Objective value of the best solution found by the solver, if any.
+    pub best_cost: Option<IntCst>,
+    /// Best bound proved on the objective (a lower bound when minimizing), if any.
+    pub objective_bound: Option<IntCst>,
+}
+
+impl Progress {
+    /// Average number of conflicts per second since the solver was started.
+    pub fn conflicts_per_sec(&self) -> f64 {
+        self.num_conflicts as f64 / self.solve_time.as_secs_f64().max(f64::EPSILON)
+    }
 }
 
 /// A structure that holds the various components to communicate to a solver.
@@ -65,6 +104,12 @@ pub struct Synchro {
     pub signals: Receiver<InputSignal>,
     /// A channel where a solver's output can be sent (typically for learnt clauses or intermediate solutions).
     pub output: Option<Sender<SolverOutput>>,
+    /// Time at which the last progress report was sent.
+    last_progress_report: Option<Instant>,
+    /// Minimal delay between two progress reports.
+    progress_report_interval: Duration,
+    /// Number of calls to `notify_progress` since the clock was last read.
+    calls_since_progress_check: u32,
 }
 
 impl Synchro {
@@ -75,6 +120,9 @@ impl Synchro {
             sender: snd,
             signals: rcv,
             output: None,
+            last_progress_report: None,
+            progress_report_interval: Duration::from_millis(PROGRESS_REPORT_INTERVAL_MS.get()),
+            calls_since_progress_check: 0,
         }
     }
 
@@ -112,6 +160,34 @@ impl Synchro {
             let _ = output.send(SolverOutput { emitter: self.id, msg });
         }
     }
+
+    /// Notify listeners of the progress of the search.
+    ///
+    /// Unless `force` is set, reports are throttled so that at most one is sent every `ARIES_PROGRESS_REPORT_INTERVAL_MS`
+    /// milliseconds, and the clock is only read once every few calls.
+    /// The report is only built (by calling `progress`) if it is actually sent.
+    pub fn notify_progress(&mut self, force: bool, progress: impl FnOnce() -> Progress) {
+        if let Some(output) = &self.output {
+            if !force && self.last_progress_report.is_some() {
+                self.calls_since_progress_check += 1;
+                if self.calls_since_progress_check < PROGRESS_CHECK_PERIOD {
+                    return;
+                }
+            }
+            self.calls_since_progress_check = 0;
+            let now = Instant::now();
+            let due = match self.last_progress_report {
+                Some(last) => now.duration_since(last) >= self.progress_report_interval,
+                None => true,
+            };
+            if force || due {
+                self.last_progress_report = Some(now);
+                let msg = OutputSignal::Progress(progress());
+                // ignore errors as the thread might just be running alone in the ether
+                let _ = output.send(SolverOutput { emitter: self.id, msg });
+            }
+        }
+    }
 }
 
 impl Clone for Synchro {
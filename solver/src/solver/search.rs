@@ -1,12 +1,16 @@
 pub mod activity;
 pub mod combinators;
+pub mod impact;
 pub mod lexical;
+mod var_queue;
+pub mod wdeg;
 
 use crate::backtrack::Backtrack;
 use crate::core::state::{Conflict, Explainer};
 use crate::core::*;
 use crate::model::extensions::SavedAssignment;
 use crate::model::{Label, Model};
+use crate::reasoners::Reasoners;
 use crate::solver::stats::Stats;
 
 pub enum Decision {
@@ -31,10 +35,25 @@ pub trait SearchControl<Lbl>: Backtrack {
     /// Returns `None` if no decision is left to be made.
     fn next_decision(&mut self, stats: &Stats, model: &Model<Lbl>) -> Option<Decision>;
 
+    /// Same as `next_decision` but also gives access to the reasoners, which allows taking into account their
+    /// estimate of the impact of a decision (see [Reasoners::decision_impact]).
+    /// This is the method invoked by the solver and it defaults to ignoring the reasoners.
+    fn next_decision_with_reasoners(
+        &mut self,
+        stats: &Stats,
+        model: &Model<Lbl>,
+        reasoners: &Reasoners,
+    ) -> Option<Decision> {
+        self.next_decision(stats, model)
+    }
+
     fn import_vars(&mut self, model: &Model<Lbl>) {}
 
     /// Notifies the search control that a new assignment has been found (either by itself or by an other solver running in parallel).
-    fn new_assignment_found(&mut self, objective_value: IntCst, assignment: std::sync::Arc<SavedAssignment>) {}
+    ///
+    /// The `cost` of the assignment is the value of the objective when minimizing and its opposite when maximizing,
+    /// so that lower costs always denote better assignments.
+    fn new_assignment_found(&mut self, cost: IntCst, assignment: std::sync::Arc<SavedAssignment>) {}
 
     /// Invoked by search immediately before saving the state
     fn pre_save_state(&mut self, _model: &Model<Lbl>) {}
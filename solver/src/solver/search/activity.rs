@@ -5,32 +5,177 @@ use crate::core::literals::Watches;
 use crate::core::state::{Conflict, Event, Explainer, IntDomain};
 use crate::core::*;
 use crate::model::extensions::{AssignmentExt, SavedAssignment, Shaped};
+use crate::model::lang::Kind;
 use crate::model::{Label, Model};
 use crate::solver::search::{Decision, SearchControl};
 use crate::solver::stats::Stats;
 use env_param::EnvParam;
 use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::str::FromStr;
 use std::sync::Arc;
 
+pub static BOOL_VALUE_SELECTION: EnvParam<ValueSelection> = EnvParam::new("ARIES_SMT_BOOL_VALUE_SELECTION", "min");
+pub static NUMERIC_VALUE_SELECTION: EnvParam<ValueSelection> =
+    EnvParam::new("ARIES_SMT_NUMERIC_VALUE_SELECTION", "min");
+pub static SYMBOLIC_VALUE_SELECTION: EnvParam<ValueSelection> =
+    EnvParam::new("ARIES_SMT_SYMBOLIC_VALUE_SELECTION", "min");
+/// Deprecated alias of the value selection parameters: when set to `false`, the largest value is selected instead of
+/// the smallest one for all groups of variables (see [BranchingParams::prefer_min_value]).
+#[deprecated(note = "use BOOL_VALUE_SELECTION, NUMERIC_VALUE_SELECTION and SYMBOLIC_VALUE_SELECTION instead")]
 pub static PREFER_MIN_VALUE: EnvParam<bool> = EnvParam::new("ARIES_SMT_PREFER_MIN_VALUE", "true");
+pub static RANDOM_SEED: EnvParam<u64> = EnvParam::new("ARIES_SMT_RANDOM_SEED", "0");
 pub static INITIALLY_ALLOWED_CONFLICTS: EnvParam<u64> = EnvParam::new("ARIES_SMT_INITIALLY_ALLOWED_CONFLICT", "100");
 pub static INCREASE_RATIO_FOR_ALLOWED_CONFLICTS: EnvParam<f32> =
     EnvParam::new("ARIES_SMT_INCREASE_RATIO_FOR_ALLOWED_CONFLICTS", "1.5");
 pub static USE_LNS: EnvParam<bool> = EnvParam::new("ARIES_ACTIVITY_USES_LNS", "true");
 
+/// Strategy for selecting the value of a variable when branching on it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ValueSelection {
+    /// Select the lower bound of the domain.
+    Min,
+    /// Select the upper bound of the domain.
+    Max,
+    /// Split the domain in its middle, exploring the lower half first.
+    Midpoint,
+    /// Select a value uniformly at random in the domain.
+    Random,
+}
+
+impl ValueSelection {
+    /// Returns the value to be tried for a variable with the domain `[lb, ub]`.
+    fn select(self, lb: IntCst, ub: IntCst, rng: &mut StdRng) -> IntCst {
+        match self {
+            ValueSelection::Min => lb,
+            ValueSelection::Max => ub,
+            ValueSelection::Midpoint => lb + (ub - lb) / 2,
+            ValueSelection::Random => rng.gen_range(lb..=ub),
+        }
+    }
+}
+
+impl FromStr for ValueSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "min" => Ok(ValueSelection::Min),
+            "max" => Ok(ValueSelection::Max),
+            "mid" | "midpoint" => Ok(ValueSelection::Midpoint),
+            "random" => Ok(ValueSelection::Random),
+            _ => Err(format!("Unknown value selection strategy: {s}")),
+        }
+    }
+}
+
+/// Group of variables that can be given distinct value selection strategies.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum VarGroup {
+    /// Boolean variables.
+    Boolean,
+    /// Integer and fixed-point variables (e.g. timepoints).
+    Numeric,
+    /// Variables whose values are symbols.
+    Symbolic,
+}
+
+impl From<Kind> for VarGroup {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Bool => VarGroup::Boolean,
+            Kind::Int | Kind::Fixed(_) => VarGroup::Numeric,
+            Kind::Sym => VarGroup::Symbolic,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct BranchingParams {
-    pub prefer_min_value: bool,
+    /// Value selection strategy for boolean variables
+    pub bool_values: ValueSelection,
+    /// Value selection strategy for integer and fixed-point variables
+    pub numeric_values: ValueSelection,
+    /// Value selection strategy for symbolic variables
+    pub symbolic_values: ValueSelection,
+    /// If true, the value of a boolean variable in the best solution found so far will be preferred
+    /// over the one of the value selection strategy (solution-guided search, as used in LNS).
+    pub bool_solution_guided: bool,
+    /// If true, integer and fixed-point variables are assigned their value in the best solution found so far.
+    pub numeric_solution_guided: bool,
+    /// If true, symbolic variables are assigned their value in the best solution found so far.
+    pub symbolic_solution_guided: bool,
+    /// Seed of the random number generator used by the [ValueSelection::Random] strategy.
+    pub seed: u64,
     pub allowed_conflicts: u64,
     pub increase_ratio_for_allowed_conflicts: f32,
+    /// Deprecated alias of the value selection strategies: if false, [ValueSelection::Max] is used instead of
+    /// [ValueSelection::Min] for all groups of variables. Other strategies are left unchanged.
+    #[deprecated(note = "use the value selection strategy of each group of variables instead")]
+    pub prefer_min_value: bool,
+}
+
+impl BranchingParams {
+    /// Sets the same value selection strategy for all groups of variables.
+    #[allow(deprecated)]
+    pub fn with_value_selection(mut self, strategy: ValueSelection) -> Self {
+        self.bool_values = strategy;
+        self.numeric_values = strategy;
+        self.symbolic_values = strategy;
+        self.prefer_min_value = true;
+        self
+    }
+
+    /// Enables or disables solution-guided value selection for all groups of variables.
+    pub fn with_solution_guidance(mut self, solution_guided: bool) -> Self {
+        self.bool_solution_guided = solution_guided;
+        self.numeric_solution_guided = solution_guided;
+        self.symbolic_solution_guided = solution_guided;
+        self
+    }
+
+    #[allow(deprecated)]
+    pub fn value_selection(&self, group: VarGroup) -> ValueSelection {
+        let strategy = match group {
+            VarGroup::Boolean => self.bool_values,
+            VarGroup::Numeric => self.numeric_values,
+            VarGroup::Symbolic => self.symbolic_values,
+        };
+        match strategy {
+            ValueSelection::Min if !self.prefer_min_value => ValueSelection::Max,
+            strategy => strategy,
+        }
+    }
+
+    /// Returns true if the variables of the group should be given their value in the best solution found so far.
+    pub fn solution_guided(&self, group: VarGroup) -> bool {
+        match group {
+            VarGroup::Boolean => self.bool_solution_guided,
+            VarGroup::Numeric => self.numeric_solution_guided,
+            VarGroup::Symbolic => self.symbolic_solution_guided,
+        }
+    }
+
+    fn any_solution_guided(&self) -> bool {
+        self.bool_solution_guided || self.numeric_solution_guided || self.symbolic_solution_guided
+    }
 }
 
 impl Default for BranchingParams {
+    #[allow(deprecated)]
     fn default() -> Self {
         BranchingParams {
-            prefer_min_value: PREFER_MIN_VALUE.get(),
+            bool_values: BOOL_VALUE_SELECTION.get(),
+            numeric_values: NUMERIC_VALUE_SELECTION.get(),
+            symbolic_values: SYMBOLIC_VALUE_SELECTION.get(),
+            bool_solution_guided: USE_LNS.get(),
+            numeric_solution_guided: USE_LNS.get(),
+            symbolic_solution_guided: USE_LNS.get(),
+            seed: RANDOM_SEED.get(),
             allowed_conflicts: INITIALLY_ALLOWED_CONFLICTS.get(),
             increase_ratio_for_allowed_conflicts: INCREASE_RATIO_FOR_ALLOWED_CONFLICTS.get(),
+            prefer_min_value: PREFER_MIN_VALUE.get(),
         }
     }
 }
@@ -65,6 +210,7 @@ pub struct ActivityBrancher<Lbl> {
     /// Essentially a Map<Lit, Set<VarRef>>
     presences: Watches<VarRef>,
     cursor: ObsTrailCursor<Event>,
+    rng: StdRng,
 }
 
 #[derive(Clone, Default)]
@@ -90,6 +236,7 @@ impl<Lbl: Label> ActivityBrancher<Lbl> {
 
     pub fn new_with(params: BranchingParams, h: impl Heuristic<Lbl>) -> Self {
         ActivityBrancher {
+            rng: StdRng::seed_from_u64(params.seed),
             params,
             heuristic: Arc::new(h),
             heap: VarSelect::new(Default::default()),
@@ -178,30 +325,28 @@ impl<Lbl: Label> ActivityBrancher<Lbl> {
                 Some(Decision::Restart)
             } else {
                 // determine value for literal:
-                // - first from per-variable preferred assignments
-                // - otherwise from the preferred value for boolean variables
+                // - first from per-variable preferred assignments (e.g. from the incumbent solution),
+                //   if enabled for the variable's group
+                // - otherwise from the value selection strategy of the variable's group
                 let IntDomain { lb, ub } = model.var_domain(v);
                 debug_assert!(lb < ub);
 
-                let value = self
-                    .default_assignment
-                    .values
+                let group = model
+                    .shape
+                    .types
                     .get(v)
-                    .copied()
-                    .unwrap_or(if self.params.prefer_min_value { lb } else { ub });
+                    .map_or(VarGroup::Numeric, |&t| Kind::from(t).into());
+                let value = match self.default_assignment.values.get(v) {
+                    Some(&value) if self.params.solution_guided(group) => value,
+                    _ => self.params.value_selection(group).select(lb, ub, &mut self.rng),
+                };
 
-                let literal = if value < lb || value > ub {
-                    if self.params.prefer_min_value {
-                        Lit::leq(v, lb)
-                    } else {
-                        Lit::geq(v, ub)
-                    }
-                } else if ub > value && self.params.prefer_min_value {
-                    Lit::leq(v, value)
-                } else if lb < value {
-                    Lit::geq(v, value)
+                // split the domain so that the value is at the boundary of the explored part
+                let literal = if value <= lb {
+                    Lit::leq(v, lb)
+                } else if value >= ub {
+                    Lit::geq(v, ub)
                 } else {
-                    debug_assert!(ub > value);
                     Lit::leq(v, value)
                 };
 
@@ -213,6 +358,8 @@ impl<Lbl: Label> ActivityBrancher<Lbl> {
         }
     }
 
+    /// Sets the value preferred for the variable, which is only used if its group is solution-guided
+    /// (see [BranchingParams::solution_guided]).
     pub fn set_default_value(&mut self, var: VarRef, val: IntCst) {
         self.default_assignment.values.insert(var, val);
     }
@@ -466,14 +613,14 @@ impl<Lbl: Label> SearchControl<Lbl> for ActivityBrancher<Lbl> {
     }
 
     fn new_assignment_found(&mut self, objective: IntCst, assignment: Arc<SavedAssignment>) {
-        // if we are in LNS mode and the given solution is better than the previous one,
+        // if we are in solution-guided mode and the given solution is better than the previous one,
         // set the default value of all variables to the one they have in the solution.
         let is_improvement = self
             .default_assignment
             .objective_found
             .map(|prev| objective < prev)
             .unwrap_or(true);
-        if USE_LNS.get() && is_improvement {
+        if self.params.any_solution_guided() && is_improvement {
             self.default_assignment.objective_found = Some(objective);
             for (var, val) in assignment.bound_variables() {
                 self.set_default_value(var, val);
@@ -493,3 +640,107 @@ impl<Lbl: Label> SearchControl<Lbl> for ActivityBrancher<Lbl> {
         Box::new(self.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::expr::{lt, neq};
+    use crate::model::lang::IVar;
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    /// A model with three ordered variables `a < b < c` in `[0, 100]`, where `c != 50`.
+    fn ordered_variables() -> (Model, [IVar; 3]) {
+        let mut model = Model::new();
+        let a = model.new_ivar(0, 100, "a");
+        let b = model.new_ivar(0, 100, "b");
+        let c = model.new_ivar(0, 100, "c");
+        model.enforce(lt(a, b), []);
+        model.enforce(lt(b, c), []);
+        model.enforce(neq(c, 50), []);
+        (model, [a, b, c])
+    }
+
+    #[test]
+    fn value_selection_strategies() {
+        for strategy in [
+            ValueSelection::Min,
+            ValueSelection::Max,
+            ValueSelection::Midpoint,
+            ValueSelection::Random,
+        ] {
+            let (model, vars) = ordered_variables();
+            let mut solver = Solver::new(model);
+            let params = BranchingParams::default().with_value_selection(strategy);
+            solver.set_brancher(ActivityBrancher::new_with_params(params));
+            let sol = solver.solve().unwrap().expect("No solution found");
+            let values = vars.map(|v| sol.lower_bound(v));
+            assert!(values[0] < values[1] && values[1] < values[2] && values[2] != 50);
+            match strategy {
+                ValueSelection::Min => assert_eq!(values, [0, 1, 2]),
+                ValueSelection::Max => assert_eq!(values, [98, 99, 100]),
+                _ => {}
+            }
+
+            // optimization, where each new solution guides the search for the next one
+            solver.reset();
+            match solver.minimize(vars[2]).unwrap() {
+                None => panic!(),
+                Some((val, _)) => assert_eq!(val, 2),
+            }
+        }
+    }
+
+    #[test]
+    fn solution_guidance_per_group() {
+        let params = BranchingParams::default().with_solution_guidance(false);
+        assert!(!params.solution_guided(VarGroup::Numeric));
+        let params = BranchingParams {
+            numeric_solution_guided: true,
+            ..params
+        };
+        assert!(params.solution_guided(VarGroup::Numeric));
+        assert!(!params.solution_guided(VarGroup::Boolean) && !params.solution_guided(VarGroup::Symbolic));
+
+        // the preferred values are only used for the groups that are solution-guided
+        for guided in [true, false] {
+            let mut model = Model::new();
+            let x = model.new_ivar(0, 100, "x");
+            let mut brancher = ActivityBrancher::new_with_params(
+                BranchingParams::default()
+                    .with_value_selection(ValueSelection::Min)
+                    .with_solution_guidance(guided),
+            );
+            brancher.set_default_value(x.into(), 7);
+            let mut solver = Solver::new(model);
+            solver.set_brancher(brancher);
+            let sol = solver.solve().unwrap().expect("No solution found");
+            let expected = if guided { 7 } else { 0 };
+            assert_eq!(sol.lower_bound(x), expected);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_prefer_min_value() {
+        let params = BranchingParams {
+            prefer_min_value: false,
+            ..BranchingParams::default().with_value_selection(ValueSelection::Min)
+        };
+        for group in [VarGroup::Boolean, VarGroup::Numeric, VarGroup::Symbolic] {
+            assert_eq!(params.value_selection(group), ValueSelection::Max);
+        }
+        // the deprecated alias only replaces the selection of the smallest value
+        let params = BranchingParams {
+            numeric_values: ValueSelection::Midpoint,
+            ..params
+        };
+        assert_eq!(params.value_selection(VarGroup::Numeric), ValueSelection::Midpoint);
+        assert_eq!(params.value_selection(VarGroup::Boolean), ValueSelection::Max);
+        // setting a strategy for all groups overrides the deprecated alias
+        let params = params.with_value_selection(ValueSelection::Min);
+        assert!(params.prefer_min_value);
+        assert_eq!(params.value_selection(VarGroup::Numeric), ValueSelection::Min);
+    }
+}
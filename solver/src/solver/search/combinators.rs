@@ -1,8 +1,9 @@
-use crate::backtrack::{Backtrack, DecLvl};
+use crate::backtrack::{Backtrack, DecLvl, DecisionLevelTracker};
 use crate::core::state::{Conflict, Explainer};
 use crate::core::{IntCst, Lit};
 use crate::model::extensions::SavedAssignment;
 use crate::model::Model;
+use crate::reasoners::Reasoners;
 use crate::solver::search::{Brancher, Decision, SearchControl};
 use crate::solver::stats::Stats;
 use std::sync::Arc;
@@ -15,6 +16,10 @@ pub trait CombinatorExt<L> {
 
     /// Creates a brancher that extends `self` to have geometric restarts.
     fn with_restarts(self, allowed_conflicts: u64, increase_ratio: f32) -> Brancher<L>;
+
+    /// Creates a brancher that explores the search space of `self` by increasing number of discrepancies
+    /// (see [LimitedDiscrepancy]).
+    fn with_limited_discrepancy(self, initial_limit: u32) -> Brancher<L>;
 }
 
 impl<L: 'static> CombinatorExt<L> for Brancher<L> {
@@ -25,6 +30,10 @@ impl<L: 'static> CombinatorExt<L> for Brancher<L> {
     fn with_restarts(self, allowed_conflicts: u64, increase_ratio: f32) -> Brancher<L> {
         Box::new(WithGeomRestart::new(allowed_conflicts, increase_ratio, self))
     }
+
+    fn with_limited_discrepancy(self, initial_limit: u32) -> Brancher<L> {
+        Box::new(LimitedDiscrepancy::new(initial_limit, self))
+    }
 }
 
 /// A brancher that will systematically ask the `first` brancher for a decision.
@@ -63,6 +72,17 @@ impl<L: 'static> SearchControl<L> for AndThen<L> {
             .or_else(|| self.second.next_decision(stats, model))
     }
 
+    fn next_decision_with_reasoners(
+        &mut self,
+        stats: &Stats,
+        model: &Model<L>,
+        reasoners: &Reasoners,
+    ) -> Option<Decision> {
+        self.first
+            .next_decision_with_reasoners(stats, model, reasoners)
+            .or_else(|| self.second.next_decision_with_reasoners(stats, model, reasoners))
+    }
+
     fn import_vars(&mut self, model: &Model<L>) {
         self.first.import_vars(model);
         self.second.import_vars(model);
@@ -136,6 +156,19 @@ impl<L: 'static> SearchControl<L> for UntilFirstConflict<L> {
         }
     }
 
+    fn next_decision_with_reasoners(
+        &mut self,
+        stats: &Stats,
+        model: &Model<L>,
+        reasoners: &Reasoners,
+    ) -> Option<Decision> {
+        if self.active {
+            self.brancher.next_decision_with_reasoners(stats, model, reasoners)
+        } else {
+            None
+        }
+    }
+
     fn import_vars(&mut self, model: &Model<L>) {
         if self.active {
             self.brancher.import_vars(model)
@@ -195,6 +228,19 @@ impl<L> WithGeomRestart<L> {
             brancher,
         }
     }
+
+    /// Returns true if a restart should be made, in which case the number of allowed conflicts before the next one is increased.
+    fn restart_due(&mut self, stats: &Stats) -> bool {
+        if stats.num_conflicts() - self.conflicts_at_last_restart >= self.allowed_conflicts {
+            // we have exceeded the number of allowed conflict, time for a restart
+            self.conflicts_at_last_restart = stats.num_conflicts();
+            // increase the number of allowed conflicts
+            self.allowed_conflicts = (self.allowed_conflicts as f32 * self.increase_ratio_for_allowed_conflict) as u64;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 impl<L> Backtrack for WithGeomRestart<L> {
@@ -213,17 +259,26 @@ impl<L> Backtrack for WithGeomRestart<L> {
 
 impl<L: 'static> SearchControl<L> for WithGeomRestart<L> {
     fn next_decision(&mut self, stats: &Stats, model: &Model<L>) -> Option<Decision> {
-        if stats.num_conflicts() - self.conflicts_at_last_restart >= self.allowed_conflicts {
-            // we have exceeded the number of allowed conflict, time for a restart
-            self.conflicts_at_last_restart = stats.num_conflicts();
-            // increase the number of allowed conflicts
-            self.allowed_conflicts = (self.allowed_conflicts as f32 * self.increase_ratio_for_allowed_conflict) as u64;
+        if self.restart_due(stats) {
             Some(Decision::Restart)
         } else {
             self.brancher.next_decision(stats, model)
         }
     }
 
+    fn next_decision_with_reasoners(
+        &mut self,
+        stats: &Stats,
+        model: &Model<L>,
+        reasoners: &Reasoners,
+    ) -> Option<Decision> {
+        if self.restart_due(stats) {
+            Some(Decision::Restart)
+        } else {
+            self.brancher.next_decision_with_reasoners(stats, model, reasoners)
+        }
+    }
+
     fn import_vars(&mut self, model: &Model<L>) {
         self.brancher.import_vars(model)
     }
@@ -257,3 +312,165 @@ impl<L: 'static> SearchControl<L> for WithGeomRestart<L> {
         })
     }
 }
+
+/// A brancher that explores the search space of a `brancher` in the manner of limited discrepancy search (LDS):
+/// branches that depart the least from the choices of the heuristic are explored first.
+///
+/// A discrepancy is a literal asserted after a conflict at a non-root level, i.e., a departure from a choice
+/// that the heuristic made on the current branch. When the branch has more discrepancies than allowed, the search
+/// restarts with a limit increased by one. The learnt clauses are kept across restarts, so that the branches already
+/// refuted are not explored again, and the search remains complete since the limit grows without bound.
+///
+/// Each time a new assignment is found, the limit is reset to its initial value, so that the search for an improved
+/// one first follows the heuristic (which is typically guided by the incumbent), as in best-first search.
+pub struct LimitedDiscrepancy<L> {
+    initial_limit: u32,
+    limit: u32,
+    /// Decision level at which each discrepancy of the current branch was made.
+    discrepancies: Vec<DecLvl>,
+    lvl: DecisionLevelTracker,
+    brancher: Brancher<L>,
+}
+
+impl<L> LimitedDiscrepancy<L> {
+    pub fn new(initial_limit: u32, brancher: Brancher<L>) -> Self {
+        LimitedDiscrepancy {
+            initial_limit,
+            limit: initial_limit,
+            discrepancies: Vec::new(),
+            lvl: DecisionLevelTracker::new(),
+            brancher,
+        }
+    }
+
+    /// Returns true if the current branch has too many discrepancies, in which case the limit is increased for the next run.
+    fn restart_due(&mut self) -> bool {
+        if self.discrepancies.len() > self.limit as usize {
+            self.limit += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<L> Backtrack for LimitedDiscrepancy<L> {
+    fn save_state(&mut self) -> DecLvl {
+        self.lvl.save_state();
+        self.brancher.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.brancher.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.brancher.restore_last();
+        self.lvl.restore_last();
+        let lvl = self.lvl.current_decision_level();
+        while self.discrepancies.last().map_or(false, |&dl| dl > lvl) {
+            self.discrepancies.pop();
+        }
+    }
+}
+
+impl<L: 'static> SearchControl<L> for LimitedDiscrepancy<L> {
+    fn next_decision(&mut self, stats: &Stats, model: &Model<L>) -> Option<Decision> {
+        if self.restart_due() {
+            Some(Decision::Restart)
+        } else {
+            self.brancher.next_decision(stats, model)
+        }
+    }
+
+    fn next_decision_with_reasoners(
+        &mut self,
+        stats: &Stats,
+        model: &Model<L>,
+        reasoners: &Reasoners,
+    ) -> Option<Decision> {
+        if self.restart_due() {
+            Some(Decision::Restart)
+        } else {
+            self.brancher.next_decision_with_reasoners(stats, model, reasoners)
+        }
+    }
+
+    fn import_vars(&mut self, model: &Model<L>) {
+        self.brancher.import_vars(model)
+    }
+
+    fn new_assignment_found(&mut self, objective_value: IntCst, assignment: Arc<SavedAssignment>) {
+        self.limit = self.initial_limit;
+        self.brancher.new_assignment_found(objective_value, assignment)
+    }
+
+    fn conflict(&mut self, clause: &Conflict, model: &Model<L>, explainer: &mut dyn Explainer) {
+        self.brancher.conflict(clause, model, explainer)
+    }
+
+    fn asserted_after_conflict(&mut self, lit: Lit, model: &Model<L>) {
+        // invoked after backjumping, the asserted literal is thus at the current level
+        let lvl = self.lvl.current_decision_level();
+        if lvl > DecLvl::ROOT {
+            self.discrepancies.push(lvl);
+        }
+        self.brancher.asserted_after_conflict(lit, model)
+    }
+
+    fn pre_save_state(&mut self, model: &Model<L>) {
+        self.brancher.pre_save_state(model);
+    }
+
+    fn pre_conflict_analysis(&mut self, model: &Model<L>) {
+        self.brancher.pre_conflict_analysis(model);
+    }
+
+    fn clone_to_box(&self) -> Box<dyn SearchControl<L> + Send> {
+        Box::new(LimitedDiscrepancy {
+            initial_limit: self.initial_limit,
+            limit: self.limit,
+            discrepancies: self.discrepancies.clone(),
+            lvl: self.lvl,
+            brancher: self.brancher.clone_to_box(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::lang::expr::{geq, leq, or};
+    use crate::solver::search::default_brancher;
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    #[test]
+    fn limited_discrepancy_search() {
+        // scheduling of jobs on a single machine, minimizing the makespan
+        let durations = [3, 5, 2, 4, 6];
+        let mut model = Model::new();
+        let starts = durations.map(|_| model.new_ivar(0, 100, ""));
+        let makespan = model.new_ivar(0, 100, "makespan");
+        for (i, &s) in starts.iter().enumerate() {
+            model.enforce(leq(s + durations[i], makespan), []);
+            for j in (i + 1)..starts.len() {
+                let (t, d) = (starts[j], durations[j]);
+                let before = model.reify(leq(s + durations[i], t));
+                let after = model.reify(leq(t + d, s));
+                model.enforce(or([before, after]), []);
+            }
+        }
+        // the third job cannot start before 7
+        model.enforce(geq(starts[2], 7), []);
+
+        // the restarts of the search do not prevent it from proving optimality
+        let mut solver = Solver::new(model);
+        solver.set_brancher_boxed(default_brancher().with_limited_discrepancy(0));
+        match solver.minimize(makespan).unwrap() {
+            None => panic!(),
+            Some((val, _)) => assert_eq!(val, 20),
+        }
+    }
+}
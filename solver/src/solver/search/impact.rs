@@ -0,0 +1,310 @@
+use crate::backtrack::{Backtrack, DecLvl, DecisionLevelTracker};
+use crate::core::state::{Conflict, Explainer};
+use crate::core::*;
+use crate::model::Model;
+use crate::reasoners::Reasoners;
+use crate::solver::search::var_queue::VarQueue;
+use crate::solver::search::{Decision, SearchControl};
+use crate::solver::stats::Stats;
+use std::collections::{HashMap, HashSet};
+
+/// A brancher that selects, among the unassigned boolean variables, the literal whose assertion would have
+/// the largest immediate impact on the reasoners (as estimated by [Reasoners::decision_impact]).
+///
+/// With the STN, this prioritizes the enablers of the edges whose activation would cause the largest bound changes,
+/// which is known to be an effective heuristic for disjunctive temporal problems.
+///
+/// Literals for which no reasoner provides an estimate are ignored: the brancher should thus be followed by
+/// another one (e.g. with [and_then](crate::solver::search::combinators::CombinatorExt::and_then)) to complete the search.
+/// Note that all variables of the model are scanned at each decision.
+#[derive(Copy, Clone, Default)]
+pub struct ImpactBrancher {
+    lvl: DecisionLevelTracker,
+}
+
+impl ImpactBrancher {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl Backtrack for ImpactBrancher {
+    fn save_state(&mut self) -> DecLvl {
+        self.lvl.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.lvl.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.lvl.restore_last()
+    }
+}
+
+impl<L> SearchControl<L> for ImpactBrancher {
+    fn next_decision(&mut self, _stats: &Stats, _model: &Model<L>) -> Option<Decision> {
+        // impacts can only be estimated by the reasoners
+        None
+    }
+
+    fn next_decision_with_reasoners(
+        &mut self,
+        _stats: &Stats,
+        model: &Model<L>,
+        reasoners: &Reasoners,
+    ) -> Option<Decision> {
+        let mut best: Option<(u64, Lit)> = None;
+        for v in model.state.variables() {
+            if model.state.bounds(v) != (0, 1) || model.state.present(v) != Some(true) {
+                continue;
+            }
+            for lit in [v.geq(1), v.leq(0)] {
+                if let Some(impact) = reasoners.decision_impact(lit, &model.state) {
+                    if best.map_or(true, |(best_impact, _)| impact > best_impact) {
+                        best = Some((impact, lit));
+                    }
+                }
+            }
+        }
+        best.map(|(_, lit)| Decision::SetLiteral(lit))
+    }
+
+    fn clone_to_box(&self) -> Box<dyn SearchControl<L> + Send> {
+        Box::new(*self)
+    }
+}
+
+/// Impact assumed for the variables on which no decision was made yet.
+/// Being maximal, it ensures that each variable is tried at least once before relying on the measured impacts.
+const INITIAL_IMPACT: f64 = 1.0;
+
+/// A brancher implementing impact-based search (Refalo, 2004): it selects the unbound variable whose past decisions
+/// resulted, on average, in the largest reduction of the search space, and sets it to its lower bound.
+/// Ties are broken in favor of the variable with the smallest domain.
+///
+/// The size of the search space is measured as the product of the widths of the domains of all variables.
+/// The impact of a decision is `1 - size_after / size_before` where `size_after` is measured once the decision has
+/// been propagated. A decision that leads to a conflict has an impact of `1`.
+/// Unlike [ImpactBrancher], the impacts are thus measured rather than estimated by the reasoners.
+#[derive(Clone)]
+pub struct MeasuredImpactBrancher {
+    /// Variables, by decreasing impact and increasing domain size
+    queue: VarQueue<(f64, IntCst)>,
+    /// For each variable, the sum of the impacts of the decisions made on it and the number of such decisions.
+    impacts: HashMap<VarRef, (f64, u32)>,
+    /// The last decision made, whose impact is not measured yet.
+    pending: Option<PendingDecision>,
+}
+
+#[derive(Copy, Clone)]
+struct PendingDecision {
+    var: VarRef,
+    /// Decision level at which the decision was taken.
+    lvl: u32,
+}
+
+impl MeasuredImpactBrancher {
+    pub fn new() -> Self {
+        MeasuredImpactBrancher {
+            queue: VarQueue::new(),
+            impacts: Default::default(),
+            pending: None,
+        }
+    }
+
+    /// Average impact of the decisions made on the variable.
+    pub fn impact(&self, var: VarRef) -> f64 {
+        impact(&self.impacts, var)
+    }
+
+    fn record_impact<L>(&mut self, var: VarRef, impact: f64, model: &Model<L>) {
+        let entry = self.impacts.entry(var).or_insert((0.0, 0));
+        entry.0 += impact;
+        entry.1 += 1;
+        self.queue.rescore(var, priority(&self.impacts, var, model));
+    }
+}
+
+impl Default for MeasuredImpactBrancher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn impact(impacts: &HashMap<VarRef, (f64, u32)>, var: VarRef) -> f64 {
+    match impacts.get(&var) {
+        Some(&(sum, count)) => sum / count as f64,
+        None => INITIAL_IMPACT,
+    }
+}
+
+/// Priority of the variable in the queue: its impact, then the opposite of its domain width.
+fn priority<L>(impacts: &HashMap<VarRef, (f64, u32)>, var: VarRef, model: &Model<L>) -> (f64, IntCst) {
+    (impact(impacts, var), -model.state.domain_width(var))
+}
+
+/// Logarithm of the size of a domain.
+fn log_width(lb: IntCst, ub: IntCst) -> f64 {
+    ((ub - lb) as f64 + 1.0).ln()
+}
+
+impl Backtrack for MeasuredImpactBrancher {
+    fn save_state(&mut self) -> DecLvl {
+        self.queue.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.queue.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.queue.restore_last()
+    }
+}
+
+impl<L> SearchControl<L> for MeasuredImpactBrancher {
+    fn next_decision(&mut self, _stats: &Stats, model: &Model<L>) -> Option<Decision> {
+        // the decision was successfully propagated if we are right below the level at which it was taken,
+        // in which case all events since the last call result from the decision
+        let pending = self
+            .pending
+            .take()
+            .filter(|decision| self.queue.num_saved() == decision.lvl + 1);
+        // value of each bound before the first event that modified it
+        let mut previous: HashMap<SignedVar, IntCst> = HashMap::new();
+        let impacts = &self.impacts;
+        let score = |var, model: &Model<L>| priority(impacts, var, model);
+        self.queue.update(model, score, |event| {
+            if pending.is_some() {
+                previous
+                    .entry(event.affected_bound)
+                    .or_insert(event.previous.value.as_int());
+            }
+        });
+        if let Some(decision) = pending {
+            // change of the logarithm of the search space size, from the variables whose domain was reduced
+            let mut log_change = 0.0;
+            let vars: HashSet<VarRef> = previous.keys().map(|svar| svar.variable()).collect();
+            for var in vars {
+                if model.state.present(var) == Some(false) {
+                    continue;
+                }
+                let (lb, ub) = model.state.bounds(var);
+                let lb_before = previous.get(&SignedVar::minus(var)).map_or(lb, |&prev| -prev);
+                let ub_before = previous.get(&SignedVar::plus(var)).copied().unwrap_or(ub);
+                log_change += log_width(lb, ub) - log_width(lb_before, ub_before);
+            }
+            let impact = 1.0 - log_change.exp();
+            self.record_impact(decision.var, impact.clamp(0.0, 1.0), model);
+        }
+
+        let impacts = &self.impacts;
+        let var = self.queue.best(model, |var, model| priority(impacts, var, model))?;
+        self.pending = Some(PendingDecision {
+            var,
+            lvl: self.queue.num_saved(),
+        });
+        Some(Decision::SetLiteral(var.leq(model.state.lb(var))))
+    }
+
+    fn conflict(&mut self, _clause: &Conflict, model: &Model<L>, _explainer: &mut dyn Explainer) {
+        if let Some(decision) = self.pending.take() {
+            // the decision immediately led to a conflict
+            if self.queue.num_saved() == decision.lvl + 1 {
+                self.record_impact(decision.var, 1.0, model);
+            }
+        }
+    }
+
+    fn clone_to_box(&self) -> Box<dyn SearchControl<L> + Send> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::{Cause, Domains, Explanation, InferenceCause};
+    use crate::model::lang::expr::{leq, or};
+    use crate::solver::search::combinators::CombinatorExt;
+    use crate::solver::search::{default_brancher, Brancher};
+
+    type Model = crate::model::Model<&'static str>;
+    type Solver = crate::solver::Solver<&'static str>;
+
+    struct NoExplain;
+    impl Explainer for NoExplain {
+        fn explain(&mut self, _: InferenceCause, _: Lit, _: &Domains, _: &mut Explanation) {
+            panic!("No explanation expected")
+        }
+    }
+
+    fn decision(brancher: &mut MeasuredImpactBrancher, model: &Model) -> Option<Lit> {
+        match brancher.next_decision(&Stats::new(), model) {
+            Some(Decision::SetLiteral(lit)) => Some(lit),
+            Some(Decision::Restart) => panic!("Unexpected restart"),
+            None => None,
+        }
+    }
+
+    #[test]
+    fn test_selection_order() {
+        let mut m = Model::new();
+        let x: VarRef = m.new_ivar(0, 10, "x").into();
+        let y: VarRef = m.new_ivar(0, 3, "y").into();
+        let mut brancher = MeasuredImpactBrancher::new();
+
+        // all variables have the initial impact, the smallest domain is selected first
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(y, 0)));
+
+        // the decision on y is propagated and reduces the domain of x
+        brancher.save_state();
+        m.state.save_state();
+        m.state.set_ub(y, 0, Cause::Decision).unwrap();
+        m.state.set_ub(x, 4, Cause::Decision).unwrap();
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(x, 0)));
+        // the search space went from 4 * 11 to 1 * 5
+        let expected = 1.0 - 5.0 / 44.0;
+        assert!((brancher.impact(y) - expected).abs() < 1e-9, "{}", brancher.impact(y));
+        assert_eq!(brancher.impact(x), INITIAL_IMPACT);
+
+        // after backtracking, x has not been tried yet and is preferred despite its larger domain
+        brancher.restore_last();
+        m.state.restore_last();
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(x, 0)));
+
+        // a decision leading to a conflict has the maximal impact
+        brancher.save_state();
+        brancher.conflict(&Conflict::contradiction(), &m, &mut NoExplain);
+        brancher.restore_last();
+        assert_eq!(brancher.impact(x), 1.0);
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(x, 0)));
+    }
+
+    #[test]
+    fn impact_branching() {
+        // scheduling of jobs on a single machine, minimizing the makespan
+        let durations = [3, 5, 2, 4];
+        let mut model = Model::new();
+        let starts = durations.map(|_| model.new_ivar(0, 100, ""));
+        let makespan = model.new_ivar(0, 100, "makespan");
+        for (i, &s) in starts.iter().enumerate() {
+            model.enforce(leq(s + durations[i], makespan), []);
+            for j in (i + 1)..starts.len() {
+                let (t, d) = (starts[j], durations[j]);
+                let before = model.reify(leq(s + durations[i], t));
+                let after = model.reify(leq(t + d, s));
+                model.enforce(or([before, after]), []);
+            }
+        }
+
+        let mut solver = Solver::new(model);
+        let brancher: Brancher<&'static str> = Box::new(ImpactBrancher::new());
+        solver.set_brancher_boxed(brancher.and_then(default_brancher()));
+        match solver.minimize(makespan).unwrap() {
+            None => panic!(),
+            Some((val, _)) => assert_eq!(val, 14),
+        }
+    }
+}
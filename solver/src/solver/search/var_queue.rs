@@ -0,0 +1,115 @@
+use crate::backtrack::{Backtrack, DecLvl, ObsTrailCursor, Trail};
+use crate::collections::heap::IdxHeap;
+use crate::core::literals::Watches;
+use crate::core::state::Event;
+use crate::core::*;
+use crate::model::Model;
+
+/// A priority queue of the variables of a model, from which the present and unbound variable with the highest score
+/// can be extracted without scanning all variables.
+///
+/// The score of a variable is computed by the brancher and may depend on its domain. Changes to the domains are
+/// processed incrementally, from the events of the trail:
+///  - a variable whose domain is reduced has its priority updated immediately,
+///  - on backtrack, domains are relaxed and the priorities of the affected variables may become too high. They are
+///    only corrected when the variable reaches the top of the queue.
+///
+/// This lazy correction requires that the priority recorded for a variable is never lower than its actual score,
+/// i.e., that the score of a variable can only decrease when its domain is relaxed. Changes of the scores that
+/// do not stem from domain changes (e.g. a weight updated after a conflict) must be notified with [VarQueue::rescore].
+#[derive(Clone)]
+pub(crate) struct VarQueue<P> {
+    heap: IdxHeap<VarRef, P>,
+    /// Variables removed from the queue, to be put back on backtrack.
+    removed: Trail<VarRef>,
+    num_processed_var: usize,
+    /// Associates presence literals to the optional variables, that must be enqueued when they become present.
+    presences: Watches<VarRef>,
+    cursor: ObsTrailCursor<Event>,
+}
+
+impl<P: PartialOrd + Copy> VarQueue<P> {
+    pub fn new() -> Self {
+        VarQueue {
+            heap: IdxHeap::new(),
+            removed: Trail::default(),
+            num_processed_var: 0,
+            presences: Default::default(),
+            cursor: ObsTrailCursor::new(),
+        }
+    }
+
+    /// Declares the variables created since the last call and processes the events that occurred since then,
+    /// passing each of them to `on_event` once the priority of its variable is updated.
+    pub fn update<L>(
+        &mut self,
+        model: &Model<L>,
+        score: impl Fn(VarRef, &Model<L>) -> P,
+        mut on_event: impl FnMut(&Event),
+    ) {
+        for var in model.state.variables().skip(self.num_processed_var) {
+            let prez = model.state.presence(var);
+            self.heap.declare_element(var, score(var, model));
+            self.presences.add_watch(var, prez);
+            if model.state.entails(prez) {
+                self.heap.enqueue(var);
+            }
+            self.num_processed_var += 1;
+        }
+
+        while let Some(event) = self.cursor.pop(model.state.trail()) {
+            let var = event.affected_bound.variable();
+            if self.heap.is_declared(var) {
+                self.heap.set_priority(var, score(var, model));
+            }
+            for var in self.presences.watches_on(event.new_literal()) {
+                self.heap.enqueue(var);
+            }
+            on_event(event);
+        }
+    }
+
+    /// Updates the priority of the variable, whose score changed independently of its domain.
+    pub fn rescore(&mut self, var: VarRef, priority: P) {
+        if self.heap.is_declared(var) {
+            self.heap.set_priority(var, priority);
+        }
+    }
+
+    /// Returns the present and unbound variable with the highest score, if any.
+    ///
+    /// Bound and non-present variables encountered are removed from the queue, until backtracking to the
+    /// current decision level.
+    pub fn best<L>(&mut self, model: &Model<L>, score: impl Fn(VarRef, &Model<L>) -> P) -> Option<VarRef> {
+        while let Some(&var) = self.heap.peek() {
+            if model.state.is_bound(var) || model.state.present(var) != Some(true) {
+                self.heap.pop();
+                self.removed.push(var);
+                continue;
+            }
+            let actual = score(var, model);
+            if actual != self.heap.priority(var) {
+                // the priority was outdated by a backtrack
+                self.heap.set_priority(var, actual);
+                continue;
+            }
+            return Some(var);
+        }
+        None
+    }
+}
+
+impl<P: PartialOrd + Copy> Backtrack for VarQueue<P> {
+    fn save_state(&mut self) -> DecLvl {
+        self.removed.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.removed.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        let heap = &mut self.heap;
+        self.removed.restore_last_with(|var| heap.enqueue(var))
+    }
+}
@@ -0,0 +1,147 @@
+use crate::backtrack::{Backtrack, DecLvl};
+use crate::collections::ref_store::RefMap;
+use crate::core::state::{Conflict, Explainer};
+use crate::core::*;
+use crate::model::Model;
+use crate::solver::search::var_queue::VarQueue;
+use crate::solver::search::{Decision, SearchControl};
+use crate::solver::stats::Stats;
+
+/// A brancher implementing the dom/wdeg heuristic: it selects the unbound variable with the smallest ratio
+/// between the size of its domain and the number of conflicts it was involved in, and sets it to its lower bound.
+///
+/// This favors small domains of variables that appear in many conflicts, which is well suited to strongly constrained
+/// problems where conflicts are frequent but propagation is weak.
+/// A variable is considered involved in a conflict if it appears in the learnt clause.
+#[derive(Clone)]
+pub struct DomWDegBrancher {
+    /// Variables, by decreasing ratio `wdeg / dom`
+    queue: VarQueue<f64>,
+    /// Number of conflicts in which each variable was involved
+    num_conflicts: RefMap<VarRef, u64>,
+}
+
+impl DomWDegBrancher {
+    pub fn new() -> Self {
+        DomWDegBrancher {
+            queue: VarQueue::new(),
+            num_conflicts: Default::default(),
+        }
+    }
+}
+
+/// Priority of the variable in the queue, which is the inverse of its dom/wdeg ratio.
+fn priority<L>(num_conflicts: &RefMap<VarRef, u64>, var: VarRef, model: &Model<L>) -> f64 {
+    let weighted_degree = 1 + num_conflicts.get(var).copied().unwrap_or(0);
+    weighted_degree as f64 / (model.state.domain_width(var) as f64 + 1.0)
+}
+
+impl Default for DomWDegBrancher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backtrack for DomWDegBrancher {
+    fn save_state(&mut self) -> DecLvl {
+        self.queue.save_state()
+    }
+
+    fn num_saved(&self) -> u32 {
+        self.queue.num_saved()
+    }
+
+    fn restore_last(&mut self) {
+        self.queue.restore_last()
+    }
+}
+
+impl<L> SearchControl<L> for DomWDegBrancher {
+    fn next_decision(&mut self, _stats: &Stats, model: &Model<L>) -> Option<Decision> {
+        let num_conflicts = &self.num_conflicts;
+        let score = |var, model: &Model<L>| priority(num_conflicts, var, model);
+        self.queue.update(model, score, |_| {});
+        let var = self.queue.best(model, score)?;
+        Some(Decision::SetLiteral(var.leq(model.state.lb(var))))
+    }
+
+    fn conflict(&mut self, clause: &Conflict, model: &Model<L>, _explainer: &mut dyn Explainer) {
+        for lit in clause.literals() {
+            let var = lit.variable();
+            if var == VarRef::ZERO {
+                continue;
+            }
+            let n = self.num_conflicts.get(var).copied().unwrap_or(0);
+            self.num_conflicts.insert(var, n + 1);
+            self.queue.rescore(var, priority(&self.num_conflicts, var, model));
+        }
+    }
+
+    fn clone_to_box(&self) -> Box<dyn SearchControl<L> + Send> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::literals::{Disjunction, LitSet};
+    use crate::core::state::{Cause, Domains, Explanation, InferenceCause};
+
+    type Model = crate::model::Model<&'static str>;
+
+    struct NoExplain;
+    impl Explainer for NoExplain {
+        fn explain(&mut self, _: InferenceCause, _: Lit, _: &Domains, _: &mut Explanation) {
+            panic!("No explanation expected")
+        }
+    }
+
+    fn decision(brancher: &mut DomWDegBrancher, model: &Model) -> Option<Lit> {
+        match brancher.next_decision(&Stats::new(), model) {
+            Some(Decision::SetLiteral(lit)) => Some(lit),
+            Some(Decision::Restart) => panic!("Unexpected restart"),
+            None => None,
+        }
+    }
+
+    #[test]
+    fn test_selection_order() {
+        let mut m = Model::new();
+        let x = m.new_ivar(0, 10, "x");
+        let y = m.new_ivar(0, 3, "y");
+        let z = m.new_ivar(0, 5, "z");
+        let mut brancher = DomWDegBrancher::new();
+
+        // without conflicts, the smallest domain is selected first
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(y, 0)));
+
+        // x is involved in three conflicts: 4/11 > 1/4
+        let conflict = Conflict {
+            clause: Disjunction::new(vec![Lit::leq(x, 3)]),
+            resolved: LitSet::new(),
+        };
+        for _ in 0..3 {
+            brancher.conflict(&conflict, &m, &mut NoExplain);
+        }
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(x, 0)));
+
+        // reducing the domain of z makes it preferred: 1/2 > 4/11
+        brancher.save_state();
+        m.state.save_state();
+        m.state.set_ub(z, 1, Cause::Decision).unwrap();
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(z, 0)));
+
+        // bound variables are ignored
+        m.state.set_ub(z, 0, Cause::Decision).unwrap();
+        m.state.set_ub(x, 0, Cause::Decision).unwrap();
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(y, 0)));
+        m.state.set_ub(y, 0, Cause::Decision).unwrap();
+        assert_eq!(decision(&mut brancher, &m), None);
+
+        // after backtracking, the domain of z is relaxed and x is preferred again
+        brancher.restore_last();
+        m.state.restore_last();
+        assert_eq!(decision(&mut brancher, &m), Some(Lit::leq(x, 0)));
+    }
+}
@@ -1,25 +1,45 @@
-use crate::backtrack::{Backtrack, DecLvl};
+use crate::backtrack::{Backtrack, DecLvl, EventIndex};
 use crate::core::literals::Disjunction;
 use crate::core::state::*;
 use crate::core::*;
-use crate::model::extensions::{AssignmentExt, DisjunctionExt, SavedAssignment, Shaped};
+use crate::model::extensions::{AssignmentExt, DecisionValue, DisjunctionExt, SavedAssignment, Shaped};
 use crate::model::lang::IAtom;
 use crate::model::{Constraint, Label, Model, ModelShape};
 use crate::reasoners::{Contradiction, Reasoners};
 use crate::reif::{ReifExpr, Reifiable};
-use crate::solver::parallel::signals::{InputSignal, InputStream, SolverOutput, Synchro};
+use crate::solver::lemmas::{LemmaSender, LiteralNames};
+use crate::solver::lp::{LpBound, LpRelaxation};
+use crate::solver::parallel::signals::{InputSignal, InputStream, Progress, SolverOutput, Synchro};
 use crate::solver::search::{default_brancher, Decision, SearchControl};
 use crate::solver::stats::Stats;
+use crate::solver::trace::SearchTrace;
 use crate::utils::cpu_time::StartCycleCount;
 use crossbeam_channel::Sender;
 use env_param::EnvParam;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::fmt::Formatter;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 /// If true, decisions will be logged to the standard output.
 static LOG_DECISIONS: EnvParam<bool> = EnvParam::new("ARIES_LOG_DECISIONS", "false");
 
+/// Maximum size of the nogoods recorded when restarting (see [Solver::record_restart_nogoods]).
+/// A value of 0 disables the recording of nogoods on restarts.
+static RESTART_NOGOOD_MAX_SIZE: EnvParam<usize> = EnvParam::new("ARIES_RESTART_NOGOOD_MAX_SIZE", "10");
+
+/// If true, the objective is bounded at the root with the LP relaxation of the model (see [LpRelaxation])
+/// when optimizing.
+static LP_BOUND: EnvParam<bool> = EnvParam::new("ARIES_LP_BOUND", "false");
+
+/// If true, the bound of the objective is shaved at the root by probing between two improvements of the objective
+/// (see [Solver::shave_objective]).
+static SHAVE_OBJECTIVE: EnvParam<bool> = EnvParam::new("ARIES_SHAVE_OBJECTIVE", "false");
+
+/// Maximum number of probes made by each shaving of the objective.
+const MAX_SHAVING_PROBES: usize = 32;
+
 /// Macro that uses the the same syntax as `println!()` but:
 ///  - only evaluate arguments and print if `LOG_DECISIONS` is true.
 ///  - prepends the thread id to the line.
@@ -54,6 +74,42 @@ impl std::fmt::Display for Exit {
 }
 impl std::error::Error for Exit {}
 
+/// Maximal difference between the cost of a solution and the optimal cost that is accepted when optimizing
+/// (see [Solver::minimize_within_gap]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OptimalityGap {
+    /// Absolute difference between the two costs.
+    Absolute(IntCst),
+    /// Difference relative to the cost of the solution, e.g. `0.05` for a solution at most 5% worse than the optimum.
+    Relative(f64),
+}
+
+impl OptimalityGap {
+    /// Largest difference accepted between a solution of the given cost and the optimal one.
+    fn allowed(self, cost: IntCst) -> IntCst {
+        match self {
+            OptimalityGap::Absolute(gap) => gap.max(0),
+            OptimalityGap::Relative(gap) => (gap * cost.abs() as f64).floor().max(0.0) as IntCst,
+        }
+    }
+}
+
+/// A solution of an optimization problem, together with a proven bound on the optimal cost.
+#[derive(Clone)]
+pub struct BoundedSolution {
+    pub cost: IntCst,
+    /// Bound on the optimal cost: a lower bound when minimizing, an upper bound when maximizing.
+    pub bound: IntCst,
+    pub assignment: Arc<SavedAssignment>,
+}
+
+impl BoundedSolution {
+    /// Certified gap of the solution: the largest possible difference between its cost and the optimal one.
+    pub fn gap(&self) -> IntCst {
+        (self.cost - self.bound).abs()
+    }
+}
+
 pub struct Solver<Lbl> {
     pub model: Model<Lbl>,
     /// Index of the next constraint to post in the model.
@@ -65,9 +121,24 @@ pub struct Solver<Lbl> {
     /// A data structure with the various communication channels
     /// needed to receive/send updates and commands.
     sync: Synchro,
+    /// If set, all nodes of the search tree will be recorded in this trace.
+    trace: Option<SearchTrace>,
+    /// Literals asserted after a conflict on the current branch, together with the decision level at which
+    /// they were asserted (in increasing order of level).
+    /// Each of them refutes a subtree of the current branch, which is recorded as a nogood on restart.
+    refutations: Vec<(DecLvl, Lit)>,
+    /// Decision level and decisions of the deepest consistent node reached by the last search.
+    deepest_branch: (DecLvl, Vec<Lit>),
+    /// Literals decided, in order, at the first decision levels of each search (see [Solver::set_assumptions]).
+    assumptions: Vec<Lit>,
+    /// Assumption that was entailed false at the end of the last search, if it was unsatisfiable because of it.
+    failed_assumption: Option<Lit>,
+    /// Buffer for the lemmas that theories request to be permanently kept in the clause database.
+    lemmas: Vec<Disjunction>,
 }
 impl<Lbl: Label> Solver<Lbl> {
-    pub fn new(model: Model<Lbl>) -> Solver<Lbl> {
+    pub fn new(mut model: Model<Lbl>) -> Solver<Lbl> {
+        model.seal();
         Solver {
             model,
             next_unposted_constraint: 0,
@@ -76,6 +147,12 @@ impl<Lbl: Label> Solver<Lbl> {
             decision_level: DecLvl::ROOT,
             stats: Default::default(),
             sync: Synchro::new(),
+            trace: None,
+            refutations: Vec::new(),
+            deepest_branch: (DecLvl::ROOT, Vec::new()),
+            assumptions: Vec::new(),
+            failed_assumption: None,
+            lemmas: Vec::new(),
         }
     }
 
@@ -87,6 +164,12 @@ impl<Lbl: Label> Solver<Lbl> {
         self.brancher = brancher
     }
 
+    /// Records all nodes of the search tree subsequently explored by this solver in the given trace.
+    /// The trace is not shared with clones of this solver.
+    pub fn set_search_trace(&mut self, trace: SearchTrace) {
+        self.trace = Some(trace);
+    }
+
     pub fn input_stream(&self) -> InputStream {
         self.sync.input_stream()
     }
@@ -95,14 +178,35 @@ impl<Lbl: Label> Solver<Lbl> {
         self.sync.set_output(output);
     }
 
+    /// Returns a handle to add lemmas over named literals to this solver (see [lemmas](crate::solver::lemmas)),
+    /// e.g. from another thread while it is running.
+    pub fn lemma_sender(&self) -> LemmaSender {
+        LemmaSender::new(LiteralNames::new(&self.model), vec![self.input_stream().sender])
+    }
+
+    /// Returns the learnt clauses of at most `max_size` literals that are currently in the clause database,
+    /// over named literals (see [lemmas](crate::solver::lemmas)).
+    /// The clauses involving a variable without a unique label are ignored.
+    pub fn export_lemmas(&self, max_size: usize) -> Vec<String> {
+        let names = LiteralNames::new(&self.model);
+        self.reasoners
+            .sat
+            .learnt_clauses()
+            .filter(|clause| clause.len() <= max_size)
+            .filter_map(|clause| names.format_clause(clause.literals()))
+            .collect()
+    }
+
     pub fn enforce<Expr: Reifiable<Lbl>>(&mut self, bool_expr: Expr, scope: impl IntoIterator<Item = Lit>) {
         assert_eq!(self.decision_level, DecLvl::ROOT);
         self.model.enforce(bool_expr, scope);
     }
+    /// Enforces all given expressions in the scope, their reification being deferred until
+    /// the constraints of the model are posted (see [Model::enforce_all]).
     pub fn enforce_all<Expr: Reifiable<Lbl>>(
         &mut self,
         bools: impl IntoIterator<Item = Expr>,
-        scope: impl IntoIterator<Item = Lit> + Clone,
+        scope: impl IntoIterator<Item = Lit>,
     ) {
         assert_eq!(self.decision_level, DecLvl::ROOT);
         self.model.enforce_all(bools, scope);
@@ -152,10 +256,8 @@ impl<Lbl: Label> Solver<Lbl> {
                         self.add_clause(clause, scope)?;
                     }
                     // make (or a b ...) => l    <=> (and (a => l) (b => l) ...)
-                    for &disjunct in disjuncts {
-                        // enforce a => l
-                        self.add_clause([!disjunct, value], scope)?;
-                    }
+                    // the implications are only turned into clauses when they become relevant to propagation
+                    self.reasoners.sat.add_lazy_implications(disjuncts, value, scope);
                     Ok(())
                 }
             }
@@ -169,6 +271,42 @@ impl<Lbl: Label> Solver<Lbl> {
                 self.reasoners.cp.add_linear_constraint(lin);
                 Ok(())
             }
+            ReifExpr::EqMax(em) => {
+                assert!(self.model.entails(value), "Unsupported reified max constraints.");
+                assert_eq!(self.model.presence_literal(value.variable()), Lit::TRUE);
+                self.reasoners.cp.add_eq_max_constraint(em);
+                Ok(())
+            }
+            ReifExpr::EqAbs(abs) => {
+                assert!(self.model.entails(value), "Unsupported reified abs constraints.");
+                assert_eq!(self.model.presence_literal(value.variable()), Lit::TRUE);
+                self.reasoners.cp.add_eq_abs_constraint(abs);
+                Ok(())
+            }
+            ReifExpr::EqDiv(div) => {
+                assert!(self.model.entails(value), "Unsupported reified div constraints.");
+                assert_eq!(self.model.presence_literal(value.variable()), Lit::TRUE);
+                self.reasoners.cp.add_eq_div_constraint(div);
+                Ok(())
+            }
+            ReifExpr::EqMod(rem) => {
+                assert!(self.model.entails(value), "Unsupported reified mod constraints.");
+                assert_eq!(self.model.presence_literal(value.variable()), Lit::TRUE);
+                self.reasoners.cp.add_eq_mod_constraint(rem);
+                Ok(())
+            }
+            ReifExpr::EqMul(mul) => {
+                assert!(self.model.entails(value), "Unsupported reified mul constraints.");
+                assert_eq!(self.model.presence_literal(value.variable()), Lit::TRUE);
+                self.reasoners.cp.add_eq_mul_constraint(mul);
+                Ok(())
+            }
+            ReifExpr::Channel(ch) => {
+                assert!(self.model.entails(value), "Unsupported reified channeling constraints.");
+                assert_eq!(self.model.presence_literal(value.variable()), Lit::TRUE);
+                self.reasoners.cp.add_channel_constraint(ch);
+                Ok(())
+            }
         }
     }
 
@@ -218,6 +356,7 @@ impl<Lbl: Label> Solver<Lbl> {
 
     /// Post all constraints of the model that have not been previously posted.
     fn post_constraints(&mut self) -> Result<(), InvalidUpdate> {
+        self.model.seal();
         if self.next_unposted_constraint == self.model.shape.constraints.len() {
             return Ok(()); // fast path that avoids updating metrics
         }
@@ -233,6 +372,248 @@ impl<Lbl: Label> Solver<Lbl> {
         Ok(())
     }
 
+    /// Sets the literals that are assumed to hold in all subsequent searches, replacing any previous assumptions.
+    ///
+    /// Assumptions are decided, in order, before any decision of the brancher. Unlike constraints, they can
+    /// later be relaxed, which allows solving a variant of the problem while keeping all clauses learnt so far.
+    /// When a search is unsatisfiable because an assumption is entailed false, this assumption is
+    /// reported by [Solver::failed_assumption].
+    pub fn set_assumptions(&mut self, assumptions: impl IntoIterator<Item = Lit>) {
+        self.reset();
+        self.assumptions = assumptions.into_iter().collect();
+    }
+
+    /// If the last search proved the problem unsatisfiable under the assumptions (see [Solver::set_assumptions]),
+    /// returns the assumption that was entailed false.
+    /// In this case, the problem might be satisfiable without the assumptions.
+    pub fn failed_assumption(&self) -> Option<Lit> {
+        self.failed_assumption
+    }
+
+    /// Decision level of the deepest consistent node reached by the last search.
+    pub fn deepest_decision_level(&self) -> DecLvl {
+        self.deepest_branch.0
+    }
+
+    /// Returns the deepest consistent partial assignment reached by the last search, e.g. to provide some diagnostic
+    /// when the search was interrupted without finding a solution.
+    ///
+    /// The assignment is rebuilt by replaying the decisions of the deepest node from the root. As the clauses learnt
+    /// since then might refute some of these decisions, the replay stops at the first one that is no longer consistent.
+    /// Returns `None` if the problem is inconsistent at the root. The solver is left at the root.
+    pub fn deepest_partial_assignment(&mut self) -> Option<Arc<SavedAssignment>> {
+        self.reset();
+        if self.propagate().is_err() {
+            return None;
+        }
+        let (_, decisions) = self.deepest_branch.clone();
+        for dec in decisions {
+            match self.model.state.value(dec) {
+                Some(true) => continue,
+                Some(false) => break,
+                None => {}
+            }
+            self.decide(dec);
+            if self.propagate().is_err() {
+                self.restore_last();
+                break;
+            }
+        }
+        let assignment = Arc::new(self.model.state.clone());
+        self.reset();
+        Some(assignment)
+    }
+
+    /// Removes from the model the reified expressions that are no longer needed (see [Model::collect_garbage]),
+    /// to be called between two solves of an incremental session. The assumptions of the solver are always kept.
+    ///
+    /// The solver is reset to the root level. Returns the number of removed expressions.
+    pub fn collect_garbage(&mut self, roots: impl IntoIterator<Item = Lit>) -> usize {
+        self.reset();
+        let roots = roots.into_iter().chain(self.assumptions.iter().copied());
+        let removed = self.model.collect_garbage(roots);
+        // posted constraints remain in the reasoners, only the position of the first unposted one changes
+        let removed_posted = removed.iter().filter(|&&i| i < self.next_unposted_constraint).count();
+        self.next_unposted_constraint -= removed_posted;
+        removed.len()
+    }
+
+    /// Propagates the problem at the root level and returns all literals entailed there, each with its explanation:
+    /// literals, themselves entailed at the root, whose conjunction implies it (see [Domains::implying_literals]).
+    /// This allows simplifying the problem before search, or reporting to the user the facts that follow from it
+    /// (e.g. an optional element that can never be present).
+    ///
+    /// Only the strongest literal on each bound of a variable is returned, in the order in which they were inferred.
+    /// Literals that were set directly (e.g. with [Solver::add_root_facts]) rather than inferred have no explanation,
+    /// and bounds of the initial domains are not reported.
+    ///
+    /// The solver is reset to the root level. Returns `None` if propagation proves the problem unsatisfiable.
+    pub fn root_entailments(&mut self) -> Option<Vec<(Lit, Option<Vec<Lit>>)>> {
+        self.reset();
+        if !self.propagate_and_backtrack_to_consistent() {
+            return None;
+        }
+        // literal of the last (i.e. strongest) event on each bound
+        let mut strongest: Vec<Lit> = Vec::new();
+        let mut index_of: HashMap<SignedVar, usize> = HashMap::new();
+        for event in self.model.state.trail().events() {
+            let lit = event.new_literal();
+            match index_of.entry(event.affected_bound) {
+                Entry::Occupied(e) => strongest[*e.get()] = lit,
+                Entry::Vacant(e) => {
+                    e.insert(strongest.len());
+                    strongest.push(lit);
+                }
+            }
+        }
+        let entailments = strongest
+            .into_iter()
+            .map(|lit| (lit, self.model.state.implying_literals(lit, &mut self.reasoners)))
+            .collect();
+        Some(entailments)
+    }
+
+    /// Adds facts that hold at the root level (e.g. observations made while executing a solution) to a solver that may be in
+    /// the middle of a search, without discarding the search state.
+    ///
+    /// Since the facts only restrict the problem, all clauses learnt so far remain valid. The solver backtracks to
+    /// the root level to record the facts and then replays the decisions of the current branch, up to the first one
+    /// that is contradicted by the facts or whose propagation fails. A subsequent search thus resumes from the longest
+    /// prefix of the current branch that is still consistent.
+    ///
+    /// Returns `false` if the facts are inconsistent with the problem, in which case it has no solution.
+    #[must_use]
+    pub fn add_root_facts(&mut self, facts: impl IntoIterator<Item = Lit>) -> bool {
+        let facts: Vec<Lit> = facts
+            .into_iter()
+            .filter(|&fact| !self.model.state.entailed_at_level(fact, DecLvl::ROOT))
+            .collect();
+        if facts.is_empty() {
+            return true; // all facts already hold at the root level, the current branch is unaffected
+        }
+        let branch = self.branch_decisions();
+        self.reset();
+        for fact in facts {
+            if self.model.state.set(fact, Cause::Encoding).is_err() {
+                return false;
+            }
+        }
+        if !self.propagate_and_backtrack_to_consistent() {
+            return false;
+        }
+        for decision in branch {
+            match decision {
+                Some(lit) if self.model.state.value(lit).is_none() => self.decide(lit),
+                Some(lit) if self.model.state.entails(!lit) => break,
+                // empty level, or the decision is now entailed: open an empty level so that
+                // the levels of the assumptions are preserved
+                _ => {
+                    self.save_state();
+                }
+            }
+            if let Err(conflict) = self.propagate() {
+                // the branch is not valid beyond this point, learn from the conflict and resume from there
+                return self.add_conflicting_clause_and_backtrack(conflict)
+                    && self.propagate_and_backtrack_to_consistent();
+            }
+        }
+        true
+    }
+
+    /// Returns, for each decision level of the current branch, the decision that opened it (if any).
+    fn branch_decisions(&self) -> Vec<Option<Lit>> {
+        let trail = self.model.state.trail();
+        let mut decisions = Vec::with_capacity(self.decision_level.to_int() as usize);
+        let mut lvl = DecLvl::ROOT;
+        while lvl < self.decision_level {
+            let first = trail.num_events_at_level(lvl);
+            let next = trail.num_events_at_level(lvl + 1);
+            let decision = (first < next)
+                .then(|| trail.get_event(EventIndex::new(first as usize)))
+                .filter(|ev| ev.cause == Origin::DECISION)
+                .map(|ev| ev.new_literal());
+            decisions.push(decision);
+            lvl += 1;
+        }
+        decisions
+    }
+
+    /// Enumerates all solutions of the problem, projected on its decision variables (see [Model::tag_decision]):
+    /// solutions that only differ on auxiliary variables are considered identical and only one of them is reported.
+    /// If no variable is tagged as a decision, at most one solution is reported.
+    ///
+    /// Instead of excluding each solution with a blocking clause, the search space is partitioned on the decisions:
+    /// once a solution is found in a subspace, the remaining ones are in the subspaces obtained by fixing the first decision
+    /// variables to their value in the solution and reversing the value of the next one (which is either made absent,
+    /// or restricted to smaller or greater values). Each subspace is explored with a search under the assumptions defining it,
+    /// which keeps the clauses learnt in previous searches valid.
+    ///
+    /// The assumptions of the solver (see [Solver::set_assumptions]) restrict the enumeration and are restored afterwards.
+    /// `on_solution` is called on each solution, together with its projection on the decision variables.
+    /// Returns the number of solutions found.
+    pub fn enumerate_projected(
+        &mut self,
+        mut on_solution: impl FnMut(&[DecisionValue<Lbl>], &SavedAssignment),
+    ) -> Result<u64, Exit> {
+        let base_assumptions = self.assumptions.clone();
+        self.reset();
+        let decisions: Vec<(VarRef, IntCst, IntCst)> = self
+            .model
+            .shape
+            .decision_variables()
+            .map(|var| (var, self.model.state.lb(var), self.model.state.ub(var)))
+            .collect();
+
+        // subspaces that remain to be explored, defined by their assumptions and by the index
+        // of the first decision variable whose value is not fixed by them
+        let mut subspaces = vec![(base_assumptions.clone(), 0)];
+        let mut num_solutions = 0;
+        while let Some((mut assumptions, first)) = subspaces.pop() {
+            self.set_assumptions(assumptions.iter().copied());
+            let sol = match self.solve() {
+                Ok(Some(sol)) => sol,
+                Ok(None) => continue,
+                Err(e) => {
+                    self.set_assumptions(base_assumptions);
+                    return Err(e);
+                }
+            };
+            num_solutions += 1;
+            on_solution(&sol.project_to_decisions(&self.model), &sol);
+
+            for (i, &(var, lb, ub)) in decisions.iter().enumerate().skip(first) {
+                let prez = sol.presence(var);
+                if sol.entails(prez) {
+                    let value = sol.lb(var);
+                    if prez != Lit::TRUE && !assumptions.contains(&prez) {
+                        let mut absent = assumptions.clone();
+                        absent.push(!prez);
+                        subspaces.push((absent, i + 1));
+                        assumptions.push(prez);
+                    }
+                    if value > lb {
+                        let mut smaller = assumptions.clone();
+                        smaller.push(Lit::lt(var, value));
+                        subspaces.push((smaller, i));
+                    }
+                    if value < ub {
+                        let mut greater = assumptions.clone();
+                        greater.push(Lit::gt(var, value));
+                        subspaces.push((greater, i));
+                    }
+                    assumptions.extend([Lit::leq(var, value), Lit::geq(var, value)]);
+                } else {
+                    let mut present = assumptions.clone();
+                    present.push(prez);
+                    subspaces.push((present, i));
+                    assumptions.push(!prez);
+                }
+            }
+        }
+        self.set_assumptions(base_assumptions);
+        Ok(num_solutions)
+    }
+
     /// Searches for the first satisfying assignment, returning none if the search
     /// space was exhausted without encountering a solution.
     pub fn solve(&mut self) -> Result<Option<Arc<SavedAssignment>>, Exit> {
@@ -249,6 +630,8 @@ impl<Lbl: Label> Solver<Lbl> {
     fn _solve(&mut self) -> Result<SolveResult, Exit> {
         // make sure brancher has knowledge of all variables.
         self.brancher.import_vars(&self.model);
+        self.failed_assumption = None;
+        self.deepest_branch = (DecLvl::ROOT, Vec::new());
 
         let start_time = Instant::now();
         let start_cycles = StartCycleCount::now();
@@ -263,6 +646,9 @@ impl<Lbl: Label> Solver<Lbl> {
                     InputSignal::LearnedClause(cl) => {
                         self.reasoners.sat.add_forgettable_clause(cl.as_ref());
                     }
+                    InputSignal::Lemma(cl) => {
+                        self.reasoners.sat.add_permanent_clause(cl.as_ref());
+                    }
                     InputSignal::SolutionFound(assignment) => {
                         self.stats.solve_time += start_time.elapsed();
                         self.stats.solve_cycles += start_cycles.elapsed();
@@ -271,23 +657,64 @@ impl<Lbl: Label> Solver<Lbl> {
                 }
             }
 
+            let stats = &self.stats;
+            self.sync
+                .notify_progress(false, || Self::progress(stats, stats.solve_time + start_time.elapsed()));
+
             if !self.propagate_and_backtrack_to_consistent() {
                 // UNSAT
+                if let Some(trace) = &mut self.trace {
+                    trace.exhausted(self.model.state.num_events());
+                }
                 self.stats.solve_time += start_time.elapsed();
                 self.stats.solve_cycles += start_cycles.elapsed();
                 return Ok(SolveResult::Unsat);
             }
-            match self.brancher.next_decision(&self.stats, &self.model) {
+            if self.decision_level > self.deepest_branch.0 {
+                self.deepest_branch = (
+                    self.decision_level,
+                    self.branch_decisions().into_iter().flatten().collect(),
+                );
+            }
+            // decide the next assumption, if any, so that the i-th decision level is always the one of the i-th assumption
+            if let Some(&assumption) = self.assumptions.get(self.decision_level.to_int() as usize) {
+                match self.model.state.value(assumption) {
+                    Some(true) => {
+                        // already entailed, open an empty decision level
+                        self.save_state();
+                    }
+                    Some(false) => {
+                        // UNSAT under the assumptions
+                        self.failed_assumption = Some(assumption);
+                        self.stats.solve_time += start_time.elapsed();
+                        self.stats.solve_cycles += start_cycles.elapsed();
+                        return Ok(SolveResult::Unsat);
+                    }
+                    None => self.decide(assumption),
+                }
+                continue;
+            }
+            match self
+                .brancher
+                .next_decision_with_reasoners(&self.stats, &self.model, &self.reasoners)
+            {
                 Some(Decision::SetLiteral(lit)) => {
                     // println!("Decision: {}", self.model.fmt(lit));
                     self.decide(lit);
                 }
                 Some(Decision::Restart) => {
+                    if let Some(trace) = &mut self.trace {
+                        trace.restart(self.decision_level, self.model.state.num_events());
+                    }
+                    self.record_restart_nogoods();
                     self.reset();
                     self.stats.add_restart();
                 }
                 None => {
                     log_dec!("=> SOLUTION");
+                    if let Some(trace) = &mut self.trace {
+                        trace.solution(self.decision_level, self.model.state.num_events());
+                    }
                     // SAT: consistent + no choices left
                     self.stats.solve_time += start_time.elapsed();
                     self.stats.solve_cycles += start_cycles.elapsed();
@@ -301,6 +728,19 @@ impl<Lbl: Label> Solver<Lbl> {
         }
     }
 
+    /// Builds a report on the progress of the search, `solve_time` being the time spent searching so far.
+    fn progress(stats: &Stats, solve_time: Duration) -> Progress {
+        Progress {
+            solve_time,
+            num_decisions: stats.num_decisions(),
+            num_conflicts: stats.num_conflicts(),
+            num_restarts: stats.num_restarts(),
+            num_solutions: stats.num_solutions(),
+            best_cost: stats.best_cost(),
+            objective_bound: stats.objective_bound(),
+        }
+    }
+
     pub fn minimize(&mut self, objective: impl Into<IAtom>) -> Result<Option<(IntCst, Arc<SavedAssignment>)>, Exit> {
         self.minimize_with(objective, |_, _| ())
     }
@@ -310,7 +750,22 @@ impl<Lbl: Label> Solver<Lbl> {
         objective: impl Into<IAtom>,
         on_new_solution: impl FnMut(IntCst, &SavedAssignment),
     ) -> Result<Option<(IntCst, Arc<SavedAssignment>)>, Exit> {
-        self.optimize_with(objective.into(), true, on_new_solution)
+        let best = self.optimize_with(objective.into(), true, OptimalityGap::Absolute(0), on_new_solution)?;
+        Ok(best.map(|sol| (sol.cost, sol.assignment)))
+    }
+
+    /// Minimizes the objective until the best solution found is proven to be within the given gap of the optimum,
+    /// which is typically much faster than proving its optimality.
+    ///
+    /// Each new solution is required to improve on the previous one by more than the allowed gap, so that the search
+    /// can stop as soon as no such solution exists. The returned solution holds the lower bound proven on the
+    /// objective, from which its certified gap is derived.
+    pub fn minimize_within_gap(
+        &mut self,
+        objective: impl Into<IAtom>,
+        gap: OptimalityGap,
+    ) -> Result<Option<BoundedSolution>, Exit> {
+        self.optimize_with(objective.into(), true, gap, |_, _| ())
     }
 
     pub fn maximize(&mut self, objective: impl Into<IAtom>) -> Result<Option<(IntCst, Arc<SavedAssignment>)>, Exit> {
@@ -322,18 +777,44 @@ impl<Lbl: Label> Solver<Lbl> {
         objective: impl Into<IAtom>,
         on_new_solution: impl FnMut(IntCst, &SavedAssignment),
     ) -> Result<Option<(IntCst, Arc<SavedAssignment>)>, Exit> {
-        self.optimize_with(objective.into(), false, on_new_solution)
+        let best = self.optimize_with(objective.into(), false, OptimalityGap::Absolute(0), on_new_solution)?;
+        Ok(best.map(|sol| (sol.cost, sol.assignment)))
+    }
+
+    /// Same as [Solver::minimize_within_gap], but maximizes the objective.
+    /// The returned solution holds the upper bound proven on the objective.
+    pub fn maximize_within_gap(
+        &mut self,
+        objective: impl Into<IAtom>,
+        gap: OptimalityGap,
+    ) -> Result<Option<BoundedSolution>, Exit> {
+        self.optimize_with(objective.into(), false, gap, |_, _| ())
     }
 
+    /// Optimizes the objective, until no solution improves on the best one found by more than the allowed gap.
     fn optimize_with(
         &mut self,
         objective: IAtom,
         minimize: bool,
+        gap: OptimalityGap,
         mut on_new_solution: impl FnMut(IntCst, &SavedAssignment),
-    ) -> Result<Option<(IntCst, Arc<SavedAssignment>)>, Exit> {
-        // best solution found so far
-        let mut best = None;
+    ) -> Result<Option<BoundedSolution>, Exit> {
+        // best solution found so far, with the bound on the objective that holds once no better solution exists
+        let mut best: Option<BoundedSolution> = None;
+        let mut lp = if LP_BOUND.get() {
+            LpRelaxation::new(&self.model, objective, minimize)
+        } else {
+            None
+        };
         loop {
+            if let Some(lp) = &mut lp {
+                if !self.bound_objective(lp, objective, minimize) {
+                    return Ok(self.exhausted(best)); // no solution better than the best one exists
+                }
+            }
+            if SHAVE_OBJECTIVE.get() && !self.shave_objective(objective, minimize) {
+                return Ok(self.exhausted(best)); // no solution better than the best one exists
+            }
             let sol = match self._solve()? {
                 SolveResult::AtSolution => {
                     // solver stopped at a solution, this is necessarily an improvement on the best solution found so far
@@ -345,18 +826,18 @@ impl<Lbl: Label> Solver<Lbl> {
                     sol
                 }
                 SolveResult::ExternalSolution(sol) => sol, // a solution was handed out to us by another solver
-                SolveResult::Unsat => return Ok(best), // exhausted search space, return the best result found so far
+                SolveResult::Unsat => return Ok(self.exhausted(best)), // exhausted search space, return the best result found so far
             };
 
             // determine whether the solution found is an improvement on the previous one (might not be the case if sent by another solver)
             let objective_value = sol.var_domain(objective).lb;
-            let is_improvement = match best {
+            let is_improvement = match &best {
                 None => true,
-                Some((previous_best, _)) => {
+                Some(previous_best) => {
                     if minimize {
-                        objective_value < previous_best
+                        objective_value < previous_best.cost
                     } else {
-                        objective_value > previous_best
+                        objective_value > previous_best.cost
                     }
                 }
             };
@@ -365,25 +846,130 @@ impl<Lbl: Label> Solver<Lbl> {
                 // Notify the brancher that a new solution has been found.
                 // This enables the use of LNS-like solution and letting the brancher use the values in the best solution
                 // as the preferred ones.
-                self.brancher.new_assignment_found(objective_value, sol.clone());
+                let cost = if minimize { objective_value } else { -objective_value };
+                self.brancher.new_assignment_found(cost, sol.clone());
                 self.stats.add_solution(objective_value); // TODO: might consider external solutions
-                                                          // save the best solution
-                best = Some((objective_value, sol));
+                let stats = &self.stats;
+                self.sync
+                    .notify_progress(true, || Self::progress(stats, stats.solve_time));
 
-                // restart at root with a constraint enforcing future solution to improve the objective
+                // restart at root with a constraint enforcing future solution to improve the objective by more than
+                // the allowed gap
                 self.reset();
+                let allowed = gap.allowed(objective_value);
+                let (lb, ub) = self.model.int_bounds(objective);
+                let bound = if minimize {
+                    // println!("Setting objective < {}", objective_value - allowed);
+                    self.reasoners
+                        .sat
+                        .add_clause([objective.lt_lit(objective_value.saturating_sub(allowed))]);
+                    objective_value.saturating_sub(allowed).max(lb).min(objective_value)
+                } else {
+                    // println!("Setting objective > {}", objective_value + allowed);
+                    self.reasoners
+                        .sat
+                        .add_clause([objective.gt_lit(objective_value.saturating_add(allowed))]);
+                    objective_value.saturating_add(allowed).min(ub).max(objective_value)
+                };
+                // save the best solution
+                best = Some(BoundedSolution {
+                    cost: objective_value,
+                    bound,
+                    assignment: sol,
+                });
+            }
+        }
+    }
+
+    /// Returns the best solution once the search space is exhausted, i.e., once no solution improves on it by more
+    /// than the allowed gap, which makes its bound proven.
+    fn exhausted(&mut self, best: Option<BoundedSolution>) -> Option<BoundedSolution> {
+        if let Some(best) = &best {
+            self.stats.set_objective_bound(best.bound);
+        }
+        best
+    }
+
+    /// Tightens the domain of the objective with the bound of its LP relaxation in the root domains.
+    /// Does nothing if the solver is not at the root.
+    ///
+    /// Returns false if the problem was proved to have no solution (in particular none improving on the best one).
+    fn bound_objective(&mut self, lp: &mut LpRelaxation, objective: IAtom, minimize: bool) -> bool {
+        if self.decision_level != DecLvl::ROOT {
+            return true;
+        }
+        // make sure the domains account for the constraint on improving the objective
+        if !self.propagate_and_backtrack_to_consistent() {
+            return false;
+        }
+        match lp.bound(&self.model.state) {
+            LpBound::Infeasible => false,
+            LpBound::Bound(bound) => {
+                self.stats.set_objective_bound(bound);
                 if minimize {
-                    // println!("Setting objective < {objective_value}");
-                    self.reasoners.sat.add_clause([objective.lt_lit(objective_value)]);
+                    self.reasoners.sat.add_clause([!objective.lt_lit(bound)]);
                 } else {
-                    // println!("Setting objective > {objective_value}");
-                    self.reasoners.sat.add_clause([objective.gt_lit(objective_value)]);
+                    self.reasoners.sat.add_clause([!objective.gt_lit(bound)]);
                 }
+                true
             }
         }
     }
 
+    /// Tightens the bound of the objective (its lower bound when minimizing) in the root domains by destructive
+    /// probing: the objective is assumed to be at least as good as a value close to its bound and,
+    /// if this is refuted by propagation alone (without search), the value is excluded from its domain.
+    /// After each refutation, the distance of the probed value to the bound is doubled, and halved otherwise,
+    /// until a value one step away from the bound is not refuted (or `MAX_SHAVING_PROBES` probes were made).
+    /// Does nothing if the solver is not at the root.
+    ///
+    /// Returns false if the problem was proved to have no solution (in particular none improving on the best one).
+    pub fn shave_objective(&mut self, objective: impl Into<IAtom>, minimize: bool) -> bool {
+        let objective = objective.into();
+        if self.decision_level != DecLvl::ROOT {
+            return true;
+        }
+        if !self.propagate_and_backtrack_to_consistent() {
+            return false;
+        }
+        let mut step: IntCst = 1;
+        for _ in 0..MAX_SHAVING_PROBES {
+            let (lb, ub) = self.model.int_bounds(objective);
+            if lb >= ub {
+                break;
+            }
+            // literal stating that the objective is at least as good as the probed value (strictly in the domain)
+            let probe = if minimize {
+                objective.lt_lit(lb.saturating_add(step).min(ub))
+            } else {
+                objective.gt_lit(ub.saturating_sub(step).max(lb))
+            };
+            self.decide(probe);
+            let refuted = self.propagate().is_err();
+            self.restore_last();
+            if refuted {
+                // no solution reaches the probed value, exclude it (and all better ones) from the domain
+                self.reasoners.sat.add_clause([!probe]);
+                if !self.propagate_and_backtrack_to_consistent() {
+                    return false;
+                }
+                step = step.saturating_mul(2);
+            } else if step > 1 {
+                step /= 2;
+            } else {
+                break;
+            }
+        }
+        let (lb, ub) = self.model.int_bounds(objective);
+        self.stats.set_objective_bound(if minimize { lb } else { ub });
+        true
+    }
+
     pub fn decide(&mut self, decision: Lit) {
+        if let Some(trace) = &mut self.trace {
+            let label = self.model.fmt(decision).to_string();
+            trace.decision(&label, self.decision_level, self.model.state.num_events());
+        }
         self.save_state();
         log_dec!(
             "decision: {:?} -- {}     dom:{:?}",
@@ -396,6 +982,38 @@ impl<Lbl: Label> Solver<Lbl> {
         self.stats.add_decision(decision)
     }
 
+    /// Records the subtrees refuted on the current branch as permanent clauses, so that they are not explored again
+    /// after a restart (restart-based nogood recording, as in Lecoutre et al., 2007).
+    ///
+    /// For each literal `l` asserted at level `i` after a conflict, the clause `!d_1 | ... | !d_i | l` is added,
+    /// where `d_1 ... d_i` are the decisions of the first `i` levels. Unlike learnt clauses, these clauses are never
+    /// forgotten and are only recorded if they contain at most `ARIES_RESTART_NOGOOD_MAX_SIZE` literals.
+    fn record_restart_nogoods(&mut self) {
+        let max_size = RESTART_NOGOOD_MAX_SIZE.get();
+        if self.refutations.is_empty() || max_size == 0 {
+            return;
+        }
+        let decisions = self.model.state.decisions();
+        let mut nogoods = Vec::new();
+        for &(lvl, refutation) in &self.refutations {
+            let size = lvl.to_int() as usize + 1;
+            if size > max_size {
+                // refutations are sorted by increasing level, all the following ones are too large
+                break;
+            }
+            let mut clause: Vec<Lit> = decisions
+                .iter()
+                .take_while(|(dec_lvl, _)| *dec_lvl <= lvl)
+                .map(|(_, dec)| !*dec)
+                .collect();
+            clause.push(refutation);
+            nogoods.push(clause);
+        }
+        for clause in nogoods {
+            self.reasoners.sat.add_permanent_clause(clause);
+        }
+    }
+
     /// Determines the appropriate backtrack level for this clause and returns the literal that
     /// is asserted at this level.
     ///
@@ -472,7 +1090,14 @@ impl<Lbl: Label> Solver<Lbl> {
         // }
         // println!();
 
-        if let Some((dl, asserted)) = self.backtrack_level_for_clause(expl.literals()) {
+        let backjump = self.backtrack_level_for_clause(expl.literals());
+        if let Some(trace) = &mut self.trace {
+            let num_events = self.model.state.num_events();
+            trace.conflict(self.decision_level, num_events, expl.len(), backjump.map(|(dl, _)| dl));
+        }
+        #[cfg(feature = "var_stats")]
+        self.model.state.record_conflict(expl.literals());
+        if let Some((dl, asserted)) = backjump {
             // inform the brancher that we are in a conflict state
             self.brancher.conflict(&expl, &self.model, &mut self.reasoners);
             // backtrack
@@ -480,6 +1105,9 @@ impl<Lbl: Label> Solver<Lbl> {
             debug_assert_eq!(self.model.state.value_of_clause(&expl.clause), None);
 
             if let Some(asserted) = asserted {
+                if dl > DecLvl::ROOT {
+                    self.refutations.push((dl, asserted));
+                }
                 // add clause to sat solver, making sure the asserted literal is set to true
                 self.reasoners.sat.add_learnt_clause(expl.clause, asserted);
                 self.brancher.asserted_after_conflict(asserted, &self.model)
@@ -551,8 +1179,13 @@ impl<Lbl: Label> Solver<Lbl> {
                 let theory_propagation_start = StartCycleCount::now();
                 self.stats[i].propagation_loops += 1;
                 let th = self.reasoners.reasoner_mut(i);
+                let result = th.propagate(&mut self.model.state);
+                th.take_permanent_lemmas(&self.model.state, &mut self.lemmas);
+                for lemma in self.lemmas.drain(..) {
+                    self.reasoners.sat.add_permanent_clause(lemma);
+                }
 
-                match th.propagate(&mut self.model.state) {
+                match result {
                     Ok(()) => (),
                     Err(contradiction) => {
                         self.brancher.pre_conflict_analysis(&self.model);
@@ -575,8 +1208,14 @@ impl<Lbl: Label> Solver<Lbl> {
                 self.stats[i].propagation_time += theory_propagation_start.elapsed();
             }
 
-            if num_events_at_start == self.model.state.num_events() {
-                // no new events, inferred in this propagation loop, exit.
+            if num_events_at_start == self.model.state.num_events()
+                && !self
+                    .reasoners
+                    .writers()
+                    .iter()
+                    .any(|&i| self.reasoners.reasoner(i).has_deferred_propagation())
+            {
+                // no new events, inferred in this propagation loop and no pending work, exit.
                 break;
             }
         }
@@ -624,11 +1263,13 @@ impl<Lbl> Backtrack for Solver<Lbl> {
     fn restore_last(&mut self) {
         assert!(self.decision_level > DecLvl::ROOT);
         self.restore(self.decision_level - 1);
-        self.decision_level -= 1;
     }
 
     fn restore(&mut self, saved_id: DecLvl) {
         self.decision_level = saved_id;
+        while self.refutations.last().map_or(false, |(lvl, _)| *lvl > saved_id) {
+            self.refutations.pop();
+        }
         self.model.restore(saved_id);
         self.brancher.restore(saved_id);
         for w in self.reasoners.writers() {
@@ -649,6 +1290,13 @@ impl<Lbl: Label> Clone for Solver<Lbl> {
             decision_level: self.decision_level,
             stats: self.stats.clone(),
             sync: self.sync.clone(),
+            // a trace is attached to a single solver
+            trace: None,
+            refutations: self.refutations.clone(),
+            deepest_branch: self.deepest_branch.clone(),
+            assumptions: self.assumptions.clone(),
+            failed_assumption: self.failed_assumption,
+            lemmas: Vec::new(),
         }
     }
 }
@@ -710,4 +1358,428 @@ mod test {
         // check(s, T, [!px, !py, xy1], [xy1]);
         // check(s, T, [!px, !py], [!px, !py]); // !pxy, would be correct as well
     }
+
+    #[test]
+    fn test_assumptions() {
+        use crate::backtrack::Backtrack;
+        use crate::model::lang::expr::or;
+
+        let mut m = Model::new();
+        let a = m.new_bvar("a").true_lit();
+        let b = m.new_bvar("b").true_lit();
+        let c = m.new_bvar("c").true_lit();
+        m.enforce(or([!a, !b]), []);
+        let mut s = Solver::new(m);
+
+        s.set_assumptions([a, c, b]);
+        assert!(s.solve().unwrap().is_none());
+        assert_eq!(s.failed_assumption(), Some(b));
+
+        // relaxing the assumptions makes the problem satisfiable again
+        s.set_assumptions([b]);
+        let sol = s.solve().unwrap().unwrap();
+        assert!(sol.entails(b) && sol.entails(!a));
+        assert_eq!(s.failed_assumption(), None);
+
+        // unsatisfiable regardless of the assumptions
+        s.reset();
+        s.enforce(or([a]), []);
+        s.enforce(or([b]), []);
+        s.set_assumptions([c]);
+        assert!(s.solve().unwrap().is_none());
+        assert_eq!(s.failed_assumption(), None);
+    }
+
+    #[test]
+    fn test_root_facts() {
+        use crate::backtrack::{Backtrack, DecLvl};
+        use crate::model::lang::expr::or;
+
+        let mut m = Model::new();
+        let a = m.new_bvar("a").true_lit();
+        let b = m.new_bvar("b").true_lit();
+        let c = m.new_bvar("c").true_lit();
+        m.enforce(or([!a, !b]), []);
+        let mut s = Solver::new(m);
+        assert!(s.propagate().is_ok());
+        s.decide(c);
+        s.decide(a);
+        assert!(s.propagate().is_ok());
+        assert!(s.model.state.entails(!b));
+
+        // fact compatible with the current branch, which is fully replayed
+        assert!(s.add_root_facts([!b]));
+        assert_eq!(s.current_decision_level(), DecLvl::new(2));
+        assert!(s.model.state.entails(a) && s.model.state.entails(c));
+        assert!(s.model.state.entailed_at_level(!b, DecLvl::ROOT));
+
+        // fact contradicting the second decision, only the first one is replayed
+        assert!(s.add_root_facts([!a]));
+        assert_eq!(s.current_decision_level(), DecLvl::new(1));
+        assert!(s.model.state.entails(c));
+        let sol = s.solve().unwrap().unwrap();
+        assert!(sol.entails(!a) && sol.entails(!b));
+
+        // fact contradicting the previous ones
+        assert!(!s.add_root_facts([a]));
+    }
+
+    #[test]
+    fn test_scoped_lit() {
+        use crate::model::lang::expr::leq;
+
+        let mut m = Model::new();
+        let p = m.new_presence_variable(Lit::TRUE, "p").true_lit();
+        let x = m.new_optional_ivar(0, 10, p, "x");
+        let y = m.new_optional_ivar(0, 10, p, "y");
+
+        let tauto = m.scoped_lit(p);
+        assert_eq!(m.scoped_lit(p), tauto);
+        assert_eq!(m.state.presence(tauto.variable()), p);
+        assert_eq!(m.scoped_lit(Lit::TRUE), Lit::TRUE);
+        assert_eq!(m.scoped_value(Lit::FALSE, p), !tauto);
+        assert_eq!(m.scoped_value(p, Lit::TRUE), p);
+
+        // binding to a constant is interpreted in the scope of the expression
+        m.bind(leq(x, y), Lit::FALSE);
+        m.enforce(p, []);
+        let mut s = Solver::new(m);
+        let sol = s.solve().unwrap().unwrap();
+        assert!(sol.lb(x.into()) > sol.lb(y.into()));
+    }
+
+    #[test]
+    fn test_deferred_constraints() {
+        use crate::model::extensions::AssignmentExt;
+        use crate::model::lang::expr::leq;
+
+        let mut m = Model::new();
+        let p = m.new_presence_variable(Lit::TRUE, "p").true_lit();
+        let x = m.new_ivar(0, 10, "x");
+        let y = m.new_ivar(0, 10, "y");
+        m.enforce_all([leq(x, y), leq(y, 5)], [p]);
+        assert!(!m.is_sealed());
+        assert!(m.shape.constraints.is_empty());
+        m.seal();
+        assert!(m.is_sealed());
+        assert!(m.shape.constraints.len() >= 2);
+
+        // the remaining deferred constraints are reified when the solver is created
+        m.enforce_all([leq(x, 3)], []);
+        m.enforce(p, []);
+        let mut s = Solver::new(m);
+        assert!(s.model.is_sealed());
+        s.propagate().unwrap();
+        assert_eq!(s.model.int_bounds(x), (0, 3));
+        // and when posting the constraints of the solver
+        s.enforce_all([leq(y, 2)], []);
+        s.propagate().unwrap();
+        assert_eq!(s.model.int_bounds(x), (0, 2));
+    }
+
+    #[test]
+    fn test_restart_nogoods() {
+        use crate::backtrack::{Backtrack, DecLvl};
+        use crate::model::lang::expr::or;
+
+        let mut m = Model::new();
+        let a = m.new_bvar("a").true_lit();
+        let b = m.new_bvar("b").true_lit();
+        let c = m.new_bvar("c").true_lit();
+        m.enforce(or([!a, !b, c]), []);
+        m.enforce(or([!a, !b, !c]), []);
+        let mut s = Solver::new(m);
+        s.brancher.import_vars(&s.model);
+        assert!(s.propagate_and_backtrack_to_consistent());
+
+        // deciding `a` then `b` leads to a conflict, whose learnt clause asserts `!b` at the first level
+        s.decide(a);
+        assert!(s.propagate_and_backtrack_to_consistent());
+        s.decide(b);
+        assert!(s.propagate_and_backtrack_to_consistent());
+        assert_eq!(s.current_decision_level(), DecLvl::ROOT + 1);
+        assert!(s.model.state.entails(!b));
+        assert_eq!(s.refutations, vec![(DecLvl::ROOT + 1, !b)]);
+
+        s.record_restart_nogoods();
+        s.reset();
+        assert!(s.refutations.is_empty());
+
+        // the refuted subtree is not explored again
+        s.decide(a);
+        assert!(s.propagate_and_backtrack_to_consistent());
+        assert!(s.model.state.entails(!b));
+    }
+
+    #[test]
+    fn test_collect_garbage() {
+        use crate::model::lang::expr::{leq, or};
+
+        let mut m = Model::new();
+        let a = m.new_bvar("a").true_lit();
+        let x = m.new_ivar(0, 10, "x");
+        let y = m.new_ivar(0, 10, "y");
+        let z = m.new_ivar(0, 10, "z");
+        m.enforce(leq(x, 3), []);
+        // chain of definitions, only referred to by the last one
+        let x_leq_y = m.reify(leq(x, y));
+        let x_leq_y_or_a = m.reify(or([x_leq_y, a]));
+        let y_leq_z = m.reify(leq(y, z));
+        let mut s = Solver::new(m);
+        s.set_assumptions([y_leq_z]);
+        assert!(s.solve().unwrap().is_some());
+
+        let num_constraints = s.model.shape.constraints.len();
+        let num_expressions = s.model.shape.expressions.len();
+        assert_eq!(s.collect_garbage([]), 2);
+        assert_eq!(s.model.shape.constraints.len(), num_constraints - 2);
+        assert_eq!(s.model.shape.expressions.len(), num_expressions - 4);
+        // nothing left to collect
+        assert_eq!(s.collect_garbage([]), 0);
+
+        // a collected expression is reified to a new literal, whose constraint is posted at the next solve
+        let x_geq_y = s.model.reify(leq(y, x));
+        assert_ne!(x_geq_y, !x_leq_y);
+        assert_ne!(x_geq_y.variable(), x_leq_y_or_a.variable());
+        s.set_assumptions([y_leq_z, x_geq_y]);
+        let sol = s.solve().unwrap().unwrap();
+        assert!(sol.ub(x.into()) <= 3);
+        assert!(sol.ub(y.into()) <= sol.lb(x.into()) && sol.ub(y.into()) <= sol.lb(z.into()));
+
+        // literals referred to by the caller are kept
+        s.set_assumptions([]);
+        assert_eq!(s.collect_garbage([y_leq_z, x_geq_y]), 0);
+        assert_eq!(s.collect_garbage([]), 2);
+    }
+
+    #[test]
+    fn test_root_entailments() {
+        use crate::model::lang::expr::{leq, or};
+
+        let mut m = Model::new();
+        let a = m.new_bvar("a").true_lit();
+        let b = m.new_bvar("b").true_lit();
+        let c = m.new_bvar("c").true_lit();
+        let x = m.new_ivar(0, 10, "x");
+        let y = m.new_ivar(0, 10, "y");
+        m.enforce(or([a]), []);
+        m.enforce(or([!a, b]), []);
+        m.enforce(leq(x + 5, y), []);
+        let mut s = Solver::new(m);
+
+        let entailments = s.root_entailments().unwrap();
+        let explanation = |lit: Lit| {
+            entailments
+                .iter()
+                .find(|(l, _)| *l == lit)
+                .map(|(_, explanation)| explanation.clone())
+        };
+        assert_eq!(explanation(b), Some(Some(vec![a])));
+        assert!(explanation(Lit::geq(y, 5)).is_some());
+        assert!(explanation(Lit::leq(x, 5)).is_some());
+        // unconstrained and initial bounds are not reported
+        assert_eq!(explanation(c), None);
+        assert_eq!(explanation(!c), None);
+        assert_eq!(explanation(Lit::leq(y, 10)), None);
+        // only the strongest literal on a bound is reported
+        assert_eq!(explanation(Lit::geq(y, 4)), None);
+
+        // facts set by the user are reported without explanation
+        assert!(s.add_root_facts([c]));
+        let entailments = s.root_entailments().unwrap();
+        assert!(entailments.contains(&(c, None)));
+
+        s.enforce(or([!b]), []);
+        assert_eq!(s.root_entailments(), None);
+    }
+
+    #[test]
+    fn decision_projection() {
+        use crate::backtrack::Backtrack;
+        use crate::model::lang::expr::{eq_max, or};
+        use crate::model::lang::IVar;
+
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 2, "x");
+        let y = model.new_ivar(0, 2, "y");
+        // auxiliary variable, whose value is determined by the decisions
+        let max = model.new_ivar(0, 2, "max");
+        model.enforce(eq_max(max, [x, y]), []);
+        model.tag_decision(x);
+        model.tag_decision(y);
+        assert!(model.shape.is_decision(x) && !model.shape.is_decision(max));
+
+        let mut solver = Solver::new(model);
+        let mut projections = Vec::new();
+        while let Some(sol) = solver.solve().unwrap() {
+            let projection = sol.project_to_decisions(&solver.model);
+            let labels: Vec<_> = projection.iter().map(|d| d.label.unwrap()).collect();
+            assert_eq!(labels, ["x", "y"]);
+            // exclude the current assignment of the decision variables
+            let clause: Vec<Lit> = projection
+                .iter()
+                .flat_map(|d| {
+                    let (var, value) = (IVar::new(d.var), d.value.unwrap());
+                    [var.lt(value), var.gt(value)]
+                })
+                .collect();
+            projections.push(projection);
+            solver.reset();
+            solver.enforce(or(clause), []);
+        }
+        assert_eq!(projections.len(), 9);
+    }
+
+    #[test]
+    fn projected_enumeration() {
+        use crate::model::lang::expr::{leq, neq};
+        use itertools::Itertools;
+
+        let mut model = Model::new();
+        let x = model.new_ivar(0, 3, "x");
+        let y = model.new_ivar(0, 3, "y");
+        let p = model.new_bvar("p").true_lit();
+        let z = model.new_optional_ivar(0, 2, p, "z");
+        // auxiliary variable, unconstrained
+        let _aux = model.new_ivar(0, 5, "aux");
+        model.enforce(neq(x, y), []);
+        model.enforce(leq(z, x), [p]);
+        for var in [x, y, z] {
+            model.tag_decision(var);
+        }
+
+        // expected projections: x != y, with z either absent or in [0, min(x, 2)]
+        let mut expected: Vec<Vec<Option<i32>>> = Vec::new();
+        for (x, y) in (0..=3).cartesian_product(0..=3).filter(|(x, y)| x != y) {
+            expected.push(vec![Some(x), Some(y), None]);
+            for z in 0..=x.min(2) {
+                expected.push(vec![Some(x), Some(y), Some(z)]);
+            }
+        }
+        expected.sort();
+
+        let mut solver = Solver::new(model);
+        // restrict the enumeration with an assumption, that should be restored afterwards
+        solver.set_assumptions([x.leq(2)]);
+        let mut projections = Vec::new();
+        let num_solutions = solver
+            .enumerate_projected(|projection, _| {
+                projections.push(projection.iter().map(|d| d.value).collect::<Vec<_>>())
+            })
+            .unwrap();
+        projections.sort();
+        let restricted: Vec<_> = expected.iter().filter(|sol| sol[0] <= Some(2)).cloned().collect();
+        assert_eq!(projections, restricted);
+        assert_eq!(num_solutions as usize, restricted.len());
+
+        solver.set_assumptions([]);
+        let mut projections = Vec::new();
+        solver
+            .enumerate_projected(|projection, _| {
+                projections.push(projection.iter().map(|d| d.value).collect::<Vec<_>>())
+            })
+            .unwrap();
+        projections.sort();
+        assert_eq!(projections, expected);
+    }
+
+    #[test]
+    fn deepest_partial_assignment() {
+        use crate::backtrack::DecLvl;
+        use crate::model::lang::expr::{and, or};
+        use itertools::Itertools;
+
+        let mut model = Model::new();
+        let lits: Vec<Lit> = (0..5).map(|_| model.new_bvar("l").true_lit()).collect();
+        for (&a, &b) in lits.iter().tuple_windows() {
+            model.enforce(or([a, b]), []);
+        }
+        let mut solver = Solver::new(model);
+        let sol = solver.solve().unwrap().unwrap();
+
+        // the deepest node of a satisfiable search is the solution
+        assert!(solver.deepest_decision_level() > DecLvl::ROOT);
+        let partial = solver.deepest_partial_assignment().unwrap();
+        for &l in &lits {
+            assert_eq!(partial.value(l), sol.value(l));
+        }
+
+        // no partial assignment once the problem is proved inconsistent
+        solver.enforce(and(lits.iter().map(|&l| !l).collect::<Vec<_>>()), []);
+        assert!(solver.solve().unwrap().is_none());
+        assert!(solver.deepest_partial_assignment().is_none());
+    }
+
+    #[test]
+    fn objective_shaving() {
+        use crate::model::extensions::AssignmentExt;
+        use crate::model::lang::expr::{leq, or};
+
+        let mut model = Model::new();
+        let a = model.new_ivar(0, 100, "a");
+        let b = model.new_ivar(0, 100, "b");
+        let makespan = model.new_ivar(0, 100, "makespan");
+        // two tasks of duration 5 that cannot overlap
+        let a_first = model.reify(leq(a + 5, b));
+        let b_first = model.reify(leq(b + 5, a));
+        model.enforce(or([a_first, b_first]), []);
+        model.enforce(leq(a + 5, makespan), []);
+        model.enforce(leq(b + 5, makespan), []);
+
+        let mut solver = Solver::new(model);
+        assert!(solver.propagate_and_backtrack_to_consistent());
+        assert_eq!(solver.model.domain_of(makespan), (5, 100));
+        // any makespan below 10 is refuted by propagation alone
+        assert!(solver.shave_objective(makespan, true));
+        assert_eq!(solver.model.domain_of(makespan), (10, 100));
+        assert_eq!(solver.stats.objective_bound(), Some(10));
+
+        let (cost, _) = solver.minimize(makespan).unwrap().unwrap();
+        assert_eq!(cost, 10);
+    }
+
+    #[test]
+    fn minimize_within_gap() {
+        use crate::model::extensions::AssignmentExt;
+        use crate::model::lang::expr::{geq, leq, or};
+        use crate::solver::OptimalityGap;
+
+        // scheduling of jobs on a single machine, minimizing the makespan
+        let durations = [3, 5, 2, 4, 6];
+        let mut model = Model::new();
+        let starts = durations.map(|_| model.new_ivar(0, 100, ""));
+        let makespan = model.new_ivar(0, 100, "makespan");
+        for (i, &s) in starts.iter().enumerate() {
+            model.enforce(leq(s + durations[i], makespan), []);
+            for j in (i + 1)..starts.len() {
+                let (t, d) = (starts[j], durations[j]);
+                let before = model.reify(leq(s + durations[i], t));
+                let after = model.reify(leq(t + d, s));
+                model.enforce(or([before, after]), []);
+            }
+        }
+        model.enforce(geq(starts[2], 7), []);
+        let optimum = 20;
+
+        for gap in [OptimalityGap::Absolute(5), OptimalityGap::Relative(0.1)] {
+            let mut solver = Solver::new(model.clone());
+            let sol = solver.minimize_within_gap(makespan, gap).unwrap().unwrap();
+            assert_eq!(sol.assignment.var_domain(makespan).lb, sol.cost);
+            // the bound is proven and the certified gap respects the allowed one
+            assert!(sol.bound <= optimum && optimum <= sol.cost, "{:?}", gap);
+            match gap {
+                OptimalityGap::Absolute(allowed) => assert!(sol.gap() <= allowed),
+                OptimalityGap::Relative(allowed) => assert!(sol.gap() as f64 <= allowed * sol.cost as f64),
+            }
+            assert_eq!(solver.stats.objective_bound(), Some(sol.bound));
+        }
+
+        // a null gap proves optimality
+        let mut solver = Solver::new(model);
+        let sol = solver
+            .minimize_within_gap(makespan, OptimalityGap::Absolute(0))
+            .unwrap()
+            .unwrap();
+        assert_eq!((sol.cost, sol.gap()), (optimum, 0));
+    }
 }
@@ -27,6 +27,7 @@ pub struct Stats {
     pub per_module_stat: BTreeMap<ReasonerId, ModuleStat>,
     running: RunningStats,
     best_cost: Option<IntCst>,
+    objective_bound: Option<IntCst>,
 }
 
 #[derive(Clone, Default)]
@@ -56,6 +57,7 @@ impl Stats {
             per_module_stat: per_mod,
             running: Default::default(),
             best_cost: None,
+            objective_bound: None,
         }
     }
 
@@ -103,6 +105,32 @@ impl Stats {
     pub fn num_conflicts(&self) -> u64 {
         self.num_conflicts
     }
+
+    pub fn num_decisions(&self) -> u64 {
+        self.num_decisions
+    }
+
+    pub fn num_restarts(&self) -> u64 {
+        self.num_restarts
+    }
+
+    pub fn num_solutions(&self) -> u64 {
+        self.num_solutions
+    }
+
+    /// Objective value of the last solution found, if any.
+    pub fn best_cost(&self) -> Option<IntCst> {
+        self.best_cost
+    }
+
+    pub fn set_objective_bound(&mut self, bound: IntCst) {
+        self.objective_bound = Some(bound);
+    }
+
+    /// Best bound proved on the objective (a lower bound when minimizing), if any.
+    pub fn objective_bound(&self) -> Option<IntCst> {
+        self.objective_bound
+    }
 }
 
 impl Default for Stats {
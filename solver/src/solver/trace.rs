@@ -0,0 +1,270 @@
+//! Export of the search tree explored by the solver, for offline visualization and profiling.
+//!
+//! The trace is written in the JSON lines format: each line is a JSON object describing a node of the search tree.
+//! Nodes are identified by an integer `id` and attached to the node of their `parent` (the root node has id `0`).
+//! Each node has a `kind`, one of:
+//!
+//! - `"decision"`: a branching decision, whose `label` is the literal that was set by the solver.
+//! - `"conflict"`: a failed node, with the size of the learnt clause (`clause_size`) and the decision level
+//!   the solver backjumped to (`backjump`, `null` if the conflict occurred at the root and proved unsatisfiability).
+//! - `"solution"`: a leaf on which a solution was found.
+//! - `"restart"`: the solver abandoned the current branch and went back to the root.
+//! - `"exhausted"`: the search space was exhausted (written at the very end of a complete search).
+//!
+//! Each node also records the decision level at which it was created (`depth`, which is also the level of its parent)
+//! and the number of `propagations` (inferences) that were made in its parent node since the parent's creation,
+//! which allows identifying where the effort of the search is spent.
+
+use crate::backtrack::DecLvl;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Identifier of the root of the search tree.
+const ROOT: u64 = 0;
+
+/// A node on the current branch of the search tree.
+#[derive(Copy, Clone)]
+struct Node {
+    id: u64,
+    /// Number of events in the solver's trail when the node was created.
+    events_at_creation: u32,
+}
+
+/// Writer of a search trace, to be attached to a solver with [`Solver::set_search_trace`](crate::solver::Solver::set_search_trace).
+pub struct SearchTrace {
+    /// Output of the trace, or `None` if tracing was disabled after a failure to write it.
+    out: Option<Box<dyn Write + Send>>,
+    next_id: u64,
+    /// Nodes of the current branch, indexed by decision level
+    branch: Vec<Node>,
+}
+
+impl SearchTrace {
+    /// Creates a new trace that will be written to the given output.
+    pub fn new(out: impl Write + Send + 'static) -> Self {
+        SearchTrace {
+            out: Some(Box::new(out)),
+            next_id: ROOT + 1,
+            branch: vec![Node {
+                id: ROOT,
+                events_at_creation: 0,
+            }],
+        }
+    }
+
+    /// Creates a new trace that will be written to the file at `path`, overwriting it if it exists.
+    pub fn to_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Returns the current node at decision level `depth`, dropping any deeper node
+    /// that was left by backtracking.
+    fn current(&mut self, depth: DecLvl) -> Node {
+        self.branch.truncate(depth.to_int() as usize + 1);
+        *self.branch.last().unwrap()
+    }
+
+    /// Writes a new node as a child of the current one, returning its id.
+    fn write_node(&mut self, depth: DecLvl, num_events: u32, kind: &str, details: &str) -> u64 {
+        let parent = self.current(depth);
+        let id = self.next_id;
+        self.next_id += 1;
+        if let Some(out) = &mut self.out {
+            let res = writeln!(
+                out,
+                r#"{{"id":{},"parent":{},"kind":"{}","depth":{},"propagations":{}{}}}"#,
+                id,
+                parent.id,
+                kind,
+                depth.to_int(),
+                num_events.saturating_sub(parent.events_at_creation),
+                details
+            );
+            if let Err(e) = res {
+                self.disable(e);
+            }
+        }
+        id
+    }
+
+    /// Reports a failure to write the trace and stops tracing, the search itself being unaffected.
+    fn disable(&mut self, error: std::io::Error) {
+        eprintln!("Failed to write search trace, tracing is disabled: {error}");
+        self.out = None;
+    }
+
+    /// Records a decision made at `depth` (the decision level before the decision was taken),
+    /// when the solver's trail contained `num_events` events.
+    pub fn decision(&mut self, label: &str, depth: DecLvl, num_events: u32) {
+        let details = format!(r#","label":"{}""#, escape(label));
+        let id = self.write_node(depth, num_events, "decision", &details);
+        self.branch.push(Node {
+            id,
+            // the decision itself is not counted as a propagation
+            events_at_creation: num_events + 1,
+        });
+    }
+
+    /// Records a conflict detected at `depth`, leading to a clause of `clause_size` literals
+    /// and a backjump to the given level (or `None` if the conflict proves unsatisfiability).
+    pub fn conflict(&mut self, depth: DecLvl, num_events: u32, clause_size: usize, backjump: Option<DecLvl>) {
+        let backjump = backjump.map_or("null".to_string(), |lvl| lvl.to_int().to_string());
+        let details = format!(r#","clause_size":{clause_size},"backjump":{backjump}"#);
+        self.write_node(depth, num_events, "conflict", &details);
+    }
+
+    /// Records that a solution was found at `depth`.
+    pub fn solution(&mut self, depth: DecLvl, num_events: u32) {
+        self.write_node(depth, num_events, "solution", "");
+        self.flush();
+    }
+
+    /// Records a restart triggered at `depth`.
+    pub fn restart(&mut self, depth: DecLvl, num_events: u32) {
+        self.write_node(depth, num_events, "restart", "");
+    }
+
+    /// Records that the search space was exhausted.
+    pub fn exhausted(&mut self, num_events: u32) {
+        self.write_node(DecLvl::ROOT, num_events, "exhausted", "");
+        self.flush();
+    }
+
+    pub fn flush(&mut self) {
+        if let Some(Err(e)) = self.out.as_mut().map(|out| out.flush()) {
+            self.disable(e);
+        }
+    }
+}
+
+/// Escapes a string so that it can be placed in a JSON string literal.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtrack::Backtrack;
+    use crate::core::Lit;
+    use crate::model::extensions::AssignmentExt;
+    use crate::model::lang::expr::{neq, or};
+    use crate::model::lang::IVar;
+
+    type Model = crate::model::Model<String>;
+    type Solver = crate::solver::Solver<String>;
+
+    /// A solver for the problem of assigning distinct values in `[0, 3]` to four variables, with 24 solutions.
+    fn all_different() -> (Solver, Vec<IVar>) {
+        let mut model = Model::new();
+        let vars: Vec<IVar> = (0..4).map(|i| model.new_ivar(0, 3, format!("x{i}"))).collect();
+        for (i, &a) in vars.iter().enumerate() {
+            for &b in &vars[i + 1..] {
+                model.enforce(neq(a, b), []);
+            }
+        }
+        (Solver::new(model), vars)
+    }
+
+    /// Enumerates all solutions of the solver, excluding each of them once found, and returns their number.
+    fn count_solutions(solver: &mut Solver, vars: &[IVar]) -> usize {
+        let mut count = 0;
+        while let Some(sol) = solver.solve().unwrap() {
+            let clause: Vec<Lit> = vars
+                .iter()
+                .flat_map(|&v| {
+                    let value = sol.lower_bound(v);
+                    [v.lt(value), v.gt(value)]
+                })
+                .collect();
+            count += 1;
+            solver.reset();
+            solver.enforce(or(clause), []);
+        }
+        count
+    }
+
+    #[test]
+    fn search_trace() {
+        use std::sync::{Arc, Mutex};
+
+        /// A writer to a buffer that remains accessible after being handed to the solver
+        #[derive(Clone, Default)]
+        struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+        impl Write for SharedBuffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (mut solver, vars) = all_different();
+        let buffer = SharedBuffer::default();
+        solver.set_search_trace(SearchTrace::new(buffer.clone()));
+        assert_eq!(count_solutions(&mut solver, &vars), 24);
+
+        let trace = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = trace.lines().collect();
+        let count = |kind: &str| {
+            let pattern = format!(r#""kind":"{kind}""#);
+            lines.iter().filter(|l| l.contains(&pattern)).count()
+        };
+        assert_eq!(count("solution"), 24);
+        assert!(count("decision") > 0);
+        assert!(count("conflict") > 0);
+        assert_eq!(count("exhausted"), 1);
+        assert!(lines.last().unwrap().contains(r#""kind":"exhausted""#));
+        // each node is attached to a previously declared one
+        for (i, line) in lines.iter().enumerate() {
+            assert!(line.starts_with(&format!(r#"{{"id":{},"parent":"#, i + 1)));
+            let parent: usize = line
+                .split(r#""parent":"#)
+                .nth(1)
+                .unwrap()
+                .split(',')
+                .next()
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert!(parent <= i);
+        }
+    }
+
+    #[test]
+    fn search_trace_write_failure() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// A writer that always fails, counting the number of attempts to write to it
+        #[derive(Clone, Default)]
+        struct FailingWriter(Arc<AtomicUsize>);
+        impl Write for FailingWriter {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"))
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (mut solver, vars) = all_different();
+        let writer = FailingWriter::default();
+        solver.set_search_trace(SearchTrace::new(writer.clone()));
+        // the search is unaffected by the failure, and tracing stops after the first one
+        assert_eq!(count_solutions(&mut solver, &vars), 24);
+        assert_eq!(writer.0.load(Ordering::SeqCst), 1);
+    }
+}
@@ -158,6 +158,7 @@ impl Loc {
             context: vec![],
             inline_err: Some(error.into()),
             loc: Some(self),
+            others: vec![],
         }
     }
 
@@ -172,9 +173,29 @@ impl Loc {
         self.span
     }
 
+    /// Returns the smallest location covering both `self` and `other`, that must be in the same input.
+    pub fn join(self, other: &Loc) -> Loc {
+        debug_assert!(Arc::ptr_eq(&self.source, &other.source));
+        Loc {
+            source: self.source,
+            span: Span::new(self.span.start.min(other.span.start), self.span.end.max(other.span.end)),
+        }
+    }
+
     pub fn underlined(&self) -> impl Display + '_ {
         self.source.underlined(self.span)
     }
+
+    /// Returns a short description of the start of the location, of the form `file:line:column`
+    /// (the file being omitted when the input was not read from a file). Lines and columns are 1-based.
+    pub fn position(&self) -> String {
+        let line = self.span.start.line + 1;
+        let column = self.span.start.column + 1;
+        match &self.source.source {
+            Some(path) => format!("{path}:{line}:{column}"),
+            None => format!("{line}:{column}"),
+        }
+    }
 }
 
 impl std::fmt::Debug for Loc {
@@ -187,9 +208,30 @@ pub struct ErrLoc {
     context: Vec<String>,
     inline_err: Option<String>,
     loc: Option<Loc>,
+    /// Additional errors reported together with this one, when processing continued after the first error.
+    others: Vec<ErrLoc>,
 }
 
 impl ErrLoc {
+    /// Combines several errors into a single one that reports all of them, in order.
+    /// Returns `None` if there is no error to report.
+    pub fn merge(errors: impl IntoIterator<Item = ErrLoc>) -> Option<ErrLoc> {
+        let mut errors = errors.into_iter();
+        let mut first = errors.next()?;
+        first.others.extend(errors);
+        Some(first)
+    }
+
+    /// Location of the (first) error, if known.
+    pub fn loc(&self) -> Option<&Loc> {
+        self.loc.as_ref()
+    }
+
+    /// Total number of errors reported by this error.
+    pub fn num_errors(&self) -> usize {
+        1 + self.others.iter().map(|e| e.num_errors()).sum::<usize>()
+    }
+
     pub fn with_error(mut self, inline_message: impl Into<String>) -> ErrLoc {
         self.inline_err = Some(inline_message.into());
         self
@@ -205,6 +247,7 @@ impl From<String> for ErrLoc {
             context: vec![],
             inline_err: Some(e),
             loc: None,
+            others: vec![],
         }
     }
 }
@@ -226,6 +269,9 @@ impl std::fmt::Display for ErrLoc {
         if let Some(err) = &self.inline_err {
             write!(f, " {err}")?;
         }
+        for other in &self.others {
+            write!(f, "\n\n{other}")?;
+        }
         Ok(())
     }
 }
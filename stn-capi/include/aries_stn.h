@@ -0,0 +1,112 @@
+/* C API for the incremental Simple Temporal Network of aries.
+ *
+ * An edge `source -- weight --> target` encodes the constraint `target - source <= weight`.
+ * Reified edges are only taken into account once their literal is activated with `aries_stn_activate`.
+ * All functions taking a network expect a valid pointer returned by `aries_stn_new` and return a status code. */
+
+#ifndef ARIES_STN_H
+#define ARIES_STN_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque handle to a temporal network. */
+typedef struct AriesStn AriesStn;
+
+/* Identifier of a timepoint in a network. */
+typedef uint32_t AriesTimepoint;
+
+/* Relation of a literal: `var <= value` */
+#define ARIES_LEQ 0
+/* Relation of a literal: `var > value` */
+#define ARIES_GT 1
+
+/* A literal `var <= value` or `var > value`, depending on its relation.
+ * The literal of a reified edge holds iff the edge is active. */
+typedef struct AriesLit {
+    uint32_t var;
+    uint8_t relation;
+    int32_t value;
+} AriesLit;
+
+/* Status code returned by the functions taking a network, whose results are written through output pointers. */
+typedef int32_t AriesStatus;
+
+/* The operation succeeded. */
+#define ARIES_STN_OK 0
+/* The operation made the network inconsistent (e.g. empty domain of a timepoint or negative cycle). */
+#define ARIES_STN_INCONSISTENT 1
+/* The literal to explain does not hold or was set directly, and thus has no explanation. */
+#define ARIES_STN_NO_EXPLANATION 2
+/* The API was misused (null pointer, unknown timepoint or literal with an unknown relation).
+ * The network must not be used anymore, except to be released.
+ * When the library is built with `panic = "abort"` (release profile), some misuses abort the process instead. */
+#define ARIES_STN_ERROR (-1)
+
+/* Creates a new empty network, to be released with `aries_stn_free` (NULL on failure). */
+AriesStn *aries_stn_new(void);
+
+/* Releases a network created with `aries_stn_new` (no-op on NULL). */
+void aries_stn_free(AriesStn *stn);
+
+/* Adds a new timepoint whose value must be in `[lb, ub]`, writing its identifier in `tp`. */
+AriesStatus aries_stn_add_timepoint(AriesStn *stn, int32_t lb, int32_t ub, AriesTimepoint *tp);
+
+/* Sets a bound of the timepoint, returning ARIES_STN_INCONSISTENT if this results in an empty domain. */
+AriesStatus aries_stn_set_lb(AriesStn *stn, AriesTimepoint tp, int32_t lb);
+AriesStatus aries_stn_set_ub(AriesStn *stn, AriesTimepoint tp, int32_t ub);
+
+/* Writes the current bounds of the timepoint, as of the last propagation. */
+AriesStatus aries_stn_lb(const AriesStn *stn, AriesTimepoint tp, int32_t *lb);
+AriesStatus aries_stn_ub(const AriesStn *stn, AriesTimepoint tp, int32_t *ub);
+
+/* Adds the edge `target - source <= weight`, that is always active. */
+AriesStatus aries_stn_add_edge(AriesStn *stn, AriesTimepoint source, AriesTimepoint target, int32_t weight);
+
+/* Adds the edge `target - source <= weight` and writes its literal in `lit`.
+ * The edge is only taken into account once its literal is activated. */
+AriesStatus aries_stn_add_reified_edge(AriesStn *stn, AriesTimepoint source, AriesTimepoint target, int32_t weight,
+                                       AriesLit *lit);
+
+/* Makes the literal true, returning ARIES_STN_INCONSISTENT if it is already known to be false. */
+AriesStatus aries_stn_activate(AriesStn *stn, AriesLit lit);
+
+/* Writes in `entailed` whether the literal holds in the current state of the network. */
+AriesStatus aries_stn_entails(const AriesStn *stn, AriesLit lit, bool *entailed);
+
+/* Propagates all active edges, returning ARIES_STN_INCONSISTENT if the network is inconsistent. */
+AriesStatus aries_stn_propagate(AriesStn *stn);
+
+/* Saves the current state of the network, that can be restored with `aries_stn_backtrack`.
+ * All changes made to the network must have been propagated with `aries_stn_propagate`. */
+AriesStatus aries_stn_set_backtrack_point(AriesStn *stn);
+
+/* Restores the state of the network to the last backtrack point. */
+AriesStatus aries_stn_backtrack(AriesStn *stn);
+
+/* Restores the state of the network before the first backtrack point. */
+AriesStatus aries_stn_reset(AriesStn *stn);
+
+/* Flexibility metrics of the network, as of the last propagation:
+ *  - naive flexibility: sum of the widths `ub - lb` of the domains of all timepoints,
+ *  - root mean square of the widths of the domains of all timepoints,
+ *  - sum of the slacks of all active edges when each timepoint takes its lower bound. */
+AriesStatus aries_stn_naive_flexibility(const AriesStn *stn, int64_t *flexibility);
+AriesStatus aries_stn_rms_flexibility(const AriesStn *stn, double *flexibility);
+AriesStatus aries_stn_sum_of_slacks(const AriesStn *stn, int64_t *slacks);
+
+/* Writes in `out` a set of literals that imply `lit` in the current state, and in `len` the number of literals
+ * in the explanation, of which only the first `capacity` are written.
+ * Returns ARIES_STN_NO_EXPLANATION if the literal does not hold or was set directly. */
+AriesStatus aries_stn_explain(AriesStn *stn, AriesLit lit, AriesLit *out, size_t capacity, size_t *len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ARIES_STN_H */
@@ -0,0 +1,451 @@
+//! C API for the incremental Simple Temporal Network of aries, declared in `include/aries_stn.h`.
+//!
+//! The network is manipulated through an opaque pointer created by [aries_stn_new] and released with [aries_stn_free].
+//! An edge `source -- weight --> target` encodes the constraint `target - source <= weight`.
+//! Reified edges are only taken into account once their literal is activated, which allows a client
+//! (e.g. a plan executive) to incrementally test the consistency of candidate constraints and retract them
+//! by backtracking.
+//!
+//! All functions taking a network return a status code ([AriesStatus]), their results being written through
+//! output pointers. Panics are not propagated through the API: misusing it (e.g. passing a timepoint that does not
+//! belong to the network, a null pointer or a literal with an unknown relation) results in [ARIES_STN_ERROR],
+//! after which the network must not be used anymore, except to be released.
+//! When the library is built with `panic = "abort"` (as in the `release` profile), a misuse detected by a panic
+//! aborts the process instead.
+
+use aries::core::state::Cause;
+use aries::core::{IntCst, Lit, Relation, VarRef};
+use aries::reasoners::stn::Stn;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Opaque handle to a temporal network.
+pub struct AriesStn(Stn);
+
+/// Identifier of a timepoint in a network.
+pub type AriesTimepoint = u32;
+
+/// Status code returned by the functions of the API.
+pub type AriesStatus = i32;
+
+/// The operation succeeded.
+pub const ARIES_STN_OK: AriesStatus = 0;
+/// The operation made the network inconsistent (e.g. empty domain of a timepoint or negative cycle).
+pub const ARIES_STN_INCONSISTENT: AriesStatus = 1;
+/// The literal to explain does not hold or was set directly, and thus has no explanation.
+pub const ARIES_STN_NO_EXPLANATION: AriesStatus = 2;
+/// The API was misused (invalid pointer, timepoint or literal), the network must not be used anymore.
+pub const ARIES_STN_ERROR: AriesStatus = -1;
+
+/// Relation of a literal: `var <= value`.
+pub const ARIES_LEQ: u8 = 0;
+/// Relation of a literal: `var > value`.
+pub const ARIES_GT: u8 = 1;
+
+/// A literal `var <= value` or `var > value`, depending on its relation.
+/// The literal of a reified edge holds iff the edge is active.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AriesLit {
+    pub var: u32,
+    pub relation: u8,
+    pub value: IntCst,
+}
+
+impl From<Lit> for AriesLit {
+    fn from(lit: Lit) -> Self {
+        let (var, relation, value) = lit.unpack();
+        let relation = match relation {
+            Relation::Leq => ARIES_LEQ,
+            Relation::Gt => ARIES_GT,
+        };
+        AriesLit {
+            var: var.to_u32(),
+            relation,
+            value,
+        }
+    }
+}
+
+impl TryFrom<AriesLit> for Lit {
+    type Error = AriesStatus;
+
+    fn try_from(lit: AriesLit) -> Result<Self, Self::Error> {
+        let relation = match lit.relation {
+            ARIES_LEQ => Relation::Leq,
+            ARIES_GT => Relation::Gt,
+            _ => return Err(ARIES_STN_ERROR),
+        };
+        Ok(Lit::new(VarRef::from_u32(lit.var), relation, lit.value))
+    }
+}
+
+fn timepoint(tp: AriesTimepoint) -> VarRef {
+    VarRef::from_u32(tp)
+}
+
+/// Status of an operation that fails iff the network becomes inconsistent.
+fn consistency<T, E>(res: Result<T, E>) -> AriesStatus {
+    match res {
+        Ok(_) => ARIES_STN_OK,
+        Err(_) => ARIES_STN_INCONSISTENT,
+    }
+}
+
+/// Runs `f` on the network, returning [ARIES_STN_ERROR] if the network is null or if `f` panics.
+///
+/// # Safety
+/// `stn` must be null or a valid pointer returned by `aries_stn_new`.
+unsafe fn with_stn(stn: *mut AriesStn, f: impl FnOnce(&mut Stn) -> Result<AriesStatus, AriesStatus>) -> AriesStatus {
+    if stn.is_null() {
+        return ARIES_STN_ERROR;
+    }
+    let stn = &mut (*stn).0;
+    match catch_unwind(AssertUnwindSafe(|| f(stn))) {
+        Ok(Ok(status)) | Ok(Err(status)) => status,
+        Err(_) => ARIES_STN_ERROR,
+    }
+}
+
+/// Writes `value` to `out`, returning [ARIES_STN_ERROR] if `out` is null.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+unsafe fn write<T>(out: *mut T, value: T) -> AriesStatus {
+    if out.is_null() {
+        ARIES_STN_ERROR
+    } else {
+        *out = value;
+        ARIES_STN_OK
+    }
+}
+
+/// Creates a new empty network, to be released with `aries_stn_free`.
+#[no_mangle]
+pub extern "C" fn aries_stn_new() -> *mut AriesStn {
+    catch_unwind(|| Box::into_raw(Box::new(AriesStn(Stn::new())))).unwrap_or(std::ptr::null_mut())
+}
+
+/// Releases a network created with `aries_stn_new`.
+///
+/// # Safety
+/// `stn` must be null or a pointer returned by `aries_stn_new` that was not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_free(stn: *mut AriesStn) {
+    if !stn.is_null() {
+        // a panic while dropping the network would only leak it
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(stn))));
+    }
+}
+
+/// Adds a new timepoint whose value must be in `[lb, ub]`, writing its identifier in `tp`.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `tp` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_add_timepoint(
+    stn: *mut AriesStn,
+    lb: IntCst,
+    ub: IntCst,
+    tp: *mut AriesTimepoint,
+) -> AriesStatus {
+    with_stn(stn, |stn| Ok(write(tp, stn.add_timepoint(lb, ub).to_u32())))
+}
+
+/// Sets the lower bound of the timepoint, returning `ARIES_STN_INCONSISTENT` if this results in an empty domain.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_set_lb(stn: *mut AriesStn, tp: AriesTimepoint, lb: IntCst) -> AriesStatus {
+    with_stn(stn, |stn| {
+        Ok(consistency(stn.model.state.set_lb(timepoint(tp), lb, Cause::Decision)))
+    })
+}
+
+/// Sets the upper bound of the timepoint, returning `ARIES_STN_INCONSISTENT` if this results in an empty domain.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_set_ub(stn: *mut AriesStn, tp: AriesTimepoint, ub: IntCst) -> AriesStatus {
+    with_stn(stn, |stn| {
+        Ok(consistency(stn.model.state.set_ub(timepoint(tp), ub, Cause::Decision)))
+    })
+}
+
+/// Writes in `lb` the current lower bound of the timepoint, as of the last propagation.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `lb` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_lb(stn: *const AriesStn, tp: AriesTimepoint, lb: *mut IntCst) -> AriesStatus {
+    with_stn(stn as *mut _, |stn| Ok(write(lb, stn.model.state.lb(timepoint(tp)))))
+}
+
+/// Writes in `ub` the current upper bound of the timepoint, as of the last propagation.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `ub` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_ub(stn: *const AriesStn, tp: AriesTimepoint, ub: *mut IntCst) -> AriesStatus {
+    with_stn(stn as *mut _, |stn| Ok(write(ub, stn.model.state.ub(timepoint(tp)))))
+}
+
+/// Adds the edge `target - source <= weight`, that is always active.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_add_edge(
+    stn: *mut AriesStn,
+    source: AriesTimepoint,
+    target: AriesTimepoint,
+    weight: IntCst,
+) -> AriesStatus {
+    with_stn(stn, |stn| {
+        stn.add_edge(timepoint(source), timepoint(target), weight);
+        Ok(ARIES_STN_OK)
+    })
+}
+
+/// Adds the edge `target - source <= weight` and writes its literal in `lit`.
+/// The edge is only taken into account once its literal is activated with `aries_stn_activate`.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `lit` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_add_reified_edge(
+    stn: *mut AriesStn,
+    source: AriesTimepoint,
+    target: AriesTimepoint,
+    weight: IntCst,
+    lit: *mut AriesLit,
+) -> AriesStatus {
+    with_stn(stn, |stn| {
+        let edge = stn.add_inactive_edge(timepoint(source), timepoint(target), weight);
+        Ok(write(lit, edge.into()))
+    })
+}
+
+/// Makes the literal true, which activates the edge of a reified edge.
+/// Returns `ARIES_STN_INCONSISTENT` if the literal is already known to be false.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_activate(stn: *mut AriesStn, lit: AriesLit) -> AriesStatus {
+    with_stn(stn, |stn| Ok(consistency(stn.model.state.decide(lit.try_into()?))))
+}
+
+/// Writes in `entailed` whether the literal holds in the current state of the network.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `entailed` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_entails(stn: *const AriesStn, lit: AriesLit, entailed: *mut bool) -> AriesStatus {
+    with_stn(stn as *mut _, |stn| {
+        Ok(write(entailed, stn.model.state.entails(lit.try_into()?)))
+    })
+}
+
+/// Propagates all active edges, updating the bounds of the timepoints.
+/// Returns `ARIES_STN_INCONSISTENT` if the network is inconsistent.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_propagate(stn: *mut AriesStn) -> AriesStatus {
+    with_stn(stn, |stn| Ok(consistency(stn.propagate_all())))
+}
+
+/// Saves the current state of the network, that can be restored with `aries_stn_backtrack`.
+/// All changes made to the network must have been propagated with `aries_stn_propagate`.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_set_backtrack_point(stn: *mut AriesStn) -> AriesStatus {
+    with_stn(stn, |stn| {
+        stn.set_backtrack_point();
+        Ok(ARIES_STN_OK)
+    })
+}
+
+/// Restores the state of the network to the last backtrack point.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_backtrack(stn: *mut AriesStn) -> AriesStatus {
+    with_stn(stn, |stn| {
+        stn.undo_to_last_backtrack_point();
+        Ok(ARIES_STN_OK)
+    })
+}
+
+/// Restores the state of the network before the first backtrack point.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_reset(stn: *mut AriesStn) -> AriesStatus {
+    with_stn(stn, |stn| {
+        stn.reset_to_root();
+        Ok(ARIES_STN_OK)
+    })
+}
+
+/// Writes in `flexibility` the naive flexibility of the network: the sum of the widths `ub - lb` of the domains
+/// of all timepoints, as of the last propagation.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `flexibility` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_naive_flexibility(stn: *const AriesStn, flexibility: *mut i64) -> AriesStatus {
+    with_stn(stn as *mut _, |stn| Ok(write(flexibility, stn.naive_flexibility())))
+}
+
+/// Writes in `flexibility` the root mean square of the widths `ub - lb` of the domains of all timepoints,
+/// as of the last propagation.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `flexibility` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_rms_flexibility(stn: *const AriesStn, flexibility: *mut f64) -> AriesStatus {
+    with_stn(stn as *mut _, |stn| Ok(write(flexibility, stn.rms_flexibility())))
+}
+
+/// Writes in `slacks` the sum of the slacks of all active edges when each timepoint takes its lower bound.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new` and `slacks` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_sum_of_slacks(stn: *const AriesStn, slacks: *mut i64) -> AriesStatus {
+    with_stn(stn as *mut _, |stn| Ok(write(slacks, stn.sum_of_slacks())))
+}
+
+/// Explains why a literal holds (e.g. a bound of a timepoint), by writing in `out` a set of literals that
+/// imply it in the current state (bounds of timepoints and literals of active reified edges).
+///
+/// Writes in `len` the number of literals in the explanation, of which only the first `capacity` are written.
+/// Returns `ARIES_STN_NO_EXPLANATION` if the literal does not hold or if it was set directly.
+///
+/// # Safety
+/// `stn` must be a valid pointer returned by `aries_stn_new`, `out` must point to an array of
+/// at least `capacity` literals and `len` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn aries_stn_explain(
+    stn: *mut AriesStn,
+    lit: AriesLit,
+    out: *mut AriesLit,
+    capacity: usize,
+    len: *mut usize,
+) -> AriesStatus {
+    with_stn(stn, |stn| {
+        let lit: Lit = lit.try_into()?;
+        if !stn.model.state.entails(lit) {
+            return Ok(ARIES_STN_NO_EXPLANATION);
+        }
+        match stn.implying_literals(lit) {
+            Some(mut explanation) => {
+                explanation.retain(|&l| l != Lit::TRUE);
+                if capacity > 0 && out.is_null() {
+                    return Err(ARIES_STN_ERROR);
+                }
+                for (i, &l) in explanation.iter().take(capacity).enumerate() {
+                    *out.add(i) = l.into();
+                }
+                Ok(write(len, explanation.len()))
+            }
+            None => Ok(ARIES_STN_NO_EXPLANATION),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capi() {
+        unsafe {
+            let stn = aries_stn_new();
+            let (mut a, mut b) = (0, 0);
+            assert_eq!(aries_stn_add_timepoint(stn, 0, 10, &mut a), ARIES_STN_OK);
+            assert_eq!(aries_stn_add_timepoint(stn, 0, 10, &mut b), ARIES_STN_OK);
+            let bounds = |tp: AriesTimepoint| {
+                let (mut lb, mut ub) = (0, 0);
+                assert_eq!(aries_stn_lb(stn, tp, &mut lb), ARIES_STN_OK);
+                assert_eq!(aries_stn_ub(stn, tp, &mut ub), ARIES_STN_OK);
+                (lb, ub)
+            };
+            // b >= a + 3
+            assert_eq!(aries_stn_add_edge(stn, b, a, -3), ARIES_STN_OK);
+            assert_eq!(aries_stn_propagate(stn), ARIES_STN_OK);
+            assert_eq!(bounds(b).0, 3);
+            assert_eq!(bounds(a).1, 7);
+            let mut flexibility = 0;
+            assert_eq!(aries_stn_naive_flexibility(stn, &mut flexibility), ARIES_STN_OK);
+            assert_eq!(flexibility, 14);
+            let mut slacks = -1;
+            assert_eq!(aries_stn_sum_of_slacks(stn, &mut slacks), ARIES_STN_OK);
+            assert_eq!(slacks, 0);
+
+            // b <= a + 2, inconsistent once active
+            let mut edge = AriesLit::from(Lit::TRUE);
+            assert_eq!(aries_stn_add_reified_edge(stn, a, b, 2, &mut edge), ARIES_STN_OK);
+            let mut entailed = true;
+            assert_eq!(aries_stn_entails(stn, edge, &mut entailed), ARIES_STN_OK);
+            assert!(!entailed);
+            assert_eq!(aries_stn_set_backtrack_point(stn), ARIES_STN_OK);
+            assert_eq!(aries_stn_activate(stn, edge), ARIES_STN_OK);
+            assert_eq!(aries_stn_propagate(stn), ARIES_STN_INCONSISTENT);
+            assert_eq!(aries_stn_backtrack(stn), ARIES_STN_OK);
+            assert_eq!(aries_stn_propagate(stn), ARIES_STN_OK);
+
+            // a >= 5 implies b >= 8
+            assert_eq!(aries_stn_set_backtrack_point(stn), ARIES_STN_OK);
+            assert_eq!(aries_stn_set_lb(stn, a, 5), ARIES_STN_OK);
+            assert_eq!(aries_stn_propagate(stn), ARIES_STN_OK);
+            assert_eq!(bounds(b).0, 8);
+            let b_geq_8 = AriesLit::from(Lit::geq(VarRef::from_u32(b), 8));
+            let mut explanation = [b_geq_8; 4];
+            let mut n = 0;
+            let status = aries_stn_explain(stn, b_geq_8, explanation.as_mut_ptr(), explanation.len(), &mut n);
+            assert_eq!(status, ARIES_STN_OK);
+            assert!(n >= 1);
+            assert!(explanation[..n].contains(&Lit::geq(VarRef::from_u32(a), 5).into()));
+            // a bound set directly has no explanation
+            let a_geq_5 = AriesLit::from(Lit::geq(VarRef::from_u32(a), 5));
+            let status = aries_stn_explain(stn, a_geq_5, explanation.as_mut_ptr(), 0, &mut n);
+            assert_eq!(status, ARIES_STN_NO_EXPLANATION);
+
+            assert_eq!(aries_stn_reset(stn), ARIES_STN_OK);
+            assert_eq!(aries_stn_propagate(stn), ARIES_STN_OK);
+            assert_eq!(bounds(b).0, 3);
+            aries_stn_free(stn);
+        }
+    }
+
+    #[test]
+    fn test_misuse() {
+        unsafe {
+            let stn = aries_stn_new();
+            let mut a = 0;
+            assert_eq!(aries_stn_add_timepoint(stn, 0, 10, &mut a), ARIES_STN_OK);
+            // unknown relation
+            let mut lit = AriesLit::from(Lit::geq(VarRef::from_u32(a), 5));
+            lit.relation = 2;
+            let mut entailed = false;
+            assert_eq!(aries_stn_entails(stn, lit, &mut entailed), ARIES_STN_ERROR);
+            assert_eq!(aries_stn_activate(stn, lit), ARIES_STN_ERROR);
+            // null pointers
+            assert_eq!(aries_stn_propagate(std::ptr::null_mut()), ARIES_STN_ERROR);
+            assert_eq!(aries_stn_lb(stn, a, std::ptr::null_mut()), ARIES_STN_ERROR);
+            // timepoint that does not belong to the network
+            let mut lb = 0;
+            assert_eq!(aries_stn_lb(stn, 1000, &mut lb), ARIES_STN_ERROR);
+            aries_stn_free(stn);
+        }
+    }
+}